@@ -1,7 +1,12 @@
 import { DefaultTheme, NoteDisplayMode } from '@blocksuite/affine-model';
 import {
   embedSyncedDocMiddleware,
+  HTML_EXPORT_THEME_STYLES,
   HtmlAdapter,
+  HtmlExportColorScheme,
+  HtmlExportTheme,
+  htmlExportThemeMiddleware,
+  printLayoutMiddleware,
 } from '@blocksuite/affine-shared/adapters';
 import type {
   BlockSnapshot,
@@ -1904,6 +1909,353 @@ describe('snapshot to html', () => {
     });
     expect(target.file).toBe(docHtml);
   });
+
+  test('theme and flavour classes', async () => {
+    const blockSnapshot: BlockSnapshot = {
+      type: 'block',
+      id: 'block:vu6SK6WJpW',
+      flavour: 'affine:page',
+      props: {
+        title: {
+          '$blocksuite:internal:text$': true,
+          delta: [],
+        },
+      },
+      children: [
+        {
+          type: 'block',
+          id: 'block:WfnS5ZDCJT',
+          flavour: 'affine:note',
+          props: {
+            xywh: '[0,0,800,95]',
+            background: DefaultTheme.noteBackgrounColor,
+            index: 'a0',
+            hidden: false,
+            displayMode: NoteDisplayMode.DocAndEdgeless,
+          },
+          children: [
+            {
+              type: 'block',
+              id: 'block:Bdn8Yvqcny',
+              flavour: 'affine:paragraph',
+              props: {
+                type: 'text',
+                text: {
+                  '$blocksuite:internal:text$': true,
+                  delta: [
+                    {
+                      insert: 'aaa',
+                    },
+                  ],
+                },
+              },
+              children: [],
+            },
+            {
+              type: 'block',
+              id: 'block:8hOLxad5Fv',
+              flavour: 'affine:code',
+              props: {
+                language: 'python',
+                text: {
+                  '$blocksuite:internal:text$': true,
+                  delta: [
+                    {
+                      insert: 'import this',
+                    },
+                  ],
+                },
+              },
+              children: [],
+            },
+          ],
+        },
+      ],
+    };
+
+    const html = template(
+      `<div class="affine-paragraph-block-container prose mb-4"><p>aaa</p><div class="affine-block-children-container" style="padding-left: 26px;"></div></div><pre class="snippet"><code class="code-python">import this</code></pre>`
+    )
+      .replace('<html>', '<html class="doc">')
+      .replace(
+        '</head>',
+        `<style>${HTML_EXPORT_THEME_STYLES[HtmlExportTheme.GitHub]}</style></head>`
+      );
+
+    const middleware = htmlExportThemeMiddleware({
+      theme: HtmlExportTheme.GitHub,
+      classes: {
+        'affine:page': ['doc'],
+        'affine:paragraph': ['prose', 'mb-4'],
+        'affine:code': ['snippet'],
+      },
+    });
+    const htmlAdapter = new HtmlAdapter(createJob([middleware]), provider);
+    const target = await htmlAdapter.fromBlockSnapshot({
+      snapshot: blockSnapshot,
+    });
+    expect(target.file).toBe(html);
+  });
+
+  test('inline styles', async () => {
+    const blockSnapshot: BlockSnapshot = {
+      type: 'block',
+      id: 'block:vu6SK6WJpW',
+      flavour: 'affine:page',
+      props: {
+        title: {
+          '$blocksuite:internal:text$': true,
+          delta: [],
+        },
+      },
+      children: [
+        {
+          type: 'block',
+          id: 'block:WfnS5ZDCJT',
+          flavour: 'affine:note',
+          props: {
+            xywh: '[0,0,800,95]',
+            background: DefaultTheme.noteBackgrounColor,
+            index: 'a0',
+            hidden: false,
+            displayMode: NoteDisplayMode.DocAndEdgeless,
+          },
+          children: [
+            {
+              type: 'block',
+              id: 'block:8hOLxad5Fv',
+              flavour: 'affine:code',
+              props: {
+                language: 'python',
+                text: {
+                  '$blocksuite:internal:text$': true,
+                  delta: [
+                    {
+                      insert: 'import this',
+                    },
+                  ],
+                },
+              },
+              children: [],
+            },
+          ],
+        },
+      ],
+    };
+
+    const exportWith = async (inlineStyles: HtmlExportColorScheme) => {
+      const middleware = htmlExportThemeMiddleware({ inlineStyles });
+      const htmlAdapter = new HtmlAdapter(createJob([middleware]), provider);
+      const target = await htmlAdapter.fromBlockSnapshot({
+        snapshot: blockSnapshot,
+      });
+      return target.file;
+    };
+
+    const auto = await exportWith(HtmlExportColorScheme.Auto);
+    expect(auto).toContain(
+      '<head><meta name="color-scheme" content="light dark"><style>'
+    );
+    expect(auto).toContain(
+      '<pre style="background: #f6f8fa; color: #1f2328; border: 1px solid #d1d9e0; border-radius: 6px; padding: 12px; overflow: auto;" class="affine-inline-pre"><code class="code-python affine-inline-code" style="font-family: Menlo, Consolas, monospace;">import this</code></pre>'
+    );
+    expect(auto).toContain(
+      '<style>@media (prefers-color-scheme: dark) { .affine-inline-body { background: #141414 !important;'
+    );
+    expect(auto).toContain(
+      '.affine-inline-pre { background: #1e1e1e !important; color: #e6edf3 !important;'
+    );
+
+    const dark = await exportWith(HtmlExportColorScheme.Dark);
+    expect(dark).toContain('<meta name="color-scheme" content="dark">');
+    expect(dark).toContain(
+      '<pre style="background: #1e1e1e; color: #e6edf3; border: 1px solid #30363d; border-radius: 6px; padding: 12px; overflow: auto;">'
+    );
+    expect(dark).not.toContain('prefers-color-scheme');
+  });
+
+  test('print layout', async () => {
+    const blockSnapshot: BlockSnapshot = {
+      type: 'block',
+      id: 'block:vu6SK6WJpW',
+      flavour: 'affine:page',
+      props: {
+        title: {
+          '$blocksuite:internal:text$': true,
+          delta: [],
+        },
+      },
+      children: [
+        {
+          type: 'block',
+          id: 'block:WfnS5ZDCJT',
+          flavour: 'affine:note',
+          props: {
+            xywh: '[0,0,800,95]',
+            background: DefaultTheme.noteBackgrounColor,
+            index: 'a0',
+            hidden: false,
+            displayMode: NoteDisplayMode.DocAndEdgeless,
+          },
+          children: [
+            {
+              type: 'block',
+              id: 'block:table',
+              flavour: 'affine:table',
+              props: {
+                columns: {
+                  col1: {
+                    columnId: 'col1',
+                    order: 'a0',
+                  },
+                },
+                rows: {
+                  row1: {
+                    rowId: 'row1',
+                    order: 'a0',
+                  },
+                  row2: {
+                    rowId: 'row2',
+                    order: 'a1',
+                  },
+                },
+                cells: {
+                  'row1:col1': {
+                    text: {
+                      '$blocksuite:internal:text$': true,
+                      delta: [{ insert: 'Header' }],
+                    },
+                  },
+                  'row2:col1': {
+                    text: {
+                      '$blocksuite:internal:text$': true,
+                      delta: [{ insert: 'Value' }],
+                    },
+                  },
+                },
+              },
+              children: [],
+            },
+          ],
+        },
+      ],
+    };
+
+    const middleware = printLayoutMiddleware({
+      avoidBreakInside: true,
+      repeatTableHeader: true,
+    });
+    const htmlAdapter = new HtmlAdapter(createJob([middleware]), provider);
+    const target = await htmlAdapter.fromBlockSnapshot({
+      snapshot: blockSnapshot,
+    });
+    expect(target.file).toContain(
+      '<style>@media print { table, pre { break-inside: avoid; } thead { display: table-header-group; } }</style></head>'
+    );
+    expect(target.file).toMatch(
+      /<thead><tr><td>.*Header.*<\/td><\/tr><\/thead><tbody><tr><td>.*Value.*<\/td><\/tr><\/tbody>/
+    );
+  });
+
+  test('footnote', async () => {
+    const blockSnapshot: BlockSnapshot = {
+      type: 'block',
+      id: 'block:vu6SK6WJpW',
+      flavour: 'affine:page',
+      props: {
+        title: {
+          '$blocksuite:internal:text$': true,
+          delta: [],
+        },
+      },
+      children: [
+        {
+          type: 'block',
+          id: 'block:WfnS5ZDCJT',
+          flavour: 'affine:note',
+          props: {
+            xywh: '[0,0,800,95]',
+            background: DefaultTheme.noteBackgrounColor,
+            index: 'a0',
+            hidden: false,
+            displayMode: NoteDisplayMode.DocAndEdgeless,
+          },
+          children: [
+            {
+              type: 'block',
+              id: 'block:zxDyvrg1Mh',
+              flavour: 'affine:paragraph',
+              props: {
+                type: 'text',
+                text: {
+                  '$blocksuite:internal:text$': true,
+                  delta: [
+                    {
+                      insert: 'aaa',
+                    },
+                    {
+                      insert: ' ',
+                      attributes: {
+                        footnote: {
+                          label: '1',
+                          reference: {
+                            type: 'url',
+                            url: 'https://www.example.com',
+                            title: 'Example Domain',
+                          },
+                        },
+                      },
+                    },
+                    {
+                      insert: ' ',
+                      attributes: {
+                        footnote: {
+                          label: '2',
+                          reference: {
+                            type: 'attachment',
+                            blobId: 'abcdefg',
+                            fileName: 'test.txt',
+                          },
+                        },
+                      },
+                    },
+                    {
+                      insert: ' ',
+                      attributes: {
+                        footnote: {
+                          label: '1',
+                          reference: {
+                            type: 'url',
+                            url: 'https://www.example.com',
+                            title: 'Example Domain',
+                          },
+                        },
+                      },
+                    },
+                  ],
+                },
+              },
+              children: [],
+            },
+          ],
+        },
+      ],
+    };
+
+    const htmlAdapter = new HtmlAdapter(createJob(), provider);
+    const target = await htmlAdapter.fromBlockSnapshot({
+      snapshot: blockSnapshot,
+    });
+    expect(target.file).toContain(
+      'aaa<sup class="footnote-ref" id="fnref-1"><a href="#fn-1">1</a></sup><sup class="footnote-ref" id="fnref-2"><a href="#fn-2">2</a></sup><sup class="footnote-ref"><a href="#fn-1">1</a></sup>'
+    );
+    expect(target.file).toContain(
+      '<section class="footnotes"><ol><li id="fn-1"><a href="https://www.example.com">Example Domain</a> <a href="#fnref-1" class="footnote-back">'
+    );
+    expect(target.file).toContain(
+      '<li id="fn-2">test.txt <a href="#fnref-2" class="footnote-back">'
+    );
+  });
 });
 
 describe('html to snapshot', () => {
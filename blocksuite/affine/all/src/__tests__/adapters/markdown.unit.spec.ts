@@ -9,6 +9,7 @@ import {
   calloutMarkdownExportMiddleware,
   embedSyncedDocMiddleware,
   MarkdownAdapter,
+  numberedHeadingsMiddleware,
 } from '@blocksuite/affine-shared/adapters';
 import type {
   BlockSnapshot,
@@ -2735,6 +2736,104 @@ Text in details callout with new line
       expect(target.file).toBe(markdown);
     });
   });
+
+  test('numbered headings', async () => {
+    const blockSnapshot: BlockSnapshot = {
+      type: 'block',
+      id: 'block:vu6SK6WJpW',
+      flavour: 'affine:page',
+      props: {
+        title: {
+          '$blocksuite:internal:text$': true,
+          delta: [],
+        },
+      },
+      children: [
+        {
+          type: 'block',
+          id: 'block:WfnS5ZDCJT',
+          flavour: 'affine:note',
+          props: {
+            xywh: '[0,0,800,95]',
+            background: DefaultTheme.noteBackgrounColor,
+            index: 'a0',
+            hidden: false,
+            displayMode: NoteDisplayMode.DocAndEdgeless,
+          },
+          children: [
+            {
+              type: 'block',
+              id: 'block:h2a',
+              flavour: 'affine:paragraph',
+              props: {
+                type: 'h2',
+                text: {
+                  '$blocksuite:internal:text$': true,
+                  delta: [{ insert: 'Setup' }],
+                },
+              },
+              children: [],
+            },
+            {
+              type: 'block',
+              id: 'block:h3a',
+              flavour: 'affine:paragraph',
+              props: {
+                type: 'h3',
+                text: {
+                  '$blocksuite:internal:text$': true,
+                  delta: [{ insert: 'Linux' }],
+                },
+              },
+              children: [],
+            },
+            {
+              type: 'block',
+              id: 'block:text',
+              flavour: 'affine:paragraph',
+              props: {
+                type: 'text',
+                text: {
+                  '$blocksuite:internal:text$': true,
+                  delta: [{ insert: 'body' }],
+                },
+              },
+              children: [],
+            },
+            {
+              type: 'block',
+              id: 'block:h2b',
+              flavour: 'affine:paragraph',
+              props: {
+                type: 'h2',
+                text: {
+                  '$blocksuite:internal:text$': true,
+                  delta: [{ insert: 'Usage' }],
+                },
+              },
+              children: [],
+            },
+          ],
+        },
+      ],
+    };
+    const markdown = `## 1. Setup
+
+### 1.1 Linux
+
+body
+
+## 2. Usage
+`;
+    const mdAdapter = new MarkdownAdapter(
+      createJob([numberedHeadingsMiddleware(true)]),
+      provider
+    );
+    const target = await mdAdapter.fromBlockSnapshot({
+      snapshot: blockSnapshot,
+    });
+    expect(target.file).toBe(markdown);
+  });
 });
 
 describe('markdown to snapshot', () => {
@@ -1,5 +1,10 @@
 import { DefaultTheme, NoteDisplayMode } from '@blocksuite/affine-model';
-import { PdfAdapter } from '@blocksuite/affine-shared/adapters';
+import {
+  captionListsMiddleware,
+  numberedHeadingsMiddleware,
+  PdfAdapter,
+  printLayoutMiddleware,
+} from '@blocksuite/affine-shared/adapters';
 import type { BlockSnapshot, DocSnapshot } from '@blocksuite/store';
 import { AssetsManager, MemoryBlobCRUD } from '@blocksuite/store';
 import { describe, expect, test } from 'vitest';
@@ -1615,4 +1620,321 @@ describe('snapshot to pdf', () => {
       expect(todoItem.table.body[0][0].svg).toContain('svg');
     });
   });
+  describe('print layout', () => {
+    test('page breaks and table headers', async () => {
+      const blockSnapshot = createBaseSnapshot([
+        {
+          type: 'block',
+          id: 'block:h1a',
+          flavour: 'affine:paragraph',
+          props: {
+            type: 'h1',
+            text: {
+              '$blocksuite:internal:text$': true,
+              delta: [{ insert: 'Intro' }],
+            },
+          },
+          children: [],
+        },
+        {
+          type: 'block',
+          id: 'block:table',
+          flavour: 'affine:table',
+          props: {
+            columns: {
+              col1: {
+                columnId: 'col1',
+                order: 'a0',
+              },
+            },
+            rows: {
+              row1: {
+                rowId: 'row1',
+                order: 'a0',
+              },
+              row2: {
+                rowId: 'row2',
+                order: 'a1',
+              },
+            },
+            cells: {
+              'row1:col1': {
+                text: {
+                  delta: [{ insert: 'Header' }],
+                },
+              },
+              'row2:col1': {
+                text: {
+                  delta: [{ insert: 'Value' }],
+                },
+              },
+            },
+          },
+          children: [],
+        },
+        {
+          type: 'block',
+          id: 'block:h1b',
+          flavour: 'affine:paragraph',
+          props: {
+            type: 'h1',
+            text: {
+              '$blocksuite:internal:text$': true,
+              delta: [{ insert: 'Details' }],
+            },
+          },
+          children: [],
+        },
+      ]);
+
+      const middleware = printLayoutMiddleware({
+        breakBeforeH1: true,
+        avoidBreakInside: true,
+        repeatTableHeader: true,
+      });
+      const pdfAdapter = new PdfAdapter(createJob([middleware]), provider);
+      const definition = await pdfAdapter.getDocDefinition(
+        [blockSnapshot],
+        'Title'
+      );
+
+      const content = definition.content as any[];
+      const headings = content.filter((item: any) => item.headlineLevel === 1);
+      expect(headings.length).toBe(2);
+      expect(headings[0].pageBreak).toBeUndefined();
+      expect(headings[1].pageBreak).toBe('before');
+
+      const stack = content.find((item: any) => item.stack?.[0]?.table);
+      expect(stack.unbreakable).toBe(true);
+      expect(stack.stack[0].table.headerRows).toBe(1);
+    });
+
+    test('no options', async () => {
+      const blockSnapshot = createBaseSnapshot([
+        {
+          type: 'block',
+          id: 'block:h1c',
+          flavour: 'affine:paragraph',
+          props: {
+            type: 'h1',
+            text: {
+              '$blocksuite:internal:text$': true,
+              delta: [{ insert: 'Only' }],
+            },
+          },
+          children: [],
+        },
+        {
+          type: 'block',
+          id: 'block:table',
+          flavour: 'affine:table',
+          props: {
+            columns: {
+              col1: {
+                columnId: 'col1',
+                order: 'a0',
+              },
+            },
+            rows: {
+              row1: {
+                rowId: 'row1',
+                order: 'a0',
+              },
+              row2: {
+                rowId: 'row2',
+                order: 'a1',
+              },
+            },
+            cells: {
+              'row1:col1': {
+                text: {
+                  delta: [{ insert: 'Header' }],
+                },
+              },
+              'row2:col1': {
+                text: {
+                  delta: [{ insert: 'Value' }],
+                },
+              },
+            },
+          },
+          children: [],
+        },
+      ]);
+
+      const pdfAdapter = new PdfAdapter(createJob(), provider);
+      const definition = await pdfAdapter.getDocDefinition(
+        [blockSnapshot],
+        undefined
+      );
+
+      const content = definition.content as any[];
+      expect(content.some((item: any) => item.pageBreak)).toBe(false);
+      const table = content.find((item: any) => item.table);
+      expect(table.table.headerRows).toBe(0);
+    });
+  });
+  describe('caption lists', () => {
+    test('list of figures and tables', async () => {
+      const blobCRUD = new MemoryBlobCRUD();
+      const svgBlob = new Blob(
+        [
+          '<svg width="100" height="100"><rect width="100" height="100"/></svg>',
+        ],
+        {
+          type: 'image/svg+xml',
+        }
+      );
+      const blobId = await blobCRUD.set(svgBlob);
+      const assets = new AssetsManager({ blob: blobCRUD });
+      await assets.readFromBlob(blobId);
+      assets.getAssets().set(blobId, svgBlob);
+
+      const blockSnapshot = createBaseSnapshot([
+        {
+          type: 'block',
+          id: 'block:image',
+          flavour: 'affine:image',
+          props: {
+            sourceId: blobId,
+            caption: 'Architecture',
+          },
+          children: [],
+        },
+        {
+          type: 'block',
+          id: 'block:table',
+          flavour: 'affine:table',
+          props: {
+            columns: {
+              col1: {
+                columnId: 'col1',
+                order: 'a0',
+              },
+            },
+            rows: {
+              row1: {
+                rowId: 'row1',
+                order: 'a0',
+              },
+            },
+            cells: {
+              'row1:col1': {
+                text: {
+                  delta: [{ insert: 'Cell' }],
+                },
+              },
+            },
+          },
+          children: [],
+        },
+      ]);
+
+      const middleware = captionListsMiddleware({
+        listOfFigures: true,
+        listOfTables: true,
+        tablesTitle: 'Tables',
+      });
+      const pdfAdapter = new PdfAdapter(createJob([middleware]), provider);
+      const definition = await pdfAdapter.getDocDefinition(
+        [blockSnapshot],
+        'Report',
+        assets
+      );
+
+      const content = definition.content as any[];
+      expect(content[1].toc.id).toBe('figures');
+      expect(content[1].toc.title.text).toBe('List of Figures');
+      expect(content[2].toc.id).toBe('tables');
+      expect(content[2].toc.title.text).toBe('Tables');
+
+      const figure = content.find((item: any) => item.tocItem === 'figures');
+      expect(figure.text).toBe('Figure 1: Architecture');
+      expect(figure.id).toBe('figure-1');
+      const table = content.find((item: any) => item.tocItem === 'tables');
+      expect(table.text).toBe('Table 1');
+      expect(table.id).toBe('table-1');
+    });
+
+    test('no lists without captions', async () => {
+      const blockSnapshot = createBaseSnapshot([]);
+
+      const middleware = captionListsMiddleware({
+        listOfFigures: true,
+        listOfTables: true,
+      });
+      const pdfAdapter = new PdfAdapter(createJob([middleware]), provider);
+      const definition = await pdfAdapter.getDocDefinition(
+        [blockSnapshot],
+        undefined
+      );
+
+      const content = definition.content as any[];
+      expect(content.some((item: any) => item.toc)).toBe(false);
+    });
+  });
+
+  test('numbered headings', async () => {
+    const blockSnapshot = createBaseSnapshot([
+        {
+          type: 'block',
+          id: 'block:h1a',
+          flavour: 'affine:paragraph',
+          props: {
+            type: 'h1',
+            text: {
+              '$blocksuite:internal:text$': true,
+              delta: [{ insert: 'Intro' }],
+            },
+          },
+          children: [],
+        },
+        {
+          type: 'block',
+          id: 'block:h2a',
+          flavour: 'affine:paragraph',
+          props: {
+            type: 'h2',
+            text: {
+              '$blocksuite:internal:text$': true,
+              delta: [{ insert: 'Scope' }],
+            },
+          },
+          children: [],
+        },
+        {
+          type: 'block',
+          id: 'block:h1b',
+          flavour: 'affine:paragraph',
+          props: {
+            type: 'h1',
+            text: {
+              '$blocksuite:internal:text$': true,
+              delta: [{ insert: 'Usage' }],
+            },
+          },
+          children: [],
+        },
+    ]);
+
+    const pdfAdapter = new PdfAdapter(
+      createJob([numberedHeadingsMiddleware(true)]),
+      provider
+    );
+    const definition = await pdfAdapter.getDocDefinition(
+      [blockSnapshot],
+      'Title'
+    );
+
+    const textOf = (item: any): string =>
+      Array.isArray(item.text)
+        ? item.text.map((part: any) => textOf({ text: part })).join('')
+        : typeof item.text === 'string'
+          ? item.text
+          : textOf(item.text);
+    const headings = (definition.content as any[])
+      .filter((item: any) => /^header[1-4]$/.test(item.style))
+      .map(textOf);
+    expect(headings).toEqual(['1. Intro', '1.1 Scope', '2. Usage']);
+  });
 });
@@ -1,9 +1,58 @@
-import { NoteBlockSchema, NoteDisplayMode } from '@blocksuite/affine-model';
 import {
+  type FootNoteReferenceParams,
+  NoteBlockSchema,
+  NoteDisplayMode,
+} from '@blocksuite/affine-model';
+import {
+  AdapterTextUtils,
   BlockHtmlAdapterExtension,
   type BlockHtmlAdapterMatcher,
+  FOOTNOTE_DEFINITION_PREFIX,
+  type InlineHtmlAST,
 } from '@blocksuite/affine-shared/adapters';
 
+/**
+ * Create the content of the endnote of a footnote: a link to the url, doc or
+ * attachment it references, or its plain name when there is nothing to link to.
+ */
+const createFootnoteContent = (
+  reference: FootNoteReferenceParams,
+  configs: Map<string, string>
+): InlineHtmlAST => {
+  let href: string | undefined;
+  let label: string;
+  switch (reference.type) {
+    case 'url':
+      href = reference.url;
+      label = reference.title || reference.url || '';
+      break;
+    case 'doc':
+      href = reference.docId
+        ? AdapterTextUtils.generateDocUrl(
+            configs.get('docLinkBaseUrl') ?? '',
+            reference.docId,
+            Object.create(null)
+          )
+        : undefined;
+      label =
+        (reference.docId && configs.get(`title:${reference.docId}`)) ||
+        reference.docId ||
+        '';
+      break;
+    default:
+      label = reference.fileName || reference.blobId || '';
+  }
+  const text: InlineHtmlAST = { type: 'text', value: label };
+  return href
+    ? {
+        type: 'element',
+        tagName: 'a',
+        properties: { href },
+        children: [text],
+      }
+    : text;
+};
+
 /**
  * Create a html adapter matcher for note block.
  *
@@ -25,6 +74,80 @@ const createNoteBlockHtmlAdapterMatcher = (
         context.walkerContext.skipAllChildren();
       }
     },
+    leave: (_, context) => {
+      const { walkerContext, configs } = context;
+      // Footnotes referenced in the note and not listed yet become endnotes,
+      // each linking back to its first reference
+      const items = Array.from(configs.keys())
+        .filter(
+          key =>
+            key.startsWith(FOOTNOTE_DEFINITION_PREFIX) &&
+            !walkerContext.getGlobalContext(key)
+        )
+        .flatMap(key => {
+          walkerContext.setGlobalContext(key, true);
+          const label = key.slice(FOOTNOTE_DEFINITION_PREFIX.length);
+          try {
+            const reference = JSON.parse(
+              configs.get(key) ?? ''
+            ) as FootNoteReferenceParams;
+            return [
+              { label, content: createFootnoteContent(reference, configs) },
+            ];
+          } catch {
+            return [];
+          }
+        });
+      if (items.length === 0) {
+        return;
+      }
+
+      walkerContext
+        .openNode(
+          {
+            type: 'element',
+            tagName: 'section',
+            properties: { className: ['footnotes'] },
+            children: [],
+          },
+          'children'
+        )
+        .openNode(
+          {
+            type: 'element',
+            tagName: 'ol',
+            properties: {},
+            children: [],
+          },
+          'children'
+        );
+      for (const { label, content } of items) {
+        walkerContext
+          .openNode(
+            {
+              type: 'element',
+              tagName: 'li',
+              properties: { id: `fn-${label}` },
+              children: [
+                content,
+                { type: 'text', value: ' ' },
+                {
+                  type: 'element',
+                  tagName: 'a',
+                  properties: {
+                    href: `#fnref-${label}`,
+                    className: ['footnote-back'],
+                  },
+                  children: [{ type: 'text', value: '↩' }],
+                },
+              ],
+            },
+            'children'
+          )
+          .closeNode();
+      }
+      walkerContext.closeNode().closeNode();
+    },
   },
 });
 
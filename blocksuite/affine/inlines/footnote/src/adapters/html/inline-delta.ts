@@ -0,0 +1,45 @@
+import {
+  FOOTNOTE_DEFINITION_PREFIX,
+  InlineDeltaToHtmlAdapterExtension,
+} from '@blocksuite/affine-shared/adapters';
+
+export const footnoteReferenceDeltaToHtmlAdapterMatcher =
+  InlineDeltaToHtmlAdapterExtension({
+    name: 'footnote-reference',
+    match: delta => !!delta.attributes?.footnote,
+    toAST: (delta, context) => {
+      const footnote = delta.attributes?.footnote;
+      if (!footnote) {
+        return {
+          type: 'text',
+          value: delta.insert,
+        };
+      }
+      const footnoteDefinitionKey = `${FOOTNOTE_DEFINITION_PREFIX}${footnote.label}`;
+      const { configs } = context;
+      // The endnotes are rendered by the note adapter from the configs, only
+      // the first reference to a footnote gets the anchor they link back to
+      const isFirstReference = !configs.has(footnoteDefinitionKey);
+      if (isFirstReference) {
+        configs.set(footnoteDefinitionKey, JSON.stringify(footnote.reference));
+      }
+      return {
+        type: 'element',
+        tagName: 'sup',
+        properties: {
+          className: ['footnote-ref'],
+          ...(isFirstReference ? { id: `fnref-${footnote.label}` } : {}),
+        },
+        children: [
+          {
+            type: 'element',
+            tagName: 'a',
+            properties: {
+              href: `#fn-${footnote.label}`,
+            },
+            children: [{ type: 'text', value: footnote.label }],
+          },
+        ],
+      };
+    },
+  });
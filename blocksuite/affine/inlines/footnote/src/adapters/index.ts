@@ -1,3 +1,4 @@
+export * from './html/inline-delta';
 export * from './markdown/inline-delta';
 export * from './markdown/markdown-inline';
 export * from './markdown/preprocessor';
@@ -4,6 +4,7 @@ import {
 } from '@blocksuite/affine-ext-loader';
 
 import {
+  footnoteReferenceDeltaToHtmlAdapterMatcher,
   footnoteReferenceDeltaToMarkdownAdapterMatcher,
   FootnoteReferenceMarkdownPreprocessorExtension,
   markdownFootnoteReferenceToDeltaMatcher,
@@ -15,6 +16,7 @@ export class FootnoteStoreExtension extends StoreExtensionProvider {
   override setup(context: StoreExtensionContext) {
     super.setup(context);
     context.register(markdownFootnoteReferenceToDeltaMatcher);
+    context.register(footnoteReferenceDeltaToHtmlAdapterMatcher);
     context.register(footnoteReferenceDeltaToMarkdownAdapterMatcher);
     context.register(FootnoteReferenceMarkdownPreprocessorExtension);
   }
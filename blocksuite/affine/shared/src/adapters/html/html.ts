@@ -3,6 +3,7 @@ import type { ServiceProvider } from '@blocksuite/global/di';
 import {
   type AssetsManager,
   ASTWalker,
+  type ASTWalkerContext,
   BaseAdapter,
   type BlockSnapshot,
   BlockSnapshotSchema,
@@ -21,11 +22,23 @@ import {
   type Transformer,
 } from '@blocksuite/store';
 import DOMPurify from 'dompurify';
-import type { Root } from 'hast';
+import type { Element, Root } from 'hast';
 import rehypeParse from 'rehype-parse';
 import rehypeStringify from 'rehype-stringify';
 import { unified } from 'unified';
 
+import {
+  getHtmlExportThemeOptions,
+  HTML_EXPORT_THEME_STYLES,
+} from '../middlewares/html-theme';
+import {
+  applyNumberedHeadings,
+  isNumberedHeadingsEnabled,
+} from '../middlewares/numbered-headings';
+import {
+  getPrintLayoutOptions,
+  printLayoutCss,
+} from '../middlewares/print-layout';
 import {
   type AdapterContext,
   AdapterFactoryIdentifier,
@@ -41,6 +54,7 @@ import {
   HtmlDeltaConverter,
   InlineDeltaToHtmlAdapterMatcherIdentifier,
 } from './delta-converter';
+import { inlineHtmlStyles } from './inline-styles';
 import {
   rehypeInlineToBlock,
   rehypeWrapInlineElements,
@@ -56,6 +70,95 @@ type HtmlToSliceSnapshotPayload = {
 };
 
 export class HtmlAdapter extends BaseAdapter<Html> {
+  private readonly _addFlavourClasses = (
+    classes: string[],
+    walkerContext: ASTWalkerContext<HtmlAST>,
+    depth: number,
+    siblings: number
+  ) => {
+    // the outermost element of a block is the first node its matchers opened,
+    // or the first child they appended when they closed it right away
+    const parent = walkerContext.stack[depth - 1]?.node;
+    const node =
+      walkerContext.stack[depth]?.node ??
+      (parent && 'children' in parent ? parent.children[siblings] : undefined);
+    if (node?.type !== 'element') {
+      return;
+    }
+    const className = node.properties.className;
+    const existing = Array.isArray(className)
+      ? className
+      : typeof className === 'string'
+        ? className.split(' ')
+        : [];
+    node.properties.className = [...existing, ...classes];
+  };
+
+  private readonly _applyTheme = (ast: Root) => {
+    const options = getHtmlExportThemeOptions(this.configs);
+    if (options?.inlineStyles) {
+      inlineHtmlStyles(ast, options.inlineStyles);
+    }
+    const theme = options?.theme;
+    const head = theme ? HastUtils.querySelector(ast, 'head') : undefined;
+    if (!theme || !head) {
+      return;
+    }
+    const style: Element = {
+      type: 'element',
+      tagName: 'style',
+      properties: {},
+      children: [{ type: 'text', value: HTML_EXPORT_THEME_STYLES[theme] }],
+    };
+    head.children.push(style);
+  };
+
+  private readonly _applyPrintLayout = (ast: Root) => {
+    const options = getPrintLayoutOptions(this.configs);
+    if (!options) {
+      return;
+    }
+    if (options.repeatTableHeader) {
+      // browsers repeat the rows of a thead on every printed page
+      const moveHeaderRow = (node: HtmlAST) => {
+        if (node.type !== 'root' && node.type !== 'element') {
+          return;
+        }
+        node.children.forEach(moveHeaderRow);
+        if (node.type !== 'element' || node.tagName !== 'table') {
+          return;
+        }
+        const sections = node.children.filter(
+          (child): child is Element => child.type === 'element'
+        );
+        const body = sections.find(child => child.tagName === 'tbody');
+        const hasHead = sections.some(child => child.tagName === 'thead');
+        if (!body || hasHead || body.children.length < 2) {
+          return;
+        }
+        const head: Element = {
+          type: 'element',
+          tagName: 'thead',
+          properties: {},
+          children: body.children.splice(0, 1),
+        };
+        node.children.splice(node.children.indexOf(body), 0, head);
+      };
+      moveHeaderRow(ast);
+    }
+    const css = printLayoutCss(options);
+    const head = css ? HastUtils.querySelector(ast, 'head') : undefined;
+    if (!head) {
+      return;
+    }
+    head.children.push({
+      type: 'element',
+      tagName: 'style',
+      properties: {},
+      children: [{ type: 'text', value: css }],
+    });
+  };
+
   private readonly _astToHtml = (ast: Root) => {
     return unified().use(rehypeStringify).stringify(ast);
   };
@@ -119,12 +222,17 @@ export class HtmlAdapter extends BaseAdapter<Html> {
     assets?: AssetsManager
   ) => {
     const assetsIds: string[] = [];
+    const flavourClasses = getHtmlExportThemeOptions(this.configs)?.classes;
     const walker = new ASTWalker<BlockSnapshot, HtmlAST>();
     walker.setONodeTypeGuard(
       (node): node is BlockSnapshot =>
         BlockSnapshotSchema.safeParse(node).success
     );
     walker.setEnter(async (o, context) => {
+      const depth = context.stack.length;
+      const parent = context.currentNode();
+      const siblings =
+        parent && 'children' in parent ? parent.children.length : 0;
       for (const matcher of this.blockMatchers) {
         if (matcher.fromMatch(o)) {
           const adapterContext: AdapterContext<
@@ -147,6 +255,10 @@ export class HtmlAdapter extends BaseAdapter<Html> {
           await matcher.fromBlockSnapshot.enter?.(o, adapterContext);
         }
       }
+      const classes = flavourClasses?.[o.node.flavour];
+      if (classes?.length) {
+        this._addFlavourClasses(classes, context, depth, siblings);
+      }
     });
     walker.setLeave(async (o, context) => {
       for (const matcher of this.blockMatchers) {
@@ -170,7 +282,12 @@ export class HtmlAdapter extends BaseAdapter<Html> {
       }
     });
     return {
-      ast: (await walker.walk(snapshot, html)) as Root,
+      ast: (await walker.walk(
+        isNumberedHeadingsEnabled(this.configs)
+          ? applyNumberedHeadings(snapshot)
+          : snapshot,
+        html
+      )) as Root,
       assetsIds,
     };
   };
@@ -224,6 +341,8 @@ export class HtmlAdapter extends BaseAdapter<Html> {
       root,
       payload.assets
     );
+    this._applyTheme(ast);
+    this._applyPrintLayout(ast);
     return {
       file: this._astToHtml(ast),
       assetsIds,
@@ -262,6 +381,8 @@ export class HtmlAdapter extends BaseAdapter<Html> {
         payload.assets
       );
       sliceAssetsIds.push(...assetsIds);
+      this._applyTheme(ast);
+      this._applyPrintLayout(ast);
       buffer += this._astToHtml(ast);
     }
     const html = buffer;
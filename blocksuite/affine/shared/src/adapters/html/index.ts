@@ -1,3 +1,4 @@
 export * from './block-adapter.js';
 export * from './delta-converter.js';
 export * from './html.js';
+export * from './inline-styles.js';
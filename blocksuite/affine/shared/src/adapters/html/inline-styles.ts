@@ -0,0 +1,223 @@
+import type { Element, Root } from 'hast';
+
+import { HtmlExportColorScheme } from '../middlewares/html-theme.js';
+import { HastUtils } from '../utils/hast.js';
+
+export type HtmlExportPalette = {
+  text: string;
+  background: string;
+  muted: string;
+  border: string;
+  link: string;
+  codeText: string;
+  codeBackground: string;
+  codeBorder: string;
+  inlineCodeText: string;
+  inlineCodeBackground: string;
+};
+
+export const HTML_EXPORT_PALETTES: Record<
+  Exclude<HtmlExportColorScheme, HtmlExportColorScheme.Auto>,
+  HtmlExportPalette
+> = {
+  [HtmlExportColorScheme.Light]: {
+    text: '#1f2328',
+    background: '#ffffff',
+    muted: '#59636e',
+    border: '#d1d9e0',
+    link: '#0969da',
+    codeText: '#1f2328',
+    codeBackground: '#f6f8fa',
+    codeBorder: '#d1d9e0',
+    inlineCodeText: '#cf222e',
+    inlineCodeBackground: '#eff1f3',
+  },
+  [HtmlExportColorScheme.Dark]: {
+    text: '#e6e6e6',
+    background: '#141414',
+    muted: '#a3a3a3',
+    border: '#3a3a3a',
+    link: '#4ea1ff',
+    codeText: '#e6edf3',
+    codeBackground: '#1e1e1e',
+    codeBorder: '#30363d',
+    inlineCodeText: '#ff7b72',
+    inlineCodeBackground: '#2b2b2b',
+  },
+};
+
+type StyleRule = (palette: HtmlExportPalette) => Record<string, string>;
+
+const STYLE_RULES: Record<string, StyleRule> = {
+  body: p => ({
+    background: p.background,
+    color: p.text,
+    'font-family':
+      "-apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif",
+    'line-height': '1.5',
+  }),
+  link: p => ({ color: p.link }),
+  heading: p => ({ color: p.text }),
+  blockquote: p => ({
+    color: p.muted,
+    'border-left': `3px solid ${p.border}`,
+    margin: '0',
+    'padding-left': '12px',
+  }),
+  pre: p => ({
+    background: p.codeBackground,
+    color: p.codeText,
+    border: `1px solid ${p.codeBorder}`,
+    'border-radius': '6px',
+    padding: '12px',
+    overflow: 'auto',
+  }),
+  code: () => ({
+    'font-family': 'Menlo, Consolas, monospace',
+  }),
+  'inline-code': p => ({
+    background: p.inlineCodeBackground,
+    color: p.inlineCodeText,
+    'border-radius': '4px',
+    padding: '0 4px',
+    'font-family': 'Menlo, Consolas, monospace',
+  }),
+  table: () => ({ 'border-collapse': 'collapse' }),
+  'table-cell': p => ({
+    border: `1px solid ${p.border}`,
+    padding: '4px 8px',
+  }),
+  hr: p => ({ border: 'none', 'border-top': `1px solid ${p.border}` }),
+};
+
+const ruleOf = (element: Element, parent?: Element) => {
+  switch (element.tagName) {
+    case 'body':
+    case 'blockquote':
+    case 'pre':
+    case 'table':
+    case 'hr':
+      return element.tagName;
+    case 'a':
+      return 'link';
+    case 'h1':
+    case 'h2':
+    case 'h3':
+    case 'h4':
+    case 'h5':
+    case 'h6':
+      return 'heading';
+    case 'code':
+      return parent?.tagName === 'pre' ? 'code' : 'inline-code';
+    case 'th':
+    case 'td':
+      return 'table-cell';
+    default:
+      return undefined;
+  }
+};
+
+const toCss = (declarations: Record<string, string>, important = false) =>
+  Object.entries(declarations)
+    .map(
+      ([key, value]) => `${key}: ${value}${important ? ' !important' : ''};`
+    )
+    .join(' ');
+
+const visit = (
+  element: Element,
+  parent: Element | undefined,
+  callback: (element: Element, parent?: Element) => void
+) => {
+  callback(element, parent);
+  for (const child of element.children) {
+    if (child.type === 'element') {
+      visit(child, element, callback);
+    }
+  }
+};
+
+/**
+ * Writes the styles of a color scheme into the `style` attribute of the
+ * elements the HTML adapter emits, keeping styles the adapter set itself.
+ *
+ * With `HtmlExportColorScheme.Auto`, the light styles are inlined and the
+ * elements are tagged with an `affine-inline-*` class, so a
+ * `prefers-color-scheme: dark` media query added to the `<head>` can switch
+ * them to the dark palette. Clients without media query support keep the
+ * light styles.
+ */
+export const inlineHtmlStyles = (
+  ast: Root,
+  scheme: HtmlExportColorScheme
+) => {
+  const palette =
+    HTML_EXPORT_PALETTES[
+      scheme === HtmlExportColorScheme.Dark
+        ? HtmlExportColorScheme.Dark
+        : HtmlExportColorScheme.Light
+    ];
+  const usedRules = new Set<string>();
+
+  for (const child of ast.children) {
+    if (child.type !== 'element') {
+      continue;
+    }
+    visit(child, undefined, (element, parent) => {
+      const rule = ruleOf(element, parent);
+      if (!rule) {
+        return;
+      }
+      const existing = element.properties.style;
+      element.properties.style = [
+        toCss(STYLE_RULES[rule](palette)),
+        typeof existing === 'string' ? existing : '',
+      ]
+        .filter(Boolean)
+        .join(' ');
+      if (scheme === HtmlExportColorScheme.Auto) {
+        const className = element.properties.className;
+        element.properties.className = [
+          ...(Array.isArray(className) ? className : []),
+          `affine-inline-${rule}`,
+        ];
+        usedRules.add(rule);
+      }
+    });
+  }
+
+  const head = HastUtils.querySelector(ast, 'head');
+  if (!head) {
+    return;
+  }
+  head.children.unshift({
+    type: 'element',
+    tagName: 'meta',
+    properties: {
+      name: 'color-scheme',
+      content: scheme === HtmlExportColorScheme.Auto ? 'light dark' : scheme,
+    },
+    children: [],
+  });
+  if (usedRules.size === 0) {
+    return;
+  }
+  const dark = HTML_EXPORT_PALETTES[HtmlExportColorScheme.Dark];
+  const overrides = [...usedRules]
+    .map(
+      rule =>
+        `.affine-inline-${rule} { ${toCss(STYLE_RULES[rule](dark), true)} }`
+    )
+    .join(' ');
+  head.children.push({
+    type: 'element',
+    tagName: 'style',
+    properties: {},
+    children: [
+      {
+        type: 'text',
+        value: `@media (prefers-color-scheme: dark) { ${overrides} }`,
+      },
+    ],
+  });
+};
@@ -26,6 +26,10 @@ import remarkParse from 'remark-parse';
 import remarkStringify from 'remark-stringify';
 import { unified } from 'unified';
 
+import {
+  applyNumberedHeadings,
+  isNumberedHeadingsEnabled,
+} from '../middlewares/numbered-headings';
 import { type AdapterContext, AdapterFactoryIdentifier } from '../types';
 import {
   type BlockMarkdownAdapterMatcher,
@@ -163,7 +167,12 @@ export class MarkdownAdapter extends BaseAdapter<Markdown> {
       }
     });
     return {
-      ast: (await walker.walk(snapshot, markdown)) as Root,
+      ast: (await walker.walk(
+        isNumberedHeadingsEnabled(this.configs)
+          ? applyNumberedHeadings(snapshot)
+          : snapshot,
+        markdown
+      )) as Root,
       assetsIds,
     };
   };
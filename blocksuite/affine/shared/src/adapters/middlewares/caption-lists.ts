@@ -0,0 +1,46 @@
+import type { TransformerMiddleware } from '@blocksuite/store';
+import { z } from 'zod';
+
+export const CAPTION_LISTS_OPTIONS_KEY = 'captionListsOptions';
+
+export const captionListsOptionsSchema = z.object({
+  /** Number image captions and list them after the doc title */
+  listOfFigures: z.boolean().optional(),
+  /** Number tables and list them after the doc title */
+  listOfTables: z.boolean().optional(),
+  figuresTitle: z.string().optional(),
+  tablesTitle: z.string().optional(),
+});
+export type CaptionListsOptions = z.infer<typeof captionListsOptionsSchema>;
+
+export const DEFAULT_FIGURES_TITLE = 'List of Figures';
+export const DEFAULT_TABLES_TITLE = 'List of Tables';
+
+/**
+ * Get the caption lists options from the configs
+ * @param configs - The configs of the adapter
+ * @returns The options, or undefined when none or invalid ones are set
+ */
+export function getCaptionListsOptions(
+  configs: Map<string, unknown>
+): CaptionListsOptions | undefined {
+  const options = configs.get(CAPTION_LISTS_OPTIONS_KEY);
+  if (!options) {
+    return undefined;
+  }
+  const result = captionListsOptionsSchema.safeParse(options);
+  return result.success ? result.data : undefined;
+}
+
+/**
+ * Middleware to add a "List of Figures" and a "List of Tables" to exports
+ * @param options - The lists to add and their titles
+ * @returns A TransformerMiddleware that sets the caption lists options
+ */
+export const captionListsMiddleware = (
+  options: CaptionListsOptions
+): TransformerMiddleware => {
+  return ({ adapterConfigs }) => {
+    adapterConfigs.set(CAPTION_LISTS_OPTIONS_KEY, options);
+  };
+};
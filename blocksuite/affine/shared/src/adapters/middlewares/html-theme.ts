@@ -0,0 +1,112 @@
+import type { TransformerMiddleware } from '@blocksuite/store';
+import { z } from 'zod';
+
+export const HTML_EXPORT_THEME_OPTIONS_KEY = 'htmlExportThemeOptions';
+
+export enum HtmlExportTheme {
+  GitHub = 'github',
+  Dark = 'dark',
+}
+
+export enum HtmlExportColorScheme {
+  Light = 'light',
+  Dark = 'dark',
+  /**
+   * Inline the light palette and switch to the dark one with a
+   * `prefers-color-scheme` media query
+   */
+  Auto = 'auto',
+}
+
+/**
+ * Stylesheets of the built-in themes, added to the `<head>` of exported docs.
+ * They only target the classes and tags the HTML adapter emits by default.
+ */
+export const HTML_EXPORT_THEME_STYLES: Record<HtmlExportTheme, string> = {
+  [HtmlExportTheme.GitHub]: `
+    body {
+      color: #1f2328;
+      font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif;
+      line-height: 1.5;
+    }
+    h1, h2 {
+      border-bottom: 1px solid #d1d9e0;
+      padding-bottom: 0.3em;
+    }
+    pre {
+      background: #f6f8fa;
+      border-radius: 6px;
+      padding: 16px;
+      overflow: auto;
+    }
+    blockquote {
+      border-left: 0.25em solid #d1d9e0;
+      color: #59636e;
+      margin: 0;
+      padding: 0 1em;
+    }
+    `.replace(/\s\s+/g, ''),
+  [HtmlExportTheme.Dark]: `
+    body {
+      background: #141414;
+      color: #e6e6e6;
+      font-family: Inter, sans-serif;
+    }
+    a {
+      color: #4ea1ff;
+    }
+    pre {
+      background: #252525;
+      border-radius: 4px;
+      padding: 12px;
+    }
+    blockquote {
+      border-left: 2px solid #3a3a3a;
+      color: #a3a3a3;
+      margin: 0;
+      padding-left: 12px;
+    }
+    `.replace(/\s\s+/g, ''),
+};
+
+export const htmlExportThemeOptionsSchema = z.object({
+  theme: z.nativeEnum(HtmlExportTheme).optional(),
+  classes: z.record(z.string(), z.array(z.string())).optional(),
+  inlineStyles: z.nativeEnum(HtmlExportColorScheme).optional(),
+});
+export type HtmlExportThemeOptions = z.infer<
+  typeof htmlExportThemeOptionsSchema
+>;
+
+/**
+ * Get the html export theme options from the configs
+ * @param configs - The configs of the adapter
+ * @returns The theme options, or undefined when none or invalid ones are set
+ */
+export function getHtmlExportThemeOptions(
+  configs: Map<string, unknown>
+): HtmlExportThemeOptions | undefined {
+  const options = configs.get(HTML_EXPORT_THEME_OPTIONS_KEY);
+  if (!options) {
+    return undefined;
+  }
+  const result = htmlExportThemeOptionsSchema.safeParse(options);
+  return result.success ? result.data : undefined;
+}
+
+/**
+ * Middleware to style the html export without post-processing it
+ * @param options - `classes` maps block flavours, e.g. `affine:paragraph`, to
+ * CSS classes added to the outermost element of those blocks, and `theme`
+ * adds the stylesheet of a built-in theme to the exported doc. `inlineStyles`
+ * writes the styles of a color scheme into `style` attributes, for emails and
+ * webviews that cannot load stylesheets
+ * @returns A TransformerMiddleware that sets the html export theme options
+ */
+export const htmlExportThemeMiddleware = (
+  options: HtmlExportThemeOptions
+): TransformerMiddleware => {
+  return ({ adapterConfigs }) => {
+    adapterConfigs.set(HTML_EXPORT_THEME_OPTIONS_KEY, options);
+  };
+};
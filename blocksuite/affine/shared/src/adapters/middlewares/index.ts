@@ -1,10 +1,14 @@
 export * from './callout-export-options';
+export * from './caption-lists';
 export * from './code';
 export * from './copy';
 export * from './doc-link';
 export * from './file-name';
 export * from './file-path';
+export * from './html-theme';
+export * from './numbered-headings';
 export * from './paste';
+export * from './print-layout';
 export * from './proxy';
 export * from './replace-id';
 export * from './surface-ref-to-embed';
@@ -0,0 +1,98 @@
+import type { BlockSnapshot, TransformerMiddleware } from '@blocksuite/store';
+
+export const NUMBERED_HEADINGS_KEY = 'numberedHeadings';
+
+const HEADING_LEVELS: Record<string, number> = {
+  h1: 1,
+  h2: 2,
+  h3: 3,
+  h4: 4,
+  h5: 5,
+  h6: 6,
+};
+
+/**
+ * Whether headings should be numbered in the export
+ * @param configs - The configs of the adapter
+ */
+export function isNumberedHeadingsEnabled(configs: Map<string, unknown>) {
+  return configs.get(NUMBERED_HEADINGS_KEY) === 'true';
+}
+
+/**
+ * Number the headings of a snapshot hierarchically, `1.` for top-level
+ * headings and `1.1`, `1.1.1`, ... below them. Numbering starts at the
+ * shallowest heading level used and skipped levels are numbered `0`, the same
+ * way the outline of the native doc parser numbers them.
+ * @param snapshot - The snapshot to number
+ * @returns The numbers of the headings, keyed by block id
+ */
+export function numberHeadings(snapshot: BlockSnapshot): Map<string, string> {
+  const headings: [string, number][] = [];
+  const visit = (block: BlockSnapshot) => {
+    const level =
+      block.flavour === 'affine:paragraph'
+        ? HEADING_LEVELS[block.props.type as string]
+        : undefined;
+    if (level) {
+      headings.push([block.id, level]);
+    }
+    block.children.forEach(visit);
+  };
+  visit(snapshot);
+
+  const top = Math.min(...headings.map(([, level]) => level));
+  const counters = [0, 0, 0, 0, 0, 0];
+  const numbers = new Map<string, string>();
+  for (const [id, level] of headings) {
+    const depth = level - top;
+    counters[depth] += 1;
+    counters.fill(0, depth + 1);
+    numbers.set(
+      id,
+      depth === 0 ? `${counters[0]}.` : counters.slice(0, depth + 1).join('.')
+    );
+  }
+  return numbers;
+}
+
+/**
+ * Prepend the numbers of `numberHeadings` to the text of the headings
+ * @param snapshot - The snapshot to number
+ * @returns A copy of the snapshot, the original is left untouched
+ */
+export function applyNumberedHeadings(snapshot: BlockSnapshot): BlockSnapshot {
+  const numbers = numberHeadings(snapshot);
+  const apply = (block: BlockSnapshot): BlockSnapshot => {
+    const number = numbers.get(block.id);
+    const text = block.props.text as { delta?: unknown } | undefined;
+    return {
+      ...block,
+      props:
+        number && text && Array.isArray(text.delta)
+          ? {
+              ...block.props,
+              text: {
+                ...text,
+                delta: [{ insert: `${number} ` }, ...text.delta],
+              },
+            }
+          : block.props,
+      children: block.children.map(apply),
+    };
+  };
+  return apply(snapshot);
+}
+
+/**
+ * Middleware to number the headings of markdown, html and pdf exports
+ * @param enabled - Whether headings are numbered
+ * @returns A TransformerMiddleware that sets the numbered headings option
+ */
+export const numberedHeadingsMiddleware = (
+  enabled: boolean
+): TransformerMiddleware => {
+  return ({ adapterConfigs }) => {
+    adapterConfigs.set(NUMBERED_HEADINGS_KEY, String(enabled));
+  };
+};
@@ -0,0 +1,65 @@
+import type { TransformerMiddleware } from '@blocksuite/store';
+import { z } from 'zod';
+
+export const PRINT_LAYOUT_OPTIONS_KEY = 'printLayoutOptions';
+
+export const printLayoutOptionsSchema = z.object({
+  /** Start every H1 heading on a new page, except one opening the doc */
+  breakBeforeH1: z.boolean().optional(),
+  /** Keep tables and code blocks on one page when they fit on one */
+  avoidBreakInside: z.boolean().optional(),
+  /** Repeat the first row of tables at the top of every page */
+  repeatTableHeader: z.boolean().optional(),
+});
+export type PrintLayoutOptions = z.infer<typeof printLayoutOptionsSchema>;
+
+/**
+ * Get the print layout options from the configs
+ * @param configs - The configs of the adapter
+ * @returns The options, or undefined when none or invalid ones are set
+ */
+export function getPrintLayoutOptions(
+  configs: Map<string, unknown>
+): PrintLayoutOptions | undefined {
+  const options = configs.get(PRINT_LAYOUT_OPTIONS_KEY);
+  if (!options) {
+    return undefined;
+  }
+  const result = printLayoutOptionsSchema.safeParse(options);
+  return result.success ? result.data : undefined;
+}
+
+/**
+ * The `@media print` stylesheet applying the options to an HTML export
+ * @param options - The print layout options
+ * @returns The stylesheet, empty when no option is enabled
+ */
+export function printLayoutCss(options: PrintLayoutOptions): string {
+  const rules: string[] = [];
+  if (options.breakBeforeH1) {
+    // the doc title is an h1 directly before the first block
+    rules.push(
+      '.affine-paragraph-block-container:not(:first-child):not(h1 + *) > h1 { break-before: page; }'
+    );
+  }
+  if (options.avoidBreakInside) {
+    rules.push('table, pre { break-inside: avoid; }');
+  }
+  if (options.repeatTableHeader) {
+    rules.push('thead { display: table-header-group; }');
+  }
+  return rules.length ? `@media print { ${rules.join(' ')} }` : '';
+}
+
+/**
+ * Middleware to control page breaks when the PDF or HTML export is printed
+ * @param options - The print layout options
+ * @returns A TransformerMiddleware that sets the print layout options
+ */
+export const printLayoutMiddleware = (
+  options: PrintLayoutOptions
+): TransformerMiddleware => {
+  return ({ adapterConfigs }) => {
+    adapterConfigs.set(PRINT_LAYOUT_OPTIONS_KEY, options);
+  };
+};
@@ -29,6 +29,16 @@ import type {
 } from 'pdfmake/interfaces';
 
 import { getNumberPrefix } from '../../utils';
+import {
+  DEFAULT_FIGURES_TITLE,
+  DEFAULT_TABLES_TITLE,
+  getCaptionListsOptions,
+} from '../middlewares/caption-lists.js';
+import {
+  applyNumberedHeadings,
+  isNumberedHeadingsEnabled,
+} from '../middlewares/numbered-headings.js';
+import { getPrintLayoutOptions } from '../middlewares/print-layout.js';
 import { resolveCssVariable } from './css-utils.js';
 import { extractTextWithInline } from './delta-converter.js';
 import {
@@ -84,6 +94,10 @@ export type PdfAdapterFile = {
  * ```
  */
 export class PdfAdapter extends BaseAdapter<PdfAdapterFile> {
+  private _figureCount = 0;
+
+  private _tableCount = 0;
+
   constructor(job: Transformer, provider: ServiceProvider) {
     super(job, provider);
   }
@@ -172,9 +186,18 @@ export class PdfAdapter extends BaseAdapter<PdfAdapterFile> {
     blocks: BlockSnapshot[],
     assets?: FromDocSnapshotPayload['assets']
   ): Promise<Content[]> {
+    this._figureCount = 0;
+    this._tableCount = 0;
+    const numbered = isNumberedHeadingsEnabled(this.configs);
     const content: Content[] = [];
     for (const block of blocks) {
-      const blockContent = await this._blockToContent(block, assets, 0, 0, 0);
+      const blockContent = await this._blockToContent(
+        numbered ? applyNumberedHeadings(block) : block,
+        assets,
+        0,
+        0,
+        0
+      );
       content.push(...blockContent);
     }
     return content;
@@ -278,6 +301,16 @@ export class PdfAdapter extends BaseAdapter<PdfAdapterFile> {
       const tableContent = await this._createTableContent(props);
       if (tableContent) {
         content.push(tableContent);
+        if (getCaptionListsOptions(this.configs)?.listOfTables) {
+          content.push({
+            ...this._createTableCaption(),
+            italics: true,
+            fontSize: 10,
+            color: PDF_COLORS.textMuted,
+            margin: [0, 2, 0, 10],
+            alignment: 'center',
+          });
+        }
       }
     } else if (
       flavour === 'affine:embed-linked-doc' ||
@@ -358,6 +391,10 @@ export class PdfAdapter extends BaseAdapter<PdfAdapterFile> {
     const paragraphContent: Content = style
       ? { text: textContent, style, margin: [baseIndent, 6, 0, 3] }
       : { text: textContent, margin: [baseIndent, 2, 0, 2] };
+    if (type === 'h1') {
+      // marks the pages to break before, see `_createDocDefinition`
+      paragraphContent.headlineLevel = 1;
+    }
 
     if (textAlign && textAlign !== 'left') {
       paragraphContent.alignment = textAlign;
@@ -552,6 +589,9 @@ export class PdfAdapter extends BaseAdapter<PdfAdapterFile> {
       });
     }
 
+    if (getPrintLayoutOptions(this.configs)?.avoidBreakInside) {
+      return [{ stack: codeBlockContent, unbreakable: true }];
+    }
     return codeBlockContent;
   }
 
@@ -768,14 +808,7 @@ export class PdfAdapter extends BaseAdapter<PdfAdapterFile> {
         ];
 
         if (caption) {
-          content.push({
-            text: caption,
-            italics: true,
-            fontSize: 10,
-            color: PDF_COLORS.textMuted,
-            margin: [0, 2, 0, 10],
-            alignment: textAlign as 'left' | 'center' | 'right',
-          });
+          content.push(this._createFigureCaption(caption, textAlign));
         }
 
         return content;
@@ -817,14 +850,7 @@ export class PdfAdapter extends BaseAdapter<PdfAdapterFile> {
       ];
 
       if (caption) {
-        content.push({
-          text: caption,
-          italics: true,
-          fontSize: 10,
-          color: PDF_COLORS.textMuted,
-          margin: [0, 2, 0, 10],
-          alignment: textAlign as 'left' | 'center' | 'right',
-        });
+        content.push(this._createFigureCaption(caption, textAlign));
       }
 
       return content;
@@ -870,9 +896,11 @@ export class PdfAdapter extends BaseAdapter<PdfAdapterFile> {
       tableBody.push(rowData);
     }
 
-    return {
+    const printLayout = getPrintLayoutOptions(this.configs);
+    const tableContent: Content = {
       table: {
-        headerRows: 0,
+        headerRows:
+          printLayout?.repeatTableHeader && tableBody.length > 1 ? 1 : 0,
         widths: Array(sortedColumns.length).fill('*'),
         body: tableBody,
       },
@@ -891,6 +919,9 @@ export class PdfAdapter extends BaseAdapter<PdfAdapterFile> {
         paddingBottom: () => 5,
       },
     };
+    return printLayout?.avoidBreakInside
+      ? { stack: [tableContent], unbreakable: true }
+      : tableContent;
   }
 
   private async _processChildrenWithMargins(
@@ -921,6 +952,36 @@ export class PdfAdapter extends BaseAdapter<PdfAdapterFile> {
     return content;
   }
 
+  private _createFigureCaption(caption: string, textAlign: string): Content {
+    const captionContent: ContentText = {
+      text: caption,
+      italics: true,
+      fontSize: 10,
+      color: PDF_COLORS.textMuted,
+      margin: [0, 2, 0, 10],
+      alignment: textAlign as 'left' | 'center' | 'right',
+    };
+    if (getCaptionListsOptions(this.configs)?.listOfFigures) {
+      const number = ++this._figureCount;
+      captionContent.text = `Figure ${number}: ${caption}`;
+      captionContent.id = `figure-${number}`;
+      captionContent.tocItem = 'figures';
+    }
+    return captionContent;
+  }
+
+  /**
+   * The numbered caption of a table, listed in the "List of Tables"
+   */
+  private _createTableCaption(): ContentText {
+    const number = ++this._tableCount;
+    return {
+      text: `Table ${number}`,
+      id: `table-${number}`,
+      tocItem: 'tables',
+    };
+  }
+
   private _getImagePlaceholderContent(caption: string): Content {
     return {
       text: getImagePlaceholder(caption),
@@ -934,15 +995,54 @@ export class PdfAdapter extends BaseAdapter<PdfAdapterFile> {
     title: string | undefined,
     content: Content[]
   ): TDocumentDefinitions {
+    if (getPrintLayoutOptions(this.configs)?.breakBeforeH1) {
+      content.forEach((item, index) => {
+        if (
+          index > 0 &&
+          typeof item === 'object' &&
+          'headlineLevel' in item &&
+          item.headlineLevel === 1
+        ) {
+          item.pageBreak = 'before';
+        }
+      });
+    }
+    const captionLists = getCaptionListsOptions(this.configs);
+    const lists: Content[] = [];
+    if (captionLists?.listOfFigures && this._figureCount > 0) {
+      lists.push({
+        toc: {
+          id: 'figures',
+          title: {
+            text: captionLists.figuresTitle ?? DEFAULT_FIGURES_TITLE,
+            style: 'header2',
+          },
+        },
+        margin: [0, 0, 0, 20],
+      });
+    }
+    if (captionLists?.listOfTables && this._tableCount > 0) {
+      lists.push({
+        toc: {
+          id: 'tables',
+          title: {
+            text: captionLists.tablesTitle ?? DEFAULT_TABLES_TITLE,
+            style: 'header2',
+          },
+        },
+        margin: [0, 0, 0, 20],
+      });
+    }
     const docContent =
       title === undefined
-        ? content
+        ? [...lists, ...content]
         : [
             {
               text: title || 'Untitled',
               style: 'title',
               margin: [0, 0, 0, 20],
             } as ContentText,
+            ...lists,
             ...content,
           ];
 
@@ -1,24 +1,558 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
+/**
+ * Doc titles, headings and tags of a workspace, for the mention and quick
+ * open pickers.
+ */
+export declare class AutocompleteIndex {
+  /** Number of indexed completions. */
+  get size(): number
+}
+
+/** Computes the same key as [`hash_blob`] from a blob read in chunks. */
+export declare class BlobHasher {
+  constructor()
+  update(chunk: Uint8Array): void
+  /**
+   * Returns the key of the chunks passed so far. More chunks can be added
+   * afterwards.
+   */
+  digest(): string
+}
+
+/**
+ * Crawls a doc once and hands its blocks to JS in batches, so a large doc
+ * does not turn into one array of JS objects at once.
+ */
+export declare class DocCrawler {
+  /** Takes the same arguments as `parse_doc_from_binary`. */
+  constructor(docBin: Buffer, docId: string, includeChecksums?: boolean | undefined | null, extrasFields?: Array<string> | undefined | null)
+  get title(): string
+  get summary(): string
+  /** Number of blocks in the doc. */
+  get total(): number
+  /** Number of blocks not returned by `next_batch` yet. */
+  get remaining(): number
+  /**
+   * Returns up to `size` of the next blocks in document order, an empty
+   * array once all blocks were returned.
+   */
+  nextBatch(size: number): Array<NativeBlockInfo>
+}
+
+/**
+ * Full-text index over the titles and blocks of the docs of a workspace,
+ * filled from crawl results and kept in a file between runs.
+ */
+export declare class SearchIndex {
+  constructor()
+  /**
+   * Restores an index saved with `save`, or an empty one when there is no
+   * file at `path` yet.
+   *
+   * Fails with `Status::InvalidArg` when the file is not a search index.
+   */
+  static load(path: string): SearchIndex
+  /** Number of indexed docs. */
+  get size(): number
+  /**
+   * Indexes a doc from its crawl result, replacing the doc indexed under
+   * the same id.
+   */
+  addDoc(docId: string, crawlResult: NativeCrawlResult): void
+  /** Removes a doc, returning whether it was indexed. */
+  removeDoc(docId: string): boolean
+  /**
+   * Returns the `limit` docs with every term and `"phrase"` of `query`,
+   * best first. Filters like `tag:` are ignored, see `parse_search_query`.
+   */
+  search(query: string, limit: number): Array<NativeSearchHit>
+  /**
+   * Writes the index to `path`, through a temporary file renamed over it so
+   * a crash never leaves a partial index behind.
+   */
+  save(path: string): void
+}
+
 export declare class Tokenizer {
   count(content: string, allowedSpecial?: Array<string> | undefined | null): number
 }
 
+/**
+ * In-memory vector store for semantic search over doc chunks, keyed by
+ * block or chunk id. Results are ranked by cosine similarity.
+ *
+ * A handle belongs to the worker thread that created it. To use one index
+ * from several `worker_threads`, every worker opens its own handle with
+ * `VectorIndex.shared` under the same name; handles lock the index
+ * internally, so queries run concurrently and writes wait for them.
+ */
+export declare class VectorIndex {
+  constructor(dimensions: number)
+  /** Restores an index saved with `toBytes`. */
+  static fromBytes(data: Buffer): VectorIndex
+  /**
+   * Opens the process-wide index registered as `name`, creating it empty
+   * when no worker did yet. Handles opened under the same name in any worker
+   * see each other's writes.
+   *
+   * Fails with `Status::InvalidArg` when the index exists with other
+   * dimensions.
+   */
+  static shared(name: string, dimensions: number): VectorIndex
+  /**
+   * Unregisters the shared index `name`, returning whether it existed.
+   * Handles already open keep using the index until they are collected.
+   */
+  static releaseShared(name: string): boolean
+  get dimensions(): number
+  get size(): number
+  /**
+   * Adds a vector, replacing the one stored under the same id.
+   *
+   * Fails with `Status::InvalidArg` when the vector does not have the
+   * dimensions of the index or is all zeros.
+   */
+  add(id: string, vector: Float32Array): void
+  /** Removes a vector, returning whether it was stored. */
+  remove(id: string): boolean
+  /** Returns the `limit` entries most similar to `vector`, best first. */
+  query(vector: Float32Array, limit: number): Array<NativeVectorMatch>
+  /**
+   * Serializes the index so it can be persisted and restored with
+   * `fromBytes`.
+   */
+  toBytes(): Buffer
+}
+
+/** The words of a workspace, for "did you mean" suggestions. */
+export declare class Vocabulary {
+  /** Number of distinct words. */
+  get size(): number
+  /** Returns up to `limit` words close to `term`, closest first. */
+  suggest(term: string, limit: number): Array<NativeSuggestion>
+}
+
+/**
+ * A workspace stored as files, opened with `load_workspace_dir`. Runs the
+ * workspace-level parsers on its docs, reading them from disk as needed.
+ */
+export declare class WorkspaceDir {
+  get path(): string
+  /** Ids of the docs, sorted, the folders doc included. */
+  get docIds(): Array<string>
+  /** Keys of the blobs, sorted. */
+  get blobKeys(): Array<string>
+  /** Fails with `doc_not_found` when the doc is not in the workspace. */
+  readDoc(docId: string): Buffer
+  /** Fails with `doc_not_found` when the blob is not in the workspace. */
+  readBlob(key: string): Buffer
+  /** Same as `parse_doc_from_path` for a doc of the workspace. */
+  crawlDoc(docId: string, includeChecksums?: boolean | undefined | null, extrasFields?: Array<string> | undefined | null, encoding?: string | undefined | null, summaryJson?: string | undefined | null): Promise<NativeCrawlResult | Buffer>
+  /**
+   * Same as `parse_root_doc_meta`, with folders when the workspace has a
+   * folders doc.
+   */
+  docMetas(): Array<NativeDocMeta>
+  /** Same as `read_folder_tree_from_root_doc`. */
+  folderTree(): NativeFolderTree
+  /** Same as `build_reference_graph` over all docs of the workspace. */
+  buildReferenceGraph(): Promise<Array<NativeReferenceEdge>>
+  /**
+   * Same as `export_workspace` over all docs of the workspace but the
+   * folders doc.
+   */
+  export(format: string, onProgress?: ((err: Error | null, arg: NativeProgress) => void) | undefined | null, filterJson?: string | undefined | null): Promise<Array<NativeExportResult>>
+}
+
 export const AFFINE_PRO_LICENSE_AES_KEY: string | undefined | null
 
 export const AFFINE_PRO_PUBLIC_KEY: string | undefined | null
 
+/**
+ * Flags mass deletions, full rewrites and bot-like edit frequencies in the
+ * updates of a doc, split into windows of `window_ms`, so a safety snapshot
+ * can be taken before they are compacted away. `updates` must be in the
+ * order they were applied, from an empty doc.
+ */
+export declare function analyzeUpdateStream(updates: Array<NativeTimestampedUpdate>, windowMs: number): Array<NativeUpdateAnomaly>
+
+/**
+ * Returns up to `limit` completions whose label, or a word in it, starts
+ * with `prefix`. `kinds` restricts the results to `doc`, `heading` or `tag`
+ * completions and defaults to all of them.
+ */
+export declare function autocomplete(index: AutocompleteIndex, prefix: string, kinds: Array<string> | undefined | null, limit: number): Array<NativeCompletion>
+
+/**
+ * Indexes the doc titles and tags of a workspace root doc and the headings
+ * of `docs` on a worker thread.
+ */
+export declare function buildAutocompleteIndex(rootDocBin: Buffer, docs: Array<NativeWorkspaceDoc>): Promise<AutocompleteIndex>
+
+/**
+ * Returns the search index changes of a doc since the indexing that produced
+ * `prev_hashes`, so syncs only reindex the blocks that changed.
+ *
+ * Pass `bulk_index` to also get the changes as the body of an Elasticsearch
+ * `_bulk` request against that index.
+ */
+export declare function buildIndexDelta(prevHashes: Record<string, string>, docBin: Buffer, docId: string, bulkIndex?: string | undefined | null): NativeIndexDelta
+
+/**
+ * Lists the references between the docs of a workspace on a worker thread,
+ * one edge per referencing block and target doc, for backlink and forward
+ * link queries.
+ */
+export declare function buildReferenceGraph(docs: Array<NativeWorkspaceDoc>): Promise<Array<NativeReferenceEdge>>
+
+/**
+ * Builds the `sitemap.xml` of a published workspace, listing the pages of
+ * `published_doc_ids` under `base_url`. `last_edits` maps doc ids to the
+ * time of their last stored update, used as `lastmod` before the dates in
+ * the page meta.
+ */
+export declare function buildSitemap(rootDocBin: Buffer, publishedDocIds: Array<string>, baseUrl: string, lastEdits?: Record<string, number> | undefined | null): string
+
+/** Collects the words of the titles and blocks of `docs` on a worker thread. */
+export declare function buildVocabulary(docs: Array<NativeWorkspaceDoc>): Promise<Vocabulary>
+
+/**
+ * Checks the schema versions of the blocks of a doc without parsing it, so
+ * docs written by newer clients can be routed to upgraded workers.
+ */
+export declare function canParse(docBin: Buffer): NativeSchemaCheck
+
+/**
+ * Parses and exports the sample docs bundled with the module and compares
+ * the results with their expected outputs, so a deployment can validate a
+ * module upgrade before rolling it out. `corpus_dir` adds the deployment's
+ * own samples: `<doc id>.ydoc` binaries with optional `<doc id>.ydoc.json`
+ * crawl results and `<doc id>.ydoc.md` markdown next to them.
+ */
+export declare function checkAgainstCorpus(corpusDir?: string | undefined | null): Promise<NativeCorpusReport>
+
 export interface Chunk {
   index: number
   content: string
 }
 
+/**
+ * Splits a doc into chunks of at most `maxTokens` for embedding, each with
+ * the ids of the blocks it came from. Headings start new chunks and blocks
+ * are only split when one does not fit a chunk on its own.
+ *
+ * Fails with `invalid_options` when `maxTokens` is 0.
+ */
+export declare function chunkDocForEmbedding(docBin: Buffer, docId: string, options?: NativeEmbeddingChunkOptions | undefined | null): Array<NativeEmbeddingChunk>
+
+/**
+ * Guesses the kind of a doc (meeting notes, spec, journal, kanban project
+ * or link dump) from its structure, for default templates and analytics.
+ * Returns `other` when no kind scores 0.5, along with the scores of every
+ * kind and the features they were computed from.
+ */
+export declare function classifyDoc(docBin: Buffer, docId: string): NativeDocClassification
+
+export declare function cleanupDoc(docBin: Buffer, docId: string, optionsJson?: string | undefined | null): NativeCleanupResult
+
+/**
+ * Re-encodes a doc snapshot on a worker thread with the content of deleted
+ * items garbage-collected. The result still merges with updates made
+ * against the original snapshot.
+ */
+export declare function compactSnapshot(docBin: Buffer): Promise<Buffer>
+
+/**
+ * Aggregates the stored updates of each doc that fall in `window` into the
+ * recently edited docs and the activity of each editor. A missing window
+ * aggregates every update.
+ */
+export declare function computeActivity(updatesByDoc: Record<string, Array<NativeDocUpdate>>, window?: NativeActivityWindow | undefined | null): NativeActivityFeed
+
+export declare function computeChecklistProgress(docBin: Buffer, docId: string): NativeChecklistProgress
+
+/**
+ * Sums the content each user inserted per UTC day, for a contribution
+ * heatmap. `user_client_map` maps the CRDT client ids found in the updates
+ * to user ids; other clients are ignored.
+ */
+export declare function computeContributionHeatmap(updates: Array<NativeTimestampedUpdate>, userClientMap: Record<string, string>): Array<NativeUserContributions>
+
+/**
+ * Callbacks run while a doc is crawled.
+ *
+ * `onBlock` receives every crawled block and returns `"keep"`, `"drop"` or
+ * the block to return in its place. A hook that throws or returns any other
+ * string fails the crawl.
+ */
+export interface CrawlHooks {
+  onBlock?: (arg: NativeBlockInfo) => string | NativeBlockInfo
+}
+
+export declare function definePreset(workspaceId: string, json: string): string
+
+/**
+ * Parses a doc and returns the diagnostic bundle of the failure, or nothing
+ * when the doc parses.
+ */
+export declare function diagnoseDoc(docBin: Buffer, docId: string): NativeDiagnosticBundle | null
+
+/**
+ * Compares two snapshots of a doc on a worker thread, listing the blocks
+ * added, removed, moved and edited with their text before and after, for
+ * version history and change summaries.
+ */
+export declare function diffDocs(oldBin: Buffer, newBin: Buffer, docId: string): Promise<NativeDocDiff>
+
+/**
+ * Returns the update a peer at `state_vector` is missing, as
+ * `Y.encodeStateAsUpdate` does. An empty state vector asks for the whole
+ * doc.
+ */
+export declare function diffUpdate(docBin: Buffer, stateVector: Buffer): Buffer
+
+/**
+ * A version of a doc that grows with every insertion and deletion, cheap
+ * enough to compute on every request.
+ */
+export declare function docClock(docBin: Buffer): number
+
+/**
+ * Counts the words, characters, blocks by flavour, images and attachments
+ * of a doc and estimates its reading time, for doc info panels. Counts
+ * are taken on the stored text, without the syntax of an export.
+ */
+export declare function docStats(docBin: Buffer, docId: string): NativeDocStats
+
+/**
+ * Rewrites the blocks of flavours newer than `target_schema_version` into
+ * their closest legacy flavour, so docs shared with older clients stay
+ * readable. Returns the whole downgraded doc.
+ */
+export declare function downgradeDoc(docBin: Buffer, docId: string, targetSchemaVersion: number): NativeDowngradedDoc
+
+/** Encodes the state vector of a doc as `Y.encodeStateVector` does. */
+export declare function encodeStateVector(docBin: Buffer): Buffer
+
+/**
+ * Lists the blobs the attachments and images of a doc embed, in document
+ * order, for an antivirus pipeline to scan.
+ */
+export declare function enumerateAttachmentsForScan(docBin: Buffer, docId: string): Array<NativeScanTarget>
+
+export declare function estimateExportSize(docBin: Buffer, docId: string, format: string): NativeExportSizeEstimate
+
+/**
+ * Finds the docs the retention policy in `policy_json` archives or deletes.
+ *
+ * `last_edits` maps doc ids to the time of their last stored update, in
+ * milliseconds since the Unix epoch, and takes precedence over the dates in
+ * the page meta. `now` defaults to the current time, or the epoch in
+ * deterministic mode.
+ */
+export declare function evaluateRetention(rootDocBin: Buffer, lastEdits: Record<string, number> | undefined | null, policyJson: string, now?: number | undefined | null): NativeRetentionReport
+
+export declare function expandRowToDoc(docBin: Buffer, docId: string, databaseBlockId: string, rowId: string, newDocId: string): NativeExpandedRow
+
+/**
+ * Writes one JSON line of stats, tags, links, properties and structure
+ * metrics per doc of a workspace, for BI tools. Pass `{ "noContent": true }`
+ * as `options_json` to leave out titles and string property values.
+ */
+export declare function exportAnalytics(rootDocBin: Buffer, docs: Array<NativeWorkspaceDoc>, optionsJson?: string | undefined | null): Promise<string>
+
+export declare function exportBlockSubtree(docBin: Buffer, docId: string, blockId: string, format: string, filterJson?: string | undefined | null): NativeExportResult
+
+/**
+ * Callbacks run around the export pipeline.
+ *
+ * `onBlock` receives every block before it is rendered and returns the block
+ * to render in its place, or `null`/`undefined` to skip it. `onOutput`
+ * receives the rendered output of every block and returns the text to emit.
+ * A hook that throws leaves its input unchanged.
+ */
+export interface ExportHooks {
+  onBlock?: (arg: NativeBlockSnapshot) => NativeBlockSnapshot | undefined | null
+  onOutput?: (arg: string) => string
+}
+
+export declare function exportWithPreset(docBin: Buffer, docId: string, workspaceId: string, presetName: string): NativeExportResult
+
+export declare function exportWorkspace(docs: Array<NativeWorkspaceDoc>, format: string, onProgress?: ((err: Error | null, arg: NativeProgress) => void) | undefined | null, filterJson?: string | undefined | null): Promise<Array<NativeExportResult>>
+
+/**
+ * Lists every blob a doc embeds, in document order, with the block
+ * embedding it and whether it is an image, an audio file or another
+ * attachment, for storage GC.
+ */
+export declare function extractBlobManifest(docBin: Buffer, docId: string): Array<NativeBlobReference>
+
+/**
+ * Lists the headings of a doc in document order, with the slugs and numbers
+ * exports use for them, so tables of contents and cross-references match the
+ * exported output.
+ */
+export declare function extractOutline(docBin: Buffer, docId: string, encoding?: string | undefined | null): Array<NativeOutlineEntry> | Buffer
+
+export declare function extractSkeleton(docBin: Buffer, docId: string, encoding?: string | undefined | null): NativeDocSkeleton | Buffer
+
+/**
+ * Finds the `{{name}}` tokens and form-like blocks (`Label: ____` lines and
+ * questions followed by todo items) of a template doc, as a schema to
+ * render a form from before the doc is instantiated or fed to a workflow.
+ */
+export declare function extractTemplateVariables(docBin: Buffer, docId: string): NativeTemplateSchema
+
+/**
+ * Rewrites a doc as a binary holding only its current content, without
+ * history, deleted items or the ids of the clients that edited it. Used
+ * when archiving docs whose history must not be retained.
+ */
+export declare function flattenDoc(docBin: Buffer, docId: string): Buffer
+
+export interface FlavourHandlers {
+  crawl?: (arg: NativeBlockSnapshot) => Array<string> | undefined | null
+  markdown?: (arg: NativeBlockSnapshot) => string | undefined | null
+  html?: (arg: NativeBlockSnapshot) => string | undefined | null
+}
+
 export declare function fromModelName(modelName: string): Tokenizer | null
 
+/**
+ * Merges lexical (e.g. BM25) and vector search hits, each given best first,
+ * with reciprocal rank fusion. Either list may be empty when its backend is
+ * not configured.
+ *
+ * Fails with `Status::InvalidArg` for a negative `k` or weight.
+ */
+export declare function fuseRankings(bm25Hits: Array<NativeRankedHit>, vectorHits: Array<NativeRankedHit>, options?: NativeFusionOptions | undefined | null): Array<NativeFusedHit>
+
+/**
+ * Builds the list view preview of a doc: its title and a thumbnail of its
+ * first image, see [`generate_thumbnail`].
+ *
+ * `load_blob` is called synchronously with the blob id of the first image
+ * and returns its bytes, or `null`/`undefined` when the blob is missing.
+ */
+export declare function generateDocPreviewImage(docBin: Buffer, docId: string, loadBlob: (arg: string) => Buffer | undefined | null, maxDim: number, format: string): NativeDocPreview
+
+/**
+ * Cuts highlighted snippets from the blocks of a doc that match
+ * `query_terms`, best blocks first, so search result rows do not need the
+ * whole doc. Terms match anywhere in the text, ignoring case.
+ */
+export declare function generateSnippets(docBin: Buffer, docId: string, queryTerms: Array<string>, options?: NativeSnippetOptions | undefined | null): Array<NativeSnippet>
+
+/**
+ * Decodes an image and scales it down to fit in `max_dim` x `max_dim`,
+ * keeping its aspect ratio, then encodes it as `format`: `png`, `jpeg` or
+ * `webp`. Smaller images are re-encoded without being scaled up.
+ *
+ * Fails with `Status::InvalidArg` for unknown formats, a `max_dim` of 0 or
+ * bytes that are not a supported image.
+ */
+export declare function generateThumbnail(image: Buffer, maxDim: number, format: string): Buffer
+
 export declare function getMime(input: Uint8Array): string
 
+/**
+ * Returns the block schema versions this build reads, one range per
+ * built-in flavour, to compare workers of a mixed-version deployment.
+ */
+export declare function getSupportedSchemaRange(): Array<NativeSchemaRange>
+
+/**
+ * Returns the storage key of a blob: its SHA-256 digest in padded, URL safe
+ * base64. Importers can compare keys to skip uploading duplicates.
+ */
+export declare function hashBlob(input: Uint8Array): string
+
+export declare function healReference(docBin: Buffer, docId: string, brokenBlockId: string): Array<NativeHealCandidate>
+
 export declare function htmlSanitize(input: string): string
 
+export interface ImportBatch {
+  /** Docs loaded by this batch, in input order. */
+  docs: Array<ParsedDoc>
+  failed: Array<ImportFailure>
+  /** Opaque checkpoint to persist and pass to the next batch. */
+  checkpoint: Buffer
+}
+
+export interface ImportFailure {
+  filePath: string
+  error: string
+}
+
+export interface ImportFile {
+  filePath: string
+  content: Buffer
+}
+
+/**
+ * Loads a batch of files of a long running import on a worker thread.
+ *
+ * Files recorded in `checkpoint` are skipped. Persist the returned
+ * checkpoint after every batch; after an interruption, restart the import
+ * from its first batch with the last persisted checkpoint and the files
+ * already imported are skipped. `on_progress` is called with the `"import"`
+ * stage after every file.
+ */
+export declare function importFiles(files: Array<ImportFile>, checkpoint?: Buffer | undefined | null, onProgress?: ((err: Error | null, arg: NativeProgress) => void) | undefined | null): Promise<ImportBatch>
+
+export interface ImportPreflightReport {
+  docCount: number
+  /** Files the checkpoint records as already imported. */
+  skippedCount: number
+  root: PreflightFolder
+  attachments: Array<PreflightAttachment>
+  /** Paths of files no loader handles. */
+  unsupported: Array<string>
+}
+
+/**
+ * Checks exported markdown for style issues: skipped heading levels, long
+ * lines and bare URLs. `rules_json` configures the rules, see
+ * `validateOptions("lint", ..)`; all rules run with their defaults when it is
+ * omitted. Invalid rules are rejected with `invalid_options`.
+ */
+export declare function lintMarkdown(markdown: string, rulesJson?: string | undefined | null): Array<NativeLintIssue>
+
+/**
+ * Lists the `{{ref:<anchor>}}` tokens of a doc with what they resolve to,
+ * so broken references can be reported before exporting.
+ */
+export declare function listCrossReferences(docBin: Buffer, docId: string): Array<NativeCrossReference>
+
+/**
+ * Opens a workspace stored as files, laid out as `root.ydoc`,
+ * `docs/<doc id>.ydoc` and `blobs/<blob key>`, so the desktop app and CLI
+ * tools can work on a workspace without a database.
+ *
+ * Fails with `doc_not_found` when there is no root doc.
+ */
+export declare function loadWorkspaceDir(path: string): WorkspaceDir
+
+/**
+ * Builds a doc from Markdown, mapping headings, lists, code fences, tables
+ * and `blob://` images to their blocks, and returns it as a full update.
+ * Used to import AI-generated content and Markdown files in bulk.
+ */
+/**
+ * Wraps a doc snapshot with a label and the current time, so restore points
+ * can be listed without decoding the docs.
+ */
+export declare function makeLabeledSnapshot(docBin: Buffer, label: string): Buffer
+
+export declare function markdownToDocBinary(markdown: string, docId: string): Buffer
+
+/**
+ * Merges incremental updates of a doc into a single update on a worker
+ * thread, so update tables can be squashed without loading them into yjs.
+ */
+export declare function mergeUpdates(updates: Array<Buffer>): Promise<Buffer>
+
 /**
  * Merge updates in form like `Y.applyUpdate(doc, update)` way and return the
  * result binary.
@@ -27,6 +561,91 @@ export declare function mergeUpdatesInApplyWay(updates: Array<Buffer>): Buffer
 
 export declare function mintChallengeResponse(resource: string, bits?: number | undefined | null): Promise<string>
 
+export interface NativeActivityFeed {
+  /** Most recently edited first. */
+  docs: Array<NativeDocActivity>
+  /** Most recently active first. */
+  users: Array<NativeUserActivity>
+}
+
+/**
+ * Milliseconds since the Unix epoch. `since` is inclusive, `until`
+ * exclusive.
+ */
+export interface NativeActivityWindow {
+  since?: number
+  until?: number
+}
+
+/**
+ * Crawl result of one doc of a batch, `error` is set instead of `result`
+ * when the doc failed to parse.
+ */
+export interface NativeBatchCrawlResult {
+  docId: string
+  result?: NativeCrawlResult
+  error?: string
+  /** Set along with `error` when diagnostics are requested. */
+  diagnostics?: NativeDiagnosticBundle
+}
+
+export interface NativeBlobReference {
+  blobId: string
+  blockId: string
+  /** `image`, `audio` or `attachment`. */
+  kind: string
+  /** MIME type recorded by the editor, not checked against the blob. */
+  mimeType?: string
+  /** Size in bytes recorded by the editor. */
+  size?: number
+  /** File name, only attachments have one. */
+  name?: string
+}
+
+export interface NativeBlobType {
+  mime: string
+  /** Pixel width, for images whose header could be read. */
+  width?: number
+  /** Pixel height, for images whose header could be read. */
+  height?: number
+}
+
+export interface NativeBlockChange {
+  blockId: string
+  flavour: string
+  /** The block in the old doc, missing for added blocks. */
+  before?: NativeBlockVersion
+  /** The block in the new doc, missing for removed blocks. */
+  after?: NativeBlockVersion
+  /** Props whose value differs, for edited blocks. */
+  changedProps: Array<string>
+}
+
+export interface NativeBlockContext {
+  title: string
+  blockIds: Array<string>
+  markdown: string
+  plainText: string
+}
+
+/** Typed `additional` of a crawled block. */
+export interface NativeBlockExtras {
+  /** Schema version the extras were written with. */
+  version: number
+  /** `page` or `edgeless`, where the block is shown. */
+  displayMode?: string
+  /** Id of the note the block belongs to. */
+  noteBlockId?: string
+  /** Title of an `affine:database` block. */
+  databaseName?: string
+}
+
+export interface NativeBlockHit {
+  blockId: string
+  /** The text around the first match, with `…` where it was cut. */
+  snippet: string
+}
+
 export interface NativeBlockInfo {
   blockId: string
   flavour: string
@@ -34,9 +653,98 @@ export interface NativeBlockInfo {
   blob?: Array<string>
   refDocId?: Array<string>
   refInfo?: Array<string>
+  /**
+   * `ref_info` decoded, one entry per reference payload. Ignored when the
+   * block is passed in.
+   */
+  refs?: Array<NativeRefInfo>
   parentFlavour?: string
   parentBlockId?: string
   additional?: string
+  /** CRC-32 of the block's content, only set when checksums are requested. */
+  checksum?: string
+}
+
+export interface NativeBlockSnapshot {
+  blockId: string
+  flavour: string
+  parentBlockId?: string
+  /** `prop:*` entries of the block encoded as a JSON object. */
+  props: string
+}
+
+export interface NativeBlockVersion {
+  parentBlockId?: string
+  text: string
+}
+
+export interface NativeChecklistProgress {
+  total: number
+  checked: number
+  ratio: number
+  lists: Array<NativeListProgress>
+}
+
+export interface NativeCitation {
+  /** Offsets into the packed text, in UTF-16 units. */
+  start: number
+  end: number
+  docId: string
+  blockId?: string
+  /** Position of the chunk in the input. */
+  chunkIndex: number
+}
+
+export interface NativeCleanupResult {
+  update: Buffer
+  removedBlockIds: Array<string>
+  trimmedBlockIds: Array<string>
+}
+
+export interface NativeCollectionInfo {
+  id: string
+  name: string
+  /** Docs added to the collection by hand, besides the ones its rules match. */
+  allowList: Array<string>
+}
+
+export interface NativeCompletion {
+  /** `doc`, `heading` or `tag`. */
+  kind: string
+  label: string
+  /** Doc of doc titles and headings. */
+  docId?: string
+  /** Heading block, or the tag id for tags. */
+  id?: string
+}
+
+export interface NativeContextChunk {
+  docId: string
+  blockId?: string
+  text: string
+  /** Higher is better. Defaults to 0. */
+  score?: number
+}
+
+export interface NativeContributionDay {
+  /** `YYYY-MM-DD`, in UTC. */
+  date: string
+  /** Length of the content inserted that day. */
+  edits: number
+}
+
+export interface NativeCorpusReport {
+  corpusVersion: number
+  /** Version of the parser that produced the report. */
+  parserVersion: string
+  passed: boolean
+  samples: Array<NativeSampleCheck>
+}
+
+export interface NativeCorrection {
+  term: string
+  suggestion: string
+  distance: number
 }
 
 export interface NativeCrawlResult {
@@ -45,22 +753,1330 @@ export interface NativeCrawlResult {
   summary: string
 }
 
-export interface NativeMarkdownResult {
+export interface NativeCrossReference {
+  blockId: string
+  anchor: string
+  label?: string
+  /** `null` when the anchor matches nothing in the doc. */
+  targetBlockId?: string
+  /** Number of the target heading, see `extractOutline`. */
+  number?: string
+}
+
+export interface NativeDanglingChild {
+  parentBlockId: string
+  childBlockId: string
+}
+
+export interface NativeDatabaseBlock {
+  blockId: string
   title: string
-  markdown: string
+  columns: Array<NativeDatabaseColumn>
+  rows: Array<NativeDatabaseRow>
+  views: Array<NativeDatabaseView>
 }
 
-export interface ParsedDoc {
+export interface NativeDatabaseCell {
+  columnId: string
+  /** `empty`, `text`, `number`, `checkbox`, `date`, `options` or `other`. */
+  kind: string
+  /**
+   * The value encoded as JSON, unset for empty cells. Dates are
+   * milliseconds since the Unix epoch, options are option ids.
+   */
+  value?: string
+  /** The value as exports display it. */
+  text: string
+}
+
+export interface NativeDatabaseColumn {
+  id: string
   name: string
-  chunks: Array<Chunk>
+  /** e.g. `title`, `rich-text`, `number` or `multi-select`. */
+  columnType: string
+  options: Array<NativeDatabaseOption>
+}
+
+export interface NativeDatabaseOption {
+  id: string
+  value: string
+  color?: string
 }
 
-export declare function parseDoc(filePath: string, doc: Buffer): Promise<ParsedDoc>
+export interface NativeDatabaseRow {
+  rowId: string
+  /** One cell per column, in column order. */
+  cells: Array<NativeDatabaseCell>
+}
 
-export declare function parseDocFromBinary(docBin: Buffer, docId: string): NativeCrawlResult
+export interface NativeDatabaseView {
+  id: string
+  name: string
+  /** `table` or `kanban`. */
+  mode: string
+  /** Columns the view shows, in its order. */
+  columnIds: Array<string>
+  /** The whole view encoded as JSON, including filters and grouping. */
+  config: string
+}
 
-export declare function parseDocToMarkdown(docBin: Buffer, docId: string, aiEditable?: boolean | undefined | null): NativeMarkdownResult
+export interface NativeDateFilter {
+  /** `created` or `updated`. */
+  field: string
+  /** One of `=`, `>`, `>=`, `<` and `<=`. */
+  op: string
+  /** `YYYY-MM-DD`. */
+  date: string
+}
 
-export declare function readAllDocIdsFromRootDoc(docBin: Buffer, includeTrash?: boolean | undefined | null): Array<string>
+/**
+ * What went wrong when a doc failed to parse, small enough to attach to a
+ * bug report instead of the doc itself.
+ */
+export interface NativeDiagnosticBundle {
+  /** The parse error, followed by the errors that caused it. */
+  errorChain: Array<string>
+  /**
+   * Offset in the doc binary where decoding stopped, when the binary itself
+   * is malformed.
+   */
+  decodeOffset?: number
+  /**
+   * `xxd`-style dump of at most 96 bytes around `decodeOffset`, or of the
+   * start of the binary without one.
+   */
+  hexdump: string
+  docSize: number
+  /** Block schema versions of the doc, unset when it does not load. */
+  schema?: NativeSchemaCheck
+}
 
-export declare function verifyChallengeResponse(response: string, bits: number, resource: string): Promise<boolean>
+export interface NativeDocActivity {
+  docId: string
+  edits: number
+  lastEdit: number
+  /** Known editors of the doc, most recent first. */
+  editors: Array<string>
+}
+
+export interface NativeDocClassification {
+  /**
+   * `meeting_notes`, `spec`, `journal`, `kanban_project`, `link_dump` or
+   * `other`.
+   */
+  kind: string
+  confidence: number
+  /** Every kind but `other`, best first. */
+  scores: Array<NativeDocKindScore>
+  features: NativeDocFeatures
+}
+
+export interface NativeDocDelta {
+  added: Array<NativeBlockInfo>
+  updated: Array<NativeBlockInfo>
+  /** Ids of the deleted blocks. */
+  deleted: Array<string>
+  /** Version of the doc, to pass as `prev_state_vector` to the next call. */
+  stateVector: Buffer
+}
+
+export interface NativeDocDiff {
+  added: Array<NativeBlockChange>
+  removed: Array<NativeBlockChange>
+  moved: Array<NativeBlockChange>
+  edited: Array<NativeBlockChange>
+}
+
+/**
+ * Stable codes of the errors thrown by doc functions, set as the `code` of
+ * the JavaScript error so callers can branch on them. The message keeps the
+ * details.
+ */
+export declare enum NativeDocError {
+  DocNotFound = 'doc_not_found',
+  BlockNotFound = 'block_not_found',
+  InvalidBinary = 'invalid_binary',
+  SqliteError = 'sqlite_error',
+  ParserError = 'parser_error',
+  /** Also used for docs written with a newer block schema. */
+  UnsupportedFormat = 'unsupported_format',
+  PresetNotFound = 'preset_not_found',
+  /** Rejected arguments or options JSON. */
+  InvalidOptions = 'invalid_options',
+  Unknown = 'unknown'
+}
+
+export interface NativeDocFeatures {
+  blocks: number
+  headingRatio: number
+  todoRatio: number
+  codeRatio: number
+  linkRatio: number
+  proseRatio: number
+  datedTitle: boolean
+  kanbanViews: number
+  statusColumns: number
+  meetingTerms: number
+  specTerms: number
+}
+
+export interface NativeDocKindScore {
+  kind: string
+  score: number
+}
+
+/** Listing metadata of a doc, as the workspace root doc records it. */
+export interface NativeDocMeta {
+  docId: string
+  title: string
+  /** Milliseconds since the Unix epoch. */
+  createDate?: number
+  /** Milliseconds since the Unix epoch. */
+  updatedDate?: number
+  /** Tag ids, in the order of the doc. */
+  tags: Array<string>
+  favorite: boolean
+  trash: boolean
+  /**
+   * Id of the folder the doc is organized in, only known from the folders
+   * doc.
+   */
+  parentFolderId?: string
+}
+
+export interface NativeDocPreview {
+  title: string
+  /** Blob id of the first image of the doc. */
+  imageId?: string
+  /**
+   * Thumbnail of the first image, when the doc has one and its blob could
+   * be loaded and decoded.
+   */
+  image?: Buffer
+}
+
+export interface NativeDocSample {
+  title: string
+  blocks: Array<NativeSampledBlock>
+  /** Bytes of text in `blocks`. */
+  size: number
+  /** Blocks left out for the budget. */
+  omitted: number
+}
+
+export interface NativeDocSkeleton {
+  root: NativeSkeletonNode
+  blockCount: number
+}
+
+export interface NativeDocStateSummary {
+  /**
+   * Milliseconds since the Unix epoch, the current time when omitted
+   * or the epoch in deterministic mode.
+   */
+  now?: number
+  /** When the last snapshot was taken, omitted when the doc has none. */
+  lastSnapshotAt?: number
+  /** Updates applied since the last snapshot. */
+  pendingUpdates: number
+  /** Anomalies found in the pending updates by `analyzeUpdateStream`. */
+  anomalies?: Array<NativeUpdateAnomaly>
+}
+
+export interface NativeDocStats {
+  /** Every CJK character counts as a word. */
+  words: number
+  /** Whitespace included. */
+  characters: number
+  charactersWithoutSpaces: number
+  /** Blocks by flavour, the page and notes included. */
+  blocks: Record<string, number>
+  images: number
+  attachments: number
+  readingTimeSeconds: number
+}
+
+export interface NativeDocUpdate {
+  /** Milliseconds since the Unix epoch. */
+  timestamp: number
+  editorId?: string
+}
+
+export interface NativeDowngradedDoc {
+  docBin: Buffer
+  downgradedBlockIds: Array<string>
+}
+
+export interface NativeEmbeddingChunk {
+  text: string
+  /** Headings the first block of the chunk is under, outermost first. */
+  headingPath: Array<string>
+  /** Blocks of the chunk in document order. */
+  blockIds: Array<string>
+  startBlockId: string
+  endBlockId: string
+  tokens: number
+  /** Holds only part of the text of a block too long for one chunk. */
+  partial: boolean
+}
+
+export interface NativeEmbeddingChunkOptions {
+  /** Most tokens a chunk may hold, context line included. Defaults to 512. */
+  maxTokens?: number
+  /**
+   * Starts each chunk with a line of the doc title and the headings it is
+   * under. Defaults to `true`.
+   */
+  withContext?: boolean
+  /**
+   * Model whose tokenizer counts the tokens, `o200k_base` when it is not
+   * given or not known.
+   */
+  model?: string
+}
+
+export interface NativeExpandedRow {
+  title: string
+  docBin: Buffer
+  /** Update for the source doc linking the row to the new doc. */
+  linkUpdate: Buffer
+}
+
+export interface NativeExportResult {
+  title: string
+  format: string
+  content: string
+  unsupported: Array<NativeUnsupportedBlock>
+}
+
+export interface NativeExportSizeEstimate {
+  format: string
+  contentBytes: number
+  /** Can exceed `u32` for docs with many large attachments. */
+  blobBytes: number
+  blobCount: number
+}
+
+export interface NativeFlavourFidelity {
+  flavour: string
+  total: number
+  preserved: number
+  degraded: number
+  lost: number
+}
+
+export interface NativeFolderNode {
+  id: string
+  /** `folder`, `doc`, `tag` or `collection`. */
+  kind: string
+  /** Id of the doc, tag or collection the node links to, unset for folders. */
+  targetId?: string
+  /** Name of the folder, or title of the doc, tag or collection it links to. */
+  name: string
+  index: string
+  children: Array<NativeFolderNode>
+}
+
+export interface NativeFolderTree {
+  /** Top-level folders in their order. */
+  folders: Array<NativeFolderNode>
+  collections: Array<NativeCollectionInfo>
+}
+
+export interface NativeFusedHit {
+  docId: string
+  /** Block of the best ranked hit of the result. */
+  blockId?: string
+  score: number
+  lexicalRank?: number
+  vectorRank?: number
+  /** Every hit merged into the result, lexical hits first. */
+  provenance: Array<NativeHitProvenance>
+}
+
+export interface NativeFusionOptions {
+  /** Damping constant of reciprocal rank fusion. Defaults to 60. */
+  k?: number
+  /** Defaults to 1. */
+  lexicalWeight?: number
+  /** Defaults to 1. */
+  vectorWeight?: number
+  /** Merge hits of the same doc into one result. Defaults to true. */
+  dedupeDocs?: boolean
+  limit?: number
+}
+
+export interface NativeGroundedSentence {
+  /** Offsets in the answer, in UTF-16 units. */
+  start: number
+  end: number
+  text: string
+  /** Best score of any chunk. */
+  score: number
+  supported: boolean
+  /** Chunks supporting the sentence, best first. */
+  citations: Array<NativeGroundingCitation>
+}
+
+export interface NativeGroundingCitation {
+  docId: string
+  blockId?: string
+  /** Position of the chunk in the input. */
+  chunkIndex: number
+  score: number
+}
+
+export interface NativeGroundingOptions {
+  /**
+   * Lowest score, between 0 and 1, a chunk needs to support a sentence.
+   * Defaults to 0.5.
+   */
+  threshold?: number
+  /** Citations kept per sentence. Defaults to 3. */
+  maxCitations?: number
+}
+
+export interface NativeGroundingReport {
+  sentences: Array<NativeGroundedSentence>
+  /** Share of the sentences with content words that are supported. */
+  supportedRatio: number
+}
+
+export interface NativeHealCandidate {
+  blockId: string
+  flavour: string
+  confidence: number
+  /** `"exists"`, `"content"` or `"parent"`. */
+  reason: string
+}
+
+export interface NativeHitProvenance {
+  blockId?: string
+  /** `lexical` or `vector`. */
+  source: string
+  /** 1-based position of the hit in its source list. */
+  position: number
+}
+
+export interface NativeHtmlResult {
+  title: string
+  html: string
+  unsupported: Array<NativeUnsupportedBlock>
+}
+
+export interface NativeIndexBatch {
+  docId: string
+  title: string
+  summary: string
+  blocks: Array<NativeBlockInfo>
+  /**
+   * Whether this is the last batch of the doc. Docs without blocks get a
+   * single empty batch.
+   */
+  last: boolean
+}
+
+export interface NativeIndexDelta {
+  records: Array<NativeIndexDeltaRecord>
+  /** Record hashes by block id, to pass to the next call. */
+  hashes: Record<string, string>
+  /** Elasticsearch `_bulk` request body, when a bulk index was given. */
+  bulk?: string
+}
+
+export interface NativeIndexDeltaRecord {
+  /** `add`, `update` or `delete`. */
+  op: string
+  id: string
+  record?: NativeIndexRecord
+}
+
+export interface NativeIndexRecord {
+  docId: string
+  blockId: string
+  flavour: string
+  content: string
+  blob: Array<string>
+  refDocId: Array<string>
+  refInfo: Array<string>
+  parentFlavour?: string
+  parentBlockId?: string
+  additional?: string
+}
+
+export interface NativeInternedBatch {
+  /** Every string of the results once, indexed by their integer fields. */
+  strings: Array<string>
+  results: Array<NativeInternedBatchResult>
+}
+
+/** Same as `NativeBatchCrawlResult`, with the result interned. */
+export interface NativeInternedBatchResult {
+  docId: string
+  result?: NativeInternedCrawlResult
+  error?: string
+  diagnostics?: NativeDiagnosticBundle
+}
+
+export interface NativeInternedBlockInfo {
+  blockId: number
+  flavour: number
+  content?: Array<number>
+  blob?: Array<number>
+  refDocId?: Array<number>
+  refInfo?: Array<number>
+  parentFlavour?: number
+  parentBlockId?: number
+  additional?: number
+  checksum?: number
+}
+
+/** A crawl result whose strings are indices into `NativeInternedBatch.strings`. */
+export interface NativeInternedCrawlResult {
+  blocks: Array<NativeInternedBlockInfo>
+  title: number
+  summary: number
+}
+
+export interface NativeLabeledSnapshot {
+  label: string
+  /** Milliseconds since the Unix epoch. */
+  createdAt: number
+  docBin: Buffer
+}
+
+export interface NativeLenientCrawlResult {
+  result: NativeCrawlResult
+  /** Empty when the doc is intact. */
+  errors: Array<NativeRecoverableError>
+}
+
+export interface NativeLintIssue {
+  /** `headingincrement`, `linelength` or `bareurl`. */
+  rule: string
+  line: number
+  column: number
+  message: string
+}
+
+export interface NativeListProgress {
+  firstBlockId: string
+  parentId: string
+  total: number
+  checked: number
+  ratio: number
+}
+
+export interface NativeLogEvent {
+  /** `"error"`, `"warn"`, `"info"`, `"debug"` or `"trace"`. */
+  level: string
+  /** Module the event comes from. */
+  target: string
+  message: string
+  fields: Record<string, string>
+  /** Spans the event happened in, outermost first. */
+  spans: Array<NativeLogSpan>
+  /** Set on the event sent when a span closes, with the time it was open. */
+  elapsedMs?: number
+}
+
+/** A span an event happened in, e.g. `crawl` with the `doc_id` it crawls. */
+export interface NativeLogSpan {
+  name: string
+  fields: Record<string, string>
+}
+
+export interface NativeMarkdownResult {
+  title: string
+  markdown: string
+  /**
+   * Blocks the markdown does not represent faithfully, for the UI to warn
+   * about.
+   */
+  unsupported: Array<NativeUnsupportedBlock>
+}
+
+export interface NativeNormalizedLists {
+  update: Buffer
+  fixedBlockIds: Array<string>
+}
+
+export interface NativeOutlineEntry {
+  blockId: string
+  level: number
+  text: string
+  /** Anchor accepted by `resolveAnchor`. */
+  slug: string
+  /** `1.`, `1.1`, ... as rendered by exports with numbered headings. */
+  number: string
+}
+
+export interface NativePackedContext {
+  text: string
+  /** One entry per packed chunk, in text order. */
+  citations: Array<NativeCitation>
+  usedTokens: number
+  /** Chunks left out because they did not fit. */
+  omitted: number
+}
+
+export interface NativeProcessedImage {
+  mime: string
+  width?: number
+  height?: number
+  /** EXIF orientation from 1 to 8, when the image records one. */
+  orientation?: number
+  /** Whether the image carries GPS coordinates. */
+  hasGps: boolean
+  /**
+   * Whether metadata was removed from `data`. Only JPEG, PNG and WebP images
+   * can be stripped, other images are returned unchanged.
+   */
+  stripped: boolean
+  data: Buffer
+}
+
+export interface NativeProgress {
+  stage: string
+  done: number
+  total: number
+}
+
+export interface NativePublishedPage {
+  title: string
+  content: string
+  etag: string
+  clock: number
+}
+
+export interface NativeQuarantineResult {
+  update: Buffer
+  quarantinedBlockIds: Array<string>
+}
+
+export interface NativeQueryCorrection {
+  /** The query with every correction applied. */
+  query: string
+  corrections: Array<NativeCorrection>
+}
+
+export interface NativeRankedHit {
+  docId: string
+  blockId?: string
+}
+
+export interface NativeRecoverableError {
+  /**
+   * `decode`, `integrate`, `malformed_block`, `missing_root`,
+   * `dangling_child` or `detached_block`.
+   */
+  kind: string
+  blockId?: string
+  message: string
+}
+
+/** A reference of a block to another doc. */
+export interface NativeRefInfo {
+  docId: string
+  /** `page` or `edgeless` when the reference opens the doc in that mode. */
+  mode?: string
+  /** Blocks of the doc the reference points at. */
+  blockIds: Array<string>
+  /** Title the reference shows instead of the doc's own. */
+  title?: string
+}
+
+export interface NativeReferenceEdge {
+  sourceDocId: string
+  sourceBlockId: string
+  targetDocId: string
+  /** One of `ReferenceKind`: `linked`, `synced` or `inline`. */
+  kind: string
+  refInfo?: string
+}
+
+export interface NativeReindexReport {
+  indexed: number
+  /** Docs the fetch callback returned nothing for. */
+  missing: Array<string>
+  failed: Array<ReindexFailure>
+}
+
+export interface NativeResolvedAnchor {
+  blockId: string
+  /** `"block"`, `"heading"` or `"text"`. */
+  kind: string
+  offset: number
+}
+
+export interface NativeRetentionDecision {
+  docId: string
+  /** `archive` or `delete`. */
+  action: string
+  /** `age` or `trash`. */
+  reason: string
+  /** Whole days since the last edit, or since the doc was trashed. */
+  days: number
+}
+
+export interface NativeRetentionReport {
+  decisions: Array<NativeRetentionDecision>
+  /** Docs a rule matched but an exempt tag protects. */
+  exempted: Array<string>
+}
+
+export interface NativeRoundtripReport {
+  format: string
+  flavours: Array<NativeFlavourFidelity>
+  lossless: boolean
+}
+
+export interface NativeSampleCheck {
+  name: string
+  /** Corpus version the sample was added in. */
+  version: number
+  /** What differed from the expected outputs, empty when the sample passed. */
+  failures: Array<string>
+}
+
+export interface NativeSampledBlock {
+  blockId: string
+  flavour: string
+  /** `title`, `heading`, `paragraph` or `table_row`. */
+  kind: string
+  /** Table rows are their cells joined by ` | `. */
+  text: string
+  /** Set when the text was cut to fit the budget. */
+  truncated: boolean
+}
+
+export interface NativeScanTarget {
+  blockId: string
+  flavour: string
+  blobId: string
+  /** File name, only attachments have one. */
+  name?: string
+  /** MIME type recorded by the editor, not checked against the blob. */
+  mimeType?: string
+  /** Size in bytes recorded by the editor. */
+  size?: number
+}
+
+export interface NativeSchemaCheck {
+  supported: boolean
+  mismatches: Array<NativeSchemaMismatch>
+}
+
+export interface NativeSchemaMismatch {
+  flavour: string
+  version: number
+  blockCount: number
+}
+
+export interface NativeSchemaRange {
+  flavour: string
+  minVersion: number
+  maxVersion: number
+}
+
+export interface NativeScrubResult {
+  snapshot: Buffer
+  updates: Array<Buffer>
+  matches: number
+}
+
+export interface NativeSearchHit {
+  docId: string
+  title: string
+  /** BM25 score, higher is better. */
+  score: number
+  /**
+   * Up to 3 blocks with the most query terms. Empty when only the title
+   * matched.
+   */
+  blocks: Array<NativeBlockHit>
+}
+
+export interface NativeSearchQuery {
+  terms: Array<string>
+  phrases: Array<string>
+  tags: Array<string>
+  folders: Array<string>
+  flags: Array<string>
+  dates: Array<NativeDateFilter>
+}
+
+export interface NativeSecretFinding {
+  blockId: string
+  flavour: string
+  prop: string
+  kind: string
+  /** Character offsets of the secret in the property. */
+  start: number
+  end: number
+  preview: string
+}
+
+export interface NativeSkeletonNode {
+  flavour: string
+  textLength: number
+  children: Array<NativeSkeletonNode>
+}
+
+export interface NativeSnippet {
+  blockId: string
+  flavour: string
+  /**
+   * The text around the first match of the block, on one line, with `…`
+   * where it was cut.
+   */
+  text: string
+  /** `text` with the highlight markers around every match, not escaped. */
+  highlighted: string
+  matches: Array<NativeSnippetMatch>
+  /** Matches in the whole block, including those cut from `text`. */
+  matchCount: number
+}
+
+export interface NativeSnippetMatch {
+  /** UTF-16 offset in `text` of the snippet. */
+  start: number
+  end: number
+}
+
+export interface NativeSnippetOptions {
+  /** Most snippets returned, one per block. Defaults to 5. */
+  maxSnippets?: number
+  /**
+   * Characters kept before the first match of a block and after it.
+   * Defaults to 40.
+   */
+  contextChars?: number
+  /** Inserted before every match in `highlighted`. Defaults to `<mark>`. */
+  highlightStart?: string
+  /** Inserted after every match in `highlighted`. Defaults to `</mark>`. */
+  highlightEnd?: string
+}
+
+export interface NativeSuggestion {
+  word: string
+  distance: number
+  /** Occurrences of the word in the workspace. */
+  frequency: number
+}
+
+export interface NativeTemplateSchema {
+  title: string
+  variables: Array<NativeTemplateVariable>
+}
+
+export interface NativeTemplateVariable {
+  name: string
+  label: string
+  /** `text` or `choice`. */
+  kind: string
+  /** `placeholder` for `{{name}}` tokens, `form` for form-like blocks. */
+  source: string
+  /** Choices of a `choice` variable, in document order. */
+  options: Array<string>
+  blockIds: Array<string>
+}
+
+export interface NativeTextBlock {
+  blockId: string
+  flavour: string
+  text: string
+  /** Byte offset of the block's text in `text` of the result. */
+  start: number
+  /** Byte offset just past the block's text in `text` of the result. */
+  end: number
+}
+
+export interface NativeTextResult {
+  title: string
+  text: string
+  blocks: Array<NativeTextBlock>
+}
+
+export interface NativeTimestampedUpdate {
+  /** Milliseconds since the Unix epoch. */
+  timestamp: number
+  bin: Buffer
+}
+
+export interface NativeUnsupportedBlock {
+  blockId: string
+  flavour: string
+  /**
+   * How the block was represented: `omitted`, `plaintext`, `html` or
+   * `link`.
+   */
+  fallback: string
+}
+
+export interface NativeUpdateAnomaly {
+  /** `mass_deletion`, `full_rewrite` or `bot_like_frequency`. */
+  kind: string
+  /**
+   * Index of the first update of the window. Merging the updates before it
+   * gives the doc as it was before the anomaly.
+   */
+  firstUpdateIndex: number
+  updateCount: number
+  start: number
+  end: number
+  inserted: number
+  deleted: number
+}
+
+export interface NativeUserActivity {
+  userId: string
+  edits: number
+  /** Number of distinct docs edited. */
+  docs: number
+  lastEdit: number
+  lastDocId: string
+}
+
+export interface NativeUserContributions {
+  userId: string
+  total: number
+  /** Days with at least one edit, oldest first. */
+  days: Array<NativeContributionDay>
+}
+
+export interface NativeValidationReport {
+  /** `true` when nothing below is wrong. */
+  valid: boolean
+  /** Whether the binary decodes, nothing else is checked when it does not. */
+  decodable: boolean
+  decodeError?: string
+  /** Lowest doc schema version that reads every flavour of the doc. */
+  schemaVersion?: number
+  schema?: NativeSchemaCheck
+  blockCount: number
+  rootBlockId?: string
+  /** Blocks not reachable from the root. */
+  orphanBlockIds: Array<string>
+  /** Children listed by a block but missing from the doc. */
+  danglingChildren: Array<NativeDanglingChild>
+  /** Flavours the parser does not handle natively. */
+  unknownFlavours: Array<string>
+}
+
+export interface NativeVectorMatch {
+  id: string
+  /** Cosine similarity, from -1 to 1. */
+  score: number
+}
+
+export interface NativeWorkspaceDoc {
+  docId: string
+  docBin: Buffer
+}
+
+export declare function normalizeLists(docBin: Buffer, docId: string): NativeNormalizedLists
+
+/**
+ * Selects and orders chunks, whole docs or search hits, to fit
+ * `token_budget`, returning the packed text and a citation map from its
+ * spans back to doc and block ids.
+ *
+ * `strategy` is `relevance` (default), `document_order` or `round_robin`.
+ * Tokens are counted with the tokenizer of `model`, `o200k_base` when it is
+ * not given or not known.
+ *
+ * Fails with `Status::InvalidArg` for an unknown strategy.
+ */
+export declare function packContext(chunks: Array<NativeContextChunk>, tokenBudget: number, strategy?: string | undefined | null, model?: string | undefined | null): NativePackedContext
+
+/**
+ * Parses the `additional` JSON of a crawled block of `flavour`, so callers
+ * do not depend on its string layout. Extras newer than this module
+ * understands are rejected.
+ */
+export declare function parseAdditional(flavour: string, additional: string): NativeBlockExtras
+
+/**
+ * Renders only `block_id` and its descendants, e.g. the selection the AI
+ * copilot works on, without rendering the rest of the doc. Fails when the
+ * doc has no such block.
+ */
+export declare function parseBlockToMarkdown(docBin: Buffer, docId: string, blockId: string, aiEditable?: boolean | undefined | null): NativeMarkdownResult
+
+export interface ParsedDoc {
+  name: string
+  chunks: Array<Chunk>
+}
+
+/**
+ * Reads a database block into typed columns, rows and views, instead of
+ * the flattened texts the crawler returns for it.
+ */
+export declare function parseDatabaseBlock(docBin: Buffer, docId: string, blockId: string): NativeDatabaseBlock
+
+/**
+ * Loads a file and splits it into chunks on a worker thread.
+ *
+ * `on_progress` is called with the `"load"` stage around loading the file
+ * and with the `"chunk"` stage after every chunk is cleaned up.
+ */
+export declare function parseDoc(filePath: string, doc: Buffer, onProgress?: ((err: Error | null, arg: NativeProgress) => void) | undefined | null): Promise<ParsedDoc>
+
+/**
+ * Crawls a doc and returns only the blocks added, updated or deleted since
+ * `prev_state_vector`, the `state_vector` of the previous call. Pass an
+ * empty buffer to get every block as added.
+ */
+export declare function parseDocDelta(docBin: Buffer, prevStateVector: Buffer, docId: string, encoding?: string | undefined | null): NativeDocDelta | Buffer
+
+export declare function parseDocFromBinary(docBin: Buffer, docId: string, includeChecksums?: boolean | undefined | null, extrasFields?: Array<string> | undefined | null, encoding?: string | undefined | null, hooks?: CrawlHooks | undefined | null, summaryJson?: string | undefined | null): NativeCrawlResult | Buffer
+
+/**
+ * Same as `parse_doc_from_binary`, on a worker thread so large docs do not
+ * block the event loop. Registered flavour handlers are not applied, they
+ * can only run on the JS thread.
+ */
+export declare function parseDocFromBinaryAsync(docBin: Buffer, docId: string, includeChecksums?: boolean | undefined | null, extrasFields?: Array<string> | undefined | null, encoding?: string | undefined | null, summaryJson?: string | undefined | null): Promise<NativeCrawlResult | Buffer>
+
+/**
+ * Same as `parse_doc_from_binary_async`, with the result packed into a
+ * single buffer by `pack_crawl_result`. Large docs otherwise cross into JS
+ * as thousands of small strings and arrays; read the buffer with
+ * `PackedCrawlResult`, which only decodes the fields it is asked for.
+ */
+export declare function parseDocFromBinaryPacked(docBin: Buffer, docId: string, includeChecksums?: boolean | undefined | null, extrasFields?: Array<string> | undefined | null, summaryJson?: string | undefined | null): Promise<Buffer>
+
+/**
+ * Parses many docs at once on the Rayon thread pool, crossing the N-API
+ * boundary once per batch instead of once per doc. Results come back in the
+ * order of `docs`, a doc that fails to parse gets an `error` without
+ * failing the others. Registered flavour handlers are not applied, they can
+ * only run on the JS thread.
+ *
+ * With `diagnostics`, failed docs also get a `diagnostics` bundle to attach
+ * to bug reports in place of the doc, see `diagnoseDoc`.
+ *
+ * # Examples
+ *
+ * ```
+ * use napi::bindgen_prelude::Buffer;
+ *
+ * let docs = vec![NativeWorkspaceDoc {
+ *   doc_id: "doc-id".to_string(),
+ *   doc_bin: Buffer::from(vec![/* ...document bytes... *\/]),
+ * }];
+ * let task = parse_docs_from_binary_batch(docs, None);
+ * ```
+ */
+export declare function parseDocsFromBinaryBatch(docs: Array<NativeWorkspaceDoc>, diagnostics?: boolean | undefined | null): Promise<Array<NativeBatchCrawlResult>>
+
+/**
+ * Same as `parseDocsFromBinaryBatch`, with every string of the results
+ * stored once in `strings` and referenced by index. Flavours, extras and
+ * doc ids repeat across a workspace, interning them keeps large batches
+ * small.
+ */
+export declare function parseDocsFromBinaryBatchInterned(docs: Array<NativeWorkspaceDoc>, diagnostics?: boolean | undefined | null): Promise<NativeInternedBatch>
+
+/**
+ * Same as `parse_doc_from_binary_async`, reading the doc from a snapshot
+ * file at `path`. The file is memory-mapped rather than passed in as a
+ * `Buffer`, so a multi-hundred-MB root doc is never copied into JS or a
+ * second native buffer.
+ */
+export declare function parseDocFromPath(path: string, docId: string, includeChecksums?: boolean | undefined | null, extrasFields?: Array<string> | undefined | null, encoding?: string | undefined | null, summaryJson?: string | undefined | null): Promise<NativeCrawlResult | Buffer>
+
+/**
+ * Same as `parseDocFromBinary`, salvaging the blocks of a damaged doc
+ * instead of failing: the binary is decoded up to the damage and subtrees
+ * cut off from the root are crawled after it. What was skipped or repaired
+ * is listed in `errors`.
+ */
+export declare function parseDocLenient(docBin: Buffer, docId: string): NativeLenientCrawlResult
+
+/**
+ * Renders a doc into sanitized HTML directly, keeping callouts, databases,
+ * nested lists and inline references that are lost when converting the
+ * markdown export. Images and attachments link to `blob://<blobId>` for the
+ * caller to resolve, and doc references link to `doc_link_template` with
+ * `{docId}` substituted when it is set.
+ */
+export declare function parseDocToHtml(docBin: Buffer, docId: string, docLinkTemplate?: string | undefined | null, encoding?: string | undefined | null): NativeHtmlResult | Buffer
+
+/**
+ * Same as `parse_doc_to_html`, on a worker thread so large docs do not block
+ * the event loop.
+ */
+export declare function parseDocToHtmlAsync(docBin: Buffer, docId: string, docLinkTemplate?: string | undefined | null, encoding?: string | undefined | null): Promise<NativeHtmlResult | Buffer>
+
+/**
+ * Returns the nested block tree of a doc as a JSON string, each block with
+ * its `blockId`, `flavour`, `props` and `children` in order, to rebuild
+ * outlines and tables of contents that the flat crawl result loses.
+ */
+export declare function parseDocToJson(docBin: Buffer, docId: string): string
+
+export declare function parseDocToMarkdown(docBin: Buffer, docId: string, aiEditable?: boolean | undefined | null, hooks?: ExportHooks | undefined | null, encoding?: string | undefined | null): NativeMarkdownResult | Buffer
+
+/**
+ * Same as `parse_doc_to_markdown`, on a worker thread so large docs do not
+ * block the event loop. Registered flavour handlers and export hooks are not
+ * applied, they can only run on the JS thread.
+ */
+export declare function parseDocToMarkdownAsync(docBin: Buffer, docId: string, aiEditable?: boolean | undefined | null, encoding?: string | undefined | null): Promise<NativeMarkdownResult | Buffer>
+
+/**
+ * Extracts the plain text of every block for search indexing, without the
+ * markdown syntax an export would add. Offsets count UTF-8 bytes of `text`,
+ * use `Buffer.byteLength` on the JS side to map matches back to blocks.
+ */
+export declare function parseDocToText(docBin: Buffer, docId: string, encoding?: string | undefined | null): NativeTextResult | Buffer
+
+/**
+ * Reads the title, dates, tags, favorite and trash flags of every doc listed
+ * in a workspace root doc, trashed ones included, so listings do not parse
+ * each doc. Pass the workspace folders doc (`db$folders`) as
+ * `folders_doc_bin` to get the folder of each doc too.
+ */
+export declare function parseRootDocMeta(docBin: Buffer, foldersDocBin?: Buffer | undefined | null): Array<NativeDocMeta>
+
+/**
+ * Splits a search string into plain terms, quoted phrases and the `tag:`,
+ * `in:`, `is:`, `created:` and `updated:` filters, so JavaScript and native
+ * search read the syntax the same way.
+ *
+ * Unknown operators and invalid dates are kept as plain terms.
+ */
+export declare function parseSearchQuery(input: string): NativeSearchQuery
+
+export interface PreflightAttachment {
+  filePath: string
+  size: number
+}
+
+export interface PreflightFolder {
+  name: string
+  docCount: number
+  children: Array<PreflightFolder>
+}
+
+/**
+ * Dry run of `import_files`: reports the docs, folders, attachments and
+ * unsupported files a batch would produce without loading any of them.
+ */
+export declare function preflightImportFiles(files: Array<ImportFile>, checkpoint?: Buffer | undefined | null): ImportPreflightReport
+
+/**
+ * Reads the dimensions and EXIF orientation of an image blob and returns a
+ * copy without its metadata, so location and device details never reach
+ * storage.
+ *
+ * Stripping is lossless: the JPEG segments, PNG chunks and WebP chunks
+ * holding metadata are dropped and everything else, color profiles
+ * included, is copied as is.
+ *
+ * Fails with `Status::InvalidArg` when the blob is not an image or its
+ * structure is damaged.
+ */
+export declare function processImageBlob(input: Buffer, options?: ProcessImageOptions | undefined | null): NativeProcessedImage
+
+export interface ProcessImageOptions {
+  /**
+   * Remove EXIF, XMP and text metadata from the returned copy. Defaults to
+   * true.
+   */
+  stripMetadata?: boolean
+}
+
+/**
+ * Replaces the attachments and images embedding one of `blob_ids` with a
+ * placeholder paragraph and returns the changes as an update for the doc.
+ * Placeholders keep the id of the block they replace and no longer
+ * reference the blob.
+ */
+export declare function quarantineAttachments(docBin: Buffer, docId: string, blobIds: Array<string>): NativeQuarantineResult
+
+export declare function readAllDocIdsFromRootDoc(docBin: Buffer, includeTrash?: boolean | undefined | null): Array<string>
+
+/**
+ * Same as `read_all_doc_ids_from_root_doc`, on a worker thread so large
+ * workspaces do not block the event loop.
+ */
+export declare function readAllDocIdsFromRootDocAsync(docBin: Buffer, includeTrash?: boolean | undefined | null): Promise<Array<string>>
+
+/**
+ * Returns the title of a doc without indexing its blocks, unset when the
+ * doc has no page block. Cheaper than `parse_doc_from_binary` for listings
+ * that only show titles.
+ */
+export declare function readDocTitle(docBin: Buffer, docId: string): string | null
+
+/**
+ * Reads how the docs of a workspace are organized: its folders, in their
+ * order and with the docs, tags and collections they hold, and its
+ * collections. Folders live in the workspace folders doc (`db$folders`),
+ * without `folders_doc_bin` only collections are returned.
+ */
+export declare function readFolderTreeFromRootDoc(docBin: Buffer, foldersDocBin?: Buffer | undefined | null): NativeFolderTree
+
+/** Reads a snapshot written by `makeLabeledSnapshot`. */
+export declare function readLabeledSnapshot(snapshot: Buffer): NativeLabeledSnapshot
+
+/**
+ * Registers crawl/markdown/HTML callbacks for a proprietary block flavour.
+ *
+ * Each callback receives the block with its props encoded as JSON and
+ * returns `null`/`undefined` to leave the block out of that output.
+ * Registering a flavour again replaces its previous callbacks.
+ *
+ * Fails with `Status::InvalidArg` for flavours the parser handles natively.
+ */
+export declare function registerFlavourHandler(flavour: string, handlers: FlavourHandlers): void
+
+export interface ReindexFailure {
+  docId: string
+  error: string
+}
+
+export interface ReindexOptions {
+  /** Docs fetched, parsed and sunk at the same time. Defaults to 4. */
+  concurrency?: number
+  /** Most blocks handed to the sink in one batch. Defaults to 500. */
+  batchSize?: number
+  /** Also reindex docs in the trash. */
+  includeTrash?: boolean
+}
+
+/**
+ * Reindexes every doc listed in a workspace root doc.
+ *
+ * Each doc is fetched with `fetch_doc`, parsed on a worker thread and handed
+ * to `sink` in batches of blocks. At most `concurrency` docs are in flight,
+ * and a doc only yields its next batch once the promise returned by `sink`
+ * for the previous one resolved, so a slow sink holds back fetching instead
+ * of piling records up in memory.
+ *
+ * A doc that fails to fetch, parse or sink is reported and the others carry
+ * on. Registered flavour handlers are not applied, they can only run on the
+ * JS thread.
+ */
+export declare function reindexWorkspace(rootDocBin: Buffer, fetchDoc: ((err: Error | null, arg: string) => Promise<Buffer | undefined | null>), sink: ((err: Error | null, arg: NativeIndexBatch) => Promise<undefined>), options?: ReindexOptions | undefined | null): Promise<NativeReindexReport>
+
+/**
+ * Removes a named export preset of a workspace, returning whether it
+ * existed.
+ */
+export declare function removePreset(workspaceId: string, name: string): boolean
+
+export declare function renderBlockContext(docBin: Buffer, docId: string, blockId: string, radius: number): NativeBlockContext
+
+/**
+ * Renders a doc for its published page with a strong `etag` of the output
+ * and the `clock` of the doc, see `docClock`. The share service can answer
+ * conditional requests with a 304 while the clock of the stored doc is
+ * unchanged, without rendering again. `filter_json` lists blocks to leave
+ * out, see `validateOptions("filter", ..)`.
+ */
+export declare function renderPublishedPage(docBin: Buffer, docId: string, filterJson?: string | undefined | null): NativePublishedPage
+
+export declare function resolveAnchor(docBin: Buffer, docId: string, anchor: string): NativeResolvedAnchor | null
+
+export declare function roundtripCheck(docBin: Buffer, docId: string, format: string): NativeRoundtripReport
+
+/**
+ * Picks the title, headings, first paragraph of every section and first
+ * rows of every table of a doc, in document order, within `budget` bytes of
+ * text. Used for hover previews and AI context packing.
+ */
+export declare function sampleDoc(docBin: Buffer, docId: string, budget: number): NativeDocSample
+
+/**
+ * Looks for credentials such as AWS keys, API tokens, private keys and
+ * passwords in the blocks of a doc, so sharing it publicly can be warned
+ * about. Findings are heuristic and carry a masked preview only.
+ */
+export declare function scanForSecrets(docBin: Buffer, docId: string): Array<NativeSecretFinding>
+
+/**
+ * Masks text matching any of the regular expressions in `patterns` in a
+ * doc snapshot and in all of its stored updates, returning the rewritten
+ * binaries to store in their place. Used to remove secrets pasted by
+ * mistake from every version of a doc's history.
+ *
+ * Invalid patterns are rejected with `invalid_options`.
+ */
+export declare function scrubHistory(snapshot: Buffer, updates: Array<Buffer>, patterns: Array<string>): NativeScrubResult
+
+/**
+ * Makes parsing, export and time-based defaults reproducible across runs,
+ * for snapshot tests. Must not be enabled in production.
+ */
+export declare function setDeterministicMode(enabled: boolean): void
+
+/**
+ * Forwards the parser, exporter and importer spans and events at `level` or
+ * more severe to `callback`, e.g. to hand them to the Node logger. Events
+ * are queued without blocking the thread that emits them.
+ *
+ * `level` is one of `"off"`, `"error"`, `"warn"`, `"info"`, `"debug"` or
+ * `"trace"`. Operations run in spans named after their stage (`crawl`,
+ * `markdown`, `html`, `export`, `load`, ...) carrying the doc id or file
+ * path; failed operations are reported as `error` events in their span, and
+ * closing spans are reported at `debug` with `elapsedMs` set.
+ *
+ * The subscriber is process-wide: the last call wins, whichever worker
+ * made it.
+ */
+export declare function setLogSubscriber(level: string, callback: ((err: Error | null, arg: NativeLogEvent) => void)): void
+
+/**
+ * Tells whether a doc needs a restore point now. Returns the reason,
+ * `first_snapshot`, `anomaly`, `pending_updates` or `interval`, or `null`
+ * when it does not. `policy_json` is a snapshot policy, the defaults apply
+ * when it is omitted.
+ */
+export declare function shouldSnapshot(state: NativeDocStateSummary, policyJson?: string | undefined | null): string | null
+
+/**
+ * Detects the mime type of a blob from its magic bytes and, for PNG, JPEG,
+ * GIF, WebP and BMP images, reads the dimensions from the image header.
+ */
+export declare function sniffBlobType(input: Uint8Array): NativeBlobType
+
+/**
+ * Replaces every word of `query` missing from `vocabulary` with its closest
+ * match. Returns `null` when there is nothing to correct.
+ */
+export declare function suggestCorrections(vocabulary: Vocabulary, query: string): NativeQueryCorrection | null
+
+/**
+ * Removes the callbacks registered for `flavour`, returning whether any were
+ * registered.
+ */
+export declare function unregisterFlavourHandler(flavour: string): boolean
+
+/**
+ * Reports whether a doc binary decodes, its schema versions, orphan blocks,
+ * children pointing at missing blocks and unknown flavours, so corrupted
+ * docs can be triaged instead of failing with a parse error.
+ */
+export declare function validateDocBinary(docBin: Buffer, docId: string): NativeValidationReport
+
+/**
+ * Validates the JSON of an option object, e.g. `kind = "preset"`, failing
+ * with `invalid_options` and a message naming the offending field.
+ */
+export declare function validateOptions(kind: string, json: string): void
+
+export declare function verifyChallengeResponse(response: string, bits: number, resource: string): Promise<boolean>
+
+/**
+ * Checks which sentences of a generated answer are supported by the source
+ * chunks, by word and word pair overlap, and returns per-sentence citations
+ * for showing sources. Sentences are checked independently, so a streaming
+ * answer can be verified again as it grows.
+ *
+ * Fails with `Status::InvalidArg` for a threshold outside of 0 to 1.
+ */
+export declare function verifyGrounding(answerText: string, sourceChunks: Array<NativeContextChunk>, options?: NativeGroundingOptions | undefined | null): NativeGroundingReport
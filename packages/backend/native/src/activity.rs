@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use affine_common::activity::{
+  self, ActivityFeed, ActivityWindow, DocActivity, DocUpdate, UserActivity,
+};
+use napi_derive::napi;
+
+#[napi(object)]
+pub struct NativeDocUpdate {
+  /// Milliseconds since the Unix epoch.
+  pub timestamp: i64,
+  pub editor_id: Option<String>,
+}
+
+impl From<NativeDocUpdate> for DocUpdate {
+  fn from(update: NativeDocUpdate) -> Self {
+    Self {
+      timestamp: update.timestamp,
+      editor_id: update.editor_id,
+    }
+  }
+}
+
+/// Milliseconds since the Unix epoch. `since` is inclusive, `until`
+/// exclusive.
+#[napi(object)]
+pub struct NativeActivityWindow {
+  pub since: Option<i64>,
+  pub until: Option<i64>,
+}
+
+#[napi(object)]
+pub struct NativeDocActivity {
+  pub doc_id: String,
+  pub edits: u32,
+  pub last_edit: i64,
+  /// Known editors of the doc, most recent first.
+  pub editors: Vec<String>,
+}
+
+impl From<DocActivity> for NativeDocActivity {
+  fn from(activity: DocActivity) -> Self {
+    Self {
+      doc_id: activity.doc_id,
+      edits: activity.edits as u32,
+      last_edit: activity.last_edit,
+      editors: activity.editors,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeUserActivity {
+  pub user_id: String,
+  pub edits: u32,
+  /// Number of distinct docs edited.
+  pub docs: u32,
+  pub last_edit: i64,
+  pub last_doc_id: String,
+}
+
+impl From<UserActivity> for NativeUserActivity {
+  fn from(activity: UserActivity) -> Self {
+    Self {
+      user_id: activity.user_id,
+      edits: activity.edits as u32,
+      docs: activity.docs as u32,
+      last_edit: activity.last_edit,
+      last_doc_id: activity.last_doc_id,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeActivityFeed {
+  /// Most recently edited first.
+  pub docs: Vec<NativeDocActivity>,
+  /// Most recently active first.
+  pub users: Vec<NativeUserActivity>,
+}
+
+impl From<ActivityFeed> for NativeActivityFeed {
+  fn from(feed: ActivityFeed) -> Self {
+    Self {
+      docs: feed.docs.into_iter().map(Into::into).collect(),
+      users: feed.users.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Aggregates the stored updates of each doc that fall in `window` into the
+/// recently edited docs and the activity of each editor. A missing window
+/// aggregates every update.
+#[napi]
+pub fn compute_activity(
+  updates_by_doc: HashMap<String, Vec<NativeDocUpdate>>,
+  window: Option<NativeActivityWindow>,
+) -> NativeActivityFeed {
+  let updates_by_doc = updates_by_doc
+    .into_iter()
+    .map(|(doc_id, updates)| (doc_id, updates.into_iter().map(Into::into).collect()))
+    .collect();
+  let window = window
+    .map(|window| ActivityWindow {
+      since: window.since,
+      until: window.until,
+    })
+    .unwrap_or_default();
+  activity::compute_activity(&updates_by_doc, &window).into()
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use affine_common::doc_parser::{
+  self, AnalyticsOptions, ContributionDay, TimestampedUpdate, UpdateAnomaly, UserContributions,
+};
+use napi::{bindgen_prelude::*, Env, Task};
+use napi_derive::napi;
+
+use crate::{
+  doc::NativeWorkspaceDoc,
+  doc_error::{doc_error, options_error, task_error, DocResult},
+};
+
+pub struct AsyncExportAnalytics {
+  root_doc_bin: Vec<u8>,
+  docs: Vec<(String, Vec<u8>)>,
+  options: AnalyticsOptions,
+}
+
+#[napi]
+impl Task for AsyncExportAnalytics {
+  type Output = DocResult<String>;
+  type JsValue = String;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(
+      doc_parser::export_analytics(
+        std::mem::take(&mut self.root_doc_bin),
+        std::mem::take(&mut self.docs),
+        &self.options,
+      )
+      .map_err(doc_error),
+    )
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output.map_err(|e| task_error(env, e))
+  }
+}
+
+/// Writes one JSON line of stats, tags, links, properties and structure
+/// metrics per doc of a workspace, for BI tools. Pass `{ "noContent": true }`
+/// as `options_json` to leave out titles and string property values.
+#[napi]
+pub fn export_analytics(
+  root_doc_bin: Buffer,
+  docs: Vec<NativeWorkspaceDoc>,
+  options_json: Option<String>,
+) -> DocResult<AsyncTask<AsyncExportAnalytics>> {
+  let options = match options_json {
+    Some(json) => AnalyticsOptions::from_json(&json).map_err(options_error)?,
+    None => AnalyticsOptions::default(),
+  };
+  Ok(AsyncTask::new(AsyncExportAnalytics {
+    root_doc_bin: root_doc_bin.to_vec(),
+    docs: docs
+      .into_iter()
+      .map(|doc| (doc.doc_id, doc.doc_bin.to_vec()))
+      .collect(),
+    options,
+  }))
+}
+
+#[napi(object)]
+pub struct NativeTimestampedUpdate {
+  /// Milliseconds since the Unix epoch.
+  pub timestamp: i64,
+  pub bin: Buffer,
+}
+
+#[napi(object)]
+pub struct NativeContributionDay {
+  /// `YYYY-MM-DD`, in UTC.
+  pub date: String,
+  /// Length of the content inserted that day.
+  pub edits: u32,
+}
+
+impl From<ContributionDay> for NativeContributionDay {
+  fn from(day: ContributionDay) -> Self {
+    Self {
+      date: day.date.format("%Y-%m-%d").to_string(),
+      edits: day.edits as u32,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeUserContributions {
+  pub user_id: String,
+  pub total: u32,
+  /// Days with at least one edit, oldest first.
+  pub days: Vec<NativeContributionDay>,
+}
+
+impl From<UserContributions> for NativeUserContributions {
+  fn from(contributions: UserContributions) -> Self {
+    Self {
+      user_id: contributions.user_id,
+      total: contributions.total as u32,
+      days: contributions.days.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Sums the content each user inserted per UTC day, for a contribution
+/// heatmap. `user_client_map` maps the CRDT client ids found in the updates
+/// to user ids; other clients are ignored.
+#[napi]
+pub fn compute_contribution_heatmap(
+  updates: Vec<NativeTimestampedUpdate>,
+  user_client_map: HashMap<String, String>,
+) -> DocResult<Vec<NativeUserContributions>> {
+  let user_client_map = user_client_map
+    .into_iter()
+    .map(|(client_id, user_id)| {
+      client_id
+        .parse::<u64>()
+        .map(|client_id| (client_id, user_id))
+        .map_err(|_| options_error(format!("Invalid client id: {client_id}")))
+    })
+    .collect::<DocResult<HashMap<_, _>>>()?;
+  let updates = updates
+    .into_iter()
+    .map(|update| TimestampedUpdate {
+      timestamp: update.timestamp,
+      bin: update.bin.into(),
+    })
+    .collect::<Vec<_>>();
+  let heatmap =
+    doc_parser::compute_contribution_heatmap(&updates, &user_client_map).map_err(doc_error)?;
+  Ok(heatmap.into_iter().map(Into::into).collect())
+}
+
+#[napi(object)]
+pub struct NativeUpdateAnomaly {
+  /// `mass_deletion`, `full_rewrite` or `bot_like_frequency`.
+  pub kind: String,
+  /// Index of the first update of the window. Merging the updates before it
+  /// gives the doc as it was before the anomaly.
+  pub first_update_index: u32,
+  pub update_count: u32,
+  pub start: i64,
+  pub end: i64,
+  pub inserted: u32,
+  pub deleted: u32,
+}
+
+impl From<UpdateAnomaly> for NativeUpdateAnomaly {
+  fn from(anomaly: UpdateAnomaly) -> Self {
+    Self {
+      kind: anomaly.kind.as_str().to_string(),
+      first_update_index: anomaly.first_update_index as u32,
+      update_count: anomaly.update_count as u32,
+      start: anomaly.start,
+      end: anomaly.end,
+      inserted: anomaly.inserted as u32,
+      deleted: anomaly.deleted as u32,
+    }
+  }
+}
+
+/// Flags mass deletions, full rewrites and bot-like edit frequencies in the
+/// updates of a doc, split into windows of `window_ms`, so a safety snapshot
+/// can be taken before they are compacted away. `updates` must be in the
+/// order they were applied, from an empty doc.
+#[napi]
+pub fn analyze_update_stream(
+  updates: Vec<NativeTimestampedUpdate>,
+  window_ms: i64,
+) -> DocResult<Vec<NativeUpdateAnomaly>> {
+  let updates = updates
+    .into_iter()
+    .map(|update| TimestampedUpdate {
+      timestamp: update.timestamp,
+      bin: update.bin.into(),
+    })
+    .collect::<Vec<_>>();
+  let anomalies = doc_parser::analyze_update_stream(&updates, window_ms).map_err(doc_error)?;
+  Ok(anomalies.into_iter().map(Into::into).collect())
+}
@@ -0,0 +1,113 @@
+use affine_common::doc_parser::{self, BlobReference, QuarantineResult, ScanTarget};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::doc_error::{doc_error, DocResult};
+
+#[napi(object)]
+pub struct NativeBlobReference {
+  pub blob_id: String,
+  pub block_id: String,
+  /// `image`, `audio` or `attachment`.
+  pub kind: String,
+  /// MIME type recorded by the editor, not checked against the blob.
+  pub mime_type: Option<String>,
+  /// Size in bytes recorded by the editor.
+  pub size: Option<i64>,
+  /// File name, only attachments have one.
+  pub name: Option<String>,
+}
+
+impl From<BlobReference> for NativeBlobReference {
+  fn from(blob: BlobReference) -> Self {
+    Self {
+      blob_id: blob.blob_id,
+      block_id: blob.block_id,
+      kind: blob.kind.as_str().to_string(),
+      mime_type: blob.mime_type,
+      size: blob.size.map(|size| size as i64),
+      name: blob.name,
+    }
+  }
+}
+
+/// Lists every blob a doc embeds, in document order, with the block
+/// embedding it and whether it is an image, an audio file or another
+/// attachment, for storage GC.
+#[napi]
+pub fn extract_blob_manifest(
+  doc_bin: Buffer,
+  doc_id: String,
+) -> DocResult<Vec<NativeBlobReference>> {
+  doc_parser::extract_blob_manifest(doc_bin.into(), doc_id)
+    .map(|blobs| blobs.into_iter().map(Into::into).collect())
+    .map_err(doc_error)
+}
+
+#[napi(object)]
+pub struct NativeScanTarget {
+  pub block_id: String,
+  pub flavour: String,
+  pub blob_id: String,
+  /// File name, only attachments have one.
+  pub name: Option<String>,
+  /// MIME type recorded by the editor, not checked against the blob.
+  pub mime_type: Option<String>,
+  /// Size in bytes recorded by the editor.
+  pub size: Option<i64>,
+}
+
+impl From<ScanTarget> for NativeScanTarget {
+  fn from(target: ScanTarget) -> Self {
+    Self {
+      block_id: target.block_id,
+      flavour: target.flavour,
+      blob_id: target.blob_id,
+      name: target.name,
+      mime_type: target.mime_type,
+      size: target.size.map(|size| size as i64),
+    }
+  }
+}
+
+/// Lists the blobs the attachments and images of a doc embed, in document
+/// order, for an antivirus pipeline to scan.
+#[napi]
+pub fn enumerate_attachments_for_scan(
+  doc_bin: Buffer,
+  doc_id: String,
+) -> DocResult<Vec<NativeScanTarget>> {
+  doc_parser::enumerate_attachments_for_scan(doc_bin.into(), doc_id)
+    .map(|targets| targets.into_iter().map(Into::into).collect())
+    .map_err(doc_error)
+}
+
+#[napi(object)]
+pub struct NativeQuarantineResult {
+  pub update: Buffer,
+  pub quarantined_block_ids: Vec<String>,
+}
+
+impl From<QuarantineResult> for NativeQuarantineResult {
+  fn from(result: QuarantineResult) -> Self {
+    Self {
+      update: result.update.into(),
+      quarantined_block_ids: result.quarantined_block_ids,
+    }
+  }
+}
+
+/// Replaces the attachments and images embedding one of `blob_ids` with a
+/// placeholder paragraph and returns the changes as an update for the doc.
+/// Placeholders keep the id of the block they replace and no longer
+/// reference the blob.
+#[napi]
+pub fn quarantine_attachments(
+  doc_bin: Buffer,
+  doc_id: String,
+  blob_ids: Vec<String>,
+) -> DocResult<NativeQuarantineResult> {
+  doc_parser::quarantine_attachments(doc_bin.into(), doc_id, &blob_ids)
+    .map(Into::into)
+    .map_err(doc_error)
+}
@@ -0,0 +1,111 @@
+use affine_common::doc_parser::{
+  self, AutocompleteIndex as CoreAutocompleteIndex, Completion, CompletionKind,
+};
+use napi::{
+  bindgen_prelude::{AsyncTask, Buffer},
+  Env, Error, Result, Status, Task,
+};
+use napi_derive::napi;
+
+use crate::doc::NativeWorkspaceDoc;
+
+#[napi(object)]
+pub struct NativeCompletion {
+  /// `doc`, `heading` or `tag`.
+  pub kind: String,
+  pub label: String,
+  /// Doc of doc titles and headings.
+  pub doc_id: Option<String>,
+  /// Heading block, or the tag id for tags.
+  pub id: Option<String>,
+}
+
+impl From<Completion> for NativeCompletion {
+  fn from(completion: Completion) -> Self {
+    Self {
+      kind: completion.kind.as_str().to_string(),
+      label: completion.label,
+      doc_id: completion.doc_id,
+      id: completion.id,
+    }
+  }
+}
+
+/// Doc titles, headings and tags of a workspace, for the mention and quick
+/// open pickers.
+#[napi]
+pub struct AutocompleteIndex {
+  inner: CoreAutocompleteIndex,
+}
+
+#[napi]
+impl AutocompleteIndex {
+  /// Number of indexed completions.
+  #[napi(getter)]
+  pub fn size(&self) -> u32 {
+    self.inner.len() as u32
+  }
+}
+
+pub struct AsyncBuildAutocompleteIndex {
+  root_doc_bin: Vec<u8>,
+  docs: Vec<(String, Vec<u8>)>,
+}
+
+#[napi]
+impl Task for AsyncBuildAutocompleteIndex {
+  type Output = CoreAutocompleteIndex;
+  type JsValue = AutocompleteIndex;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    doc_parser::build_autocomplete_index(
+      std::mem::take(&mut self.root_doc_bin),
+      std::mem::take(&mut self.docs),
+    )
+    .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+  }
+
+  fn resolve(&mut self, _: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(AutocompleteIndex { inner: output })
+  }
+}
+
+/// Indexes the doc titles and tags of a workspace root doc and the headings
+/// of `docs` on a worker thread.
+#[napi]
+pub fn build_autocomplete_index(
+  root_doc_bin: Buffer,
+  docs: Vec<NativeWorkspaceDoc>,
+) -> AsyncTask<AsyncBuildAutocompleteIndex> {
+  AsyncTask::new(AsyncBuildAutocompleteIndex {
+    root_doc_bin: root_doc_bin.to_vec(),
+    docs: docs
+      .into_iter()
+      .map(|doc| (doc.doc_id, doc.doc_bin.to_vec()))
+      .collect(),
+  })
+}
+
+/// Returns up to `limit` completions whose label, or a word in it, starts
+/// with `prefix`. `kinds` restricts the results to `doc`, `heading` or `tag`
+/// completions and defaults to all of them.
+#[napi]
+pub fn autocomplete(
+  index: &AutocompleteIndex,
+  prefix: String,
+  kinds: Option<Vec<String>>,
+  limit: u32,
+) -> Result<Vec<NativeCompletion>> {
+  let kinds = kinds
+    .unwrap_or_default()
+    .iter()
+    .map(|kind| kind.parse::<CompletionKind>())
+    .collect::<std::result::Result<Vec<_>, _>>()
+    .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+  Ok(
+    doc_parser::autocomplete(&index.inner, &prefix, &kinds, limit as usize)
+      .into_iter()
+      .map(Into::into)
+      .collect(),
+  )
+}
@@ -0,0 +1,36 @@
+use affine_common::blob_hash::{self, BlobHasher as CoreBlobHasher};
+use napi_derive::napi;
+
+/// Returns the storage key of a blob: its SHA-256 digest in padded, URL safe
+/// base64. Importers can compare keys to skip uploading duplicates.
+#[napi]
+pub fn hash_blob(input: &[u8]) -> String {
+  blob_hash::hash_blob(input)
+}
+
+/// Computes the same key as [`hash_blob`] from a blob read in chunks.
+#[napi]
+#[derive(Default)]
+pub struct BlobHasher {
+  inner: CoreBlobHasher,
+}
+
+#[napi]
+impl BlobHasher {
+  #[napi(constructor)]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  #[napi]
+  pub fn update(&mut self, chunk: &[u8]) {
+    self.inner.update(chunk);
+  }
+
+  /// Returns the key of the chunks passed so far. More chunks can be added
+  /// afterwards.
+  #[napi]
+  pub fn digest(&self) -> String {
+    self.inner.clone().finalize()
+  }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use affine_common::doc_parser::{self, EmbeddingChunk, EmbeddingChunkOptions};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{
+  context_pack::model_bpe,
+  doc_error::{doc_error, status_error, DocResult},
+};
+
+#[napi(object)]
+pub struct NativeEmbeddingChunkOptions {
+  /// Most tokens a chunk may hold, context line included. Defaults to 512.
+  pub max_tokens: Option<u32>,
+  /// Starts each chunk with a line of the doc title and the headings it is
+  /// under. Defaults to `true`.
+  pub with_context: Option<bool>,
+  /// Model whose tokenizer counts the tokens, `o200k_base` when it is not
+  /// given or not known.
+  pub model: Option<String>,
+}
+
+#[napi(object)]
+pub struct NativeEmbeddingChunk {
+  pub text: String,
+  /// Headings the first block of the chunk is under, outermost first.
+  pub heading_path: Vec<String>,
+  /// Blocks of the chunk in document order.
+  pub block_ids: Vec<String>,
+  pub start_block_id: String,
+  pub end_block_id: String,
+  pub tokens: u32,
+  /// Holds only part of the text of a block too long for one chunk.
+  pub partial: bool,
+}
+
+impl From<EmbeddingChunk> for NativeEmbeddingChunk {
+  fn from(chunk: EmbeddingChunk) -> Self {
+    Self {
+      text: chunk.text,
+      heading_path: chunk.heading_path,
+      block_ids: chunk.block_ids,
+      start_block_id: chunk.start_block_id,
+      end_block_id: chunk.end_block_id,
+      tokens: chunk.tokens as u32,
+      partial: chunk.partial,
+    }
+  }
+}
+
+/// Splits a doc into chunks of at most `maxTokens` for embedding, each with
+/// the ids of the blocks it came from. Headings start new chunks and blocks
+/// are only split when one does not fit a chunk on its own.
+///
+/// Fails with `invalid_options` when `maxTokens` is 0.
+#[napi]
+pub fn chunk_doc_for_embedding(
+  doc_bin: Buffer,
+  doc_id: String,
+  options: Option<NativeEmbeddingChunkOptions>,
+) -> DocResult<Vec<NativeEmbeddingChunk>> {
+  let defaults = EmbeddingChunkOptions::default();
+  let (chunk_options, model) = match options {
+    Some(options) => (
+      EmbeddingChunkOptions {
+        max_tokens: options
+          .max_tokens
+          .map_or(defaults.max_tokens, |max| max as usize),
+        with_context: options.with_context.unwrap_or(defaults.with_context),
+      },
+      options.model,
+    ),
+    None => (defaults, None),
+  };
+  let bpe = model_bpe(model).map_err(status_error)?;
+  let allowed_special = HashSet::new();
+
+  let chunks =
+    doc_parser::chunk_doc_for_embedding(doc_bin.into(), doc_id, &chunk_options, |text| {
+      bpe.encode(text, &allowed_special).0.len()
+    })
+    .map_err(doc_error)?;
+  Ok(chunks.into_iter().map(Into::into).collect())
+}
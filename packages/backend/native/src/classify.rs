@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use affine_common::doc_parser::{self, DocClassification, DocFeatures, DocKindScore, DocStats};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::doc_error::{doc_error, DocResult};
+
+#[napi(object)]
+pub struct NativeDocFeatures {
+  pub blocks: u32,
+  pub heading_ratio: f64,
+  pub todo_ratio: f64,
+  pub code_ratio: f64,
+  pub link_ratio: f64,
+  pub prose_ratio: f64,
+  pub dated_title: bool,
+  pub kanban_views: u32,
+  pub status_columns: u32,
+  pub meeting_terms: u32,
+  pub spec_terms: u32,
+}
+
+impl From<DocFeatures> for NativeDocFeatures {
+  fn from(features: DocFeatures) -> Self {
+    Self {
+      blocks: features.blocks as u32,
+      heading_ratio: features.heading_ratio,
+      todo_ratio: features.todo_ratio,
+      code_ratio: features.code_ratio,
+      link_ratio: features.link_ratio,
+      prose_ratio: features.prose_ratio,
+      dated_title: features.dated_title,
+      kanban_views: features.kanban_views as u32,
+      status_columns: features.status_columns as u32,
+      meeting_terms: features.meeting_terms as u32,
+      spec_terms: features.spec_terms as u32,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeDocKindScore {
+  pub kind: String,
+  pub score: f64,
+}
+
+impl From<DocKindScore> for NativeDocKindScore {
+  fn from(score: DocKindScore) -> Self {
+    Self {
+      kind: score.kind.as_str().to_string(),
+      score: score.score,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeDocClassification {
+  /// `meeting_notes`, `spec`, `journal`, `kanban_project`, `link_dump` or
+  /// `other`.
+  pub kind: String,
+  pub confidence: f64,
+  /// Every kind but `other`, best first.
+  pub scores: Vec<NativeDocKindScore>,
+  pub features: NativeDocFeatures,
+}
+
+impl From<DocClassification> for NativeDocClassification {
+  fn from(classification: DocClassification) -> Self {
+    Self {
+      kind: classification.kind.as_str().to_string(),
+      confidence: classification.confidence,
+      scores: classification.scores.into_iter().map(Into::into).collect(),
+      features: classification.features.into(),
+    }
+  }
+}
+
+/// Guesses the kind of a doc (meeting notes, spec, journal, kanban project
+/// or link dump) from its structure, for default templates and analytics.
+/// Returns `other` when no kind scores 0.5, along with the scores of every
+/// kind and the features they were computed from.
+#[napi]
+pub fn classify_doc(doc_bin: Buffer, doc_id: String) -> DocResult<NativeDocClassification> {
+  let classification = doc_parser::classify_doc(doc_bin.into(), doc_id).map_err(doc_error)?;
+  Ok(classification.into())
+}
+
+#[napi(object)]
+pub struct NativeDocStats {
+  /// Every CJK character counts as a word.
+  pub words: u32,
+  /// Whitespace included.
+  pub characters: u32,
+  pub characters_without_spaces: u32,
+  /// Blocks by flavour, the page and notes included.
+  pub blocks: HashMap<String, u32>,
+  pub images: u32,
+  pub attachments: u32,
+  pub reading_time_seconds: u32,
+}
+
+impl From<DocStats> for NativeDocStats {
+  fn from(stats: DocStats) -> Self {
+    Self {
+      words: stats.words as u32,
+      characters: stats.characters as u32,
+      characters_without_spaces: stats.characters_without_spaces as u32,
+      blocks: stats
+        .blocks
+        .into_iter()
+        .map(|(flavour, count)| (flavour, count as u32))
+        .collect(),
+      images: stats.images as u32,
+      attachments: stats.attachments as u32,
+      reading_time_seconds: stats.reading_time_seconds as u32,
+    }
+  }
+}
+
+/// Counts the words, characters, blocks by flavour, images and attachments
+/// of a doc and estimates its reading time, for doc info panels. Counts
+/// are taken on the stored text, without the syntax of an export.
+#[napi]
+pub fn doc_stats(doc_bin: Buffer, doc_id: String) -> DocResult<NativeDocStats> {
+  let stats = doc_parser::doc_stats(doc_bin.into(), doc_id).map_err(doc_error)?;
+  Ok(stats.into())
+}
@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use affine_common::context_pack::{self, Citation, ContextChunk, PackStrategy, PackedContext};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use tiktoken_rs::{get_bpe_from_tokenizer, tokenizer::Tokenizer, CoreBPE};
+
+#[napi(object)]
+pub struct NativeContextChunk {
+  pub doc_id: String,
+  pub block_id: Option<String>,
+  pub text: String,
+  /// Higher is better. Defaults to 0.
+  pub score: Option<f64>,
+}
+
+impl From<NativeContextChunk> for ContextChunk {
+  fn from(chunk: NativeContextChunk) -> Self {
+    Self {
+      doc_id: chunk.doc_id,
+      block_id: chunk.block_id,
+      text: chunk.text,
+      score: chunk.score.unwrap_or_default(),
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeCitation {
+  /// Offsets into the packed text, in UTF-16 units.
+  pub start: u32,
+  pub end: u32,
+  pub doc_id: String,
+  pub block_id: Option<String>,
+  /// Position of the chunk in the input.
+  pub chunk_index: u32,
+}
+
+impl From<Citation> for NativeCitation {
+  fn from(citation: Citation) -> Self {
+    Self {
+      start: citation.start as u32,
+      end: citation.end as u32,
+      doc_id: citation.doc_id,
+      block_id: citation.block_id,
+      chunk_index: citation.chunk_index as u32,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativePackedContext {
+  pub text: String,
+  /// One entry per packed chunk, in text order.
+  pub citations: Vec<NativeCitation>,
+  pub used_tokens: u32,
+  /// Chunks left out because they did not fit.
+  pub omitted: u32,
+}
+
+impl From<PackedContext> for NativePackedContext {
+  fn from(packed: PackedContext) -> Self {
+    Self {
+      text: packed.text,
+      citations: packed.citations.into_iter().map(Into::into).collect(),
+      used_tokens: packed.used_tokens as u32,
+      omitted: packed.omitted as u32,
+    }
+  }
+}
+
+/// Selects and orders chunks, whole docs or search hits, to fit
+/// `token_budget`, returning the packed text and a citation map from its
+/// spans back to doc and block ids.
+///
+/// `strategy` is `relevance` (default), `document_order` or `round_robin`.
+/// Tokens are counted with the tokenizer of `model`, `o200k_base` when it is
+/// not given or not known.
+///
+/// Fails with `Status::InvalidArg` for an unknown strategy.
+#[napi]
+pub fn pack_context(
+  chunks: Vec<NativeContextChunk>,
+  token_budget: u32,
+  strategy: Option<String>,
+  model: Option<String>,
+) -> Result<NativePackedContext> {
+  let strategy = match strategy {
+    Some(strategy) => strategy
+      .parse::<PackStrategy>()
+      .map_err(|e| Error::new(Status::InvalidArg, e))?,
+    None => PackStrategy::default(),
+  };
+  let bpe = model_bpe(model)?;
+  let allowed_special = HashSet::new();
+
+  let chunks = chunks.into_iter().map(Into::into).collect::<Vec<_>>();
+  Ok(
+    context_pack::pack_context(&chunks, token_budget as usize, strategy, |text| {
+      bpe.encode(text, &allowed_special).0.len()
+    })
+    .into(),
+  )
+}
+
+/// The tokenizer of `model`, `o200k_base` when it is not given or not known.
+pub(crate) fn model_bpe(model: Option<String>) -> Result<CoreBPE> {
+  model
+    .and_then(|model| tiktoken_rs::get_bpe_from_model(&model).ok())
+    .map(Ok)
+    .unwrap_or_else(|| get_bpe_from_tokenizer(Tokenizer::O200kBase))
+    .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+}
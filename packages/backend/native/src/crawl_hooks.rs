@@ -0,0 +1,107 @@
+use std::cell::RefCell;
+
+use affine_common::doc_parser::{BlockAction, BlockInfo, CrawlPolicy};
+use napi::{
+  bindgen_prelude::{Either, Function},
+  Error,
+};
+use napi_derive::napi;
+
+use crate::{
+  doc::NativeBlockInfo,
+  doc_error::{options_error, NativeDocError},
+};
+
+/// Callbacks run while a doc is crawled.
+///
+/// `onBlock` receives every crawled block and returns `"keep"`, `"drop"` or
+/// the block to return in its place. A hook that throws or returns any other
+/// string fails the crawl.
+#[napi(object, object_to_js = false)]
+pub struct CrawlHooks<'env> {
+  pub on_block: Option<Function<'env, NativeBlockInfo, Either<String, NativeBlockInfo>>>,
+}
+
+pub(crate) struct JsCrawlPolicy<'a, 'env> {
+  hooks: &'a CrawlHooks<'env>,
+  /// First failure of a hook. Later blocks are dropped without calling it.
+  error: RefCell<Option<Error<NativeDocError>>>,
+}
+
+impl<'a, 'env> JsCrawlPolicy<'a, 'env> {
+  pub(crate) fn new(hooks: &'a CrawlHooks<'env>) -> Self {
+    Self {
+      hooks,
+      error: RefCell::new(None),
+    }
+  }
+
+  /// Fails with the first error of a hook, so a crawl it did not vet is not
+  /// returned.
+  pub(crate) fn finish(self) -> napi::Result<(), NativeDocError> {
+    match self.error.into_inner() {
+      Some(error) => Err(error),
+      None => Ok(()),
+    }
+  }
+}
+
+impl CrawlPolicy for JsCrawlPolicy<'_, '_> {
+  fn on_block(&self, block: &BlockInfo) -> BlockAction {
+    let Some(on_block) = &self.hooks.on_block else {
+      return BlockAction::Keep;
+    };
+    if self.error.borrow().is_some() {
+      return BlockAction::Drop;
+    }
+    block_action(on_block.call(block.clone().into())).unwrap_or_else(|error| {
+      self.error.replace(Some(error));
+      BlockAction::Drop
+    })
+  }
+}
+
+fn block_action(
+  returned: napi::Result<Either<String, NativeBlockInfo>>,
+) -> napi::Result<BlockAction, NativeDocError> {
+  match returned {
+    Ok(Either::A(action)) => match action.as_str() {
+      "keep" => Ok(BlockAction::Keep),
+      "drop" => Ok(BlockAction::Drop),
+      _ => Err(options_error(format!(
+        "onBlock returned `{action}`, expected `keep`, `drop` or a block"
+      ))),
+    },
+    Ok(Either::B(transformed)) => Ok(BlockAction::Transform(Box::new(transformed.into()))),
+    Err(e) => Err(Error::new(
+      NativeDocError::Unknown,
+      format!("onBlock failed: {}", e.reason),
+    )),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_block_action() {
+    assert!(matches!(
+      block_action(Ok(Either::A("drop".into()))),
+      Ok(BlockAction::Drop)
+    ));
+    assert!(matches!(
+      block_action(Ok(Either::A("keep".into()))),
+      Ok(BlockAction::Keep)
+    ));
+
+    let error = block_action(Ok(Either::A("skip".into()))).err().unwrap();
+    assert_eq!(error.status, NativeDocError::InvalidOptions);
+
+    let thrown = block_action(Err(Error::from_reason("hook threw")))
+      .err()
+      .unwrap();
+    assert_eq!(thrown.status, NativeDocError::Unknown);
+    assert!(thrown.reason.contains("hook threw"));
+  }
+}
@@ -0,0 +1,192 @@
+use affine_common::doc_parser::{self, CellValue, DatabaseBlock, ExpandedRow};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::doc_error::{doc_error, DocResult};
+
+#[napi(object)]
+pub struct NativeExpandedRow {
+  pub title: String,
+  pub doc_bin: Buffer,
+  /// Update for the source doc linking the row to the new doc.
+  pub link_update: Buffer,
+}
+
+impl From<ExpandedRow> for NativeExpandedRow {
+  fn from(expanded: ExpandedRow) -> Self {
+    Self {
+      title: expanded.title,
+      doc_bin: expanded.doc_bin.into(),
+      link_update: expanded.link_update.into(),
+    }
+  }
+}
+
+/// Converts a database row into a standalone doc seeded with the row's
+/// properties and description, plus the update linking the row to it.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
+/// let expanded = expand_row_to_doc(
+///   doc_bin,
+///   "doc-id".to_string(),
+///   "database-id".to_string(),
+///   "row-id".to_string(),
+///   "new-doc-id".to_string(),
+/// )
+/// .unwrap();
+/// ```
+#[napi]
+pub fn expand_row_to_doc(
+  doc_bin: Buffer,
+  doc_id: String,
+  database_block_id: String,
+  row_id: String,
+  new_doc_id: String,
+) -> DocResult<NativeExpandedRow> {
+  let result = doc_parser::expand_row_to_doc(
+    doc_bin.into(),
+    doc_id,
+    &database_block_id,
+    &row_id,
+    &new_doc_id,
+  )
+  .map_err(doc_error)?;
+  Ok(result.into())
+}
+
+#[napi(object)]
+pub struct NativeDatabaseBlock {
+  pub block_id: String,
+  pub title: String,
+  pub columns: Vec<NativeDatabaseColumn>,
+  pub rows: Vec<NativeDatabaseRow>,
+  pub views: Vec<NativeDatabaseView>,
+}
+
+#[napi(object)]
+pub struct NativeDatabaseColumn {
+  pub id: String,
+  pub name: String,
+  /// e.g. `title`, `rich-text`, `number` or `multi-select`.
+  pub column_type: String,
+  pub options: Vec<NativeDatabaseOption>,
+}
+
+#[napi(object)]
+pub struct NativeDatabaseOption {
+  pub id: String,
+  pub value: String,
+  pub color: Option<String>,
+}
+
+#[napi(object)]
+pub struct NativeDatabaseRow {
+  pub row_id: String,
+  /// One cell per column, in column order.
+  pub cells: Vec<NativeDatabaseCell>,
+}
+
+#[napi(object)]
+pub struct NativeDatabaseCell {
+  pub column_id: String,
+  /// `empty`, `text`, `number`, `checkbox`, `date`, `options` or `other`.
+  pub kind: String,
+  /// The value encoded as JSON, unset for empty cells. Dates are
+  /// milliseconds since the Unix epoch, options are option ids.
+  pub value: Option<String>,
+  /// The value as exports display it.
+  pub text: String,
+}
+
+#[napi(object)]
+pub struct NativeDatabaseView {
+  pub id: String,
+  pub name: String,
+  /// `table` or `kanban`.
+  pub mode: String,
+  /// Columns the view shows, in its order.
+  pub column_ids: Vec<String>,
+  /// The whole view encoded as JSON, including filters and grouping.
+  pub config: String,
+}
+
+impl From<DatabaseBlock> for NativeDatabaseBlock {
+  fn from(database: DatabaseBlock) -> Self {
+    Self {
+      block_id: database.block_id,
+      title: database.title,
+      columns: database
+        .columns
+        .into_iter()
+        .map(|column| NativeDatabaseColumn {
+          id: column.id,
+          name: column.name,
+          column_type: column.column_type,
+          options: column
+            .options
+            .into_iter()
+            .map(|option| NativeDatabaseOption {
+              id: option.id,
+              value: option.value,
+              color: option.color,
+            })
+            .collect(),
+        })
+        .collect(),
+      rows: database
+        .rows
+        .into_iter()
+        .map(|row| NativeDatabaseRow {
+          row_id: row.row_id,
+          cells: row
+            .cells
+            .into_iter()
+            .map(|cell| NativeDatabaseCell {
+              column_id: cell.column_id,
+              kind: cell.value.kind().to_string(),
+              value: match cell.value {
+                CellValue::Empty => None,
+                CellValue::Text(text) => serde_json::to_string(&text).ok(),
+                CellValue::Number(number) => serde_json::to_string(&number).ok(),
+                CellValue::Checkbox(checked) => serde_json::to_string(&checked).ok(),
+                CellValue::Date(date) => serde_json::to_string(&date).ok(),
+                CellValue::Options(ids) => serde_json::to_string(&ids).ok(),
+                CellValue::Other(value) => Some(value.to_string()),
+              },
+              text: cell.text,
+            })
+            .collect(),
+        })
+        .collect(),
+      views: database
+        .views
+        .into_iter()
+        .map(|view| NativeDatabaseView {
+          id: view.id,
+          name: view.name,
+          mode: view.mode,
+          column_ids: view.column_ids,
+          config: view.config.to_string(),
+        })
+        .collect(),
+    }
+  }
+}
+
+/// Reads a database block into typed columns, rows and views, instead of
+/// the flattened texts the crawler returns for it.
+#[napi]
+pub fn parse_database_block(
+  doc_bin: Buffer,
+  doc_id: String,
+  block_id: String,
+) -> DocResult<NativeDatabaseBlock> {
+  doc_parser::parse_database_block(doc_bin.into(), doc_id, &block_id)
+    .map(Into::into)
+    .map_err(doc_error)
+}
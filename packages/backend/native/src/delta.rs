@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use affine_common::doc_parser::{
+  self, BlockChange, BlockVersion, DocDelta, DocDiff, IndexDeltaRecord, IndexRecord,
+};
+use napi::{bindgen_prelude::*, Env, Task};
+use napi_derive::napi;
+
+use crate::{
+  doc::NativeBlockInfo,
+  doc_error::{doc_error, status_error, task_error, DocResult},
+  encoding::ResultEncoding,
+};
+
+#[napi(object)]
+pub struct NativeIndexRecord {
+  pub doc_id: String,
+  pub block_id: String,
+  pub flavour: String,
+  pub content: String,
+  pub blob: Vec<String>,
+  pub ref_doc_id: Vec<String>,
+  pub ref_info: Vec<String>,
+  pub parent_flavour: Option<String>,
+  pub parent_block_id: Option<String>,
+  pub additional: Option<String>,
+}
+
+impl From<IndexRecord> for NativeIndexRecord {
+  fn from(record: IndexRecord) -> Self {
+    Self {
+      doc_id: record.doc_id,
+      block_id: record.block_id,
+      flavour: record.flavour,
+      content: record.content,
+      blob: record.blob,
+      ref_doc_id: record.ref_doc_id,
+      ref_info: record.ref_info,
+      parent_flavour: record.parent_flavour,
+      parent_block_id: record.parent_block_id,
+      additional: record.additional,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeIndexDeltaRecord {
+  /// `add`, `update` or `delete`.
+  pub op: String,
+  pub id: String,
+  pub record: Option<NativeIndexRecord>,
+}
+
+impl From<IndexDeltaRecord> for NativeIndexDeltaRecord {
+  fn from(delta: IndexDeltaRecord) -> Self {
+    Self {
+      op: delta.op.as_str().to_string(),
+      id: delta.id,
+      record: delta.record.map(Into::into),
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeIndexDelta {
+  pub records: Vec<NativeIndexDeltaRecord>,
+  /// Record hashes by block id, to pass to the next call.
+  pub hashes: HashMap<String, String>,
+  /// Elasticsearch `_bulk` request body, when a bulk index was given.
+  pub bulk: Option<String>,
+}
+
+/// Returns the search index changes of a doc since the indexing that produced
+/// `prev_hashes`, so syncs only reindex the blocks that changed.
+///
+/// Pass `bulk_index` to also get the changes as the body of an Elasticsearch
+/// `_bulk` request against that index.
+#[napi]
+pub fn build_index_delta(
+  prev_hashes: HashMap<String, String>,
+  doc_bin: Buffer,
+  doc_id: String,
+  bulk_index: Option<String>,
+) -> DocResult<NativeIndexDelta> {
+  let delta =
+    doc_parser::build_index_delta(&prev_hashes, doc_bin.into(), doc_id).map_err(doc_error)?;
+  let bulk = bulk_index.map(|index| delta.to_bulk_ndjson(&index));
+  Ok(NativeIndexDelta {
+    records: delta.records.into_iter().map(Into::into).collect(),
+    hashes: delta.hashes.into_iter().collect(),
+    bulk,
+  })
+}
+
+#[napi(object)]
+pub struct NativeDocDelta {
+  pub added: Vec<NativeBlockInfo>,
+  pub updated: Vec<NativeBlockInfo>,
+  /// Ids of the deleted blocks.
+  pub deleted: Vec<String>,
+  /// Version of the doc, to pass as `prev_state_vector` to the next call.
+  pub state_vector: Buffer,
+}
+
+impl From<DocDelta> for NativeDocDelta {
+  fn from(delta: DocDelta) -> Self {
+    Self {
+      added: delta.added.into_iter().map(Into::into).collect(),
+      updated: delta.updated.into_iter().map(Into::into).collect(),
+      deleted: delta.deleted,
+      state_vector: delta.state_vector.into(),
+    }
+  }
+}
+
+/// Crawls a doc and returns only the blocks added, updated or deleted since
+/// `prev_state_vector`, the `state_vector` of the previous call. Pass an
+/// empty buffer to get every block as added.
+#[napi]
+pub fn parse_doc_delta(
+  doc_bin: Buffer,
+  prev_state_vector: Buffer,
+  doc_id: String,
+  encoding: Option<String>,
+) -> DocResult<Either<NativeDocDelta, Buffer>> {
+  let encoding = ResultEncoding::parse(encoding).map_err(status_error)?;
+  let delta =
+    doc_parser::parse_doc_delta(doc_bin.into(), &prev_state_vector, doc_id).map_err(doc_error)?;
+  Ok(
+    encoding
+      .encode(delta)
+      .map_err(status_error)?
+      .into_js(Into::into),
+  )
+}
+
+#[napi(object)]
+pub struct NativeBlockVersion {
+  pub parent_block_id: Option<String>,
+  pub text: String,
+}
+
+#[napi(object)]
+pub struct NativeBlockChange {
+  pub block_id: String,
+  pub flavour: String,
+  /// The block in the old doc, missing for added blocks.
+  pub before: Option<NativeBlockVersion>,
+  /// The block in the new doc, missing for removed blocks.
+  pub after: Option<NativeBlockVersion>,
+  /// Props whose value differs, for edited blocks.
+  pub changed_props: Vec<String>,
+}
+
+impl From<BlockChange> for NativeBlockChange {
+  fn from(change: BlockChange) -> Self {
+    let version = |version: BlockVersion| NativeBlockVersion {
+      parent_block_id: version.parent_block_id,
+      text: version.text,
+    };
+    Self {
+      block_id: change.block_id,
+      flavour: change.flavour,
+      before: change.before.map(version),
+      after: change.after.map(version),
+      changed_props: change.changed_props,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeDocDiff {
+  pub added: Vec<NativeBlockChange>,
+  pub removed: Vec<NativeBlockChange>,
+  pub moved: Vec<NativeBlockChange>,
+  pub edited: Vec<NativeBlockChange>,
+}
+
+impl From<DocDiff> for NativeDocDiff {
+  fn from(diff: DocDiff) -> Self {
+    let changes = |changes: Vec<BlockChange>| changes.into_iter().map(Into::into).collect();
+    Self {
+      added: changes(diff.added),
+      removed: changes(diff.removed),
+      moved: changes(diff.moved),
+      edited: changes(diff.edited),
+    }
+  }
+}
+
+pub struct AsyncDiffDocs {
+  old_bin: Vec<u8>,
+  new_bin: Vec<u8>,
+  doc_id: String,
+}
+
+#[napi]
+impl Task for AsyncDiffDocs {
+  type Output = DocResult<DocDiff>;
+  type JsValue = NativeDocDiff;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(
+      doc_parser::diff_docs(
+        std::mem::take(&mut self.old_bin),
+        std::mem::take(&mut self.new_bin),
+        std::mem::take(&mut self.doc_id),
+      )
+      .map_err(doc_error),
+    )
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into())
+  }
+}
+
+/// Compares two snapshots of a doc on a worker thread, listing the blocks
+/// added, removed, moved and edited with their text before and after, for
+/// version history and change summaries.
+#[napi]
+pub fn diff_docs(old_bin: Buffer, new_bin: Buffer, doc_id: String) -> AsyncTask<AsyncDiffDocs> {
+  AsyncTask::new(AsyncDiffDocs {
+    old_bin: old_bin.to_vec(),
+    new_bin: new_bin.to_vec(),
+    doc_id,
+  })
+}
@@ -0,0 +1,8 @@
+use napi_derive::napi;
+
+/// Makes parsing, export and time-based defaults reproducible across runs,
+/// for snapshot tests. Must not be enabled in production.
+#[napi]
+pub fn set_deterministic_mode(enabled: bool) {
+  affine_common::deterministic::set_deterministic_mode(enabled);
+}
@@ -1,11 +1,52 @@
-use affine_common::doc_parser::{self, BlockInfo, CrawlResult, MarkdownResult};
-use napi::bindgen_prelude::*;
+use affine_common::{
+  deterministic::is_deterministic,
+  doc_parser::{
+    self, BlobHandling, BlockExtras, BlockInfo, BlockSnapshot, CollectionInfo, CrawlOptions,
+    CrawlPolicy, CrawlResult, DiagnosticBundle, DocMeta, ExportMiddleware, ExtrasField, FolderNode,
+    FolderTree, HtmlResult, InternedBlockInfo, InternedCrawlResult, MarkdownOptions,
+    MarkdownResult, ParseError, RefInfo, StringInterner, SummaryOptions, TextBlock, TextResult,
+    UnsupportedBlock,
+  },
+};
+use napi::{bindgen_prelude::*, Env, Task};
 use napi_derive::napi;
+use rayon::prelude::*;
+
+use crate::{
+  crawl_hooks::{CrawlHooks, JsCrawlPolicy},
+  doc_error::{doc_error, status_error, task_error, DocResult, NativeDocError},
+  encoding::{Encoded, ResultEncoding},
+  export_hooks::{ExportHooks, JsExportMiddleware},
+  flavour_registry::with_flavour_registry,
+  validation::NativeDiagnosticBundle,
+};
+
+#[napi(object)]
+pub struct NativeUnsupportedBlock {
+  pub block_id: String,
+  pub flavour: String,
+  /// How the block was represented: `omitted`, `plaintext`, `html` or
+  /// `link`.
+  pub fallback: String,
+}
+
+impl From<UnsupportedBlock> for NativeUnsupportedBlock {
+  fn from(block: UnsupportedBlock) -> Self {
+    Self {
+      block_id: block.block_id,
+      flavour: block.flavour,
+      fallback: block.fallback.as_str().to_string(),
+    }
+  }
+}
 
 #[napi(object)]
 pub struct NativeMarkdownResult {
   pub title: String,
   pub markdown: String,
+  /// Blocks the markdown does not represent faithfully, for the UI to warn
+  /// about.
+  pub unsupported: Vec<NativeUnsupportedBlock>,
 }
 
 impl From<MarkdownResult> for NativeMarkdownResult {
@@ -14,7 +55,7 @@ impl From<MarkdownResult> for NativeMarkdownResult {
   /// # Examples
   ///
   /// ```
-  /// let mr = MarkdownResult { title: "t".into(), markdown: "md".into() };
+  /// let mr = MarkdownResult { title: "t".into(), markdown: "md".into(), unsupported: vec![] };
   /// let n: NativeMarkdownResult = NativeMarkdownResult::from(mr);
   /// assert_eq!(n.title, "t");
   /// assert_eq!(n.markdown, "md");
@@ -23,6 +64,30 @@ impl From<MarkdownResult> for NativeMarkdownResult {
     Self {
       title: result.title,
       markdown: result.markdown,
+      unsupported: result.unsupported.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// A reference of a block to another doc.
+#[napi(object)]
+pub struct NativeRefInfo {
+  pub doc_id: String,
+  /// `page` or `edgeless` when the reference opens the doc in that mode.
+  pub mode: Option<String>,
+  /// Blocks of the doc the reference points at.
+  pub block_ids: Vec<String>,
+  /// Title the reference shows instead of the doc's own.
+  pub title: Option<String>,
+}
+
+impl From<RefInfo> for NativeRefInfo {
+  fn from(info: RefInfo) -> Self {
+    Self {
+      doc_id: info.doc_id,
+      mode: info.mode,
+      block_ids: info.block_ids,
+      title: info.title,
     }
   }
 }
@@ -35,9 +100,31 @@ pub struct NativeBlockInfo {
   pub blob: Option<Vec<String>>,
   pub ref_doc_id: Option<Vec<String>>,
   pub ref_info: Option<Vec<String>>,
+  /// `ref_info` decoded, one entry per reference payload. Ignored when the
+  /// block is passed in.
+  pub refs: Option<Vec<NativeRefInfo>>,
   pub parent_flavour: Option<String>,
   pub parent_block_id: Option<String>,
   pub additional: Option<String>,
+  /// CRC-32 of the block's content, only set when checksums are requested.
+  pub checksum: Option<String>,
+}
+
+impl From<NativeBlockInfo> for BlockInfo {
+  fn from(info: NativeBlockInfo) -> Self {
+    Self {
+      block_id: info.block_id,
+      flavour: info.flavour,
+      content: info.content,
+      blob: info.blob,
+      ref_doc_id: info.ref_doc_id,
+      ref_info: info.ref_info,
+      parent_flavour: info.parent_flavour,
+      parent_block_id: info.parent_block_id,
+      additional: info.additional,
+      checksum: info.checksum,
+    }
+  }
 }
 
 impl From<BlockInfo> for NativeBlockInfo {
@@ -59,11 +146,19 @@ impl From<BlockInfo> for NativeBlockInfo {
   ///     parent_flavour: None,
   ///     parent_block_id: None,
   ///     additional: None,
+  ///     checksum: None,
   /// };
   ///
   /// let native: NativeBlockInfo = NativeBlockInfo::from(info);
   /// ```
   fn from(info: BlockInfo) -> Self {
+    let refs = info.ref_info.as_ref().map(|payloads| {
+      payloads
+        .iter()
+        .filter_map(|payload| RefInfo::parse(payload))
+        .map(Into::into)
+        .collect()
+    });
     Self {
       block_id: info.block_id,
       flavour: info.flavour,
@@ -71,13 +166,48 @@ impl From<BlockInfo> for NativeBlockInfo {
       blob: info.blob,
       ref_doc_id: info.ref_doc_id,
       ref_info: info.ref_info,
+      refs,
       parent_flavour: info.parent_flavour,
       parent_block_id: info.parent_block_id,
       additional: info.additional,
+      checksum: info.checksum,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeBlockSnapshot {
+  pub block_id: String,
+  pub flavour: String,
+  pub parent_block_id: Option<String>,
+  /// `prop:*` entries of the block encoded as a JSON object.
+  pub props: String,
+}
+
+impl From<&BlockSnapshot> for NativeBlockSnapshot {
+  fn from(block: &BlockSnapshot) -> Self {
+    Self {
+      block_id: block.block_id.clone(),
+      flavour: block.flavour.clone(),
+      parent_block_id: block.parent_block_id.clone(),
+      props: serde_json::Value::Object(block.props.clone()).to_string(),
     }
   }
 }
 
+impl TryFrom<NativeBlockSnapshot> for BlockSnapshot {
+  type Error = serde_json::Error;
+
+  fn try_from(block: NativeBlockSnapshot) -> std::result::Result<Self, Self::Error> {
+    Ok(Self {
+      block_id: block.block_id,
+      flavour: block.flavour,
+      parent_block_id: block.parent_block_id,
+      props: serde_json::from_str(&block.props)?,
+    })
+  }
+}
+
 #[napi(object)]
 pub struct NativeCrawlResult {
   pub blocks: Vec<NativeBlockInfo>,
@@ -110,11 +240,18 @@ impl From<CrawlResult> for NativeCrawlResult {
 
 /// Parse a binary document into a NativeCrawlResult suitable for JavaScript consumption.
 ///
-/// On failure, returns a `napi::Error` with `Status::GenericFailure` describing the parse error.
+/// On failure, returns a `napi::Error` whose code is the `NativeDocError` of the parse error.
 ///
 /// # Returns
 ///
-/// `NativeCrawlResult` with parsed `blocks`, `title`, and `summary`.
+/// `NativeCrawlResult` with parsed `blocks`, `title`, and `summary`. Blocks
+/// carry a CRC-32 `checksum` of their content when `include_checksums` is
+/// `true`, to spot-check stored crawl results against the doc later.
+/// `extras_fields` selects the fields written to `additional`, see
+/// `parse_additional`. With an `encoding` of `msgpack` or `json` the result
+/// comes back serialized in a `Buffer` instead. `hooks.onBlock` can keep,
+/// drop or rewrite every block, see `CrawlHooks`. `summary_json` tunes the
+/// summary with a `"summary"` options object, see `validate_options`.
 ///
 /// # Examples
 ///
@@ -125,14 +262,546 @@ impl From<CrawlResult> for NativeCrawlResult {
 /// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
 /// let doc_id = "example-doc-id".to_string();
 ///
-/// let result = parse_doc_from_binary(doc_bin, doc_id).expect("parsing should succeed");
-/// assert!(!result.title.is_empty());
+/// let result = parse_doc_from_binary(doc_bin, doc_id, None, None, None, None).expect("parsing should succeed");
+/// if let Either::A(result) = result {
+///   assert!(!result.title.is_empty());
+/// }
 /// ```
 #[napi]
-pub fn parse_doc_from_binary(doc_bin: Buffer, doc_id: String) -> Result<NativeCrawlResult> {
-  let result = doc_parser::parse_doc_from_binary(doc_bin.into(), doc_id)
-    .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
-  Ok(result.into())
+pub fn parse_doc_from_binary(
+  env: Env,
+  doc_bin: Buffer,
+  doc_id: String,
+  include_checksums: Option<bool>,
+  extras_fields: Option<Vec<String>>,
+  encoding: Option<String>,
+  hooks: Option<CrawlHooks>,
+  summary_json: Option<String>,
+) -> DocResult<Either<NativeCrawlResult, Buffer>> {
+  let extras = parse_extras_fields(extras_fields).map_err(status_error)?;
+  let encoding = ResultEncoding::parse(encoding).map_err(status_error)?;
+  let summary = parse_summary_options(summary_json).map_err(status_error)?;
+  let policy = hooks.as_ref().map(JsCrawlPolicy::new);
+  let result = with_flavour_registry(&env, |handlers| {
+    doc_parser::parse_doc_from_binary_with_options(
+      doc_bin.into(),
+      doc_id,
+      &CrawlOptions {
+        handlers: Some(handlers),
+        checksums: include_checksums.unwrap_or(false),
+        extras,
+        policy: policy.as_ref().map(|policy| policy as &dyn CrawlPolicy),
+        summary,
+      },
+    )
+  });
+  if let Some(policy) = policy {
+    policy.finish()?;
+  }
+  let result = result.map_err(doc_error)?;
+  Ok(
+    encoding
+      .encode(result)
+      .map_err(status_error)?
+      .into_js(Into::into),
+  )
+}
+
+pub struct AsyncParseDocFromBinary {
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  checksums: bool,
+  extras: Option<Vec<ExtrasField>>,
+  encoding: ResultEncoding,
+  summary: SummaryOptions,
+}
+
+#[napi]
+impl Task for AsyncParseDocFromBinary {
+  type Output = DocResult<Encoded<CrawlResult>>;
+  type JsValue = Either<NativeCrawlResult, Buffer>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(
+      doc_parser::parse_doc_from_binary_with_options(
+        std::mem::take(&mut self.doc_bin),
+        std::mem::take(&mut self.doc_id),
+        &CrawlOptions {
+          checksums: self.checksums,
+          extras: self.extras.take(),
+          summary: self.summary.clone(),
+          ..Default::default()
+        },
+      )
+      .map_err(doc_error)
+      .and_then(|result| self.encoding.encode(result).map_err(status_error)),
+    )
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into_js(Into::into))
+  }
+}
+
+/// Same as `parse_doc_from_binary`, on a worker thread so large docs do not
+/// block the event loop. Registered flavour handlers are not applied, they
+/// can only run on the JS thread.
+#[napi]
+pub fn parse_doc_from_binary_async(
+  doc_bin: Buffer,
+  doc_id: String,
+  include_checksums: Option<bool>,
+  extras_fields: Option<Vec<String>>,
+  encoding: Option<String>,
+  summary_json: Option<String>,
+) -> DocResult<AsyncTask<AsyncParseDocFromBinary>> {
+  Ok(AsyncTask::new(AsyncParseDocFromBinary {
+    doc_bin: doc_bin.to_vec(),
+    doc_id,
+    checksums: include_checksums.unwrap_or(false),
+    extras: parse_extras_fields(extras_fields).map_err(status_error)?,
+    encoding: ResultEncoding::parse(encoding).map_err(status_error)?,
+    summary: parse_summary_options(summary_json).map_err(status_error)?,
+  }))
+}
+
+pub struct AsyncParseDocFromBinaryPacked {
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  checksums: bool,
+  extras: Option<Vec<ExtrasField>>,
+  summary: SummaryOptions,
+}
+
+#[napi]
+impl Task for AsyncParseDocFromBinaryPacked {
+  type Output = DocResult<Vec<u8>>;
+  type JsValue = Buffer;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(
+      doc_parser::parse_doc_from_binary_with_options(
+        std::mem::take(&mut self.doc_bin),
+        std::mem::take(&mut self.doc_id),
+        &CrawlOptions {
+          checksums: self.checksums,
+          extras: self.extras.take(),
+          summary: self.summary.clone(),
+          ..Default::default()
+        },
+      )
+      .map(|result| doc_parser::pack_crawl_result(&result))
+      .map_err(doc_error),
+    )
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into())
+  }
+}
+
+/// Same as `parse_doc_from_binary_async`, with the result packed into a
+/// single buffer by `pack_crawl_result`. Large docs otherwise cross into JS
+/// as thousands of small strings and arrays; read the buffer with
+/// `PackedCrawlResult`, which only decodes the fields it is asked for.
+#[napi]
+pub fn parse_doc_from_binary_packed(
+  doc_bin: Buffer,
+  doc_id: String,
+  include_checksums: Option<bool>,
+  extras_fields: Option<Vec<String>>,
+  summary_json: Option<String>,
+) -> DocResult<AsyncTask<AsyncParseDocFromBinaryPacked>> {
+  Ok(AsyncTask::new(AsyncParseDocFromBinaryPacked {
+    doc_bin: doc_bin.to_vec(),
+    doc_id,
+    checksums: include_checksums.unwrap_or(false),
+    extras: parse_extras_fields(extras_fields).map_err(status_error)?,
+    summary: parse_summary_options(summary_json).map_err(status_error)?,
+  }))
+}
+
+/// Crawls a doc once and hands its blocks to JS in batches, so a large doc
+/// does not turn into one array of JS objects at once.
+#[napi]
+pub struct DocCrawler {
+  title: String,
+  summary: String,
+  total: u32,
+  blocks: std::vec::IntoIter<BlockInfo>,
+}
+
+#[napi]
+impl DocCrawler {
+  /// Takes the same arguments as `parse_doc_from_binary`.
+  #[napi(constructor)]
+  pub fn new(
+    env: Env,
+    doc_bin: Buffer,
+    doc_id: String,
+    include_checksums: Option<bool>,
+    extras_fields: Option<Vec<String>>,
+  ) -> DocResult<Self> {
+    let extras = parse_extras_fields(extras_fields).map_err(status_error)?;
+    let result = with_flavour_registry(&env, |handlers| {
+      doc_parser::parse_doc_from_binary_with_options(
+        doc_bin.into(),
+        doc_id,
+        &CrawlOptions {
+          handlers: Some(handlers),
+          checksums: include_checksums.unwrap_or(false),
+          extras,
+          policy: None,
+          summary: SummaryOptions::default(),
+        },
+      )
+    })
+    .map_err(doc_error)?;
+
+    Ok(Self {
+      title: result.title,
+      summary: result.summary,
+      total: result.blocks.len() as u32,
+      blocks: result.blocks.into_iter(),
+    })
+  }
+
+  #[napi(getter)]
+  pub fn title(&self) -> String {
+    self.title.clone()
+  }
+
+  #[napi(getter)]
+  pub fn summary(&self) -> String {
+    self.summary.clone()
+  }
+
+  /// Number of blocks in the doc.
+  #[napi(getter)]
+  pub fn total(&self) -> u32 {
+    self.total
+  }
+
+  /// Number of blocks not returned by `next_batch` yet.
+  #[napi(getter)]
+  pub fn remaining(&self) -> u32 {
+    self.blocks.len() as u32
+  }
+
+  /// Returns up to `size` of the next blocks in document order, an empty
+  /// array once all blocks were returned.
+  #[napi]
+  pub fn next_batch(&mut self, size: u32) -> Vec<NativeBlockInfo> {
+    self
+      .blocks
+      .by_ref()
+      .take(size as usize)
+      .map(Into::into)
+      .collect()
+  }
+}
+
+pub(crate) fn parse_extras_fields(
+  extras_fields: Option<Vec<String>>,
+) -> Result<Option<Vec<ExtrasField>>> {
+  extras_fields
+    .map(|fields| {
+      fields
+        .iter()
+        .map(|field| field.parse::<ExtrasField>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))
+    })
+    .transpose()
+}
+
+pub(crate) fn parse_summary_options(summary_json: Option<String>) -> Result<SummaryOptions> {
+  match summary_json {
+    Some(json) => {
+      SummaryOptions::from_json(&json).map_err(|e| Error::new(Status::InvalidArg, e.to_string()))
+    }
+    None => Ok(SummaryOptions::default()),
+  }
+}
+
+/// Typed `additional` of a crawled block.
+#[napi(object)]
+pub struct NativeBlockExtras {
+  /// Schema version the extras were written with.
+  pub version: u32,
+  /// `page` or `edgeless`, where the block is shown.
+  pub display_mode: Option<String>,
+  /// Id of the note the block belongs to.
+  pub note_block_id: Option<String>,
+  /// Title of an `affine:database` block.
+  pub database_name: Option<String>,
+}
+
+impl From<BlockExtras> for NativeBlockExtras {
+  fn from(extras: BlockExtras) -> Self {
+    Self {
+      version: extras.version,
+      display_mode: extras.display_mode,
+      note_block_id: extras.note_block_id,
+      database_name: extras.database_name,
+    }
+  }
+}
+
+/// Parses the `additional` JSON of a crawled block of `flavour`, so callers
+/// do not depend on its string layout. Extras newer than this module
+/// understands are rejected.
+#[napi]
+pub fn parse_additional(flavour: String, additional: String) -> DocResult<NativeBlockExtras> {
+  doc_parser::parse_additional(&flavour, &additional)
+    .map(Into::into)
+    .map_err(doc_error)
+}
+
+/// Crawl result of one doc of a batch, `error` is set instead of `result`
+/// when the doc failed to parse.
+#[napi(object)]
+pub struct NativeBatchCrawlResult {
+  pub doc_id: String,
+  pub result: Option<NativeCrawlResult>,
+  pub error: Option<String>,
+  /// Set along with `error` when diagnostics are requested.
+  pub diagnostics: Option<NativeDiagnosticBundle>,
+}
+
+pub struct AsyncParseDocsFromBinaryBatch {
+  docs: Vec<(String, Vec<u8>)>,
+  diagnostics: bool,
+}
+
+type BatchCrawlOutput = (
+  String,
+  std::result::Result<CrawlResult, ParseError>,
+  Option<DiagnosticBundle>,
+);
+
+fn crawl_batch(docs: Vec<(String, Vec<u8>)>, diagnostics: bool) -> Vec<BatchCrawlOutput> {
+  let crawl = |(doc_id, doc_bin): (String, Vec<u8>)| {
+    let result =
+      doc_parser::parse_doc_from_slice(&doc_bin, doc_id.clone(), &CrawlOptions::default());
+    let bundle = match &result {
+      Err(e) if diagnostics => Some(doc_parser::diagnose_parse_failure(&doc_bin, e)),
+      _ => None,
+    };
+    (doc_id, result, bundle)
+  };
+  if is_deterministic() {
+    docs.into_iter().map(crawl).collect()
+  } else {
+    docs.into_par_iter().map(crawl).collect()
+  }
+}
+
+#[napi]
+impl Task for AsyncParseDocsFromBinaryBatch {
+  type Output = Vec<BatchCrawlOutput>;
+  type JsValue = Vec<NativeBatchCrawlResult>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(crawl_batch(
+      std::mem::take(&mut self.docs),
+      self.diagnostics,
+    ))
+  }
+
+  fn resolve(&mut self, _: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(
+      output
+        .into_iter()
+        .map(|(doc_id, result, bundle)| match result {
+          Ok(result) => NativeBatchCrawlResult {
+            doc_id,
+            result: Some(result.into()),
+            error: None,
+            diagnostics: None,
+          },
+          Err(e) => NativeBatchCrawlResult {
+            doc_id,
+            result: None,
+            error: Some(e.to_string()),
+            diagnostics: bundle.map(Into::into),
+          },
+        })
+        .collect(),
+    )
+  }
+}
+
+/// Parses many docs at once on the Rayon thread pool, crossing the N-API
+/// boundary once per batch instead of once per doc. Results come back in the
+/// order of `docs`, a doc that fails to parse gets an `error` without
+/// failing the others. Registered flavour handlers are not applied, they can
+/// only run on the JS thread.
+///
+/// With `diagnostics`, failed docs also get a `diagnostics` bundle to attach
+/// to bug reports in place of the doc, see `diagnoseDoc`.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let docs = vec![NativeWorkspaceDoc {
+///   doc_id: "doc-id".to_string(),
+///   doc_bin: Buffer::from(vec![/* ...document bytes... */]),
+/// }];
+/// let task = parse_docs_from_binary_batch(docs, None);
+/// ```
+#[napi]
+pub fn parse_docs_from_binary_batch(
+  docs: Vec<NativeWorkspaceDoc>,
+  diagnostics: Option<bool>,
+) -> AsyncTask<AsyncParseDocsFromBinaryBatch> {
+  AsyncTask::new(AsyncParseDocsFromBinaryBatch {
+    docs: docs
+      .into_iter()
+      .map(|doc| (doc.doc_id, doc.doc_bin.to_vec()))
+      .collect(),
+    diagnostics: diagnostics.unwrap_or(false),
+  })
+}
+
+#[napi(object)]
+pub struct NativeInternedBlockInfo {
+  pub block_id: u32,
+  pub flavour: u32,
+  pub content: Option<Vec<u32>>,
+  pub blob: Option<Vec<u32>>,
+  pub ref_doc_id: Option<Vec<u32>>,
+  pub ref_info: Option<Vec<u32>>,
+  pub parent_flavour: Option<u32>,
+  pub parent_block_id: Option<u32>,
+  pub additional: Option<u32>,
+  pub checksum: Option<u32>,
+}
+
+impl From<InternedBlockInfo> for NativeInternedBlockInfo {
+  fn from(info: InternedBlockInfo) -> Self {
+    Self {
+      block_id: info.block_id,
+      flavour: info.flavour,
+      content: info.content,
+      blob: info.blob,
+      ref_doc_id: info.ref_doc_id,
+      ref_info: info.ref_info,
+      parent_flavour: info.parent_flavour,
+      parent_block_id: info.parent_block_id,
+      additional: info.additional,
+      checksum: info.checksum,
+    }
+  }
+}
+
+/// A crawl result whose strings are indices into `NativeInternedBatch.strings`.
+#[napi(object)]
+pub struct NativeInternedCrawlResult {
+  pub blocks: Vec<NativeInternedBlockInfo>,
+  pub title: u32,
+  pub summary: u32,
+}
+
+impl From<InternedCrawlResult> for NativeInternedCrawlResult {
+  fn from(result: InternedCrawlResult) -> Self {
+    Self {
+      blocks: result.blocks.into_iter().map(Into::into).collect(),
+      title: result.title,
+      summary: result.summary,
+    }
+  }
+}
+
+/// Same as `NativeBatchCrawlResult`, with the result interned.
+#[napi(object)]
+pub struct NativeInternedBatchResult {
+  pub doc_id: String,
+  pub result: Option<NativeInternedCrawlResult>,
+  pub error: Option<String>,
+  pub diagnostics: Option<NativeDiagnosticBundle>,
+}
+
+#[napi(object)]
+pub struct NativeInternedBatch {
+  /// Every string of the results once, indexed by their integer fields.
+  pub strings: Vec<String>,
+  pub results: Vec<NativeInternedBatchResult>,
+}
+
+pub struct AsyncParseDocsFromBinaryBatchInterned {
+  docs: Vec<(String, Vec<u8>)>,
+  diagnostics: bool,
+}
+
+#[napi]
+impl Task for AsyncParseDocsFromBinaryBatchInterned {
+  type Output = (Vec<String>, Vec<BatchInternedOutput>);
+  type JsValue = NativeInternedBatch;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let mut interner = StringInterner::default();
+    let results = crawl_batch(std::mem::take(&mut self.docs), self.diagnostics)
+      .into_iter()
+      .map(|(doc_id, result, bundle)| {
+        let result = result.map(|result| interner.intern_crawl_result(&result));
+        (doc_id, result, bundle)
+      })
+      .collect();
+    Ok((interner.into_strings(), results))
+  }
+
+  fn resolve(&mut self, _: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let (strings, output) = output;
+    Ok(NativeInternedBatch {
+      strings,
+      results: output
+        .into_iter()
+        .map(|(doc_id, result, bundle)| match result {
+          Ok(result) => NativeInternedBatchResult {
+            doc_id,
+            result: Some(result.into()),
+            error: None,
+            diagnostics: None,
+          },
+          Err(e) => NativeInternedBatchResult {
+            doc_id,
+            result: None,
+            error: Some(e.to_string()),
+            diagnostics: bundle.map(Into::into),
+          },
+        })
+        .collect(),
+    })
+  }
+}
+
+type BatchInternedOutput = (
+  String,
+  std::result::Result<InternedCrawlResult, ParseError>,
+  Option<DiagnosticBundle>,
+);
+
+/// Same as `parseDocsFromBinaryBatch`, with every string of the results
+/// stored once in `strings` and referenced by index. Flavours, extras and
+/// doc ids repeat across a workspace, interning them keeps large batches
+/// small.
+#[napi]
+pub fn parse_docs_from_binary_batch_interned(
+  docs: Vec<NativeWorkspaceDoc>,
+  diagnostics: Option<bool>,
+) -> AsyncTask<AsyncParseDocsFromBinaryBatchInterned> {
+  AsyncTask::new(AsyncParseDocsFromBinaryBatchInterned {
+    docs: docs
+      .into_iter()
+      .map(|doc| (doc.doc_id, doc.doc_bin.to_vec()))
+      .collect(),
+    diagnostics: diagnostics.unwrap_or(false),
+  })
 }
 
 /// Converts a serialized document into a markdown result exposed to JavaScript.
@@ -144,6 +813,8 @@ pub fn parse_doc_from_binary(doc_bin: Buffer, doc_id: String) -> Result<NativeCr
 /// # Parameters
 ///
 /// * `ai_editable` - When `Some(true)`, request AI-editable markdown formatting; when `None` or `Some(false)`, return standard markdown.
+/// * `hooks` - Optional callbacks transforming blocks before and output after rendering.
+/// * `encoding` - `msgpack` or `json` to get the result serialized in a `Buffer`, `objects` by default.
 ///
 /// # Returns
 ///
@@ -156,22 +827,296 @@ pub fn parse_doc_from_binary(doc_bin: Buffer, doc_id: String) -> Result<NativeCr
 ///
 /// // Binary document bytes (example)
 /// let doc_bin = Buffer::from(vec![/* ... document bytes ... */]);
-/// let result = parse_doc_to_markdown(doc_bin, "doc-id-123".to_string(), None).unwrap();
-/// println!("{}", result.title);
-/// println!("{}", result.markdown);
+/// let result = parse_doc_to_markdown(doc_bin, "doc-id-123".to_string(), None, None, None).unwrap();
+/// if let Either::A(result) = result {
+///   println!("{}", result.title);
+///   println!("{}", result.markdown);
+/// }
 /// ```
 #[napi]
 pub fn parse_doc_to_markdown(
+  env: Env,
+  doc_bin: Buffer,
+  doc_id: String,
+  ai_editable: Option<bool>,
+  hooks: Option<ExportHooks>,
+  encoding: Option<String>,
+) -> DocResult<Either<NativeMarkdownResult, Buffer>> {
+  let encoding = ResultEncoding::parse(encoding).map_err(status_error)?;
+  let middleware = hooks.as_ref().map(JsExportMiddleware::new);
+  let result = with_flavour_registry(&env, |handlers| {
+    doc_parser::parse_doc_to_markdown_with_options(
+      doc_bin.into(),
+      doc_id,
+      &MarkdownOptions {
+        ai_editable: ai_editable.unwrap_or(false),
+        handlers: Some(handlers),
+        middleware: middleware
+          .as_ref()
+          .map(|middleware| middleware as &dyn ExportMiddleware),
+        ..Default::default()
+      },
+    )
+  })
+  .map_err(doc_error)?;
+  Ok(
+    encoding
+      .encode(result)
+      .map_err(status_error)?
+      .into_js(Into::into),
+  )
+}
+
+pub struct AsyncParseDocToMarkdown {
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  ai_editable: bool,
+  encoding: ResultEncoding,
+}
+
+#[napi]
+impl Task for AsyncParseDocToMarkdown {
+  type Output = DocResult<Encoded<MarkdownResult>>;
+  type JsValue = Either<NativeMarkdownResult, Buffer>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(
+      doc_parser::parse_doc_to_markdown(
+        std::mem::take(&mut self.doc_bin),
+        std::mem::take(&mut self.doc_id),
+        self.ai_editable,
+      )
+      .map_err(doc_error)
+      .and_then(|result| self.encoding.encode(result).map_err(status_error)),
+    )
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into_js(Into::into))
+  }
+}
+
+/// Same as `parse_doc_to_markdown`, on a worker thread so large docs do not
+/// block the event loop. Registered flavour handlers and export hooks are not
+/// applied, they can only run on the JS thread.
+#[napi]
+pub fn parse_doc_to_markdown_async(
+  doc_bin: Buffer,
+  doc_id: String,
+  ai_editable: Option<bool>,
+  encoding: Option<String>,
+) -> DocResult<AsyncTask<AsyncParseDocToMarkdown>> {
+  Ok(AsyncTask::new(AsyncParseDocToMarkdown {
+    doc_bin: doc_bin.to_vec(),
+    doc_id,
+    ai_editable: ai_editable.unwrap_or(false),
+    encoding: ResultEncoding::parse(encoding).map_err(status_error)?,
+  }))
+}
+
+/// Renders only `block_id` and its descendants, e.g. the selection the AI
+/// copilot works on, without rendering the rest of the doc. Fails when the
+/// doc has no such block.
+#[napi]
+pub fn parse_block_to_markdown(
+  env: Env,
   doc_bin: Buffer,
   doc_id: String,
+  block_id: String,
   ai_editable: Option<bool>,
-) -> Result<NativeMarkdownResult> {
-  let result =
-    doc_parser::parse_doc_to_markdown(doc_bin.into(), doc_id, ai_editable.unwrap_or(false))
-      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+) -> DocResult<NativeMarkdownResult> {
+  let result = with_flavour_registry(&env, |handlers| {
+    doc_parser::parse_subtree_to_markdown_with_options(
+      doc_bin.into(),
+      doc_id,
+      &block_id,
+      &MarkdownOptions {
+        ai_editable: ai_editable.unwrap_or(false),
+        handlers: Some(handlers),
+        ..Default::default()
+      },
+    )
+  })
+  .map_err(doc_error)?;
   Ok(result.into())
 }
 
+#[napi(object)]
+pub struct NativeHtmlResult {
+  pub title: String,
+  pub html: String,
+  pub unsupported: Vec<NativeUnsupportedBlock>,
+}
+
+impl From<HtmlResult> for NativeHtmlResult {
+  fn from(result: HtmlResult) -> Self {
+    Self {
+      title: result.title,
+      html: result.html,
+      unsupported: result.unsupported.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Renders a doc into sanitized HTML directly, keeping callouts, databases,
+/// nested lists and inline references that are lost when converting the
+/// markdown export. Images and attachments link to `blob://<blobId>` for the
+/// caller to resolve, and doc references link to `doc_link_template` with
+/// `{docId}` substituted when it is set.
+#[napi]
+pub fn parse_doc_to_html(
+  doc_bin: Buffer,
+  doc_id: String,
+  doc_link_template: Option<String>,
+  encoding: Option<String>,
+) -> DocResult<Either<NativeHtmlResult, Buffer>> {
+  let encoding = ResultEncoding::parse(encoding).map_err(status_error)?;
+  let result = doc_parser::parse_doc_to_html(
+    doc_bin.into(),
+    doc_id,
+    &MarkdownOptions {
+      doc_link_template,
+      blobs: BlobHandling::Reference,
+      ..Default::default()
+    },
+  )
+  .map_err(doc_error)?;
+  Ok(
+    encoding
+      .encode(result)
+      .map_err(status_error)?
+      .into_js(Into::into),
+  )
+}
+
+pub struct AsyncParseDocToHtml {
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  doc_link_template: Option<String>,
+  encoding: ResultEncoding,
+}
+
+#[napi]
+impl Task for AsyncParseDocToHtml {
+  type Output = DocResult<Encoded<HtmlResult>>;
+  type JsValue = Either<NativeHtmlResult, Buffer>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(
+      doc_parser::parse_doc_to_html(
+        std::mem::take(&mut self.doc_bin),
+        std::mem::take(&mut self.doc_id),
+        &MarkdownOptions {
+          doc_link_template: self.doc_link_template.take(),
+          blobs: BlobHandling::Reference,
+          ..Default::default()
+        },
+      )
+      .map_err(doc_error)
+      .and_then(|result| self.encoding.encode(result).map_err(status_error)),
+    )
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into_js(Into::into))
+  }
+}
+
+/// Same as `parse_doc_to_html`, on a worker thread so large docs do not block
+/// the event loop.
+#[napi]
+pub fn parse_doc_to_html_async(
+  doc_bin: Buffer,
+  doc_id: String,
+  doc_link_template: Option<String>,
+  encoding: Option<String>,
+) -> DocResult<AsyncTask<AsyncParseDocToHtml>> {
+  Ok(AsyncTask::new(AsyncParseDocToHtml {
+    doc_bin: doc_bin.to_vec(),
+    doc_id,
+    doc_link_template,
+    encoding: ResultEncoding::parse(encoding).map_err(status_error)?,
+  }))
+}
+
+#[napi(object)]
+pub struct NativeTextBlock {
+  pub block_id: String,
+  pub flavour: String,
+  pub text: String,
+  /// Byte offset of the block's text in `text` of the result.
+  pub start: u32,
+  /// Byte offset just past the block's text in `text` of the result.
+  pub end: u32,
+}
+
+impl From<TextBlock> for NativeTextBlock {
+  fn from(block: TextBlock) -> Self {
+    Self {
+      block_id: block.block_id,
+      flavour: block.flavour,
+      text: block.text,
+      start: block.start as u32,
+      end: block.end as u32,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeTextResult {
+  pub title: String,
+  pub text: String,
+  pub blocks: Vec<NativeTextBlock>,
+}
+
+impl From<TextResult> for NativeTextResult {
+  fn from(result: TextResult) -> Self {
+    Self {
+      title: result.title,
+      text: result.text,
+      blocks: result.blocks.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Extracts the plain text of every block for search indexing, without the
+/// markdown syntax an export would add. Offsets count UTF-8 bytes of `text`,
+/// use `Buffer.byteLength` on the JS side to map matches back to blocks.
+#[napi]
+pub fn parse_doc_to_text(
+  doc_bin: Buffer,
+  doc_id: String,
+  encoding: Option<String>,
+) -> DocResult<Either<NativeTextResult, Buffer>> {
+  let encoding = ResultEncoding::parse(encoding).map_err(status_error)?;
+  let result = doc_parser::parse_doc_to_text(doc_bin.into(), doc_id).map_err(doc_error)?;
+  Ok(
+    encoding
+      .encode(result)
+      .map_err(status_error)?
+      .into_js(Into::into),
+  )
+}
+
+/// Returns the nested block tree of a doc as a JSON string, each block with
+/// its `blockId`, `flavour`, `props` and `children` in order, to rebuild
+/// outlines and tables of contents that the flat crawl result loses.
+#[napi]
+pub fn parse_doc_to_json(doc_bin: Buffer, doc_id: String) -> DocResult<String> {
+  let tree = doc_parser::parse_doc_to_json(doc_bin.into(), doc_id).map_err(doc_error)?;
+  serde_json::to_string(&tree).map_err(|e| Error::new(NativeDocError::Unknown, e.to_string()))
+}
+
+/// Returns the title of a doc without indexing its blocks, unset when the
+/// doc has no page block. Cheaper than `parse_doc_from_binary` for listings
+/// that only show titles.
+#[napi]
+pub fn read_doc_title(doc_bin: Buffer, doc_id: String) -> DocResult<Option<String>> {
+  doc_parser::read_doc_title(&doc_bin, &doc_id).map_err(doc_error)
+}
+
 /// Extracts all document IDs contained in a binary root document.
 ///
 /// If `include_trash` is `true`, IDs for trashed documents are included; otherwise trashed IDs are excluded.
@@ -196,8 +1141,170 @@ pub fn parse_doc_to_markdown(
 pub fn read_all_doc_ids_from_root_doc(
   doc_bin: Buffer,
   include_trash: Option<bool>,
-) -> Result<Vec<String>> {
+) -> DocResult<Vec<String>> {
   let result = doc_parser::get_doc_ids_from_binary(doc_bin.into(), include_trash.unwrap_or(false))
-    .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    .map_err(doc_error)?;
   Ok(result)
-}
\ No newline at end of file
+}
+
+pub struct AsyncReadAllDocIdsFromRootDoc {
+  doc_bin: Vec<u8>,
+  include_trash: bool,
+}
+
+#[napi]
+impl Task for AsyncReadAllDocIdsFromRootDoc {
+  type Output = DocResult<Vec<String>>;
+  type JsValue = Vec<String>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(
+      doc_parser::get_doc_ids_from_binary(std::mem::take(&mut self.doc_bin), self.include_trash)
+        .map_err(doc_error),
+    )
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output.map_err(|e| task_error(env, e))
+  }
+}
+
+/// Same as `read_all_doc_ids_from_root_doc`, on a worker thread so large
+/// workspaces do not block the event loop.
+#[napi]
+pub fn read_all_doc_ids_from_root_doc_async(
+  doc_bin: Buffer,
+  include_trash: Option<bool>,
+) -> AsyncTask<AsyncReadAllDocIdsFromRootDoc> {
+  AsyncTask::new(AsyncReadAllDocIdsFromRootDoc {
+    doc_bin: doc_bin.to_vec(),
+    include_trash: include_trash.unwrap_or(false),
+  })
+}
+
+/// Listing metadata of a doc, as the workspace root doc records it.
+#[napi(object)]
+pub struct NativeDocMeta {
+  pub doc_id: String,
+  pub title: String,
+  /// Milliseconds since the Unix epoch.
+  pub create_date: Option<i64>,
+  /// Milliseconds since the Unix epoch.
+  pub updated_date: Option<i64>,
+  /// Tag ids, in the order of the doc.
+  pub tags: Vec<String>,
+  pub favorite: bool,
+  pub trash: bool,
+  /// Id of the folder the doc is organized in, only known from the folders
+  /// doc.
+  pub parent_folder_id: Option<String>,
+}
+
+impl From<DocMeta> for NativeDocMeta {
+  fn from(meta: DocMeta) -> Self {
+    Self {
+      doc_id: meta.doc_id,
+      title: meta.title,
+      create_date: meta.create_date,
+      updated_date: meta.updated_date,
+      tags: meta.tags,
+      favorite: meta.favorite,
+      trash: meta.trash,
+      parent_folder_id: meta.parent_folder_id,
+    }
+  }
+}
+
+/// Reads the title, dates, tags, favorite and trash flags of every doc listed
+/// in a workspace root doc, trashed ones included, so listings do not parse
+/// each doc. Pass the workspace folders doc (`db$folders`) as
+/// `folders_doc_bin` to get the folder of each doc too.
+#[napi]
+pub fn parse_root_doc_meta(
+  doc_bin: Buffer,
+  folders_doc_bin: Option<Buffer>,
+) -> DocResult<Vec<NativeDocMeta>> {
+  doc_parser::parse_root_doc_meta(doc_bin.into(), folders_doc_bin.map(Into::into))
+    .map(|metas| metas.into_iter().map(Into::into).collect())
+    .map_err(doc_error)
+}
+
+#[napi(object)]
+pub struct NativeFolderNode {
+  pub id: String,
+  /// `folder`, `doc`, `tag` or `collection`.
+  pub kind: String,
+  /// Id of the doc, tag or collection the node links to, unset for folders.
+  pub target_id: Option<String>,
+  /// Name of the folder, or title of the doc, tag or collection it links to.
+  pub name: String,
+  pub index: String,
+  pub children: Vec<NativeFolderNode>,
+}
+
+impl From<FolderNode> for NativeFolderNode {
+  fn from(node: FolderNode) -> Self {
+    Self {
+      id: node.id,
+      kind: node.kind,
+      target_id: node.target_id,
+      name: node.name,
+      index: node.index,
+      children: node.children.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeCollectionInfo {
+  pub id: String,
+  pub name: String,
+  /// Docs added to the collection by hand, besides the ones its rules match.
+  pub allow_list: Vec<String>,
+}
+
+impl From<CollectionInfo> for NativeCollectionInfo {
+  fn from(collection: CollectionInfo) -> Self {
+    Self {
+      id: collection.id,
+      name: collection.name,
+      allow_list: collection.allow_list,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeFolderTree {
+  /// Top-level folders in their order.
+  pub folders: Vec<NativeFolderNode>,
+  pub collections: Vec<NativeCollectionInfo>,
+}
+
+impl From<FolderTree> for NativeFolderTree {
+  fn from(tree: FolderTree) -> Self {
+    Self {
+      folders: tree.folders.into_iter().map(Into::into).collect(),
+      collections: tree.collections.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Reads how the docs of a workspace are organized: its folders, in their
+/// order and with the docs, tags and collections they hold, and its
+/// collections. Folders live in the workspace folders doc (`db$folders`),
+/// without `folders_doc_bin` only collections are returned.
+#[napi]
+pub fn read_folder_tree_from_root_doc(
+  doc_bin: Buffer,
+  folders_doc_bin: Option<Buffer>,
+) -> DocResult<NativeFolderTree> {
+  doc_parser::read_folder_tree_from_root_doc(doc_bin.into(), folders_doc_bin.map(Into::into))
+    .map(Into::into)
+    .map_err(doc_error)
+}
+
+#[napi(object)]
+pub struct NativeWorkspaceDoc {
+  pub doc_id: String,
+  pub doc_bin: Buffer,
+}
@@ -0,0 +1,123 @@
+use std::fmt::Display;
+
+use affine_common::doc_parser::ParseError;
+use napi::{Env, Error, JsError, Status};
+use napi_derive::napi;
+
+/// Stable codes of the errors thrown by doc functions, set as the `code` of
+/// the JavaScript error so callers can branch on them. The message keeps the
+/// details.
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeDocError {
+  #[napi(value = "doc_not_found")]
+  DocNotFound,
+  #[napi(value = "block_not_found")]
+  BlockNotFound,
+  #[napi(value = "invalid_binary")]
+  InvalidBinary,
+  #[napi(value = "sqlite_error")]
+  SqliteError,
+  #[napi(value = "parser_error")]
+  ParserError,
+  /// Also used for docs written with a newer block schema.
+  #[napi(value = "unsupported_format")]
+  UnsupportedFormat,
+  #[napi(value = "preset_not_found")]
+  PresetNotFound,
+  /// Rejected arguments or options JSON.
+  #[napi(value = "invalid_options")]
+  InvalidOptions,
+  #[napi(value = "unknown")]
+  Unknown,
+}
+
+impl NativeDocError {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      NativeDocError::DocNotFound => "doc_not_found",
+      NativeDocError::BlockNotFound => "block_not_found",
+      NativeDocError::InvalidBinary => "invalid_binary",
+      NativeDocError::SqliteError => "sqlite_error",
+      NativeDocError::ParserError => "parser_error",
+      NativeDocError::UnsupportedFormat => "unsupported_format",
+      NativeDocError::PresetNotFound => "preset_not_found",
+      NativeDocError::InvalidOptions => "invalid_options",
+      NativeDocError::Unknown => "unknown",
+    }
+  }
+}
+
+impl AsRef<str> for NativeDocError {
+  fn as_ref(&self) -> &str {
+    self.as_str()
+  }
+}
+
+impl From<&ParseError> for NativeDocError {
+  fn from(error: &ParseError) -> Self {
+    match error {
+      ParseError::DocNotFound => NativeDocError::DocNotFound,
+      ParseError::BlockNotFound(_) => NativeDocError::BlockNotFound,
+      ParseError::InvalidBinary => NativeDocError::InvalidBinary,
+      ParseError::SqliteError(_) => NativeDocError::SqliteError,
+      ParseError::ParserError(_) => NativeDocError::ParserError,
+      ParseError::UnsupportedFormat(_) => NativeDocError::UnsupportedFormat,
+      ParseError::PresetNotFound(_) => NativeDocError::PresetNotFound,
+      ParseError::InvalidOptions(_) => NativeDocError::InvalidOptions,
+      ParseError::Unknown(_) => NativeDocError::Unknown,
+    }
+  }
+}
+
+/// Result of the doc functions, rejected with a [`NativeDocError`] code.
+pub type DocResult<T> = napi::Result<T, NativeDocError>;
+
+pub(crate) fn doc_error(error: ParseError) -> Error<NativeDocError> {
+  Error::new(NativeDocError::from(&error), error.to_string())
+}
+
+/// Rejects an argument the caller got wrong.
+pub(crate) fn options_error(error: impl Display) -> Error<NativeDocError> {
+  Error::new(NativeDocError::InvalidOptions, error.to_string())
+}
+
+/// Carries over an error of a helper that still reports a [`Status`].
+pub(crate) fn status_error(error: Error) -> Error<NativeDocError> {
+  let code = match error.status {
+    Status::InvalidArg => NativeDocError::InvalidOptions,
+    _ => NativeDocError::Unknown,
+  };
+  Error::new(code, error.reason.clone())
+}
+
+/// Rejects an async task with a [`NativeDocError`] code. `Task` only fails
+/// with a [`Status`], so tasks compute a [`DocResult`] and reject from
+/// `resolve`, where the JavaScript error is made with its code.
+pub(crate) fn task_error(env: Env, error: Error<NativeDocError>) -> Error {
+  JsError::from(error).into_unknown(env).into()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_doc_error_codes() {
+    for error in [
+      ParseError::DocNotFound,
+      ParseError::BlockNotFound("b".into()),
+      ParseError::InvalidBinary,
+      ParseError::SqliteError("s".into()),
+      ParseError::ParserError("p".into()),
+      ParseError::UnsupportedFormat("u".into()),
+      ParseError::PresetNotFound("p".into()),
+      ParseError::InvalidOptions("o".into()),
+      ParseError::Unknown("u".into()),
+    ] {
+      let code = NativeDocError::from(&error);
+      assert_eq!(code.as_str(), error.code());
+      assert_eq!(doc_error(error).status, code);
+    }
+  }
+}
@@ -1,10 +1,16 @@
-use affine_common::doc_loader::Doc;
+use affine_common::{
+  doc_loader::{import_files as load_files, preflight_files, Doc, ImportCheckpoint, ImportFolder},
+  progress::{Progress, ProgressSink},
+};
 use napi::{
   anyhow::anyhow,
   bindgen_prelude::{AsyncTask, Buffer},
-  Env, Result, Task,
+  threadsafe_function::ThreadsafeFunction,
+  Env, Error, Result, Status, Task,
 };
 
+use crate::progress::{JsProgressSink, NativeProgress};
+
 #[napi(object)]
 pub struct Chunk {
   pub index: i64,
@@ -18,34 +24,46 @@ pub struct ParsedDoc {
 }
 
 pub struct Document {
-  inner: Doc,
+  name: String,
+  chunks: Vec<Chunk>,
 }
 
 impl Document {
-  fn name(&self) -> String {
-    self.inner.name.clone()
-  }
-
-  fn chunks(&self) -> Vec<Chunk> {
-    self
-      .inner
+  /// Cleans up the chunks of a loaded doc, reporting every cleaned chunk
+  /// under the `"chunk"` stage.
+  fn new(doc: Doc, progress: Option<&dyn ProgressSink>) -> Self {
+    let total = doc.chunks.len();
+    let chunks = doc
       .chunks
       .iter()
       .enumerate()
       .map(|(i, chunk)| {
         let content = crate::utils::clean_content(&chunk.content);
+        if let Some(progress) = progress {
+          progress.report(Progress {
+            stage: "chunk",
+            done: i + 1,
+            total,
+          });
+        }
         Chunk {
           index: i as i64,
           content,
         }
       })
-      .collect::<Vec<Chunk>>()
+      .collect::<Vec<Chunk>>();
+
+    Self {
+      name: doc.name,
+      chunks,
+    }
   }
 }
 
 pub struct AsyncParseDocResponse {
   file_path: String,
   doc: Vec<u8>,
+  progress: Option<JsProgressSink>,
 }
 
 #[napi]
@@ -54,22 +72,218 @@ impl Task for AsyncParseDocResponse {
   type JsValue = ParsedDoc;
 
   fn compute(&mut self) -> Result<Self::Output> {
+    let progress = self.progress.as_ref().map(|sink| sink as &dyn ProgressSink);
+    let report = |done| {
+      if let Some(progress) = progress {
+        progress.report(Progress {
+          stage: "load",
+          done,
+          total: 1,
+        });
+      }
+    };
+
+    report(0);
     let doc = Doc::new(&self.file_path, &self.doc).map_err(|e| anyhow!(e))?;
-    Ok(Document { inner: doc })
+    report(1);
+    Ok(Document::new(doc, progress))
   }
 
   fn resolve(&mut self, _: Env, doc: Document) -> Result<Self::JsValue> {
     Ok(ParsedDoc {
-      name: doc.name(),
-      chunks: doc.chunks(),
+      name: doc.name,
+      chunks: doc.chunks,
     })
   }
 }
 
+/// Loads a file and splits it into chunks on a worker thread.
+///
+/// `on_progress` is called with the `"load"` stage around loading the file
+/// and with the `"chunk"` stage after every chunk is cleaned up.
 #[napi]
-pub fn parse_doc(file_path: String, doc: Buffer) -> AsyncTask<AsyncParseDocResponse> {
+pub fn parse_doc(
+  file_path: String,
+  doc: Buffer,
+  on_progress: Option<ThreadsafeFunction<NativeProgress, ()>>,
+) -> AsyncTask<AsyncParseDocResponse> {
   AsyncTask::new(AsyncParseDocResponse {
     file_path,
     doc: doc.to_vec(),
+    progress: on_progress.map(JsProgressSink),
+  })
+}
+
+#[napi(object)]
+pub struct ImportFile {
+  pub file_path: String,
+  pub content: Buffer,
+}
+
+#[napi(object)]
+pub struct ImportFailure {
+  pub file_path: String,
+  pub error: String,
+}
+
+#[napi(object)]
+pub struct ImportBatch {
+  /// Docs loaded by this batch, in input order.
+  pub docs: Vec<ParsedDoc>,
+  pub failed: Vec<ImportFailure>,
+  /// Opaque checkpoint to persist and pass to the next batch.
+  pub checkpoint: Buffer,
+}
+
+pub struct ImportedBatch {
+  docs: Vec<Document>,
+  failed: Vec<ImportFailure>,
+  checkpoint: Vec<u8>,
+}
+
+pub struct AsyncImportFiles {
+  files: Vec<(String, Vec<u8>)>,
+  checkpoint: ImportCheckpoint,
+  progress: Option<JsProgressSink>,
+}
+
+#[napi]
+impl Task for AsyncImportFiles {
+  type Output = ImportedBatch;
+  type JsValue = ImportBatch;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let progress = self.progress.as_ref().map(|sink| sink as &dyn ProgressSink);
+    let mut batch = ImportedBatch {
+      docs: vec![],
+      failed: vec![],
+      checkpoint: vec![],
+    };
+    for file in load_files(
+      std::mem::take(&mut self.files),
+      &mut self.checkpoint,
+      progress,
+    ) {
+      match file.doc {
+        Ok(doc) => batch.docs.push(Document::new(doc, None)),
+        Err(e) => batch.failed.push(ImportFailure {
+          file_path: file.file_path,
+          error: e.to_string(),
+        }),
+      }
+    }
+    batch.checkpoint = self.checkpoint.to_bytes();
+    Ok(batch)
+  }
+
+  fn resolve(&mut self, _: Env, batch: ImportedBatch) -> Result<Self::JsValue> {
+    Ok(ImportBatch {
+      docs: batch
+        .docs
+        .into_iter()
+        .map(|doc| ParsedDoc {
+          name: doc.name,
+          chunks: doc.chunks,
+        })
+        .collect(),
+      failed: batch.failed,
+      checkpoint: batch.checkpoint.into(),
+    })
+  }
+}
+
+/// Loads a batch of files of a long running import on a worker thread.
+///
+/// Files recorded in `checkpoint` are skipped. Persist the returned
+/// checkpoint after every batch; after an interruption, restart the import
+/// from its first batch with the last persisted checkpoint and the files
+/// already imported are skipped. `on_progress` is called with the `"import"`
+/// stage after every file.
+#[napi]
+pub fn import_files(
+  files: Vec<ImportFile>,
+  checkpoint: Option<Buffer>,
+  on_progress: Option<ThreadsafeFunction<NativeProgress, ()>>,
+) -> Result<AsyncTask<AsyncImportFiles>> {
+  let checkpoint = match checkpoint {
+    Some(bytes) => ImportCheckpoint::from_bytes(&bytes)
+      .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?,
+    None => ImportCheckpoint::new(),
+  };
+  Ok(AsyncTask::new(AsyncImportFiles {
+    files: files
+      .into_iter()
+      .map(|file| (file.file_path, file.content.to_vec()))
+      .collect(),
+    checkpoint,
+    progress: on_progress.map(JsProgressSink),
+  }))
+}
+
+#[napi(object)]
+pub struct PreflightFolder {
+  pub name: String,
+  pub doc_count: u32,
+  pub children: Vec<PreflightFolder>,
+}
+
+impl From<ImportFolder> for PreflightFolder {
+  fn from(folder: ImportFolder) -> Self {
+    Self {
+      name: folder.name,
+      doc_count: folder.doc_count as u32,
+      children: folder.children.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+#[napi(object)]
+pub struct PreflightAttachment {
+  pub file_path: String,
+  pub size: u32,
+}
+
+#[napi(object)]
+pub struct ImportPreflightReport {
+  pub doc_count: u32,
+  /// Files the checkpoint records as already imported.
+  pub skipped_count: u32,
+  pub root: PreflightFolder,
+  pub attachments: Vec<PreflightAttachment>,
+  /// Paths of files no loader handles.
+  pub unsupported: Vec<String>,
+}
+
+/// Dry run of `import_files`: reports the docs, folders, attachments and
+/// unsupported files a batch would produce without loading any of them.
+#[napi]
+pub fn preflight_import_files(
+  files: Vec<ImportFile>,
+  checkpoint: Option<Buffer>,
+) -> Result<ImportPreflightReport> {
+  let checkpoint = match checkpoint {
+    Some(bytes) => ImportCheckpoint::from_bytes(&bytes)
+      .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?,
+    None => ImportCheckpoint::new(),
+  };
+  let files = files
+    .into_iter()
+    .map(|file| (file.file_path, file.content.to_vec()))
+    .collect::<Vec<_>>();
+  let preflight = preflight_files(&files, &checkpoint);
+
+  Ok(ImportPreflightReport {
+    doc_count: preflight.doc_count as u32,
+    skipped_count: preflight.skipped_count as u32,
+    root: preflight.root.into(),
+    attachments: preflight
+      .attachments
+      .into_iter()
+      .map(|(file_path, size)| PreflightAttachment {
+        file_path,
+        size: size as u32,
+      })
+      .collect(),
+    unsupported: preflight.unsupported,
   })
 }
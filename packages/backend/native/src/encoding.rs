@@ -0,0 +1,93 @@
+use napi::{bindgen_prelude::*, Error, Result, Status};
+use serde::Serialize;
+
+/// How a parse or extract function hands its result to JS, from the
+/// optional `encoding` argument.
+///
+/// `objects` builds napi objects as before. `msgpack` and `json` serialize
+/// the Rust result into a single `Buffer` instead, skipping the construction
+/// of an object per field for consumers that forward or store the result.
+/// Serialized results keep the Rust field names, in snake case.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum ResultEncoding {
+  #[default]
+  Objects,
+  Msgpack,
+  Json,
+}
+
+impl ResultEncoding {
+  pub(crate) fn parse(encoding: Option<String>) -> Result<Self> {
+    match encoding.as_deref() {
+      None | Some("objects") => Ok(Self::Objects),
+      Some("msgpack") => Ok(Self::Msgpack),
+      Some("json") => Ok(Self::Json),
+      Some(other) => Err(Error::new(
+        Status::InvalidArg,
+        format!("unknown encoding: {other}"),
+      )),
+    }
+  }
+
+  pub(crate) fn encode<T: Serialize>(self, result: T) -> Result<Encoded<T>> {
+    let encoded = match self {
+      Self::Objects => return Ok(Encoded::Objects(result)),
+      Self::Msgpack => rmp_serde::to_vec_named(&result).map_err(|e| e.to_string()),
+      Self::Json => serde_json::to_vec(&result).map_err(|e| e.to_string()),
+    };
+    encoded
+      .map(Encoded::Bytes)
+      .map_err(|e| Error::new(Status::GenericFailure, e))
+  }
+}
+
+/// A result encoded by [`ResultEncoding::encode`], still to be converted
+/// into its napi type when it stayed an object.
+pub(crate) enum Encoded<T> {
+  Objects(T),
+  Bytes(Vec<u8>),
+}
+
+impl<T> Encoded<T> {
+  pub(crate) fn into_js<N>(self, convert: impl FnOnce(T) -> N) -> Either<N, Buffer> {
+    match self {
+      Self::Objects(result) => Either::A(convert(result)),
+      Self::Bytes(bytes) => Either::B(bytes.into()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[derive(Serialize)]
+  struct Sample {
+    block_id: &'static str,
+  }
+
+  #[test]
+  fn test_encode_result() {
+    let sample = || Sample { block_id: "b1" };
+
+    let json = ResultEncoding::parse(Some("json".into()))
+      .unwrap()
+      .encode(sample())
+      .unwrap();
+    assert!(matches!(json, Encoded::Bytes(bytes) if bytes == br#"{"block_id":"b1"}"#));
+
+    let msgpack = ResultEncoding::parse(Some("msgpack".into()))
+      .unwrap()
+      .encode(sample())
+      .unwrap();
+    assert!(
+      matches!(msgpack, Encoded::Bytes(bytes) if bytes == rmp_serde::to_vec_named(&sample()).unwrap())
+    );
+
+    assert_eq!(
+      ResultEncoding::parse(None).unwrap(),
+      ResultEncoding::Objects
+    );
+    assert!(ResultEncoding::parse(Some("xml".into())).is_err());
+  }
+}
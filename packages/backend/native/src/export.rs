@@ -0,0 +1,363 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, RwLock},
+};
+
+use affine_common::{
+  doc_parser::{
+    self, BlockFilter, ExportFormat, ExportResult, ExportSizeEstimate, FlavourFidelity,
+    MarkdownOptions, ParseError, PresetStore, RoundtripReport,
+  },
+  progress::ProgressSink,
+};
+use napi::{bindgen_prelude::*, threadsafe_function::ThreadsafeFunction, Env, Task};
+use napi_derive::napi;
+
+use crate::{
+  doc::{NativeUnsupportedBlock, NativeWorkspaceDoc},
+  doc_error::{doc_error, options_error, status_error, task_error, DocResult, NativeDocError},
+  flavour_registry::with_flavour_registry,
+  progress::{JsProgressSink, NativeProgress},
+};
+
+/// Export presets by workspace id.
+static PRESETS: LazyLock<RwLock<HashMap<String, PresetStore>>> = LazyLock::new(Default::default);
+
+#[napi(object)]
+pub struct NativeFlavourFidelity {
+  pub flavour: String,
+  pub total: u32,
+  pub preserved: u32,
+  pub degraded: u32,
+  pub lost: u32,
+}
+
+impl From<FlavourFidelity> for NativeFlavourFidelity {
+  fn from(fidelity: FlavourFidelity) -> Self {
+    Self {
+      flavour: fidelity.flavour,
+      total: fidelity.total as u32,
+      preserved: fidelity.preserved as u32,
+      degraded: fidelity.degraded as u32,
+      lost: fidelity.lost as u32,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeRoundtripReport {
+  pub format: String,
+  pub flavours: Vec<NativeFlavourFidelity>,
+  pub lossless: bool,
+}
+
+impl From<RoundtripReport> for NativeRoundtripReport {
+  fn from(report: RoundtripReport) -> Self {
+    Self {
+      format: report.format.to_string(),
+      flavours: report.flavours.into_iter().map(Into::into).collect(),
+      lossless: report.lossless,
+    }
+  }
+}
+
+/// Exports a document to `format`, re-imports the output and reports the
+/// information lost per block flavour.
+///
+/// `format` currently accepts `"markdown"`; unknown formats are rejected with
+/// `invalid_options`.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
+/// let report = roundtrip_check(doc_bin, "doc-id".to_string(), "markdown".to_string()).unwrap();
+/// println!("lossless: {}", report.lossless);
+/// ```
+#[napi]
+pub fn roundtrip_check(
+  doc_bin: Buffer,
+  doc_id: String,
+  format: String,
+) -> DocResult<NativeRoundtripReport> {
+  let format = format.parse::<ExportFormat>().map_err(options_error)?;
+  let result = doc_parser::roundtrip_check(doc_bin.into(), doc_id, format).map_err(doc_error)?;
+  Ok(result.into())
+}
+
+#[napi(object)]
+pub struct NativeExportResult {
+  pub title: String,
+  pub format: String,
+  pub content: String,
+  pub unsupported: Vec<NativeUnsupportedBlock>,
+}
+
+impl From<ExportResult> for NativeExportResult {
+  fn from(result: ExportResult) -> Self {
+    Self {
+      title: result.title,
+      format: result.format.to_string(),
+      content: result.content,
+      unsupported: result.unsupported.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Registers a named export preset of a workspace from its JSON definition
+/// and returns the preset name. Defining a preset with an existing name
+/// replaces it, presets of other workspaces are left alone.
+///
+/// Invalid definitions are rejected with `invalid_options`.
+///
+/// # Examples
+///
+/// ```
+/// let json = r#"{ "name": "publish", "frontmatter": true }"#;
+/// let name = define_preset("ws".to_string(), json.to_string()).unwrap();
+/// assert_eq!(name, "publish");
+/// ```
+#[napi]
+pub fn define_preset(workspace_id: String, json: String) -> DocResult<String> {
+  let mut presets = PRESETS
+    .write()
+    .map_err(|e| Error::new(NativeDocError::Unknown, e.to_string()))?;
+  let preset = presets
+    .entry(workspace_id)
+    .or_default()
+    .define(&json)
+    .map_err(options_error)?;
+  Ok(preset.name)
+}
+
+/// Validates the JSON of an option object, e.g. `kind = "preset"`, failing
+/// with `invalid_options` and a message naming the offending field.
+#[napi]
+pub fn validate_options(kind: String, json: String) -> DocResult<()> {
+  doc_parser::validate_options(&kind, &json).map_err(options_error)
+}
+
+/// Removes a named export preset of a workspace, returning whether it
+/// existed.
+#[napi]
+pub fn remove_preset(workspace_id: String, name: String) -> bool {
+  PRESETS
+    .write()
+    .ok()
+    .and_then(|mut presets| Some(presets.get_mut(&workspace_id)?.remove(&name)))
+    .unwrap_or(false)
+}
+
+/// Exports a document with the options bundled in a named preset of its
+/// workspace.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
+/// let result = export_with_preset(
+///   doc_bin,
+///   "doc-id".to_string(),
+///   "ws".to_string(),
+///   "publish".to_string(),
+/// )
+/// .unwrap();
+/// println!("{}", result.content);
+/// ```
+#[napi]
+pub fn export_with_preset(
+  env: Env,
+  doc_bin: Buffer,
+  doc_id: String,
+  workspace_id: String,
+  preset_name: String,
+) -> DocResult<NativeExportResult> {
+  let presets = PRESETS
+    .read()
+    .map_err(|e| Error::new(NativeDocError::Unknown, e.to_string()))?;
+  let store = presets
+    .get(&workspace_id)
+    .ok_or_else(|| doc_error(ParseError::PresetNotFound(preset_name.clone())))?;
+  let result = with_flavour_registry(&env, |handlers| {
+    store.export_with_handlers(doc_bin.into(), doc_id, &preset_name, Some(handlers))
+  })
+  .map_err(doc_error)?;
+  Ok(result.into())
+}
+
+/// Renders a single block and its descendants to `format`, keeping the doc
+/// title.
+///
+/// `format` currently accepts `"markdown"`; unknown formats are rejected with
+/// `invalid_options`. `filter_json` lists blocks to leave out, see
+/// `validateOptions("filter", ..)`.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
+/// let section = export_block_subtree(
+///   doc_bin,
+///   "doc-id".to_string(),
+///   "block-id".to_string(),
+///   "markdown".to_string(),
+///   Some(r#"{ "denyFlavours": ["affine:code"] }"#.to_string()),
+/// )
+/// .unwrap();
+/// println!("{}", section.content);
+/// ```
+#[napi]
+pub fn export_block_subtree(
+  env: Env,
+  doc_bin: Buffer,
+  doc_id: String,
+  block_id: String,
+  format: String,
+  filter_json: Option<String>,
+) -> DocResult<NativeExportResult> {
+  let format = format.parse::<ExportFormat>().map_err(options_error)?;
+  let filter = parse_filter(filter_json).map_err(status_error)?;
+  let result = with_flavour_registry(&env, |handlers| {
+    doc_parser::export_block_subtree_with_options(
+      doc_bin.into(),
+      doc_id,
+      &block_id,
+      format,
+      &MarkdownOptions {
+        handlers: Some(handlers),
+        filter,
+        ..Default::default()
+      },
+    )
+  })
+  .map_err(doc_error)?;
+  Ok(result.into())
+}
+
+#[napi(object)]
+pub struct NativeExportSizeEstimate {
+  pub format: String,
+  pub content_bytes: u32,
+  /// Can exceed `u32` for docs with many large attachments.
+  pub blob_bytes: f64,
+  pub blob_count: u32,
+}
+
+impl From<ExportSizeEstimate> for NativeExportSizeEstimate {
+  fn from(estimate: ExportSizeEstimate) -> Self {
+    Self {
+      format: estimate.format.as_str().to_string(),
+      content_bytes: estimate.content_bytes as u32,
+      blob_bytes: estimate.blob_bytes as f64,
+      blob_count: estimate.blob_count as u32,
+    }
+  }
+}
+
+/// Estimates the output and blob sizes of an export without rendering it.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
+/// let estimate =
+///   estimate_export_size(doc_bin, "doc-id".to_string(), "markdown".to_string()).unwrap();
+/// ```
+#[napi]
+pub fn estimate_export_size(
+  doc_bin: Buffer,
+  doc_id: String,
+  format: String,
+) -> DocResult<NativeExportSizeEstimate> {
+  let format = format.parse::<ExportFormat>().map_err(options_error)?;
+  let result =
+    doc_parser::estimate_export_size(doc_bin.into(), doc_id, format).map_err(doc_error)?;
+  Ok(result.into())
+}
+
+pub struct AsyncExportWorkspace {
+  docs: Vec<(String, Vec<u8>)>,
+  format: ExportFormat,
+  filter: Option<BlockFilter>,
+  progress: Option<JsProgressSink>,
+}
+
+#[napi]
+impl Task for AsyncExportWorkspace {
+  type Output = DocResult<Vec<ExportResult>>;
+  type JsValue = Vec<NativeExportResult>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(
+      doc_parser::export_docs(
+        std::mem::take(&mut self.docs),
+        self.format,
+        &MarkdownOptions {
+          filter: self.filter.clone(),
+          ..Default::default()
+        },
+        self.progress.as_ref().map(|sink| sink as &dyn ProgressSink),
+      )
+      .map_err(doc_error),
+    )
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into_iter().map(Into::into).collect())
+  }
+}
+
+/// Exports several docs on a worker thread, e.g. a whole workspace.
+///
+/// Registered flavour handlers are not applied, they can only run on the JS
+/// thread. `on_progress` is called with the `"export"` stage after every doc.
+/// `filter_json` lists blocks to leave out of every doc, see
+/// `validateOptions("filter", ..)`.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let docs = vec![NativeWorkspaceDoc {
+///   doc_id: "doc-id".to_string(),
+///   doc_bin: Buffer::from(vec![/* ...document bytes... */]),
+/// }];
+/// let task = export_workspace(docs, "markdown".to_string(), None, None).unwrap();
+/// ```
+#[napi]
+pub fn export_workspace(
+  docs: Vec<NativeWorkspaceDoc>,
+  format: String,
+  on_progress: Option<ThreadsafeFunction<NativeProgress, ()>>,
+  filter_json: Option<String>,
+) -> DocResult<AsyncTask<AsyncExportWorkspace>> {
+  let format = format.parse::<ExportFormat>().map_err(options_error)?;
+  let filter = parse_filter(filter_json).map_err(status_error)?;
+  Ok(AsyncTask::new(AsyncExportWorkspace {
+    docs: docs
+      .into_iter()
+      .map(|doc| (doc.doc_id, doc.doc_bin.to_vec()))
+      .collect(),
+    format,
+    filter,
+    progress: on_progress.map(JsProgressSink),
+  }))
+}
+
+pub(crate) fn parse_filter(filter_json: Option<String>) -> Result<Option<BlockFilter>> {
+  filter_json
+    .map(|json| {
+      BlockFilter::from_json(&json).map_err(|e| Error::new(Status::InvalidArg, e.to_string()))
+    })
+    .transpose()
+}
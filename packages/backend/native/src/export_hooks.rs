@@ -0,0 +1,47 @@
+use affine_common::doc_parser::{BlockSnapshot, ExportMiddleware};
+use napi::bindgen_prelude::Function;
+use napi_derive::napi;
+
+use crate::doc::NativeBlockSnapshot;
+
+/// Callbacks run around the export pipeline.
+///
+/// `onBlock` receives every block before it is rendered and returns the block
+/// to render in its place, or `null`/`undefined` to skip it. `onOutput`
+/// receives the rendered output of every block and returns the text to emit.
+/// A hook that throws leaves its input unchanged.
+#[napi(object, object_to_js = false)]
+pub struct ExportHooks<'env> {
+  pub on_block: Option<Function<'env, NativeBlockSnapshot, Option<NativeBlockSnapshot>>>,
+  pub on_output: Option<Function<'env, String, String>>,
+}
+
+pub(crate) struct JsExportMiddleware<'a, 'env> {
+  hooks: &'a ExportHooks<'env>,
+}
+
+impl<'a, 'env> JsExportMiddleware<'a, 'env> {
+  pub(crate) fn new(hooks: &'a ExportHooks<'env>) -> Self {
+    Self { hooks }
+  }
+}
+
+impl ExportMiddleware for JsExportMiddleware<'_, '_> {
+  fn on_block(&self, block: BlockSnapshot) -> Option<BlockSnapshot> {
+    let Some(on_block) = &self.hooks.on_block else {
+      return Some(block);
+    };
+    match on_block.call((&block).into()) {
+      Ok(Some(transformed)) => Some(BlockSnapshot::try_from(transformed).unwrap_or(block)),
+      Ok(None) => None,
+      Err(_) => Some(block),
+    }
+  }
+
+  fn on_output(&self, chunk: String) -> String {
+    match &self.hooks.on_output {
+      Some(on_output) => on_output.call(chunk.clone()).unwrap_or(chunk),
+      None => chunk,
+    }
+  }
+}
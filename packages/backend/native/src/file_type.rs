@@ -17,6 +17,106 @@ pub fn get_mime(input: &[u8]) -> String {
   }
 }
 
+#[napi(object)]
+pub struct NativeBlobType {
+  pub mime: String,
+  /// Pixel width, for images whose header could be read.
+  pub width: Option<u32>,
+  /// Pixel height, for images whose header could be read.
+  pub height: Option<u32>,
+}
+
+/// Detects the mime type of a blob from its magic bytes and, for PNG, JPEG,
+/// GIF, WebP and BMP images, reads the dimensions from the image header.
+#[napi]
+pub fn sniff_blob_type(input: &[u8]) -> NativeBlobType {
+  let mime = get_mime(input);
+  let dimensions = image_dimensions(&mime, input);
+  NativeBlobType {
+    mime,
+    width: dimensions.map(|(width, _)| width),
+    height: dimensions.map(|(_, height)| height),
+  }
+}
+
+/// Reads `(width, height)` from the header of an image of type `mime`.
+pub(crate) fn image_dimensions(mime: &str, input: &[u8]) -> Option<(u32, u32)> {
+  match mime {
+    "image/png" => png_dimensions(input),
+    "image/jpeg" => jpeg_dimensions(input),
+    "image/gif" => gif_dimensions(input),
+    "image/webp" => webp_dimensions(input),
+    "image/bmp" => bmp_dimensions(input),
+    _ => None,
+  }
+}
+
+fn u16_be(input: &[u8], at: usize) -> Option<u32> {
+  let bytes = input.get(at..at + 2)?;
+  Some(u16::from_be_bytes([bytes[0], bytes[1]]) as u32)
+}
+
+fn u16_le(input: &[u8], at: usize) -> Option<u32> {
+  let bytes = input.get(at..at + 2)?;
+  Some(u16::from_le_bytes([bytes[0], bytes[1]]) as u32)
+}
+
+fn u24_le(input: &[u8], at: usize) -> Option<u32> {
+  let bytes = input.get(at..at + 3)?;
+  Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]))
+}
+
+fn u32_be(input: &[u8], at: usize) -> Option<u32> {
+  let bytes = input.get(at..at + 4)?;
+  Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+fn png_dimensions(input: &[u8]) -> Option<(u32, u32)> {
+  // the IHDR chunk always comes first, right after the signature
+  if input.get(12..16)? != b"IHDR" {
+    return None;
+  }
+  Some((u32_be(input, 16)?, u32_be(input, 20)?))
+}
+
+fn gif_dimensions(input: &[u8]) -> Option<(u32, u32)> {
+  Some((u16_le(input, 6)?, u16_le(input, 8)?))
+}
+
+fn bmp_dimensions(input: &[u8]) -> Option<(u32, u32)> {
+  let width = i32::from_le_bytes(input.get(18..22)?.try_into().ok()?);
+  // negative heights mark top-down bitmaps
+  let height = i32::from_le_bytes(input.get(22..26)?.try_into().ok()?);
+  Some((width.unsigned_abs(), height.unsigned_abs()))
+}
+
+fn webp_dimensions(input: &[u8]) -> Option<(u32, u32)> {
+  match input.get(12..16)? {
+    b"VP8 " => Some((u16_le(input, 26)? & 0x3fff, u16_le(input, 28)? & 0x3fff)),
+    b"VP8L" => {
+      let bits = u32::from_le_bytes(input.get(21..25)?.try_into().ok()?);
+      Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+    }
+    b"VP8X" => Some((u24_le(input, 24)? + 1, u24_le(input, 27)? + 1)),
+    _ => None,
+  }
+}
+
+fn jpeg_dimensions(input: &[u8]) -> Option<(u32, u32)> {
+  let mut at = 2;
+  loop {
+    if *input.get(at)? != 0xff {
+      return None;
+    }
+    let marker = *input.get(at + 1)?;
+    // start of frame markers, except DHT, JPG and DAC which share the range
+    if (0xc0..=0xcf).contains(&marker) && !matches!(marker, 0xc4 | 0xc8 | 0xcc) {
+      return Some((u16_be(input, at + 7)?, u16_be(input, at + 5)?));
+    }
+    at += 2 + u16_be(input, at + 2)? as usize;
+  }
+}
+
 fn detect_mp4_flavor(input: &[u8]) -> String {
   let mut cursor = std::io::Cursor::new(input);
   match read_mp4(&mut cursor) {
@@ -39,3 +139,35 @@ fn detect_mp4_flavor(input: &[u8]) -> String {
     Err(_) => "video/mp4".to_string(),
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sniff_image_dimensions() {
+    let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    png.extend(640u32.to_be_bytes());
+    png.extend(480u32.to_be_bytes());
+    png.extend([8, 6, 0, 0, 0]);
+    let sniffed = sniff_blob_type(&png);
+    assert_eq!(sniffed.mime, "image/png");
+    assert_eq!((sniffed.width, sniffed.height), (Some(640), Some(480)));
+
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend(32u16.to_le_bytes());
+    gif.extend(16u16.to_le_bytes());
+    gif.extend([0, 0, 0]);
+    let sniffed = sniff_blob_type(&gif);
+    assert_eq!(sniffed.mime, "image/gif");
+    assert_eq!((sniffed.width, sniffed.height), (Some(32), Some(16)));
+
+    let mut jpeg = vec![0xff, 0xd8, 0xff, 0xe0, 0, 4, 0, 0, 0xff, 0xc0, 0, 11, 8];
+    jpeg.extend(100u16.to_be_bytes());
+    jpeg.extend(200u16.to_be_bytes());
+    assert_eq!(jpeg_dimensions(&jpeg), Some((200, 100)));
+
+    let sniffed = sniff_blob_type(b"plain text");
+    assert_eq!((sniffed.width, sniffed.height), (None, None));
+  }
+}
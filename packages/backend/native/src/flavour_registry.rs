@@ -0,0 +1,104 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use affine_common::doc_parser::{
+  is_builtin_flavour, BlockSnapshot, FlavourHandler, FlavourRegistry,
+};
+use napi::{
+  bindgen_prelude::{Function, FunctionRef},
+  Env, Error, Result, Status,
+};
+use napi_derive::napi;
+
+use crate::doc::NativeBlockSnapshot;
+
+#[napi(object, object_to_js = false)]
+pub struct FlavourHandlers<'env> {
+  pub crawl: Option<Function<'env, NativeBlockSnapshot, Option<Vec<String>>>>,
+  pub markdown: Option<Function<'env, NativeBlockSnapshot, Option<String>>>,
+  pub html: Option<Function<'env, NativeBlockSnapshot, Option<String>>>,
+}
+
+struct JsFlavourHandlers {
+  crawl: Option<FunctionRef<NativeBlockSnapshot, Option<Vec<String>>>>,
+  markdown: Option<FunctionRef<NativeBlockSnapshot, Option<String>>>,
+  html: Option<FunctionRef<NativeBlockSnapshot, Option<String>>>,
+}
+
+thread_local! {
+  static FLAVOUR_HANDLERS: RefCell<HashMap<String, Rc<JsFlavourHandlers>>> =
+    RefCell::new(HashMap::new());
+}
+
+/// Calls back into JavaScript for a registered flavour. Parsing runs on the JS
+/// thread, so the stored function references can be borrowed back directly.
+struct JsFlavourHandler<'env> {
+  env: &'env Env,
+  handlers: Rc<JsFlavourHandlers>,
+}
+
+impl FlavourHandler for JsFlavourHandler<'_> {
+  fn crawl(&self, block: &BlockSnapshot) -> Option<Vec<String>> {
+    let callback = self.handlers.crawl.as_ref()?.borrow_back(self.env).ok()?;
+    callback.call(block.into()).ok().flatten()
+  }
+
+  fn markdown(&self, block: &BlockSnapshot) -> Option<String> {
+    let callback = self
+      .handlers
+      .markdown
+      .as_ref()?
+      .borrow_back(self.env)
+      .ok()?;
+    callback.call(block.into()).ok().flatten()
+  }
+
+  fn html(&self, block: &BlockSnapshot) -> Option<String> {
+    let callback = self.handlers.html.as_ref()?.borrow_back(self.env).ok()?;
+    callback.call(block.into()).ok().flatten()
+  }
+}
+
+/// Runs `f` with a registry containing every handler registered from
+/// JavaScript on the current thread.
+pub(crate) fn with_flavour_registry<T>(env: &Env, f: impl FnOnce(&FlavourRegistry) -> T) -> T {
+  let handlers = FLAVOUR_HANDLERS.with(|handlers| handlers.borrow().clone());
+  let mut registry = FlavourRegistry::default();
+  for (flavour, handlers) in handlers {
+    registry.register(flavour, JsFlavourHandler { env, handlers });
+  }
+  f(&registry)
+}
+
+/// Registers crawl/markdown/HTML callbacks for a proprietary block flavour.
+///
+/// Each callback receives the block with its props encoded as JSON and
+/// returns `null`/`undefined` to leave the block out of that output.
+/// Registering a flavour again replaces its previous callbacks.
+///
+/// Fails with `Status::InvalidArg` for flavours the parser handles natively.
+#[napi]
+pub fn register_flavour_handler(flavour: String, handlers: FlavourHandlers) -> Result<()> {
+  if is_builtin_flavour(&flavour) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("cannot override built-in flavour {flavour}"),
+    ));
+  }
+
+  let handlers = JsFlavourHandlers {
+    crawl: handlers.crawl.map(|f| f.create_ref()).transpose()?,
+    markdown: handlers.markdown.map(|f| f.create_ref()).transpose()?,
+    html: handlers.html.map(|f| f.create_ref()).transpose()?,
+  };
+  FLAVOUR_HANDLERS.with(|registry| {
+    registry.borrow_mut().insert(flavour, Rc::new(handlers));
+  });
+  Ok(())
+}
+
+/// Removes the callbacks registered for `flavour`, returning whether any were
+/// registered.
+#[napi]
+pub fn unregister_flavour_handler(flavour: String) -> bool {
+  FLAVOUR_HANDLERS.with(|registry| registry.borrow_mut().remove(&flavour).is_some())
+}
@@ -0,0 +1,114 @@
+use affine_common::grounding::{
+  self, GroundedSentence, GroundingCitation, GroundingOptions, GroundingReport,
+};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::context_pack::NativeContextChunk;
+
+#[napi(object)]
+pub struct NativeGroundingOptions {
+  /// Lowest score, between 0 and 1, a chunk needs to support a sentence.
+  /// Defaults to 0.5.
+  pub threshold: Option<f64>,
+  /// Citations kept per sentence. Defaults to 3.
+  pub max_citations: Option<u32>,
+}
+
+#[napi(object)]
+pub struct NativeGroundingCitation {
+  pub doc_id: String,
+  pub block_id: Option<String>,
+  /// Position of the chunk in the input.
+  pub chunk_index: u32,
+  pub score: f64,
+}
+
+impl From<GroundingCitation> for NativeGroundingCitation {
+  fn from(citation: GroundingCitation) -> Self {
+    Self {
+      doc_id: citation.doc_id,
+      block_id: citation.block_id,
+      chunk_index: citation.chunk_index as u32,
+      score: citation.score,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeGroundedSentence {
+  /// Offsets in the answer, in UTF-16 units.
+  pub start: u32,
+  pub end: u32,
+  pub text: String,
+  /// Best score of any chunk.
+  pub score: f64,
+  pub supported: bool,
+  /// Chunks supporting the sentence, best first.
+  pub citations: Vec<NativeGroundingCitation>,
+}
+
+impl From<GroundedSentence> for NativeGroundedSentence {
+  fn from(sentence: GroundedSentence) -> Self {
+    Self {
+      start: sentence.start as u32,
+      end: sentence.end as u32,
+      text: sentence.text,
+      score: sentence.score,
+      supported: sentence.supported,
+      citations: sentence.citations.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeGroundingReport {
+  pub sentences: Vec<NativeGroundedSentence>,
+  /// Share of the sentences with content words that are supported.
+  pub supported_ratio: f64,
+}
+
+impl From<GroundingReport> for NativeGroundingReport {
+  fn from(report: GroundingReport) -> Self {
+    Self {
+      sentences: report.sentences.into_iter().map(Into::into).collect(),
+      supported_ratio: report.supported_ratio,
+    }
+  }
+}
+
+/// Checks which sentences of a generated answer are supported by the source
+/// chunks, by word and word pair overlap, and returns per-sentence citations
+/// for showing sources. Sentences are checked independently, so a streaming
+/// answer can be verified again as it grows.
+///
+/// Fails with `Status::InvalidArg` for a threshold outside of 0 to 1.
+#[napi]
+pub fn verify_grounding(
+  answer_text: String,
+  source_chunks: Vec<NativeContextChunk>,
+  options: Option<NativeGroundingOptions>,
+) -> Result<NativeGroundingReport> {
+  let defaults = GroundingOptions::default();
+  let options = match options {
+    Some(options) => GroundingOptions {
+      threshold: options.threshold.unwrap_or(defaults.threshold),
+      max_citations: options
+        .max_citations
+        .map_or(defaults.max_citations, |max| max as usize),
+    },
+    None => defaults,
+  };
+  if !(0.0..=1.0).contains(&options.threshold) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "threshold must be between 0 and 1",
+    ));
+  }
+
+  let chunks = source_chunks
+    .into_iter()
+    .map(Into::into)
+    .collect::<Vec<_>>();
+  Ok(grounding::verify_grounding(&answer_text, &chunks, &options).into())
+}
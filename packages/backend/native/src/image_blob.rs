@@ -0,0 +1,267 @@
+use napi::{bindgen_prelude::*, Error, Result, Status};
+use napi_derive::napi;
+
+use crate::file_type::{get_mime, image_dimensions};
+
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+const ORIENTATION_TAG: u16 = 0x0112;
+const GPS_IFD_TAG: u16 = 0x8825;
+const PNG_METADATA_CHUNKS: [&[u8]; 5] = [b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+#[napi(object)]
+pub struct ProcessImageOptions {
+  /// Remove EXIF, XMP and text metadata from the returned copy. Defaults to
+  /// true.
+  pub strip_metadata: Option<bool>,
+}
+
+#[napi(object)]
+pub struct NativeProcessedImage {
+  pub mime: String,
+  pub width: Option<u32>,
+  pub height: Option<u32>,
+  /// EXIF orientation from 1 to 8, when the image records one.
+  pub orientation: Option<u32>,
+  /// Whether the image carries GPS coordinates.
+  pub has_gps: bool,
+  /// Whether metadata was removed from `data`. Only JPEG, PNG and WebP images
+  /// can be stripped, other images are returned unchanged.
+  pub stripped: bool,
+  pub data: Buffer,
+}
+
+/// A copy of an image without its metadata, along with the TIFF structured
+/// EXIF block found in the original.
+struct Stripped<'a> {
+  exif: Option<&'a [u8]>,
+  data: Vec<u8>,
+}
+
+/// Reads the dimensions and EXIF orientation of an image blob and returns a
+/// copy without its metadata, so location and device details never reach
+/// storage.
+///
+/// Stripping is lossless: the JPEG segments, PNG chunks and WebP chunks
+/// holding metadata are dropped and everything else, color profiles
+/// included, is copied as is.
+///
+/// Fails with `Status::InvalidArg` when the blob is not an image or its
+/// structure is damaged.
+#[napi]
+pub fn process_image_blob(
+  input: Buffer,
+  options: Option<ProcessImageOptions>,
+) -> Result<NativeProcessedImage> {
+  let input = input.as_ref();
+  let mime = get_mime(input);
+  if !mime.starts_with("image/") {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("not an image: {mime}"),
+    ));
+  }
+  let strip_metadata = options
+    .and_then(|options| options.strip_metadata)
+    .unwrap_or(true);
+
+  let strip: Option<fn(&[u8]) -> Option<Stripped<'_>>> = match mime.as_str() {
+    "image/jpeg" => Some(strip_jpeg),
+    "image/png" => Some(strip_png),
+    "image/webp" => Some(strip_webp),
+    _ => None,
+  };
+  let stripped = strip
+    .map(|strip| {
+      strip(input).ok_or_else(|| Error::new(Status::InvalidArg, format!("malformed {mime} image")))
+    })
+    .transpose()?;
+
+  let (orientation, has_gps) = stripped
+    .as_ref()
+    .and_then(|stripped| stripped.exif)
+    .and_then(read_exif)
+    .unwrap_or_default();
+  let dimensions = image_dimensions(&mime, input);
+  let (stripped, data) = match stripped {
+    Some(stripped) if strip_metadata => (true, stripped.data),
+    _ => (false, input.to_vec()),
+  };
+
+  Ok(NativeProcessedImage {
+    mime,
+    width: dimensions.map(|(width, _)| width),
+    height: dimensions.map(|(_, height)| height),
+    orientation,
+    has_gps,
+    stripped,
+    data: data.into(),
+  })
+}
+
+fn strip_jpeg(input: &[u8]) -> Option<Stripped<'_>> {
+  let mut data = input.get(..2).filter(|soi| *soi == b"\xff\xd8")?.to_vec();
+  let mut exif = None;
+  let mut at = 2;
+  loop {
+    if *input.get(at)? != 0xff {
+      return None;
+    }
+    let marker = *input.get(at + 1)?;
+    // fill byte before the actual marker
+    if marker == 0xff {
+      at += 1;
+      continue;
+    }
+    // from the start of scan on there is only image data
+    if marker == 0xda || marker == 0xd9 {
+      data.extend_from_slice(&input[at..]);
+      break;
+    }
+    let len = u16::from_be_bytes(input.get(at + 2..at + 4)?.try_into().ok()?) as usize;
+    let segment = input.get(at..at + 2 + len)?;
+    match marker {
+      // APP1 holds EXIF and XMP, APP13 IPTC and COM free text comments
+      0xe1 | 0xed | 0xfe => {
+        if marker == 0xe1 && exif.is_none() {
+          exif = segment.get(4..)?.strip_prefix(EXIF_HEADER);
+        }
+      }
+      _ => data.extend_from_slice(segment),
+    }
+    at += 2 + len;
+  }
+  Some(Stripped { exif, data })
+}
+
+fn strip_png(input: &[u8]) -> Option<Stripped<'_>> {
+  let mut data = input.get(..8)?.to_vec();
+  let mut exif = None;
+  let mut at = 8;
+  while at < input.len() {
+    let len = u32::from_be_bytes(input.get(at..at + 4)?.try_into().ok()?) as usize;
+    // length, type, data and crc
+    let chunk = input.get(at..at + 12 + len)?;
+    let kind = &chunk[4..8];
+    if kind == b"eXIf" {
+      exif = Some(&chunk[8..8 + len]);
+    }
+    if !PNG_METADATA_CHUNKS.contains(&kind) {
+      data.extend_from_slice(chunk);
+    }
+    at += 12 + len;
+  }
+  Some(Stripped { exif, data })
+}
+
+fn strip_webp(input: &[u8]) -> Option<Stripped<'_>> {
+  if input.get(..4)? != b"RIFF" || input.get(8..12)? != b"WEBP" {
+    return None;
+  }
+  let mut data = b"RIFF\0\0\0\0WEBP".to_vec();
+  let mut exif = None;
+  let mut at = 12;
+  while at < input.len() {
+    let kind = input.get(at..at + 4)?;
+    let len = u32::from_le_bytes(input.get(at + 4..at + 8)?.try_into().ok()?) as usize;
+    // chunks are padded to an even size, some encoders leave out the padding
+    // of the last one
+    let end = at + 8 + len + len % 2;
+    let chunk = input.get(at..end.min(input.len()))?;
+    match kind {
+      b"EXIF" => {
+        let block = chunk.get(8..8 + len)?;
+        exif = Some(block.strip_prefix(EXIF_HEADER).unwrap_or(block));
+      }
+      b"XMP " => {}
+      _ => {
+        let start = data.len();
+        data.extend_from_slice(chunk);
+        if kind == b"VP8X" {
+          // clear the EXIF and XMP flags
+          *data.get_mut(start + 8)? &= !0x0c;
+        }
+      }
+    }
+    at = end;
+  }
+  let riff_len = u32::try_from(data.len() - 8).ok()?;
+  data[4..8].copy_from_slice(&riff_len.to_le_bytes());
+  Some(Stripped { exif, data })
+}
+
+/// Reads the orientation and whether there are GPS coordinates from the
+/// first IFD of a TIFF structured EXIF block.
+fn read_exif(tiff: &[u8]) -> Option<(Option<u32>, bool)> {
+  let big_endian = match tiff.get(..2)? {
+    b"II" => false,
+    b"MM" => true,
+    _ => return None,
+  };
+  let u16_at = |at: usize| -> Option<u16> {
+    let bytes = tiff.get(at..at + 2)?.try_into().ok()?;
+    Some(if big_endian {
+      u16::from_be_bytes(bytes)
+    } else {
+      u16::from_le_bytes(bytes)
+    })
+  };
+  let u32_at = |at: usize| -> Option<u32> {
+    let bytes = tiff.get(at..at + 4)?.try_into().ok()?;
+    Some(if big_endian {
+      u32::from_be_bytes(bytes)
+    } else {
+      u32::from_le_bytes(bytes)
+    })
+  };
+
+  let ifd = u32_at(4)? as usize;
+  let mut orientation = None;
+  let mut has_gps = false;
+  for entry in 0..u16_at(ifd)? as usize {
+    let at = ifd + 2 + entry * 12;
+    match u16_at(at)? {
+      ORIENTATION_TAG => {
+        orientation = u16_at(at + 8)
+          .map(u32::from)
+          .filter(|orientation| (1..=8).contains(orientation));
+      }
+      GPS_IFD_TAG => has_gps = true,
+      _ => {}
+    }
+  }
+  Some((orientation, has_gps))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_strip_jpeg_exif() {
+    // little endian TIFF with orientation 6 and a GPS IFD pointer
+    let mut tiff = b"II\x2a\0\x08\0\0\0\x02\0".to_vec();
+    tiff.extend([0x12, 0x01, 3, 0, 1, 0, 0, 0, 6, 0, 0, 0]);
+    tiff.extend([0x25, 0x88, 4, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+    tiff.extend([0, 0, 0, 0]);
+    let mut app1 = EXIF_HEADER.to_vec();
+    app1.extend(&tiff);
+
+    let mut jpeg = vec![0xff, 0xd8, 0xff, 0xe0, 0, 4, 0, 0, 0xff, 0xe1];
+    jpeg.extend((app1.len() as u16 + 2).to_be_bytes());
+    jpeg.extend(&app1);
+    jpeg.extend([0xff, 0xc0, 0, 11, 8, 0, 100, 0, 200, 1, 1, 0x11, 0]);
+    jpeg.extend([0xff, 0xda, 1, 2, 3, 0xff, 0xd9]);
+
+    let stripped = strip_jpeg(&jpeg).unwrap();
+    assert_eq!(stripped.exif, Some(tiff.as_slice()));
+    assert_eq!(read_exif(&tiff), Some((Some(6), true)));
+    assert_eq!(stripped.data.len(), jpeg.len() - app1.len() - 4);
+    assert!(!stripped.data.windows(4).any(|window| window == b"Exif"));
+    assert_eq!(
+      image_dimensions("image/jpeg", &stripped.data),
+      Some((200, 100))
+    );
+
+    assert!(strip_jpeg(&jpeg[..12]).is_none());
+  }
+}
@@ -1,18 +1,84 @@
+//! Native helpers of the server.
+//!
+//! # Worker threads
+//!
+//! The module can be loaded by any number of Node `worker_threads`. Every
+//! worker gets its own exports, and class instances belong to the worker that
+//! created them, they cannot be sent with `postMessage`. State is shared as
+//! follows:
+//!
+//! - `VectorIndex.shared` opens one index per name for the whole process,
+//!   locked internally so all workers can query and write it.
+//! - Export presets from `definePreset`, kept per workspace id, and
+//!   `setDeterministicMode` are process-wide.
+//! - Flavour handlers from `registerFlavourHandler` are per worker, as they
+//!   call into the JS of the worker that registered them.
+//! - The log subscriber from `setLogSubscriber` is process-wide, events of
+//!   every worker go to the callback of the last call.
+//! - `AutocompleteIndex`, `Vocabulary`, `Tokenizer`, `BlobHasher`,
+//!   `DocCrawler`, `WorkspaceDir`, `SearchIndex` and unshared `VectorIndex`es
+//!   hold no shared state and are cheap to recreate per worker from the same
+//!   inputs, from `toBytes` for vector indexes or the saved file for search
+//!   indexes.
+//!
+//! Every class is `Send + Sync`, so async tasks can build and hand them over
+//! from the libuv thread pool.
+
 #![deny(clippy::all)]
 
+mod encoding;
 mod utils;
 
+pub mod activity;
+pub mod analytics;
+pub mod attachments;
+pub mod autocomplete;
+pub mod blob_hash;
+pub mod chunking;
+pub mod classify;
+pub mod context_pack;
+pub mod crawl_hooks;
+pub mod database;
+pub mod delta;
+pub mod deterministic;
 pub mod doc;
+pub mod doc_error;
 pub mod doc_loader;
+pub mod export;
+pub mod export_hooks;
 pub mod file_type;
+pub mod flavour_registry;
+pub mod grounding;
 pub mod hashcash;
 pub mod html_sanitize;
+pub mod image_blob;
+pub mod lists;
+pub mod logging;
+pub mod progress;
+pub mod publish;
+pub mod ranking;
+pub mod references;
+pub mod reindex;
+pub mod retention;
+pub mod scrub;
+pub mod search_index;
+pub mod search_query;
+pub mod snapshot;
+pub mod snippets;
+pub mod spelling;
+pub mod structure;
+pub mod template;
+pub mod thumbnail;
 pub mod tiktoken;
+pub mod transform;
+pub mod validation;
+pub mod vector_index;
+pub mod workspace_dir;
 
 use std::fmt::{Debug, Display};
 
+use affine_common::doc_parser;
 use napi::{bindgen_prelude::*, Error, Result, Status};
-use y_octo::Doc;
 
 #[cfg(not(target_arch = "arm"))]
 #[global_allocator]
@@ -44,12 +110,8 @@ macro_rules! map_err {
 /// result binary.
 #[napi(catch_unwind)]
 pub fn merge_updates_in_apply_way(updates: Vec<Buffer>) -> Result<Buffer> {
-  let mut doc = Doc::default();
-  for update in updates {
-    map_err!(doc.apply_update_from_binary_v1(update.as_ref()))?;
-  }
-
-  let buf = map_err!(doc.encode_update_v1())?;
+  let updates = updates.into_iter().map(Into::into).collect();
+  let buf = map_err!(doc_parser::merge_updates(updates))?;
 
   Ok(buf.into())
 }
@@ -60,3 +122,39 @@ pub const AFFINE_PRO_PUBLIC_KEY: Option<&'static str> = std::option_env!("AFFINE
 #[napi]
 pub const AFFINE_PRO_LICENSE_AES_KEY: Option<&'static str> =
   std::option_env!("AFFINE_PRO_LICENSE_AES_KEY");
+
+#[cfg(test)]
+mod tests {
+  use std::{env, fs, path::Path};
+
+  use affine_common::doc_parser::typescript_definitions;
+
+  use super::*;
+
+  fn assert_send_sync<T: Send + Sync>() {}
+
+  #[test]
+  fn test_classes_are_send_sync() {
+    assert_send_sync::<autocomplete::AutocompleteIndex>();
+    assert_send_sync::<blob_hash::BlobHasher>();
+    assert_send_sync::<doc::DocCrawler>();
+    assert_send_sync::<spelling::Vocabulary>();
+    assert_send_sync::<tiktoken::Tokenizer>();
+    assert_send_sync::<vector_index::VectorIndex>();
+  }
+
+  /// `types.d.ts` is checked in, run with `UPDATE_TYPES=1` to regenerate it.
+  #[test]
+  fn test_types_up_to_date() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("types.d.ts");
+    let definitions = typescript_definitions();
+    if env::var_os("UPDATE_TYPES").is_some() {
+      fs::write(&path, &definitions).unwrap();
+    }
+    assert_eq!(
+      fs::read_to_string(&path).unwrap(),
+      definitions,
+      "types.d.ts is outdated, run the tests with UPDATE_TYPES=1"
+    );
+  }
+}
@@ -0,0 +1,96 @@
+use affine_common::doc_parser::{self, ChecklistProgress, ListProgress, NormalizedLists};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::doc_error::{doc_error, DocResult};
+
+#[napi(object)]
+pub struct NativeListProgress {
+  pub first_block_id: String,
+  pub parent_id: String,
+  pub total: u32,
+  pub checked: u32,
+  pub ratio: f64,
+}
+
+impl From<ListProgress> for NativeListProgress {
+  fn from(list: ListProgress) -> Self {
+    Self {
+      first_block_id: list.first_block_id,
+      parent_id: list.parent_id,
+      total: list.total as u32,
+      checked: list.checked as u32,
+      ratio: list.ratio,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeChecklistProgress {
+  pub total: u32,
+  pub checked: u32,
+  pub ratio: f64,
+  pub lists: Vec<NativeListProgress>,
+}
+
+impl From<ChecklistProgress> for NativeChecklistProgress {
+  fn from(progress: ChecklistProgress) -> Self {
+    Self {
+      total: progress.total as u32,
+      checked: progress.checked as u32,
+      ratio: progress.ratio,
+      lists: progress.lists.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Counts the checked todo items of a doc, per list and in total.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
+/// let progress = compute_checklist_progress(doc_bin, "doc-id".to_string()).unwrap();
+/// ```
+#[napi]
+pub fn compute_checklist_progress(
+  doc_bin: Buffer,
+  doc_id: String,
+) -> DocResult<NativeChecklistProgress> {
+  let result = doc_parser::compute_checklist_progress(doc_bin.into(), doc_id).map_err(doc_error)?;
+  Ok(result.into())
+}
+
+#[napi(object)]
+pub struct NativeNormalizedLists {
+  pub update: Buffer,
+  pub fixed_block_ids: Vec<String>,
+}
+
+impl From<NormalizedLists> for NativeNormalizedLists {
+  fn from(normalized: NormalizedLists) -> Self {
+    Self {
+      update: normalized.update.into(),
+      fixed_block_ids: normalized.fixed_block_ids,
+    }
+  }
+}
+
+/// Repairs orphaned, duplicated, mistyped and misnumbered list items and
+/// returns the changes as an update for the doc.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
+/// let normalized = normalize_lists(doc_bin, "doc-id".to_string()).unwrap();
+/// ```
+#[napi]
+pub fn normalize_lists(doc_bin: Buffer, doc_id: String) -> DocResult<NativeNormalizedLists> {
+  let result = doc_parser::normalize_lists(doc_bin.into(), doc_id).map_err(doc_error)?;
+  Ok(result.into())
+}
@@ -0,0 +1,229 @@
+use std::{
+  collections::HashMap,
+  fmt,
+  sync::{Once, RwLock},
+  time::Instant,
+};
+
+use napi::{
+  threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode},
+  Env, Error, Result, Status,
+};
+use napi_derive::napi;
+use tracing::{
+  field::{Field, Visit},
+  level_filters::LevelFilter,
+  span, Event, Metadata, Subscriber,
+};
+use tracing_subscriber::{
+  layer::{Context, SubscriberExt},
+  registry::{LookupSpan, SpanRef},
+  Layer,
+};
+
+/// A span an event happened in, e.g. `crawl` with the `doc_id` it crawls.
+#[napi(object)]
+pub struct NativeLogSpan {
+  pub name: String,
+  pub fields: HashMap<String, String>,
+}
+
+#[napi(object)]
+pub struct NativeLogEvent {
+  /// `"error"`, `"warn"`, `"info"`, `"debug"` or `"trace"`.
+  pub level: String,
+  /// Module the event comes from.
+  pub target: String,
+  pub message: String,
+  pub fields: HashMap<String, String>,
+  /// Spans the event happened in, outermost first.
+  pub spans: Vec<NativeLogSpan>,
+  /// Set on the event sent when a span closes, with the time it was open.
+  pub elapsed_ms: Option<f64>,
+}
+
+struct LogSink {
+  level: LevelFilter,
+  callback: ThreadsafeFunction<NativeLogEvent, ()>,
+}
+
+static SINK: RwLock<Option<LogSink>> = RwLock::new(None);
+static INSTALL: Once = Once::new();
+
+/// Forwards the parser, exporter and importer spans and events at `level` or
+/// more severe to `callback`, e.g. to hand them to the Node logger. Events
+/// are queued without blocking the thread that emits them.
+///
+/// `level` is one of `"off"`, `"error"`, `"warn"`, `"info"`, `"debug"` or
+/// `"trace"`. Operations run in spans named after their stage (`crawl`,
+/// `markdown`, `html`, `export`, `load`, ...) carrying the doc id or file
+/// path; failed operations are reported as `error` events in their span, and
+/// closing spans are reported at `debug` with `elapsedMs` set.
+///
+/// The subscriber is process-wide: the last call wins, whichever worker
+/// made it.
+#[napi]
+pub fn set_log_subscriber(
+  env: Env,
+  level: String,
+  mut callback: ThreadsafeFunction<NativeLogEvent, ()>,
+) -> Result<()> {
+  let level = level
+    .parse::<LevelFilter>()
+    .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+  // a logger must not keep the process alive
+  callback.unref(&env)?;
+  INSTALL.call_once(|| {
+    // another subscriber may already be installed by the embedder
+    let _ = tracing::subscriber::set_global_default(tracing_subscriber::registry().with(JsLayer));
+  });
+  *SINK.write().unwrap() = Some(LogSink { level, callback });
+  tracing::callsite::rebuild_interest_cache();
+  Ok(())
+}
+
+fn current_level() -> LevelFilter {
+  SINK
+    .read()
+    .unwrap()
+    .as_ref()
+    .map_or(LevelFilter::OFF, |sink| sink.level)
+}
+
+struct SpanData {
+  fields: HashMap<String, String>,
+  opened: Instant,
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+  message: String,
+  fields: HashMap<String, String>,
+}
+
+impl Visit for FieldVisitor {
+  fn record_str(&mut self, field: &Field, value: &str) {
+    match field.name() {
+      "message" => self.message = value.to_string(),
+      name => {
+        self.fields.insert(name.to_string(), value.to_string());
+      }
+    }
+  }
+
+  fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+    match field.name() {
+      "message" => self.message = format!("{value:?}"),
+      name => {
+        self.fields.insert(name.to_string(), format!("{value:?}"));
+      }
+    }
+  }
+}
+
+/// Sends events to the callback of [`set_log_subscriber`], along with the
+/// fields of the spans they happened in.
+struct JsLayer;
+
+impl JsLayer {
+  fn send(&self, event: NativeLogEvent) {
+    if let Some(sink) = SINK.read().unwrap().as_ref() {
+      let _ = sink
+        .callback
+        .call(Ok(event), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+  }
+}
+
+fn spans<'a, S>(scope: impl Iterator<Item = SpanRef<'a, S>>) -> Vec<NativeLogSpan>
+where
+  S: LookupSpan<'a>,
+{
+  scope
+    .map(|span| NativeLogSpan {
+      name: span.name().to_string(),
+      fields: span
+        .extensions()
+        .get::<SpanData>()
+        .map(|data| data.fields.clone())
+        .unwrap_or_default(),
+    })
+    .collect()
+}
+
+fn level_name(metadata: &Metadata<'_>) -> String {
+  metadata.level().as_str().to_lowercase()
+}
+
+impl<S> Layer<S> for JsLayer
+where
+  S: Subscriber + for<'a> LookupSpan<'a>,
+{
+  fn enabled(&self, metadata: &Metadata<'_>, _: Context<'_, S>) -> bool {
+    let level = current_level();
+    // spans are kept at any level, for errors to tell which doc they are about
+    if metadata.is_span() {
+      level != LevelFilter::OFF
+    } else {
+      level >= *metadata.level()
+    }
+  }
+
+  fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+    let mut visitor = FieldVisitor::default();
+    attrs.record(&mut visitor);
+    if let Some(span) = ctx.span(id) {
+      span.extensions_mut().insert(SpanData {
+        fields: visitor.fields,
+        opened: Instant::now(),
+      });
+    }
+  }
+
+  fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+    let mut visitor = FieldVisitor::default();
+    values.record(&mut visitor);
+    if let Some(span) = ctx.span(id) {
+      if let Some(data) = span.extensions_mut().get_mut::<SpanData>() {
+        data.fields.extend(visitor.fields);
+      }
+    }
+  }
+
+  fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+    let mut visitor = FieldVisitor::default();
+    event.record(&mut visitor);
+    self.send(NativeLogEvent {
+      level: level_name(event.metadata()),
+      target: event.metadata().target().to_string(),
+      message: visitor.message,
+      fields: visitor.fields,
+      spans: ctx
+        .event_scope(event)
+        .map(|scope| spans(scope.from_root()))
+        .unwrap_or_default(),
+      elapsed_ms: None,
+    });
+  }
+
+  fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+    if current_level() < LevelFilter::DEBUG {
+      return;
+    }
+    let Some(span) = ctx.span(&id) else {
+      return;
+    };
+    let elapsed_ms = span
+      .extensions()
+      .get::<SpanData>()
+      .map(|data| data.opened.elapsed().as_secs_f64() * 1000.0);
+    self.send(NativeLogEvent {
+      level: "debug".into(),
+      target: span.metadata().target().to_string(),
+      message: "close".into(),
+      fields: HashMap::new(),
+      spans: spans(span.scope().from_root()),
+      elapsed_ms,
+    });
+  }
+}
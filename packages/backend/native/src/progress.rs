@@ -0,0 +1,32 @@
+use affine_common::progress::{Progress, ProgressSink};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+
+#[napi(object)]
+pub struct NativeProgress {
+  pub stage: String,
+  pub done: u32,
+  pub total: u32,
+}
+
+impl From<Progress> for NativeProgress {
+  fn from(progress: Progress) -> Self {
+    Self {
+      stage: progress.stage.to_string(),
+      done: progress.done as u32,
+      total: progress.total as u32,
+    }
+  }
+}
+
+/// Forwards progress reports from a worker thread to a JS callback. Reports
+/// are queued without blocking the worker.
+pub(crate) struct JsProgressSink(pub(crate) ThreadsafeFunction<NativeProgress, ()>);
+
+impl ProgressSink for JsProgressSink {
+  fn report(&self, progress: Progress) {
+    let _ = self
+      .0
+      .call(Ok(progress.into()), ThreadsafeFunctionCallMode::NonBlocking);
+  }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use affine_common::doc_parser::{self, MarkdownOptions, PublishedPage};
+use napi::{bindgen_prelude::*, Env};
+use napi_derive::napi;
+
+use crate::{
+  doc_error::{doc_error, status_error, DocResult},
+  export::parse_filter,
+  flavour_registry::with_flavour_registry,
+};
+
+#[napi(object)]
+pub struct NativePublishedPage {
+  pub title: String,
+  pub content: String,
+  pub etag: String,
+  pub clock: i64,
+}
+
+impl From<PublishedPage> for NativePublishedPage {
+  fn from(page: PublishedPage) -> Self {
+    Self {
+      title: page.title,
+      content: page.content,
+      etag: page.etag,
+      clock: page.clock as i64,
+    }
+  }
+}
+
+/// Renders a doc for its published page with a strong `etag` of the output
+/// and the `clock` of the doc, see `docClock`. The share service can answer
+/// conditional requests with a 304 while the clock of the stored doc is
+/// unchanged, without rendering again. `filter_json` lists blocks to leave
+/// out, see `validateOptions("filter", ..)`.
+#[napi]
+pub fn render_published_page(
+  env: Env,
+  doc_bin: Buffer,
+  doc_id: String,
+  filter_json: Option<String>,
+) -> DocResult<NativePublishedPage> {
+  let filter = parse_filter(filter_json).map_err(status_error)?;
+  let result = with_flavour_registry(&env, |handlers| {
+    doc_parser::render_published_page(
+      doc_bin.into(),
+      doc_id,
+      &MarkdownOptions {
+        handlers: Some(handlers),
+        filter,
+        ..Default::default()
+      },
+    )
+  })
+  .map_err(doc_error)?;
+  Ok(result.into())
+}
+
+/// Builds the `sitemap.xml` of a published workspace, listing the pages of
+/// `published_doc_ids` under `base_url`. `last_edits` maps doc ids to the
+/// time of their last stored update, used as `lastmod` before the dates in
+/// the page meta.
+#[napi]
+pub fn build_sitemap(
+  root_doc_bin: Buffer,
+  published_doc_ids: Vec<String>,
+  base_url: String,
+  last_edits: Option<HashMap<String, i64>>,
+) -> DocResult<String> {
+  doc_parser::build_sitemap(
+    root_doc_bin.into(),
+    &published_doc_ids,
+    &base_url,
+    &last_edits.unwrap_or_default(),
+  )
+  .map_err(doc_error)
+}
@@ -0,0 +1,117 @@
+use affine_common::ranking::{self, FusedHit, FusionOptions, HitProvenance, RankedHit};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+#[napi(object)]
+pub struct NativeRankedHit {
+  pub doc_id: String,
+  pub block_id: Option<String>,
+}
+
+impl From<NativeRankedHit> for RankedHit {
+  fn from(hit: NativeRankedHit) -> Self {
+    Self {
+      doc_id: hit.doc_id,
+      block_id: hit.block_id,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeFusionOptions {
+  /// Damping constant of reciprocal rank fusion. Defaults to 60.
+  pub k: Option<f64>,
+  /// Defaults to 1.
+  pub lexical_weight: Option<f64>,
+  /// Defaults to 1.
+  pub vector_weight: Option<f64>,
+  /// Merge hits of the same doc into one result. Defaults to true.
+  pub dedupe_docs: Option<bool>,
+  pub limit: Option<u32>,
+}
+
+#[napi(object)]
+pub struct NativeHitProvenance {
+  pub block_id: Option<String>,
+  /// `lexical` or `vector`.
+  pub source: String,
+  /// 1-based position of the hit in its source list.
+  pub position: u32,
+}
+
+impl From<HitProvenance> for NativeHitProvenance {
+  fn from(provenance: HitProvenance) -> Self {
+    Self {
+      block_id: provenance.block_id,
+      source: provenance.source.as_str().to_string(),
+      position: provenance.position as u32,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeFusedHit {
+  pub doc_id: String,
+  /// Block of the best ranked hit of the result.
+  pub block_id: Option<String>,
+  pub score: f64,
+  pub lexical_rank: Option<u32>,
+  pub vector_rank: Option<u32>,
+  /// Every hit merged into the result, lexical hits first.
+  pub provenance: Vec<NativeHitProvenance>,
+}
+
+impl From<FusedHit> for NativeFusedHit {
+  fn from(hit: FusedHit) -> Self {
+    Self {
+      doc_id: hit.doc_id,
+      block_id: hit.block_id,
+      score: hit.score,
+      lexical_rank: hit.lexical_rank.map(|rank| rank as u32),
+      vector_rank: hit.vector_rank.map(|rank| rank as u32),
+      provenance: hit.provenance.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Merges lexical (e.g. BM25) and vector search hits, each given best first,
+/// with reciprocal rank fusion. Either list may be empty when its backend is
+/// not configured.
+///
+/// Fails with `Status::InvalidArg` for a negative `k` or weight.
+#[napi]
+pub fn fuse_rankings(
+  bm25_hits: Vec<NativeRankedHit>,
+  vector_hits: Vec<NativeRankedHit>,
+  options: Option<NativeFusionOptions>,
+) -> Result<Vec<NativeFusedHit>> {
+  let defaults = FusionOptions::default();
+  let options = match options {
+    Some(options) => FusionOptions {
+      k: options.k.unwrap_or(defaults.k),
+      lexical_weight: options.lexical_weight.unwrap_or(defaults.lexical_weight),
+      vector_weight: options.vector_weight.unwrap_or(defaults.vector_weight),
+      dedupe_docs: options.dedupe_docs.unwrap_or(defaults.dedupe_docs),
+      limit: options.limit.map(|limit| limit as usize),
+    },
+    None => defaults,
+  };
+  if [options.k, options.lexical_weight, options.vector_weight]
+    .iter()
+    .any(|value| value.is_nan() || *value < 0.0)
+  {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "k and weights must be non-negative numbers",
+    ));
+  }
+
+  let bm25_hits = bm25_hits.into_iter().map(Into::into).collect::<Vec<_>>();
+  let vector_hits = vector_hits.into_iter().map(Into::into).collect::<Vec<_>>();
+  Ok(
+    ranking::fuse_rankings(&bm25_hits, &vector_hits, &options)
+      .into_iter()
+      .map(Into::into)
+      .collect(),
+  )
+}
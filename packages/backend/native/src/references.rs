@@ -0,0 +1,140 @@
+use affine_common::doc_parser::{self, CrossReference, HealCandidate, ReferenceEdge};
+use napi::{bindgen_prelude::*, Env, Task};
+use napi_derive::napi;
+
+use crate::{
+  doc::NativeWorkspaceDoc,
+  doc_error::{doc_error, task_error, DocResult},
+};
+
+#[napi(object)]
+pub struct NativeCrossReference {
+  pub block_id: String,
+  pub anchor: String,
+  pub label: Option<String>,
+  /// `null` when the anchor matches nothing in the doc.
+  pub target_block_id: Option<String>,
+  /// Number of the target heading, see `extractOutline`.
+  pub number: Option<String>,
+}
+
+impl From<CrossReference> for NativeCrossReference {
+  fn from(reference: CrossReference) -> Self {
+    Self {
+      block_id: reference.block_id,
+      anchor: reference.anchor,
+      label: reference.label,
+      target_block_id: reference.target_block_id,
+      number: reference.number,
+    }
+  }
+}
+
+/// Lists the `{{ref:<anchor>}}` tokens of a doc with what they resolve to,
+/// so broken references can be reported before exporting.
+#[napi]
+pub fn list_cross_references(
+  doc_bin: Buffer,
+  doc_id: String,
+) -> DocResult<Vec<NativeCrossReference>> {
+  let references = doc_parser::list_cross_references(doc_bin.into(), doc_id).map_err(doc_error)?;
+  Ok(references.into_iter().map(Into::into).collect())
+}
+
+#[napi(object)]
+pub struct NativeHealCandidate {
+  pub block_id: String,
+  pub flavour: String,
+  pub confidence: f64,
+  /// `"exists"`, `"content"` or `"parent"`.
+  pub reason: String,
+}
+
+impl From<HealCandidate> for NativeHealCandidate {
+  fn from(candidate: HealCandidate) -> Self {
+    Self {
+      block_id: candidate.block_id,
+      flavour: candidate.flavour,
+      confidence: candidate.confidence,
+      reason: candidate.reason.as_str().to_string(),
+    }
+  }
+}
+
+/// Suggests surviving blocks a broken block-level link could point to,
+/// best candidate first, based on the deleted block's content in the doc
+/// history.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
+/// let candidates =
+///   heal_reference(doc_bin, "doc-id".to_string(), "deleted-block".to_string()).unwrap();
+/// ```
+#[napi]
+pub fn heal_reference(
+  doc_bin: Buffer,
+  doc_id: String,
+  broken_block_id: String,
+) -> DocResult<Vec<NativeHealCandidate>> {
+  let result =
+    doc_parser::heal_reference(doc_bin.into(), doc_id, &broken_block_id).map_err(doc_error)?;
+  Ok(result.into_iter().map(Into::into).collect())
+}
+
+#[napi(object)]
+pub struct NativeReferenceEdge {
+  pub source_doc_id: String,
+  pub source_block_id: String,
+  pub target_doc_id: String,
+  /// One of `ReferenceKind`: `linked`, `synced` or `inline`.
+  pub kind: String,
+  pub ref_info: Option<String>,
+}
+
+impl From<ReferenceEdge> for NativeReferenceEdge {
+  fn from(edge: ReferenceEdge) -> Self {
+    Self {
+      source_doc_id: edge.source_doc_id,
+      source_block_id: edge.source_block_id,
+      target_doc_id: edge.target_doc_id,
+      kind: edge.kind.as_str().to_string(),
+      ref_info: edge.ref_info,
+    }
+  }
+}
+
+pub struct AsyncBuildReferenceGraph {
+  docs: Vec<(String, Vec<u8>)>,
+}
+
+#[napi]
+impl Task for AsyncBuildReferenceGraph {
+  type Output = DocResult<Vec<ReferenceEdge>>;
+  type JsValue = Vec<NativeReferenceEdge>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(doc_parser::build_reference_graph(std::mem::take(&mut self.docs)).map_err(doc_error))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into_iter().map(Into::into).collect())
+  }
+}
+
+/// Lists the references between the docs of a workspace on a worker thread,
+/// one edge per referencing block and target doc, for backlink and forward
+/// link queries.
+#[napi]
+pub fn build_reference_graph(docs: Vec<NativeWorkspaceDoc>) -> AsyncTask<AsyncBuildReferenceGraph> {
+  AsyncTask::new(AsyncBuildReferenceGraph {
+    docs: docs
+      .into_iter()
+      .map(|doc| (doc.doc_id, doc.doc_bin.to_vec()))
+      .collect(),
+  })
+}
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use affine_common::doc_parser;
+use napi::{
+  bindgen_prelude::{Buffer, Promise},
+  threadsafe_function::ThreadsafeFunction,
+  tokio::task::{self, JoinSet},
+  Error, Result, Status,
+};
+use napi_derive::napi;
+
+use crate::doc::NativeBlockInfo;
+
+const DEFAULT_CONCURRENCY: usize = 4;
+const DEFAULT_BATCH_SIZE: usize = 500;
+
+type FetchDoc = ThreadsafeFunction<String, Promise<Option<Buffer>>>;
+type SinkBatch = ThreadsafeFunction<NativeIndexBatch, Promise<()>>;
+
+#[napi(object)]
+pub struct ReindexOptions {
+  /// Docs fetched, parsed and sunk at the same time. Defaults to 4.
+  pub concurrency: Option<u32>,
+  /// Most blocks handed to the sink in one batch. Defaults to 500.
+  pub batch_size: Option<u32>,
+  /// Also reindex docs in the trash.
+  pub include_trash: Option<bool>,
+}
+
+#[napi(object)]
+pub struct NativeIndexBatch {
+  pub doc_id: String,
+  pub title: String,
+  pub summary: String,
+  pub blocks: Vec<NativeBlockInfo>,
+  /// Whether this is the last batch of the doc. Docs without blocks get a
+  /// single empty batch.
+  pub last: bool,
+}
+
+#[napi(object)]
+pub struct ReindexFailure {
+  pub doc_id: String,
+  pub error: String,
+}
+
+#[napi(object)]
+pub struct NativeReindexReport {
+  pub indexed: u32,
+  /// Docs the fetch callback returned nothing for.
+  pub missing: Vec<String>,
+  pub failed: Vec<ReindexFailure>,
+}
+
+/// Reindexes every doc listed in a workspace root doc.
+///
+/// Each doc is fetched with `fetch_doc`, parsed on a worker thread and handed
+/// to `sink` in batches of blocks. At most `concurrency` docs are in flight,
+/// and a doc only yields its next batch once the promise returned by `sink`
+/// for the previous one resolved, so a slow sink holds back fetching instead
+/// of piling records up in memory.
+///
+/// A doc that fails to fetch, parse or sink is reported and the others carry
+/// on. Registered flavour handlers are not applied, they can only run on the
+/// JS thread.
+#[napi]
+pub async fn reindex_workspace(
+  root_doc_bin: Buffer,
+  fetch_doc: FetchDoc,
+  sink: SinkBatch,
+  options: Option<ReindexOptions>,
+) -> Result<NativeReindexReport> {
+  let concurrency = options
+    .as_ref()
+    .and_then(|options| options.concurrency)
+    .map_or(DEFAULT_CONCURRENCY, |concurrency| concurrency as usize);
+  let batch_size = options
+    .as_ref()
+    .and_then(|options| options.batch_size)
+    .map_or(DEFAULT_BATCH_SIZE, |batch_size| batch_size as usize);
+  if concurrency == 0 || batch_size == 0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "concurrency and batch_size must be greater than 0",
+    ));
+  }
+  let include_trash = options
+    .and_then(|options| options.include_trash)
+    .unwrap_or(false);
+
+  let doc_ids = doc_parser::get_doc_ids_from_binary(root_doc_bin.to_vec(), include_trash)
+    .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+
+  let fetch_doc = Arc::new(fetch_doc);
+  let sink = Arc::new(sink);
+  let mut report = NativeReindexReport {
+    indexed: 0,
+    missing: vec![],
+    failed: vec![],
+  };
+  let mut pending = doc_ids.into_iter();
+  let mut running = JoinSet::new();
+  loop {
+    while running.len() < concurrency {
+      let Some(doc_id) = pending.next() else {
+        break;
+      };
+      let (fetch_doc, sink) = (fetch_doc.clone(), sink.clone());
+      running.spawn(async move {
+        let indexed = reindex_doc(&doc_id, &fetch_doc, &sink, batch_size).await;
+        (doc_id, indexed)
+      });
+    }
+
+    let Some(joined) = running.join_next().await else {
+      break;
+    };
+    let (doc_id, indexed) =
+      joined.map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    match indexed {
+      Ok(true) => report.indexed += 1,
+      Ok(false) => report.missing.push(doc_id),
+      Err(e) => report.failed.push(ReindexFailure {
+        doc_id,
+        error: e.reason,
+      }),
+    }
+  }
+
+  Ok(report)
+}
+
+/// Indexes a single doc, returning whether it could be fetched.
+async fn reindex_doc(
+  doc_id: &str,
+  fetch_doc: &FetchDoc,
+  sink: &SinkBatch,
+  batch_size: usize,
+) -> Result<bool> {
+  let Some(doc_bin) = fetch_doc.call_async(Ok(doc_id.to_string())).await?.await? else {
+    return Ok(false);
+  };
+
+  let parse_id = doc_id.to_string();
+  let doc_bin = doc_bin.to_vec();
+  let result = task::spawn_blocking(move || doc_parser::parse_doc_from_binary(doc_bin, parse_id))
+    .await
+    .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?
+    .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+
+  let mut blocks = result
+    .blocks
+    .into_iter()
+    .map(NativeBlockInfo::from)
+    .peekable();
+  loop {
+    let batch = blocks.by_ref().take(batch_size).collect::<Vec<_>>();
+    let last = blocks.peek().is_none();
+    sink
+      .call_async(Ok(NativeIndexBatch {
+        doc_id: doc_id.to_string(),
+        title: result.title.clone(),
+        summary: result.summary.clone(),
+        blocks: batch,
+        last,
+      }))
+      .await?
+      .await?;
+    if last {
+      return Ok(true);
+    }
+  }
+}
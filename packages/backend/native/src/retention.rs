@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use affine_common::{
+  deterministic::now_millis,
+  doc_parser::{self, RetentionDecision, RetentionPolicy},
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::doc_error::{doc_error, options_error, DocResult};
+
+#[napi(object)]
+pub struct NativeRetentionDecision {
+  pub doc_id: String,
+  /// `archive` or `delete`.
+  pub action: String,
+  /// `age` or `trash`.
+  pub reason: String,
+  /// Whole days since the last edit, or since the doc was trashed.
+  pub days: u32,
+}
+
+impl From<RetentionDecision> for NativeRetentionDecision {
+  fn from(decision: RetentionDecision) -> Self {
+    Self {
+      doc_id: decision.doc_id,
+      action: decision.action.as_str().to_string(),
+      reason: decision.reason.as_str().to_string(),
+      days: decision.days,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeRetentionReport {
+  pub decisions: Vec<NativeRetentionDecision>,
+  /// Docs a rule matched but an exempt tag protects.
+  pub exempted: Vec<String>,
+}
+
+/// Finds the docs the retention policy in `policy_json` archives or deletes.
+///
+/// `last_edits` maps doc ids to the time of their last stored update, in
+/// milliseconds since the Unix epoch, and takes precedence over the dates in
+/// the page meta. `now` defaults to the current time, or the epoch in
+/// deterministic mode.
+#[napi]
+pub fn evaluate_retention(
+  root_doc_bin: Buffer,
+  last_edits: Option<HashMap<String, i64>>,
+  policy_json: String,
+  now: Option<i64>,
+) -> DocResult<NativeRetentionReport> {
+  let policy = RetentionPolicy::from_json(&policy_json).map_err(options_error)?;
+  let now = now.unwrap_or_else(now_millis);
+  let report = doc_parser::evaluate_retention(
+    root_doc_bin.into(),
+    &last_edits.unwrap_or_default(),
+    &policy,
+    now,
+  )
+  .map_err(doc_error)?;
+  Ok(NativeRetentionReport {
+    decisions: report.decisions.into_iter().map(Into::into).collect(),
+    exempted: report.exempted,
+  })
+}
@@ -0,0 +1,74 @@
+use affine_common::doc_parser::{self, ScrubResult, SecretFinding};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::doc_error::{doc_error, DocResult};
+
+#[napi(object)]
+pub struct NativeScrubResult {
+  pub snapshot: Buffer,
+  pub updates: Vec<Buffer>,
+  pub matches: u32,
+}
+
+impl From<ScrubResult> for NativeScrubResult {
+  fn from(result: ScrubResult) -> Self {
+    Self {
+      snapshot: result.snapshot.into(),
+      updates: result.updates.into_iter().map(Into::into).collect(),
+      matches: result.matches as u32,
+    }
+  }
+}
+
+/// Masks text matching any of the regular expressions in `patterns` in a
+/// doc snapshot and in all of its stored updates, returning the rewritten
+/// binaries to store in their place. Used to remove secrets pasted by
+/// mistake from every version of a doc's history.
+///
+/// Invalid patterns are rejected with `invalid_options`.
+#[napi]
+pub fn scrub_history(
+  snapshot: Buffer,
+  updates: Vec<Buffer>,
+  patterns: Vec<String>,
+) -> DocResult<NativeScrubResult> {
+  let updates = updates.into_iter().map(Into::into).collect();
+  let result = doc_parser::scrub_history(snapshot.into(), updates, &patterns).map_err(doc_error)?;
+  Ok(result.into())
+}
+
+#[napi(object)]
+pub struct NativeSecretFinding {
+  pub block_id: String,
+  pub flavour: String,
+  pub prop: String,
+  pub kind: String,
+  /// Character offsets of the secret in the property.
+  pub start: u32,
+  pub end: u32,
+  pub preview: String,
+}
+
+impl From<SecretFinding> for NativeSecretFinding {
+  fn from(finding: SecretFinding) -> Self {
+    Self {
+      block_id: finding.block_id,
+      flavour: finding.flavour,
+      prop: finding.prop,
+      kind: finding.kind.as_str().to_string(),
+      start: finding.start as u32,
+      end: finding.end as u32,
+      preview: finding.preview,
+    }
+  }
+}
+
+/// Looks for credentials such as AWS keys, API tokens, private keys and
+/// passwords in the blocks of a doc, so sharing it publicly can be warned
+/// about. Findings are heuristic and carry a masked preview only.
+#[napi]
+pub fn scan_for_secrets(doc_bin: Buffer, doc_id: String) -> DocResult<Vec<NativeSecretFinding>> {
+  let findings = doc_parser::scan_for_secrets(doc_bin.into(), doc_id).map_err(doc_error)?;
+  Ok(findings.into_iter().map(Into::into).collect())
+}
@@ -0,0 +1,136 @@
+use std::{fs, io::ErrorKind};
+
+use affine_common::search_index::{
+  BlockHit, IndexedBlock, SearchHit, SearchIndex as CoreSearchIndex, SearchIndexError,
+};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::doc::NativeCrawlResult;
+
+fn map_err(e: SearchIndexError) -> Error {
+  Error::new(Status::InvalidArg, e.to_string())
+}
+
+fn io_error(path: &str, e: std::io::Error) -> Error {
+  Error::new(Status::GenericFailure, format!("{path}: {e}"))
+}
+
+#[napi(object)]
+pub struct NativeBlockHit {
+  pub block_id: String,
+  /// The text around the first match, with `…` where it was cut.
+  pub snippet: String,
+}
+
+impl From<BlockHit> for NativeBlockHit {
+  fn from(hit: BlockHit) -> Self {
+    Self {
+      block_id: hit.block_id,
+      snippet: hit.snippet,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeSearchHit {
+  pub doc_id: String,
+  pub title: String,
+  /// BM25 score, higher is better.
+  pub score: f64,
+  /// Up to 3 blocks with the most query terms. Empty when only the title
+  /// matched.
+  pub blocks: Vec<NativeBlockHit>,
+}
+
+impl From<SearchHit> for NativeSearchHit {
+  fn from(hit: SearchHit) -> Self {
+    Self {
+      doc_id: hit.doc_id,
+      title: hit.title,
+      score: hit.score as f64,
+      blocks: hit.blocks.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Full-text index over the titles and blocks of the docs of a workspace,
+/// filled from crawl results and kept in a file between runs.
+#[napi]
+#[derive(Default)]
+pub struct SearchIndex {
+  inner: CoreSearchIndex,
+}
+
+#[napi]
+impl SearchIndex {
+  #[napi(constructor)]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Restores an index saved with `save`, or an empty one when there is no
+  /// file at `path` yet.
+  ///
+  /// Fails with `Status::InvalidArg` when the file is not a search index.
+  #[napi(factory)]
+  pub fn load(path: String) -> Result<Self> {
+    let inner = match fs::read(&path) {
+      Ok(bytes) => CoreSearchIndex::from_bytes(&bytes).map_err(map_err)?,
+      Err(e) if e.kind() == ErrorKind::NotFound => CoreSearchIndex::new(),
+      Err(e) => return Err(io_error(&path, e)),
+    };
+    Ok(Self { inner })
+  }
+
+  /// Number of indexed docs.
+  #[napi(getter)]
+  pub fn size(&self) -> u32 {
+    self.inner.len() as u32
+  }
+
+  /// Indexes a doc from its crawl result, replacing the doc indexed under
+  /// the same id.
+  #[napi]
+  pub fn add_doc(&mut self, doc_id: String, crawl_result: NativeCrawlResult) {
+    let blocks = crawl_result
+      .blocks
+      .into_iter()
+      .filter_map(|block| {
+        let text = block.content?.join("\n");
+        (!text.trim().is_empty()).then_some(IndexedBlock {
+          block_id: block.block_id,
+          text,
+        })
+      })
+      .collect();
+    self.inner.add_doc(&doc_id, &crawl_result.title, blocks);
+  }
+
+  /// Removes a doc, returning whether it was indexed.
+  #[napi]
+  pub fn remove_doc(&mut self, doc_id: String) -> bool {
+    self.inner.remove_doc(&doc_id)
+  }
+
+  /// Returns the `limit` docs with every term and `"phrase"` of `query`,
+  /// best first. Filters like `tag:` are ignored, see `parse_search_query`.
+  #[napi]
+  pub fn search(&self, query: String, limit: u32) -> Vec<NativeSearchHit> {
+    self
+      .inner
+      .search(&query, limit as usize)
+      .into_iter()
+      .map(Into::into)
+      .collect()
+  }
+
+  /// Writes the index to `path`, through a temporary file renamed over it so
+  /// a crash never leaves a partial index behind.
+  #[napi]
+  pub fn save(&self, path: String) -> Result<()> {
+    let temp_path = format!("{path}.tmp");
+    fs::write(&temp_path, self.inner.to_bytes()).map_err(|e| io_error(&temp_path, e))?;
+    fs::rename(&temp_path, &path).map_err(|e| io_error(&path, e))
+  }
+}
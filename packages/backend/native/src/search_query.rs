@@ -0,0 +1,55 @@
+use affine_common::search_query::{self, DateFilter, SearchQuery};
+use napi_derive::napi;
+
+#[napi(object)]
+pub struct NativeDateFilter {
+  /// `created` or `updated`.
+  pub field: String,
+  /// One of `=`, `>`, `>=`, `<` and `<=`.
+  pub op: String,
+  /// `YYYY-MM-DD`.
+  pub date: String,
+}
+
+impl From<DateFilter> for NativeDateFilter {
+  fn from(filter: DateFilter) -> Self {
+    Self {
+      field: filter.field.as_str().to_string(),
+      op: filter.op.as_str().to_string(),
+      date: filter.date.format("%Y-%m-%d").to_string(),
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeSearchQuery {
+  pub terms: Vec<String>,
+  pub phrases: Vec<String>,
+  pub tags: Vec<String>,
+  pub folders: Vec<String>,
+  pub flags: Vec<String>,
+  pub dates: Vec<NativeDateFilter>,
+}
+
+impl From<SearchQuery> for NativeSearchQuery {
+  fn from(query: SearchQuery) -> Self {
+    Self {
+      terms: query.terms,
+      phrases: query.phrases,
+      tags: query.tags,
+      folders: query.folders,
+      flags: query.flags,
+      dates: query.dates.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Splits a search string into plain terms, quoted phrases and the `tag:`,
+/// `in:`, `is:`, `created:` and `updated:` filters, so JavaScript and native
+/// search read the syntax the same way.
+///
+/// Unknown operators and invalid dates are kept as plain terms.
+#[napi]
+pub fn parse_search_query(input: String) -> NativeSearchQuery {
+  search_query::parse_search_query(&input).into()
+}
@@ -0,0 +1,173 @@
+use affine_common::{
+  deterministic::now_millis,
+  doc_parser::{self, AnomalyKind, DocStateSummary, SnapshotPolicy, UpdateAnomaly},
+};
+use napi::{bindgen_prelude::*, Env, Task};
+use napi_derive::napi;
+
+use crate::{
+  analytics::NativeUpdateAnomaly,
+  doc_error::{doc_error, options_error, task_error, DocResult},
+};
+
+/// A version of a doc that grows with every insertion and deletion, cheap
+/// enough to compute on every request.
+#[napi]
+pub fn doc_clock(doc_bin: Buffer) -> DocResult<i64> {
+  let clock = doc_parser::doc_clock(&doc_bin).map_err(doc_error)?;
+  Ok(clock as i64)
+}
+
+#[napi(object)]
+pub struct NativeDocStateSummary {
+  /// Milliseconds since the Unix epoch, the current time when omitted
+  /// or the epoch in deterministic mode.
+  pub now: Option<i64>,
+  /// When the last snapshot was taken, omitted when the doc has none.
+  pub last_snapshot_at: Option<i64>,
+  /// Updates applied since the last snapshot.
+  pub pending_updates: u32,
+  /// Anomalies found in the pending updates by `analyzeUpdateStream`.
+  pub anomalies: Option<Vec<NativeUpdateAnomaly>>,
+}
+
+/// Tells whether a doc needs a restore point now. Returns the reason,
+/// `first_snapshot`, `anomaly`, `pending_updates` or `interval`, or `null`
+/// when it does not. `policy_json` is a snapshot policy, the defaults apply
+/// when it is omitted.
+#[napi]
+pub fn should_snapshot(
+  state: NativeDocStateSummary,
+  policy_json: Option<String>,
+) -> DocResult<Option<String>> {
+  let policy = match policy_json {
+    Some(json) => SnapshotPolicy::from_json(&json).map_err(options_error)?,
+    None => SnapshotPolicy::default(),
+  };
+  let anomalies = state
+    .anomalies
+    .unwrap_or_default()
+    .into_iter()
+    .map(|anomaly| {
+      Ok(UpdateAnomaly {
+        kind: anomaly.kind.parse::<AnomalyKind>().map_err(options_error)?,
+        first_update_index: anomaly.first_update_index as usize,
+        update_count: anomaly.update_count as usize,
+        start: anomaly.start,
+        end: anomaly.end,
+        inserted: anomaly.inserted as u64,
+        deleted: anomaly.deleted as u64,
+      })
+    })
+    .collect::<DocResult<Vec<_>>>()?;
+  let state = DocStateSummary {
+    now: state.now.unwrap_or_else(now_millis),
+    last_snapshot_at: state.last_snapshot_at,
+    pending_updates: state.pending_updates,
+    anomalies,
+  };
+  Ok(doc_parser::should_snapshot(&state, &policy).map(|reason| reason.as_str().to_string()))
+}
+
+/// Wraps a doc snapshot with a label and the current time, so restore points
+/// can be listed without decoding the docs.
+#[napi]
+pub fn make_labeled_snapshot(doc_bin: Buffer, label: String) -> DocResult<Buffer> {
+  doc_parser::make_labeled_snapshot(doc_bin.into(), &label)
+    .map(Into::into)
+    .map_err(doc_error)
+}
+
+#[napi(object)]
+pub struct NativeLabeledSnapshot {
+  pub label: String,
+  /// Milliseconds since the Unix epoch.
+  pub created_at: i64,
+  pub doc_bin: Buffer,
+}
+
+/// Reads a snapshot written by `makeLabeledSnapshot`.
+#[napi]
+pub fn read_labeled_snapshot(snapshot: Buffer) -> DocResult<NativeLabeledSnapshot> {
+  let snapshot = doc_parser::read_labeled_snapshot(&snapshot).map_err(doc_error)?;
+  Ok(NativeLabeledSnapshot {
+    label: snapshot.label,
+    created_at: snapshot.created_at,
+    doc_bin: snapshot.doc_bin.into(),
+  })
+}
+
+pub struct AsyncMergeUpdates {
+  updates: Vec<Vec<u8>>,
+}
+
+#[napi]
+impl Task for AsyncMergeUpdates {
+  type Output = DocResult<Vec<u8>>;
+  type JsValue = Buffer;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(doc_parser::merge_updates(std::mem::take(&mut self.updates)).map_err(doc_error))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into())
+  }
+}
+
+/// Merges incremental updates of a doc into a single update on a worker
+/// thread, so update tables can be squashed without loading them into yjs.
+#[napi]
+pub fn merge_updates(updates: Vec<Buffer>) -> AsyncTask<AsyncMergeUpdates> {
+  AsyncTask::new(AsyncMergeUpdates {
+    updates: updates.iter().map(|update| update.to_vec()).collect(),
+  })
+}
+
+pub struct AsyncCompactSnapshot {
+  doc_bin: Vec<u8>,
+}
+
+#[napi]
+impl Task for AsyncCompactSnapshot {
+  type Output = DocResult<Vec<u8>>;
+  type JsValue = Buffer;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(doc_parser::compact_snapshot(std::mem::take(&mut self.doc_bin)).map_err(doc_error))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into())
+  }
+}
+
+/// Re-encodes a doc snapshot on a worker thread with the content of deleted
+/// items garbage-collected. The result still merges with updates made
+/// against the original snapshot.
+#[napi]
+pub fn compact_snapshot(doc_bin: Buffer) -> AsyncTask<AsyncCompactSnapshot> {
+  AsyncTask::new(AsyncCompactSnapshot {
+    doc_bin: doc_bin.to_vec(),
+  })
+}
+
+/// Encodes the state vector of a doc as `Y.encodeStateVector` does.
+#[napi]
+pub fn encode_state_vector(doc_bin: Buffer) -> DocResult<Buffer> {
+  doc_parser::encode_state_vector(&doc_bin)
+    .map(Into::into)
+    .map_err(doc_error)
+}
+
+/// Returns the update a peer at `state_vector` is missing, as
+/// `Y.encodeStateAsUpdate` does. An empty state vector asks for the whole
+/// doc.
+#[napi]
+pub fn diff_update(doc_bin: Buffer, state_vector: Buffer) -> DocResult<Buffer> {
+  doc_parser::diff_update(&doc_bin, &state_vector)
+    .map(Into::into)
+    .map_err(doc_error)
+}
@@ -0,0 +1,91 @@
+use affine_common::doc_parser::{self, Snippet, SnippetMatch, SnippetOptions};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::doc_error::{doc_error, DocResult};
+
+#[napi(object)]
+pub struct NativeSnippetOptions {
+  /// Most snippets returned, one per block. Defaults to 5.
+  pub max_snippets: Option<u32>,
+  /// Characters kept before the first match of a block and after it.
+  /// Defaults to 40.
+  pub context_chars: Option<u32>,
+  /// Inserted before every match in `highlighted`. Defaults to `<mark>`.
+  pub highlight_start: Option<String>,
+  /// Inserted after every match in `highlighted`. Defaults to `</mark>`.
+  pub highlight_end: Option<String>,
+}
+
+#[napi(object)]
+pub struct NativeSnippetMatch {
+  /// UTF-16 offset in `text` of the snippet.
+  pub start: u32,
+  pub end: u32,
+}
+
+impl From<SnippetMatch> for NativeSnippetMatch {
+  fn from(snippet_match: SnippetMatch) -> Self {
+    Self {
+      start: snippet_match.start as u32,
+      end: snippet_match.end as u32,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeSnippet {
+  pub block_id: String,
+  pub flavour: String,
+  /// The text around the first match of the block, on one line, with `…`
+  /// where it was cut.
+  pub text: String,
+  /// `text` with the highlight markers around every match, not escaped.
+  pub highlighted: String,
+  pub matches: Vec<NativeSnippetMatch>,
+  /// Matches in the whole block, including those cut from `text`.
+  pub match_count: u32,
+}
+
+impl From<Snippet> for NativeSnippet {
+  fn from(snippet: Snippet) -> Self {
+    Self {
+      block_id: snippet.block_id,
+      flavour: snippet.flavour,
+      text: snippet.text,
+      highlighted: snippet.highlighted,
+      matches: snippet.matches.into_iter().map(Into::into).collect(),
+      match_count: snippet.match_count as u32,
+    }
+  }
+}
+
+/// Cuts highlighted snippets from the blocks of a doc that match
+/// `query_terms`, best blocks first, so search result rows do not need the
+/// whole doc. Terms match anywhere in the text, ignoring case.
+#[napi]
+pub fn generate_snippets(
+  doc_bin: Buffer,
+  doc_id: String,
+  query_terms: Vec<String>,
+  options: Option<NativeSnippetOptions>,
+) -> DocResult<Vec<NativeSnippet>> {
+  let defaults = SnippetOptions::default();
+  let options = match options {
+    Some(options) => SnippetOptions {
+      max_snippets: options
+        .max_snippets
+        .map_or(defaults.max_snippets, |max| max as usize),
+      context_chars: options
+        .context_chars
+        .map_or(defaults.context_chars, |chars| chars as usize),
+      highlight_start: options.highlight_start.unwrap_or(defaults.highlight_start),
+      highlight_end: options.highlight_end.unwrap_or(defaults.highlight_end),
+    },
+    None => defaults,
+  };
+
+  let snippets = doc_parser::generate_snippets(doc_bin.into(), doc_id, &query_terms, &options)
+    .map_err(doc_error)?;
+  Ok(snippets.into_iter().map(Into::into).collect())
+}
@@ -0,0 +1,137 @@
+use affine_common::{
+  doc_parser,
+  spelling::{self, Correction, QueryCorrection, Suggestion, Vocabulary as CoreVocabulary},
+};
+use napi::{bindgen_prelude::AsyncTask, Env, Result, Task};
+use napi_derive::napi;
+
+use crate::doc::NativeWorkspaceDoc;
+
+#[napi(object)]
+pub struct NativeSuggestion {
+  pub word: String,
+  pub distance: u32,
+  /// Occurrences of the word in the workspace.
+  pub frequency: u32,
+}
+
+impl From<Suggestion> for NativeSuggestion {
+  fn from(suggestion: Suggestion) -> Self {
+    Self {
+      word: suggestion.word,
+      distance: suggestion.distance as u32,
+      frequency: suggestion.frequency,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeCorrection {
+  pub term: String,
+  pub suggestion: String,
+  pub distance: u32,
+}
+
+impl From<Correction> for NativeCorrection {
+  fn from(correction: Correction) -> Self {
+    Self {
+      term: correction.term,
+      suggestion: correction.suggestion,
+      distance: correction.distance as u32,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeQueryCorrection {
+  /// The query with every correction applied.
+  pub query: String,
+  pub corrections: Vec<NativeCorrection>,
+}
+
+impl From<QueryCorrection> for NativeQueryCorrection {
+  fn from(correction: QueryCorrection) -> Self {
+    Self {
+      query: correction.query,
+      corrections: correction.corrections.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// The words of a workspace, for "did you mean" suggestions.
+#[napi]
+pub struct Vocabulary {
+  inner: CoreVocabulary,
+}
+
+#[napi]
+impl Vocabulary {
+  /// Number of distinct words.
+  #[napi(getter)]
+  pub fn size(&self) -> u32 {
+    self.inner.len() as u32
+  }
+
+  /// Returns up to `limit` words close to `term`, closest first.
+  #[napi]
+  pub fn suggest(&self, term: String, limit: u32) -> Vec<NativeSuggestion> {
+    self
+      .inner
+      .suggest(&term, limit as usize)
+      .into_iter()
+      .map(Into::into)
+      .collect()
+  }
+}
+
+pub struct AsyncBuildVocabulary {
+  docs: Vec<(String, Vec<u8>)>,
+}
+
+#[napi]
+impl Task for AsyncBuildVocabulary {
+  type Output = CoreVocabulary;
+  type JsValue = Vocabulary;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let mut vocabulary = CoreVocabulary::new();
+    for (doc_id, doc_bin) in std::mem::take(&mut self.docs) {
+      // docs that fail to parse have no words to contribute
+      let Ok(result) = doc_parser::parse_doc_from_binary(doc_bin, doc_id) else {
+        continue;
+      };
+      vocabulary.add_text(&result.title);
+      for block in result.blocks {
+        for content in block.content.unwrap_or_default() {
+          vocabulary.add_text(&content);
+        }
+      }
+    }
+    Ok(vocabulary)
+  }
+
+  fn resolve(&mut self, _: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(Vocabulary { inner: output })
+  }
+}
+
+/// Collects the words of the titles and blocks of `docs` on a worker thread.
+#[napi]
+pub fn build_vocabulary(docs: Vec<NativeWorkspaceDoc>) -> AsyncTask<AsyncBuildVocabulary> {
+  AsyncTask::new(AsyncBuildVocabulary {
+    docs: docs
+      .into_iter()
+      .map(|doc| (doc.doc_id, doc.doc_bin.to_vec()))
+      .collect(),
+  })
+}
+
+/// Replaces every word of `query` missing from `vocabulary` with its closest
+/// match. Returns `null` when there is nothing to correct.
+#[napi]
+pub fn suggest_corrections(
+  vocabulary: &Vocabulary,
+  query: String,
+) -> Option<NativeQueryCorrection> {
+  spelling::suggest_corrections(&vocabulary.inner, &query).map(Into::into)
+}
@@ -0,0 +1,249 @@
+use affine_common::doc_parser::{
+  self, BlockContext, DocSample, DocSkeleton, OutlineEntry, ResolvedAnchor, SkeletonNode,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{
+  doc_error::{doc_error, status_error, DocResult},
+  encoding::ResultEncoding,
+};
+
+#[napi(object)]
+pub struct NativeSkeletonNode {
+  pub flavour: String,
+  pub text_length: u32,
+  pub children: Vec<NativeSkeletonNode>,
+}
+
+impl From<SkeletonNode> for NativeSkeletonNode {
+  fn from(node: SkeletonNode) -> Self {
+    Self {
+      flavour: node.flavour,
+      text_length: node.text_length as u32,
+      children: node.children.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeDocSkeleton {
+  pub root: NativeSkeletonNode,
+  pub block_count: u32,
+}
+
+impl From<DocSkeleton> for NativeDocSkeleton {
+  fn from(skeleton: DocSkeleton) -> Self {
+    Self {
+      root: skeleton.root.into(),
+      block_count: skeleton.block_count as u32,
+    }
+  }
+}
+
+/// Extracts the block tree of a document with flavours and text lengths but
+/// without ids or content.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
+/// if let Either::A(skeleton) = extract_skeleton(doc_bin, "doc-id".to_string(), None).unwrap() {
+///   println!("{} blocks", skeleton.block_count);
+/// }
+/// ```
+#[napi]
+pub fn extract_skeleton(
+  doc_bin: Buffer,
+  doc_id: String,
+  encoding: Option<String>,
+) -> DocResult<Either<NativeDocSkeleton, Buffer>> {
+  let encoding = ResultEncoding::parse(encoding).map_err(status_error)?;
+  let result = doc_parser::extract_skeleton(doc_bin.into(), doc_id).map_err(doc_error)?;
+  Ok(
+    encoding
+      .encode(result)
+      .map_err(status_error)?
+      .into_js(Into::into),
+  )
+}
+
+#[napi(object)]
+pub struct NativeSampledBlock {
+  pub block_id: String,
+  pub flavour: String,
+  /// `title`, `heading`, `paragraph` or `table_row`.
+  pub kind: String,
+  /// Table rows are their cells joined by ` | `.
+  pub text: String,
+  /// Set when the text was cut to fit the budget.
+  pub truncated: bool,
+}
+
+#[napi(object)]
+pub struct NativeDocSample {
+  pub title: String,
+  pub blocks: Vec<NativeSampledBlock>,
+  /// Bytes of text in `blocks`.
+  pub size: u32,
+  /// Blocks left out for the budget.
+  pub omitted: u32,
+}
+
+impl From<DocSample> for NativeDocSample {
+  fn from(sample: DocSample) -> Self {
+    Self {
+      title: sample.title,
+      blocks: sample
+        .blocks
+        .into_iter()
+        .map(|block| NativeSampledBlock {
+          block_id: block.block_id,
+          flavour: block.flavour,
+          kind: block.kind.as_str().to_string(),
+          text: block.text,
+          truncated: block.truncated,
+        })
+        .collect(),
+      size: sample.size as u32,
+      omitted: sample.omitted as u32,
+    }
+  }
+}
+
+/// Picks the title, headings, first paragraph of every section and first
+/// rows of every table of a doc, in document order, within `budget` bytes of
+/// text. Used for hover previews and AI context packing.
+#[napi]
+pub fn sample_doc(doc_bin: Buffer, doc_id: String, budget: u32) -> DocResult<NativeDocSample> {
+  doc_parser::sample_doc(doc_bin.into(), doc_id, budget as usize)
+    .map(Into::into)
+    .map_err(doc_error)
+}
+
+#[napi(object)]
+pub struct NativeBlockContext {
+  pub title: String,
+  pub block_ids: Vec<String>,
+  pub markdown: String,
+  pub plain_text: String,
+}
+
+impl From<BlockContext> for NativeBlockContext {
+  fn from(context: BlockContext) -> Self {
+    Self {
+      title: context.title,
+      block_ids: context.block_ids,
+      markdown: context.markdown,
+      plain_text: context.plain_text,
+    }
+  }
+}
+
+/// Renders a block with up to `radius` blocks of surrounding context, as
+/// markdown and plain text, for quoting it in notifications.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
+/// let context =
+///   render_block_context(doc_bin, "doc-id".to_string(), "block-id".to_string(), 2).unwrap();
+/// println!("{}", context.plain_text);
+/// ```
+#[napi]
+pub fn render_block_context(
+  doc_bin: Buffer,
+  doc_id: String,
+  block_id: String,
+  radius: u32,
+) -> DocResult<NativeBlockContext> {
+  let result = doc_parser::render_block_context(doc_bin.into(), doc_id, &block_id, radius as usize)
+    .map_err(doc_error)?;
+  Ok(result.into())
+}
+
+#[napi(object)]
+pub struct NativeResolvedAnchor {
+  pub block_id: String,
+  /// `"block"`, `"heading"` or `"text"`.
+  pub kind: String,
+  pub offset: u32,
+}
+
+impl From<ResolvedAnchor> for NativeResolvedAnchor {
+  fn from(anchor: ResolvedAnchor) -> Self {
+    Self {
+      block_id: anchor.block_id,
+      kind: anchor.kind.as_str().to_string(),
+      offset: anchor.offset as u32,
+    }
+  }
+}
+
+/// Resolves a deep-link anchor (block id, heading slug or text fragment) to
+/// the block it points at, or `null` when nothing matches.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
+/// let anchor = resolve_anchor(doc_bin, "doc-id".to_string(), "#intro".to_string()).unwrap();
+/// ```
+#[napi]
+pub fn resolve_anchor(
+  doc_bin: Buffer,
+  doc_id: String,
+  anchor: String,
+) -> DocResult<Option<NativeResolvedAnchor>> {
+  let result = doc_parser::resolve_anchor(doc_bin.into(), doc_id, &anchor).map_err(doc_error)?;
+  Ok(result.map(Into::into))
+}
+
+#[napi(object)]
+pub struct NativeOutlineEntry {
+  pub block_id: String,
+  pub level: u32,
+  pub text: String,
+  /// Anchor accepted by `resolveAnchor`.
+  pub slug: String,
+  /// `1.`, `1.1`, ... as rendered by exports with numbered headings.
+  pub number: String,
+}
+
+impl From<OutlineEntry> for NativeOutlineEntry {
+  fn from(entry: OutlineEntry) -> Self {
+    Self {
+      block_id: entry.block_id,
+      level: entry.level as u32,
+      text: entry.text,
+      slug: entry.slug,
+      number: entry.number,
+    }
+  }
+}
+
+/// Lists the headings of a doc in document order, with the slugs and numbers
+/// exports use for them, so tables of contents and cross-references match the
+/// exported output.
+#[napi]
+pub fn extract_outline(
+  doc_bin: Buffer,
+  doc_id: String,
+  encoding: Option<String>,
+) -> DocResult<Either<Vec<NativeOutlineEntry>, Buffer>> {
+  let encoding = ResultEncoding::parse(encoding).map_err(status_error)?;
+  let entries = doc_parser::extract_outline(doc_bin.into(), doc_id).map_err(doc_error)?;
+  Ok(
+    encoding
+      .encode(entries)
+      .map_err(status_error)?
+      .into_js(|entries| entries.into_iter().map(Into::into).collect()),
+  )
+}
@@ -0,0 +1,58 @@
+use affine_common::doc_parser::{self, TemplateSchema, TemplateVariable};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::doc_error::{doc_error, DocResult};
+
+#[napi(object)]
+pub struct NativeTemplateVariable {
+  pub name: String,
+  pub label: String,
+  /// `text` or `choice`.
+  pub kind: String,
+  /// `placeholder` for `{{name}}` tokens, `form` for form-like blocks.
+  pub source: String,
+  /// Choices of a `choice` variable, in document order.
+  pub options: Vec<String>,
+  pub block_ids: Vec<String>,
+}
+
+impl From<TemplateVariable> for NativeTemplateVariable {
+  fn from(variable: TemplateVariable) -> Self {
+    Self {
+      name: variable.name,
+      label: variable.label,
+      kind: variable.kind.as_str().to_string(),
+      source: variable.source.as_str().to_string(),
+      options: variable.options,
+      block_ids: variable.block_ids,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeTemplateSchema {
+  pub title: String,
+  pub variables: Vec<NativeTemplateVariable>,
+}
+
+impl From<TemplateSchema> for NativeTemplateSchema {
+  fn from(schema: TemplateSchema) -> Self {
+    Self {
+      title: schema.title,
+      variables: schema.variables.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Finds the `{{name}}` tokens and form-like blocks (`Label: ____` lines and
+/// questions followed by todo items) of a template doc, as a schema to
+/// render a form from before the doc is instantiated or fed to a workflow.
+#[napi]
+pub fn extract_template_variables(
+  doc_bin: Buffer,
+  doc_id: String,
+) -> DocResult<NativeTemplateSchema> {
+  let schema = doc_parser::extract_template_variables(doc_bin.into(), doc_id).map_err(doc_error)?;
+  Ok(schema.into())
+}
@@ -0,0 +1,133 @@
+use std::io::Cursor;
+
+use affine_common::doc_parser;
+use image::{DynamicImage, ImageFormat, ImageResult};
+use napi::{
+  bindgen_prelude::{Buffer, Function},
+  Error, Result, Status,
+};
+use napi_derive::napi;
+
+/// Decodes an image and scales it down to fit in `max_dim` x `max_dim`,
+/// keeping its aspect ratio, then encodes it as `format`: `png`, `jpeg` or
+/// `webp`. Smaller images are re-encoded without being scaled up.
+///
+/// Fails with `Status::InvalidArg` for unknown formats, a `max_dim` of 0 or
+/// bytes that are not a supported image.
+#[napi]
+pub fn generate_thumbnail(image: Buffer, max_dim: u32, format: String) -> Result<Buffer> {
+  let format = parse_format(&format, max_dim)?;
+  let thumbnail = thumbnail(image.as_ref(), max_dim, format)
+    .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+  Ok(thumbnail.into())
+}
+
+#[napi(object)]
+pub struct NativeDocPreview {
+  pub title: String,
+  /// Blob id of the first image of the doc.
+  pub image_id: Option<String>,
+  /// Thumbnail of the first image, when the doc has one and its blob could
+  /// be loaded and decoded.
+  pub image: Option<Buffer>,
+}
+
+/// Builds the list view preview of a doc: its title and a thumbnail of its
+/// first image, see [`generate_thumbnail`].
+///
+/// `load_blob` is called synchronously with the blob id of the first image
+/// and returns its bytes, or `null`/`undefined` when the blob is missing.
+#[napi]
+pub fn generate_doc_preview_image(
+  doc_bin: Buffer,
+  doc_id: String,
+  load_blob: Function<String, Option<Buffer>>,
+  max_dim: u32,
+  format: String,
+) -> Result<NativeDocPreview> {
+  let format = parse_format(&format, max_dim)?;
+  let result = doc_parser::parse_doc_from_binary(doc_bin.into(), doc_id)
+    .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+
+  let image_id = result
+    .blocks
+    .into_iter()
+    .filter(|block| block.flavour == "affine:image")
+    .find_map(|block| block.blob.and_then(|blobs| blobs.into_iter().next()));
+  let image = match &image_id {
+    Some(image_id) => load_blob
+      .call(image_id.clone())?
+      .and_then(|blob| thumbnail(blob.as_ref(), max_dim, format).ok())
+      .map(Into::into),
+    None => None,
+  };
+
+  Ok(NativeDocPreview {
+    title: result.title,
+    image_id,
+    image,
+  })
+}
+
+fn parse_format(format: &str, max_dim: u32) -> Result<ImageFormat> {
+  if max_dim == 0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "max_dim must be greater than 0",
+    ));
+  }
+  match format {
+    "png" => Ok(ImageFormat::Png),
+    "jpeg" | "jpg" => Ok(ImageFormat::Jpeg),
+    "webp" => Ok(ImageFormat::WebP),
+    _ => Err(Error::new(
+      Status::InvalidArg,
+      format!("unsupported thumbnail format: {format}"),
+    )),
+  }
+}
+
+fn thumbnail(input: &[u8], max_dim: u32, format: ImageFormat) -> ImageResult<Vec<u8>> {
+  let mut image = image::load_from_memory(input)?;
+  if image.width() > max_dim || image.height() > max_dim {
+    image = image.thumbnail(max_dim, max_dim);
+  }
+  // the JPEG encoder takes no alpha channel and the WebP one only 8 bit RGB(A)
+  let image = match format {
+    ImageFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8()),
+    ImageFormat::WebP => DynamicImage::ImageRgba8(image.to_rgba8()),
+    _ => image,
+  };
+
+  let mut output = Cursor::new(Vec::new());
+  image.write_to(&mut output, format)?;
+  Ok(output.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+  use image::RgbImage;
+
+  use super::*;
+
+  #[test]
+  fn test_thumbnail() {
+    let mut png = Cursor::new(Vec::new());
+    DynamicImage::ImageRgb8(RgbImage::new(40, 20))
+      .write_to(&mut png, ImageFormat::Png)
+      .unwrap();
+    let png = png.into_inner();
+
+    for format in [ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::WebP] {
+      let scaled = thumbnail(&png, 10, format).unwrap();
+      assert_eq!(image::guess_format(&scaled).unwrap(), format);
+      let scaled = image::load_from_memory(&scaled).unwrap();
+      assert_eq!((scaled.width(), scaled.height()), (10, 5));
+    }
+
+    let kept = image::load_from_memory(&thumbnail(&png, 100, ImageFormat::Png).unwrap()).unwrap();
+    assert_eq!((kept.width(), kept.height()), (40, 20));
+
+    assert!(thumbnail(b"not an image", 10, ImageFormat::Png).is_err());
+  }
+}
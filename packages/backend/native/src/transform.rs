@@ -0,0 +1,142 @@
+use affine_common::doc_parser::{
+  self, CleanupOptions, CleanupResult, DowngradedDoc, LintIssue, LintRules,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::doc_error::{doc_error, options_error, DocResult};
+
+#[napi(object)]
+pub struct NativeDowngradedDoc {
+  pub doc_bin: Buffer,
+  pub downgraded_block_ids: Vec<String>,
+}
+
+impl From<DowngradedDoc> for NativeDowngradedDoc {
+  fn from(downgraded: DowngradedDoc) -> Self {
+    Self {
+      doc_bin: downgraded.doc_bin.into(),
+      downgraded_block_ids: downgraded.downgraded_block_ids,
+    }
+  }
+}
+
+/// Rewrites the blocks of flavours newer than `target_schema_version` into
+/// their closest legacy flavour, so docs shared with older clients stay
+/// readable. Returns the whole downgraded doc.
+#[napi]
+pub fn downgrade_doc(
+  doc_bin: Buffer,
+  doc_id: String,
+  target_schema_version: u32,
+) -> DocResult<NativeDowngradedDoc> {
+  doc_parser::downgrade_doc(doc_bin.into(), doc_id, target_schema_version)
+    .map(Into::into)
+    .map_err(doc_error)
+}
+
+/// Rewrites a doc as a binary holding only its current content, without
+/// history, deleted items or the ids of the clients that edited it. Used
+/// when archiving docs whose history must not be retained.
+#[napi]
+pub fn flatten_doc(doc_bin: Buffer, doc_id: String) -> DocResult<Buffer> {
+  let flattened = doc_parser::flatten_doc(doc_bin.into(), doc_id).map_err(doc_error)?;
+  Ok(flattened.into())
+}
+
+/// Builds a doc from Markdown, mapping headings, lists, code fences, tables
+/// and `blob://` images to their blocks, and returns it as a full update.
+/// Used to import AI-generated content and Markdown files in bulk.
+#[napi]
+pub fn markdown_to_doc_binary(markdown: String, doc_id: String) -> DocResult<Buffer> {
+  let doc_bin = doc_parser::markdown_to_doc_binary(markdown, doc_id).map_err(doc_error)?;
+  Ok(doc_bin.into())
+}
+
+#[napi(object)]
+pub struct NativeLintIssue {
+  /// `headingincrement`, `linelength` or `bareurl`.
+  pub rule: String,
+  pub line: u32,
+  pub column: u32,
+  pub message: String,
+}
+
+impl From<LintIssue> for NativeLintIssue {
+  fn from(issue: LintIssue) -> Self {
+    Self {
+      rule: issue.rule.as_str().to_string(),
+      line: issue.line as u32,
+      column: issue.column as u32,
+      message: issue.message,
+    }
+  }
+}
+
+/// Checks exported markdown for style issues: skipped heading levels, long
+/// lines and bare URLs. `rules_json` configures the rules, see
+/// `validateOptions("lint", ..)`; all rules run with their defaults when it is
+/// omitted. Invalid rules are rejected with `invalid_options`.
+#[napi]
+pub fn lint_markdown(
+  markdown: String,
+  rules_json: Option<String>,
+) -> DocResult<Vec<NativeLintIssue>> {
+  let rules = match rules_json {
+    Some(json) => LintRules::from_json(&json).map_err(options_error)?,
+    None => LintRules::default(),
+  };
+  let issues = doc_parser::lint_markdown(&markdown, &rules);
+  Ok(issues.into_iter().map(Into::into).collect())
+}
+
+#[napi(object)]
+pub struct NativeCleanupResult {
+  pub update: Buffer,
+  pub removed_block_ids: Vec<String>,
+  pub trimmed_block_ids: Vec<String>,
+}
+
+impl From<CleanupResult> for NativeCleanupResult {
+  fn from(result: CleanupResult) -> Self {
+    Self {
+      update: result.update.into(),
+      removed_block_ids: result.removed_block_ids,
+      trimmed_block_ids: result.trimmed_block_ids,
+    }
+  }
+}
+
+/// Removes trailing and repeated blank paragraphs and trims trailing
+/// whitespace, returning the changes as an update for the doc.
+///
+/// `options_json` selects the passes, see `validateOptions("cleanup", ..)`;
+/// all passes run when it is omitted. Invalid options are rejected with
+/// `invalid_options`.
+///
+/// # Examples
+///
+/// ```
+/// use napi::bindgen_prelude::Buffer;
+///
+/// let doc_bin = Buffer::from(vec![/* ...document bytes... */]);
+/// let result = cleanup_doc(
+///   doc_bin,
+///   "doc-id".to_string(),
+///   Some(r#"{ "trimTrailingWhitespace": false }"#.to_string()),
+/// )
+/// .unwrap();
+/// ```
+#[napi]
+pub fn cleanup_doc(
+  doc_bin: Buffer,
+  doc_id: String,
+  options_json: Option<String>,
+) -> DocResult<NativeCleanupResult> {
+  let options = match options_json {
+    Some(json) => CleanupOptions::from_json(&json).map_err(options_error)?,
+    None => CleanupOptions::default(),
+  };
+  let result = doc_parser::cleanup_doc(doc_bin.into(), doc_id, &options).map_err(doc_error)?;
+  Ok(result.into())
+}
@@ -0,0 +1,294 @@
+use affine_common::doc_parser::{
+  self, CorpusReport, DiagnosticBundle, LenientCrawlResult, RecoverableError, SampleCheck,
+  SchemaCheck, SchemaMismatch, SchemaRange, ValidationReport,
+};
+use napi::{bindgen_prelude::*, Env, Task};
+use napi_derive::napi;
+
+use crate::{
+  doc::NativeCrawlResult,
+  doc_error::{doc_error, task_error, DocResult},
+};
+
+#[napi(object)]
+pub struct NativeSchemaRange {
+  pub flavour: String,
+  pub min_version: i64,
+  pub max_version: i64,
+}
+
+impl From<SchemaRange> for NativeSchemaRange {
+  fn from(range: SchemaRange) -> Self {
+    Self {
+      flavour: range.flavour,
+      min_version: range.min_version,
+      max_version: range.max_version,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeSchemaMismatch {
+  pub flavour: String,
+  pub version: i64,
+  pub block_count: u32,
+}
+
+impl From<SchemaMismatch> for NativeSchemaMismatch {
+  fn from(mismatch: SchemaMismatch) -> Self {
+    Self {
+      flavour: mismatch.flavour,
+      version: mismatch.version,
+      block_count: mismatch.block_count as u32,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeSchemaCheck {
+  pub supported: bool,
+  pub mismatches: Vec<NativeSchemaMismatch>,
+}
+
+impl From<SchemaCheck> for NativeSchemaCheck {
+  fn from(check: SchemaCheck) -> Self {
+    Self {
+      supported: check.supported,
+      mismatches: check.mismatches.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Returns the block schema versions this build reads, one range per
+/// built-in flavour, to compare workers of a mixed-version deployment.
+#[napi]
+pub fn get_supported_schema_range() -> Vec<NativeSchemaRange> {
+  doc_parser::get_supported_schema_range()
+    .into_iter()
+    .map(Into::into)
+    .collect()
+}
+
+/// Checks the schema versions of the blocks of a doc without parsing it, so
+/// docs written by newer clients can be routed to upgraded workers.
+#[napi]
+pub fn can_parse(doc_bin: Buffer) -> DocResult<NativeSchemaCheck> {
+  doc_parser::can_parse(&doc_bin)
+    .map(Into::into)
+    .map_err(doc_error)
+}
+
+#[napi(object)]
+pub struct NativeDanglingChild {
+  pub parent_block_id: String,
+  pub child_block_id: String,
+}
+
+#[napi(object)]
+pub struct NativeValidationReport {
+  /// `true` when nothing below is wrong.
+  pub valid: bool,
+  /// Whether the binary decodes, nothing else is checked when it does not.
+  pub decodable: bool,
+  pub decode_error: Option<String>,
+  /// Lowest doc schema version that reads every flavour of the doc.
+  pub schema_version: Option<u32>,
+  pub schema: Option<NativeSchemaCheck>,
+  pub block_count: u32,
+  pub root_block_id: Option<String>,
+  /// Blocks not reachable from the root.
+  pub orphan_block_ids: Vec<String>,
+  /// Children listed by a block but missing from the doc.
+  pub dangling_children: Vec<NativeDanglingChild>,
+  /// Flavours the parser does not handle natively.
+  pub unknown_flavours: Vec<String>,
+}
+
+impl From<ValidationReport> for NativeValidationReport {
+  fn from(report: ValidationReport) -> Self {
+    Self {
+      valid: report.valid,
+      decodable: report.decodable,
+      decode_error: report.decode_error,
+      schema_version: report.schema_version,
+      schema: report.schema.map(Into::into),
+      block_count: report.block_count as u32,
+      root_block_id: report.root_block_id,
+      orphan_block_ids: report.orphan_block_ids,
+      dangling_children: report
+        .dangling_children
+        .into_iter()
+        .map(|child| NativeDanglingChild {
+          parent_block_id: child.parent_block_id,
+          child_block_id: child.child_block_id,
+        })
+        .collect(),
+      unknown_flavours: report.unknown_flavours,
+    }
+  }
+}
+
+/// Reports whether a doc binary decodes, its schema versions, orphan blocks,
+/// children pointing at missing blocks and unknown flavours, so corrupted
+/// docs can be triaged instead of failing with a parse error.
+#[napi]
+pub fn validate_doc_binary(doc_bin: Buffer, doc_id: String) -> DocResult<NativeValidationReport> {
+  doc_parser::validate_doc_binary(&doc_bin, &doc_id)
+    .map(Into::into)
+    .map_err(doc_error)
+}
+
+#[napi(object)]
+pub struct NativeRecoverableError {
+  /// `decode`, `integrate`, `malformed_block`, `missing_root`,
+  /// `dangling_child` or `detached_block`.
+  pub kind: String,
+  pub block_id: Option<String>,
+  pub message: String,
+}
+
+impl From<RecoverableError> for NativeRecoverableError {
+  fn from(error: RecoverableError) -> Self {
+    Self {
+      kind: error.kind.as_str().to_string(),
+      block_id: error.block_id,
+      message: error.message,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeLenientCrawlResult {
+  pub result: NativeCrawlResult,
+  /// Empty when the doc is intact.
+  pub errors: Vec<NativeRecoverableError>,
+}
+
+impl From<LenientCrawlResult> for NativeLenientCrawlResult {
+  fn from(result: LenientCrawlResult) -> Self {
+    Self {
+      result: result.result.into(),
+      errors: result.errors.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Same as `parseDocFromBinary`, salvaging the blocks of a damaged doc
+/// instead of failing: the binary is decoded up to the damage and subtrees
+/// cut off from the root are crawled after it. What was skipped or repaired
+/// is listed in `errors`.
+#[napi]
+pub fn parse_doc_lenient(doc_bin: Buffer, doc_id: String) -> DocResult<NativeLenientCrawlResult> {
+  doc_parser::parse_doc_lenient(doc_bin.into(), doc_id)
+    .map(Into::into)
+    .map_err(doc_error)
+}
+
+/// What went wrong when a doc failed to parse, small enough to attach to a
+/// bug report instead of the doc itself.
+#[napi(object)]
+pub struct NativeDiagnosticBundle {
+  /// The parse error, followed by the errors that caused it.
+  pub error_chain: Vec<String>,
+  /// Offset in the doc binary where decoding stopped, when the binary itself
+  /// is malformed.
+  pub decode_offset: Option<u32>,
+  /// `xxd`-style dump of at most 96 bytes around `decodeOffset`, or of the
+  /// start of the binary without one.
+  pub hexdump: String,
+  pub doc_size: u32,
+  /// Block schema versions of the doc, unset when it does not load.
+  pub schema: Option<NativeSchemaCheck>,
+}
+
+impl From<DiagnosticBundle> for NativeDiagnosticBundle {
+  fn from(bundle: DiagnosticBundle) -> Self {
+    Self {
+      error_chain: bundle.error_chain,
+      decode_offset: bundle.decode_offset.map(|offset| offset as u32),
+      hexdump: bundle.hexdump,
+      doc_size: bundle.doc_size as u32,
+      schema: bundle.schema.map(Into::into),
+    }
+  }
+}
+
+/// Parses a doc and returns the diagnostic bundle of the failure, or nothing
+/// when the doc parses.
+#[napi]
+pub fn diagnose_doc(doc_bin: Buffer, doc_id: String) -> Option<NativeDiagnosticBundle> {
+  doc_parser::parse_doc_from_binary(doc_bin.to_vec(), doc_id)
+    .err()
+    .map(|e| doc_parser::diagnose_parse_failure(&doc_bin, &e).into())
+}
+
+#[napi(object)]
+pub struct NativeSampleCheck {
+  pub name: String,
+  /// Corpus version the sample was added in.
+  pub version: u32,
+  /// What differed from the expected outputs, empty when the sample passed.
+  pub failures: Vec<String>,
+}
+
+impl From<SampleCheck> for NativeSampleCheck {
+  fn from(check: SampleCheck) -> Self {
+    Self {
+      name: check.name,
+      version: check.version,
+      failures: check.failures,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NativeCorpusReport {
+  pub corpus_version: u32,
+  /// Version of the parser that produced the report.
+  pub parser_version: String,
+  pub passed: bool,
+  pub samples: Vec<NativeSampleCheck>,
+}
+
+impl From<CorpusReport> for NativeCorpusReport {
+  fn from(report: CorpusReport) -> Self {
+    Self {
+      corpus_version: report.corpus_version,
+      passed: report.passed(),
+      parser_version: report.parser_version,
+      samples: report.samples.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+pub struct AsyncCheckAgainstCorpus {
+  corpus_dir: Option<String>,
+}
+
+#[napi]
+impl Task for AsyncCheckAgainstCorpus {
+  type Output = DocResult<CorpusReport>;
+  type JsValue = NativeCorpusReport;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(match &self.corpus_dir {
+      Some(corpus_dir) => doc_parser::check_against_corpus(corpus_dir.as_ref()).map_err(doc_error),
+      None => Ok(doc_parser::check_bundled_corpus()),
+    })
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into())
+  }
+}
+
+/// Parses and exports the sample docs bundled with the module and compares
+/// the results with their expected outputs, so a deployment can validate a
+/// module upgrade before rolling it out. `corpus_dir` adds the deployment's
+/// own samples: `<doc id>.ydoc` binaries with optional `<doc id>.ydoc.json`
+/// crawl results and `<doc id>.ydoc.md` markdown next to them.
+#[napi]
+pub fn check_against_corpus(corpus_dir: Option<String>) -> AsyncTask<AsyncCheckAgainstCorpus> {
+  AsyncTask::new(AsyncCheckAgainstCorpus { corpus_dir })
+}
@@ -0,0 +1,184 @@
+use std::{
+  collections::HashMap,
+  sync::{Arc, LazyLock, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use affine_common::vector_index::{VectorIndex as CoreVectorIndex, VectorIndexError};
+use napi::{
+  bindgen_prelude::{Buffer, Float32Array},
+  Error, Result, Status,
+};
+use napi_derive::napi;
+
+/// Indexes created with `VectorIndex.shared`, reachable from every worker
+/// thread of the process.
+static SHARED_INDEXES: LazyLock<Mutex<HashMap<String, Arc<RwLock<CoreVectorIndex>>>>> =
+  LazyLock::new(Default::default);
+
+fn map_err(e: VectorIndexError) -> Error {
+  Error::new(Status::InvalidArg, e.to_string())
+}
+
+fn poisoned<T>(_: T) -> Error {
+  Error::new(
+    Status::GenericFailure,
+    "vector index lock poisoned by a panic",
+  )
+}
+
+#[napi(object)]
+pub struct NativeVectorMatch {
+  pub id: String,
+  /// Cosine similarity, from -1 to 1.
+  pub score: f64,
+}
+
+/// In-memory vector store for semantic search over doc chunks, keyed by
+/// block or chunk id. Results are ranked by cosine similarity.
+///
+/// A handle belongs to the worker thread that created it. To use one index
+/// from several `worker_threads`, every worker opens its own handle with
+/// `VectorIndex.shared` under the same name; handles lock the index
+/// internally, so queries run concurrently and writes wait for them.
+#[napi]
+pub struct VectorIndex {
+  inner: Arc<RwLock<CoreVectorIndex>>,
+}
+
+#[napi]
+impl VectorIndex {
+  #[napi(constructor)]
+  pub fn new(dimensions: u32) -> Self {
+    Self {
+      inner: Arc::new(RwLock::new(CoreVectorIndex::new(dimensions as usize))),
+    }
+  }
+
+  /// Restores an index saved with `toBytes`.
+  #[napi(factory)]
+  pub fn from_bytes(data: Buffer) -> Result<Self> {
+    Ok(Self {
+      inner: Arc::new(RwLock::new(
+        CoreVectorIndex::from_bytes(&data).map_err(map_err)?,
+      )),
+    })
+  }
+
+  /// Opens the process-wide index registered as `name`, creating it empty
+  /// when no worker did yet. Handles opened under the same name in any worker
+  /// see each other's writes.
+  ///
+  /// Fails with `Status::InvalidArg` when the index exists with other
+  /// dimensions.
+  #[napi(factory)]
+  pub fn shared(name: String, dimensions: u32) -> Result<Self> {
+    let mut indexes = SHARED_INDEXES.lock().map_err(poisoned)?;
+    let inner = indexes
+      .entry(name)
+      .or_insert_with(|| Arc::new(RwLock::new(CoreVectorIndex::new(dimensions as usize))))
+      .clone();
+    let existing = inner.read().map_err(poisoned)?.dimensions();
+    if existing != dimensions as usize {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("shared index has {existing} dimensions, not {dimensions}"),
+      ));
+    }
+    Ok(Self { inner })
+  }
+
+  /// Unregisters the shared index `name`, returning whether it existed.
+  /// Handles already open keep using the index until they are collected.
+  #[napi]
+  pub fn release_shared(name: String) -> Result<bool> {
+    let mut indexes = SHARED_INDEXES.lock().map_err(poisoned)?;
+    Ok(indexes.remove(&name).is_some())
+  }
+
+  #[napi(getter)]
+  pub fn dimensions(&self) -> Result<u32> {
+    Ok(self.read()?.dimensions() as u32)
+  }
+
+  #[napi(getter)]
+  pub fn size(&self) -> Result<u32> {
+    Ok(self.read()?.len() as u32)
+  }
+
+  /// Adds a vector, replacing the one stored under the same id.
+  ///
+  /// Fails with `Status::InvalidArg` when the vector does not have the
+  /// dimensions of the index or is all zeros.
+  #[napi]
+  pub fn add(&self, id: String, vector: Float32Array) -> Result<()> {
+    self.write()?.add(&id, &vector).map_err(map_err)
+  }
+
+  /// Removes a vector, returning whether it was stored.
+  #[napi]
+  pub fn remove(&self, id: String) -> Result<bool> {
+    Ok(self.write()?.remove(&id))
+  }
+
+  /// Returns the `limit` entries most similar to `vector`, best first.
+  #[napi]
+  pub fn query(&self, vector: Float32Array, limit: u32) -> Result<Vec<NativeVectorMatch>> {
+    let matches = self
+      .read()?
+      .query(&vector, limit as usize)
+      .map_err(map_err)?;
+    Ok(
+      matches
+        .into_iter()
+        .map(|(id, score)| NativeVectorMatch {
+          id,
+          score: score as f64,
+        })
+        .collect(),
+    )
+  }
+
+  /// Serializes the index so it can be persisted and restored with
+  /// `fromBytes`.
+  #[napi]
+  pub fn to_bytes(&self) -> Result<Buffer> {
+    Ok(self.read()?.to_bytes().into())
+  }
+}
+
+impl VectorIndex {
+  fn read(&self) -> Result<RwLockReadGuard<'_, CoreVectorIndex>> {
+    self.inner.read().map_err(poisoned)
+  }
+
+  fn write(&self) -> Result<RwLockWriteGuard<'_, CoreVectorIndex>> {
+    self.inner.write().map_err(poisoned)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::thread;
+
+  use super::*;
+
+  #[test]
+  fn test_shared_index_across_threads() {
+    let name = "test_shared_index_across_threads".to_string();
+    let writer = VectorIndex::shared(name.clone(), 2).unwrap();
+
+    let reader = {
+      let name = name.clone();
+      thread::spawn(move || VectorIndex::shared(name, 2).unwrap())
+        .join()
+        .unwrap()
+    };
+    writer.write().unwrap().add("a", &[1.0, 0.0]).unwrap();
+    assert_eq!(reader.size().unwrap(), 1);
+
+    assert!(VectorIndex::shared(name.clone(), 3).is_err());
+    assert!(VectorIndex::release_shared(name.clone()).unwrap());
+    assert_eq!(reader.size().unwrap(), 1);
+    assert_eq!(VectorIndex::shared(name, 2).unwrap().size().unwrap(), 0);
+  }
+}
@@ -0,0 +1,296 @@
+use std::path::Path;
+
+use affine_common::{
+  doc_parser::{
+    self, BlockFilter, CrawlOptions, CrawlResult, ExportFormat, ExportResult, ExtrasField,
+    MarkdownOptions, ReferenceEdge, SummaryOptions,
+  },
+  progress::ProgressSink,
+};
+use napi::{bindgen_prelude::*, threadsafe_function::ThreadsafeFunction, Env, Task};
+use napi_derive::napi;
+
+use crate::{
+  doc::{
+    parse_extras_fields, parse_summary_options, NativeCrawlResult, NativeDocMeta, NativeFolderTree,
+  },
+  doc_error::{doc_error, options_error, status_error, task_error, DocResult},
+  encoding::{Encoded, ResultEncoding},
+  export::{parse_filter, NativeExportResult},
+  progress::{JsProgressSink, NativeProgress},
+  references::NativeReferenceEdge,
+};
+
+pub struct AsyncParseDocFromPath {
+  path: String,
+  doc_id: String,
+  checksums: bool,
+  extras: Option<Vec<ExtrasField>>,
+  encoding: ResultEncoding,
+  summary: SummaryOptions,
+}
+
+#[napi]
+impl Task for AsyncParseDocFromPath {
+  type Output = DocResult<Encoded<CrawlResult>>;
+  type JsValue = Either<NativeCrawlResult, Buffer>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(
+      doc_parser::parse_doc_from_path(
+        Path::new(&self.path),
+        std::mem::take(&mut self.doc_id),
+        &CrawlOptions {
+          checksums: self.checksums,
+          extras: self.extras.take(),
+          summary: self.summary.clone(),
+          ..Default::default()
+        },
+      )
+      .map_err(doc_error)
+      .and_then(|result| self.encoding.encode(result).map_err(status_error)),
+    )
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into_js(Into::into))
+  }
+}
+
+/// Same as `parse_doc_from_binary_async`, reading the doc from a snapshot
+/// file at `path`. The file is memory-mapped rather than passed in as a
+/// `Buffer`, so a multi-hundred-MB root doc is never copied into JS or a
+/// second native buffer.
+#[napi]
+pub fn parse_doc_from_path(
+  path: String,
+  doc_id: String,
+  include_checksums: Option<bool>,
+  extras_fields: Option<Vec<String>>,
+  encoding: Option<String>,
+  summary_json: Option<String>,
+) -> DocResult<AsyncTask<AsyncParseDocFromPath>> {
+  Ok(AsyncTask::new(AsyncParseDocFromPath {
+    path,
+    doc_id,
+    checksums: include_checksums.unwrap_or(false),
+    extras: parse_extras_fields(extras_fields).map_err(status_error)?,
+    encoding: ResultEncoding::parse(encoding).map_err(status_error)?,
+    summary: parse_summary_options(summary_json).map_err(status_error)?,
+  }))
+}
+
+/// A workspace stored as files, opened with `load_workspace_dir`. Runs the
+/// workspace-level parsers on its docs, reading them from disk as needed.
+#[napi]
+pub struct WorkspaceDir {
+  inner: doc_parser::WorkspaceDir,
+}
+
+#[napi]
+impl WorkspaceDir {
+  #[napi(getter)]
+  pub fn path(&self) -> String {
+    self.inner.path.to_string_lossy().into_owned()
+  }
+
+  /// Ids of the docs, sorted, the folders doc included.
+  #[napi(getter)]
+  pub fn doc_ids(&self) -> Vec<String> {
+    self.inner.doc_ids.clone()
+  }
+
+  /// Keys of the blobs, sorted.
+  #[napi(getter)]
+  pub fn blob_keys(&self) -> Vec<String> {
+    self.inner.blob_keys.clone()
+  }
+
+  /// Fails with `doc_not_found` when the doc is not in the workspace.
+  #[napi]
+  pub fn read_doc(&self, doc_id: String) -> DocResult<Buffer> {
+    self
+      .inner
+      .read_doc(&doc_id)
+      .map(Into::into)
+      .map_err(doc_error)
+  }
+
+  /// Fails with `doc_not_found` when the blob is not in the workspace.
+  #[napi]
+  pub fn read_blob(&self, key: String) -> DocResult<Buffer> {
+    self
+      .inner
+      .read_blob(&key)
+      .map(Into::into)
+      .map_err(doc_error)
+  }
+
+  /// Same as `parse_doc_from_path` for a doc of the workspace.
+  #[napi]
+  pub fn crawl_doc(
+    &self,
+    doc_id: String,
+    include_checksums: Option<bool>,
+    extras_fields: Option<Vec<String>>,
+    encoding: Option<String>,
+    summary_json: Option<String>,
+  ) -> DocResult<AsyncTask<AsyncCrawlWorkspaceDoc>> {
+    Ok(AsyncTask::new(AsyncCrawlWorkspaceDoc {
+      workspace: self.inner.clone(),
+      doc_id,
+      checksums: include_checksums.unwrap_or(false),
+      extras: parse_extras_fields(extras_fields).map_err(status_error)?,
+      encoding: ResultEncoding::parse(encoding).map_err(status_error)?,
+      summary: parse_summary_options(summary_json).map_err(status_error)?,
+    }))
+  }
+
+  /// Same as `parse_root_doc_meta`, with folders when the workspace has a
+  /// folders doc.
+  #[napi]
+  pub fn doc_metas(&self) -> DocResult<Vec<NativeDocMeta>> {
+    self
+      .inner
+      .doc_metas()
+      .map(|metas| metas.into_iter().map(Into::into).collect())
+      .map_err(doc_error)
+  }
+
+  /// Same as `read_folder_tree_from_root_doc`.
+  #[napi]
+  pub fn folder_tree(&self) -> DocResult<NativeFolderTree> {
+    self.inner.folder_tree().map(Into::into).map_err(doc_error)
+  }
+
+  /// Same as `build_reference_graph` over all docs of the workspace.
+  #[napi]
+  pub fn build_reference_graph(&self) -> AsyncTask<AsyncWorkspaceReferenceGraph> {
+    AsyncTask::new(AsyncWorkspaceReferenceGraph {
+      workspace: self.inner.clone(),
+    })
+  }
+
+  /// Same as `export_workspace` over all docs of the workspace but the
+  /// folders doc.
+  #[napi]
+  pub fn export(
+    &self,
+    format: String,
+    on_progress: Option<ThreadsafeFunction<NativeProgress, ()>>,
+    filter_json: Option<String>,
+  ) -> DocResult<AsyncTask<AsyncExportWorkspaceDir>> {
+    let format = format.parse::<ExportFormat>().map_err(options_error)?;
+    let filter = parse_filter(filter_json).map_err(status_error)?;
+    Ok(AsyncTask::new(AsyncExportWorkspaceDir {
+      workspace: self.inner.clone(),
+      format,
+      filter,
+      progress: on_progress.map(JsProgressSink),
+    }))
+  }
+}
+
+pub struct AsyncCrawlWorkspaceDoc {
+  workspace: doc_parser::WorkspaceDir,
+  doc_id: String,
+  checksums: bool,
+  extras: Option<Vec<ExtrasField>>,
+  encoding: ResultEncoding,
+  summary: SummaryOptions,
+}
+
+#[napi]
+impl Task for AsyncCrawlWorkspaceDoc {
+  type Output = DocResult<Encoded<CrawlResult>>;
+  type JsValue = Either<NativeCrawlResult, Buffer>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(
+      self
+        .workspace
+        .crawl_doc(
+          &self.doc_id,
+          &CrawlOptions {
+            checksums: self.checksums,
+            extras: self.extras.take(),
+            summary: self.summary.clone(),
+            ..Default::default()
+          },
+        )
+        .map_err(doc_error)
+        .and_then(|result| self.encoding.encode(result).map_err(status_error)),
+    )
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into_js(Into::into))
+  }
+}
+
+pub struct AsyncWorkspaceReferenceGraph {
+  workspace: doc_parser::WorkspaceDir,
+}
+
+#[napi]
+impl Task for AsyncWorkspaceReferenceGraph {
+  type Output = DocResult<Vec<ReferenceEdge>>;
+  type JsValue = Vec<NativeReferenceEdge>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(self.workspace.reference_graph().map_err(doc_error))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into_iter().map(Into::into).collect())
+  }
+}
+
+pub struct AsyncExportWorkspaceDir {
+  workspace: doc_parser::WorkspaceDir,
+  format: ExportFormat,
+  filter: Option<BlockFilter>,
+  progress: Option<JsProgressSink>,
+}
+
+#[napi]
+impl Task for AsyncExportWorkspaceDir {
+  type Output = DocResult<Vec<ExportResult>>;
+  type JsValue = Vec<NativeExportResult>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(
+      self
+        .workspace
+        .export(
+          self.format,
+          &MarkdownOptions {
+            filter: self.filter.clone(),
+            ..Default::default()
+          },
+          self.progress.as_ref().map(|sink| sink as &dyn ProgressSink),
+        )
+        .map_err(doc_error),
+    )
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    let output = output.map_err(|e| task_error(env, e))?;
+    Ok(output.into_iter().map(Into::into).collect())
+  }
+}
+
+/// Opens a workspace stored as files, laid out as `root.ydoc`,
+/// `docs/<doc id>.ydoc` and `blobs/<blob key>`, so the desktop app and CLI
+/// tools can work on a workspace without a database.
+///
+/// Fails with `doc_not_found` when there is no root doc.
+#[napi]
+pub fn load_workspace_dir(path: String) -> DocResult<WorkspaceDir> {
+  doc_parser::load_workspace_dir(Path::new(&path))
+    .map(|inner| WorkspaceDir { inner })
+    .map_err(doc_error)
+}
@@ -0,0 +1,170 @@
+// Generated from the Rust definitions of affine_common, do not edit.
+
+export type BuiltinFlavour =
+  | 'affine:page'
+  | 'affine:note'
+  | 'affine:surface'
+  | 'affine:paragraph'
+  | 'affine:list'
+  | 'affine:code'
+  | 'affine:embed-linked-doc'
+  | 'affine:embed-synced-doc'
+  | 'affine:attachment'
+  | 'affine:image'
+  | 'affine:database'
+  | 'affine:latex'
+  | 'affine:table'
+  | 'affine:bookmark'
+  | 'affine:embed-youtube'
+  | 'affine:embed-figma'
+  | 'affine:embed-github'
+  | 'affine:embed-loom';
+
+export type ParseErrorCode =
+  | 'doc_not_found'
+  | 'block_not_found'
+  | 'invalid_binary'
+  | 'sqlite_error'
+  | 'parser_error'
+  | 'unsupported_format'
+  | 'preset_not_found'
+  | 'invalid_options'
+  | 'unknown';
+
+export type AnchorKind =
+  | 'block'
+  | 'heading'
+  | 'text';
+
+export type CompletionKind =
+  | 'doc'
+  | 'heading'
+  | 'tag';
+
+export type ExportFallback =
+  | 'omitted'
+  | 'plaintext'
+  | 'html'
+  | 'link'
+  | 'generic';
+
+export type ExportFormat =
+  | 'markdown';
+
+export type ExtrasField =
+  | 'displayMode'
+  | 'noteBlockId'
+  | 'databaseName';
+
+export type HealReason =
+  | 'exists'
+  | 'content'
+  | 'parent';
+
+export type IndexOp =
+  | 'add'
+  | 'update'
+  | 'delete';
+
+export type LintRule =
+  | 'headingincrement'
+  | 'linelength'
+  | 'bareurl';
+
+export type MarkdownDialect =
+  | 'gfm'
+  | 'commonmark';
+
+export type ReferenceKind =
+  | 'linked'
+  | 'synced'
+  | 'inline';
+
+export type RetentionAction =
+  | 'archive'
+  | 'delete';
+
+export type RetentionReason =
+  | 'age'
+  | 'trash';
+
+export type SecretKind =
+  | 'aws'
+  | 'privatekey'
+  | 'token'
+  | 'credential'
+  | 'entropy';
+
+export type BlobHandling =
+  | { mode: 'omit' }
+  | { mode: 'reference' }
+  | { mode: 'url'; template: string };
+
+export interface AnalyticsOptions {
+  includeTrash?: boolean;
+  noContent?: boolean;
+}
+
+export interface CleanupOptions {
+  collapseBlankRuns?: boolean;
+  removeTrailingEmpty?: boolean;
+  trimTrailingWhitespace?: boolean;
+}
+
+export interface PropRule {
+  key: string;
+  values: string[];
+}
+
+export interface BlockFilter {
+  denyBlockIds?: string[];
+  denyFlavours?: string[];
+  denyProps?: PropRule[];
+}
+
+export interface ExportPreset {
+  aiEditable?: boolean;
+  blobs?: BlobHandling;
+  dialect?: MarkdownDialect;
+  docLinkTemplate?: string | null;
+  filter?: BlockFilter | null;
+  format?: ExportFormat;
+  frontmatter?: boolean;
+  name: string;
+  numberedHeadings?: boolean;
+}
+
+export interface LintRules {
+  bareUrls?: boolean;
+  headingIncrement?: boolean;
+  maxLineLength?: number | null;
+}
+
+export interface RetentionPolicy {
+  archiveAfterDays?: number | null;
+  deleteAfterDays?: number | null;
+  exemptTags?: string[];
+  purgeTrashAfterDays?: number | null;
+}
+
+export interface BlockNode {
+  blockId: string;
+  children: BlockNode[];
+  flavour: string;
+  props: Record<string, unknown>;
+}
+
+export interface BlockTree {
+  blockCount: number;
+  root: BlockNode;
+  title: string;
+}
+
+export type ExtrasVersion = 1;
+
+export interface BlockExtras {
+  databaseName?: string | null;
+  displayMode?: string | null;
+  noteBlockId?: string | null;
+  version?: ExtrasVersion;
+}
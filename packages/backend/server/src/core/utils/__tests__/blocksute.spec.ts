@@ -4,11 +4,18 @@ import { omit } from 'lodash-es';
 import { createModule } from '../../../__tests__/create-module';
 import { Mockers } from '../../../__tests__/mocks';
 import { Models } from '../../../models';
+import {
+  asObjects,
+  DocCrawler,
+  parseYDocFromBinary,
+  parseYDocFromBinaryPacked,
+} from '../../../native';
 import {
   parseDocToMarkdownFromDocSnapshot,
   readAllBlocksFromDocSnapshot,
   readAllDocIdsFromWorkspaceSnapshot,
 } from '../blocksuite';
+import { PackedCrawlResult } from '../packed-crawl';
 
 const module = await createModule({});
 const models = module.get(Models);
@@ -95,3 +102,100 @@ test('can parse doc to markdown from doc snapshot with ai editable', async t =>
 
   t.snapshot(result);
 });
+
+test('can read packed crawl result from doc snapshot', async t => {
+  const expected = asObjects(
+    parseYDocFromBinary(docSnapshot.blob, docSnapshot.id)
+  );
+  const packed = new PackedCrawlResult(
+    await parseYDocFromBinaryPacked(docSnapshot.blob, docSnapshot.id)
+  );
+
+  t.is(packed.title, expected.title);
+  t.is(packed.length, expected.blocks.length);
+  t.is(packed.flavour(0), expected.blocks[0].flavour);
+  t.deepEqual(
+    JSON.parse(JSON.stringify(packed)),
+    JSON.parse(JSON.stringify(expected))
+  );
+  t.throws(() => packed.block(packed.length), { instanceOf: RangeError });
+});
+
+test('can read encoded crawl result from doc snapshot', async t => {
+  const expected = asObjects(
+    parseYDocFromBinary(docSnapshot.blob, docSnapshot.id)
+  );
+  const json = parseYDocFromBinary(
+    docSnapshot.blob,
+    docSnapshot.id,
+    false,
+    null,
+    'json'
+  );
+
+  t.true(Buffer.isBuffer(json));
+  const result = JSON.parse((json as Buffer).toString());
+  t.is(result.title, expected.title);
+  t.is(result.blocks.length, expected.blocks.length);
+  t.is(result.blocks[0].block_id, expected.blocks[0].blockId);
+  t.throws(() => asObjects(json), { instanceOf: TypeError });
+});
+
+test('can crawl doc snapshot in batches', async t => {
+  const expected = asObjects(
+    parseYDocFromBinary(docSnapshot.blob, docSnapshot.id)
+  );
+  const crawler = new DocCrawler(docSnapshot.blob, docSnapshot.id);
+  t.is(crawler.title, expected.title);
+  t.is(crawler.total, expected.blocks.length);
+
+  const blocks: typeof expected.blocks = [];
+  for (let batch = crawler.nextBatch(2); batch.length; ) {
+    t.true(batch.length <= 2);
+    blocks.push(...batch);
+    batch = crawler.nextBatch(2);
+  }
+
+  t.is(crawler.remaining, 0);
+  t.deepEqual(blocks, expected.blocks);
+});
+
+test('can apply crawl hooks to doc snapshot', async t => {
+  const expected = asObjects(
+    parseYDocFromBinary(docSnapshot.blob, docSnapshot.id)
+  );
+  const result = asObjects(
+    parseYDocFromBinary(docSnapshot.blob, docSnapshot.id, false, null, null, {
+      onBlock: block => {
+        if (block.flavour === 'affine:image') return 'drop';
+        if (block.flavour === 'affine:page') {
+          return { ...block, content: ['redacted'] };
+        }
+        return 'keep';
+      },
+    })
+  );
+
+  t.is(
+    result.blocks.length,
+    expected.blocks.filter(block => block.flavour !== 'affine:image').length
+  );
+  t.deepEqual(result.blocks[0].content, ['redacted']);
+  t.is(result.title, expected.title);
+});
+
+test('fails the crawl when a crawl hook throws', async t => {
+  const crawl = (onBlock: () => string) =>
+    parseYDocFromBinary(docSnapshot.blob, docSnapshot.id, false, null, null, {
+      onBlock,
+    });
+
+  t.throws(
+    () =>
+      crawl(() => {
+        throw new Error('policy service down');
+      }),
+    { code: 'unknown', message: /policy service down/ }
+  );
+  t.throws(() => crawl(() => 'skip'), { code: 'invalid_options' });
+});
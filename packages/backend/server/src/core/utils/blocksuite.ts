@@ -1,6 +1,7 @@
 import { Array as YArray, Doc as YDoc, Map as YMap } from 'yjs';
 
 import {
+  asObjects,
   parseYDocFromBinary,
   parseYDocToMarkdown,
   readAllDocIdsFromRootDoc,
@@ -199,7 +200,9 @@ export async function readAllBlocksFromDocSnapshot(
   docId: string,
   docSnapshot: Uint8Array
 ) {
-  const result = parseYDocFromBinary(Buffer.from(docSnapshot), docId);
+  const result = asObjects(
+    parseYDocFromBinary(Buffer.from(docSnapshot), docId)
+  );
 
   return {
     ...result,
@@ -227,10 +230,8 @@ export function parseDocToMarkdownFromDocSnapshot(
   docSnapshot: Uint8Array,
   aiEditable = false
 ) {
-  const parsed = parseYDocToMarkdown(
-    Buffer.from(docSnapshot),
-    docId,
-    aiEditable
+  const parsed = asObjects(
+    parseYDocToMarkdown(Buffer.from(docSnapshot), docId, aiEditable)
   );
 
   return {
@@ -0,0 +1,136 @@
+import type { NativeBlockInfo, NativeCrawlResult } from '@affine/server-native';
+
+// layout written by `pack_crawl_result` in affine_common
+const MAGIC = 'ACR1';
+const NONE = 0xffffffff;
+const BLOCK_STRINGS = 6;
+const BLOCK_LISTS = 4;
+
+const decoder = new TextDecoder();
+
+/**
+ * Reads the buffer returned by `parseYDocFromBinaryPacked` without
+ * unpacking it. Strings are decoded when a field is read, so going over the
+ * block ids and flavours of a large doc does not allocate its content.
+ */
+export class PackedCrawlResult {
+  private readonly view: DataView;
+  private readonly stringCount: number;
+  private readonly stringsStart: number;
+  private readonly blocksStart: number;
+
+  constructor(private readonly buffer: Uint8Array) {
+    this.view = new DataView(
+      buffer.buffer,
+      buffer.byteOffset,
+      buffer.byteLength
+    );
+    if (
+      buffer.byteLength < 8 ||
+      decoder.decode(buffer.subarray(0, 4)) !== MAGIC
+    ) {
+      throw new Error('Invalid packed crawl result');
+    }
+
+    this.stringCount = this.u32(4);
+    this.stringsStart = 8 + 4 * this.stringCount;
+    const stringsLength = this.stringCount
+      ? this.u32(this.stringsStart - 4)
+      : 0;
+    this.blocksStart = this.stringsStart + stringsLength;
+  }
+
+  get title(): string {
+    return this.string(this.u32(this.blocksStart)) ?? '';
+  }
+
+  get summary(): string {
+    return this.string(this.u32(this.blocksStart + 4)) ?? '';
+  }
+
+  get length(): number {
+    return this.u32(this.blocksStart + 8);
+  }
+
+  blockId(index: number): string {
+    return this.string(this.u32(this.blockOffset(index))) ?? '';
+  }
+
+  flavour(index: number): string {
+    return this.string(this.u32(this.blockOffset(index) + 4)) ?? '';
+  }
+
+  block(index: number): NativeBlockInfo {
+    let offset = this.blockOffset(index);
+    const strings: (string | undefined)[] = [];
+    for (let i = 0; i < BLOCK_STRINGS; i++, offset += 4) {
+      strings.push(this.string(this.u32(offset)));
+    }
+    const lists: (string[] | undefined)[] = [];
+    for (let i = 0; i < BLOCK_LISTS; i++) {
+      const length = this.u32(offset);
+      offset += 4;
+      if (length === NONE) {
+        lists.push(undefined);
+        continue;
+      }
+      const items: string[] = [];
+      for (let j = 0; j < length; j++, offset += 4) {
+        items.push(this.string(this.u32(offset)) ?? '');
+      }
+      lists.push(items);
+    }
+
+    const [blockId, flavour, parentFlavour, parentBlockId] = strings;
+    const [additional, checksum] = strings.slice(4);
+    const [content, blob, refDocId, refInfo] = lists;
+    return {
+      blockId: blockId ?? '',
+      flavour: flavour ?? '',
+      content,
+      blob,
+      refDocId,
+      refInfo,
+      parentFlavour,
+      parentBlockId,
+      additional,
+      checksum,
+    };
+  }
+
+  *blocks(): IterableIterator<NativeBlockInfo> {
+    for (let i = 0; i < this.length; i++) {
+      yield this.block(i);
+    }
+  }
+
+  toJSON(): NativeCrawlResult {
+    return {
+      title: this.title,
+      summary: this.summary,
+      blocks: Array.from(this.blocks()),
+    };
+  }
+
+  private blockOffset(index: number) {
+    if (index < 0 || index >= this.length) {
+      throw new RangeError(`Block index ${index} out of range`);
+    }
+    return this.u32(this.blocksStart + 12 + 4 * index);
+  }
+
+  private string(index: number): string | undefined {
+    if (index === NONE || index >= this.stringCount) {
+      return undefined;
+    }
+    const start = index ? this.u32(4 + 4 * index) : 0;
+    const end = this.u32(8 + 4 * index);
+    return decoder.decode(
+      this.buffer.subarray(this.stringsStart + start, this.stringsStart + end)
+    );
+  }
+
+  private u32(offset: number) {
+    return this.view.getUint32(offset, true);
+  }
+}
@@ -1,6 +1,14 @@
 import serverNativeModule, { type Tokenizer } from '@affine/server-native';
 
 export const mergeUpdatesInApplyWay = serverNativeModule.mergeUpdatesInApplyWay;
+export const mergeYDocUpdates = serverNativeModule.mergeUpdates;
+export const compactYDocSnapshot = serverNativeModule.compactSnapshot;
+export const encodeYDocStateVector = serverNativeModule.encodeStateVector;
+export const diffYDocUpdate = serverNativeModule.diffUpdate;
+export const analyzeYDocUpdateStream = serverNativeModule.analyzeUpdateStream;
+export const shouldSnapshotYDoc = serverNativeModule.shouldSnapshot;
+export const makeLabeledYDocSnapshot = serverNativeModule.makeLabeledSnapshot;
+export const readLabeledYDocSnapshot = serverNativeModule.readLabeledSnapshot;
 
 export const verifyChallengeResponse = async (
   response: any,
@@ -37,13 +45,75 @@ export function getTokenEncoder(model?: string | null): Tokenizer | null {
   }
 }
 
+/**
+ * Narrows the result of a native parse function called without an
+ * `encoding`, which only returns a `Buffer` for `msgpack` and `json`.
+ */
+export function asObjects<T>(result: T | Buffer): T {
+  if (Buffer.isBuffer(result)) {
+    throw new TypeError('Expected native result objects, got a buffer');
+  }
+  return result;
+}
+
 export const getMime = serverNativeModule.getMime;
 export const parseDoc = serverNativeModule.parseDoc;
 export const htmlSanitize = serverNativeModule.htmlSanitize;
 export const parseYDocFromBinary = serverNativeModule.parseDocFromBinary;
+export const parseYDocFromBinaryAsync =
+  serverNativeModule.parseDocFromBinaryAsync;
+export const parseYDocFromBinaryPacked =
+  serverNativeModule.parseDocFromBinaryPacked;
+export const parseYDocFromPath = serverNativeModule.parseDocFromPath;
+export const parseYDocLenient = serverNativeModule.parseDocLenient;
+export const NativeDocError = serverNativeModule.NativeDocError;
+export const DocCrawler = serverNativeModule.DocCrawler;
+export const SearchIndex = serverNativeModule.SearchIndex;
+export const parseYDocsFromBinaryBatch =
+  serverNativeModule.parseDocsFromBinaryBatch;
+export const parseYDocsFromBinaryBatchInterned =
+  serverNativeModule.parseDocsFromBinaryBatchInterned;
+export const buildReferenceGraph = serverNativeModule.buildReferenceGraph;
+export const loadWorkspaceDir = serverNativeModule.loadWorkspaceDir;
+export const WorkspaceDir = serverNativeModule.WorkspaceDir;
+export const parseBlockAdditional = serverNativeModule.parseAdditional;
+export const parseYDocDelta = serverNativeModule.parseDocDelta;
+export const diffYDocs = serverNativeModule.diffDocs;
+export const canParseYDoc = serverNativeModule.canParse;
+export const diagnoseYDoc = serverNativeModule.diagnoseDoc;
+export const validateYDocBinary = serverNativeModule.validateDocBinary;
+export const checkNativeCorpus = serverNativeModule.checkAgainstCorpus;
+export const getSupportedSchemaRange =
+  serverNativeModule.getSupportedSchemaRange;
 export const parseYDocToMarkdown = serverNativeModule.parseDocToMarkdown;
+export const parseYBlockToMarkdown = serverNativeModule.parseBlockToMarkdown;
+export const parseYDocToJson = serverNativeModule.parseDocToJson;
+export const parseYDocDatabaseBlock = serverNativeModule.parseDatabaseBlock;
+export const parseYDocToText = serverNativeModule.parseDocToText;
+export const readYDocTitle = serverNativeModule.readDocTitle;
+export const extractYDocBlobManifest = serverNativeModule.extractBlobManifest;
+export const extractYDocTemplateVariables =
+  serverNativeModule.extractTemplateVariables;
+export const classifyYDoc = serverNativeModule.classifyDoc;
+export const readYDocStats = serverNativeModule.docStats;
+export const chunkYDocForEmbedding = serverNativeModule.chunkDocForEmbedding;
+export const generateYDocSnippets = serverNativeModule.generateSnippets;
+export const sampleYDoc = serverNativeModule.sampleDoc;
+export const packContext = serverNativeModule.packContext;
+export const verifyGrounding = serverNativeModule.verifyGrounding;
+export const parseYDocToMarkdownAsync =
+  serverNativeModule.parseDocToMarkdownAsync;
+export const parseYDocToHtml = serverNativeModule.parseDocToHtml;
+export const parseYDocToHtmlAsync = serverNativeModule.parseDocToHtmlAsync;
+export const markdownToYDocBinary = serverNativeModule.markdownToDocBinary;
 export const readAllDocIdsFromRootDoc =
   serverNativeModule.readAllDocIdsFromRootDoc;
+export const readAllDocIdsFromRootDocAsync =
+  serverNativeModule.readAllDocIdsFromRootDocAsync;
+export const parseRootDocMeta = serverNativeModule.parseRootDocMeta;
+export const readFolderTreeFromRootDoc =
+  serverNativeModule.readFolderTreeFromRootDoc;
+export const setNativeLogSubscriber = serverNativeModule.setLogSubscriber;
 export const AFFINE_PRO_PUBLIC_KEY = serverNativeModule.AFFINE_PRO_PUBLIC_KEY;
 export const AFFINE_PRO_LICENSE_AES_KEY =
   serverNativeModule.AFFINE_PRO_LICENSE_AES_KEY;
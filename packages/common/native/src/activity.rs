@@ -0,0 +1,216 @@
+//! Recently edited docs and per-user activity from the update history of a
+//! workspace.
+
+use std::collections::{BTreeMap, HashMap};
+
+/// One stored update of a doc.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocUpdate {
+  /// Milliseconds since the Unix epoch.
+  pub timestamp: i64,
+  /// Missing for updates applied by the server or by a deleted account.
+  pub editor_id: Option<String>,
+}
+
+/// Time range of the updates to aggregate, in milliseconds since the Unix
+/// epoch. `since` is inclusive, `until` exclusive, and a missing bound leaves
+/// the range open on that side.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ActivityWindow {
+  pub since: Option<i64>,
+  pub until: Option<i64>,
+}
+
+impl ActivityWindow {
+  pub fn contains(&self, timestamp: i64) -> bool {
+    self.since.is_none_or(|since| timestamp >= since)
+      && self.until.is_none_or(|until| timestamp < until)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocActivity {
+  pub doc_id: String,
+  pub edits: usize,
+  pub last_edit: i64,
+  /// Known editors of the doc, most recent first.
+  pub editors: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserActivity {
+  pub user_id: String,
+  pub edits: usize,
+  /// Number of distinct docs edited.
+  pub docs: usize,
+  pub last_edit: i64,
+  pub last_doc_id: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivityFeed {
+  /// Docs with at least one update in the window, most recently edited first.
+  pub docs: Vec<DocActivity>,
+  /// Users with at least one update in the window, most recently active
+  /// first.
+  pub users: Vec<UserActivity>,
+}
+
+/// Aggregates the updates of each doc that fall in `window` into an activity
+/// feed. Ties between equally recent docs or users are broken by id, so the
+/// feed does not depend on the order of the input.
+pub fn compute_activity(
+  updates_by_doc: &HashMap<String, Vec<DocUpdate>>,
+  window: &ActivityWindow,
+) -> ActivityFeed {
+  let mut docs = Vec::new();
+  // user -> (edits, last edit, doc of the last edit, edited docs)
+  let mut users: BTreeMap<&str, (usize, i64, &str, usize)> = BTreeMap::new();
+
+  for (doc_id, updates) in updates_by_doc {
+    let mut edits = 0;
+    let mut last_edit = i64::MIN;
+    let mut editors: HashMap<&str, (usize, i64)> = HashMap::new();
+    for update in updates
+      .iter()
+      .filter(|update| window.contains(update.timestamp))
+    {
+      edits += 1;
+      last_edit = last_edit.max(update.timestamp);
+      if let Some(editor_id) = &update.editor_id {
+        let (count, last) = editors.entry(editor_id.as_str()).or_insert((0, i64::MIN));
+        *count += 1;
+        *last = (*last).max(update.timestamp);
+      }
+    }
+    if edits == 0 {
+      continue;
+    }
+
+    for (editor_id, (count, last)) in &editors {
+      let user = users
+        .entry(*editor_id)
+        .or_insert((0, i64::MIN, doc_id.as_str(), 0));
+      user.0 += count;
+      user.3 += 1;
+      if *last > user.1 || (*last == user.1 && doc_id.as_str() < user.2) {
+        user.1 = *last;
+        user.2 = doc_id.as_str();
+      }
+    }
+
+    let mut editors = editors.into_iter().collect::<Vec<_>>();
+    editors.sort_by(|(a_id, (_, a_last)), (b_id, (_, b_last))| {
+      b_last.cmp(a_last).then_with(|| a_id.cmp(b_id))
+    });
+    docs.push(DocActivity {
+      doc_id: doc_id.clone(),
+      edits,
+      last_edit,
+      editors: editors
+        .into_iter()
+        .map(|(editor_id, _)| editor_id.to_string())
+        .collect(),
+    });
+  }
+
+  docs.sort_by(|a, b| {
+    b.last_edit
+      .cmp(&a.last_edit)
+      .then_with(|| a.doc_id.cmp(&b.doc_id))
+  });
+  let mut users = users
+    .into_iter()
+    .map(
+      |(user_id, (edits, last_edit, last_doc_id, docs))| UserActivity {
+        user_id: user_id.to_string(),
+        edits,
+        docs,
+        last_edit,
+        last_doc_id: last_doc_id.to_string(),
+      },
+    )
+    .collect::<Vec<_>>();
+  users.sort_by(|a, b| {
+    b.last_edit
+      .cmp(&a.last_edit)
+      .then_with(|| a.user_id.cmp(&b.user_id))
+  });
+
+  ActivityFeed { docs, users }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_compute_activity() {
+    let update = |timestamp, editor_id: Option<&str>| DocUpdate {
+      timestamp,
+      editor_id: editor_id.map(Into::into),
+    };
+    let updates_by_doc = HashMap::from([
+      (
+        "a".to_string(),
+        vec![
+          update(100, Some("alice")),
+          update(300, Some("bob")),
+          update(200, Some("alice")),
+          update(900, Some("alice")),
+        ],
+      ),
+      (
+        "b".to_string(),
+        vec![update(250, Some("alice")), update(400, None)],
+      ),
+      ("c".to_string(), vec![update(50, Some("bob"))]),
+    ]);
+    let window = ActivityWindow {
+      since: Some(100),
+      until: Some(900),
+    };
+
+    let feed = compute_activity(&updates_by_doc, &window);
+    assert_eq!(
+      feed.docs,
+      vec![
+        DocActivity {
+          doc_id: "b".into(),
+          edits: 2,
+          last_edit: 400,
+          editors: vec!["alice".into()],
+        },
+        DocActivity {
+          doc_id: "a".into(),
+          edits: 3,
+          last_edit: 300,
+          editors: vec!["bob".into(), "alice".into()],
+        },
+      ]
+    );
+    assert_eq!(
+      feed.users,
+      vec![
+        UserActivity {
+          user_id: "bob".into(),
+          edits: 1,
+          docs: 1,
+          last_edit: 300,
+          last_doc_id: "a".into(),
+        },
+        UserActivity {
+          user_id: "alice".into(),
+          edits: 3,
+          docs: 2,
+          last_edit: 250,
+          last_doc_id: "b".into(),
+        },
+      ]
+    );
+
+    let everything = compute_activity(&updates_by_doc, &ActivityWindow::default());
+    assert_eq!(everything.docs.len(), 3);
+    assert_eq!(everything.docs[0].doc_id, "a");
+  }
+}
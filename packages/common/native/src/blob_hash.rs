@@ -0,0 +1,52 @@
+use sha2::{Digest, Sha256};
+
+/// Computes blob keys incrementally, for blobs read in chunks.
+///
+/// Keys are the SHA-256 digest of the content in padded, URL safe base64,
+/// the scheme the editor uses when it uploads blobs, so identical content
+/// always maps to the same key.
+#[derive(Default, Clone)]
+pub struct BlobHasher {
+  hasher: Sha256,
+}
+
+impl BlobHasher {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn update(&mut self, chunk: &[u8]) {
+    self.hasher.update(chunk);
+  }
+
+  pub fn finalize(self) -> String {
+    base64_simd::URL_SAFE.encode_to_string(self.hasher.finalize())
+  }
+}
+
+/// Returns the blob key of `bytes`, see [`BlobHasher`].
+pub fn hash_blob(bytes: &[u8]) -> String {
+  let mut hasher = BlobHasher::new();
+  hasher.update(bytes);
+  hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_hash_blob() {
+    // base64 of the SHA-256 digest of "hello world" with `+` and `/` replaced
+    assert_eq!(
+      hash_blob(b"hello world"),
+      "uU0nuZNNPgilLlLX2n2r-sSE7-N6U4DukIj3rOLvzek="
+    );
+
+    let mut hasher = BlobHasher::new();
+    for chunk in b"hello world".chunks(3) {
+      hasher.update(chunk);
+    }
+    assert_eq!(hasher.finalize(), hash_blob(b"hello world"));
+  }
+}
@@ -0,0 +1,261 @@
+//! Packing of retrieved content into a model context window.
+
+use std::{collections::HashMap, str::FromStr};
+
+/// Separator written between two packed chunks.
+const CHUNK_SEPARATOR: &str = "\n\n";
+
+/// A piece of content that may go into the context, e.g. a whole doc or a
+/// search hit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextChunk {
+  pub doc_id: String,
+  pub block_id: Option<String>,
+  pub text: String,
+  /// Relevance of the chunk, higher is better.
+  pub score: f64,
+}
+
+/// How chunks are picked and ordered, see [`pack_context`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PackStrategy {
+  /// Best chunks first.
+  #[default]
+  Relevance,
+  /// Best chunks are picked, then written in input order.
+  DocumentOrder,
+  /// One chunk per doc in turn, so a single long doc cannot take the whole
+  /// budget.
+  RoundRobin,
+}
+
+impl PackStrategy {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      PackStrategy::Relevance => "relevance",
+      PackStrategy::DocumentOrder => "document_order",
+      PackStrategy::RoundRobin => "round_robin",
+    }
+  }
+}
+
+impl FromStr for PackStrategy {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "relevance" => Ok(PackStrategy::Relevance),
+      "document_order" => Ok(PackStrategy::DocumentOrder),
+      "round_robin" => Ok(PackStrategy::RoundRobin),
+      _ => Err(format!("unknown pack strategy: {s}")),
+    }
+  }
+}
+
+/// Where a packed chunk landed in the packed text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Citation {
+  /// Offsets in UTF-16 units, so they index JavaScript strings directly.
+  pub start: usize,
+  pub end: usize,
+  pub doc_id: String,
+  pub block_id: Option<String>,
+  /// Position of the chunk in the input.
+  pub chunk_index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedContext {
+  pub text: String,
+  /// One entry per packed chunk, in text order.
+  pub citations: Vec<Citation>,
+  /// Tokens of `text`, separators included, at most the budget.
+  pub used_tokens: usize,
+  /// Chunks left out because they did not fit.
+  pub omitted: usize,
+}
+
+/// Selects and orders chunks to fit `token_budget`, returning the packed text
+/// and a citation map from its spans back to the docs and blocks they came
+/// from.
+///
+/// Chunks are taken in the order of `strategy` and skipped, never cut, when
+/// they do not fit, so a smaller chunk further down may still get in. Empty
+/// chunks are ignored. Ties in score keep the input order, so the output is
+/// deterministic. `count_tokens` is the tokenizer of the target model.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::context_pack::{pack_context, ContextChunk, PackStrategy};
+///
+/// let chunk = |doc_id: &str, text: &str, score| ContextChunk {
+///   doc_id: doc_id.into(),
+///   block_id: None,
+///   text: text.into(),
+///   score,
+/// };
+/// let chunks = [chunk("a", "alpha", 0.2), chunk("b", "beta", 0.9)];
+/// let words = |text: &str| text.split_whitespace().count();
+/// let packed = pack_context(&chunks, 10, PackStrategy::Relevance, words);
+/// assert_eq!(packed.text, "beta\n\nalpha");
+/// assert_eq!(packed.citations[0].doc_id, "b");
+/// ```
+pub fn pack_context(
+  chunks: &[ContextChunk],
+  token_budget: usize,
+  strategy: PackStrategy,
+  count_tokens: impl Fn(&str) -> usize,
+) -> PackedContext {
+  let mut by_score = (0..chunks.len())
+    .filter(|&i| !chunks[i].text.trim().is_empty())
+    .collect::<Vec<_>>();
+  // the sort is stable, equal scores keep the input order
+  by_score.sort_by(|&a, &b| chunks[b].score.total_cmp(&chunks[a].score));
+
+  let order = match strategy {
+    PackStrategy::Relevance | PackStrategy::DocumentOrder => by_score,
+    PackStrategy::RoundRobin => {
+      // docs take turns in the order of their best chunk
+      let mut docs: Vec<Vec<usize>> = Vec::new();
+      let mut positions: HashMap<&str, usize> = HashMap::new();
+      for i in by_score {
+        let position = *positions
+          .entry(chunks[i].doc_id.as_str())
+          .or_insert_with(|| {
+            docs.push(vec![]);
+            docs.len() - 1
+          });
+        docs[position].push(i);
+      }
+      let rounds = docs.iter().map(Vec::len).max().unwrap_or(0);
+      (0..rounds)
+        .flat_map(|round| docs.iter().filter_map(move |doc| doc.get(round).copied()))
+        .collect()
+    }
+  };
+
+  let separator_tokens = count_tokens(CHUNK_SEPARATOR);
+  let mut picked = Vec::new();
+  let mut used_tokens = 0;
+  let mut omitted = 0;
+  for i in order {
+    let mut tokens = count_tokens(&chunks[i].text);
+    if !picked.is_empty() {
+      tokens += separator_tokens;
+    }
+    if used_tokens + tokens > token_budget {
+      omitted += 1;
+      continue;
+    }
+    used_tokens += tokens;
+    picked.push(i);
+  }
+  if strategy == PackStrategy::DocumentOrder {
+    picked.sort();
+  }
+
+  let mut text = String::new();
+  let mut offset = 0;
+  let mut citations = Vec::with_capacity(picked.len());
+  for i in picked {
+    let chunk = &chunks[i];
+    if !text.is_empty() {
+      text.push_str(CHUNK_SEPARATOR);
+      offset += CHUNK_SEPARATOR.len();
+    }
+    text.push_str(&chunk.text);
+    let end = offset + chunk.text.encode_utf16().count();
+    citations.push(Citation {
+      start: offset,
+      end,
+      doc_id: chunk.doc_id.clone(),
+      block_id: chunk.block_id.clone(),
+      chunk_index: i,
+    });
+    offset = end;
+  }
+
+  PackedContext {
+    text,
+    citations,
+    used_tokens,
+    omitted,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn chunk(doc_id: &str, block_id: &str, text: &str, score: f64) -> ContextChunk {
+    ContextChunk {
+      doc_id: doc_id.into(),
+      block_id: Some(block_id.into()),
+      text: text.into(),
+      score,
+    }
+  }
+
+  fn words(text: &str) -> usize {
+    text.split_whitespace().count()
+  }
+
+  #[test]
+  fn test_pack_context() {
+    let chunks = [
+      chunk("a", "a1", "one two three", 0.9),
+      chunk("a", "a2", "four five", 0.8),
+      chunk("b", "b1", "six", 0.5),
+      chunk("c", "c1", "  ", 1.0),
+      chunk("b", "b2", "seven eight nine ten", 0.1),
+    ];
+
+    let packed = pack_context(&chunks, 6, PackStrategy::Relevance, words);
+    assert_eq!(packed.text, "one two three\n\nfour five\n\nsix");
+    assert_eq!(packed.used_tokens, 6);
+    assert_eq!(packed.omitted, 1);
+    let spans = packed
+      .citations
+      .iter()
+      .map(|citation| (citation.start, citation.end, citation.block_id.as_deref()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      spans,
+      [
+        (0, 13, Some("a1")),
+        (15, 24, Some("a2")),
+        (26, 29, Some("b1"))
+      ]
+    );
+    for citation in &packed.citations {
+      assert_eq!(
+        &packed.text[citation.start..citation.end],
+        chunks[citation.chunk_index].text
+      );
+    }
+
+    let packed = pack_context(&chunks, 4, PackStrategy::RoundRobin, words);
+    assert_eq!(packed.text, "one two three\n\nsix");
+    assert_eq!(packed.omitted, 2);
+
+    let packed = pack_context(&chunks, 100, PackStrategy::DocumentOrder, words);
+    assert_eq!(
+      packed
+        .citations
+        .iter()
+        .map(|citation| citation.chunk_index)
+        .collect::<Vec<_>>(),
+      [0, 1, 2, 4]
+    );
+
+    // offsets count UTF-16 units
+    let chunks = [chunk("a", "a1", "日本", 1.0), chunk("a", "a2", "x", 0.5)];
+    let packed = pack_context(&chunks, 100, PackStrategy::Relevance, words);
+    assert_eq!(packed.citations[1].start, 4);
+    assert_eq!(
+      "x".parse::<PackStrategy>(),
+      Err("unknown pack strategy: x".to_string())
+    );
+  }
+}
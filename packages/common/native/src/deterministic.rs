@@ -0,0 +1,42 @@
+//! A process-wide switch trading performance and freshness for reproducible
+//! output, meant for snapshot tests.
+//!
+//! While enabled:
+//! - docs are decoded with a fixed client id instead of a random one,
+//! - ties that would otherwise be broken by hash map iteration order are broken
+//!   by id,
+//! - the current time, e.g. of labeled snapshots or the defaults of snapshot
+//!   and retention policies, is the Unix epoch,
+//! - batches are processed on the calling thread instead of a thread pool.
+//!
+//! Hashcash stamps are not affected, a reproducible stamp would be a valid
+//! proof of work for anyone.
+//!
+//! Caches added to the crate must be bypassed when
+//! [`is_deterministic`] returns `true`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::Utc;
+
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+
+/// Client id of docs created while deterministic mode is enabled.
+pub const DETERMINISTIC_CLIENT_ID: u64 = 1;
+
+pub fn set_deterministic_mode(enabled: bool) {
+  DETERMINISTIC.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_deterministic() -> bool {
+  DETERMINISTIC.load(Ordering::Relaxed)
+}
+
+/// Milliseconds since the Unix epoch, `0` in deterministic mode.
+pub fn now_millis() -> i64 {
+  if is_deterministic() {
+    0
+  } else {
+    Utc::now().timestamp_millis()
+  }
+}
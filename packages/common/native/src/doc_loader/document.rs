@@ -5,6 +5,7 @@ use std::{
 };
 
 use path_ext::PathExt;
+use tracing::instrument;
 
 use super::*;
 
@@ -33,23 +34,29 @@ pub struct Doc {
   pub chunks: Vec<Chunk>,
 }
 
-impl Doc {
-  pub fn new(file_path: &str, doc: &[u8]) -> LoaderResult<Self> {
-    Self::with_options(file_path, doc, DocOptions::default())
-  }
+/// How a file is loaded, decided from its content and path.
+pub(crate) enum FileKind {
+  Pdf,
+  Docx,
+  Html,
+  Text(String),
+  /// Content no loader handles, with its type when it could be inferred.
+  Other(Option<infer::Type>),
+}
 
-  pub fn with_options(file_path: &str, doc: &[u8], options: DocOptions) -> LoaderResult<Self> {
+impl FileKind {
+  pub(crate) fn detect(file_path: &str, doc: &[u8]) -> Self {
     if let Some(kind) =
       infer::get(&doc[..4096.min(doc.len())]).or(infer::get_from_path(file_path).ok().flatten())
     {
-      if kind.extension() == "pdf" {
-        return Self::load_pdf(file_path, doc);
-      } else if kind.extension() == "docx" {
-        return Self::load_docx(file_path, doc);
-      } else if kind.extension() == "html" {
-        return Self::load_html(file_path, doc);
-      }
-    } else if let Ok(string) = String::from_utf8(doc.to_vec()).or_else(|_| {
+      return match kind.extension() {
+        "pdf" => FileKind::Pdf,
+        "docx" => FileKind::Docx,
+        "html" => FileKind::Html,
+        _ => FileKind::Other(Some(kind)),
+      };
+    }
+    match String::from_utf8(doc.to_vec()).or_else(|_| {
       String::from_utf16(
         &doc
           .chunks_exact(2)
@@ -57,30 +64,50 @@ impl Doc {
           .collect::<Vec<_>>(),
       )
     }) {
-      let path = PathBuf::from(file_path);
-      match path.ext_str() {
-        "md" => {
-          let loader = TextLoader::new(string);
-          let splitter = MarkdownSplitter::default();
-          return Self::from_loader(file_path, loader, splitter);
-        }
-        "rs" | "c" | "cpp" | "h" | "hpp" | "js" | "ts" | "tsx" | "go" | "py" => {
-          let name = path.full_str().to_string();
-          let loader =
-            SourceCodeLoader::from_string(string).with_parser_option(LanguageParserOptions {
-              language: get_language_by_filename(&name)?,
-              parser_threshold: options.code_threshold,
-            });
-          let splitter = TokenSplitter::default();
-          return Self::from_loader(file_path, loader, splitter);
+      Ok(string) => FileKind::Text(string),
+      Err(_) => FileKind::Other(None),
+    }
+  }
+}
+
+impl Doc {
+  pub fn new(file_path: &str, doc: &[u8]) -> LoaderResult<Self> {
+    Self::with_options(file_path, doc, DocOptions::default())
+  }
+
+  #[instrument(name = "load", skip_all, fields(file_path = %file_path, bytes = doc.len()), err)]
+  pub fn with_options(file_path: &str, doc: &[u8], options: DocOptions) -> LoaderResult<Self> {
+    match FileKind::detect(file_path, doc) {
+      FileKind::Pdf => Self::load_pdf(file_path, doc),
+      FileKind::Docx => Self::load_docx(file_path, doc),
+      FileKind::Html => Self::load_html(file_path, doc),
+      FileKind::Text(string) => {
+        let path = PathBuf::from(file_path);
+        match path.ext_str() {
+          "md" => {
+            let loader = TextLoader::new(string);
+            let splitter = MarkdownSplitter::default();
+            Self::from_loader(file_path, loader, splitter)
+          }
+          "rs" | "c" | "cpp" | "h" | "hpp" | "js" | "ts" | "tsx" | "go" | "py" => {
+            let name = path.full_str().to_string();
+            let loader =
+              SourceCodeLoader::from_string(string).with_parser_option(LanguageParserOptions {
+                language: get_language_by_filename(&name)?,
+                parser_threshold: options.code_threshold,
+              });
+            let splitter = TokenSplitter::default();
+            Self::from_loader(file_path, loader, splitter)
+          }
+          _ => {
+            let loader = TextLoader::new(string);
+            let splitter = TokenSplitter::default();
+            Self::from_loader(file_path, loader, splitter)
+          }
         }
-        _ => {}
       }
-      let loader = TextLoader::new(string);
-      let splitter = TokenSplitter::default();
-      return Self::from_loader(file_path, loader, splitter);
+      FileKind::Other(_) => Err(LoaderError::Other("Failed to infer document type".into())),
     }
-    Err(LoaderError::Other("Failed to infer document type".into()))
   }
 
   fn from_loader(
@@ -0,0 +1,315 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use tracing::instrument;
+
+use super::{document::FileKind, *};
+use crate::progress::{Progress, ProgressSink};
+
+const MAGIC: &[u8; 4] = b"AFIC";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4;
+
+/// Remembers which files of a long running import were already imported, so
+/// an interrupted import can resume where it stopped.
+///
+/// Files are identified by a stable hash of their path. The encoded form is
+/// opaque to callers and survives restarts and upgrades.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportCheckpoint {
+  imported: BTreeSet<u64>,
+}
+
+impl ImportCheckpoint {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn from_bytes(bytes: &[u8]) -> LoaderResult<Self> {
+    let invalid = || LoaderError::Other("Invalid import checkpoint".into());
+    if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+      return Err(invalid());
+    }
+    if bytes[MAGIC.len()] != VERSION {
+      return Err(LoaderError::Other(format!(
+        "Unsupported import checkpoint version {}",
+        bytes[MAGIC.len()]
+      )));
+    }
+
+    let count = u32::from_le_bytes(bytes[MAGIC.len() + 1..HEADER_LEN].try_into().unwrap());
+    let hashes = &bytes[HEADER_LEN..];
+    if hashes.len() != count as usize * 8 {
+      return Err(invalid());
+    }
+
+    Ok(Self {
+      imported: hashes
+        .chunks_exact(8)
+        .map(|hash| u64::from_le_bytes(hash.try_into().unwrap()))
+        .collect(),
+    })
+  }
+
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + self.imported.len() * 8);
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&(self.imported.len() as u32).to_le_bytes());
+    for hash in &self.imported {
+      bytes.extend_from_slice(&hash.to_le_bytes());
+    }
+    bytes
+  }
+
+  pub fn contains(&self, file_path: &str) -> bool {
+    self.imported.contains(&path_hash(file_path))
+  }
+
+  pub fn mark_imported(&mut self, file_path: &str) {
+    self.imported.insert(path_hash(file_path));
+  }
+
+  pub fn len(&self) -> usize {
+    self.imported.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.imported.is_empty()
+  }
+}
+
+/// The outcome of importing one file.
+pub struct ImportedFile {
+  pub file_path: String,
+  pub doc: LoaderResult<Doc>,
+}
+
+/// Loads a batch of files, skipping the ones `checkpoint` already records and
+/// recording the ones that load successfully.
+///
+/// Large imports are fed in batches, persisting the checkpoint after each
+/// one; when an import is interrupted it is restarted from the first batch
+/// with the last persisted checkpoint. Files that fail to load are returned
+/// with their error and stay unrecorded, so a resumed import retries them.
+/// Progress is reported under the `"import"` stage after every file of the
+/// batch, skipped ones included.
+#[instrument(name = "import", skip_all, fields(files = files.len()))]
+pub fn import_files(
+  files: Vec<(String, Vec<u8>)>,
+  checkpoint: &mut ImportCheckpoint,
+  progress: Option<&dyn ProgressSink>,
+) -> Vec<ImportedFile> {
+  let total = files.len();
+  let mut imported = Vec::new();
+  for (done, (file_path, content)) in files.into_iter().enumerate() {
+    if !checkpoint.contains(&file_path) {
+      let doc = Doc::new(&file_path, &content);
+      if doc.is_ok() {
+        checkpoint.mark_imported(&file_path);
+      }
+      imported.push(ImportedFile { file_path, doc });
+    }
+    if let Some(progress) = progress {
+      progress.report(Progress {
+        stage: "import",
+        done: done + 1,
+        total,
+      });
+    }
+  }
+  imported
+}
+
+/// A folder of an import, with the docs directly inside it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportFolder {
+  pub name: String,
+  pub doc_count: usize,
+  pub children: Vec<ImportFolder>,
+}
+
+/// What [`import_files`] would do with a batch, see [`preflight_files`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportPreflight {
+  /// Files that would be loaded as docs.
+  pub doc_count: usize,
+  /// Files the checkpoint records as already imported.
+  pub skipped_count: usize,
+  /// Folders of the docs, rooted at an unnamed folder.
+  pub root: ImportFolder,
+  /// Images, audio and video, as `(path, size in bytes)`.
+  pub attachments: Vec<(String, usize)>,
+  /// Paths of files no loader handles.
+  pub unsupported: Vec<String>,
+}
+
+/// Reports what [`import_files`] would create from a batch without loading
+/// anything, so a large migration can be reviewed before it runs.
+///
+/// Files are only classified by their content type and extension; a file
+/// counted as a doc can still fail to load.
+pub fn preflight_files(
+  files: &[(String, Vec<u8>)],
+  checkpoint: &ImportCheckpoint,
+) -> ImportPreflight {
+  let mut preflight = ImportPreflight::default();
+  let mut folders = FolderBuilder::default();
+
+  for (file_path, content) in files {
+    if checkpoint.contains(file_path) {
+      preflight.skipped_count += 1;
+      continue;
+    }
+    match FileKind::detect(file_path, content) {
+      FileKind::Other(Some(kind))
+        if matches!(
+          kind.matcher_type(),
+          infer::MatcherType::Image | infer::MatcherType::Audio | infer::MatcherType::Video
+        ) =>
+      {
+        preflight
+          .attachments
+          .push((file_path.clone(), content.len()));
+      }
+      FileKind::Other(_) => preflight.unsupported.push(file_path.clone()),
+      _ => {
+        preflight.doc_count += 1;
+        let mut folder = &mut folders;
+        let mut segments = file_path.split('/').filter(|segment| !segment.is_empty());
+        segments.next_back();
+        for segment in segments {
+          folder = folder.children.entry(segment.to_string()).or_default();
+        }
+        folder.doc_count += 1;
+      }
+    }
+  }
+
+  preflight.root = folders.build(String::new());
+  preflight
+}
+
+#[derive(Default)]
+struct FolderBuilder {
+  doc_count: usize,
+  children: BTreeMap<String, FolderBuilder>,
+}
+
+impl FolderBuilder {
+  fn build(self, name: String) -> ImportFolder {
+    ImportFolder {
+      name,
+      doc_count: self.doc_count,
+      children: self
+        .children
+        .into_iter()
+        .map(|(name, child)| child.build(name))
+        .collect(),
+    }
+  }
+}
+
+/// 64-bit FNV-1a. Unlike `DefaultHasher`, its output is guaranteed to stay
+/// the same across Rust releases.
+fn path_hash(file_path: &str) -> u64 {
+  file_path.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+    (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_import_checkpoint() {
+    let mut checkpoint = ImportCheckpoint::new();
+    checkpoint.mark_imported("notes/a.md");
+    checkpoint.mark_imported("notes/b.md");
+    checkpoint.mark_imported("notes/a.md");
+
+    let restored = ImportCheckpoint::from_bytes(&checkpoint.to_bytes()).unwrap();
+    assert_eq!(restored, checkpoint);
+    assert_eq!(restored.len(), 2);
+    assert!(restored.contains("notes/b.md"));
+    assert!(!restored.contains("notes/c.md"));
+
+    // the encoding must not change between releases
+    assert_eq!(path_hash("notes/a.md"), 0x299e_17ec_01e6_95a5);
+
+    assert!(ImportCheckpoint::from_bytes(b"AFIC").is_err());
+    let mut truncated = checkpoint.to_bytes();
+    truncated.pop();
+    assert!(ImportCheckpoint::from_bytes(&truncated).is_err());
+  }
+
+  #[test]
+  fn test_import_files() {
+    let file = |path: &str, content: &str| (path.to_string(), content.as_bytes().to_vec());
+    let mut checkpoint = ImportCheckpoint::new();
+
+    let imported = import_files(
+      vec![file("a.md", "# A"), file("b.md", "# B")],
+      &mut checkpoint,
+      None,
+    );
+    assert_eq!(imported.len(), 2);
+    assert!(checkpoint.contains("a.md") && checkpoint.contains("b.md"));
+
+    // an interrupted import restarts with the persisted checkpoint
+    let mut resumed = ImportCheckpoint::from_bytes(&checkpoint.to_bytes()).unwrap();
+    let imported = import_files(
+      vec![
+        file("a.md", "# A"),
+        file("b.md", "# B"),
+        file("c.md", "# C"),
+      ],
+      &mut resumed,
+      None,
+    );
+    let paths = imported
+      .iter()
+      .map(|file| file.file_path.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(paths, vec!["c.md"]);
+    assert_eq!(resumed.len(), 3);
+  }
+
+  #[test]
+  fn test_preflight_files() {
+    let file = |path: &str, content: &[u8]| (path.to_string(), content.to_vec());
+    let png = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0, 0];
+    let files = vec![
+      file("readme.md", b"# Readme"),
+      file("notes/a.md", b"# A"),
+      file("notes/daily/b.md", b"# B"),
+      file("notes/logo.png", &png),
+      file("archive.zip", b"PK\x03\x04\x14\x00\x00\x00"),
+    ];
+    let mut checkpoint = ImportCheckpoint::new();
+    checkpoint.mark_imported("readme.md");
+
+    let preflight = preflight_files(&files, &checkpoint);
+    assert_eq!(preflight.doc_count, 2);
+    assert_eq!(preflight.skipped_count, 1);
+    assert_eq!(
+      preflight.attachments,
+      vec![("notes/logo.png".to_string(), 10)]
+    );
+    assert_eq!(preflight.unsupported, vec!["archive.zip"]);
+
+    let folder = |name: &str, doc_count, children| ImportFolder {
+      name: name.into(),
+      doc_count,
+      children,
+    };
+    assert_eq!(
+      preflight.root,
+      folder(
+        "",
+        0,
+        vec![folder("notes", 1, vec![folder("daily", 1, vec![])])]
+      )
+    );
+  }
+}
@@ -1,11 +1,15 @@
 mod document;
 mod error;
+mod import;
 mod loader;
 mod splitter;
 mod types;
 
 pub use document::{Chunk, Doc};
 pub use error::{LoaderError, LoaderResult};
+pub use import::{
+  import_files, preflight_files, ImportCheckpoint, ImportFolder, ImportPreflight, ImportedFile,
+};
 use loader::{
   get_language_by_filename, DocxLoader, HtmlLoader, LanguageParserOptions, Loader,
   PdfExtractLoader, SourceCodeLoader, TextLoader, Url,
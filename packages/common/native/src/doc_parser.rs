@@ -1,9 +1,191 @@
-use std::collections::{HashMap, HashSet};
-
+mod analytics;
+mod anchor;
+mod anomaly;
+mod autocomplete;
+mod blob_manifest;
+mod block_filter;
+mod block_index;
+mod block_snapshot;
+mod block_tree;
+mod checklist;
+mod checksum;
+mod chunking;
+mod classify;
+mod cleanup;
+mod compact;
+mod context;
+mod contribution;
+mod corpus;
+mod crawl_policy;
+mod cross_ref;
+mod database;
+mod diagnostics;
+mod doc_delta;
+mod doc_diff;
+mod downgrade;
+mod estimate;
+mod export;
+mod extras;
+mod flatten;
+mod flavour_registry;
+mod folder_tree;
+mod footnote;
+mod generic;
+mod heal;
+mod html;
+mod index_delta;
+mod interned;
+mod lenient;
+mod list_normalize;
+mod mapped;
+mod markdown;
+mod markdown_import;
+mod markdown_lint;
+mod markdown_reader;
+mod middleware;
+mod options;
+mod outline;
+mod packed;
+mod plain_text;
+mod preset;
+mod published;
+mod quarantine;
+mod reference_graph;
+mod retention;
+mod root_meta;
+mod roundtrip;
+mod row_doc;
+mod sample;
+mod schema;
+mod scrub;
+mod secrets;
+mod sitemap;
+mod skeleton;
+mod snapshot_policy;
+mod snippets;
+mod stats;
+mod summary;
+mod sync;
+mod template;
+#[cfg(test)]
+mod test_utils;
+mod title;
+mod ts_types;
+mod unsupported;
+mod validate;
+mod workspace_dir;
+
+use std::{
+  collections::{HashMap, HashSet},
+  fmt,
+  str::FromStr,
+};
+
+pub use analytics::{export_analytics, AnalyticsOptions};
+pub use anchor::{resolve_anchor, slugify, AnchorKind, ResolvedAnchor};
+pub use anomaly::{analyze_update_stream, AnomalyKind, UpdateAnomaly};
+pub use autocomplete::{
+  autocomplete, build_autocomplete_index, AutocompleteIndex, Completion, CompletionKind,
+};
+pub use blob_manifest::{extract_blob_manifest, BlobKind, BlobReference};
+pub use block_filter::{BlockFilter, PropRule};
+pub use block_snapshot::BlockSnapshot;
+pub use block_tree::{parse_doc_to_json, BlockNode, BlockTree};
+pub use checklist::{compute_checklist_progress, ChecklistProgress, ListProgress};
+use checksum::block_checksum;
+pub use chunking::{chunk_doc_for_embedding, EmbeddingChunk, EmbeddingChunkOptions};
+pub use classify::{classify_doc, DocClassification, DocFeatures, DocKind, DocKindScore};
+pub use cleanup::{cleanup_doc, CleanupOptions, CleanupResult};
+pub use compact::{compact_snapshot, merge_updates};
+pub use context::{render_block_context, BlockContext};
+pub use contribution::{
+  compute_contribution_heatmap, ContributionDay, TimestampedUpdate, UserContributions,
+};
+pub use corpus::{
+  bundled_corpus, check_against_corpus, check_bundled_corpus, CorpusReport, CorpusSample,
+  SampleCheck, CORPUS_VERSION,
+};
+pub use crawl_policy::{BlockAction, CrawlPolicy};
+pub use cross_ref::{list_cross_references, CrossReference};
+pub use database::{
+  parse_database_block, CellValue, DatabaseBlock, DatabaseCell, DatabaseColumn, DatabaseOption,
+  DatabaseRow, DatabaseView,
+};
+pub use diagnostics::{diagnose_parse_failure, DiagnosticBundle};
+pub use doc_delta::{parse_doc_delta, DocDelta};
+pub use doc_diff::{diff_docs, BlockChange, BlockVersion, DocDiff};
+pub use downgrade::{downgrade_doc, DowngradedDoc, DOC_SCHEMA_VERSION};
+pub use estimate::{estimate_export_size, ExportSizeEstimate};
+pub use export::{export_block_subtree, export_block_subtree_with_options, export_docs};
+pub use extras::{parse_additional, BlockExtras, ExtrasField, EXTRAS_VERSION};
+pub use flatten::flatten_doc;
+pub use flavour_registry::{is_builtin_flavour, FlavourHandler, FlavourRegistry, BUILTIN_FLAVOURS};
+pub use folder_tree::{read_folder_tree_from_root_doc, CollectionInfo, FolderNode, FolderTree};
+use footnote::{text_with_footnotes, Footnote};
+pub use generic::{GenericFlavourHandler, GENERIC_TEXT_PROPS};
+pub use heal::{heal_reference, HealCandidate, HealReason};
+pub use html::{parse_doc_to_html, HtmlResult};
+pub use index_delta::{build_index_delta, IndexDelta, IndexDeltaRecord, IndexOp, IndexRecord};
+pub use interned::{InternedBlockInfo, InternedCrawlResult, StringInterner};
+pub use lenient::{parse_doc_lenient, LenientCrawlResult, RecoverableError, RecoverableErrorKind};
+pub use list_normalize::{normalize_lists, NormalizedLists};
+pub use mapped::parse_doc_from_path;
+pub use markdown::{
+  parse_block_to_markdown, parse_doc_to_markdown, parse_doc_to_markdown_with_options,
+  parse_subtree_to_markdown_with_options, BlobHandling, MarkdownDialect, MarkdownOptions,
+};
+pub use markdown_import::markdown_to_doc_binary;
+pub use markdown_lint::{lint_markdown, LintIssue, LintRule, LintRules};
+pub use middleware::ExportMiddleware;
+pub use options::validate_options;
+pub use outline::{extract_outline, OutlineEntry};
+pub use packed::{pack_crawl_result, unpack_crawl_result, PACKED_CRAWL_MAGIC};
+pub use plain_text::{parse_doc_to_text, TextBlock, TextResult};
+pub use preset::{ExportPreset, ExportResult, PresetStore};
+pub use published::{doc_clock, render_published_page, PublishedPage};
+pub use quarantine::{
+  enumerate_attachments_for_scan, quarantine_attachments, QuarantineResult, ScanTarget,
+};
+pub use reference_graph::{build_reference_graph, ReferenceEdge, ReferenceKind};
+pub use retention::{
+  evaluate_retention, RetentionAction, RetentionDecision, RetentionPolicy, RetentionReason,
+  RetentionReport,
+};
+pub use root_meta::{parse_root_doc_meta, DocMeta};
+pub use roundtrip::{roundtrip_check, FlavourFidelity, RoundtripReport};
+pub use row_doc::{expand_row_to_doc, ExpandedRow};
+pub use sample::{sample_doc, DocSample, SampleKind, SampledBlock};
+pub use schema::{can_parse, get_supported_schema_range, SchemaCheck, SchemaMismatch, SchemaRange};
+pub use scrub::{scrub_history, ScrubResult};
+pub use secrets::{scan_for_secrets, SecretFinding, SecretKind};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map as JsonMap, Value as JsonValue};
+pub use sitemap::build_sitemap;
+pub use skeleton::{extract_skeleton, DocSkeleton, SkeletonNode};
+pub use snapshot_policy::{
+  make_labeled_snapshot, read_labeled_snapshot, should_snapshot, DocStateSummary, LabeledSnapshot,
+  SnapshotPolicy, SnapshotReason, LABELED_SNAPSHOT_MAGIC,
+};
+pub use snippets::{generate_snippets, Snippet, SnippetMatch, SnippetOptions};
+pub use stats::{doc_stats, DocStats};
+pub use summary::SummaryOptions;
+pub use sync::{diff_update, encode_state_vector};
+pub use template::{
+  extract_template_variables, TemplateSchema, TemplateVariable, TemplateVariableKind,
+  TemplateVariableSource,
+};
 use thiserror::Error;
-use y_octo::{Any, DocOptions, JwstCodecError, Map, Value};
+pub use title::read_doc_title;
+use tracing::instrument;
+pub use ts_types::typescript_definitions;
+pub use unsupported::{ExportFallback, UnsupportedBlock};
+pub use validate::{validate_doc_binary, DanglingChild, ValidationReport};
+pub use workspace_dir::{
+  load_workspace_dir, WorkspaceDir, BLOBS_DIR, DOCS_DIR, FOLDERS_DOC_ID, ROOT_DOC_FILE,
+};
+use y_octo::{Any, Doc, DocOptions, JwstCodecError, Map, Value};
+
+use crate::deterministic::{is_deterministic, DETERMINISTIC_CLIENT_ID};
 
 const SUMMARY_LIMIT: usize = 1000;
 const PAGE_FLAVOUR: &str = "affine:page";
@@ -28,6 +210,10 @@ pub struct BlockInfo {
   pub parent_flavour: Option<String>,
   pub parent_block_id: Option<String>,
   pub additional: Option<String>,
+  /// CRC-32 of the block's serialized content, only set when crawling with
+  /// [`CrawlOptions::checksums`].
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub checksum: Option<String>,
 }
 
 impl BlockInfo {
@@ -48,10 +234,52 @@ impl BlockInfo {
       parent_flavour: parent_flavour.cloned(),
       parent_block_id: parent_block_id.cloned(),
       additional,
+      checksum: None,
     }
   }
 }
 
+/// An entry of [`BlockInfo::ref_info`], decoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefInfo {
+  pub doc_id: String,
+  /// `page` or `edgeless` when the reference opens the doc in that mode.
+  #[serde(default)]
+  pub mode: Option<String>,
+  /// Blocks of the doc the reference points at.
+  #[serde(default)]
+  pub block_ids: Vec<String>,
+  /// Title the reference shows instead of the doc's own.
+  #[serde(default)]
+  pub title: Option<String>,
+}
+
+impl RefInfo {
+  /// Decodes a `ref_info` entry, `None` if it is not a reference payload.
+  pub fn parse(payload: &str) -> Option<Self> {
+    serde_json::from_str(payload).ok()
+  }
+}
+
+/// Options for [`parse_doc_from_binary_with_options`].
+#[derive(Default)]
+pub struct CrawlOptions<'a> {
+  /// Crawls blocks of flavours the crawler does not know about.
+  pub handlers: Option<&'a FlavourRegistry<'a>>,
+  /// Sets [`BlockInfo::checksum`] on every block, so stored crawl results can
+  /// be checked against the doc later to detect silent corruption.
+  pub checksums: bool,
+  /// Fields written to [`BlockInfo::additional`], together with the
+  /// [`EXTRAS_VERSION`]. `None` writes every field without a version.
+  pub extras: Option<Vec<ExtrasField>>,
+  /// Decides for every crawled block whether it is kept, dropped or
+  /// rewritten, see [`CrawlPolicy`].
+  pub policy: Option<&'a dyn CrawlPolicy>,
+  /// How [`CrawlResult::summary`] is made.
+  pub summary: SummaryOptions,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrawlResult {
   pub blocks: Vec<BlockInfo>,
@@ -63,16 +291,41 @@ pub struct CrawlResult {
 pub enum ParseError {
   #[error("doc_not_found")]
   DocNotFound,
+  #[error("block_not_found: {0}")]
+  BlockNotFound(String),
   #[error("invalid_binary")]
   InvalidBinary,
   #[error("sqlite_error: {0}")]
   SqliteError(String),
   #[error("parser_error: {0}")]
   ParserError(String),
+  #[error("unsupported_format: {0}")]
+  UnsupportedFormat(String),
+  #[error("preset_not_found: {0}")]
+  PresetNotFound(String),
+  #[error("invalid_options: {0}")]
+  InvalidOptions(String),
   #[error("unknown: {0}")]
   Unknown(String),
 }
 
+impl ParseError {
+  /// The stable code the error message starts with, e.g. `invalid_binary`.
+  pub fn code(&self) -> &'static str {
+    match self {
+      ParseError::DocNotFound => "doc_not_found",
+      ParseError::BlockNotFound(_) => "block_not_found",
+      ParseError::InvalidBinary => "invalid_binary",
+      ParseError::SqliteError(_) => "sqlite_error",
+      ParseError::ParserError(_) => "parser_error",
+      ParseError::UnsupportedFormat(_) => "unsupported_format",
+      ParseError::PresetNotFound(_) => "preset_not_found",
+      ParseError::InvalidOptions(_) => "invalid_options",
+      ParseError::Unknown(_) => "unknown",
+    }
+  }
+}
+
 impl From<JwstCodecError> for ParseError {
   /// Convert a `JwstCodecError` into a `ParseError::ParserError`.
   ///
@@ -94,278 +347,40 @@ impl From<JwstCodecError> for ParseError {
 pub struct MarkdownResult {
   pub title: String,
   pub markdown: String,
+  /// Blocks the markdown does not represent faithfully, in document order.
+  pub unsupported: Vec<UnsupportedBlock>,
 }
 
-/// Converts a serialized document binary into a Markdown representation and a document title.
-///
-/// Parses the provided document binary, traverses its block structure, and renders supported
-/// block types (pages, paragraphs, lists, code blocks, tables, databases, and embeds) into
-/// a single Markdown string. When `ai_editable` is true, top-level child blocks include an
-/// HTML comment with their `block_id` and `flavour` to aid downstream editing.
-///
-/// # Returns
-///
-/// `Ok(MarkdownResult)` containing the document `title` and rendered `markdown` on success,
-/// `Err(ParseError)` when the binary is invalid or the document cannot be parsed.
-///
-/// # Examples
-///
-/// ```
-/// use crate::{parse_doc_to_markdown, ParseError};
-///
-/// // invalid/empty binary yields an error
-/// let res = parse_doc_to_markdown(vec![], "doc-id".into(), false);
-/// assert!(matches!(res, Err(ParseError::InvalidBinary)));
-/// ```
-pub fn parse_doc_to_markdown(
-  doc_bin: Vec<u8>,
-  doc_id: String,
-  ai_editable: bool,
-) -> Result<MarkdownResult, ParseError> {
-  if doc_bin.is_empty() || doc_bin == [0, 0] {
-    return Err(ParseError::InvalidBinary);
-  }
-
-  let mut doc = DocOptions::new().with_guid(doc_id.clone()).build();
-  doc
-    .apply_update_from_binary_v1(&doc_bin)
-    .map_err(|_| ParseError::InvalidBinary)?;
-
-  let blocks_map = doc.get_map("blocks")?;
-  if blocks_map.is_empty() {
-    return Ok(MarkdownResult {
-      title: "".into(),
-      markdown: "".into(),
-    });
-  }
-
-  let mut block_pool: HashMap<String, Map> = HashMap::new();
-  let mut parent_lookup: HashMap<String, String> = HashMap::new();
+/// Output formats a document can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+  Markdown,
+}
 
-  for (_, value) in blocks_map.iter() {
-    if let Some(block_map) = value.to_map() {
-      if let Some(block_id) = get_block_id(&block_map) {
-        for child_id in collect_child_ids(&block_map) {
-          parent_lookup.insert(child_id, block_id.clone());
-        }
-        block_pool.insert(block_id, block_map);
-      }
+impl ExportFormat {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ExportFormat::Markdown => "markdown",
     }
   }
+}
 
-  let root_block_id = block_pool
-    .iter()
-    .find_map(|(id, block)| {
-      get_flavour(block)
-        .filter(|flavour| flavour == PAGE_FLAVOUR)
-        .map(|_| id.clone())
-    })
-    .ok_or_else(|| ParseError::ParserError("root block not found".into()))?;
-
-  let mut queue: Vec<(Option<String>, String)> = vec![(None, root_block_id.clone())];
-  let mut visited: HashSet<String> = HashSet::from([root_block_id.clone()]);
-  let mut doc_title = String::from("Untitled");
-  let mut markdown = String::new();
-
-  while let Some((parent_block_id, block_id)) = queue.pop() {
-    let block = match block_pool.get(&block_id) {
-      Some(block) => block,
-      None => continue,
-    };
-
-    let flavour = match get_flavour(block) {
-      Some(flavour) => flavour,
-      None => continue,
-    };
-
-    let parent_id = parent_lookup.get(&block_id);
-    let parent_flavour = parent_id
-      .and_then(|id| block_pool.get(id))
-      .and_then(get_flavour);
-
-    if parent_flavour.as_deref() == Some("affine:database") {
-      continue;
-    }
-
-    // enqueue children first to keep traversal order similar to JS implementation
-    let mut child_ids = collect_child_ids(block);
-    for child_id in child_ids.drain(..).rev() {
-      if visited.insert(child_id.clone()) {
-        queue.push((Some(block_id.clone()), child_id));
-      }
-    }
-
-    if flavour == PAGE_FLAVOUR {
-      let title = get_string(block, "prop:title").unwrap_or_default();
-      doc_title = title.clone();
-      continue;
-    }
-
-    if flavour == "affine:database" {
-      let title = get_string(block, "prop:title").unwrap_or_default();
-      markdown.push_str(&format!("\n### {}\n", title));
-
-      let columns_array = block.get("prop:columns").and_then(|v| v.to_array());
-      let cells_map = block.get("prop:cells").and_then(|v| v.to_map());
-
-      if let (Some(columns_array), Some(cells_map)) = (columns_array, cells_map) {
-        let mut columns = Vec::new();
-        for col_val in columns_array.iter() {
-          if let Some(col_map) = col_val.to_map() {
-            let id = get_string(&col_map, "id").unwrap_or_default();
-            let name = get_string(&col_map, "name").unwrap_or_default();
-            let type_ = get_string(&col_map, "type").unwrap_or_default();
-            let data = col_map.get("data").and_then(|v| v.to_map());
-            columns.push((id, name, type_, data));
-          }
-        }
-
-        let escape_table = |s: &str| s.replace('|', "\\|").replace('\n', "<br>");
-
-        markdown.push('|');
-        for (_, name, _, _) in &columns {
-          markdown.push_str(&escape_table(name));
-          markdown.push('|');
-        }
-        markdown.push('\n');
-
-        markdown.push('|');
-        for _ in &columns {
-          markdown.push_str("---|");
-        }
-        markdown.push('\n');
-
-        let child_ids = collect_child_ids(block);
-        for child_id in child_ids {
-          markdown.push('|');
-          let row_cells = cells_map.get(&child_id).and_then(|v| v.to_map());
-
-          for (col_id, _, col_type, col_data) in &columns {
-            let mut cell_text = String::new();
-            if col_type == "title" {
-              if let Some(child_block) = block_pool.get(&child_id) {
-                if let Some((text, _)) = text_content(child_block, "prop:text") {
-                  cell_text = text;
-                }
-              }
-            } else if let Some(row_cells) = &row_cells {
-              if let Some(cell_val) = row_cells.get(col_id).and_then(|v| v.to_map()) {
-                if let Some(value) = cell_val.get("value").and_then(|v| v.to_any()) {
-                  cell_text = format_cell_value(&value, col_type, col_data.as_ref());
-                }
-              }
-            }
-            markdown.push_str(&escape_table(&cell_text));
-            markdown.push('|');
-          }
-          markdown.push('\n');
-        }
-      }
-      continue;
-    }
-
-    if flavour == "affine:table" {
-      let contents = gather_table_contents(block);
-      markdown.push_str(&contents.join("|"));
-      markdown.push('\n');
-      continue;
-    }
-
-    if ai_editable && parent_block_id.as_ref() == Some(&root_block_id) {
-      markdown.push_str(&format!(
-        "<!-- block_id={} flavour={} -->\n",
-        block_id, flavour
-      ));
-    }
-
-    if flavour == "affine:paragraph" {
-      if let Some((text, _)) = text_content(block, "prop:text") {
-        let type_ = get_string(block, "prop:type").unwrap_or_default();
-        let prefix = match type_.as_str() {
-          "h1" => "# ",
-          "h2" => "## ",
-          "h3" => "### ",
-          "h4" => "#### ",
-          "h5" => "##### ",
-          "h6" => "###### ",
-          "quote" => "> ",
-          _ => "",
-        };
-        markdown.push_str(prefix);
-        markdown.push_str(&text);
-        markdown.push('\n');
-      }
-      continue;
-    }
-
-    if flavour == "affine:list" {
-      if let Some((text, _)) = text_content(block, "prop:text") {
-        let depth = get_list_depth(&block_id, &parent_lookup, &block_pool);
-        let indent = "    ".repeat(depth);
-        markdown.push_str(&indent);
-        markdown.push_str("- ");
-        markdown.push_str(&text);
-        markdown.push('\n');
-      }
-      continue;
-    }
-
-    if flavour == "affine:code" {
-      if let Some((text, _)) = text_content(block, "prop:text") {
-        let lang = get_string(block, "prop:language").unwrap_or_default();
-        markdown.push_str("```");
-        markdown.push_str(&lang);
-        markdown.push('\n');
-        markdown.push_str(&text);
-        markdown.push_str("\n```\n");
-      }
-      continue;
-    }
+impl fmt::Display for ExportFormat {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
   }
-
-  Ok(MarkdownResult {
-    title: doc_title,
-    markdown,
-  })
 }
 
-/// Computes the nesting depth of a list item by walking its ancestor chain and counting
-/// how many ancestor blocks have flavour `"affine:list"`.
-///
-/// # Returns
-///
-/// `usize` number of ancestor list blocks (the nesting depth).
-///
-/// # Examples
-///
-/// ```
-/// use std::collections::HashMap;
-/// // `Map` is the block map value type expected by `get_list_depth`.
-/// // Here we use empty maps to illustrate the call; an empty parent map yields depth 0.
-/// let parent_lookup: HashMap<String, String> = HashMap::new();
-/// let blocks: HashMap<String, y_octo::Map> = HashMap::new();
-/// let depth = crate::get_list_depth("some-block", &parent_lookup, &blocks);
-/// assert_eq!(depth, 0);
-/// ```
-fn get_list_depth(
-  block_id: &str,
-  parent_lookup: &HashMap<String, String>,
-  blocks: &HashMap<String, Map>,
-) -> usize {
-  let mut depth = 0;
-  let mut current_id = block_id.to_string();
-
-  while let Some(parent_id) = parent_lookup.get(&current_id) {
-    if let Some(parent_block) = blocks.get(parent_id) {
-      if get_flavour(parent_block).as_deref() == Some("affine:list") {
-        depth += 1;
-        current_id = parent_id.clone();
-        continue;
-      }
+impl FromStr for ExportFormat {
+  type Err = ParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_ascii_lowercase().as_str() {
+      "markdown" | "md" => Ok(ExportFormat::Markdown),
+      _ => Err(ParseError::UnsupportedFormat(s.to_string())),
     }
-    break;
   }
-  depth
 }
 
 /// Parses an Affine document binary into a structured crawl representation containing blocks, title, and a text summary.
@@ -395,13 +410,54 @@ fn get_list_depth(
 /// }
 /// ```
 pub fn parse_doc_from_binary(doc_bin: Vec<u8>, doc_id: String) -> Result<CrawlResult, ParseError> {
+  parse_doc_from_binary_with_handlers(doc_bin, doc_id, &FlavourRegistry::default())
+}
+
+/// Same as [`parse_doc_from_binary`], crawling blocks of unknown flavours
+/// through the matching handler in `handlers` instead of dropping them.
+pub fn parse_doc_from_binary_with_handlers(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  handlers: &FlavourRegistry,
+) -> Result<CrawlResult, ParseError> {
+  parse_doc_from_binary_with_options(
+    doc_bin,
+    doc_id,
+    &CrawlOptions {
+      handlers: Some(handlers),
+      ..Default::default()
+    },
+  )
+}
+
+/// Same as [`parse_doc_from_binary`], with the behaviour tuned by `options`.
+pub fn parse_doc_from_binary_with_options(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  options: &CrawlOptions,
+) -> Result<CrawlResult, ParseError> {
+  parse_doc_from_slice(&doc_bin, doc_id, options)
+}
+
+/// Same as [`parse_doc_from_binary_with_options`], borrowing the binary so
+/// callers can still inspect it when parsing fails.
+#[instrument(
+  name = "crawl",
+  skip_all,
+  fields(doc_id = %doc_id, blocks = tracing::field::Empty),
+  err
+)]
+pub fn parse_doc_from_slice(
+  doc_bin: &[u8],
+  doc_id: String,
+  options: &CrawlOptions,
+) -> Result<CrawlResult, ParseError> {
   if doc_bin.is_empty() || doc_bin == [0, 0] {
     return Err(ParseError::InvalidBinary);
   }
-
-  let mut doc = DocOptions::new().with_guid(doc_id.clone()).build();
+  let mut doc = new_doc(Some(&doc_id));
   doc
-    .apply_update_from_binary_v1(&doc_bin)
+    .apply_update_from_binary_v1(doc_bin)
     .map_err(|_| ParseError::InvalidBinary)?;
 
   let blocks_map = doc.get_map("blocks")?;
@@ -423,21 +479,37 @@ pub fn parse_doc_from_binary(doc_bin: Vec<u8>, doc_id: String) -> Result<CrawlRe
     }
   }
 
-  let root_block_id = block_pool
-    .iter()
-    .find_map(|(id, block)| {
-      get_flavour(block)
-        .filter(|flavour| flavour == PAGE_FLAVOUR)
-        .map(|_| id.clone())
-    })
+  let root_block_id = find_root_block_id(&block_pool)
     .ok_or_else(|| ParseError::ParserError("root block not found".into()))?;
 
-  let mut queue: Vec<(Option<String>, String)> = vec![(None, root_block_id.clone())];
-  let mut visited: HashSet<String> = HashSet::from([root_block_id.clone()]);
+  let result = crawl_blocks(&block_pool, &parent_lookup, &[root_block_id], options);
+  tracing::Span::current().record("blocks", result.blocks.len());
+  Ok(result)
+}
+
+/// Crawls the subtrees of `roots` in order, the common part of
+/// [`parse_doc_from_binary_with_options`] and [`parse_doc_lenient`].
+fn crawl_blocks(
+  block_pool: &HashMap<String, Map>,
+  parent_lookup: &HashMap<String, String>,
+  roots: &[String],
+  options: &CrawlOptions,
+) -> CrawlResult {
+  let default_handlers = FlavourRegistry::default();
+  let handlers = options.handlers.unwrap_or(&default_handlers);
+
+  // roots are popped first to last, each with its subtree
+  let mut queue: Vec<(Option<String>, String)> = roots
+    .iter()
+    .rev()
+    .map(|root_block_id| (None, root_block_id.clone()))
+    .collect();
+  let mut visited: HashSet<String> = roots.iter().cloned().collect();
   let mut blocks: Vec<BlockInfo> = Vec::with_capacity(block_pool.len());
   let mut doc_title = String::new();
-  let mut summary = String::new();
-  let mut summary_remaining = SUMMARY_LIMIT as isize;
+  // texts the summary is made of, with the index of their block in `blocks`
+  // and their length
+  let mut summary_texts: Vec<(usize, usize, String)> = Vec::new();
 
   while let Some((parent_block_id, block_id)) = queue.pop() {
     let block = match block_pool.get(&block_id) {
@@ -453,7 +525,7 @@ pub fn parse_doc_from_binary(doc_bin: Vec<u8>, doc_id: String) -> Result<CrawlRe
     let parent_block = parent_block_id.as_ref().and_then(|id| block_pool.get(id));
     let parent_flavour = parent_block.and_then(get_flavour);
 
-    let note_block = nearest_by_flavour(&block_id, NOTE_FLAVOUR, &parent_lookup, &block_pool);
+    let note_block = nearest_by_flavour(&block_id, NOTE_FLAVOUR, parent_lookup, block_pool);
     let note_block_id = note_block.as_ref().and_then(get_block_id);
     let display_mode = determine_display_mode(note_block.as_ref());
 
@@ -471,7 +543,12 @@ pub fn parse_doc_from_binary(doc_bin: Vec<u8>, doc_id: String) -> Result<CrawlRe
         &flavour,
         parent_flavour.as_ref(),
         parent_block_id.as_ref(),
-        compose_additional(&display_mode, note_block_id.as_ref(), database_name),
+        compose_additional(
+          &display_mode,
+          note_block_id.as_ref(),
+          database_name,
+          options.extras.as_deref(),
+        ),
       )
     };
 
@@ -498,9 +575,23 @@ pub fn parse_doc_from_binary(doc_bin: Vec<u8>, doc_id: String) -> Result<CrawlRe
         };
 
         let mut info = build_block(database_name.as_ref());
-        info.content = Some(vec![text.clone()]);
+        // footnotes keep their marker in the text, followed by their
+        // definitions
+        info.content = match text_with_footnotes(block, "prop:text") {
+          Some((content, footnotes)) if !footnotes.is_empty() => Some(
+            std::iter::once(content)
+              .chain(footnotes.iter().map(Footnote::definition))
+              .collect(),
+          ),
+          _ => Some(vec![text.clone()]),
+        };
         blocks.push(info);
-        append_summary(&mut summary, &mut summary_remaining, text_len, &text);
+        if options
+          .summary
+          .includes(&flavour, get_string(block, "prop:type").as_deref())
+        {
+          summary_texts.push((blocks.len() - 1, text_len, text));
+        }
       }
       continue;
     }
@@ -559,6 +650,7 @@ pub fn parse_doc_from_binary(doc_bin: Vec<u8>, doc_id: String) -> Result<CrawlRe
           &display_mode,
           note_block_id.as_ref(),
           database_name.as_ref(),
+          options.extras.as_deref(),
         ),
       );
       info.content = Some(texts);
@@ -585,6 +677,30 @@ pub fn parse_doc_from_binary(doc_bin: Vec<u8>, doc_id: String) -> Result<CrawlRe
 
     if BOOKMARK_FLAVOURS.contains(&flavour.as_str()) {
       blocks.push(build_block(None));
+      continue;
+    }
+
+    let handler = match handlers.get(&flavour) {
+      Some(handler) => Some(handler),
+      None if !is_builtin_flavour(&flavour) => Some(&GenericFlavourHandler as &dyn FlavourHandler),
+      None => None,
+    };
+    if let Some(handler) = handler {
+      let texts = BlockSnapshot::from_block(block, parent_block_id.as_ref())
+        .and_then(|snapshot| handler.crawl(&snapshot));
+      if let Some(texts) = texts {
+        if options
+          .summary
+          .includes(&flavour, get_string(block, "prop:type").as_deref())
+        {
+          for text in &texts {
+            summary_texts.push((blocks.len(), text.chars().count(), text.clone()));
+          }
+        }
+        let mut info = build_block(None);
+        info.content = Some(texts);
+        blocks.push(info);
+      }
     }
   }
 
@@ -592,11 +708,19 @@ pub fn parse_doc_from_binary(doc_bin: Vec<u8>, doc_id: String) -> Result<CrawlRe
     doc_title = "Untitled".into();
   }
 
-  Ok(CrawlResult {
+  if options.checksums {
+    for info in &mut blocks {
+      info.checksum = block_pool.get(&info.block_id).map(block_checksum);
+    }
+  }
+
+  let (blocks, summary) = apply_policy(blocks, summary_texts, options);
+
+  CrawlResult {
     blocks,
     title: doc_title,
     summary,
-  })
+  }
 }
 
 /// Extracts document IDs from an Affine binary document.
@@ -628,7 +752,7 @@ pub fn get_doc_ids_from_binary(
     return Err(ParseError::InvalidBinary);
   }
 
-  let mut doc = DocOptions::new().build();
+  let mut doc = new_doc(None);
   doc
     .apply_update_from_binary_v1(&doc_bin)
     .map_err(|_| ParseError::InvalidBinary)?;
@@ -663,6 +787,33 @@ pub fn get_doc_ids_from_binary(
   Ok(doc_ids)
 }
 
+/// Creates an empty doc to decode updates into, with a fixed client id in
+/// deterministic mode.
+fn new_doc(doc_id: Option<&str>) -> Doc {
+  let mut options = DocOptions::new();
+  if let Some(doc_id) = doc_id {
+    options = options.with_guid(doc_id.to_string());
+  }
+  if is_deterministic() {
+    options = options.with_client_id(DETERMINISTIC_CLIENT_ID);
+  }
+  options.build()
+}
+
+/// Finds the `affine:page` block. Docs should only have one; if there are
+/// more, deterministic mode picks the smallest id.
+fn find_root_block_id(block_pool: &HashMap<String, Map>) -> Option<String> {
+  let mut pages = block_pool
+    .iter()
+    .filter(|(_, block)| get_flavour(block).as_deref() == Some(PAGE_FLAVOUR))
+    .map(|(id, _)| id);
+  if is_deterministic() {
+    pages.min().cloned()
+  } else {
+    pages.next().cloned()
+  }
+}
+
 /// Extracts the list of child block IDs from a block's `"sys:children"` entry.
 ///
 /// Returns a vector of child IDs found in the block's `"sys:children"` array, or an empty vector if the key is absent or not an array.
@@ -746,19 +897,15 @@ fn compose_additional(
   display_mode: &str,
   note_block_id: Option<&String>,
   database_name: Option<&String>,
+  fields: Option<&[ExtrasField]>,
 ) -> Option<String> {
-  let mut payload = JsonMap::new();
-  payload.insert(
-    "displayMode".into(),
-    JsonValue::String(display_mode.to_string()),
-  );
-  if let Some(note_id) = note_block_id {
-    payload.insert("noteBlockId".into(), JsonValue::String(note_id.clone()));
-  }
-  if let Some(name) = database_name {
-    payload.insert("databaseName".into(), JsonValue::String(name.clone()));
-  }
-  Some(JsonValue::Object(payload).to_string())
+  let extras = BlockExtras {
+    version: EXTRAS_VERSION,
+    display_mode: Some(display_mode.to_string()),
+    note_block_id: note_block_id.cloned(),
+    database_name: database_name.cloned(),
+  };
+  Some(extras.to_additional(fields))
 }
 
 fn embed_ref_payload(block: &Map, page_id: &str) -> Option<String> {
@@ -773,6 +920,12 @@ fn embed_ref_payload(block: &Map, page_id: &str) -> Option<String> {
     }
   }
 
+  if let Some(title) = get_string(block, "prop:title").filter(|title| !title.is_empty()) {
+    payload
+      .entry("title")
+      .or_insert_with(|| JsonValue::String(title));
+  }
+
   Some(JsonValue::Object(payload).to_string())
 }
 
@@ -840,7 +993,8 @@ fn gather_database_texts(block: &Map) -> (Vec<String>, Option<String>) {
 /// Collects non-empty cell text values from a block's keys named like `prop:cells.<id>.text`.
 ///
 /// Scans the provided map for keys that start with `prop:cells.` and end with `.text`, converts
-/// their values to strings, and returns the non-empty results in the same order as the map's iteration.
+/// their values to strings, and returns the non-empty results sorted by the `order` of their row,
+/// then of their column. Cells without a known row or column sort by their id.
 ///
 /// # Examples
 ///
@@ -856,17 +1010,34 @@ fn gather_database_texts(block: &Map) -> (Vec<String>, Option<String>) {
 /// assert_eq!(contents, vec!["First".to_string(), "Second".to_string()]);
 /// ```
 fn gather_table_contents(block: &Map) -> Vec<String> {
-  let mut contents = Vec::new();
+  let order = |prefix: &str, id: &str| {
+    let order = block
+      .get(&format!("{prefix}{id}.order"))
+      .and_then(|value| value_to_string(&value));
+    (order, id.to_string())
+  };
+
+  let mut cells = Vec::new();
   for key in block.keys() {
-    if key.starts_with("prop:cells.") && key.ends_with(".text") {
-      if let Some(value) = block.get(key).and_then(|value| value_to_string(&value)) {
-        if !value.is_empty() {
-          contents.push(value);
-        }
+    let Some(cell) = key
+      .strip_prefix("prop:cells.")
+      .and_then(|cell| cell.strip_suffix(".text"))
+    else {
+      continue;
+    };
+    if let Some(value) = block.get(key).and_then(|value| value_to_string(&value)) {
+      if !value.is_empty() {
+        let (row, column) = cell.split_once(':').unwrap_or((cell, ""));
+        cells.push((
+          order("prop:rows.", row),
+          order("prop:columns.", column),
+          value,
+        ));
       }
     }
   }
-  contents
+  cells.sort();
+  cells.into_iter().map(|(_, _, value)| value).collect()
 }
 
 /// Formats a table cell value according to its column type and metadata.
@@ -984,11 +1155,46 @@ fn any_to_string(any: &Any) -> Option<String> {
   }
 }
 
-fn append_summary(summary: &mut String, remaining: &mut isize, text_len: usize, text: &str) {
-  if *remaining > 0 {
-    summary.push_str(text);
-    *remaining -= text_len as isize;
+/// Runs `policy` over the crawled blocks and builds the summary from the
+/// texts of the blocks it keeps, read from the rewritten content of blocks it
+/// transforms.
+fn apply_policy(
+  blocks: Vec<BlockInfo>,
+  summary_texts: Vec<(usize, usize, String)>,
+  options: &CrawlOptions,
+) -> (Vec<BlockInfo>, String) {
+  let mut summary = Vec::new();
+  let mut summary_texts = summary_texts.into_iter().peekable();
+  let mut kept = Vec::with_capacity(blocks.len());
+  for (index, info) in blocks.into_iter().enumerate() {
+    let mut texts = Vec::new();
+    while let Some((_, text_len, text)) = summary_texts.next_if(|(i, ..)| *i == index) {
+      texts.push((text_len, text));
+    }
+    match options
+      .policy
+      .map_or(BlockAction::Keep, |policy| policy.on_block(&info))
+    {
+      BlockAction::Keep => {
+        summary.extend(texts);
+        kept.push(info);
+      }
+      BlockAction::Drop => {}
+      BlockAction::Transform(info) => {
+        if !texts.is_empty() {
+          summary.extend(
+            info
+              .content
+              .iter()
+              .flatten()
+              .map(|text| (text.chars().count(), text.clone())),
+          );
+        }
+        kept.push(*info);
+      }
+    }
   }
+  (kept, options.summary.summarize(summary))
 }
 
 #[cfg(test)]
@@ -1010,4 +1216,45 @@ mod tests {
       config
     );
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn test_ref_info() {
+    let mut doc = test_utils::TestDoc::new("Links");
+    let mut embed = doc.add_block(
+      "note",
+      "linked",
+      "affine:embed-linked-doc",
+      &[("pageId", "target"), ("title", "Renamed")],
+    );
+    let params = Any::Object(
+      [
+        ("mode".to_string(), Any::String("edgeless".into())),
+        (
+          "blockIds".to_string(),
+          Any::Array(vec![Any::String("b1".into())]),
+        ),
+      ]
+      .into_iter()
+      .collect(),
+    );
+    embed.insert("prop:params".into(), params).unwrap();
+
+    let result = parse_doc_from_binary(doc.encode(), test_utils::TestDoc::DOC_ID.into()).unwrap();
+    let block = result
+      .blocks
+      .iter()
+      .find(|block| block.block_id == "linked")
+      .unwrap();
+    let payload = &block.ref_info.as_ref().unwrap()[0];
+    assert_eq!(
+      RefInfo::parse(payload),
+      Some(RefInfo {
+        doc_id: "target".into(),
+        mode: Some("edgeless".into()),
+        block_ids: vec!["b1".into()],
+        title: Some("Renamed".into()),
+      })
+    );
+    assert_eq!(RefInfo::parse("[]"), None);
+  }
+}
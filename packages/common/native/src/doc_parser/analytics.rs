@@ -0,0 +1,233 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use y_octo::{Any, Map};
+
+use super::{
+  extract_skeleton,
+  options::{parse_options, OptionsObject},
+  parse_doc_from_binary,
+  root_meta::RootDoc,
+  ParseError, SkeletonNode,
+};
+
+/// Page meta fields reported on their own rather than as properties.
+const RESERVED_META_KEYS: [&str; 4] = ["id", "title", "tags", "trash"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct AnalyticsOptions {
+  /// Leave out titles and every string property value, keeping only ids,
+  /// counts, numbers and flags.
+  pub no_content: bool,
+  pub include_trash: bool,
+}
+
+impl AnalyticsOptions {
+  /// Parses and checks options sent as JSON, see [`validate_options`].
+  ///
+  /// [`validate_options`]: super::validate_options
+  pub fn from_json(json: &str) -> Result<Self, ParseError> {
+    parse_options(json)
+  }
+}
+
+impl OptionsObject for AnalyticsOptions {}
+
+/// Writes one JSON line per doc listed in a workspace root doc, for
+/// ingestion into BI tools.
+///
+/// Each record has the doc id, its tag ids and meta properties, the ids of
+/// the docs it links to, text statistics and metrics of its block tree.
+/// Docs missing from `docs`, given as `(doc id, doc binary)` pairs, or
+/// failing to load are still reported, with `"loaded": false` and meta
+/// fields only.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{export_analytics, AnalyticsOptions};
+///
+/// let root_doc_bin = std::fs::read("fixtures/root.ydoc").unwrap();
+/// let docs = vec![("doc-id".to_string(), std::fs::read("fixtures/demo.ydoc").unwrap())];
+/// let options = AnalyticsOptions {
+///   no_content: true,
+///   ..Default::default()
+/// };
+/// let ndjson = export_analytics(root_doc_bin, docs, &options).unwrap();
+/// ```
+pub fn export_analytics(
+  root_doc_bin: Vec<u8>,
+  docs: Vec<(String, Vec<u8>)>,
+  options: &AnalyticsOptions,
+) -> Result<String, ParseError> {
+  let root = RootDoc::decode(&root_doc_bin)?;
+
+  let mut docs = docs.into_iter().collect::<HashMap<_, _>>();
+  let mut ndjson = String::new();
+  for (meta, page) in root.pages()? {
+    if meta.trash && !options.include_trash {
+      continue;
+    }
+    let doc_id = meta.doc_id;
+
+    let mut record = json!({
+      "docId": doc_id,
+      "trash": meta.trash,
+      "tags": meta.tags,
+      "properties": page_properties(&page, options.no_content),
+      "loaded": false,
+    });
+    if !options.no_content {
+      record["title"] = meta.title.into();
+    }
+    if let Some(doc_bin) = docs.remove(&doc_id) {
+      add_doc_metrics(&mut record, doc_bin, &doc_id, options.no_content);
+    }
+
+    ndjson.push_str(&record.to_string());
+    ndjson.push('\n');
+  }
+
+  Ok(ndjson)
+}
+
+/// Scalar meta fields of a page, such as its dates and favorite flag.
+fn page_properties(page: &Map, no_content: bool) -> BTreeMap<String, JsonValue> {
+  page
+    .iter()
+    .filter(|(key, _)| !RESERVED_META_KEYS.contains(key))
+    .filter_map(|(key, value)| {
+      let value = match value.to_any()? {
+        Any::Integer(value) => value.into(),
+        Any::Float32(value) => value.0.into(),
+        Any::Float64(value) => value.0.into(),
+        Any::BigInt64(value) => value.into(),
+        Any::True => true.into(),
+        Any::False => false.into(),
+        Any::String(value) if !no_content => value.into(),
+        _ => return None,
+      };
+      Some((key.to_string(), value))
+    })
+    .collect()
+}
+
+fn add_doc_metrics(record: &mut JsonValue, doc_bin: Vec<u8>, doc_id: &str, no_content: bool) {
+  let (Ok(crawl), Ok(skeleton)) = (
+    parse_doc_from_binary(doc_bin.clone(), doc_id.to_string()),
+    extract_skeleton(doc_bin, doc_id.to_string()),
+  ) else {
+    return;
+  };
+
+  let mut flavours: BTreeMap<String, usize> = BTreeMap::new();
+  let mut links = BTreeSet::new();
+  let (mut words, mut characters) = (0, 0);
+  for block in &crawl.blocks {
+    *flavours.entry(block.flavour.clone()).or_default() += 1;
+    links.extend(block.ref_doc_id.iter().flatten().cloned());
+    for content in block.content.iter().flatten() {
+      words += content.split_whitespace().count();
+      characters += content.chars().count();
+    }
+  }
+  links.remove(doc_id);
+
+  record["loaded"] = true.into();
+  record["links"] = json!(links);
+  record["stats"] = json!({
+    "words": words,
+    "characters": characters,
+    "flavours": flavours,
+  });
+  record["skeleton"] = json!({
+    "blockCount": skeleton.block_count,
+    "maxDepth": depth(&skeleton.root),
+    "textLength": text_length(&skeleton.root),
+  });
+  if !no_content && !crawl.title.is_empty() {
+    record["title"] = crawl.title.into();
+  }
+}
+
+fn depth(node: &SkeletonNode) -> usize {
+  1 + node.children.iter().map(depth).max().unwrap_or_default()
+}
+
+fn text_length(node: &SkeletonNode) -> usize {
+  node.text_length + node.children.iter().map(text_length).sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::{TestDoc, TestRootDoc};
+
+  fn records(ndjson: &str) -> Vec<JsonValue> {
+    ndjson
+      .lines()
+      .map(|line| serde_json::from_str(line).unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn test_export_analytics() {
+    let mut doc = TestDoc::new("Plan 2025");
+    doc.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[("text", "ship the thing")],
+    );
+    doc.add_block(
+      "note",
+      "e1",
+      "affine:embed-linked-doc",
+      &[("pageId", "other-doc")],
+    );
+    let docs = vec![(TestDoc::DOC_ID.to_string(), doc.encode())];
+    let mut root = TestRootDoc::new();
+    for (id, title, trash) in [(TestDoc::DOC_ID, "Plan", false), ("gone", "Old", true)] {
+      root.add_page(
+        id,
+        &["tag-1"],
+        &[
+          ("title", title.into()),
+          ("trash", trash.into()),
+          ("createDate", 1_700_000_000_000f64.into()),
+          ("status", "draft".into()),
+        ],
+      );
+    }
+
+    let ndjson =
+      export_analytics(root.encode(), docs.clone(), &AnalyticsOptions::default()).unwrap();
+    let rows = records(&ndjson);
+    assert_eq!(rows.len(), 1);
+    let record = &rows[0];
+    assert_eq!(record["docId"], TestDoc::DOC_ID);
+    assert_eq!(record["title"], "Plan 2025");
+    assert_eq!(record["loaded"], true);
+    assert_eq!(record["tags"], json!(["tag-1"]));
+    assert_eq!(record["links"], json!(["other-doc"]));
+    assert_eq!(record["properties"]["status"], "draft");
+    assert_eq!(record["stats"]["flavours"]["affine:paragraph"], 1);
+    assert_eq!(record["skeleton"]["maxDepth"], 3);
+
+    let options = AnalyticsOptions {
+      no_content: true,
+      include_trash: true,
+    };
+    let ndjson = export_analytics(root.encode(), docs, &options).unwrap();
+    assert!(!ndjson.contains("Plan") && !ndjson.contains("draft") && !ndjson.contains("ship"));
+    let rows = records(&ndjson);
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["properties"]["createDate"], 1_700_000_000_000.0);
+    assert_eq!(rows[1]["docId"], "gone");
+    assert_eq!(rows[1]["loaded"], false);
+
+    assert!(AnalyticsOptions::from_json(r#"{ "redact": true }"#).is_err());
+  }
+}
@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+
+use super::{block_index::BlockIndex, outline::outline, text_content, ParseError};
+
+const TEXT_FRAGMENT_PREFIX: &str = ":~:text=";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnchorKind {
+  Block,
+  Heading,
+  Text,
+}
+
+impl AnchorKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AnchorKind::Block => "block",
+      AnchorKind::Heading => "heading",
+      AnchorKind::Text => "text",
+    }
+  }
+}
+
+/// Where an anchor points to inside a doc.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedAnchor {
+  pub block_id: String,
+  pub kind: AnchorKind,
+  /// Character offset of the match in the block text, `0` unless `kind` is
+  /// `Text`.
+  pub offset: usize,
+}
+
+/// Resolves a deep-link anchor to the block it points at.
+///
+/// `anchor` may carry a leading `#` and is tried, in order, as:
+/// - a block id,
+/// - a heading slug as produced by [`slugify`], with `-1`, `-2`, ... suffixes
+///   for repeated headings,
+/// - a text fragment, either `:~:text=<percent-encoded text>` or raw text,
+///   matched case-sensitively first and case-insensitively second.
+///
+/// Returns `Ok(None)` when nothing in the doc matches.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::resolve_anchor;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// if let Some(anchor) = resolve_anchor(doc_bin, "doc-id".into(), "#getting-started").unwrap() {
+///   println!("{} at {}", anchor.block_id, anchor.offset);
+/// }
+/// ```
+pub fn resolve_anchor(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  anchor: &str,
+) -> Result<Option<ResolvedAnchor>, ParseError> {
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(None);
+  };
+
+  let anchor = anchor.strip_prefix('#').unwrap_or(anchor);
+  if anchor.is_empty() {
+    return Ok(None);
+  }
+
+  if index.get(anchor).is_some() {
+    return Ok(Some(ResolvedAnchor {
+      block_id: anchor.to_string(),
+      kind: AnchorKind::Block,
+      offset: 0,
+    }));
+  }
+
+  let blocks = index.descendants(&index.root_block_id);
+
+  if let Some(block_id) = find_heading(&index, anchor) {
+    return Ok(Some(ResolvedAnchor {
+      block_id,
+      kind: AnchorKind::Heading,
+      offset: 0,
+    }));
+  }
+
+  let fragment = match anchor.strip_prefix(TEXT_FRAGMENT_PREFIX) {
+    // only the start of `textStart,textEnd` ranges is located
+    Some(fragment) => percent_decode(fragment.split(',').next().unwrap_or_default()),
+    None => anchor.to_string(),
+  };
+  Ok(find_text(&index, &blocks, &fragment))
+}
+
+/// Turns heading text into a URL fragment: lowercased, with whitespace
+/// replaced by `-` and punctuation other than `-` and `_` dropped.
+pub fn slugify(text: &str) -> String {
+  text
+    .trim()
+    .chars()
+    .filter_map(|c| {
+      if c.is_whitespace() {
+        Some('-')
+      } else if c.is_alphanumeric() || c == '-' || c == '_' {
+        Some(c)
+      } else {
+        None
+      }
+    })
+    .flat_map(char::to_lowercase)
+    .collect()
+}
+
+fn find_heading(index: &BlockIndex, slug: &str) -> Option<String> {
+  outline(index)
+    .into_iter()
+    .find(|entry| entry.slug == slug)
+    .map(|entry| entry.block_id)
+}
+
+fn find_text(index: &BlockIndex, blocks: &[String], fragment: &str) -> Option<ResolvedAnchor> {
+  if fragment.trim().is_empty() {
+    return None;
+  }
+
+  let texts = blocks
+    .iter()
+    .filter_map(|block_id| {
+      let (text, _) = text_content(index.get(block_id)?, "prop:text")?;
+      Some((block_id, text))
+    })
+    .collect::<Vec<_>>();
+
+  let resolve = |block_id: &String, text: &str, byte_offset: usize| ResolvedAnchor {
+    block_id: block_id.clone(),
+    kind: AnchorKind::Text,
+    offset: text[..byte_offset].chars().count(),
+  };
+
+  for (block_id, text) in &texts {
+    if let Some(byte_offset) = text.find(fragment) {
+      return Some(resolve(block_id, text, byte_offset));
+    }
+  }
+
+  let fragment = fragment.to_lowercase();
+  for (block_id, text) in &texts {
+    let lower = text.to_lowercase();
+    if let Some(byte_offset) = lower.find(&fragment) {
+      return Some(resolve(block_id, &lower, byte_offset));
+    }
+  }
+  None
+}
+
+fn percent_decode(input: &str) -> String {
+  let bytes = input.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    if bytes[i] == b'%' && i + 2 < bytes.len() {
+      let byte = std::str::from_utf8(&bytes[i + 1..i + 3])
+        .ok()
+        .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+      if let Some(byte) = byte {
+        decoded.push(byte);
+        i += 3;
+        continue;
+      }
+    }
+    decoded.push(bytes[i]);
+    i += 1;
+  }
+  String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_resolve_anchor() {
+    let mut doc = TestDoc::new("Anchors");
+    doc.add_block(
+      "note",
+      "h1",
+      "affine:paragraph",
+      &[("type", "h2"), ("text", "Getting Started!")],
+    );
+    doc.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[("text", "Café au lait")],
+    );
+    doc.add_block(
+      "note",
+      "h2",
+      "affine:paragraph",
+      &[("type", "h2"), ("text", "Getting started")],
+    );
+    let doc_bin = doc.encode();
+    let resolve = |anchor: &str| {
+      resolve_anchor(doc_bin.clone(), TestDoc::DOC_ID.into(), anchor)
+        .unwrap()
+        .map(|anchor| (anchor.block_id, anchor.kind, anchor.offset))
+    };
+
+    assert_eq!(resolve("p1"), Some(("p1".into(), AnchorKind::Block, 0)));
+    assert_eq!(
+      resolve("#getting-started"),
+      Some(("h1".into(), AnchorKind::Heading, 0))
+    );
+    assert_eq!(
+      resolve("#getting-started-1"),
+      Some(("h2".into(), AnchorKind::Heading, 0))
+    );
+    assert_eq!(
+      resolve("#:~:text=au%20lait"),
+      Some(("p1".into(), AnchorKind::Text, 5))
+    );
+    assert_eq!(resolve("CAFÉ"), Some(("p1".into(), AnchorKind::Text, 0)));
+    assert_eq!(resolve("#nowhere"), None);
+  }
+}
@@ -0,0 +1,261 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use y_octo::{DeleteSet, Update};
+
+use super::{ParseError, TimestampedUpdate};
+
+/// Content a window has to delete before it can be a mass deletion, in Y
+/// clock units, so that emptying a short doc is not flagged.
+const MASS_DELETION_MIN: u64 = 200;
+/// Share of the content at the start of a window it has to delete to be a
+/// mass deletion.
+const MASS_DELETION_RATIO: f64 = 0.5;
+/// Share of the content at the start of a window it has to both delete and
+/// insert again to be a full rewrite.
+const REWRITE_RATIO: f64 = 0.9;
+/// Updates a window needs before its edit frequency is checked.
+const BOT_MIN_UPDATES: usize = 50;
+/// Mean interval between updates below which edits are too fast for a
+/// person typing, in milliseconds.
+const BOT_MAX_INTERVAL_MS: i64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+  /// Most of the doc was deleted.
+  MassDeletion,
+  /// Most of the doc was deleted and replaced by new content.
+  FullRewrite,
+  /// Updates came in faster than a person edits.
+  BotLikeFrequency,
+}
+
+impl AnomalyKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AnomalyKind::MassDeletion => "mass_deletion",
+      AnomalyKind::FullRewrite => "full_rewrite",
+      AnomalyKind::BotLikeFrequency => "bot_like_frequency",
+    }
+  }
+}
+
+impl FromStr for AnomalyKind {
+  type Err = ParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "mass_deletion" => Ok(AnomalyKind::MassDeletion),
+      "full_rewrite" => Ok(AnomalyKind::FullRewrite),
+      "bot_like_frequency" => Ok(AnomalyKind::BotLikeFrequency),
+      _ => Err(ParseError::InvalidOptions(format!(
+        "unknown anomaly kind: {s}"
+      ))),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateAnomaly {
+  pub kind: AnomalyKind,
+  /// Index in the stream of the first update of the window. Merging the
+  /// updates before it gives the doc as it was before the anomaly.
+  pub first_update_index: usize,
+  pub update_count: usize,
+  /// Timestamps of the first and last update of the window.
+  pub start: i64,
+  pub end: i64,
+  /// Content inserted and deleted in the window, in Y clock units.
+  pub inserted: u64,
+  pub deleted: u64,
+}
+
+/// Updates received within one window.
+struct Window {
+  first_update_index: usize,
+  update_count: usize,
+  start: i64,
+  end: i64,
+  /// Content of the doc when the window started.
+  content: u64,
+  inserted: u64,
+  deleted: u64,
+}
+
+impl Window {
+  fn anomalies(&self) -> Vec<UpdateAnomaly> {
+    let content = self.content as f64;
+    let deleted = self.deleted as f64;
+    let mut kinds = vec![];
+    if self.deleted >= MASS_DELETION_MIN {
+      if deleted >= content * REWRITE_RATIO && self.inserted as f64 >= deleted * REWRITE_RATIO {
+        kinds.push(AnomalyKind::FullRewrite);
+      } else if deleted >= content * MASS_DELETION_RATIO {
+        kinds.push(AnomalyKind::MassDeletion);
+      }
+    }
+    if self.update_count >= BOT_MIN_UPDATES
+      && (self.end - self.start) / (self.update_count as i64 - 1) < BOT_MAX_INTERVAL_MS
+    {
+      kinds.push(AnomalyKind::BotLikeFrequency);
+    }
+
+    kinds
+      .into_iter()
+      .map(|kind| UpdateAnomaly {
+        kind,
+        first_update_index: self.first_update_index,
+        update_count: self.update_count,
+        start: self.start,
+        end: self.end,
+        inserted: self.inserted,
+        deleted: self.deleted,
+      })
+      .collect()
+  }
+}
+
+/// Flags the windows of an update stream with suspicious changes: mass
+/// deletions, full rewrites of the doc and bot-like edit frequencies, so a
+/// safety snapshot can be taken before the updates are compacted away.
+///
+/// `updates` are the updates of one doc in the order they were applied,
+/// starting from an empty doc, since the size of the doc is estimated from
+/// them. They are split into consecutive windows of `window_ms`
+/// milliseconds, each starting at the first update not in the previous
+/// one. A window can have several anomalies.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{analyze_update_stream, TimestampedUpdate};
+///
+/// let updates = vec![TimestampedUpdate {
+///   timestamp: 1_700_000_000_000,
+///   bin: std::fs::read("fixtures/update.bin").unwrap(),
+/// }];
+/// for anomaly in analyze_update_stream(&updates, 60_000).unwrap() {
+///   println!("{} at update {}", anomaly.kind.as_str(), anomaly.first_update_index);
+/// }
+/// ```
+pub fn analyze_update_stream(
+  updates: &[TimestampedUpdate],
+  window_ms: i64,
+) -> Result<Vec<UpdateAnomaly>, ParseError> {
+  if window_ms <= 0 {
+    return Err(ParseError::InvalidOptions(
+      "window must be longer than 0 ms".into(),
+    ));
+  }
+
+  let mut anomalies = vec![];
+  let mut content = 0u64;
+  let mut window: Option<Window> = None;
+  let mut deleted_ranges = DeleteSet::default();
+  for (index, update) in updates.iter().enumerate() {
+    let decoded = Update::decode_v1(&update.bin).map_err(|_| ParseError::InvalidBinary)?;
+    let inserted = decoded.item_lengths().map(|(_, len)| len).sum::<u64>();
+    // updates repeat the deletions of earlier ones, only new ranges count
+    let before = deleted_len(&deleted_ranges);
+    deleted_ranges.merge(decoded.delete_set());
+    let deleted = deleted_len(&deleted_ranges) - before;
+
+    if window
+      .as_ref()
+      .is_some_and(|window| update.timestamp - window.start >= window_ms)
+    {
+      anomalies.extend(
+        window
+          .take()
+          .into_iter()
+          .flat_map(|window| window.anomalies()),
+      );
+    }
+    let window = window.get_or_insert(Window {
+      first_update_index: index,
+      update_count: 0,
+      start: update.timestamp,
+      end: update.timestamp,
+      content,
+      inserted: 0,
+      deleted: 0,
+    });
+    window.update_count += 1;
+    window.end = update.timestamp;
+    window.inserted += inserted;
+    window.deleted += deleted;
+    content = (content + inserted).saturating_sub(deleted);
+  }
+  anomalies.extend(window.into_iter().flat_map(|window| window.anomalies()));
+
+  Ok(anomalies)
+}
+
+fn deleted_len(delete_set: &DeleteSet) -> u64 {
+  delete_set
+    .values()
+    .flat_map(|ranges| ranges.into_iter())
+    .map(|range| range.end - range.start)
+    .sum()
+}
+
+#[cfg(test)]
+mod tests {
+  use y_octo::{Doc, DocOptions};
+
+  use super::*;
+
+  const MINUTE: i64 = 60 * 1000;
+
+  fn edit(doc: &Doc, f: impl FnOnce()) -> Vec<u8> {
+    let state_vector = doc.get_state_vector();
+    f();
+    doc.encode_state_as_update_v1(&state_vector).unwrap()
+  }
+
+  #[test]
+  fn test_analyze_update_stream() {
+    let doc = DocOptions::new().with_client_id(1).build();
+    let mut text = doc.get_or_create_text("content").unwrap();
+    let mut updates = vec![];
+    let mut push = |timestamp, bin| updates.push(TimestampedUpdate { timestamp, bin });
+
+    push(0, edit(&doc, || text.insert(0, "a".repeat(1000)).unwrap()));
+    push(MINUTE, edit(&doc, || text.insert(0, "typing").unwrap()));
+    push(2 * MINUTE, edit(&doc, || text.remove(0, 700).unwrap()));
+    push(
+      4 * MINUTE,
+      edit(&doc, || {
+        text.remove(0, text.len()).unwrap();
+        text.insert(0, "b".repeat(300)).unwrap();
+      }),
+    );
+    for i in 0..60 {
+      push(
+        6 * MINUTE + i * 10,
+        edit(&doc, || text.insert(0, "x").unwrap()),
+      );
+    }
+
+    let anomalies = analyze_update_stream(&updates, MINUTE).unwrap();
+    assert_eq!(
+      anomalies
+        .iter()
+        .map(|anomaly| (anomaly.kind, anomaly.first_update_index))
+        .collect::<Vec<_>>(),
+      vec![
+        (AnomalyKind::MassDeletion, 2),
+        (AnomalyKind::FullRewrite, 3),
+        (AnomalyKind::BotLikeFrequency, 4),
+      ]
+    );
+    assert_eq!(anomalies[0].deleted, 700);
+    assert_eq!(anomalies[2].update_count, 60);
+
+    assert!(matches!(
+      analyze_update_stream(&updates, 0),
+      Err(ParseError::InvalidOptions(_))
+    ));
+  }
+}
@@ -0,0 +1,317 @@
+use std::{collections::HashMap, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::{block_index::BlockIndex, get_string, root_meta::RootDoc, ParseError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionKind {
+  Doc,
+  Heading,
+  Tag,
+}
+
+impl CompletionKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      CompletionKind::Doc => "doc",
+      CompletionKind::Heading => "heading",
+      CompletionKind::Tag => "tag",
+    }
+  }
+}
+
+impl FromStr for CompletionKind {
+  type Err = ParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_ascii_lowercase().as_str() {
+      "doc" => Ok(CompletionKind::Doc),
+      "heading" => Ok(CompletionKind::Heading),
+      "tag" => Ok(CompletionKind::Tag),
+      _ => Err(ParseError::InvalidOptions(format!(
+        "unknown completion kind: {s}"
+      ))),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Completion {
+  pub kind: CompletionKind,
+  pub label: String,
+  /// Doc of doc titles and headings.
+  pub doc_id: Option<String>,
+  /// Heading block, or the tag id for tags.
+  pub id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+  completion: Completion,
+  /// Docs tagged with a tag, `7 - level` for headings, 0 for docs.
+  weight: usize,
+}
+
+/// Completions for the mention and quick open pickers, searchable by the
+/// start of their label or of any word in it.
+#[derive(Debug, Clone, Default)]
+pub struct AutocompleteIndex {
+  entries: Vec<Entry>,
+  /// Lowercased label suffixes starting at a word, sorted, with the entry
+  /// they belong to and whether the suffix is the whole label.
+  keys: Vec<(String, usize, bool)>,
+}
+
+impl AutocompleteIndex {
+  fn push(&mut self, completion: Completion, weight: usize) {
+    let label = completion.label.trim();
+    if label.is_empty() {
+      return;
+    }
+    let folded = label.to_lowercase();
+    let entry = self.entries.len();
+    let mut word_start = true;
+    for (offset, c) in folded.char_indices() {
+      if c.is_alphanumeric() {
+        if word_start {
+          self
+            .keys
+            .push((folded[offset..].to_string(), entry, offset == 0));
+        }
+        word_start = false;
+      } else {
+        word_start = true;
+      }
+    }
+    self.entries.push(Entry { completion, weight });
+  }
+
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+}
+
+/// Indexes the titles and tags recorded in a workspace root doc and the
+/// headings of `docs`, given as `(doc id, doc binary)` pairs. Trashed docs
+/// are left out, and docs that fail to load only contribute their title.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{autocomplete, build_autocomplete_index};
+///
+/// let root_doc_bin = std::fs::read("fixtures/root.ydoc").unwrap();
+/// let docs = vec![("doc-id".to_string(), std::fs::read("fixtures/demo.ydoc").unwrap())];
+/// let index = build_autocomplete_index(root.encode(), docs).unwrap();
+/// let completions = autocomplete(&index, "road", &[], 10);
+/// ```
+pub fn build_autocomplete_index(
+  root_doc_bin: Vec<u8>,
+  docs: Vec<(String, Vec<u8>)>,
+) -> Result<AutocompleteIndex, ParseError> {
+  let root = RootDoc::decode(&root_doc_bin)?;
+
+  let mut titles = Vec::new();
+  let mut tag_counts: HashMap<String, usize> = HashMap::new();
+  for (meta, _) in root.pages()? {
+    if meta.trash {
+      continue;
+    }
+    for tag in meta.tags {
+      *tag_counts.entry(tag).or_default() += 1;
+    }
+    titles.push((meta.doc_id, meta.title));
+  }
+
+  let mut index = AutocompleteIndex::default();
+  let mut docs = docs.into_iter().collect::<HashMap<_, _>>();
+  for (doc_id, meta_title) in titles {
+    let loaded = docs
+      .remove(&doc_id)
+      .and_then(|doc_bin| BlockIndex::load(&doc_bin, &doc_id).ok().flatten());
+    let title = loaded
+      .as_ref()
+      .and_then(|loaded| get_string(loaded.get(&loaded.root_block_id)?, "prop:title"))
+      .filter(|title| !title.trim().is_empty())
+      .unwrap_or(meta_title);
+    index.push(
+      Completion {
+        kind: CompletionKind::Doc,
+        label: title,
+        doc_id: Some(doc_id.clone()),
+        id: None,
+      },
+      0,
+    );
+
+    let Some(loaded) = loaded else {
+      continue;
+    };
+    for block_id in loaded.descendants(&loaded.root_block_id) {
+      let Some(block) = loaded.get(&block_id) else {
+        continue;
+      };
+      if loaded.flavour_of(&block_id).as_deref() != Some("affine:paragraph") {
+        continue;
+      }
+      let Some(level) = get_string(block, "prop:type")
+        .and_then(|type_| type_.strip_prefix('h')?.parse::<usize>().ok())
+        .filter(|level| (1..=6).contains(level))
+      else {
+        continue;
+      };
+      index.push(
+        Completion {
+          kind: CompletionKind::Heading,
+          label: get_string(block, "prop:text").unwrap_or_default(),
+          doc_id: Some(doc_id.clone()),
+          id: Some(block_id),
+        },
+        7 - level,
+      );
+    }
+  }
+
+  for (id, name) in root.tags()? {
+    let weight = tag_counts.get(&id).copied().unwrap_or_default();
+    index.push(
+      Completion {
+        kind: CompletionKind::Tag,
+        label: name,
+        doc_id: None,
+        id: Some(id),
+      },
+      weight,
+    );
+  }
+
+  index.keys.sort();
+  Ok(index)
+}
+
+/// Returns up to `limit` completions whose label, or a word in it, starts
+/// with `prefix`, ignoring case. An empty `kinds` allows every kind.
+///
+/// Labels starting with `prefix` rank before labels with a later word
+/// starting with it. Then docs come before headings and tags, higher level
+/// headings and more used tags first, and shorter labels first.
+pub fn autocomplete(
+  index: &AutocompleteIndex,
+  prefix: &str,
+  kinds: &[CompletionKind],
+  limit: usize,
+) -> Vec<Completion> {
+  let prefix = prefix.trim().to_lowercase();
+  let start = index
+    .keys
+    .partition_point(|(key, _, _)| key.as_str() < prefix.as_str());
+
+  // whether each matching entry matched with its whole label
+  let mut matches: HashMap<usize, bool> = HashMap::new();
+  for (key, entry_id, whole) in &index.keys[start..] {
+    if !key.starts_with(&prefix) {
+      break;
+    }
+    if kinds.is_empty() || kinds.contains(&index.entries[*entry_id].completion.kind) {
+      *matches.entry(*entry_id).or_default() |= *whole;
+    }
+  }
+
+  let mut matches = matches
+    .into_iter()
+    .map(|(entry_id, whole)| (!whole, &index.entries[entry_id], entry_id))
+    .collect::<Vec<_>>();
+  matches.sort_by(|(a_later, a, a_id), (b_later, b, b_id)| {
+    a_later
+      .cmp(b_later)
+      .then_with(|| a.completion.kind.cmp(&b.completion.kind))
+      .then_with(|| b.weight.cmp(&a.weight))
+      .then_with(|| a.completion.label.len().cmp(&b.completion.label.len()))
+      .then_with(|| a.completion.label.cmp(&b.completion.label))
+      .then_with(|| a_id.cmp(b_id))
+  });
+  matches
+    .into_iter()
+    .take(limit)
+    .map(|(_, entry, _)| entry.completion.clone())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::{TestDoc, TestRootDoc};
+
+  #[test]
+  fn test_autocomplete() {
+    let mut root = TestRootDoc::new();
+    for (id, title, trash, tags) in [
+      ("a", "", false, &["t1"][..]),
+      ("b", "Release plan", false, &["t1", "t2"]),
+      ("c", "Roadmap draft", true, &[]),
+    ] {
+      root.add_page(
+        id,
+        tags,
+        &[("title", title.into()), ("trash", trash.into())],
+      );
+    }
+    root.set_tags(&[("t1", "roadmap"), ("t2", "research")]);
+    let mut doc = TestDoc::new("Roadmap 2025");
+    doc.add_block(
+      "note",
+      "h1",
+      "affine:paragraph",
+      &[("type", "h1"), ("text", "Goals")],
+    );
+    doc.add_block(
+      "note",
+      "h3",
+      "affine:paragraph",
+      &[("type", "h3"), ("text", "Road trips")],
+    );
+    doc.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[("text", "Roads everywhere")],
+    );
+    let docs = vec![("a".to_string(), doc.encode())];
+    let index = build_autocomplete_index(root.encode(), docs).unwrap();
+    assert_eq!(index.len(), 6);
+
+    let labels = |completions: Vec<Completion>| {
+      completions
+        .into_iter()
+        .map(|completion| completion.label)
+        .collect::<Vec<_>>()
+    };
+    // the title stored in the doc wins over the empty one in the meta
+    assert_eq!(
+      labels(autocomplete(&index, "ROAD", &[], 10)),
+      vec!["Roadmap 2025", "Road trips", "roadmap"]
+    );
+    assert_eq!(
+      labels(autocomplete(&index, "pl", &[], 10)),
+      vec!["Release plan"]
+    );
+    assert_eq!(
+      labels(autocomplete(&index, "r", &[CompletionKind::Tag], 10)),
+      vec!["roadmap", "research"]
+    );
+    assert_eq!(autocomplete(&index, "r", &[], 2).len(), 2);
+
+    let heading = &autocomplete(&index, "goal", &[], 10)[0];
+    assert_eq!(heading.kind, CompletionKind::Heading);
+    assert_eq!(heading.doc_id.as_deref(), Some("a"));
+    assert_eq!(heading.id.as_deref(), Some("h1"));
+
+    assert!("mention".parse::<CompletionKind>().is_err());
+  }
+}
@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use y_octo::Map;
+
+use super::{block_index::BlockIndex, get_flavour, get_string, ParseError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlobKind {
+  /// Embedded by an `affine:image` block.
+  Image,
+  /// Embedded by an `affine:attachment` block of an `audio/*` type.
+  Audio,
+  /// Embedded by any other `affine:attachment` block.
+  Attachment,
+}
+
+impl BlobKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      BlobKind::Image => "image",
+      BlobKind::Audio => "audio",
+      BlobKind::Attachment => "attachment",
+    }
+  }
+}
+
+/// A blob a block of a doc embeds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobReference {
+  pub blob_id: String,
+  pub block_id: String,
+  pub kind: BlobKind,
+  /// MIME type recorded by the editor, not checked against the blob.
+  pub mime_type: Option<String>,
+  /// Size in bytes recorded by the editor.
+  pub size: Option<u64>,
+  /// File name, only attachments have one.
+  pub name: Option<String>,
+}
+
+/// Lists every blob a doc embeds, in document order, with the block
+/// embedding it and what kind of file it is, for storage GC and quota
+/// accounting. A blob embedded by several blocks is listed once per block.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::extract_blob_manifest;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// for blob in extract_blob_manifest(doc_bin, "doc-id".into()).unwrap() {
+///   println!("{} {} {:?}", blob.kind.as_str(), blob.blob_id, blob.size);
+/// }
+/// ```
+pub fn extract_blob_manifest(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+) -> Result<Vec<BlobReference>, ParseError> {
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(vec![]);
+  };
+
+  Ok(
+    index
+      .descendants(&index.root_block_id)
+      .into_iter()
+      .filter_map(|block_id| {
+        let block = index.get(&block_id)?;
+        let mime_type = get_string(block, "prop:type");
+        let kind = match get_flavour(block)?.as_str() {
+          "affine:image" => BlobKind::Image,
+          "affine:attachment"
+            if mime_type
+              .as_deref()
+              .is_some_and(|mime_type| mime_type.starts_with("audio/")) =>
+          {
+            BlobKind::Audio
+          }
+          "affine:attachment" => BlobKind::Attachment,
+          _ => return None,
+        };
+        Some(BlobReference {
+          blob_id: get_string(block, "prop:sourceId")?,
+          block_id,
+          kind,
+          mime_type,
+          size: declared_size(block),
+          name: get_string(block, "prop:name"),
+        })
+      })
+      .collect(),
+  )
+}
+
+/// Size in bytes recorded in the `prop:size` of an attachment or image.
+pub(super) fn declared_size(block: &Map) -> Option<u64> {
+  get_string(block, "prop:size")
+    .and_then(|size| size.parse::<f64>().ok())
+    .map(|size| size as u64)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_extract_blob_manifest() {
+    let mut doc = TestDoc::new("Files");
+    doc.add_block(
+      "note",
+      "i1",
+      "affine:image",
+      &[("sourceId", "photo"), ("size", "1024")],
+    );
+    doc.add_block(
+      "note",
+      "a1",
+      "affine:attachment",
+      &[
+        ("sourceId", "memo"),
+        ("name", "memo.m4a"),
+        ("type", "audio/mp4"),
+      ],
+    );
+    doc.add_block(
+      "note",
+      "a2",
+      "affine:attachment",
+      &[
+        ("sourceId", "report"),
+        ("name", "report.pdf"),
+        ("type", "application/pdf"),
+      ],
+    );
+    doc.add_block("note", "i2", "affine:image", &[]);
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "notes")]);
+
+    let manifest = extract_blob_manifest(doc.encode(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(
+      manifest,
+      vec![
+        BlobReference {
+          blob_id: "photo".into(),
+          block_id: "i1".into(),
+          kind: BlobKind::Image,
+          mime_type: None,
+          size: Some(1024),
+          name: None,
+        },
+        BlobReference {
+          blob_id: "memo".into(),
+          block_id: "a1".into(),
+          kind: BlobKind::Audio,
+          mime_type: Some("audio/mp4".into()),
+          size: None,
+          name: Some("memo.m4a".into()),
+        },
+        BlobReference {
+          blob_id: "report".into(),
+          block_id: "a2".into(),
+          kind: BlobKind::Attachment,
+          mime_type: Some("application/pdf".into()),
+          size: None,
+          name: Some("report.pdf".into()),
+        },
+      ]
+    );
+  }
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use y_octo::{Any, Map};
+
+use super::{
+  get_block_id, get_flavour,
+  options::{parse_options, OptionsObject},
+  ParseError, PAGE_FLAVOUR,
+};
+
+/// Matches blocks whose `prop:{key}` is one of `values`. Array props match
+/// when any of their items is.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PropRule {
+  pub key: String,
+  pub values: Vec<String>,
+}
+
+/// Blocks to leave out of an export, e.g. internal sections of a doc that
+/// is published. A denied block is omitted together with all of its
+/// descendants.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct BlockFilter {
+  pub deny_block_ids: Vec<String>,
+  pub deny_flavours: Vec<String>,
+  pub deny_props: Vec<PropRule>,
+}
+
+impl BlockFilter {
+  /// Parses and checks a filter sent as JSON, see [`validate_options`].
+  ///
+  /// [`validate_options`]: super::validate_options
+  pub fn from_json(json: &str) -> Result<Self, ParseError> {
+    parse_options(json)
+  }
+
+  pub(crate) fn denies(&self, block: &Map) -> bool {
+    if get_block_id(block).is_some_and(|id| self.deny_block_ids.contains(&id)) {
+      return true;
+    }
+    if get_flavour(block).is_some_and(|flavour| self.deny_flavours.contains(&flavour)) {
+      return true;
+    }
+    self.deny_props.iter().any(|rule| {
+      let Some(value) = block.get(&format!("prop:{}", rule.key)) else {
+        return false;
+      };
+      let matches = |any: &Any| match any {
+        Any::String(value) => rule.values.contains(value),
+        _ => false,
+      };
+      match (value.to_any(), value.to_array()) {
+        (Some(Any::Array(items)), _) => items.iter().any(matches),
+        (Some(any), _) => matches(&any),
+        (None, Some(items)) => items
+          .iter()
+          .any(|item| item.to_any().is_some_and(|any| matches(&any))),
+        (None, None) => false,
+      }
+    })
+  }
+}
+
+impl OptionsObject for BlockFilter {
+  fn check(&self) -> Result<(), String> {
+    if self
+      .deny_flavours
+      .iter()
+      .any(|flavour| flavour == PAGE_FLAVOUR)
+    {
+      return Err(format!("`denyFlavours` cannot contain `{PAGE_FLAVOUR}`"));
+    }
+    if let Some(rule) = self.deny_props.iter().find(|rule| rule.values.is_empty()) {
+      return Err(format!("`denyProps` rule for `{}` has no values", rule.key));
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{
+    export_block_subtree_with_options, parse_doc_to_markdown_with_options, test_utils::TestDoc,
+    ExportFormat, MarkdownOptions,
+  };
+
+  #[test]
+  fn test_block_filter() {
+    let mut doc = TestDoc::new("Release");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "public")]);
+    let mut internal = doc.add_block("note", "l1", "affine:list", &[("text", "internal")]);
+    doc.add_block("l1", "l2", "affine:list", &[("text", "nested")]);
+    doc.add_block("note", "c1", "affine:code", &[("text", "let x = 1;")]);
+    doc.add_block(
+      "note",
+      "p2",
+      "affine:paragraph",
+      &[("text", "denied by id")],
+    );
+    internal
+      .insert(
+        "prop:tags".into(),
+        Any::Array(vec![Any::String("internal".into())]),
+      )
+      .unwrap();
+    let doc_bin = doc.encode();
+
+    let filter = BlockFilter::from_json(
+      r#"{
+        "denyBlockIds": ["p2"],
+        "denyFlavours": ["affine:code"],
+        "denyProps": [{ "key": "tags", "values": ["internal"] }]
+      }"#,
+    )
+    .unwrap();
+    let options = MarkdownOptions {
+      filter: Some(filter),
+      ..Default::default()
+    };
+    let result =
+      parse_doc_to_markdown_with_options(doc_bin.clone(), TestDoc::DOC_ID.into(), &options)
+        .unwrap();
+    assert_eq!(result.title, "Release");
+    assert_eq!(result.markdown, "public\n");
+
+    let section = export_block_subtree_with_options(
+      doc_bin,
+      TestDoc::DOC_ID.into(),
+      "l1",
+      ExportFormat::Markdown,
+      &options,
+    )
+    .unwrap();
+    assert_eq!(section.content, "");
+
+    assert!(BlockFilter::from_json(r#"{ "denyFlavours": ["affine:page"] }"#).is_err());
+    assert!(
+      BlockFilter::from_json(r#"{ "denyProps": [{ "key": "tags", "values": [] }] }"#).is_err()
+    );
+  }
+}
@@ -0,0 +1,100 @@
+use std::collections::{HashMap, HashSet};
+
+use y_octo::{Doc, Map};
+
+use super::{
+  collect_child_ids, find_root_block_id, get_block_id, get_flavour, new_doc, ParseError,
+};
+
+/// The blocks of a decoded doc, indexed by id with parent links resolved.
+pub(crate) struct BlockIndex {
+  // block maps are views into the doc store, keep it alive alongside them
+  doc: Doc,
+  pub(crate) block_pool: HashMap<String, Map>,
+  pub(crate) parent_lookup: HashMap<String, String>,
+  pub(crate) root_block_id: String,
+}
+
+impl BlockIndex {
+  /// Decodes `doc_bin` and indexes its `blocks` map.
+  ///
+  /// Returns `Ok(None)` when the doc has no blocks at all, and
+  /// `ParseError::ParserError` when blocks exist but none is an `affine:page`.
+  pub(crate) fn load(doc_bin: &[u8], doc_id: &str) -> Result<Option<Self>, ParseError> {
+    if doc_bin.is_empty() || doc_bin == [0, 0] {
+      return Err(ParseError::InvalidBinary);
+    }
+
+    let mut doc = new_doc(Some(doc_id));
+    doc
+      .apply_update_from_binary_v1(doc_bin)
+      .map_err(|_| ParseError::InvalidBinary)?;
+
+    let blocks_map = doc.get_map("blocks")?;
+    if blocks_map.is_empty() {
+      return Ok(None);
+    }
+
+    let mut block_pool: HashMap<String, Map> = HashMap::new();
+    let mut parent_lookup: HashMap<String, String> = HashMap::new();
+
+    for (_, value) in blocks_map.iter() {
+      if let Some(block_map) = value.to_map() {
+        if let Some(block_id) = get_block_id(&block_map) {
+          for child_id in collect_child_ids(&block_map) {
+            parent_lookup.insert(child_id, block_id.clone());
+          }
+          block_pool.insert(block_id, block_map);
+        }
+      }
+    }
+
+    let root_block_id = find_root_block_id(&block_pool)
+      .ok_or_else(|| ParseError::ParserError("root block not found".into()))?;
+
+    Ok(Some(Self {
+      doc,
+      block_pool,
+      parent_lookup,
+      root_block_id,
+    }))
+  }
+
+  pub(crate) fn doc(&self) -> &Doc {
+    &self.doc
+  }
+
+  pub(crate) fn get(&self, block_id: &str) -> Option<&Map> {
+    self.block_pool.get(block_id)
+  }
+
+  pub(crate) fn flavour_of(&self, block_id: &str) -> Option<String> {
+    self.get(block_id).and_then(get_flavour)
+  }
+
+  pub(crate) fn parent_of(&self, block_id: &str) -> Option<&String> {
+    self.parent_lookup.get(block_id)
+  }
+
+  pub(crate) fn children_of(&self, block_id: &str) -> Vec<String> {
+    self
+      .get(block_id)
+      .map(collect_child_ids)
+      .unwrap_or_default()
+  }
+
+  /// Returns `block_id` and all blocks below it in document order.
+  pub(crate) fn descendants(&self, block_id: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut stack = vec![block_id.to_string()];
+    let mut visited = HashSet::new();
+    while let Some(id) = stack.pop() {
+      if self.get(&id).is_none() || !visited.insert(id.clone()) {
+        continue;
+      }
+      stack.extend(self.children_of(&id).into_iter().rev());
+      result.push(id);
+    }
+    result
+  }
+}
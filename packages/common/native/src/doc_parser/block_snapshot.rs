@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use y_octo::Map;
+
+use super::{get_block_id, get_flavour};
+
+/// A detached copy of a block handed to embedder code such as flavour
+/// handlers and export middleware.
+///
+/// `props` holds the block's `prop:*` entries with the prefix stripped; rich
+/// text props are flattened to plain strings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockSnapshot {
+  pub block_id: String,
+  pub flavour: String,
+  pub parent_block_id: Option<String>,
+  pub props: JsonMap<String, JsonValue>,
+}
+
+impl BlockSnapshot {
+  pub(crate) fn from_block(block: &Map, parent_block_id: Option<&String>) -> Option<Self> {
+    let mut props = JsonMap::new();
+    for (key, value) in block.iter() {
+      if let Some(name) = key.strip_prefix("prop:") {
+        if let Ok(value) = serde_json::to_value(&value) {
+          props.insert(name.to_string(), value);
+        }
+      }
+    }
+
+    Some(Self {
+      block_id: get_block_id(block)?,
+      flavour: get_flavour(block)?,
+      parent_block_id: parent_block_id.cloned(),
+      props,
+    })
+  }
+
+  /// Returns the string value of `prop:<key>`, stringifying numbers and
+  /// booleans.
+  pub fn prop_string(&self, key: &str) -> Option<String> {
+    match self.props.get(key)? {
+      JsonValue::String(value) => Some(value.clone()),
+      JsonValue::Number(value) => Some(value.to_string()),
+      JsonValue::Bool(value) => Some(value.to_string()),
+      _ => None,
+    }
+  }
+}
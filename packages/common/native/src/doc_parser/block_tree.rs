@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use tracing::instrument;
+
+use super::{block_index::BlockIndex, get_string, BlockSnapshot, ParseError};
+
+/// A block with its props and the blocks nested below it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockNode {
+  pub block_id: String,
+  pub flavour: String,
+  /// `prop:*` entries with the prefix stripped, rich text as plain strings.
+  pub props: JsonMap<String, JsonValue>,
+  pub children: Vec<BlockNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTree {
+  pub title: String,
+  pub root: BlockNode,
+  pub block_count: usize,
+}
+
+/// Returns the block tree of a doc, nested the way the editor shows it.
+///
+/// Unlike the crawl, which lists blocks flat with their parent id, every
+/// block carries its children in order, so outlines and tables of contents
+/// can be rebuilt without sorting blocks back into place. Blocks that are
+/// not reachable from the root page are left out, and a block listed under
+/// several parents only appears under the first.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::parse_doc_to_json;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let tree = parse_doc_to_json(doc_bin, "doc-id".into()).unwrap();
+/// println!("{}", serde_json::to_string_pretty(&tree).unwrap());
+/// ```
+#[instrument(name = "json", skip_all, fields(doc_id = %doc_id), err)]
+pub fn parse_doc_to_json(doc_bin: Vec<u8>, doc_id: String) -> Result<BlockTree, ParseError> {
+  let index = BlockIndex::load(&doc_bin, &doc_id)?
+    .ok_or_else(|| ParseError::ParserError("blocks map is empty".into()))?;
+
+  let mut visited = HashSet::new();
+  let root = build_node(&index, &index.root_block_id, &mut visited)
+    .ok_or_else(|| ParseError::ParserError("root block not found".into()))?;
+  let title = index
+    .get(&index.root_block_id)
+    .and_then(|root| get_string(root, "prop:title"))
+    .unwrap_or_default();
+
+  Ok(BlockTree {
+    title,
+    root,
+    block_count: visited.len(),
+  })
+}
+
+fn build_node(
+  index: &BlockIndex,
+  block_id: &str,
+  visited: &mut HashSet<String>,
+) -> Option<BlockNode> {
+  let block = index.get(block_id)?;
+  if !visited.insert(block_id.to_string()) {
+    return None;
+  }
+  let snapshot = BlockSnapshot::from_block(block, None)?;
+
+  let children = index
+    .children_of(block_id)
+    .iter()
+    .filter_map(|child_id| build_node(index, child_id, visited))
+    .collect();
+
+  Some(BlockNode {
+    block_id: snapshot.block_id,
+    flavour: snapshot.flavour,
+    props: snapshot.props,
+    children,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_parse_doc_to_json() {
+    let mut doc = TestDoc::new("Tree");
+    doc.add_block(
+      "note",
+      "l1",
+      "affine:list",
+      &[("type", "bulleted"), ("text", "parent")],
+    );
+    doc.add_block(
+      "l1",
+      "l2",
+      "affine:list",
+      &[("type", "bulleted"), ("text", "child")],
+    );
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "after")]);
+    // listed twice, kept under its first parent only
+    doc.add_reference("note", "l2");
+
+    let tree = parse_doc_to_json(doc.encode(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(tree.title, "Tree");
+    assert_eq!(tree.block_count, 5);
+    assert_eq!(tree.root.flavour, "affine:page");
+
+    let note = &tree.root.children[0];
+    assert_eq!(note.block_id, "note");
+    assert_eq!(
+      note
+        .children
+        .iter()
+        .map(|child| child.block_id.as_str())
+        .collect::<Vec<_>>(),
+      vec!["l1", "p1"]
+    );
+    let child = &note.children[0].children[0];
+    assert_eq!(child.block_id, "l2");
+    assert_eq!(child.props["text"], "child");
+    assert_eq!(child.props["type"], "bulleted");
+
+    let json = serde_json::to_value(&tree).unwrap();
+    assert_eq!(json["blockCount"], 5);
+    assert_eq!(json["root"]["children"][0]["blockId"], "note");
+  }
+}
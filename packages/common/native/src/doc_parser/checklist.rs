@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use super::{block_index::BlockIndex, get_string, ParseError};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListProgress {
+  /// The first todo of the list, to anchor a progress bar next to it.
+  pub first_block_id: String,
+  pub parent_id: String,
+  pub total: usize,
+  pub checked: usize,
+  /// `checked / total`.
+  pub ratio: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChecklistProgress {
+  pub total: usize,
+  pub checked: usize,
+  /// `checked / total`, `0` for docs without todos.
+  pub ratio: f64,
+  /// Runs of adjacent todo siblings in document order. Nested todos form
+  /// lists of their own and count towards the doc totals only once.
+  pub lists: Vec<ListProgress>,
+}
+
+/// Counts the checked todo items of a doc, per list and in total.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::compute_checklist_progress;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let progress = compute_checklist_progress(doc_bin, "doc-id".into()).unwrap();
+/// println!("{}/{} done", progress.checked, progress.total);
+/// ```
+pub fn compute_checklist_progress(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+) -> Result<ChecklistProgress, ParseError> {
+  let mut progress = ChecklistProgress {
+    total: 0,
+    checked: 0,
+    ratio: 0.0,
+    lists: vec![],
+  };
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(progress);
+  };
+
+  for parent_id in index.descendants(&index.root_block_id) {
+    let mut current: Option<ListProgress> = None;
+    for child_id in index.children_of(&parent_id) {
+      let Some(checked) = todo_state(&index, &child_id) else {
+        progress.lists.extend(current.take());
+        continue;
+      };
+      let list = current.get_or_insert_with(|| ListProgress {
+        first_block_id: child_id.clone(),
+        parent_id: parent_id.clone(),
+        total: 0,
+        checked: 0,
+        ratio: 0.0,
+      });
+      list.total += 1;
+      list.checked += usize::from(checked);
+    }
+    progress.lists.extend(current);
+  }
+
+  for list in &mut progress.lists {
+    list.ratio = ratio(list.checked, list.total);
+    progress.total += list.total;
+    progress.checked += list.checked;
+  }
+  progress.ratio = ratio(progress.checked, progress.total);
+
+  Ok(progress)
+}
+
+/// Whether `block_id` is a checked todo, `None` if it is no todo at all.
+fn todo_state(index: &BlockIndex, block_id: &str) -> Option<bool> {
+  let block = index.get(block_id)?;
+  if index.flavour_of(block_id).as_deref() != Some("affine:list")
+    || get_string(block, "prop:type").as_deref() != Some("todo")
+  {
+    return None;
+  }
+  Some(get_string(block, "prop:checked").as_deref() == Some("true"))
+}
+
+fn ratio(checked: usize, total: usize) -> f64 {
+  if total == 0 {
+    0.0
+  } else {
+    checked as f64 / total as f64
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_compute_checklist_progress() {
+    let mut doc = TestDoc::new("Launch");
+    let todo = |doc: &mut TestDoc, parent: &str, id: &str, checked: bool| {
+      let mut block = doc.add_block(parent, id, "affine:list", &[("type", "todo")]);
+      block.insert("prop:checked".into(), checked).unwrap();
+    };
+    todo(&mut doc, "note", "t1", true);
+    todo(&mut doc, "note", "t2", false);
+    todo(&mut doc, "t2", "t2a", true);
+    doc.add_block("note", "p", "affine:paragraph", &[("text", "break")]);
+    todo(&mut doc, "note", "t3", true);
+
+    let progress = compute_checklist_progress(doc.encode(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!((progress.checked, progress.total), (3, 4));
+    assert_eq!(progress.ratio, 0.75);
+
+    let lists = progress
+      .lists
+      .iter()
+      .map(|list| (list.first_block_id.as_str(), list.checked, list.total))
+      .collect::<Vec<_>>();
+    assert_eq!(lists, vec![("t1", 1, 2), ("t3", 1, 1), ("t2a", 1, 1)]);
+  }
+}
@@ -0,0 +1,105 @@
+use serde_json::Value as JsonValue;
+use y_octo::Map;
+
+/// CRC-32 of a block's serialized content, as lowercase hex.
+///
+/// The block is serialized to JSON with object keys sorted, so the checksum
+/// only depends on the content and not on the order props were written in.
+/// Text props contribute their plain text.
+pub(crate) fn block_checksum(block: &Map) -> String {
+  let json = serde_json::to_value(block).unwrap_or(JsonValue::Null);
+  let mut canonical = String::new();
+  write_canonical(&json, &mut canonical);
+  format!("{:08x}", crc32(canonical.as_bytes()))
+}
+
+fn write_canonical(value: &JsonValue, out: &mut String) {
+  match value {
+    JsonValue::Object(object) => {
+      let mut entries = object.iter().collect::<Vec<_>>();
+      entries.sort_by_key(|(key, _)| *key);
+      out.push('{');
+      for (i, (key, value)) in entries.into_iter().enumerate() {
+        if i > 0 {
+          out.push(',');
+        }
+        out.push_str(&JsonValue::String(key.clone()).to_string());
+        out.push(':');
+        write_canonical(value, out);
+      }
+      out.push('}');
+    }
+    JsonValue::Array(items) => {
+      out.push('[');
+      for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+          out.push(',');
+        }
+        write_canonical(item, out);
+      }
+      out.push(']');
+    }
+    scalar => out.push_str(&scalar.to_string()),
+  }
+}
+
+/// CRC-32 (IEEE 802.3), as used by zip and PNG.
+fn crc32(bytes: &[u8]) -> u32 {
+  !bytes.iter().fold(!0u32, |crc, byte| {
+    (0..8).fold(crc ^ u32::from(*byte), |crc, _| {
+      (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg())
+    })
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_from_binary_with_options, test_utils::TestDoc, CrawlOptions};
+
+  #[test]
+  fn test_crc32() {
+    assert_eq!(crc32(b""), 0);
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+  }
+
+  #[test]
+  fn test_block_checksums() {
+    let crawl = |doc: &TestDoc, checksums| {
+      parse_doc_from_binary_with_options(
+        doc.encode(),
+        TestDoc::DOC_ID.into(),
+        &CrawlOptions {
+          checksums,
+          ..Default::default()
+        },
+      )
+      .unwrap()
+      .blocks
+      .into_iter()
+      .map(|block| (block.block_id, block.checksum))
+      .collect::<Vec<_>>()
+    };
+
+    let mut doc = TestDoc::new("Checks");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "first")]);
+    let mut second = doc.add_block("note", "p2", "affine:paragraph", &[("text", "second")]);
+
+    assert!(crawl(&doc, false)
+      .iter()
+      .all(|(_, checksum)| checksum.is_none()));
+    let before = crawl(&doc, true);
+    assert!(before.iter().all(|(_, checksum)| checksum.is_some()));
+    assert_eq!(before, crawl(&doc, true));
+
+    second.insert("prop:type".into(), "quote").unwrap();
+    let after = crawl(&doc, true);
+    let changed = before
+      .iter()
+      .zip(&after)
+      .filter(|(before, after)| before != after)
+      .map(|(block, _)| block.0.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(changed, vec!["p2"]);
+  }
+}
@@ -0,0 +1,406 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+  block_index::BlockIndex, get_string, plain_text::block_text, ParseError, PAGE_FLAVOUR,
+};
+
+/// Joins the texts of the blocks of a chunk.
+const BLOCK_SEPARATOR: &str = "\n";
+/// Joins the title and headings of the context line.
+const PATH_SEPARATOR: &str = " > ";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingChunkOptions {
+  /// Most tokens a chunk may hold, context line included.
+  pub max_tokens: usize,
+  /// Starts each chunk with a line of the doc title and the headings it is
+  /// under, so chunks of a section still embed close to its topic. Left out
+  /// when it would take more than half of `max_tokens`.
+  pub with_context: bool,
+}
+
+impl Default for EmbeddingChunkOptions {
+  fn default() -> Self {
+    Self {
+      max_tokens: 512,
+      with_context: true,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingChunk {
+  pub text: String,
+  /// Headings the first block of the chunk is under, outermost first. A
+  /// chunk starting with a heading lists it last.
+  pub heading_path: Vec<String>,
+  /// Blocks of the chunk in document order.
+  pub block_ids: Vec<String>,
+  pub start_block_id: String,
+  pub end_block_id: String,
+  pub tokens: usize,
+  /// Holds only part of the text of its block, which was too long for a
+  /// single chunk.
+  pub partial: bool,
+}
+
+/// Splits a doc into chunks of at most `max_tokens` for embedding, keeping
+/// the blocks each chunk came from.
+///
+/// Blocks are read like [`super::parse_doc_to_text`] reads them and packed
+/// into chunks in document order. A heading always starts a new chunk, so
+/// a chunk never spans two sections, and blocks are only split when a
+/// single block does not fit, at whitespace, or between characters for
+/// words longer than a chunk. `count_tokens` is the tokenizer of the
+/// embedding model; chunks are packed by adding up the tokens of their
+/// blocks and words.
+///
+/// Fails with `ParseError::InvalidOptions` when `max_tokens` is 0.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{chunk_doc_for_embedding, EmbeddingChunkOptions};
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let chunks = chunk_doc_for_embedding(
+///   doc_bin,
+///   "doc-id".into(),
+///   &EmbeddingChunkOptions::default(),
+///   |text| text.split_whitespace().count(),
+/// )
+/// .unwrap();
+/// for chunk in &chunks {
+///   println!("{}..{}: {} tokens", chunk.start_block_id, chunk.end_block_id, chunk.tokens);
+/// }
+/// ```
+pub fn chunk_doc_for_embedding(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  options: &EmbeddingChunkOptions,
+  count_tokens: impl Fn(&str) -> usize,
+) -> Result<Vec<EmbeddingChunk>, ParseError> {
+  if options.max_tokens == 0 {
+    return Err(ParseError::InvalidOptions(
+      "`maxTokens` must be greater than 0".into(),
+    ));
+  }
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(vec![]);
+  };
+
+  let mut chunker = Chunker {
+    options,
+    count_tokens,
+    title: String::new(),
+    headings: vec![],
+    chunks: vec![],
+    current: vec![],
+    current_tokens: 0,
+  };
+  for block_id in index.descendants(&index.root_block_id) {
+    let (Some(block), Some(flavour)) = (index.get(&block_id), index.flavour_of(&block_id)) else {
+      continue;
+    };
+    let text = block_text(block, &flavour);
+    if flavour == PAGE_FLAVOUR {
+      chunker.title = text;
+      continue;
+    }
+    if text.is_empty() {
+      continue;
+    }
+
+    let level = match (flavour.as_str(), get_string(block, "prop:type").as_deref()) {
+      ("affine:paragraph", Some("h1")) => Some(1),
+      ("affine:paragraph", Some("h2")) => Some(2),
+      ("affine:paragraph", Some("h3")) => Some(3),
+      ("affine:paragraph", Some("h4")) => Some(4),
+      ("affine:paragraph", Some("h5")) => Some(5),
+      ("affine:paragraph", Some("h6")) => Some(6),
+      _ => None,
+    };
+    if let Some(level) = level {
+      chunker.flush();
+      chunker.headings.retain(|(other, _)| *other < level);
+      chunker.headings.push((level, text.clone()));
+    }
+    chunker.push(block_id, text);
+  }
+  chunker.flush();
+
+  Ok(chunker.chunks)
+}
+
+struct Chunker<'a, F> {
+  options: &'a EmbeddingChunkOptions,
+  count_tokens: F,
+  title: String,
+  /// Levels and texts of the headings above the current block.
+  headings: Vec<(u8, String)>,
+  chunks: Vec<EmbeddingChunk>,
+  /// Blocks of the chunk being filled, with their text.
+  current: Vec<(String, String)>,
+  /// Tokens of the blocks of the chunk being filled, separators included,
+  /// so adding a block only counts the tokens of its own text.
+  current_tokens: usize,
+}
+
+impl<F: Fn(&str) -> usize> Chunker<'_, F> {
+  fn push(&mut self, block_id: String, text: String) {
+    let budget = self.budget();
+    let tokens = (self.count_tokens)(&text);
+    if !self.current.is_empty() {
+      let separator = (self.count_tokens)(BLOCK_SEPARATOR);
+      if self.current_tokens + separator + tokens <= budget {
+        self.current.push((block_id, text));
+        self.current_tokens += separator + tokens;
+        return;
+      }
+      self.flush();
+    }
+
+    if tokens <= budget {
+      self.current.push((block_id, text));
+      self.current_tokens = tokens;
+    } else {
+      self.split(block_id, &text);
+    }
+  }
+
+  /// Closes the chunk being filled, if any.
+  fn flush(&mut self) {
+    if self.current.is_empty() {
+      return;
+    }
+    let blocks = std::mem::take(&mut self.current);
+    self.current_tokens = 0;
+    let body = blocks
+      .iter()
+      .map(|(_, text)| text.as_str())
+      .collect::<Vec<_>>()
+      .join(BLOCK_SEPARATOR);
+    let block_ids = blocks.into_iter().map(|(id, _)| id).collect();
+    self.emit(block_ids, body, false);
+  }
+
+  /// Cuts the text of a block too long for a chunk of its own at
+  /// whitespace, into chunks as large as fit. Words that do not fit a chunk
+  /// on their own are cut between characters.
+  fn split(&mut self, block_id: String, text: &str) {
+    let budget = self.budget();
+    let mut piece = String::new();
+    let mut piece_tokens = 0;
+    for word in text.split_inclusive(char::is_whitespace) {
+      let tokens = (self.count_tokens)(word.trim_end());
+      if !piece.is_empty() && piece_tokens + tokens > budget {
+        self.emit_piece(&block_id, &mut piece);
+        piece_tokens = 0;
+      }
+      if tokens <= budget {
+        piece.push_str(word);
+        piece_tokens += tokens;
+        continue;
+      }
+
+      for char in word.chars() {
+        piece.push(char);
+        if piece.len() > char.len_utf8() && (self.count_tokens)(piece.trim_end()) > budget {
+          piece.pop();
+          self.emit_piece(&block_id, &mut piece);
+          piece.push(char);
+        }
+      }
+      piece_tokens = (self.count_tokens)(piece.trim_end());
+    }
+    self.emit_piece(&block_id, &mut piece);
+  }
+
+  /// Emits the text cut from a block so far, and clears it.
+  fn emit_piece(&mut self, block_id: &str, piece: &mut String) {
+    let text = std::mem::take(piece);
+    let text = text.trim_end();
+    if !text.is_empty() {
+      self.emit(vec![block_id.to_string()], text.to_string(), true);
+    }
+  }
+
+  fn emit(&mut self, block_ids: Vec<String>, body: String, partial: bool) {
+    let text = match self.context() {
+      Some(context) => format!("{context}{BLOCK_SEPARATOR}{body}"),
+      None => body,
+    };
+    self.chunks.push(EmbeddingChunk {
+      tokens: (self.count_tokens)(&text),
+      text,
+      heading_path: self.headings.iter().map(|(_, text)| text.clone()).collect(),
+      start_block_id: block_ids.first().cloned().unwrap_or_default(),
+      end_block_id: block_ids.last().cloned().unwrap_or_default(),
+      block_ids,
+      partial,
+    });
+  }
+
+  /// The context line of the chunk being filled, if it is on and fits.
+  fn context(&self) -> Option<String> {
+    if !self.options.with_context {
+      return None;
+    }
+    let context = std::iter::once(self.title.as_str())
+      .chain(self.headings.iter().map(|(_, text)| text.as_str()))
+      .filter(|text| !text.is_empty())
+      .collect::<Vec<_>>()
+      .join(PATH_SEPARATOR);
+    if context.is_empty() || (self.count_tokens)(&context) * 2 > self.options.max_tokens {
+      return None;
+    }
+    Some(context)
+  }
+
+  /// Tokens left for the blocks of a chunk once the context line is in.
+  fn budget(&self) -> usize {
+    let context = self.context().map_or(0, |context| {
+      (self.count_tokens)(&format!("{context}{BLOCK_SEPARATOR}"))
+    });
+    self.options.max_tokens.saturating_sub(context)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  fn words(text: &str) -> usize {
+    text.split_whitespace().count()
+  }
+
+  #[test]
+  fn test_chunk_doc_for_embedding() {
+    let mut doc = TestDoc::new("Guide");
+    doc.add_block(
+      "note",
+      "intro",
+      "affine:paragraph",
+      &[("text", "Read this first.")],
+    );
+    doc.add_block(
+      "note",
+      "h1",
+      "affine:paragraph",
+      &[("type", "h1"), ("text", "Setup")],
+    );
+    doc.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[("text", "Install the app.")],
+    );
+    doc.add_block(
+      "note",
+      "p2",
+      "affine:paragraph",
+      &[("text", "Sign in once.")],
+    );
+    doc.add_block(
+      "note",
+      "h2",
+      "affine:paragraph",
+      &[("type", "h2"), ("text", "Sync")],
+    );
+    doc.add_block(
+      "note",
+      "p3",
+      "affine:paragraph",
+      &[("text", "one two three four five six seven eight nine ten")],
+    );
+    let doc_bin = doc.encode();
+
+    let options = EmbeddingChunkOptions {
+      max_tokens: 12,
+      with_context: true,
+    };
+    let chunks =
+      chunk_doc_for_embedding(doc_bin.clone(), TestDoc::DOC_ID.into(), &options, words).unwrap();
+    let summary = chunks
+      .iter()
+      .map(|chunk| {
+        (
+          chunk.text.as_str(),
+          chunk.block_ids.join(","),
+          chunk.partial,
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      summary,
+      [
+        ("Guide\nRead this first.", "intro".to_string(), false),
+        (
+          "Guide > Setup\nSetup\nInstall the app.\nSign in once.",
+          "h1,p1,p2".to_string(),
+          false
+        ),
+        ("Guide > Setup > Sync\nSync", "h2".to_string(), false),
+        (
+          "Guide > Setup > Sync\none two three four five six seven",
+          "p3".to_string(),
+          true
+        ),
+        (
+          "Guide > Setup > Sync\neight nine ten",
+          "p3".to_string(),
+          true
+        ),
+      ]
+    );
+    assert_eq!(chunks[1].heading_path, ["Setup"]);
+    assert_eq!(
+      (
+        chunks[1].start_block_id.as_str(),
+        chunks[1].end_block_id.as_str()
+      ),
+      ("h1", "p2")
+    );
+    assert!(chunks
+      .iter()
+      .all(|chunk| chunk.tokens <= options.max_tokens));
+
+    // a word longer than a chunk is cut between characters
+    let mut doc = TestDoc::new("");
+    let long_word = "a".repeat(30);
+    doc.add_block(
+      "note",
+      "blob",
+      "affine:paragraph",
+      &[("text", &format!("{long_word} tail"))],
+    );
+    let options = EmbeddingChunkOptions {
+      max_tokens: 3,
+      with_context: false,
+    };
+    // about four characters a token
+    let quarters = |text: &str| text.chars().count().div_ceil(4);
+    let chunks =
+      chunk_doc_for_embedding(doc.encode(), TestDoc::DOC_ID.into(), &options, quarters).unwrap();
+    assert_eq!(
+      chunks
+        .iter()
+        .map(|chunk| chunk.text.as_str())
+        .collect::<Vec<_>>(),
+      [&long_word[..12], &long_word[..12], "aaaaaa tail"]
+    );
+    assert!(chunks
+      .iter()
+      .all(|chunk| chunk.partial && chunk.tokens <= options.max_tokens));
+
+    let options = EmbeddingChunkOptions {
+      max_tokens: 0,
+      with_context: false,
+    };
+    assert!(matches!(
+      chunk_doc_for_embedding(doc_bin, TestDoc::DOC_ID.into(), &options, words),
+      Err(ParseError::InvalidOptions(_))
+    ));
+  }
+}
@@ -0,0 +1,431 @@
+use std::{collections::HashSet, sync::LazyLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::{
+  block_index::BlockIndex, database::read_database, get_string, text_content, ParseError,
+  BOOKMARK_FLAVOURS, NOTE_FLAVOUR, PAGE_FLAVOUR,
+};
+
+/// Best score a doc needs for its kind, below it the doc is
+/// [`DocKind::Other`].
+const MIN_SCORE: f64 = 0.5;
+/// Texts up to this many words are checked for terms, like headings.
+const LABEL_WORDS: usize = 5;
+
+const MEETING_TERMS: &[&str] = &[
+  "meeting",
+  "agenda",
+  "attendees",
+  "participants",
+  "action items",
+  "minutes",
+  "standup",
+  "stand-up",
+  "retro",
+  "next steps",
+  "decisions",
+];
+const SPEC_TERMS: &[&str] = &[
+  "spec",
+  "specification",
+  "requirements",
+  "goals",
+  "non-goals",
+  "background",
+  "motivation",
+  "design",
+  "proposal",
+  "scope",
+  "api",
+  "architecture",
+  "alternatives",
+  "open questions",
+  "rollout",
+];
+const STATUS_NAMES: &[&str] = &["status", "stage", "state", "progress"];
+
+/// Dates as titles of journal entries and meeting notes spell them.
+static DATE: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(
+    r"(?i)\b(?:\d{4}[-/.]\d{1,2}[-/.]\d{1,2}|\d{1,2}[-/.]\d{1,2}[-/.]\d{2,4}|(?:jan|feb|mar|apr|may|jun|jul|aug|sep|sept|oct|nov|dec)[a-z]*\.?\s+\d{1,2}|\d{1,2}\s+(?:jan|feb|mar|apr|may|jun|jul|aug|sep|sept|oct|nov|dec)[a-z]*)\b",
+  )
+  .expect("valid date pattern")
+});
+
+static URL: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^\s*https?://\S+\s*$").expect("valid url pattern"));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocKind {
+  MeetingNotes,
+  Spec,
+  Journal,
+  KanbanProject,
+  LinkDump,
+  /// No kind scored high enough.
+  Other,
+}
+
+impl DocKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      DocKind::MeetingNotes => "meeting_notes",
+      DocKind::Spec => "spec",
+      DocKind::Journal => "journal",
+      DocKind::KanbanProject => "kanban_project",
+      DocKind::LinkDump => "link_dump",
+      DocKind::Other => "other",
+    }
+  }
+}
+
+/// The measurements the kinds are scored from.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DocFeatures {
+  /// Content blocks: all but the page, notes, surfaces and database rows.
+  pub blocks: usize,
+  /// Shares of the content blocks, from 0 to 1.
+  pub heading_ratio: f64,
+  pub todo_ratio: f64,
+  pub code_ratio: f64,
+  /// Bookmarks, embeds and paragraphs holding a URL only.
+  pub link_ratio: f64,
+  /// Plain paragraphs with more than a few words.
+  pub prose_ratio: f64,
+  /// Whether the title contains a date.
+  pub dated_title: bool,
+  /// Database views in kanban mode.
+  pub kanban_views: usize,
+  /// Select columns named like a status.
+  pub status_columns: usize,
+  /// Distinct meeting and spec terms in the title, headings and short
+  /// labels.
+  pub meeting_terms: usize,
+  pub spec_terms: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocKindScore {
+  pub kind: DocKind,
+  /// From 0 to 1.
+  pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocClassification {
+  pub kind: DocKind,
+  /// Score of `kind`, the best score for [`DocKind::Other`].
+  pub confidence: f64,
+  /// Every kind but [`DocKind::Other`], best first.
+  pub scores: Vec<DocKindScore>,
+  pub features: DocFeatures,
+}
+
+/// Guesses what kind of doc a doc is from its structure: meeting notes,
+/// spec, journal entry, kanban project or link collection.
+///
+/// Each kind is scored from 0 to 1 by [`DocFeatures`] measured on the doc,
+/// and the best kind wins when it scores at least 0.5. Scores and features
+/// are returned as well, so callers can apply thresholds of their own.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::classify_doc;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let classification = classify_doc(doc_bin, "doc-id".into()).unwrap();
+/// println!("{} ({:.2})", classification.kind.as_str(), classification.confidence);
+/// ```
+pub fn classify_doc(doc_bin: Vec<u8>, doc_id: String) -> Result<DocClassification, ParseError> {
+  let features = match BlockIndex::load(&doc_bin, &doc_id)? {
+    Some(index) => measure(&index),
+    None => DocFeatures::default(),
+  };
+  Ok(classify(features))
+}
+
+fn measure(index: &BlockIndex) -> DocFeatures {
+  let mut features = DocFeatures::default();
+  let mut meeting_terms = HashSet::new();
+  let mut spec_terms = HashSet::new();
+  let mut collect_terms = |text: &str| {
+    let text = text.to_lowercase();
+    meeting_terms.extend(find_terms(&text, MEETING_TERMS));
+    spec_terms.extend(find_terms(&text, SPEC_TERMS));
+  };
+
+  let (mut headings, mut todos, mut code, mut links, mut prose) = (0, 0, 0, 0, 0);
+  for block_id in index.descendants(&index.root_block_id) {
+    let Some(block) = index.get(&block_id) else {
+      continue;
+    };
+    let flavour = index.flavour_of(&block_id).unwrap_or_default();
+    let in_database = index
+      .parent_of(&block_id)
+      .and_then(|parent_id| index.flavour_of(parent_id))
+      .as_deref()
+      == Some("affine:database");
+    match flavour.as_str() {
+      PAGE_FLAVOUR => {
+        let title = get_string(block, "prop:title").unwrap_or_default();
+        features.dated_title = DATE.is_match(&title);
+        collect_terms(&title);
+        continue;
+      }
+      NOTE_FLAVOUR | "affine:surface" => continue,
+      _ if in_database => continue,
+      _ => {}
+    }
+    features.blocks += 1;
+
+    let text = text_content(block, "prop:text")
+      .map(|(text, _)| text)
+      .unwrap_or_default();
+    let type_ = get_string(block, "prop:type");
+    let words = text.split_whitespace().count();
+    match (flavour.as_str(), type_.as_deref()) {
+      ("affine:paragraph", Some("h1" | "h2" | "h3" | "h4" | "h5" | "h6")) => {
+        headings += 1;
+        collect_terms(&text);
+      }
+      ("affine:paragraph", _) if URL.is_match(&text) => links += 1,
+      ("affine:paragraph", _) => {
+        if words > LABEL_WORDS {
+          prose += 1;
+        } else {
+          collect_terms(&text);
+        }
+      }
+      ("affine:list", Some("todo")) => todos += 1,
+      ("affine:list", _) if URL.is_match(&text) => links += 1,
+      ("affine:code", _) => code += 1,
+      ("affine:database", _) => {
+        let database = read_database(index, block);
+        features.kanban_views += database
+          .views
+          .iter()
+          .filter(|view| view.mode == "kanban")
+          .count();
+        features.status_columns += database
+          .columns
+          .iter()
+          .filter(|column| column.column_type == "select")
+          .filter(|column| STATUS_NAMES.contains(&column.name.trim().to_lowercase().as_str()))
+          .count();
+      }
+      (flavour, _)
+        if BOOKMARK_FLAVOURS.contains(&flavour)
+          || matches!(
+            flavour,
+            "affine:embed-linked-doc" | "affine:embed-synced-doc" | "affine:embed-iframe"
+          ) =>
+      {
+        links += 1
+      }
+      _ => {}
+    }
+  }
+
+  if features.blocks > 0 {
+    let blocks = features.blocks as f64;
+    let ratio = |count: usize| count as f64 / blocks;
+    features.heading_ratio = ratio(headings);
+    features.todo_ratio = ratio(todos);
+    features.code_ratio = ratio(code);
+    features.link_ratio = ratio(links);
+    features.prose_ratio = ratio(prose);
+  }
+  features.meeting_terms = meeting_terms.len();
+  features.spec_terms = spec_terms.len();
+  features
+}
+
+/// The terms of `terms` found as whole words in lowercased `text`.
+fn find_terms<'a>(text: &str, terms: &[&'a str]) -> Vec<&'a str> {
+  terms
+    .iter()
+    .filter(|term| {
+      text.match_indices(**term).any(|(start, _)| {
+        let end = start + term.len();
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+      })
+    })
+    .copied()
+    .collect()
+}
+
+fn classify(features: DocFeatures) -> DocClassification {
+  // how far a value got towards the level that counts as fully present
+  let towards = |value: f64, full: f64| (value / full).min(1.0);
+  let flag = |value: bool| if value { 1.0 } else { 0.0 };
+
+  let mut scores = if features.blocks == 0 {
+    vec![]
+  } else {
+    vec![
+      DocKindScore {
+        kind: DocKind::MeetingNotes,
+        score: 0.55 * towards(features.meeting_terms as f64, 2.0)
+          + 0.25 * flag(features.dated_title)
+          + 0.2 * towards(features.todo_ratio, 0.1),
+      },
+      DocKindScore {
+        kind: DocKind::Spec,
+        score: 0.5 * towards(features.spec_terms as f64, 3.0)
+          + 0.3 * towards(features.heading_ratio, 0.15)
+          + 0.2 * towards(features.code_ratio, 0.05),
+      },
+      DocKindScore {
+        kind: DocKind::Journal,
+        score: 0.5 * flag(features.dated_title)
+          + 0.5
+            * towards(features.prose_ratio, 0.6)
+            * (1.0 - towards(features.meeting_terms as f64, 2.0)),
+      },
+      DocKindScore {
+        kind: DocKind::KanbanProject,
+        score: 0.6 * towards(features.kanban_views as f64, 1.0)
+          + 0.2 * towards(features.status_columns as f64, 1.0)
+          + 0.2 * towards(features.todo_ratio, 0.3),
+      },
+      DocKindScore {
+        kind: DocKind::LinkDump,
+        score: towards(features.link_ratio, 0.6),
+      },
+    ]
+  };
+  // the sort is stable, ties keep the order above
+  scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+  let (kind, confidence) = match scores.first() {
+    Some(best) if best.score >= MIN_SCORE => (best.kind, best.score),
+    Some(best) => (DocKind::Other, best.score),
+    None => (DocKind::Other, 0.0),
+  };
+  DocClassification {
+    kind,
+    confidence,
+    scores,
+    features,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  fn kind_of(doc: &TestDoc) -> DocKind {
+    classify_doc(doc.encode(), TestDoc::DOC_ID.into())
+      .unwrap()
+      .kind
+  }
+
+  #[test]
+  fn test_classify_doc() {
+    let mut meeting = TestDoc::new("Weekly sync 2024-03-18");
+    meeting.add_block(
+      "note",
+      "h1",
+      "affine:paragraph",
+      &[("type", "h2"), ("text", "Attendees")],
+    );
+    meeting.add_block("note", "p1", "affine:paragraph", &[("text", "Ann, Bo")]);
+    meeting.add_block(
+      "note",
+      "h2",
+      "affine:paragraph",
+      &[("type", "h2"), ("text", "Action items")],
+    );
+    meeting.add_block(
+      "note",
+      "t1",
+      "affine:list",
+      &[("type", "todo"), ("text", "Send notes")],
+    );
+    let classification = classify_doc(meeting.encode(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(classification.kind, DocKind::MeetingNotes);
+    assert!(classification.features.dated_title);
+    assert_eq!(classification.features.meeting_terms, 2);
+    assert_eq!(classification.scores.len(), 5);
+
+    let mut spec = TestDoc::new("Sync protocol");
+    for (id, heading) in [("h1", "Background"), ("h2", "Goals"), ("h3", "Design")] {
+      spec.add_block(
+        "note",
+        id,
+        "affine:paragraph",
+        &[("type", "h1"), ("text", heading)],
+      );
+      spec.add_block(
+        "note",
+        &format!("{id}-p"),
+        "affine:paragraph",
+        &[(
+          "text",
+          "Clients exchange state vectors before sending updates.",
+        )],
+      );
+    }
+    spec.add_block("note", "c1", "affine:code", &[("text", "fn sync() {}")]);
+    assert_eq!(kind_of(&spec), DocKind::Spec);
+
+    let mut journal = TestDoc::new("March 18");
+    for id in ["p1", "p2"] {
+      journal.add_block(
+        "note",
+        id,
+        "affine:paragraph",
+        &[(
+          "text",
+          "Walked to the lake and read for an hour in the sun.",
+        )],
+      );
+    }
+    assert_eq!(kind_of(&journal), DocKind::Journal);
+
+    let mut board = TestDoc::new("Launch");
+    board.add_block("note", "db", "affine:database", &[("title", "Tasks")]);
+    board.add_block(
+      "db",
+      "r1",
+      "affine:paragraph",
+      &[("text", "Write the announcement")],
+    );
+    board.set_database_cells(
+      "db",
+      &[("c0", "Name", "title"), ("c1", "Status", "select")],
+      &[],
+    );
+    board.set_database_views("db", &[("v1", "kanban")]);
+    assert_eq!(kind_of(&board), DocKind::KanbanProject);
+
+    let mut links = TestDoc::new("Reading list");
+    links.add_block(
+      "note",
+      "b1",
+      "affine:bookmark",
+      &[("url", "https://example.com/a")],
+    );
+    links.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[("text", "https://example.com/b")],
+    );
+    links.add_block("note", "p2", "affine:paragraph", &[("text", "to read")]);
+    assert_eq!(kind_of(&links), DocKind::LinkDump);
+
+    let mut other = TestDoc::new("Untitled");
+    other.add_block("note", "p1", "affine:paragraph", &[("text", "hi")]);
+    let classification = classify_doc(other.encode(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(classification.kind, DocKind::Other);
+    assert!(classification.confidence < MIN_SCORE);
+  }
+}
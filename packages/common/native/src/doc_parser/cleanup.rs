@@ -0,0 +1,247 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use y_octo::Text;
+
+use super::{
+  block_index::BlockIndex,
+  get_string,
+  options::{parse_options, OptionsObject},
+  ParseError, NOTE_FLAVOUR,
+};
+
+/// Which passes [`cleanup_doc`] runs. Every pass is enabled by default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct CleanupOptions {
+  /// Remove blank paragraphs at the end of each note.
+  pub remove_trailing_empty: bool,
+  /// Keep only the first of adjacent blank paragraphs.
+  pub collapse_blank_runs: bool,
+  /// Trim whitespace at the end of every line of block text.
+  pub trim_trailing_whitespace: bool,
+}
+
+impl Default for CleanupOptions {
+  fn default() -> Self {
+    Self {
+      remove_trailing_empty: true,
+      collapse_blank_runs: true,
+      trim_trailing_whitespace: true,
+    }
+  }
+}
+
+impl CleanupOptions {
+  /// Parses and checks options sent as JSON, see [`validate_options`].
+  ///
+  /// [`validate_options`]: super::validate_options
+  pub fn from_json(json: &str) -> Result<Self, ParseError> {
+    parse_options(json)
+  }
+}
+
+impl OptionsObject for CleanupOptions {
+  fn check(&self) -> Result<(), String> {
+    if !self.remove_trailing_empty && !self.collapse_blank_runs && !self.trim_trailing_whitespace {
+      return Err("at least one cleanup pass must be enabled".into());
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupResult {
+  /// Update to apply to the doc, empty of changes when it was already clean.
+  pub update: Vec<u8>,
+  /// Sorted ids of the removed blocks.
+  pub removed_block_ids: Vec<String>,
+  /// Sorted ids of the blocks whose text was trimmed.
+  pub trimmed_block_ids: Vec<String>,
+}
+
+/// Tidies up whitespace and blank paragraphs and returns the changes as an
+/// update.
+///
+/// A paragraph is blank when it is plain text, has no children and its text
+/// is empty or whitespace only. A note is never emptied completely: when all
+/// of its children are blank, the first one stays. Text that contains
+/// embedded objects is left untrimmed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{cleanup_doc, CleanupOptions};
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let result = cleanup_doc(doc_bin, "doc-id".into(), &CleanupOptions::default()).unwrap();
+/// println!("removed {} blocks", result.removed_block_ids.len());
+/// ```
+pub fn cleanup_doc(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  options: &CleanupOptions,
+) -> Result<CleanupResult, ParseError> {
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(CleanupResult {
+      update: vec![],
+      removed_block_ids: vec![],
+      trimmed_block_ids: vec![],
+    });
+  };
+  let state_vector = index.doc().get_state_vector();
+  let mut removed = BTreeSet::new();
+  let mut trimmed = BTreeSet::new();
+
+  if options.trim_trailing_whitespace {
+    for (block_id, block) in &index.block_pool {
+      let Some(mut text) = block.get("prop:text").and_then(|value| value.to_text()) else {
+        continue;
+      };
+      if trim_lines(&mut text)? {
+        trimmed.insert(block_id.clone());
+      }
+    }
+  }
+
+  let mut blocks = index.doc().get_map("blocks")?;
+  for parent_id in index.descendants(&index.root_block_id) {
+    let child_ids = index.children_of(&parent_id);
+    let blank = child_ids
+      .iter()
+      .map(|child_id| is_blank(&index, child_id))
+      .collect::<Vec<_>>();
+
+    let mut positions = BTreeSet::new();
+    if options.collapse_blank_runs {
+      positions.extend((1..blank.len()).filter(|&i| blank[i] && blank[i - 1]));
+    }
+    if options.remove_trailing_empty
+      && index.flavour_of(&parent_id).as_deref() == Some(NOTE_FLAVOUR)
+    {
+      positions.extend((1..blank.len()).rev().take_while(|&i| blank[i]));
+    }
+    if positions.is_empty() {
+      continue;
+    }
+
+    let Some(mut children) = index
+      .get(&parent_id)
+      .and_then(|parent| parent.get("sys:children"))
+      .and_then(|value| value.to_array())
+    else {
+      continue;
+    };
+    for position in positions.into_iter().rev() {
+      children.remove(position as u64, 1)?;
+      let child_id = &child_ids[position];
+      blocks.remove(child_id);
+      trimmed.remove(child_id);
+      removed.insert(child_id.clone());
+    }
+  }
+
+  Ok(CleanupResult {
+    update: index.doc().encode_state_as_update_v1(&state_vector)?,
+    removed_block_ids: removed.into_iter().collect(),
+    trimmed_block_ids: trimmed.into_iter().collect(),
+  })
+}
+
+fn is_blank(index: &BlockIndex, block_id: &str) -> bool {
+  let Some(block) = index.get(block_id) else {
+    return false;
+  };
+  index.flavour_of(block_id).as_deref() == Some("affine:paragraph")
+    && get_string(block, "prop:type").is_none_or(|type_| type_ == "text")
+    && index.children_of(block_id).is_empty()
+    && get_string(block, "prop:text").is_none_or(|text| text.trim().is_empty())
+}
+
+/// Removes whitespace before every line break and at the end of `text`.
+/// Returns whether anything was removed.
+fn trim_lines(text: &mut Text) -> Result<bool, ParseError> {
+  let content = text.to_string();
+  // offsets are in UTF-16 units and only line up with the string content
+  // when the text holds nothing but strings
+  if content.encode_utf16().count() as u64 != text.len() {
+    return Ok(false);
+  }
+
+  let mut ranges = Vec::new();
+  let mut offset = 0;
+  for line in content.split('\n') {
+    let kept = line.trim_end();
+    let kept_len = kept.encode_utf16().count();
+    let line_len = line.encode_utf16().count();
+    if kept_len < line_len {
+      ranges.push((offset + kept_len, line_len - kept_len));
+    }
+    // skip the line and its line break
+    offset += line_len + 1;
+  }
+
+  for (start, len) in ranges.iter().rev() {
+    text.remove(*start as u64, *len as u64)?;
+  }
+  Ok(!ranges.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_to_markdown, test_utils::TestDoc};
+
+  #[test]
+  fn test_cleanup_doc() {
+    let mut doc = TestDoc::new("Tidy");
+    doc.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[("text", "one  \ntwo\t")],
+    );
+    doc.add_block("note", "b1", "affine:paragraph", &[]);
+    doc.add_block("note", "b2", "affine:paragraph", &[("text", "  ")]);
+    doc.add_block("note", "p2", "affine:paragraph", &[("text", "three")]);
+    doc.add_block("note", "h", "affine:paragraph", &[("type", "h1")]);
+    doc.add_block("note", "b3", "affine:paragraph", &[]);
+    doc.add_block("note", "b4", "affine:paragraph", &[("text", "\t")]);
+    let doc_bin = doc.encode();
+
+    let result = cleanup_doc(
+      doc_bin.clone(),
+      TestDoc::DOC_ID.into(),
+      &CleanupOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(result.removed_block_ids, vec!["b2", "b3", "b4"]);
+    assert_eq!(result.trimmed_block_ids, vec!["p1"]);
+
+    let mut cleaned = y_octo::DocOptions::new()
+      .with_guid(TestDoc::DOC_ID.to_string())
+      .build();
+    cleaned.apply_update_from_binary_v1(&doc_bin).unwrap();
+    cleaned.apply_update_from_binary_v1(&result.update).unwrap();
+    let cleaned_bin = cleaned.encode_update_v1().unwrap();
+    let markdown =
+      parse_doc_to_markdown(cleaned_bin.clone(), TestDoc::DOC_ID.into(), false).unwrap();
+    assert_eq!(markdown.markdown, "one\ntwo\nthree\n");
+
+    let again = cleanup_doc(
+      cleaned_bin,
+      TestDoc::DOC_ID.into(),
+      &CleanupOptions::default(),
+    )
+    .unwrap();
+    assert!(again.removed_block_ids.is_empty() && again.trimmed_block_ids.is_empty());
+
+    let options = CleanupOptions {
+      collapse_blank_runs: false,
+      trim_trailing_whitespace: false,
+      ..Default::default()
+    };
+    let result = cleanup_doc(doc_bin, TestDoc::DOC_ID.into(), &options).unwrap();
+    assert_eq!(result.removed_block_ids, vec!["b3", "b4"]);
+  }
+}
@@ -0,0 +1,90 @@
+use super::{new_doc, ParseError};
+
+/// Merges updates of a doc into a single update, the same as applying them
+/// one after another. Updates may overlap and come in any order. No updates
+/// merge into the empty update.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::merge_updates;
+///
+/// let updates = vec![
+///   std::fs::read("fixtures/update-1.bin").unwrap(),
+///   std::fs::read("fixtures/update-2.bin").unwrap(),
+/// ];
+/// let merged = merge_updates(updates).unwrap();
+/// ```
+pub fn merge_updates(updates: Vec<Vec<u8>>) -> Result<Vec<u8>, ParseError> {
+  let mut doc = new_doc(None);
+  for update in updates {
+    doc
+      .apply_update_from_binary_v1(&update)
+      .map_err(|_| ParseError::InvalidBinary)?;
+  }
+  Ok(doc.encode_update_v1()?)
+}
+
+/// Re-encodes a doc snapshot with the content of deleted items dropped, so
+/// stored snapshots stop growing with everything ever typed and removed.
+///
+/// Item ids and the delete set are kept, as Yjs garbage collection does, so
+/// the compacted snapshot merges with updates made against the original.
+/// Only the content of deleted items is lost, which versions restored from
+/// older snapshots or updates still carry.
+pub fn compact_snapshot(doc_bin: Vec<u8>) -> Result<Vec<u8>, ParseError> {
+  if doc_bin.is_empty() || doc_bin == [0, 0] {
+    return Err(ParseError::InvalidBinary);
+  }
+  let mut doc = new_doc(None);
+  doc
+    .apply_update_from_binary_v1(&doc_bin)
+    .map_err(|_| ParseError::InvalidBinary)?;
+  doc.gc()?;
+  Ok(doc.encode_update_v1()?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::{doc_text, TestDoc};
+
+  #[test]
+  fn test_merge_updates() {
+    let mut doc = TestDoc::new("Merge");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "one")]);
+    let first = doc.encode();
+    doc.add_block("note", "p2", "affine:paragraph", &[("text", "two")]);
+    let second = doc.encode();
+
+    let merged = merge_updates(vec![second, first.clone()]).unwrap();
+    assert_eq!(doc_text(merged), "Merge\none\ntwo");
+
+    assert_eq!(merge_updates(vec![]).unwrap(), vec![0, 0]);
+    assert!(matches!(
+      merge_updates(vec![first, vec![0xff]]),
+      Err(ParseError::InvalidBinary)
+    ));
+  }
+
+  #[test]
+  fn test_compact_snapshot() {
+    let mut doc = TestDoc::new("Compact");
+    let paragraph = doc.add_block("note", "p1", "affine:paragraph", &[("text", "kept")]);
+    let mut content = paragraph
+      .get("prop:text")
+      .and_then(|value| value.to_text())
+      .unwrap();
+    content.insert(4, " removed".repeat(100)).unwrap();
+    content.remove(4, 800).unwrap();
+    let doc_bin = doc.encode();
+
+    let compacted = compact_snapshot(doc_bin.clone()).unwrap();
+    assert!(compacted.len() < doc_bin.len() / 2);
+    assert_eq!(doc_text(compacted.clone()), doc_text(doc_bin.clone()));
+
+    // updates made against the original still apply
+    let merged = merge_updates(vec![compacted, doc_bin]).unwrap();
+    assert_eq!(doc_text(merged), "Compact\nkept");
+  }
+}
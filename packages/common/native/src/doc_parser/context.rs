@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+  block_index::BlockIndex,
+  markdown::{render_blocks, RenderedBlock},
+  text_content, MarkdownOptions, ParseError,
+};
+
+/// A block rendered together with its neighbours, e.g. to quote it in a
+/// notification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockContext {
+  pub title: String,
+  /// Ids of the rendered blocks in document order, the target included.
+  pub block_ids: Vec<String>,
+  pub markdown: String,
+  pub plain_text: String,
+}
+
+/// Renders `block_id` with up to `radius` rendered blocks before and after it.
+///
+/// Blocks that render nothing on their own, such as notes or database rows,
+/// are quoted through their closest rendered ancestor or first rendered
+/// descendant.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::render_block_context;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let context = render_block_context(doc_bin, "doc-id".into(), "block-id", 2).unwrap();
+/// println!("{}", context.plain_text);
+/// ```
+pub fn render_block_context(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  block_id: &str,
+  radius: usize,
+) -> Result<BlockContext, ParseError> {
+  let index = BlockIndex::load(&doc_bin, &doc_id)?
+    .filter(|index| index.get(block_id).is_some())
+    .ok_or_else(|| ParseError::BlockNotFound(block_id.to_string()))?;
+
+  let (title, rendered, _, _) =
+    render_blocks(&index, &index.root_block_id, &MarkdownOptions::default());
+
+  let Some(position) = find_position(&index, &rendered, block_id) else {
+    return Ok(BlockContext {
+      title,
+      block_ids: vec![],
+      markdown: String::new(),
+      plain_text: String::new(),
+    });
+  };
+
+  let start = position.saturating_sub(radius);
+  let end = (position + radius + 1).min(rendered.len());
+
+  let mut context = BlockContext {
+    title,
+    block_ids: Vec::with_capacity(end - start),
+    markdown: String::new(),
+    plain_text: String::new(),
+  };
+  for block in &rendered[start..end] {
+    let plain_text = index
+      .get(&block.block_id)
+      .and_then(|block| text_content(block, "prop:text"))
+      .map(|(text, _)| text)
+      .unwrap_or_else(|| block.markdown.trim().to_string());
+
+    context.block_ids.push(block.block_id.clone());
+    context.markdown.push_str(&block.markdown);
+    context.plain_text.push_str(&plain_text);
+    context.plain_text.push('\n');
+  }
+
+  Ok(context)
+}
+
+fn find_position(index: &BlockIndex, rendered: &[RenderedBlock], block_id: &str) -> Option<usize> {
+  let position_of = |id: &str| rendered.iter().position(|block| block.block_id == id);
+
+  let mut cursor = Some(block_id);
+  while let Some(id) = cursor {
+    if let Some(position) = position_of(id) {
+      return Some(position);
+    }
+    cursor = index.parent_of(id).map(String::as_str);
+  }
+
+  rendered.iter().position(|block| {
+    let mut cursor = index.parent_of(&block.block_id);
+    while let Some(id) = cursor {
+      if id == block_id {
+        return true;
+      }
+      cursor = index.parent_of(id);
+    }
+    false
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_render_block_context() {
+    let mut doc = TestDoc::new("Thread");
+    for (id, text) in [
+      ("p1", "one"),
+      ("p2", "two"),
+      ("p3", "three"),
+      ("p4", "four"),
+    ] {
+      doc.add_block("note", id, "affine:paragraph", &[("text", text)]);
+    }
+    doc.add_block(
+      "note",
+      "q",
+      "affine:paragraph",
+      &[("type", "quote"), ("text", "five")],
+    );
+    let doc_bin = doc.encode();
+
+    let context = render_block_context(doc_bin.clone(), TestDoc::DOC_ID.into(), "p2", 1).unwrap();
+    assert_eq!(context.title, "Thread");
+    assert_eq!(context.block_ids, vec!["p1", "p2", "p3"]);
+    assert_eq!(context.markdown, "one\ntwo\nthree\n");
+
+    let context = render_block_context(doc_bin.clone(), TestDoc::DOC_ID.into(), "q", 1).unwrap();
+    assert_eq!(context.markdown, "four\n> five\n");
+    assert_eq!(context.plain_text, "four\nfive\n");
+
+    // the note renders nothing, its first child is quoted instead
+    let context = render_block_context(doc_bin, TestDoc::DOC_ID.into(), "note", 0).unwrap();
+    assert_eq!(context.block_ids, vec!["p1"]);
+  }
+}
@@ -0,0 +1,166 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, NaiveDate};
+use y_octo::Update;
+
+use super::ParseError;
+
+/// A stored doc update with the time it was received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimestampedUpdate {
+  /// Milliseconds since the Unix epoch.
+  pub timestamp: i64,
+  pub bin: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContributionDay {
+  pub date: NaiveDate,
+  /// Length of the content inserted that day, in Y clock units.
+  pub edits: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserContributions {
+  pub user_id: String,
+  pub total: u64,
+  /// Days with at least one edit, oldest first.
+  pub days: Vec<ContributionDay>,
+}
+
+/// Sums the content each user inserted per day, for a contribution heatmap.
+///
+/// Updates are attributed through the CRDT client ids of their items, so a
+/// merged update counts for every client in it. Clients missing from
+/// `user_client_map` are ignored, and days are UTC days. Deletions carry no
+/// client of the deleting user and are not counted.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::collections::HashMap;
+///
+/// use affine_common::doc_parser::{compute_contribution_heatmap, TimestampedUpdate};
+///
+/// let updates = vec![TimestampedUpdate {
+///   timestamp: 1_700_000_000_000,
+///   bin: std::fs::read("fixtures/update.bin").unwrap(),
+/// }];
+/// let users = HashMap::from([(42, "user-id".to_string())]);
+/// let heatmap = compute_contribution_heatmap(&updates, &users).unwrap();
+/// ```
+pub fn compute_contribution_heatmap(
+  updates: &[TimestampedUpdate],
+  user_client_map: &HashMap<u64, String>,
+) -> Result<Vec<UserContributions>, ParseError> {
+  let mut volumes: BTreeMap<&str, BTreeMap<NaiveDate, u64>> = BTreeMap::new();
+  for update in updates {
+    let Some(date) = DateTime::from_timestamp_millis(update.timestamp) else {
+      continue;
+    };
+    let date = date.date_naive();
+    let decoded = Update::decode_v1(&update.bin).map_err(|_| ParseError::InvalidBinary)?;
+    for (client, len) in decoded.item_lengths() {
+      let Some(user_id) = user_client_map.get(&client) else {
+        continue;
+      };
+      if len > 0 {
+        *volumes
+          .entry(user_id.as_str())
+          .or_default()
+          .entry(date)
+          .or_default() += len;
+      }
+    }
+  }
+
+  Ok(
+    volumes
+      .into_iter()
+      .map(|(user_id, days)| UserContributions {
+        user_id: user_id.to_string(),
+        total: days.values().sum(),
+        days: days
+          .into_iter()
+          .map(|(date, edits)| ContributionDay { date, edits })
+          .collect(),
+      })
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use y_octo::DocOptions;
+
+  use super::*;
+
+  #[test]
+  fn test_compute_contribution_heatmap() {
+    const DAY: i64 = 24 * 60 * 60 * 1000;
+
+    let alice = DocOptions::new().with_client_id(1).build();
+    alice
+      .get_or_create_text("content")
+      .unwrap()
+      .insert(0, "hello")
+      .unwrap();
+    let first = alice.encode_update_v1().unwrap();
+
+    let mut bob = DocOptions::new().with_client_id(2).build();
+    bob.apply_update_from_binary_v1(&first).unwrap();
+    let state_vector = bob.get_state_vector();
+    bob
+      .get_or_create_text("content")
+      .unwrap()
+      .insert(5, " world")
+      .unwrap();
+    let second = bob.encode_state_as_update_v1(&state_vector).unwrap();
+
+    let updates = vec![
+      TimestampedUpdate {
+        timestamp: 0,
+        bin: first,
+      },
+      TimestampedUpdate {
+        timestamp: DAY + 1,
+        bin: second.clone(),
+      },
+      TimestampedUpdate {
+        timestamp: 2 * DAY - 1,
+        bin: second,
+      },
+    ];
+    let users = HashMap::from([(1, "alice".to_string()), (2, "bob".to_string())]);
+
+    let heatmap = compute_contribution_heatmap(&updates, &users).unwrap();
+    let day = |days| NaiveDate::from_ymd_opt(1970, 1, days).unwrap();
+    assert_eq!(
+      heatmap,
+      vec![
+        UserContributions {
+          user_id: "alice".into(),
+          total: 5,
+          days: vec![ContributionDay {
+            date: day(1),
+            edits: 5,
+          }],
+        },
+        UserContributions {
+          user_id: "bob".into(),
+          total: 12,
+          days: vec![ContributionDay {
+            date: day(2),
+            edits: 12,
+          }],
+        },
+      ]
+    );
+
+    let invalid = TimestampedUpdate {
+      timestamp: 0,
+      bin: vec![0xff],
+    };
+    assert!(compute_contribution_heatmap(&[invalid], &users).is_err());
+  }
+}
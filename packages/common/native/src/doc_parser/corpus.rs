@@ -0,0 +1,202 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use super::{can_parse, parse_doc_from_binary, parse_doc_to_markdown, ParseError};
+
+/// Version of the bundled corpus, bumped whenever samples are added or their
+/// expected outputs change.
+pub const CORPUS_VERSION: u32 = 1;
+
+/// A doc binary with the outputs the parser is expected to produce for it.
+#[derive(Debug, Clone)]
+pub struct CorpusSample {
+  pub name: String,
+  /// Corpus version the sample was added in.
+  pub version: u32,
+  pub doc_id: String,
+  pub doc_bin: Vec<u8>,
+  /// Expected [`parse_doc_from_binary`] result, as JSON.
+  pub crawl_json: Option<Vec<u8>>,
+  /// Expected [`parse_doc_to_markdown`] output.
+  pub markdown: Option<String>,
+}
+
+/// Outcome of checking one sample.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampleCheck {
+  pub name: String,
+  pub version: u32,
+  /// What differed from the expected outputs, empty when the sample passed.
+  pub failures: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorpusReport {
+  pub corpus_version: u32,
+  /// Version of the parser that produced the report.
+  pub parser_version: String,
+  pub samples: Vec<SampleCheck>,
+}
+
+impl CorpusReport {
+  pub fn passed(&self) -> bool {
+    self.samples.iter().all(|sample| sample.failures.is_empty())
+  }
+}
+
+/// The samples bundled with the crate.
+pub fn bundled_corpus() -> Vec<CorpusSample> {
+  vec![CorpusSample {
+    name: "demo".into(),
+    version: 1,
+    doc_id: "dYpV7PPhk8amRkY5IAcVO".into(),
+    doc_bin: include_bytes!("../../fixtures/demo.ydoc").to_vec(),
+    crawl_json: Some(include_bytes!("../../fixtures/demo.ydoc.json").to_vec()),
+    markdown: None,
+  }]
+}
+
+/// Checks the parser against the bundled corpus, so a deployment can verify
+/// a native module upgrade reads docs the way the previous one did before
+/// rolling it out.
+pub fn check_bundled_corpus() -> CorpusReport {
+  report(bundled_corpus())
+}
+
+/// Checks the parser against the bundled corpus and the samples in
+/// `corpus_dir`, for deployments to pin outputs of their own docs.
+///
+/// Each sample is a `<doc id>.ydoc` binary, with its expected crawl result
+/// in `<doc id>.ydoc.json` and its expected markdown in `<doc id>.ydoc.md`,
+/// both optional. Samples without expected outputs only need to parse.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::check_against_corpus;
+///
+/// let report = check_against_corpus("fixtures/corpus".as_ref()).unwrap();
+/// for sample in report.samples.iter().filter(|sample| !sample.failures.is_empty()) {
+///   eprintln!("{}: {}", sample.name, sample.failures.join("; "));
+/// }
+/// assert!(report.passed());
+/// ```
+pub fn check_against_corpus(corpus_dir: &Path) -> Result<CorpusReport, ParseError> {
+  let io_error = |e: std::io::Error| ParseError::Unknown(format!("{}: {e}", corpus_dir.display()));
+
+  let mut paths = fs::read_dir(corpus_dir)
+    .map_err(io_error)?
+    .map(|entry| entry.map(|entry| entry.path()))
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(io_error)?;
+  paths.retain(|path| path.extension().is_some_and(|ext| ext == "ydoc"));
+  paths.sort();
+
+  let mut samples = bundled_corpus();
+  for path in paths {
+    let Some(doc_id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+      continue;
+    };
+    samples.push(CorpusSample {
+      name: doc_id.to_string(),
+      version: CORPUS_VERSION,
+      doc_id: doc_id.to_string(),
+      doc_bin: fs::read(&path).map_err(io_error)?,
+      crawl_json: fs::read(path.with_extension("ydoc.json")).ok(),
+      markdown: fs::read_to_string(path.with_extension("ydoc.md")).ok(),
+    });
+  }
+  Ok(report(samples))
+}
+
+fn report(samples: Vec<CorpusSample>) -> CorpusReport {
+  CorpusReport {
+    corpus_version: CORPUS_VERSION,
+    parser_version: env!("CARGO_PKG_VERSION").into(),
+    samples: samples.into_iter().map(check_sample).collect(),
+  }
+}
+
+fn check_sample(sample: CorpusSample) -> SampleCheck {
+  let mut failures = vec![];
+
+  match can_parse(&sample.doc_bin) {
+    Ok(check) if !check.supported => failures.push(format!(
+      "schema: unsupported block versions {:?}",
+      check.mismatches
+    )),
+    Ok(_) => {}
+    Err(e) => failures.push(format!("schema: {e}")),
+  }
+
+  match parse_doc_from_binary(sample.doc_bin.clone(), sample.doc_id.clone()) {
+    Ok(result) => {
+      if let Some(expected) = &sample.crawl_json {
+        match serde_json::from_slice::<JsonValue>(expected) {
+          Ok(expected) => {
+            let config = assert_json_diff::Config::new(assert_json_diff::CompareMode::Strict)
+              .numeric_mode(assert_json_diff::NumericMode::AssumeFloat);
+            if let Err(diff) = assert_json_diff::assert_json_matches_no_panic(
+              &serde_json::json!(result),
+              &expected,
+              config,
+            ) {
+              failures.push(format!("crawl: {diff}"));
+            }
+          }
+          Err(e) => failures.push(format!("crawl: invalid expected json: {e}")),
+        }
+      }
+    }
+    Err(e) => failures.push(format!("crawl: {e}")),
+  }
+
+  match parse_doc_to_markdown(sample.doc_bin, sample.doc_id, false) {
+    Ok(result) => {
+      if sample
+        .markdown
+        .as_ref()
+        .is_some_and(|expected| *expected != result.markdown)
+      {
+        failures.push("markdown: output differs from the expected markdown".into());
+      }
+    }
+    Err(e) => failures.push(format!("markdown: {e}")),
+  }
+
+  SampleCheck {
+    name: sample.name,
+    version: sample.version,
+    failures,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_check_bundled_corpus() {
+    let report = check_bundled_corpus();
+    assert_eq!(report.corpus_version, CORPUS_VERSION);
+    assert_eq!(report.samples.len(), bundled_corpus().len());
+    assert!(report.passed(), "{:?}", report.samples);
+  }
+
+  #[test]
+  fn test_check_sample_mismatch() {
+    let mut sample = bundled_corpus().remove(0);
+    sample.markdown = Some("not the demo".into());
+    sample.crawl_json = Some(br#"{"blocks":[]}"#.to_vec());
+    let check = check_sample(sample);
+    assert_eq!(check.failures.len(), 2);
+    assert!(check.failures[0].starts_with("crawl: "));
+    assert!(check.failures[1].starts_with("markdown: "));
+
+    let mut sample = bundled_corpus().remove(0);
+    sample.doc_bin = vec![0, 0];
+    assert!(!check_sample(sample).failures.is_empty());
+  }
+}
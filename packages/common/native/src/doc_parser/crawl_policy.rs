@@ -0,0 +1,111 @@
+use super::BlockInfo;
+
+/// What a crawl does with a block, as decided by a [`CrawlPolicy`].
+#[derive(Debug, Clone)]
+pub enum BlockAction {
+  Keep,
+  Drop,
+  /// Returns the given block in place of the crawled one.
+  Transform(Box<BlockInfo>),
+}
+
+/// Visits every block of a crawl before it is returned, so embedders can
+/// enforce tenant policies such as stripping embeds or external links in the
+/// same pass instead of filtering crawl results afterwards.
+///
+/// Dropped blocks leave the summary too, and transformed blocks contribute
+/// their new `content` to it. Children of a dropped block are still visited
+/// on their own.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::doc_parser::{BlockAction, BlockInfo, CrawlPolicy};
+///
+/// /// Drops embedded docs and forgets the blobs of everything else.
+/// struct NoEmbeds;
+///
+/// impl CrawlPolicy for NoEmbeds {
+///   fn on_block(&self, block: &BlockInfo) -> BlockAction {
+///     if block.flavour.starts_with("affine:embed-") {
+///       return BlockAction::Drop;
+///     }
+///     match &block.blob {
+///       Some(_) => BlockAction::Transform(Box::new(BlockInfo {
+///         blob: None,
+///         ..block.clone()
+///       })),
+///       None => BlockAction::Keep,
+///     }
+///   }
+/// }
+/// ```
+pub trait CrawlPolicy {
+  fn on_block(&self, block: &BlockInfo) -> BlockAction;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_from_binary_with_options, test_utils::TestDoc, CrawlOptions};
+
+  /// Drops linked docs and redacts links from text.
+  struct Redact;
+
+  impl CrawlPolicy for Redact {
+    fn on_block(&self, block: &BlockInfo) -> BlockAction {
+      if block.flavour == "affine:embed-linked-doc" {
+        return BlockAction::Drop;
+      }
+      match &block.content {
+        Some(content) if content.iter().any(|text| text.contains("https://")) => {
+          BlockAction::Transform(Box::new(BlockInfo {
+            content: Some(vec!["[link removed]".into()]),
+            ..block.clone()
+          }))
+        }
+        _ => BlockAction::Keep,
+      }
+    }
+  }
+
+  #[test]
+  fn test_crawl_policy() {
+    let mut doc = TestDoc::new("Policy");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "hello ")]);
+    doc.add_block(
+      "note",
+      "p2",
+      "affine:paragraph",
+      &[("text", "see https://example.com")],
+    );
+    doc.add_block(
+      "note",
+      "l1",
+      "affine:embed-linked-doc",
+      &[("pageId", "other")],
+    );
+
+    let result = parse_doc_from_binary_with_options(
+      doc.encode(),
+      TestDoc::DOC_ID.into(),
+      &CrawlOptions {
+        policy: Some(&Redact),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+
+    let ids = result
+      .blocks
+      .iter()
+      .map(|block| block.block_id.as_str())
+      .collect::<Vec<_>>();
+    assert!(ids.ends_with(&["p1", "p2"]));
+    assert_eq!(
+      result.blocks.last().unwrap().content,
+      Some(vec!["[link removed]".to_string()])
+    );
+    assert_eq!(result.summary, "hello [link removed]");
+  }
+}
@@ -0,0 +1,308 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+  block_index::BlockIndex,
+  get_flavour,
+  markdown::escape_html,
+  outline::{outline, OutlineEntry},
+  text_content, ParseError,
+};
+
+const TOKEN_START: &str = "{{ref:";
+const TOKEN_END: &str = "}}";
+
+/// A `{{ref:<anchor>}}` or `{{ref:<anchor>|<label>}}` token in the text of a
+/// block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrossReference {
+  /// Block whose text carries the token.
+  pub block_id: String,
+  /// A block id or a heading slug, as accepted by [`super::resolve_anchor`].
+  pub anchor: String,
+  pub label: Option<String>,
+  /// Block the anchor points at, `None` when nothing in the doc matches.
+  pub target_block_id: Option<String>,
+  /// Number of the target when it is a heading, as listed by
+  /// [`super::extract_outline`].
+  pub number: Option<String>,
+}
+
+/// Lists the reference tokens of a doc in document order, resolved against
+/// its blocks and headings.
+///
+/// Exports replace the tokens with links: to `#<slug>` for headings, labelled
+/// with the heading text or `Section <number>` when headings are numbered,
+/// and to `#<block id>` for other blocks, labelled with the anchor. An
+/// explicit label always wins. Tokens that do not resolve are exported as
+/// their label or anchor, without a link. Code blocks are left untouched.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::list_cross_references;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// for reference in list_cross_references(doc_bin, "doc-id".into()).unwrap() {
+///   if reference.target_block_id.is_none() {
+///     println!("broken reference to {} in {}", reference.anchor, reference.block_id);
+///   }
+/// }
+/// ```
+pub fn list_cross_references(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+) -> Result<Vec<CrossReference>, ParseError> {
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(vec![]);
+  };
+
+  let targets = CrossReferenceTargets::new(&index);
+  let mut references = Vec::new();
+  for block_id in index.descendants(&index.root_block_id) {
+    let Some(block) = index.get(&block_id) else {
+      continue;
+    };
+    if get_flavour(block).as_deref() == Some("affine:code") {
+      continue;
+    }
+    let Some((text, _)) = text_content(block, "prop:text") else {
+      continue;
+    };
+    for token in tokens(&text) {
+      let target = targets.resolve(&index, token.anchor);
+      references.push(CrossReference {
+        block_id: block_id.clone(),
+        anchor: token.anchor.to_string(),
+        label: token.label.map(str::to_string),
+        target_block_id: target.as_ref().map(|target| target.block_id.clone()),
+        number: target
+          .and_then(|target| target.heading)
+          .map(|heading| heading.number.clone()),
+      });
+    }
+  }
+  Ok(references)
+}
+
+struct Token<'a> {
+  start: usize,
+  end: usize,
+  anchor: &'a str,
+  label: Option<&'a str>,
+}
+
+fn tokens(text: &str) -> Vec<Token<'_>> {
+  let mut tokens = Vec::new();
+  let mut offset = 0;
+  while let Some(found) = text[offset..].find(TOKEN_START) {
+    let start = offset + found;
+    let inner_start = start + TOKEN_START.len();
+    let Some(length) = text[inner_start..].find(TOKEN_END) else {
+      break;
+    };
+    let inner = &text[inner_start..inner_start + length];
+    let end = inner_start + length + TOKEN_END.len();
+    offset = end;
+
+    let (anchor, label) = match inner.split_once('|') {
+      Some((anchor, label)) => (anchor.trim(), Some(label.trim())),
+      None => (inner.trim(), None),
+    };
+    let anchor = anchor.strip_prefix('#').unwrap_or(anchor);
+    if anchor.is_empty() {
+      continue;
+    }
+    tokens.push(Token {
+      start,
+      end,
+      anchor,
+      label: label.filter(|label| !label.is_empty()),
+    });
+  }
+  tokens
+}
+
+struct Target<'a> {
+  block_id: String,
+  heading: Option<&'a OutlineEntry>,
+}
+
+pub(crate) fn contains_references(text: &str) -> bool {
+  text.contains(TOKEN_START)
+}
+
+/// Resolves reference tokens against the headings and blocks of a doc.
+pub(crate) struct CrossReferenceTargets {
+  outline: Vec<OutlineEntry>,
+}
+
+impl CrossReferenceTargets {
+  pub(crate) fn new(index: &BlockIndex) -> Self {
+    Self::from_outline(outline(index))
+  }
+
+  pub(crate) fn from_outline(outline: Vec<OutlineEntry>) -> Self {
+    Self { outline }
+  }
+
+  fn resolve(&self, index: &BlockIndex, anchor: &str) -> Option<Target<'_>> {
+    if index.get(anchor).is_some() {
+      return Some(Target {
+        block_id: anchor.to_string(),
+        heading: self.outline.iter().find(|entry| entry.block_id == anchor),
+      });
+    }
+    self
+      .outline
+      .iter()
+      .find(|entry| entry.slug == anchor)
+      .map(|entry| Target {
+        block_id: entry.block_id.clone(),
+        heading: Some(entry),
+      })
+  }
+
+  /// Replaces the reference tokens in `text` with markdown links.
+  pub(crate) fn render_markdown(
+    &self,
+    index: &BlockIndex,
+    text: &str,
+    numbered_headings: bool,
+  ) -> String {
+    self.render(
+      index,
+      text,
+      numbered_headings,
+      |href, label| format!("[{label}](#{href})"),
+      str::to_string,
+    )
+  }
+
+  /// Replaces the reference tokens in `text` with HTML links, escaping the
+  /// rest of the text.
+  pub(crate) fn render_html(
+    &self,
+    index: &BlockIndex,
+    text: &str,
+    numbered_headings: bool,
+  ) -> String {
+    self.render(
+      index,
+      text,
+      numbered_headings,
+      |href, label| {
+        format!(
+          "<a href=\"#{}\">{}</a>",
+          escape_html(href),
+          escape_html(label)
+        )
+      },
+      escape_html,
+    )
+  }
+
+  fn render(
+    &self,
+    index: &BlockIndex,
+    text: &str,
+    numbered_headings: bool,
+    link: impl Fn(&str, &str) -> String,
+    plain: impl Fn(&str) -> String,
+  ) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut offset = 0;
+    for token in tokens(text) {
+      out.push_str(&plain(&text[offset..token.start]));
+      offset = token.end;
+
+      let Some(target) = self.resolve(index, token.anchor) else {
+        out.push_str(&plain(token.label.unwrap_or(token.anchor)));
+        continue;
+      };
+      let (href, label) = match target.heading {
+        Some(heading) if numbered_headings => (
+          heading.slug.as_str(),
+          format!("Section {}", heading.number.trim_end_matches('.')),
+        ),
+        Some(heading) => (heading.slug.as_str(), heading.text.clone()),
+        None => (target.block_id.as_str(), token.anchor.to_string()),
+      };
+      out.push_str(&link(href, token.label.unwrap_or(&label)));
+    }
+    out.push_str(&plain(&text[offset..]));
+    out
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{
+    parse_doc_to_markdown_with_options, test_utils::TestDoc, MarkdownOptions,
+  };
+
+  #[test]
+  fn test_cross_references() {
+    let mut doc = TestDoc::new("Guide");
+    doc.add_block(
+      "note",
+      "h1",
+      "affine:paragraph",
+      &[("type", "h1"), ("text", "Setup")],
+    );
+    doc.add_block(
+      "note",
+      "h2",
+      "affine:paragraph",
+      &[("type", "h2"), ("text", "Linux")],
+    );
+    doc.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[(
+        "text",
+        "see {{ref:linux}}, {{ref:p1|this}} and {{ref:missing}}",
+      )],
+    );
+    doc.add_block("note", "c1", "affine:code", &[("text", "{{ref:linux}}")]);
+    let doc_bin = doc.encode();
+
+    let references = list_cross_references(doc_bin.clone(), TestDoc::DOC_ID.into()).unwrap();
+    let summary = references
+      .iter()
+      .map(|reference| {
+        (
+          reference.anchor.as_str(),
+          reference.target_block_id.as_deref(),
+          reference.number.as_deref(),
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      summary,
+      vec![
+        ("linux", Some("h2"), Some("1.1")),
+        ("p1", Some("p1"), None),
+        ("missing", None, None),
+      ]
+    );
+    assert_eq!(references[1].label.as_deref(), Some("this"));
+
+    let render = |numbered_headings| {
+      parse_doc_to_markdown_with_options(
+        doc_bin.clone(),
+        TestDoc::DOC_ID.into(),
+        &MarkdownOptions {
+          numbered_headings,
+          ..Default::default()
+        },
+      )
+      .unwrap()
+      .markdown
+    };
+    assert!(render(false).contains("see [Linux](#linux), [this](#p1) and missing\n"));
+    assert!(render(true).contains("see [Section 1.1](#linux), [this](#p1) and missing\n"));
+    assert!(render(true).contains("{{ref:linux}}\n```"));
+  }
+}
@@ -0,0 +1,334 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use y_octo::{Any, Map, Value};
+
+use super::{
+  block_index::BlockIndex, collect_child_ids, format_cell_value, get_block_id, get_string,
+  text_content, value_to_string, ParseError,
+};
+
+const DATABASE_FLAVOUR: &str = "affine:database";
+
+/// A database block with its columns, rows and views resolved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseBlock {
+  pub block_id: String,
+  pub title: String,
+  pub columns: Vec<DatabaseColumn>,
+  /// Rows in display order, one cell per column.
+  pub rows: Vec<DatabaseRow>,
+  pub views: Vec<DatabaseView>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseColumn {
+  pub id: String,
+  pub name: String,
+  /// Column type as stored by the editor, e.g. `title`, `rich-text`,
+  /// `number` or `multi-select`.
+  #[serde(rename = "type")]
+  pub column_type: String,
+  /// Options of `select` and `multi-select` columns.
+  pub options: Vec<DatabaseOption>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseOption {
+  pub id: String,
+  pub value: String,
+  pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseRow {
+  /// Id of the row's child block, which holds the title.
+  pub row_id: String,
+  pub cells: Vec<DatabaseCell>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseCell {
+  pub column_id: String,
+  pub value: CellValue,
+  /// The value as exports display it, select options resolved to their
+  /// labels.
+  pub text: String,
+}
+
+/// A cell value, typed after its column.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum CellValue {
+  Empty,
+  Text(String),
+  Number(f64),
+  Checkbox(bool),
+  /// Milliseconds since the Unix epoch.
+  Date(i64),
+  /// Ids of the selected options, see [`DatabaseColumn::options`].
+  Options(Vec<String>),
+  /// Values of column types the parser does not know, as stored.
+  Other(JsonValue),
+}
+
+impl CellValue {
+  pub fn kind(&self) -> &'static str {
+    match self {
+      CellValue::Empty => "empty",
+      CellValue::Text(_) => "text",
+      CellValue::Number(_) => "number",
+      CellValue::Checkbox(_) => "checkbox",
+      CellValue::Date(_) => "date",
+      CellValue::Options(_) => "options",
+      CellValue::Other(_) => "other",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseView {
+  pub id: String,
+  pub name: String,
+  /// `table` or `kanban`.
+  pub mode: String,
+  /// Ids of the columns the view shows, in its order.
+  pub column_ids: Vec<String>,
+  /// The whole view as stored, including filters, sorting and grouping.
+  pub config: JsonValue,
+}
+
+/// Reads a database block into typed columns, rows and views.
+///
+/// Returns `ParseError::BlockNotFound` when `block_id` is missing or not an
+/// `affine:database` block.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::parse_database_block;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let database = parse_database_block(doc_bin, "doc-id".into(), "database-id").unwrap();
+/// for row in &database.rows {
+///   println!("{}", row.cells.iter().map(|cell| cell.text.as_str()).collect::<Vec<_>>().join(" | "));
+/// }
+/// ```
+pub fn parse_database_block(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  block_id: &str,
+) -> Result<DatabaseBlock, ParseError> {
+  let index = BlockIndex::load(&doc_bin, &doc_id)?
+    .ok_or_else(|| ParseError::BlockNotFound(block_id.to_string()))?;
+  index
+    .get(block_id)
+    .filter(|_| index.flavour_of(block_id).as_deref() == Some(DATABASE_FLAVOUR))
+    .map(|block| read_database(&index, block))
+    .ok_or_else(|| ParseError::BlockNotFound(block_id.to_string()))
+}
+
+pub(crate) fn read_database(index: &BlockIndex, block: &Map) -> DatabaseBlock {
+  let mut columns = Vec::new();
+  // column data is kept for resolving select labels
+  let mut column_data = Vec::new();
+  for column in block
+    .get("prop:columns")
+    .and_then(|v| v.to_array())
+    .iter()
+    .flat_map(|columns| columns.iter())
+    .filter_map(|v| v.to_map())
+  {
+    let data = column.get("data").and_then(|v| v.to_map());
+    columns.push(DatabaseColumn {
+      id: get_string(&column, "id").unwrap_or_default(),
+      name: get_string(&column, "name").unwrap_or_default(),
+      column_type: get_string(&column, "type").unwrap_or_default(),
+      options: data.as_ref().map(column_options).unwrap_or_default(),
+    });
+    column_data.push(data);
+  }
+
+  let cells = block.get("prop:cells").and_then(|v| v.to_map());
+  let rows = collect_child_ids(block)
+    .into_iter()
+    .map(|row_id| {
+      let row_cells = cells
+        .as_ref()
+        .and_then(|cells| cells.get(&row_id))
+        .and_then(|v| v.to_map());
+      let cells = columns
+        .iter()
+        .zip(&column_data)
+        .map(|(column, data)| {
+          let (value, text) = if column.column_type == "title" {
+            match index
+              .get(&row_id)
+              .and_then(|row| text_content(row, "prop:text"))
+            {
+              Some((text, _)) => (CellValue::Text(text.clone()), text),
+              None => (CellValue::Empty, String::new()),
+            }
+          } else {
+            row_cells
+              .as_ref()
+              .and_then(|cells| cells.get(&column.id))
+              .and_then(|v| v.to_map())
+              .and_then(|cell| cell.get("value"))
+              .map(|value| cell_value(&value, &column.column_type, data.as_ref()))
+              .unwrap_or((CellValue::Empty, String::new()))
+          };
+          DatabaseCell {
+            column_id: column.id.clone(),
+            value,
+            text,
+          }
+        })
+        .collect();
+      DatabaseRow { row_id, cells }
+    })
+    .collect();
+
+  let views = block
+    .get("prop:views")
+    .and_then(|v| v.to_array())
+    .iter()
+    .flat_map(|views| views.iter())
+    .filter_map(|v| v.to_map())
+    .map(|view| {
+      let column_ids = view
+        .get("columns")
+        .and_then(|v| v.to_array())
+        .iter()
+        .flat_map(|columns| columns.iter())
+        .filter_map(|v| v.to_map())
+        .filter(|column| !matches!(column.get("hide").and_then(|v| v.to_any()), Some(Any::True)))
+        .filter_map(|column| get_string(&column, "id"))
+        .collect();
+      DatabaseView {
+        id: get_string(&view, "id").unwrap_or_default(),
+        name: get_string(&view, "name").unwrap_or_default(),
+        mode: get_string(&view, "mode").unwrap_or_else(|| "table".into()),
+        column_ids,
+        config: serde_json::to_value(&view).unwrap_or(JsonValue::Null),
+      }
+    })
+    .collect();
+
+  DatabaseBlock {
+    block_id: get_block_id(block).unwrap_or_default(),
+    title: get_string(block, "prop:title").unwrap_or_default(),
+    columns,
+    rows,
+    views,
+  }
+}
+
+fn column_options(data: &Map) -> Vec<DatabaseOption> {
+  data
+    .get("options")
+    .and_then(|v| v.to_array())
+    .iter()
+    .flat_map(|options| options.iter())
+    .filter_map(|v| v.to_map())
+    .map(|option| DatabaseOption {
+      id: get_string(&option, "id").unwrap_or_default(),
+      value: get_string(&option, "value").unwrap_or_default(),
+      color: get_string(&option, "color"),
+    })
+    .collect()
+}
+
+/// Types a stored cell value after its column and renders its display text.
+fn cell_value(value: &Value, column_type: &str, data: Option<&Map>) -> (CellValue, String) {
+  let Some(any) = value.to_any() else {
+    // rich text cells are Y texts
+    return match value_to_string(value) {
+      Some(text) => (CellValue::Text(text.clone()), text),
+      None => (CellValue::Empty, String::new()),
+    };
+  };
+  let text = format_cell_value(&any, column_type, data);
+
+  let number = match &any {
+    Any::Integer(value) => Some(*value as f64),
+    Any::Float32(value) => Some(value.0 as f64),
+    Any::Float64(value) => Some(value.0),
+    Any::BigInt64(value) => Some(*value as f64),
+    _ => None,
+  };
+  let value = match (column_type, &any, number) {
+    (_, Any::Null | Any::Undefined, _) => CellValue::Empty,
+    ("select", Any::String(id), _) => CellValue::Options(vec![id.to_string()]),
+    ("multi-select", Any::Array(ids), _) => CellValue::Options(
+      ids
+        .iter()
+        .filter_map(|id| match id {
+          Any::String(id) => Some(id.to_string()),
+          _ => None,
+        })
+        .collect(),
+    ),
+    ("checkbox", Any::True, _) => CellValue::Checkbox(true),
+    ("checkbox", Any::False, _) => CellValue::Checkbox(false),
+    ("date", _, Some(number)) => CellValue::Date(number as i64),
+    ("number" | "progress", _, Some(number)) => CellValue::Number(number),
+    ("rich-text" | "text" | "link" | "title", Any::String(text), _) => {
+      CellValue::Text(text.to_string())
+    }
+    _ => CellValue::Other(serde_json::to_value(&any).unwrap_or(JsonValue::Null)),
+  };
+  (value, text)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_to_markdown, test_utils::TestDoc};
+
+  #[test]
+  fn test_parse_database_block() {
+    let mut doc = TestDoc::new("Tasks");
+    doc.add_block("note", "db", DATABASE_FLAVOUR, &[("title", "Backlog")]);
+    doc.add_block("db", "r1", "affine:paragraph", &[("text", "Write docs")]);
+    doc.add_block("db", "r2", "affine:paragraph", &[("text", "Ship")]);
+    doc.set_database_cells(
+      "db",
+      &[
+        ("c0", "Name", "title"),
+        ("c1", "Owner", "rich-text"),
+        ("c2", "Estimate", "custom"),
+      ],
+      &[("r1", &[("c1", "Ann"), ("c2", "3d")])],
+    );
+    let doc_bin = doc.encode();
+
+    let database = parse_database_block(doc_bin.clone(), TestDoc::DOC_ID.into(), "db").unwrap();
+    assert_eq!(database.title, "Backlog");
+    assert_eq!(
+      database
+        .columns
+        .iter()
+        .map(|column| column.column_type.as_str())
+        .collect::<Vec<_>>(),
+      ["title", "rich-text", "custom"]
+    );
+    assert_eq!(database.rows.len(), 2);
+    let cells = &database.rows[0].cells;
+    assert_eq!(cells[0].value, CellValue::Text("Write docs".into()));
+    assert_eq!(cells[1].value, CellValue::Text("Ann".into()));
+    assert_eq!(cells[2].value, CellValue::Other("3d".into()));
+    assert_eq!(cells[2].text, "3d");
+    assert_eq!(database.rows[1].cells[1].value, CellValue::Empty);
+    assert!(database.views.is_empty());
+
+    let markdown = parse_doc_to_markdown(doc_bin.clone(), TestDoc::DOC_ID.into(), false).unwrap();
+    assert!(markdown
+      .markdown
+      .contains("|Name|Owner|Estimate|\n|---|---|---|\n|Write docs|Ann|3d|\n|Ship|||\n"));
+
+    assert!(matches!(
+      parse_database_block(doc_bin, TestDoc::DOC_ID.into(), "r1"),
+      Err(ParseError::BlockNotFound(_))
+    ));
+  }
+}
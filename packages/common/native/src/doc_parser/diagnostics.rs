@@ -0,0 +1,156 @@
+use std::{error::Error as _, fmt::Write};
+
+use serde::{Deserialize, Serialize};
+use y_octo::{JwstCodecError, Update};
+
+use super::{can_parse, new_doc, ParseError, SchemaCheck};
+
+/// Bytes shown before the decode offset in the hexdump of a bundle.
+const HEXDUMP_CONTEXT: usize = 32;
+/// Most bytes a hexdump shows, so a bundle never carries more than a sliver
+/// of the doc.
+const HEXDUMP_LIMIT: usize = 96;
+
+/// What went wrong when a doc failed to parse, small enough to attach to a
+/// bug report instead of the doc itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticBundle {
+  /// The parse error, followed by the errors that caused it.
+  pub error_chain: Vec<String>,
+  /// Offset in the doc binary where decoding stopped, when the binary itself
+  /// is malformed.
+  pub decode_offset: Option<usize>,
+  /// `xxd`-style dump of the bytes around `decode_offset`, or of the start
+  /// of the binary without one.
+  pub hexdump: String,
+  pub doc_size: usize,
+  /// Block schema versions of the doc, `None` when it does not load.
+  pub schema: Option<SchemaCheck>,
+}
+
+/// Builds the diagnostic bundle of a doc that failed to parse with `error`.
+///
+/// The binary is decoded again to recover the codec errors the parser only
+/// reports as [`ParseError::InvalidBinary`], so this is only worth calling
+/// once parsing failed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{diagnose_parse_failure, parse_doc_from_binary};
+///
+/// let doc_bin = std::fs::read("fixtures/broken.ydoc").unwrap();
+/// if let Err(error) = parse_doc_from_binary(doc_bin.clone(), "doc-id".into()) {
+///   let bundle = diagnose_parse_failure(&doc_bin, &error);
+///   eprintln!("{}\n{}", bundle.error_chain.join("\ncaused by: "), bundle.hexdump);
+/// }
+/// ```
+pub fn diagnose_parse_failure(doc_bin: &[u8], error: &ParseError) -> DiagnosticBundle {
+  let mut error_chain = vec![error.to_string()];
+  let mut decode_offset = None;
+  let mut loaded = false;
+
+  let codec_error = match Update::decode_v1(doc_bin) {
+    Ok(update) => new_doc(None).apply_update(update).err(),
+    Err(e) => {
+      decode_offset = failed_offset(doc_bin.len(), &e);
+      Some(e)
+    }
+  };
+  match codec_error {
+    Some(e) => {
+      error_chain.push(e.to_string());
+      let mut source = e.source();
+      while let Some(e) = source {
+        error_chain.push(e.to_string());
+        source = e.source();
+      }
+    }
+    None => loaded = !doc_bin.is_empty(),
+  }
+
+  DiagnosticBundle {
+    error_chain,
+    decode_offset,
+    hexdump: hexdump(doc_bin, decode_offset),
+    doc_size: doc_bin.len(),
+    schema: loaded.then(|| can_parse(doc_bin).ok()).flatten(),
+  }
+}
+
+fn failed_offset(len: usize, error: &JwstCodecError) -> Option<usize> {
+  let rest = match error {
+    JwstCodecError::UpdateInvalid(nom::Err::Error(e) | nom::Err::Failure(e)) => e.input,
+    JwstCodecError::UpdateInvalid(nom::Err::Incomplete(_)) => 0,
+    JwstCodecError::UpdateNotFullyConsumed(rest) => *rest,
+    _ => return None,
+  };
+  Some(len.saturating_sub(rest))
+}
+
+fn hexdump(doc_bin: &[u8], offset: Option<usize>) -> String {
+  let start = offset.map_or(0, |offset| offset.saturating_sub(HEXDUMP_CONTEXT) / 16 * 16);
+  let end = (start + HEXDUMP_LIMIT).min(doc_bin.len());
+
+  let mut dump = String::new();
+  for (line, bytes) in doc_bin[start.min(end)..end].chunks(16).enumerate() {
+    let _ = write!(dump, "{:08x} ", start + line * 16);
+    for i in 0..16 {
+      match bytes.get(i) {
+        Some(byte) => {
+          let _ = write!(dump, " {byte:02x}");
+        }
+        None => dump.push_str("   "),
+      }
+    }
+    dump.push_str("  |");
+    dump.extend(bytes.iter().map(|byte| match byte {
+      0x20..=0x7e => *byte as char,
+      _ => '.',
+    }));
+    dump.push_str("|\n");
+  }
+  dump
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_from_binary, test_utils::TestDoc};
+
+  #[test]
+  fn test_diagnose_parse_failure() {
+    let mut doc = TestDoc::new("Broken");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "hello")]);
+    let doc_bin = doc.encode();
+
+    let mut trailing = doc_bin.clone();
+    trailing.extend_from_slice(b"junk");
+    let error = parse_doc_from_binary(trailing.clone(), TestDoc::DOC_ID.into()).unwrap_err();
+    let bundle = diagnose_parse_failure(&trailing, &error);
+    assert_eq!(bundle.decode_offset, Some(doc_bin.len()));
+    assert_eq!(bundle.error_chain[0], "invalid_binary");
+    assert_eq!(bundle.error_chain[1], "update not fully consumed: 4");
+    assert!(bundle.hexdump.ends_with("k|\n"));
+    assert!(bundle.hexdump.lines().count() <= HEXDUMP_LIMIT / 16);
+    assert_eq!(bundle.schema, None);
+
+    let truncated = &doc_bin[..doc_bin.len() / 2];
+    let bundle = diagnose_parse_failure(truncated, &ParseError::InvalidBinary);
+    assert!(bundle.error_chain.len() >= 2);
+    assert!(bundle
+      .decode_offset
+      .is_none_or(|offset| offset <= truncated.len()));
+
+    // a doc that decodes but has no blocks
+    let empty = new_doc(None).encode_update_v1().unwrap();
+    let error = ParseError::ParserError("blocks map is empty".into());
+    let bundle = diagnose_parse_failure(&empty, &error);
+    assert_eq!(bundle.error_chain, vec![error.to_string()]);
+    assert_eq!(bundle.decode_offset, None);
+    assert_eq!(
+      bundle.hexdump,
+      "00000000  00 00                                            |..|\n"
+    );
+  }
+}
@@ -0,0 +1,194 @@
+use std::collections::{BTreeSet, HashSet};
+
+use serde::{Deserialize, Serialize};
+use y_octo::{CrdtRead, CrdtWrite, DeleteSet, Doc, History, RawDecoder, RawEncoder, StateVector};
+
+use super::{new_doc, parse_doc_from_binary, BlockInfo, ParseError};
+
+/// Blocks of a doc that changed since an earlier version of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocDelta {
+  /// Blocks created since the previous version, in document order.
+  pub added: Vec<BlockInfo>,
+  /// Blocks that existed before and changed since, in document order.
+  pub updated: Vec<BlockInfo>,
+  /// Ids of the blocks deleted since the previous version, sorted.
+  pub deleted: Vec<String>,
+  /// Version of the doc the delta was computed against, to pass to the next
+  /// call.
+  pub state_vector: Vec<u8>,
+}
+
+/// Crawls a doc like [`parse_doc_from_binary`] and returns only the blocks
+/// that changed since `prev_state_vector`, so incremental indexing writes
+/// what changed rather than the whole doc.
+///
+/// Yjs deletions carry no clock, so a plain state vector cannot tell what
+/// was deleted since. `prev_state_vector` is therefore the `state_vector` of
+/// the previous call, the delete set followed by the state vector of the doc
+/// as `Y.encodeSnapshot` writes them. Pass an empty one to get every block
+/// as added.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::parse_doc_delta;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let first = parse_doc_delta(doc_bin.clone(), &[], "doc-id".into()).unwrap();
+/// let next = parse_doc_delta(doc_bin, &first.state_vector, "doc-id".into()).unwrap();
+/// assert!(next.added.is_empty() && next.updated.is_empty() && next.deleted.is_empty());
+/// ```
+pub fn parse_doc_delta(
+  doc_bin: Vec<u8>,
+  prev_state_vector: &[u8],
+  doc_id: String,
+) -> Result<DocDelta, ParseError> {
+  if doc_bin.is_empty() || doc_bin == [0, 0] {
+    return Err(ParseError::InvalidBinary);
+  }
+  let (prev_deletes, prev_state) = decode_state(prev_state_vector)?;
+
+  let mut doc = new_doc(Some(&doc_id));
+  doc
+    .apply_update_from_binary_v1(&doc_bin)
+    .map_err(|_| ParseError::InvalidBinary)?;
+
+  let history = doc.history();
+  let inserted = history.parse_update(&doc.encode_state_as_update(&prev_state)?);
+  let deleted = history.parse_delete_sets(&prev_deletes, &doc.get_delete_set());
+
+  let mut created = HashSet::new();
+  let mut changed = HashSet::new();
+  for item in &inserted {
+    match block_path(item) {
+      Some((block_id, true)) => created.insert(block_id),
+      Some((block_id, false)) => changed.insert(block_id),
+      None => false,
+    };
+  }
+  let mut removed = BTreeSet::new();
+  for item in &deleted {
+    match block_path(item) {
+      // blocks created and deleted since were never seen by the caller
+      Some((block_id, true)) if known_at(item, &prev_state) => removed.insert(block_id),
+      Some((block_id, _)) => changed.insert(block_id),
+      None => false,
+    };
+  }
+
+  let state_vector = encode_state(&doc)?;
+  let result = parse_doc_from_binary(doc_bin, doc_id)?;
+
+  let mut added = Vec::new();
+  let mut updated = Vec::new();
+  for block in result.blocks {
+    removed.remove(block.block_id.as_str());
+    if created.contains(block.block_id.as_str()) {
+      added.push(block);
+    } else if changed.contains(block.block_id.as_str()) {
+      updated.push(block);
+    }
+  }
+
+  Ok(DocDelta {
+    added,
+    updated,
+    deleted: removed.into_iter().map(str::to_string).collect(),
+    state_vector,
+  })
+}
+
+/// The block an item belongs to, and whether the item is the block itself
+/// rather than part of its props. Paths look like
+/// `blocks/<block id>[/<prop>[/<index>]]`.
+fn block_path(history: &History) -> Option<(&str, bool)> {
+  match history.parent.as_slice() {
+    [root, block_id] if root == "blocks" => Some((block_id.as_str(), true)),
+    [root, block_id, ..] if root == "blocks" => Some((block_id.as_str(), false)),
+    _ => None,
+  }
+}
+
+/// Whether the item of `history`, with an id like `(<client>, <clock>)`, was
+/// part of the doc at `state`.
+fn known_at(history: &History, state: &StateVector) -> bool {
+  let id = history.id.trim_start_matches('(').trim_end_matches(')');
+  let Some((client, clock)) = id.split_once(", ") else {
+    return true;
+  };
+  match (client.parse::<u64>(), clock.parse::<u64>()) {
+    (Ok(client), Ok(clock)) => clock < state.get(&client),
+    _ => true,
+  }
+}
+
+fn encode_state(doc: &Doc) -> Result<Vec<u8>, ParseError> {
+  let mut encoder = RawEncoder::default();
+  doc.get_delete_set().write(&mut encoder)?;
+  doc.get_state_vector().write(&mut encoder)?;
+  Ok(encoder.into_inner())
+}
+
+fn decode_state(state: &[u8]) -> Result<(DeleteSet, StateVector), ParseError> {
+  if state.is_empty() {
+    return Ok(Default::default());
+  }
+  let mut decoder = RawDecoder::new(state);
+  let invalid = |_| ParseError::InvalidOptions("invalid state vector".into());
+  let deletes = DeleteSet::read(&mut decoder).map_err(invalid)?;
+  let state_vector = StateVector::read(&mut decoder).map_err(invalid)?;
+  Ok((deletes, state_vector))
+}
+
+#[cfg(test)]
+mod tests {
+  use y_octo::Map;
+
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  fn ids(blocks: &[BlockInfo]) -> Vec<&str> {
+    blocks.iter().map(|block| block.block_id.as_str()).collect()
+  }
+
+  #[test]
+  fn test_parse_doc_delta() {
+    let mut doc = TestDoc::new("Delta");
+    let p1 = doc.add_block("note", "p1", "affine:paragraph", &[("text", "one")]);
+    let p2 = doc.add_block("note", "p2", "affine:paragraph", &[("text", "two")]);
+    doc.add_block("note", "p3", "affine:paragraph", &[("text", "three")]);
+
+    let first = parse_doc_delta(doc.encode(), &[], TestDoc::DOC_ID.into()).unwrap();
+    assert!(ids(&first.added).starts_with(&["page"]));
+    assert!(ids(&first.added).ends_with(&["p1", "p2", "p3"]));
+    assert!(first.updated.is_empty() && first.deleted.is_empty());
+
+    let unchanged =
+      parse_doc_delta(doc.encode(), &first.state_vector, TestDoc::DOC_ID.into()).unwrap();
+    assert!(unchanged.added.is_empty() && unchanged.updated.is_empty());
+    assert!(unchanged.deleted.is_empty());
+    assert_eq!(unchanged.state_vector, first.state_vector);
+
+    let text = |block: &Map| block.get("prop:text").and_then(|value| value.to_text());
+    text(&p1).unwrap().insert(3, "!").unwrap();
+    // a deletion alone is only visible through the delete set
+    text(&p2).unwrap().remove(0, 1).unwrap();
+    doc.remove_block("note", "p3");
+    doc.add_block("note", "p4", "affine:paragraph", &[("text", "four")]);
+
+    let delta = parse_doc_delta(doc.encode(), &first.state_vector, TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(ids(&delta.added), vec!["p4"]);
+    assert!(ids(&delta.updated).ends_with(&["p1", "p2"]));
+    assert_eq!(
+      delta.updated.last().unwrap().content,
+      Some(vec!["wo".into()])
+    );
+    assert_eq!(delta.deleted, vec!["p3".to_string()]);
+
+    assert!(matches!(
+      parse_doc_delta(doc.encode(), &[0xff], TestDoc::DOC_ID.into()),
+      Err(ParseError::InvalidOptions(_))
+    ));
+  }
+}
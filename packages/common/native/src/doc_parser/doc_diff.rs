@@ -0,0 +1,318 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use super::{block_index::BlockIndex, plain_text::block_text, BlockSnapshot, ParseError};
+
+/// A block as it was on one side of a [`DocDiff`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockVersion {
+  pub parent_block_id: Option<String>,
+  /// Text of the block as [`super::parse_doc_to_text`] reads it.
+  pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockChange {
+  pub block_id: String,
+  pub flavour: String,
+  /// The block in the old doc, `None` for added blocks.
+  pub before: Option<BlockVersion>,
+  /// The block in the new doc, `None` for removed blocks.
+  pub after: Option<BlockVersion>,
+  /// Props whose value differs, sorted. Only set for edited blocks.
+  pub changed_props: Vec<String>,
+}
+
+/// Differences between two versions of a doc, block by block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocDiff {
+  /// Blocks only in the new doc, in its document order.
+  pub added: Vec<BlockChange>,
+  /// Blocks only in the old doc, in its document order.
+  pub removed: Vec<BlockChange>,
+  /// Blocks under another parent or reordered among their siblings, in the
+  /// document order of the new doc.
+  pub moved: Vec<BlockChange>,
+  /// Blocks whose props changed, in the document order of the new doc.
+  pub edited: Vec<BlockChange>,
+}
+
+struct Version {
+  flavour: String,
+  parent_block_id: Option<String>,
+  text: String,
+  props: JsonMap<String, JsonValue>,
+}
+
+impl Version {
+  fn to_block_version(&self) -> BlockVersion {
+    BlockVersion {
+      parent_block_id: self.parent_block_id.clone(),
+      text: self.text.clone(),
+    }
+  }
+}
+
+/// Compares two snapshots of a doc, for version history and change
+/// summaries.
+///
+/// Blocks are matched by id, only blocks reachable from the root page are
+/// compared. A block counts as moved when its parent changed, or when its
+/// siblings that stayed in place no longer surround it the same way, so
+/// inserting a block does not report its following siblings as moved. A
+/// block can be both moved and edited. Either snapshot may be a doc without
+/// blocks yet.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::diff_docs;
+///
+/// let old_bin = std::fs::read("fixtures/old.ydoc").unwrap();
+/// let new_bin = std::fs::read("fixtures/new.ydoc").unwrap();
+/// let diff = diff_docs(old_bin, new_bin, "doc-id".into()).unwrap();
+/// for change in &diff.edited {
+///   let (before, after) = (change.before.as_ref().unwrap(), change.after.as_ref().unwrap());
+///   println!("{}: {:?} -> {:?}", change.block_id, before.text, after.text);
+/// }
+/// ```
+pub fn diff_docs(
+  old_bin: Vec<u8>,
+  new_bin: Vec<u8>,
+  doc_id: String,
+) -> Result<DocDiff, ParseError> {
+  let old_index = BlockIndex::load(&old_bin, &doc_id)?;
+  let new_index = BlockIndex::load(&new_bin, &doc_id)?;
+  let (old_order, old) = versions(old_index.as_ref());
+  let (new_order, new) = versions(new_index.as_ref());
+
+  let change = |block_id: &str, changed_props: Vec<String>| {
+    let (before, after) = (old.get(block_id), new.get(block_id));
+    BlockChange {
+      block_id: block_id.to_string(),
+      flavour: after
+        .or(before)
+        .map(|version| version.flavour.clone())
+        .unwrap_or_default(),
+      before: before.map(Version::to_block_version),
+      after: after.map(Version::to_block_version),
+      changed_props,
+    }
+  };
+
+  let reordered = match (&old_index, &new_index) {
+    (Some(old_index), Some(new_index)) => reordered_blocks(old_index, new_index, &old, &new),
+    _ => HashSet::new(),
+  };
+
+  let mut diff = DocDiff {
+    added: vec![],
+    removed: vec![],
+    moved: vec![],
+    edited: vec![],
+  };
+  for block_id in &new_order {
+    let after = &new[block_id];
+    let Some(before) = old.get(block_id) else {
+      diff.added.push(change(block_id, vec![]));
+      continue;
+    };
+    if before.parent_block_id != after.parent_block_id || reordered.contains(block_id) {
+      diff.moved.push(change(block_id, vec![]));
+    }
+    let changed_props = changed_props(&before.props, &after.props);
+    if !changed_props.is_empty() || before.flavour != after.flavour {
+      diff.edited.push(change(block_id, changed_props));
+    }
+  }
+  for block_id in &old_order {
+    if !new.contains_key(block_id) {
+      diff.removed.push(change(block_id, vec![]));
+    }
+  }
+  Ok(diff)
+}
+
+/// Reachable blocks of a doc in document order, with their versions.
+fn versions(index: Option<&BlockIndex>) -> (Vec<String>, HashMap<String, Version>) {
+  let Some(index) = index else {
+    return Default::default();
+  };
+  let mut order = Vec::new();
+  let mut versions = HashMap::new();
+  for block_id in index.descendants(&index.root_block_id) {
+    let Some(block) = index.get(&block_id) else {
+      continue;
+    };
+    let Some(snapshot) = BlockSnapshot::from_block(block, index.parent_of(&block_id)) else {
+      continue;
+    };
+    let text = block_text(block, &snapshot.flavour);
+    versions.insert(
+      block_id.clone(),
+      Version {
+        flavour: snapshot.flavour,
+        parent_block_id: snapshot.parent_block_id,
+        text,
+        props: snapshot.props,
+      },
+    );
+    order.push(block_id);
+  }
+  (order, versions)
+}
+
+/// Blocks that kept their parent but changed places among the siblings that
+/// kept it too, the fewest that explain the new order.
+fn reordered_blocks(
+  old_index: &BlockIndex,
+  new_index: &BlockIndex,
+  old: &HashMap<String, Version>,
+  new: &HashMap<String, Version>,
+) -> HashSet<String> {
+  let mut reordered = HashSet::new();
+  for parent_id in new.keys() {
+    let stayed = |block_id: &String| {
+      matches!(
+        (old.get(block_id), new.get(block_id)),
+        (Some(before), Some(after))
+          if before.parent_block_id.as_ref() == Some(parent_id)
+            && after.parent_block_id.as_ref() == Some(parent_id)
+      )
+    };
+    let old_children = old_index
+      .children_of(parent_id)
+      .into_iter()
+      .filter(stayed)
+      .collect::<Vec<_>>();
+    let new_children = new_index
+      .children_of(parent_id)
+      .into_iter()
+      .filter(stayed)
+      .collect::<Vec<_>>();
+    if old_children == new_children {
+      continue;
+    }
+    let kept = longest_common_subsequence(&old_children, &new_children);
+    reordered.extend(
+      new_children
+        .into_iter()
+        .filter(|block_id| !kept.contains(block_id)),
+    );
+  }
+  reordered
+}
+
+fn longest_common_subsequence(a: &[String], b: &[String]) -> HashSet<String> {
+  let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+  for i in (0..a.len()).rev() {
+    for j in (0..b.len()).rev() {
+      lengths[i][j] = if a[i] == b[j] {
+        lengths[i + 1][j + 1] + 1
+      } else {
+        lengths[i + 1][j].max(lengths[i][j + 1])
+      };
+    }
+  }
+
+  let mut common = HashSet::new();
+  let (mut i, mut j) = (0, 0);
+  while i < a.len() && j < b.len() {
+    if a[i] == b[j] {
+      common.insert(a[i].clone());
+      i += 1;
+      j += 1;
+    } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+      i += 1;
+    } else {
+      j += 1;
+    }
+  }
+  common
+}
+
+fn changed_props(
+  before: &JsonMap<String, JsonValue>,
+  after: &JsonMap<String, JsonValue>,
+) -> Vec<String> {
+  let mut keys = before
+    .keys()
+    .chain(after.keys())
+    .filter(|key| before.get(*key) != after.get(*key))
+    .cloned()
+    .collect::<Vec<_>>();
+  keys.sort();
+  keys.dedup();
+  keys
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  fn ids(changes: &[BlockChange]) -> Vec<&str> {
+    changes
+      .iter()
+      .map(|change| change.block_id.as_str())
+      .collect()
+  }
+
+  #[test]
+  fn test_diff_docs() {
+    let mut doc = TestDoc::new("Diff");
+    let p1 = doc.add_block("note", "p1", "affine:paragraph", &[("text", "one")]);
+    doc.add_block("note", "p2", "affine:paragraph", &[("text", "two")]);
+    doc.add_block("note", "p3", "affine:paragraph", &[("text", "three")]);
+    doc.add_block("note", "p4", "affine:paragraph", &[("text", "four")]);
+    doc.add_block("note", "p5", "affine:paragraph", &[("text", "five")]);
+    let old_bin = doc.encode();
+
+    p1.get("prop:text")
+      .and_then(|value| value.to_text())
+      .unwrap()
+      .insert(3, "!")
+      .unwrap();
+    doc.remove_block("note", "p2");
+    // p3 goes after p5, p4 below p3
+    doc.move_block("note", "note", "p3");
+    doc.move_block("note", "p3", "p4");
+    doc.add_block("note", "p6", "affine:paragraph", &[("text", "six")]);
+
+    let diff = diff_docs(old_bin.clone(), doc.encode(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(ids(&diff.added), vec!["p6"]);
+    assert_eq!(ids(&diff.removed), vec!["p2"]);
+    assert_eq!(diff.removed[0].before.as_ref().unwrap().text, "two");
+    assert_eq!(ids(&diff.moved), vec!["p3", "p4"]);
+    assert_eq!(
+      diff.moved[1]
+        .before
+        .as_ref()
+        .unwrap()
+        .parent_block_id
+        .as_deref(),
+      Some("note")
+    );
+    assert_eq!(
+      diff.moved[1]
+        .after
+        .as_ref()
+        .unwrap()
+        .parent_block_id
+        .as_deref(),
+      Some("p3")
+    );
+
+    assert_eq!(ids(&diff.edited), vec!["p1"]);
+    let edit = &diff.edited[0];
+    assert_eq!(edit.changed_props, vec!["text".to_string()]);
+    assert_eq!(edit.before.as_ref().unwrap().text, "one");
+    assert_eq!(edit.after.as_ref().unwrap().text, "one!");
+
+    let unchanged = diff_docs(old_bin.clone(), old_bin, TestDoc::DOC_ID.into()).unwrap();
+    assert!(unchanged.added.is_empty() && unchanged.removed.is_empty());
+    assert!(unchanged.moved.is_empty() && unchanged.edited.is_empty());
+  }
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use y_octo::Any;
+
+use super::{block_index::BlockIndex, get_flavour, get_string, row_doc::insert_text, ParseError};
+
+/// Schema version of the docs current clients write, raised whenever a new
+/// flavour gets a legacy equivalent in [`downgrade_doc`].
+pub const DOC_SCHEMA_VERSION: u32 = 3;
+
+/// `(flavour, schema version that introduced it, closest flavour of the
+/// version before)`.
+const DOWNGRADES: [(&str, u32, &str); 6] = [
+  ("affine:latex", 2, "affine:code"),
+  ("affine:embed-youtube", 2, "affine:bookmark"),
+  ("affine:embed-figma", 2, "affine:bookmark"),
+  ("affine:embed-github", 2, "affine:bookmark"),
+  ("affine:embed-loom", 2, "affine:bookmark"),
+  ("affine:embed-synced-doc", 3, "affine:embed-linked-doc"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DowngradedDoc {
+  /// The whole downgraded doc, unchanged when no block needed rewriting.
+  pub doc_bin: Vec<u8>,
+  /// Ids of the rewritten blocks, sorted.
+  pub downgraded_block_ids: Vec<String>,
+}
+
+/// Rewrites the blocks of flavours newer than `target_schema_version` into
+/// their closest legacy flavour, for sharing a workspace with clients that
+/// only read that version:
+///
+/// - `affine:latex` becomes an `affine:code` block of language `latex`
+/// - embed cards (YouTube, Figma, GitHub, Loom) become `affine:bookmark`
+///   blocks of the same url
+/// - `affine:embed-synced-doc` becomes `affine:embed-linked-doc`
+///
+/// Props the legacy flavour does not know about are kept, older clients
+/// ignore them.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::downgrade_doc;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let downgraded = downgrade_doc(doc_bin, "doc-id".into(), 1).unwrap();
+/// std::fs::write("demo.v1.ydoc", downgraded.doc_bin).unwrap();
+/// ```
+pub fn downgrade_doc(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  target_schema_version: u32,
+) -> Result<DowngradedDoc, ParseError> {
+  if !(1..=DOC_SCHEMA_VERSION).contains(&target_schema_version) {
+    return Err(ParseError::InvalidOptions(format!(
+      "target schema version must be between 1 and {DOC_SCHEMA_VERSION}"
+    )));
+  }
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(DowngradedDoc {
+      doc_bin,
+      downgraded_block_ids: vec![],
+    });
+  };
+
+  let mut downgraded_block_ids = vec![];
+  for (block_id, block) in &index.block_pool {
+    let Some(flavour) = get_flavour(block) else {
+      continue;
+    };
+    let Some(legacy) = legacy_flavour(&flavour, target_schema_version) else {
+      continue;
+    };
+
+    let mut block = block.clone();
+    if flavour == "affine:latex" {
+      let latex = get_string(&block, "prop:latex").unwrap_or_default();
+      insert_text(index.doc(), &mut block, "prop:text", &latex)?;
+      block.insert("prop:language".into(), "latex")?;
+      block.remove("prop:latex");
+    }
+    block.insert("sys:flavour".into(), legacy)?;
+    block.insert("sys:version".into(), Any::Integer(1))?;
+    downgraded_block_ids.push(block_id.clone());
+  }
+  downgraded_block_ids.sort();
+
+  Ok(DowngradedDoc {
+    doc_bin: index.doc().encode_update_v1()?,
+    downgraded_block_ids,
+  })
+}
+
+/// Schema version that introduced `flavour`, `1` for flavours every version
+/// reads.
+pub(crate) fn flavour_schema_version(flavour: &str) -> u32 {
+  DOWNGRADES
+    .iter()
+    .find(|(name, ..)| *name == flavour)
+    .map_or(1, |(_, introduced_in, _)| *introduced_in)
+}
+
+/// Follows the downgrades of `flavour` down to `target_schema_version`.
+fn legacy_flavour(flavour: &str, target_schema_version: u32) -> Option<&'static str> {
+  let mut legacy = None;
+  let mut current = flavour;
+  while let Some((_, _, older)) = DOWNGRADES
+    .iter()
+    .find(|(name, introduced_in, _)| *name == current && *introduced_in > target_schema_version)
+  {
+    legacy = Some(*older);
+    current = *older;
+  }
+  legacy
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_to_markdown, test_utils::TestDoc};
+
+  #[test]
+  fn test_downgrade_doc() {
+    let mut doc = TestDoc::new("Shared");
+    doc.add_block("note", "m1", "affine:latex", &[("latex", "E = mc^2")]);
+    doc.add_block(
+      "note",
+      "y1",
+      "affine:embed-youtube",
+      &[("url", "https://youtu.be/x")],
+    );
+    doc.add_block(
+      "note",
+      "s1",
+      "affine:embed-synced-doc",
+      &[("pageId", "other")],
+    );
+    let doc_bin = doc.encode();
+
+    let downgraded = downgrade_doc(doc_bin.clone(), TestDoc::DOC_ID.into(), 2).unwrap();
+    assert_eq!(downgraded.downgraded_block_ids, vec!["s1"]);
+
+    let downgraded = downgrade_doc(doc_bin.clone(), TestDoc::DOC_ID.into(), 1).unwrap();
+    assert_eq!(downgraded.downgraded_block_ids, vec!["m1", "s1", "y1"]);
+    let index = BlockIndex::load(&downgraded.doc_bin, TestDoc::DOC_ID)
+      .unwrap()
+      .unwrap();
+    assert_eq!(index.flavour_of("m1").as_deref(), Some("affine:code"));
+    assert_eq!(index.flavour_of("y1").as_deref(), Some("affine:bookmark"));
+    assert_eq!(
+      index.flavour_of("s1").as_deref(),
+      Some("affine:embed-linked-doc")
+    );
+    let markdown = parse_doc_to_markdown(downgraded.doc_bin, TestDoc::DOC_ID.into(), false)
+      .unwrap()
+      .markdown;
+    assert!(markdown.contains("```latex\nE = mc^2\n```"), "{markdown}");
+
+    let unchanged = downgrade_doc(doc_bin, TestDoc::DOC_ID.into(), DOC_SCHEMA_VERSION).unwrap();
+    assert!(unchanged.downgraded_block_ids.is_empty());
+    assert!(matches!(
+      downgrade_doc(vec![], TestDoc::DOC_ID.into(), 0),
+      Err(ParseError::InvalidOptions(_))
+    ));
+  }
+
+  #[test]
+  fn test_legacy_flavour() {
+    assert_eq!(legacy_flavour("affine:paragraph", 1), None);
+    assert_eq!(legacy_flavour("affine:latex", 2), None);
+    assert_eq!(legacy_flavour("affine:latex", 1), Some("affine:code"));
+  }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use y_octo::Map;
+
+use super::{block_index::BlockIndex, get_string, value_to_string, ExportFormat, ParseError};
+
+/// Markup added per block on top of its text: prefixes, indentation and the
+/// line break. Deliberately generous so estimates err on the large side.
+const MARKDOWN_BLOCK_OVERHEAD: usize = 8;
+/// Delimiters added per database cell.
+const MARKDOWN_CELL_OVERHEAD: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportSizeEstimate {
+  pub format: ExportFormat,
+  /// Approximate size of the rendered output in bytes.
+  pub content_bytes: usize,
+  /// Total size of the distinct blobs the doc references, as recorded on
+  /// its blocks.
+  pub blob_bytes: u64,
+  pub blob_count: usize,
+}
+
+/// Estimates the size of an export from the raw text lengths of a doc,
+/// without rendering it, so oversized exports can be rejected or queued up
+/// front.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{estimate_export_size, ExportFormat};
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let estimate = estimate_export_size(doc_bin, "doc-id".into(), ExportFormat::Markdown).unwrap();
+/// println!("~{} bytes", estimate.content_bytes);
+/// ```
+pub fn estimate_export_size(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  format: ExportFormat,
+) -> Result<ExportSizeEstimate, ParseError> {
+  let mut estimate = ExportSizeEstimate {
+    format,
+    content_bytes: 0,
+    blob_bytes: 0,
+    blob_count: 0,
+  };
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(estimate);
+  };
+
+  let mut blobs = HashMap::new();
+  for block_id in index.descendants(&index.root_block_id) {
+    let Some(block) = index.get(&block_id) else {
+      continue;
+    };
+    match format {
+      ExportFormat::Markdown => {
+        estimate.content_bytes += MARKDOWN_BLOCK_OVERHEAD + text_bytes(block);
+      }
+    }
+    if let Some(source_id) = get_string(block, "prop:sourceId") {
+      let size = get_string(block, "prop:size")
+        .and_then(|size| size.parse::<f64>().ok())
+        .unwrap_or_default();
+      blobs.insert(source_id, size as u64);
+    }
+  }
+
+  estimate.blob_count = blobs.len();
+  estimate.blob_bytes = blobs.values().sum();
+  Ok(estimate)
+}
+
+/// Byte length of the text props of a block, database cells included.
+fn text_bytes(block: &Map) -> usize {
+  let mut bytes = 0;
+  for key in ["prop:text", "prop:title", "prop:caption", "prop:name"] {
+    bytes += get_string(block, key)
+      .map(|text| text.len())
+      .unwrap_or_default();
+  }
+
+  if let Some(cells) = block.get("prop:cells").and_then(|value| value.to_map()) {
+    for (_, row) in cells.iter() {
+      let Some(row) = row.to_map() else {
+        continue;
+      };
+      for (_, cell) in row.iter() {
+        let value = cell
+          .to_map()
+          .and_then(|cell| cell.get("value"))
+          .and_then(|value| value_to_string(&value));
+        bytes += MARKDOWN_CELL_OVERHEAD + value.map(|value| value.len()).unwrap_or_default();
+      }
+    }
+  }
+
+  bytes
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_to_markdown, test_utils::TestDoc};
+
+  #[test]
+  fn test_estimate_export_size() {
+    let mut doc = TestDoc::new("Estimate");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "hello world")]);
+    doc.add_block("note", "l1", "affine:list", &[("text", "an item")]);
+    for id in ["i1", "i2"] {
+      doc.add_block(
+        "note",
+        id,
+        "affine:image",
+        &[("sourceId", "blob-a"), ("size", "1024")],
+      );
+    }
+    doc.add_block(
+      "note",
+      "a1",
+      "affine:attachment",
+      &[("sourceId", "blob-b"), ("size", "2048"), ("name", "a.pdf")],
+    );
+    let doc_bin = doc.encode();
+
+    let estimate = estimate_export_size(
+      doc_bin.clone(),
+      TestDoc::DOC_ID.into(),
+      ExportFormat::Markdown,
+    )
+    .unwrap();
+    assert_eq!(estimate.blob_count, 2);
+    assert_eq!(estimate.blob_bytes, 3072);
+
+    let rendered = parse_doc_to_markdown(doc_bin, TestDoc::DOC_ID.into(), false).unwrap();
+    assert!(estimate.content_bytes >= rendered.markdown.len());
+  }
+}
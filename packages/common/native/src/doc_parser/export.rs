@@ -0,0 +1,176 @@
+use tracing::instrument;
+
+use super::{
+  markdown::{parse_doc_to_markdown_with_options, parse_subtree_to_markdown_with_options},
+  ExportFormat, ExportResult, MarkdownOptions, ParseError,
+};
+use crate::progress::{Progress, ProgressSink};
+
+/// Renders a single block and its descendants, e.g. to copy or share one
+/// section of a doc.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{export_block_subtree, ExportFormat};
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let section = export_block_subtree(doc_bin, "doc-id".into(), "block-id", ExportFormat::Markdown)
+///   .unwrap();
+/// println!("{}", section.content);
+/// ```
+pub fn export_block_subtree(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  block_id: &str,
+  format: ExportFormat,
+) -> Result<ExportResult, ParseError> {
+  export_block_subtree_with_options(doc_bin, doc_id, block_id, format, &Default::default())
+}
+
+/// Same as [`export_block_subtree`], with markdown rendering controlled by
+/// `options`.
+pub fn export_block_subtree_with_options(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  block_id: &str,
+  format: ExportFormat,
+  options: &MarkdownOptions,
+) -> Result<ExportResult, ParseError> {
+  match format {
+    ExportFormat::Markdown => {
+      let result = parse_subtree_to_markdown_with_options(doc_bin, doc_id, block_id, options)?;
+      Ok(ExportResult {
+        title: result.title,
+        format,
+        content: result.markdown,
+        unsupported: result.unsupported,
+      })
+    }
+  }
+}
+
+/// Exports several docs, e.g. all docs of a workspace, given as
+/// `(doc id, doc binary)` pairs.
+///
+/// Progress is reported under the `"export"` stage once before the first doc
+/// and after every doc. The first doc that fails to export aborts the run.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::{
+///   doc_parser::{export_docs, ExportFormat},
+///   progress::Progress,
+/// };
+///
+/// let docs = vec![("doc-id".to_string(), std::fs::read("fixtures/demo.ydoc").unwrap())];
+/// let report = |progress: Progress| println!("{}/{}", progress.done, progress.total);
+/// let results =
+///   export_docs(docs, ExportFormat::Markdown, &Default::default(), Some(&report)).unwrap();
+/// ```
+#[instrument(name = "export", skip_all, fields(docs = docs.len(), format = ?format), err)]
+pub fn export_docs(
+  docs: Vec<(String, Vec<u8>)>,
+  format: ExportFormat,
+  options: &MarkdownOptions,
+  progress: Option<&dyn ProgressSink>,
+) -> Result<Vec<ExportResult>, ParseError> {
+  let total = docs.len();
+  let report = |done| {
+    if let Some(progress) = progress {
+      progress.report(Progress {
+        stage: "export",
+        done,
+        total,
+      });
+    }
+  };
+
+  report(0);
+  let mut results = Vec::with_capacity(total);
+  for (doc_id, doc_bin) in docs {
+    let result = match format {
+      ExportFormat::Markdown => {
+        let result = parse_doc_to_markdown_with_options(doc_bin, doc_id, options)?;
+        ExportResult {
+          title: result.title,
+          format,
+          content: result.markdown,
+          unsupported: result.unsupported,
+        }
+      }
+    };
+    results.push(result);
+    report(results.len());
+  }
+
+  Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_export_block_subtree() {
+    let mut doc = TestDoc::new("Sections");
+    doc.add_block("note", "intro", "affine:paragraph", &[("text", "Intro")]);
+    doc.add_block("note", "l1", "affine:list", &[("text", "outer")]);
+    doc.add_block("l1", "l2", "affine:list", &[("text", "inner")]);
+    doc.add_block("l2", "l3", "affine:list", &[("text", "innermost")]);
+    let doc_bin = doc.encode();
+
+    let section = export_block_subtree(
+      doc_bin.clone(),
+      TestDoc::DOC_ID.into(),
+      "l2",
+      ExportFormat::Markdown,
+    )
+    .unwrap();
+    assert_eq!(section.title, "Sections");
+    assert_eq!(section.content, "- inner\n    - innermost\n");
+
+    let whole = export_block_subtree(
+      doc_bin.clone(),
+      TestDoc::DOC_ID.into(),
+      "note",
+      ExportFormat::Markdown,
+    )
+    .unwrap();
+    assert_eq!(
+      whole.content,
+      "Intro\n- outer\n    - inner\n        - innermost\n"
+    );
+
+    assert!(matches!(
+      export_block_subtree(doc_bin, TestDoc::DOC_ID.into(), "missing", ExportFormat::Markdown),
+      Err(ParseError::BlockNotFound(id)) if id == "missing"
+    ));
+  }
+
+  #[test]
+  fn test_export_docs() {
+    let docs = ["First", "Second"]
+      .map(|title| (TestDoc::DOC_ID.to_string(), TestDoc::new(title).encode()))
+      .to_vec();
+
+    let reports = std::cell::RefCell::new(vec![]);
+    let report = |progress: Progress| reports.borrow_mut().push((progress.done, progress.total));
+    let results = export_docs(
+      docs,
+      ExportFormat::Markdown,
+      &Default::default(),
+      Some(&report),
+    )
+    .unwrap();
+
+    let titles = results
+      .iter()
+      .map(|result| result.title.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(titles, vec!["First", "Second"]);
+    assert_eq!(reports.into_inner(), vec![(0, 2), (1, 2), (2, 2)]);
+  }
+}
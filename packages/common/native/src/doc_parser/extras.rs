@@ -0,0 +1,191 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use super::ParseError;
+
+/// Version of the [`BlockExtras`] schema. Adding a field keeps the version,
+/// changing the meaning of a field or removing one bumps it.
+pub const EXTRAS_VERSION: u32 = 1;
+
+/// Typed form of [`super::BlockInfo::additional`], the JSON object the crawl
+/// attaches to every block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockExtras {
+  /// Schema version the extras were written with.
+  #[serde(default)]
+  pub version: u32,
+  /// `page` or `edgeless`, where the block is shown.
+  pub display_mode: Option<String>,
+  /// Id of the note the block belongs to.
+  pub note_block_id: Option<String>,
+  /// Title of an `affine:database` block.
+  pub database_name: Option<String>,
+}
+
+/// A field of [`BlockExtras`], to select what the crawl writes to
+/// `additional`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExtrasField {
+  DisplayMode,
+  NoteBlockId,
+  DatabaseName,
+}
+
+impl ExtrasField {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ExtrasField::DisplayMode => "displayMode",
+      ExtrasField::NoteBlockId => "noteBlockId",
+      ExtrasField::DatabaseName => "databaseName",
+    }
+  }
+}
+
+impl FromStr for ExtrasField {
+  type Err = ParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "displayMode" => Ok(ExtrasField::DisplayMode),
+      "noteBlockId" => Ok(ExtrasField::NoteBlockId),
+      "databaseName" => Ok(ExtrasField::DatabaseName),
+      _ => Err(ParseError::InvalidOptions(format!(
+        "unknown extras field: {s}"
+      ))),
+    }
+  }
+}
+
+impl BlockExtras {
+  /// Serializes the extras for [`super::BlockInfo::additional`]. With `None`
+  /// every field is written without a version, as the crawl always did; a
+  /// selection writes only those fields and the `version`.
+  pub(crate) fn to_additional(&self, fields: Option<&[ExtrasField]>) -> String {
+    let selected = |field: ExtrasField| fields.is_none_or(|fields| fields.contains(&field));
+
+    let mut payload = JsonMap::new();
+    if fields.is_some() {
+      payload.insert("version".into(), JsonValue::from(self.version));
+    }
+    for (field, value) in [
+      (ExtrasField::DisplayMode, &self.display_mode),
+      (ExtrasField::NoteBlockId, &self.note_block_id),
+      (ExtrasField::DatabaseName, &self.database_name),
+    ] {
+      if let Some(value) = value.as_ref().filter(|_| selected(field)) {
+        payload.insert(field.as_str().into(), JsonValue::String(value.clone()));
+      }
+    }
+    JsonValue::Object(payload).to_string()
+  }
+}
+
+/// Parses the `additional` JSON of a crawled block of `flavour`.
+///
+/// Extras written without a version are read as version 1, newer versions
+/// than [`EXTRAS_VERSION`] are rejected. Fields a flavour does not carry,
+/// like the `databaseName` of a paragraph, are dropped.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::doc_parser::parse_additional;
+///
+/// let extras =
+///   parse_additional("affine:paragraph", r#"{"displayMode":"page","noteBlockId":"n"}"#).unwrap();
+/// assert_eq!(extras.display_mode.as_deref(), Some("page"));
+/// assert_eq!(extras.note_block_id.as_deref(), Some("n"));
+/// ```
+pub fn parse_additional(flavour: &str, additional: &str) -> Result<BlockExtras, ParseError> {
+  let mut extras = serde_json::from_str::<BlockExtras>(additional)
+    .map_err(|e| ParseError::ParserError(e.to_string()))?;
+  if extras.version == 0 {
+    extras.version = 1;
+  }
+  if extras.version > EXTRAS_VERSION {
+    return Err(ParseError::UnsupportedFormat(format!(
+      "extras version {}",
+      extras.version
+    )));
+  }
+  if flavour != "affine:database" {
+    extras.database_name = None;
+  }
+  Ok(extras)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_from_binary_with_options, CrawlOptions};
+
+  #[test]
+  fn test_extras_roundtrip() {
+    let extras = BlockExtras {
+      version: EXTRAS_VERSION,
+      display_mode: Some("page".into()),
+      note_block_id: Some("note".into()),
+      database_name: Some("Tasks".into()),
+    };
+
+    let legacy = extras.to_additional(None);
+    assert_eq!(
+      legacy,
+      r#"{"databaseName":"Tasks","displayMode":"page","noteBlockId":"note"}"#
+    );
+    assert_eq!(
+      parse_additional("affine:database", &legacy).unwrap(),
+      extras
+    );
+    assert_eq!(
+      parse_additional("affine:paragraph", &legacy)
+        .unwrap()
+        .database_name,
+      None
+    );
+
+    let selected = extras.to_additional(Some(&[ExtrasField::NoteBlockId]));
+    assert_eq!(selected, r#"{"noteBlockId":"note","version":1}"#);
+    assert_eq!(
+      parse_additional("affine:paragraph", &selected).unwrap(),
+      BlockExtras {
+        version: 1,
+        note_block_id: Some("note".into()),
+        ..Default::default()
+      }
+    );
+
+    assert!(matches!(
+      parse_additional("affine:paragraph", r#"{"version":2}"#),
+      Err(ParseError::UnsupportedFormat(_))
+    ));
+    assert!(parse_additional("affine:paragraph", "not json").is_err());
+    assert!("title".parse::<ExtrasField>().is_err());
+  }
+
+  #[test]
+  fn test_crawl_extras() {
+    let doc_bin = include_bytes!("../../fixtures/demo.ydoc").to_vec();
+    let result = parse_doc_from_binary_with_options(
+      doc_bin,
+      "dYpV7PPhk8amRkY5IAcVO".into(),
+      &CrawlOptions {
+        extras: Some(vec![ExtrasField::DisplayMode]),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+
+    assert!(!result.blocks.is_empty());
+    for block in result.blocks {
+      let extras = parse_additional(&block.flavour, &block.additional.unwrap()).unwrap();
+      assert_eq!(extras.version, EXTRAS_VERSION);
+      assert!(extras.display_mode.is_some());
+      assert_eq!(extras.note_block_id, None);
+    }
+  }
+}
@@ -0,0 +1,134 @@
+use y_octo::{Doc, DocOptions, JwstCodecError, Map, Value};
+
+use super::{new_doc, ParseError};
+
+/// Client id of every item of a flattened doc. Flattened docs are never
+/// edited again, so it cannot clash with a live client.
+const FLATTENED_CLIENT_ID: u64 = 0;
+
+/// Rewrites a doc as a fresh binary holding only its current content, for
+/// archives that must not retain history.
+///
+/// The result has no deleted items, no delete set and a single client id,
+/// so earlier revisions and the clients that edited the doc cannot be
+/// recovered from it. Identical content always flattens to the same bytes.
+/// Root types are carried over as maps, which covers every AFFiNE doc.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::flatten_doc;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let flattened = flatten_doc(doc_bin, "doc-id".into()).unwrap();
+/// ```
+pub fn flatten_doc(doc_bin: Vec<u8>, doc_id: String) -> Result<Vec<u8>, ParseError> {
+  if doc_bin.is_empty() || doc_bin == [0, 0] {
+    return Err(ParseError::InvalidBinary);
+  }
+  let mut source = new_doc(Some(&doc_id));
+  source
+    .apply_update_from_binary_v1(&doc_bin)
+    .map_err(|_| ParseError::InvalidBinary)?;
+
+  let flattened = DocOptions::new()
+    .with_guid(doc_id)
+    .with_client_id(FLATTENED_CLIENT_ID)
+    .build();
+  let mut roots = source.keys();
+  roots.sort();
+  for name in roots {
+    let from = source.get_map(&name)?;
+    let mut to = flattened.get_or_create_map(&name)?;
+    copy_map(&flattened, &from, &mut to)?;
+  }
+
+  Ok(flattened.encode_update_v1()?)
+}
+
+fn copy_map(doc: &Doc, from: &Map, to: &mut Map) -> Result<(), ParseError> {
+  let mut entries = from
+    .iter()
+    .map(|(key, value)| (key.to_string(), value))
+    .collect::<Vec<_>>();
+  entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+  for (key, value) in entries {
+    copy_value(doc, value, &mut |copy| to.insert(key.clone(), copy))?;
+  }
+  Ok(())
+}
+
+/// Inserts a copy of `value` with `insert`. Copied types are filled after
+/// they are inserted, as they only accept content once they are part of the
+/// doc.
+fn copy_value(
+  doc: &Doc,
+  value: Value,
+  insert: &mut dyn FnMut(Value) -> Result<(), JwstCodecError>,
+) -> Result<(), ParseError> {
+  match value {
+    Value::Any(any) => insert(Value::Any(any))?,
+    Value::Map(from) => {
+      let mut to = doc.create_map()?;
+      insert(Value::Map(to.clone()))?;
+      copy_map(doc, &from, &mut to)?;
+    }
+    Value::Array(from) => {
+      let mut to = doc.create_array()?;
+      insert(Value::Array(to.clone()))?;
+      for value in from.iter() {
+        copy_value(doc, value, &mut |copy| to.push(copy))?;
+      }
+    }
+    Value::Text(from) => {
+      let mut to = doc.create_text()?;
+      insert(Value::Text(to.clone()))?;
+      to.extend_from(&from)?;
+    }
+    // only the reference to a sub doc is part of the doc, without the
+    // options of the client that created it
+    Value::Doc(sub_doc) => insert(Value::Doc(
+      DocOptions::new()
+        .with_guid(sub_doc.guid().to_string())
+        .build(),
+    ))?,
+    _ => return Err(ParseError::UnsupportedFormat("xml".into())),
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_to_markdown, test_utils::TestDoc};
+
+  #[test]
+  fn test_flatten_doc() {
+    let mut doc = TestDoc::new("Archive");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "kept")]);
+    doc.add_block("note", "p2", "affine:paragraph", &[("text", "dropped")]);
+    doc.add_block("note", "l1", "affine:list", &[("text", "item")]);
+    doc.remove_block("note", "p2");
+    let doc_bin = doc.encode();
+
+    let flattened = flatten_doc(doc_bin.clone(), TestDoc::DOC_ID.into()).unwrap();
+    assert!(!String::from_utf8_lossy(&flattened).contains("dropped"));
+
+    let update = y_octo::Update::decode_v1(&flattened).unwrap();
+    let clients = update
+      .item_lengths()
+      .map(|(client, _)| client)
+      .collect::<Vec<_>>();
+    assert_eq!(clients, vec![FLATTENED_CLIENT_ID]);
+
+    let original = parse_doc_to_markdown(doc_bin.clone(), TestDoc::DOC_ID.into(), false).unwrap();
+    let copy = parse_doc_to_markdown(flattened.clone(), TestDoc::DOC_ID.into(), false).unwrap();
+    assert_eq!(copy.title, original.title);
+    assert_eq!(copy.markdown, original.markdown);
+
+    assert_eq!(
+      flatten_doc(doc_bin, TestDoc::DOC_ID.into()).unwrap(),
+      flattened
+    );
+  }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use super::BlockSnapshot;
+
+/// Flavours with native crawl and export support. Handlers registered for
+/// these flavours are never consulted.
+pub const BUILTIN_FLAVOURS: [&str; 18] = [
+  "affine:page",
+  "affine:note",
+  "affine:surface",
+  "affine:paragraph",
+  "affine:list",
+  "affine:code",
+  "affine:embed-linked-doc",
+  "affine:embed-synced-doc",
+  "affine:attachment",
+  "affine:image",
+  "affine:database",
+  "affine:latex",
+  "affine:table",
+  "affine:bookmark",
+  "affine:embed-youtube",
+  "affine:embed-figma",
+  "affine:embed-github",
+  "affine:embed-loom",
+];
+
+pub fn is_builtin_flavour(flavour: &str) -> bool {
+  BUILTIN_FLAVOURS.contains(&flavour)
+}
+
+/// Renders a proprietary block flavour. Every method defaults to `None`,
+/// which keeps the block out of the corresponding output.
+pub trait FlavourHandler {
+  /// Returns the searchable text content of the block.
+  fn crawl(&self, _block: &BlockSnapshot) -> Option<Vec<String>> {
+    None
+  }
+
+  /// Returns the Markdown the block should be exported as.
+  fn markdown(&self, _block: &BlockSnapshot) -> Option<String> {
+    None
+  }
+
+  /// Returns the HTML the block should be exported as.
+  fn html(&self, _block: &BlockSnapshot) -> Option<String> {
+    None
+  }
+}
+
+/// Handlers for block flavours the parser does not know about.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::doc_parser::{BlockSnapshot, FlavourHandler, FlavourRegistry};
+///
+/// struct Callout;
+///
+/// impl FlavourHandler for Callout {
+///   fn markdown(&self, block: &BlockSnapshot) -> Option<String> {
+///     let text = block.props.get("text")?.as_str()?;
+///     Some(format!("> {}", text))
+///   }
+/// }
+///
+/// let mut registry = FlavourRegistry::default();
+/// registry.register("acme:callout", Callout);
+/// assert!(registry.get("acme:callout").is_some());
+/// ```
+#[derive(Default)]
+pub struct FlavourRegistry<'a> {
+  handlers: HashMap<String, Box<dyn FlavourHandler + 'a>>,
+}
+
+impl<'a> FlavourRegistry<'a> {
+  /// Registers `handler` for `flavour`, replacing any previous handler.
+  /// Returns `false` for built-in flavours, which cannot be overridden.
+  pub fn register<H: FlavourHandler + 'a>(
+    &mut self,
+    flavour: impl Into<String>,
+    handler: H,
+  ) -> bool {
+    let flavour = flavour.into();
+    if is_builtin_flavour(&flavour) {
+      return false;
+    }
+    self.handlers.insert(flavour, Box::new(handler));
+    true
+  }
+
+  pub fn unregister(&mut self, flavour: &str) -> bool {
+    self.handlers.remove(flavour).is_some()
+  }
+
+  pub fn get(&self, flavour: &str) -> Option<&(dyn FlavourHandler + 'a)> {
+    self.handlers.get(flavour).map(|handler| handler.as_ref())
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.handlers.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{
+    parse_doc_from_binary_with_handlers, parse_doc_to_markdown_with_options, test_utils::TestDoc,
+    MarkdownOptions,
+  };
+
+  struct Callout;
+
+  impl FlavourHandler for Callout {
+    fn crawl(&self, block: &BlockSnapshot) -> Option<Vec<String>> {
+      Some(vec![block.props.get("text")?.as_str()?.to_string()])
+    }
+
+    fn markdown(&self, block: &BlockSnapshot) -> Option<String> {
+      Some(format!(
+        "> **Note:** {}",
+        block.props.get("text")?.as_str()?
+      ))
+    }
+  }
+
+  #[test]
+  fn test_custom_flavour_handler() {
+    let mut doc = TestDoc::new("Custom");
+    doc.add_block(
+      "note",
+      "callout",
+      "acme:callout",
+      &[("text", "Mind the gap")],
+    );
+    let doc_bin = doc.encode();
+
+    let mut registry = FlavourRegistry::default();
+    assert!(!registry.register("affine:paragraph", Callout));
+    assert!(registry.register("acme:callout", Callout));
+
+    let crawled =
+      parse_doc_from_binary_with_handlers(doc_bin.clone(), TestDoc::DOC_ID.into(), &registry)
+        .unwrap();
+    let callout = crawled
+      .blocks
+      .iter()
+      .find(|block| block.flavour == "acme:callout")
+      .unwrap();
+    assert_eq!(callout.content, Some(vec!["Mind the gap".to_string()]));
+
+    let markdown = parse_doc_to_markdown_with_options(
+      doc_bin.clone(),
+      TestDoc::DOC_ID.into(),
+      &MarkdownOptions {
+        handlers: Some(&registry),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(markdown.markdown, "> **Note:** Mind the gap\n");
+
+    let markdown =
+      parse_doc_to_markdown_with_options(doc_bin, TestDoc::DOC_ID.into(), &Default::default())
+        .unwrap();
+    // without the handler the text survives through the generic rules
+    assert_eq!(markdown.markdown, "Mind the gap\n");
+  }
+}
@@ -0,0 +1,286 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use y_octo::Any;
+
+use super::{get_string, new_doc, root_meta::RootDoc, ParseError};
+
+/// A node of the workspace organization tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderNode {
+  pub id: String,
+  /// `folder`, `doc`, `tag` or `collection`. Kinds added by newer clients
+  /// are kept as they are.
+  pub kind: String,
+  /// Id of the doc, tag or collection the node links to, `None` for folders.
+  pub target_id: Option<String>,
+  /// Name of the folder, or title of the doc, tag or collection it links to.
+  pub name: String,
+  /// Fractional index the node is ordered by among its siblings.
+  pub index: String,
+  /// Children in their order, only folders have any.
+  pub children: Vec<FolderNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionInfo {
+  pub id: String,
+  pub name: String,
+  /// Docs added to the collection by hand, besides the ones its rules match.
+  pub allow_list: Vec<String>,
+}
+
+/// How the docs of a workspace are organized.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderTree {
+  /// Top-level folders in their order.
+  pub folders: Vec<FolderNode>,
+  /// Collections in the order of the workspace settings.
+  pub collections: Vec<CollectionInfo>,
+}
+
+/// A record of the workspace folders doc.
+pub(super) struct FolderRecord {
+  pub id: String,
+  pub parent_id: Option<String>,
+  pub kind: String,
+  /// Name of a folder, id of what other nodes link to.
+  pub data: String,
+  pub index: String,
+}
+
+/// Reads the folders and collections of a workspace as a tree, with the
+/// names of what they hold resolved from the root doc, so clients do not
+/// rebuild it from raw Yjs maps.
+///
+/// Collections and doc titles live in the root doc, folders in the
+/// workspace folders doc (`db$folders`). Without `folders_doc_bin` only
+/// collections are returned. Nodes whose parent is gone, or which are not
+/// under a top-level folder, are left out.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::read_folder_tree_from_root_doc;
+///
+/// let root_doc_bin = std::fs::read("fixtures/root.ydoc").unwrap();
+/// let folders_doc_bin = std::fs::read("fixtures/folders.ydoc").unwrap();
+/// let tree = read_folder_tree_from_root_doc(root_doc_bin, Some(folders_doc_bin)).unwrap();
+/// for folder in &tree.folders {
+///   println!("{} ({} items)", folder.name, folder.children.len());
+/// }
+/// ```
+pub fn read_folder_tree_from_root_doc(
+  doc_bin: Vec<u8>,
+  folders_doc_bin: Option<Vec<u8>>,
+) -> Result<FolderTree, ParseError> {
+  let root = RootDoc::decode(&doc_bin)?;
+
+  let collections = root
+    .doc()
+    .get_map("setting")
+    .ok()
+    .and_then(|setting| setting.get("collections"))
+    .and_then(|collections| collections.to_array())
+    .into_iter()
+    .flat_map(|collections| collections.iter().collect::<Vec<_>>())
+    .filter_map(|collection| serde_json::to_value(&collection).ok())
+    .filter_map(|collection| {
+      Some(CollectionInfo {
+        id: collection.get("id")?.as_str()?.to_string(),
+        name: json_string(&collection, "name"),
+        allow_list: collection
+          .get("allowList")
+          .and_then(JsonValue::as_array)
+          .into_iter()
+          .flatten()
+          .filter_map(|doc_id| doc_id.as_str().map(str::to_string))
+          .collect(),
+      })
+    })
+    .collect::<Vec<_>>();
+
+  let Some(folders_doc_bin) = folders_doc_bin else {
+    return Ok(FolderTree {
+      folders: vec![],
+      collections,
+    });
+  };
+
+  let mut names = HashMap::new();
+  for (meta, _) in root.pages()? {
+    names.insert(("doc".to_string(), meta.doc_id), meta.title);
+  }
+  for (id, name) in root.tags()? {
+    names.insert(("tag".to_string(), id), name);
+  }
+  for collection in &collections {
+    names.insert(
+      ("collection".to_string(), collection.id.clone()),
+      collection.name.clone(),
+    );
+  }
+
+  let mut children = HashMap::<Option<String>, Vec<FolderRecord>>::new();
+  for record in folder_records(&folders_doc_bin)? {
+    children
+      .entry(record.parent_id.clone())
+      .or_default()
+      .push(record);
+  }
+  for siblings in children.values_mut() {
+    siblings.sort_by(|a, b| a.index.cmp(&b.index).then_with(|| a.id.cmp(&b.id)));
+  }
+
+  let mut visited = HashSet::new();
+  let folders = children
+    .remove(&None)
+    .unwrap_or_default()
+    .into_iter()
+    .filter(|record| record.kind == "folder")
+    .filter_map(|record| build_node(record, &mut children, &names, &mut visited))
+    .collect();
+  Ok(FolderTree {
+    folders,
+    collections,
+  })
+}
+
+fn build_node(
+  record: FolderRecord,
+  children: &mut HashMap<Option<String>, Vec<FolderRecord>>,
+  names: &HashMap<(String, String), String>,
+  visited: &mut HashSet<String>,
+) -> Option<FolderNode> {
+  if !visited.insert(record.id.clone()) {
+    return None;
+  }
+  let (target_id, name) = if record.kind == "folder" {
+    (None, record.data)
+  } else {
+    let name = names
+      .get(&(record.kind.clone(), record.data.clone()))
+      .cloned()
+      .unwrap_or_default();
+    (Some(record.data), name)
+  };
+  let nodes = match record.kind.as_str() {
+    "folder" => children
+      .remove(&Some(record.id.clone()))
+      .unwrap_or_default(),
+    _ => vec![],
+  };
+  Some(FolderNode {
+    children: nodes
+      .into_iter()
+      .filter_map(|child| build_node(child, children, names, visited))
+      .collect(),
+    id: record.id,
+    kind: record.kind,
+    target_id,
+    name,
+    index: record.index,
+  })
+}
+
+fn json_string(object: &JsonValue, key: &str) -> String {
+  object
+    .get(key)
+    .and_then(JsonValue::as_str)
+    .unwrap_or_default()
+    .to_string()
+}
+
+/// Reads the live records of a workspace folders doc, whose records are root
+/// maps of `id`, `parentId`, `type`, `data` and `index`, flagged with
+/// `$$DELETED` once removed.
+pub(super) fn folder_records(folders_doc_bin: &[u8]) -> Result<Vec<FolderRecord>, ParseError> {
+  let mut folders = new_doc(None);
+  folders
+    .apply_update_from_binary_v1(folders_doc_bin)
+    .map_err(|_| ParseError::InvalidBinary)?;
+
+  let mut records = Vec::new();
+  for key in folders.keys() {
+    let Ok(record) = folders.get_map(&key) else {
+      continue;
+    };
+    if matches!(
+      record.get("$$DELETED").and_then(|value| value.to_any()),
+      Some(Any::True)
+    ) {
+      continue;
+    }
+    let (Some(kind), Some(data)) = (get_string(&record, "type"), get_string(&record, "data"))
+    else {
+      continue;
+    };
+    records.push(FolderRecord {
+      id: get_string(&record, "id").unwrap_or(key),
+      parent_id: get_string(&record, "parentId"),
+      kind,
+      data,
+      index: get_string(&record, "index").unwrap_or_default(),
+    });
+  }
+  Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::{folders_doc, TestRootDoc};
+
+  fn root_doc() -> Vec<u8> {
+    let mut root = TestRootDoc::new();
+    for (id, title) in [("plan", "Plan"), ("notes", "Notes")] {
+      root.add_page(id, &[], &[("title", title.into())]);
+    }
+    root.add_collection("c1", "Reading", &["notes"]);
+    root.encode()
+  }
+
+  fn names(nodes: &[FolderNode]) -> Vec<&str> {
+    nodes.iter().map(|node| node.name.as_str()).collect()
+  }
+
+  #[test]
+  fn test_read_folder_tree_from_root_doc() {
+    let tree = read_folder_tree_from_root_doc(
+      root_doc(),
+      Some(folders_doc(&[
+        ("f1", None, "folder", "Projects", "a1"),
+        ("f0", None, "folder", "Inbox", "a0"),
+        ("f2", Some("f1"), "folder", "Archive", "a2"),
+        ("l1", Some("f1"), "doc", "plan", "a1"),
+        ("l2", Some("f1"), "collection", "c1", "a0"),
+        ("l3", Some("f2"), "doc", "notes", "a0"),
+        ("l4", Some("gone"), "doc", "notes", "a0"),
+      ])),
+    )
+    .unwrap();
+    assert_eq!(
+      tree.collections,
+      vec![CollectionInfo {
+        id: "c1".into(),
+        name: "Reading".into(),
+        allow_list: vec!["notes".into()],
+      }]
+    );
+    assert_eq!(names(&tree.folders), vec!["Inbox", "Projects"]);
+
+    let projects = &tree.folders[1];
+    assert_eq!(
+      names(&projects.children),
+      vec!["Reading", "Plan", "Archive"]
+    );
+    assert_eq!(projects.children[0].kind, "collection");
+    assert_eq!(projects.children[1].target_id.as_deref(), Some("plan"));
+    assert_eq!(names(&projects.children[2].children), vec!["Notes"]);
+
+    let tree = read_folder_tree_from_root_doc(root_doc(), None).unwrap();
+    assert!(tree.folders.is_empty());
+    assert_eq!(tree.collections.len(), 1);
+  }
+}
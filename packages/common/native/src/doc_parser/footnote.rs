@@ -0,0 +1,222 @@
+use y_octo::{AHashMap, Any, Map};
+
+/// A footnote mark. The editor stores footnotes as a placeholder character
+/// carrying a `footnote` attribute with the label and what the note points
+/// at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Footnote {
+  pub(crate) label: String,
+  pub(crate) target: FootnoteTarget,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FootnoteTarget {
+  Url {
+    url: String,
+    title: Option<String>,
+  },
+  Doc {
+    doc_id: String,
+  },
+  Attachment {
+    blob_id: String,
+    file_name: Option<String>,
+  },
+}
+
+impl Footnote {
+  /// Reads the footnote of a delta op, `None` when the op is not one or the
+  /// footnote points at nothing.
+  pub(crate) fn from_attributes(attributes: &AHashMap<String, Any>) -> Option<Self> {
+    let Some(Any::Object(footnote)) = attributes.get("footnote") else {
+      return None;
+    };
+    let label = string(footnote, "label")?
+      .split_whitespace()
+      .collect::<String>();
+    if label.is_empty() {
+      return None;
+    }
+    let Some(Any::Object(reference)) = footnote.get("reference") else {
+      return None;
+    };
+
+    let target = match string(reference, "type")?.as_str() {
+      "url" => FootnoteTarget::Url {
+        url: string(reference, "url")?,
+        title: string(reference, "title"),
+      },
+      "doc" => FootnoteTarget::Doc {
+        doc_id: string(reference, "docId")?,
+      },
+      "attachment" => FootnoteTarget::Attachment {
+        blob_id: string(reference, "blobId")?,
+        file_name: string(reference, "fileName"),
+      },
+      _ => return None,
+    };
+    Some(Self { label, target })
+  }
+
+  /// The marker exports put in place of the footnote, `[^<label>]`.
+  pub(crate) fn marker(&self) -> String {
+    format!("[^{}]", self.label)
+  }
+
+  /// Text of the note: the title or url of a link, the id of a doc or the
+  /// file name of an attachment.
+  pub(crate) fn text(&self) -> &str {
+    match &self.target {
+      FootnoteTarget::Url { url, title } => title.as_deref().unwrap_or(url),
+      FootnoteTarget::Doc { doc_id } => doc_id,
+      FootnoteTarget::Attachment { blob_id, file_name } => file_name.as_deref().unwrap_or(blob_id),
+    }
+  }
+
+  /// Where the note links to, with `{docId}` of `doc_link_template` and blob
+  /// ids resolved by `blob_url`.
+  pub(crate) fn href(
+    &self,
+    doc_link_template: Option<&str>,
+    blob_url: impl Fn(&str) -> Option<String>,
+  ) -> Option<String> {
+    match &self.target {
+      FootnoteTarget::Url { url, .. } => Some(url.clone()),
+      FootnoteTarget::Doc { doc_id } => {
+        doc_link_template.map(|template| template.replace("{docId}", doc_id))
+      }
+      FootnoteTarget::Attachment { blob_id, .. } => blob_url(blob_id),
+    }
+  }
+
+  /// A markdown footnote definition, `[^<label>]: <text>`.
+  pub(crate) fn definition(&self) -> String {
+    format!("{}: {}", self.marker(), self.text())
+  }
+}
+
+fn string(object: &AHashMap<String, Any>, key: &str) -> Option<String> {
+  match object.get(key) {
+    Some(Any::String(value)) if !value.is_empty() => Some(value.clone()),
+    _ => None,
+  }
+}
+
+/// Reads the text at `key` with footnote placeholders replaced by their
+/// [`Footnote::marker`], together with the footnotes in text order.
+pub(crate) fn text_with_footnotes(block: &Map, key: &str) -> Option<(String, Vec<Footnote>)> {
+  let text = block.get(key)?.to_text()?;
+  let mut content = String::new();
+  let mut footnotes = Vec::new();
+  for op in text.to_delta() {
+    match Footnote::from_attributes(&op.attributes) {
+      Some(footnote) => {
+        content.push_str(&footnote.marker());
+        footnotes.push(footnote);
+      }
+      None => content.push_str(&op.insert),
+    }
+  }
+  Some((content, footnotes))
+}
+
+/// Footnotes in the order they are first referenced, one per label.
+#[derive(Default)]
+pub(crate) struct Footnotes(Vec<Footnote>);
+
+impl Footnotes {
+  /// Records `footnote`, returning whether its label is new.
+  pub(crate) fn insert(&mut self, footnote: Footnote) -> bool {
+    if self.0.iter().any(|known| known.label == footnote.label) {
+      return false;
+    }
+    self.0.push(footnote);
+    true
+  }
+
+  pub(crate) fn into_inner(self) -> Vec<Footnote> {
+    self.0
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn object(entries: &[(&str, &str)]) -> Any {
+    Any::Object(
+      entries
+        .iter()
+        .map(|(key, value)| (key.to_string(), Any::String(value.to_string())))
+        .collect(),
+    )
+  }
+
+  fn footnote(label: &str, reference: &[(&str, &str)]) -> AHashMap<String, Any> {
+    let mut footnote = match object(&[("label", label)]) {
+      Any::Object(footnote) => footnote,
+      _ => unreachable!(),
+    };
+    footnote.insert("reference".into(), object(reference));
+    [("footnote".to_string(), Any::Object(footnote))]
+      .into_iter()
+      .collect()
+  }
+
+  #[test]
+  fn test_footnote_from_attributes() {
+    let url = Footnote::from_attributes(&footnote(
+      "1",
+      &[
+        ("type", "url"),
+        ("url", "https://affine.pro"),
+        ("title", "AFFiNE"),
+      ],
+    ))
+    .unwrap();
+    assert_eq!(url.definition(), "[^1]: AFFiNE");
+    assert_eq!(
+      url.href(None, |_| None).as_deref(),
+      Some("https://affine.pro")
+    );
+
+    let doc =
+      Footnote::from_attributes(&footnote("2", &[("type", "doc"), ("docId", "d1")])).unwrap();
+    assert_eq!(doc.definition(), "[^2]: d1");
+    assert_eq!(doc.href(None, |_| None), None);
+    assert_eq!(
+      doc.href(Some("/doc/{docId}"), |_| None).as_deref(),
+      Some("/doc/d1")
+    );
+
+    let attachment = Footnote::from_attributes(&footnote(
+      "a b",
+      &[
+        ("type", "attachment"),
+        ("blobId", "b1"),
+        ("fileName", "spec.pdf"),
+      ],
+    ))
+    .unwrap();
+    assert_eq!(attachment.marker(), "[^ab]");
+    assert_eq!(attachment.text(), "spec.pdf");
+    assert_eq!(
+      attachment
+        .href(None, |blob_id| Some(format!("blob://{blob_id}")))
+        .as_deref(),
+      Some("blob://b1")
+    );
+
+    assert_eq!(
+      Footnote::from_attributes(&footnote("3", &[("type", "url")])),
+      None
+    );
+    assert_eq!(Footnote::from_attributes(&AHashMap::default()), None);
+
+    let mut footnotes = Footnotes::default();
+    assert!(footnotes.insert(url.clone()));
+    assert!(!footnotes.insert(url));
+    assert!(footnotes.insert(doc));
+    assert_eq!(footnotes.into_inner().len(), 2);
+  }
+}
@@ -0,0 +1,106 @@
+use serde_json::Value as JsonValue;
+
+use super::{markdown::escape_html, BlockSnapshot, FlavourHandler};
+
+/// Props the generic rules read text from, in output order.
+pub const GENERIC_TEXT_PROPS: [&str; 5] = ["title", "text", "caption", "name", "description"];
+
+/// Renders blocks of flavours that are neither built in nor registered, so
+/// docs written by newer clients keep their content when parsed by an older
+/// backend.
+///
+/// The non-empty string values of [`GENERIC_TEXT_PROPS`] become the block's
+/// text, one paragraph each. Children are not part of the block and are
+/// rendered on their own as usual. Exports report blocks rendered this way
+/// with [`super::ExportFallback::Generic`].
+pub struct GenericFlavourHandler;
+
+impl GenericFlavourHandler {
+  fn texts(block: &BlockSnapshot) -> Option<Vec<String>> {
+    let texts = GENERIC_TEXT_PROPS
+      .iter()
+      .filter_map(|key| match block.props.get(*key) {
+        Some(JsonValue::String(value)) if !value.trim().is_empty() => Some(value.clone()),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    (!texts.is_empty()).then_some(texts)
+  }
+}
+
+impl FlavourHandler for GenericFlavourHandler {
+  fn crawl(&self, block: &BlockSnapshot) -> Option<Vec<String>> {
+    Self::texts(block)
+  }
+
+  fn markdown(&self, block: &BlockSnapshot) -> Option<String> {
+    Self::texts(block).map(|texts| texts.join("\n\n"))
+  }
+
+  fn html(&self, block: &BlockSnapshot) -> Option<String> {
+    Self::texts(block).map(|texts| {
+      texts
+        .iter()
+        .map(|text| format!("<p>{}</p>\n", escape_html(text)))
+        .collect()
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::doc_parser::{
+    parse_doc_from_binary, parse_doc_to_html, parse_doc_to_markdown, test_utils::TestDoc,
+    ExportFallback, UnsupportedBlock,
+  };
+
+  #[test]
+  fn test_generic_fallback() {
+    let mut doc = TestDoc::new("Future");
+    doc.add_block(
+      "note",
+      "w1",
+      "affine:widget",
+      &[
+        ("title", "Totals"),
+        ("text", "42 <items>"),
+        ("color", "red"),
+      ],
+    );
+    doc.add_block("w1", "p1", "affine:paragraph", &[("text", "nested")]);
+    doc.add_block("note", "d1", "affine:divider", &[]);
+    let doc_bin = doc.encode();
+
+    let crawled = parse_doc_from_binary(doc_bin.clone(), TestDoc::DOC_ID.into()).unwrap();
+    let widget = crawled
+      .blocks
+      .iter()
+      .find(|block| block.block_id == "w1")
+      .unwrap();
+    assert_eq!(
+      widget.content,
+      Some(vec!["Totals".to_string(), "42 <items>".to_string()])
+    );
+
+    let markdown = parse_doc_to_markdown(doc_bin.clone(), TestDoc::DOC_ID.into(), false).unwrap();
+    assert_eq!(markdown.markdown, "Totals\n\n42 <items>\nnested\n");
+    let report = |unsupported: Vec<UnsupportedBlock>| {
+      unsupported
+        .into_iter()
+        .map(|block| (block.block_id, block.fallback))
+        .collect::<Vec<_>>()
+    };
+    let expected = vec![
+      ("w1".to_string(), ExportFallback::Generic),
+      ("d1".to_string(), ExportFallback::Omitted),
+    ];
+    assert_eq!(report(markdown.unsupported), expected);
+
+    let html = parse_doc_to_html(doc_bin, TestDoc::DOC_ID.into(), &Default::default()).unwrap();
+    assert_eq!(
+      html.html,
+      "<p>Totals</p>\n<p>42 &lt;items&gt;</p>\n<p>nested</p>\n<hr>\n"
+    );
+    assert_eq!(report(html.unsupported), vec![expected[0].clone()]);
+  }
+}
@@ -0,0 +1,257 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use y_octo::Doc;
+
+use super::{block_index::BlockIndex, text_content, ParseError};
+
+const MAX_CANDIDATES: usize = 5;
+const MIN_CONTENT_CONFIDENCE: f64 = 0.3;
+const PARENT_CONFIDENCE: f64 = 0.2;
+/// Content matches of a different flavour are scaled down by this factor.
+const FLAVOUR_MISMATCH_PENALTY: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealReason {
+  /// The block still exists, the link is not broken.
+  Exists,
+  /// A surviving block carries content similar to the deleted one.
+  Content,
+  /// The block the deleted one used to live in.
+  Parent,
+}
+
+impl HealReason {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      HealReason::Exists => "exists",
+      HealReason::Content => "content",
+      HealReason::Parent => "parent",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealCandidate {
+  pub block_id: String,
+  pub flavour: String,
+  /// Between `0` and `1`.
+  pub confidence: f64,
+  pub reason: HealReason,
+}
+
+/// What the doc history still knows about a deleted block.
+#[derive(Default)]
+struct DeletedBlock {
+  flavour: Option<String>,
+  text: String,
+  parent_id: Option<String>,
+}
+
+/// Suggests surviving blocks a link to `broken_block_id` could point to
+/// instead, best candidate first.
+///
+/// The deleted block's flavour, text and former parent are recovered from
+/// the tombstones kept in the doc binary, then compared with the word sets of
+/// the surviving blocks. The former parent is offered as a low-confidence
+/// fallback. Returns no candidates when the binary holds no trace of the block,
+/// e.g. after it was garbage collected.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::heal_reference;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// for candidate in heal_reference(doc_bin, "doc-id".into(), "deleted-block").unwrap() {
+///   println!("{} ({:.2})", candidate.block_id, candidate.confidence);
+/// }
+/// ```
+pub fn heal_reference(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  broken_block_id: &str,
+) -> Result<Vec<HealCandidate>, ParseError> {
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(vec![]);
+  };
+
+  if let Some(flavour) = index.flavour_of(broken_block_id) {
+    return Ok(vec![HealCandidate {
+      block_id: broken_block_id.to_string(),
+      flavour,
+      confidence: 1.0,
+      reason: HealReason::Exists,
+    }]);
+  }
+
+  let deleted = find_deleted_block(index.doc(), broken_block_id);
+  let deleted_words = fingerprint(&deleted.text);
+
+  let mut candidates = Vec::new();
+  if !deleted_words.is_empty() {
+    for block_id in index.descendants(&index.root_block_id) {
+      let (Some(block), Some(flavour)) = (index.get(&block_id), index.flavour_of(&block_id)) else {
+        continue;
+      };
+      let Some((text, _)) = text_content(block, "prop:text") else {
+        continue;
+      };
+
+      let mut confidence = similarity(&deleted_words, &fingerprint(&text));
+      if deleted
+        .flavour
+        .as_ref()
+        .is_some_and(|deleted_flavour| *deleted_flavour != flavour)
+      {
+        confidence *= FLAVOUR_MISMATCH_PENALTY;
+      }
+      if confidence >= MIN_CONTENT_CONFIDENCE {
+        candidates.push(HealCandidate {
+          block_id,
+          flavour,
+          confidence,
+          reason: HealReason::Content,
+        });
+      }
+    }
+  }
+
+  candidates.sort_by(|a, b| {
+    b.confidence
+      .total_cmp(&a.confidence)
+      .then_with(|| a.block_id.cmp(&b.block_id))
+  });
+  candidates.truncate(MAX_CANDIDATES);
+
+  if let Some(parent_id) = deleted.parent_id {
+    if let Some(flavour) = index.flavour_of(&parent_id) {
+      if !candidates
+        .iter()
+        .any(|candidate| candidate.block_id == parent_id)
+      {
+        candidates.push(HealCandidate {
+          block_id: parent_id,
+          flavour,
+          confidence: PARENT_CONFIDENCE,
+          reason: HealReason::Parent,
+        });
+      }
+    }
+  }
+
+  Ok(candidates)
+}
+
+fn find_deleted_block(doc: &Doc, block_id: &str) -> DeletedBlock {
+  let mut deleted = DeletedBlock::default();
+  let mut text_chunks = BTreeMap::new();
+  for history in doc.history().parse_deleted() {
+    // paths look like `blocks/<block id>/<prop>[/<index>]`
+    let path = history
+      .parent
+      .iter()
+      .map(String::as_str)
+      .collect::<Vec<_>>();
+    match path.as_slice() {
+      ["blocks", id, "sys:flavour"] if *id == block_id => {
+        deleted.flavour = Some(unquote(&history.content).to_string());
+      }
+      ["blocks", id, "prop:text", index] if *id == block_id => {
+        if let Ok(index) = index.parse::<usize>() {
+          text_chunks.insert(index, unquote(&history.content).to_string());
+        }
+      }
+      ["blocks", parent_id, "sys:children", _] => {
+        let content = history
+          .content
+          .trim_start_matches('[')
+          .trim_end_matches(']');
+        if content
+          .split(", ")
+          .any(|child_id| unquote(child_id) == block_id)
+        {
+          deleted.parent_id = Some(parent_id.to_string());
+        }
+      }
+      _ => {}
+    }
+  }
+  deleted.text = text_chunks.into_values().collect();
+
+  deleted
+}
+
+fn unquote(value: &str) -> &str {
+  value
+    .strip_prefix('"')
+    .and_then(|value| value.strip_suffix('"'))
+    .unwrap_or(value)
+}
+
+fn fingerprint(text: &str) -> HashSet<String> {
+  text
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(str::to_lowercase)
+    .collect()
+}
+
+/// Jaccard index of two word sets.
+fn similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+  let union = a.union(b).count();
+  if union == 0 {
+    return 0.0;
+  }
+  a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_heal_reference() {
+    let mut doc = TestDoc::new("Healing");
+    doc.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[("text", "The quick brown fox jumps")],
+    );
+    doc.add_block(
+      "note",
+      "p2",
+      "affine:paragraph",
+      &[("text", "Something else entirely")],
+    );
+    doc.remove_block("note", "p1");
+    doc.add_block(
+      "note",
+      "p3",
+      "affine:paragraph",
+      &[("text", "the quick brown fox jumps!")],
+    );
+    let doc_bin = doc.encode();
+
+    let candidates = heal_reference(doc_bin.clone(), TestDoc::DOC_ID.into(), "p1").unwrap();
+    let summary = candidates
+      .iter()
+      .map(|candidate| (candidate.block_id.as_str(), candidate.reason))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      summary,
+      vec![("p3", HealReason::Content), ("note", HealReason::Parent)]
+    );
+    assert_eq!(candidates[0].confidence, 1.0);
+
+    let candidates = heal_reference(doc_bin.clone(), TestDoc::DOC_ID.into(), "p2").unwrap();
+    assert_eq!(candidates[0].reason, HealReason::Exists);
+
+    assert!(heal_reference(doc_bin, TestDoc::DOC_ID.into(), "never")
+      .unwrap()
+      .is_empty());
+  }
+}
@@ -0,0 +1,620 @@
+use std::{
+  cell::RefCell,
+  collections::{HashMap, HashSet},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use y_octo::{Any, Map};
+
+use super::{
+  block_index::BlockIndex,
+  cross_ref::{contains_references, CrossReferenceTargets},
+  footnote::{Footnote, Footnotes},
+  get_flavour, get_string, is_builtin_flavour,
+  markdown::{blob_url, database_rows, escape_html},
+  outline::{outline, OutlineEntry},
+  text_content, value_to_string, BlockSnapshot, ExportFallback, FlavourHandler,
+  GenericFlavourHandler, MarkdownOptions, ParseError, UnsupportedBlock, BOOKMARK_FLAVOURS,
+  NOTE_FLAVOUR, PAGE_FLAVOUR,
+};
+
+/// URL schemes links and images may use, anything else is dropped.
+const SAFE_SCHEMES: [&str; 4] = ["http", "https", "mailto", "blob"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HtmlResult {
+  pub title: String,
+  pub html: String,
+  /// Blocks the HTML does not represent faithfully, in document order.
+  pub unsupported: Vec<UnsupportedBlock>,
+}
+
+/// Renders a doc into an HTML fragment, without going through markdown.
+///
+/// Unlike the markdown export, lists are nested, callouts keep their
+/// children, databases and tables become `<table>`s and inline formatting,
+/// links, doc references and footnotes are kept, the latter as `<sup>`
+/// markers followed by a `<section class="footnotes">` of endnotes. Headings
+/// carry the slug of [`super::extract_outline`] as their `id`, and reference
+/// tokens are resolved as described in [`super::list_cross_references`].
+///
+/// `doc_link_template`, `blobs`, `filter` and `numbered_headings` of
+/// `options` apply as for markdown; flavour handlers and middleware only
+/// produce markdown and are not used, unknown flavours are rendered by the
+/// [`super::GenericFlavourHandler`] rules. All text is escaped and links
+/// with a scheme other than `http`, `https`, `mailto` or `blob` are dropped,
+/// so the output can be embedded as is.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::parse_doc_to_html;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let result = parse_doc_to_html(doc_bin, "doc-id".into(), &Default::default()).unwrap();
+/// println!("<h1>{}</h1>{}", result.title, result.html);
+/// ```
+#[instrument(name = "html", skip_all, fields(doc_id = %doc_id), err)]
+pub fn parse_doc_to_html(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  options: &MarkdownOptions,
+) -> Result<HtmlResult, ParseError> {
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(HtmlResult {
+      title: "".into(),
+      html: "".into(),
+      unsupported: vec![],
+    });
+  };
+
+  let outline = outline(&index);
+  let renderer = HtmlRenderer {
+    index: &index,
+    options,
+    headings: outline
+      .iter()
+      .map(|entry| (entry.block_id.clone(), entry.clone()))
+      .collect(),
+    references: CrossReferenceTargets::from_outline(outline),
+    footnotes: RefCell::default(),
+    visited: RefCell::default(),
+    unsupported: RefCell::default(),
+  };
+
+  let root_block_id = index.root_block_id.clone();
+  let title = index
+    .get(&root_block_id)
+    .and_then(|root| get_string(root, "prop:title"))
+    .unwrap_or_default();
+  let mut html = String::new();
+  if renderer.enter(&root_block_id) {
+    renderer.render_block(&mut html, &root_block_id);
+  }
+  renderer.render_footnotes(&mut html);
+
+  Ok(HtmlResult {
+    title,
+    html,
+    unsupported: renderer.unsupported.into_inner(),
+  })
+}
+
+struct HtmlRenderer<'a> {
+  index: &'a BlockIndex,
+  options: &'a MarkdownOptions<'a>,
+  headings: HashMap<String, OutlineEntry>,
+  references: CrossReferenceTargets,
+  footnotes: RefCell<Footnotes>,
+  /// Guards against blocks listed under several parents in damaged docs.
+  visited: RefCell<HashSet<String>>,
+  unsupported: RefCell<Vec<UnsupportedBlock>>,
+}
+
+impl HtmlRenderer<'_> {
+  /// Whether `block_id` should be rendered, marking it as visited.
+  fn enter(&self, block_id: &str) -> bool {
+    let Some(block) = self.index.get(block_id) else {
+      return false;
+    };
+    if self
+      .options
+      .filter
+      .as_ref()
+      .is_some_and(|filter| filter.denies(block))
+    {
+      return false;
+    }
+    self.visited.borrow_mut().insert(block_id.to_string())
+  }
+
+  fn render_children(&self, out: &mut String, block_id: &str) {
+    let children = self
+      .index
+      .children_of(block_id)
+      .into_iter()
+      .filter(|child_id| self.enter(child_id))
+      .collect::<Vec<_>>();
+
+    // consecutive list items of the same kind share a list element
+    let mut i = 0;
+    while i < children.len() {
+      let Some(tag) = self.list_tag(&children[i]) else {
+        self.render_block(out, &children[i]);
+        i += 1;
+        continue;
+      };
+      out.push_str(&format!("<{tag}>\n"));
+      while i < children.len() && self.list_tag(&children[i]) == Some(tag) {
+        self.render_list_item(out, &children[i]);
+        i += 1;
+      }
+      out.push_str(&format!("</{tag}>\n"));
+    }
+  }
+
+  fn list_tag(&self, block_id: &str) -> Option<&'static str> {
+    let block = self.index.get(block_id)?;
+    if get_flavour(block).as_deref() != Some("affine:list") {
+      return None;
+    }
+    match get_string(block, "prop:type").as_deref() {
+      Some("numbered") => Some("ol"),
+      _ => Some("ul"),
+    }
+  }
+
+  fn render_list_item(&self, out: &mut String, block_id: &str) {
+    let Some(block) = self.index.get(block_id) else {
+      return;
+    };
+    out.push_str("<li>");
+    if get_string(block, "prop:type").as_deref() == Some("todo") {
+      let checked = block
+        .get("prop:checked")
+        .and_then(|value| value.to_any())
+        .is_some_and(|value| value == Any::True);
+      out.push_str(if checked {
+        "<input type=\"checkbox\" disabled checked> "
+      } else {
+        "<input type=\"checkbox\" disabled> "
+      });
+    }
+    out.push_str(&self.inline(block));
+    let mut children = String::new();
+    self.render_children(&mut children, block_id);
+    if !children.is_empty() {
+      out.push('\n');
+      out.push_str(&children);
+    }
+    out.push_str("</li>\n");
+  }
+
+  fn render_block(&self, out: &mut String, block_id: &str) {
+    let Some(block) = self.index.get(block_id) else {
+      return;
+    };
+    let Some(flavour) = get_flavour(block) else {
+      return;
+    };
+    let prop = |key: &str| get_string(block, &format!("prop:{key}"));
+
+    match flavour.as_str() {
+      PAGE_FLAVOUR | NOTE_FLAVOUR => self.render_children(out, block_id),
+      "affine:surface" => {}
+      "affine:paragraph" => {
+        let type_ = prop("type").unwrap_or_default();
+        match type_.as_str() {
+          "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => match self.headings.get(block_id) {
+            Some(heading) => {
+              let number = if self.options.numbered_headings {
+                format!("{} ", heading.number)
+              } else {
+                String::new()
+              };
+              out.push_str(&format!(
+                "<{type_} id=\"{}\">{}{}</{type_}>\n",
+                escape_html(&heading.slug),
+                number,
+                self.inline(block)
+              ));
+            }
+            None => out.push_str(&format!("<{type_}>{}</{type_}>\n", self.inline(block))),
+          },
+          "quote" => out.push_str(&format!(
+            "<blockquote>{}</blockquote>\n",
+            self.inline(block)
+          )),
+          _ => out.push_str(&format!("<p>{}</p>\n", self.inline(block))),
+        }
+        self.render_children(out, block_id);
+      }
+      "affine:code" => {
+        let text = text_content(block, "prop:text")
+          .map(|(text, _)| text)
+          .unwrap_or_default();
+        match prop("language").filter(|lang| !lang.is_empty()) {
+          Some(lang) => out.push_str(&format!(
+            "<pre><code class=\"language-{}\">{}</code></pre>\n",
+            escape_html(&lang),
+            escape_html(&text)
+          )),
+          None => out.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&text))),
+        }
+      }
+      "affine:callout" => {
+        out.push_str("<aside class=\"callout\">\n");
+        if let Some(emoji) = prop("emoji").filter(|emoji| !emoji.is_empty()) {
+          out.push_str(&format!(
+            "<span class=\"callout-emoji\">{}</span>\n",
+            escape_html(&emoji)
+          ));
+        }
+        if text_content(block, "prop:text").is_some_and(|(text, _)| !text.is_empty()) {
+          out.push_str(&format!("<p>{}</p>\n", self.inline(block)));
+        }
+        self.render_children(out, block_id);
+        out.push_str("</aside>\n");
+      }
+      "affine:divider" => out.push_str("<hr>\n"),
+      "affine:database" => {
+        let title = prop("title").unwrap_or_default();
+        out.push_str(&format!("<h3>{}</h3>\n", escape_html(&title)));
+        if let Some((header, rows)) = database_rows(self.index, block) {
+          push_table(out, Some(&header), &rows);
+        }
+      }
+      "affine:table" => push_table(out, None, &table_rows(block)),
+      "affine:image" => match prop("sourceId").and_then(|id| self.blob_url(&id)) {
+        Some(url) => {
+          let caption = prop("caption").unwrap_or_default();
+          out.push_str(&format!(
+            "<figure><img src=\"{}\" alt=\"{}\">",
+            escape_html(&url),
+            escape_html(&caption)
+          ));
+          if !caption.is_empty() {
+            out.push_str(&format!(
+              "<figcaption>{}</figcaption>",
+              escape_html(&caption)
+            ));
+          }
+          out.push_str("</figure>\n");
+        }
+        None => self.record_unsupported(block_id, &flavour, ExportFallback::Omitted),
+      },
+      "affine:attachment" => match prop("sourceId").and_then(|id| self.blob_url(&id)) {
+        Some(url) => {
+          let name = prop("name").unwrap_or_default();
+          out.push_str(&format!(
+            "<p><a href=\"{}\" download=\"{}\">{}</a></p>\n",
+            escape_html(&url),
+            escape_html(&name),
+            escape_html(&name)
+          ));
+        }
+        None => self.record_unsupported(block_id, &flavour, ExportFallback::Omitted),
+      },
+      "affine:embed-linked-doc" | "affine:embed-synced-doc" => {
+        let (Some(template), Some(page_id)) = (&self.options.doc_link_template, prop("pageId"))
+        else {
+          self.record_unsupported(block_id, &flavour, ExportFallback::Omitted);
+          return;
+        };
+        if flavour == "affine:embed-synced-doc" {
+          self.record_unsupported(block_id, &flavour, ExportFallback::Link);
+        }
+        let label = prop("title")
+          .filter(|title| !title.is_empty())
+          .unwrap_or_else(|| page_id.clone());
+        out.push_str(&format!(
+          "<p><a href=\"{}\">{}</a></p>\n",
+          escape_html(&template.replace("{docId}", &page_id)),
+          escape_html(&label)
+        ));
+      }
+      flavour if BOOKMARK_FLAVOURS.contains(&flavour) => {
+        match prop("url").as_deref().and_then(safe_url) {
+          Some(url) => {
+            let label = prop("title")
+              .filter(|title| !title.is_empty())
+              .unwrap_or_else(|| url.to_string());
+            out.push_str(&format!(
+              "<p><a href=\"{}\">{}</a></p>\n",
+              escape_html(url),
+              escape_html(&label)
+            ));
+          }
+          None => self.record_unsupported(block_id, flavour, ExportFallback::Omitted),
+        }
+      }
+      flavour if !is_builtin_flavour(flavour) => {
+        match BlockSnapshot::from_block(block, None)
+          .and_then(|snapshot| GenericFlavourHandler.html(&snapshot))
+        {
+          Some(rendered) => {
+            self.record_unsupported(block_id, flavour, ExportFallback::Generic);
+            out.push_str(&rendered);
+          }
+          None => self.record_unsupported(block_id, flavour, ExportFallback::Omitted),
+        }
+        self.render_children(out, block_id);
+      }
+      flavour => self.record_unsupported(block_id, flavour, ExportFallback::Omitted),
+    }
+  }
+
+  /// Renders the text of a block with its inline formatting.
+  fn inline(&self, block: &Map) -> String {
+    let Some(text) = block.get("prop:text").and_then(|value| value.to_text()) else {
+      return String::new();
+    };
+
+    let mut out = String::new();
+    for op in text.to_delta() {
+      if let Some(footnote) = Footnote::from_attributes(&op.attributes) {
+        let id = escape_html(&footnote.label);
+        // later references to the same note link to it without an anchor of
+        // their own
+        let anchor = if self.footnotes.borrow_mut().insert(footnote) {
+          format!(" id=\"fnref-{id}\"")
+        } else {
+          String::new()
+        };
+        out.push_str(&format!(
+          "<sup class=\"footnote-ref\"{anchor}><a href=\"#fn-{id}\">{id}</a></sup>"
+        ));
+        continue;
+      }
+
+      let is_set = |key: &str| op.attributes.get(key) == Some(&Any::True);
+      let mut html = if contains_references(&op.insert) {
+        self
+          .references
+          .render_html(self.index, &op.insert, self.options.numbered_headings)
+      } else {
+        escape_html(&op.insert)
+      };
+
+      for (key, tag) in [
+        ("code", "code"),
+        ("bold", "strong"),
+        ("italic", "em"),
+        ("underline", "u"),
+        ("strike", "s"),
+      ] {
+        if is_set(key) {
+          html = format!("<{tag}>{html}</{tag}>");
+        }
+      }
+
+      if let Some(Any::String(link)) = op.attributes.get("link") {
+        if let Some(url) = safe_url(link) {
+          html = format!("<a href=\"{}\">{html}</a>", escape_html(url));
+        }
+      } else if let Some(Any::Object(reference)) = op.attributes.get("reference") {
+        if let Some(Any::String(page_id)) = reference.get("pageId") {
+          // references are stored as a placeholder character
+          if op.insert.trim().is_empty() {
+            html = escape_html(page_id);
+          }
+          html = match &self.options.doc_link_template {
+            Some(template) => format!(
+              "<a href=\"{}\" data-doc-id=\"{}\">{html}</a>",
+              escape_html(&template.replace("{docId}", page_id)),
+              escape_html(page_id)
+            ),
+            None => format!(
+              "<span data-doc-id=\"{}\">{html}</span>",
+              escape_html(page_id)
+            ),
+          };
+        }
+      }
+      out.push_str(&html);
+    }
+    out
+  }
+
+  /// Renders the footnotes referenced so far as endnotes, in reference
+  /// order.
+  fn render_footnotes(&self, out: &mut String) {
+    let footnotes = self.footnotes.take().into_inner();
+    if footnotes.is_empty() {
+      return;
+    }
+    out.push_str("<section class=\"footnotes\">\n<ol>\n");
+    for footnote in footnotes {
+      let id = escape_html(&footnote.label);
+      let text = escape_html(footnote.text());
+      let href = footnote.href(self.options.doc_link_template.as_deref(), |blob_id| {
+        self.blob_url(blob_id)
+      });
+      let note = match href.as_deref().and_then(safe_url) {
+        Some(url) => format!("<a href=\"{}\">{text}</a>", escape_html(url)),
+        None => text,
+      };
+      out.push_str(&format!(
+        "<li id=\"fn-{id}\">{note} <a href=\"#fnref-{id}\" class=\"footnote-back\">↩</a></li>\n"
+      ));
+    }
+    out.push_str("</ol>\n</section>\n");
+  }
+
+  fn blob_url(&self, blob_id: &str) -> Option<String> {
+    blob_url(&self.options.blobs, blob_id)
+  }
+
+  fn record_unsupported(&self, block_id: &str, flavour: &str, fallback: ExportFallback) {
+    self.unsupported.borrow_mut().push(UnsupportedBlock {
+      block_id: block_id.to_string(),
+      flavour: flavour.to_string(),
+      fallback,
+    });
+  }
+}
+
+/// Returns `url` when it is relative or uses one of [`SAFE_SCHEMES`].
+fn safe_url(url: &str) -> Option<&str> {
+  let url = url.trim();
+  let scheme = url
+    .split_once(':')
+    .map(|(scheme, _)| scheme)
+    .filter(|scheme| !scheme.contains(['/', '?', '#']));
+  match scheme {
+    None => Some(url),
+    Some(scheme) if SAFE_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()) => Some(url),
+    Some(_) => None,
+  }
+}
+
+/// Reads the cells of a table block, rows and columns sorted by their
+/// `order`.
+pub(crate) fn table_rows(block: &Map) -> Vec<Vec<String>> {
+  let ordered = |prefix: &str| {
+    let mut ids = block
+      .keys()
+      .filter_map(|key| {
+        let id = key.strip_prefix(prefix)?.strip_suffix(".order")?;
+        let order = block.get(key).and_then(|value| value_to_string(&value))?;
+        Some((order, id.to_string()))
+      })
+      .collect::<Vec<_>>();
+    ids.sort();
+    ids.into_iter().map(|(_, id)| id).collect::<Vec<_>>()
+  };
+
+  let columns = ordered("prop:columns.");
+  ordered("prop:rows.")
+    .into_iter()
+    .map(|row| {
+      columns
+        .iter()
+        .map(|column| {
+          block
+            .get(&format!("prop:cells.{row}:{column}.text"))
+            .and_then(|value| value_to_string(&value))
+            .unwrap_or_default()
+        })
+        .collect()
+    })
+    .collect()
+}
+
+fn push_table(out: &mut String, header: Option<&[String]>, rows: &[Vec<String>]) {
+  let cell = |s: &str| escape_html(s).replace('\n', "<br>");
+  out.push_str("<table>\n");
+  if let Some(header) = header {
+    out.push_str("<thead><tr>");
+    for name in header {
+      out.push_str(&format!("<th>{}</th>", cell(name)));
+    }
+    out.push_str("</tr></thead>\n");
+  }
+  out.push_str("<tbody>\n");
+  for row in rows {
+    out.push_str("<tr>");
+    for value in row {
+      out.push_str(&format!("<td>{}</td>", cell(value)));
+    }
+    out.push_str("</tr>\n");
+  }
+  out.push_str("</tbody>\n</table>\n");
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{test_utils::TestDoc, BlobHandling};
+
+  #[test]
+  fn test_parse_doc_to_html() {
+    let mut doc = TestDoc::new("Guide");
+    doc.add_block(
+      "note",
+      "h1",
+      "affine:paragraph",
+      &[("type", "h1"), ("text", "Setup & <Run>")],
+    );
+    doc.add_block(
+      "note",
+      "l1",
+      "affine:list",
+      &[("type", "bulleted"), ("text", "one")],
+    );
+    doc.add_block(
+      "l1",
+      "l2",
+      "affine:list",
+      &[("type", "numbered"), ("text", "nested")],
+    );
+    doc.add_block(
+      "note",
+      "l3",
+      "affine:list",
+      &[("type", "bulleted"), ("text", "two")],
+    );
+    doc.add_block("note", "c1", "affine:callout", &[("emoji", "💡")]);
+    doc.add_block(
+      "c1",
+      "p1",
+      "affine:paragraph",
+      &[("text", "see {{ref:setup--run}}")],
+    );
+    doc.add_block(
+      "note",
+      "code",
+      "affine:code",
+      &[("language", "rust"), ("text", "a < b")],
+    );
+    doc.add_block(
+      "note",
+      "b1",
+      "affine:bookmark",
+      &[("url", "javascript:alert(1)")],
+    );
+    doc.add_block("note", "i1", "affine:image", &[("sourceId", "blob")]);
+
+    let result = parse_doc_to_html(
+      doc.encode(),
+      TestDoc::DOC_ID.into(),
+      &MarkdownOptions {
+        blobs: BlobHandling::Reference,
+        numbered_headings: true,
+        ..Default::default()
+      },
+    )
+    .unwrap();
+
+    assert_eq!(result.title, "Guide");
+    assert_eq!(
+      result.html,
+      [
+        "<h1 id=\"setup--run\">1. Setup &amp; &lt;Run&gt;</h1>",
+        "<ul>",
+        "<li>one",
+        "<ol>",
+        "<li>nested</li>",
+        "</ol>",
+        "</li>",
+        "<li>two</li>",
+        "</ul>",
+        "<aside class=\"callout\">",
+        "<span class=\"callout-emoji\">💡</span>",
+        "<p>see <a href=\"#setup--run\">Section 1</a></p>",
+        "</aside>",
+        "<pre><code class=\"language-rust\">a &lt; b</code></pre>",
+        "<figure><img src=\"blob://blob\" alt=\"\"></figure>",
+        "",
+      ]
+      .join("\n")
+    );
+    let unsupported = result
+      .unsupported
+      .iter()
+      .map(|block| block.block_id.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(unsupported, vec!["b1"]);
+  }
+}
@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::{parse_doc_from_binary, BlockInfo, ParseError};
+
+/// A block as stored in the search index, keyed by `{doc_id}:{block_id}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexRecord {
+  pub doc_id: String,
+  pub block_id: String,
+  pub flavour: String,
+  /// Text content of the block, lines joined with `\n`.
+  pub content: String,
+  pub blob: Vec<String>,
+  pub ref_doc_id: Vec<String>,
+  pub ref_info: Vec<String>,
+  pub parent_flavour: Option<String>,
+  pub parent_block_id: Option<String>,
+  pub additional: Option<String>,
+}
+
+impl IndexRecord {
+  fn new(doc_id: &str, block: BlockInfo) -> Self {
+    Self {
+      doc_id: doc_id.to_string(),
+      block_id: block.block_id,
+      flavour: block.flavour,
+      content: block.content.unwrap_or_default().join("\n"),
+      blob: block.blob.unwrap_or_default(),
+      ref_doc_id: block.ref_doc_id.unwrap_or_default(),
+      ref_info: block.ref_info.unwrap_or_default(),
+      parent_flavour: block.parent_flavour,
+      parent_block_id: block.parent_block_id,
+      additional: block.additional,
+    }
+  }
+
+  pub fn id(&self) -> String {
+    format!("{}:{}", self.doc_id, self.block_id)
+  }
+
+  /// Fingerprint of the record, stable across releases so it can be
+  /// persisted next to the index.
+  fn hash(&self) -> String {
+    let json = serde_json::to_string(self).unwrap_or_default();
+    // 64-bit FNV-1a
+    let hash = json.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+      (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexOp {
+  Add,
+  Update,
+  Delete,
+}
+
+impl IndexOp {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      IndexOp::Add => "add",
+      IndexOp::Update => "update",
+      IndexOp::Delete => "delete",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexDeltaRecord {
+  pub op: IndexOp,
+  pub id: String,
+  /// The record to store, `None` for deletions.
+  pub record: Option<IndexRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexDelta {
+  /// Changes to apply, additions and updates in document order followed by
+  /// deletions sorted by id.
+  pub records: Vec<IndexDeltaRecord>,
+  /// Record hashes of the doc by block id, to pass to the next call.
+  pub hashes: BTreeMap<String, String>,
+}
+
+impl IndexDelta {
+  /// Renders the delta as the newline delimited body of an Elasticsearch
+  /// `_bulk` request against `index`.
+  pub fn to_bulk_ndjson(&self, index: &str) -> String {
+    let mut out = String::new();
+    for delta in &self.records {
+      let action = match delta.op {
+        IndexOp::Add | IndexOp::Update => "index",
+        IndexOp::Delete => "delete",
+      };
+      let header = serde_json::json!({ action: { "_index": index, "_id": delta.id } });
+      out.push_str(&header.to_string());
+      out.push('\n');
+      if let Some(record) = &delta.record {
+        out.push_str(&serde_json::to_string(record).unwrap_or_default());
+        out.push('\n');
+      }
+    }
+    out
+  }
+}
+
+/// Compares the blocks of a doc with the record hashes of its previous
+/// indexing, keyed by block id, and returns only what changed.
+///
+/// Pass an empty `prev_hashes` to index a doc for the first time, and the
+/// returned `hashes` on the next call.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::collections::HashMap;
+///
+/// use affine_common::doc_parser::build_index_delta;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let delta = build_index_delta(&HashMap::new(), doc_bin, "doc-id".into()).unwrap();
+/// println!("{}", delta.to_bulk_ndjson("blocks"));
+/// ```
+pub fn build_index_delta(
+  prev_hashes: &HashMap<String, String>,
+  doc_bin: Vec<u8>,
+  doc_id: String,
+) -> Result<IndexDelta, ParseError> {
+  let result = parse_doc_from_binary(doc_bin, doc_id.clone())?;
+
+  let mut records = Vec::new();
+  let mut hashes = BTreeMap::new();
+  for block in result.blocks {
+    let record = IndexRecord::new(&doc_id, block);
+    let hash = record.hash();
+    let op = match prev_hashes.get(&record.block_id) {
+      None => Some(IndexOp::Add),
+      Some(prev) if *prev != hash => Some(IndexOp::Update),
+      Some(_) => None,
+    };
+    hashes.insert(record.block_id.clone(), hash);
+    if let Some(op) = op {
+      records.push(IndexDeltaRecord {
+        op,
+        id: record.id(),
+        record: Some(record),
+      });
+    }
+  }
+
+  let mut deleted = prev_hashes
+    .keys()
+    .filter(|block_id| !hashes.contains_key(*block_id))
+    .collect::<Vec<_>>();
+  deleted.sort();
+  records.extend(deleted.into_iter().map(|block_id| IndexDeltaRecord {
+    op: IndexOp::Delete,
+    id: format!("{doc_id}:{block_id}"),
+    record: None,
+  }));
+
+  Ok(IndexDelta { records, hashes })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  fn ops(delta: &IndexDelta) -> Vec<(IndexOp, &str)> {
+    delta
+      .records
+      .iter()
+      .map(|delta| (delta.op, delta.id.as_str()))
+      .collect()
+  }
+
+  #[test]
+  fn test_build_index_delta() {
+    let mut doc = TestDoc::new("Delta");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "one")]);
+    doc.add_block("note", "p2", "affine:paragraph", &[("text", "two")]);
+    let first = build_index_delta(&HashMap::new(), doc.encode(), TestDoc::DOC_ID.into()).unwrap();
+    assert!(first
+      .records
+      .iter()
+      .all(|delta| delta.op == IndexOp::Add && delta.record.is_some()));
+
+    let prev = first.hashes.clone().into_iter().collect::<HashMap<_, _>>();
+    let unchanged = build_index_delta(&prev, doc.encode(), TestDoc::DOC_ID.into()).unwrap();
+    assert!(unchanged.records.is_empty());
+    assert_eq!(unchanged.hashes, first.hashes);
+
+    let mut doc = TestDoc::new("Delta");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "one!")]);
+    let delta = build_index_delta(&prev, doc.encode(), TestDoc::DOC_ID.into()).unwrap();
+    let id = |block_id: &str| format!("{}:{}", TestDoc::DOC_ID, block_id);
+    assert_eq!(
+      ops(&delta),
+      vec![
+        (IndexOp::Update, id("p1").as_str()),
+        (IndexOp::Delete, id("p2").as_str()),
+      ]
+    );
+
+    let bulk = delta.to_bulk_ndjson("blocks");
+    let lines = bulk.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with(r#"{"index":"#));
+    assert!(lines[1].contains(r#""content":"one!""#));
+    assert!(lines[2].starts_with(r#"{"delete":"#));
+  }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{BlockInfo, CrawlResult};
+
+/// A crawl result whose strings are indices into the table of a
+/// [`StringInterner`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InternedCrawlResult {
+  pub blocks: Vec<InternedBlockInfo>,
+  pub title: u32,
+  pub summary: u32,
+}
+
+/// A [`BlockInfo`] whose strings are indices into the table of a
+/// [`StringInterner`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InternedBlockInfo {
+  pub block_id: u32,
+  pub flavour: u32,
+  pub content: Option<Vec<u32>>,
+  pub blob: Option<Vec<u32>>,
+  pub ref_doc_id: Option<Vec<u32>>,
+  pub ref_info: Option<Vec<u32>>,
+  pub parent_flavour: Option<u32>,
+  pub parent_block_id: Option<u32>,
+  pub additional: Option<u32>,
+  pub checksum: Option<u32>,
+}
+
+/// Unique strings shared by the crawl results of a batch, in the order they
+/// were first seen.
+///
+/// Flavours, extras and doc ids repeat across every doc of a workspace; an
+/// interned batch carries each of them once instead of once per block.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{parse_doc_from_binary, StringInterner};
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let result = parse_doc_from_binary(doc_bin, "doc-id".into()).unwrap();
+///
+/// let mut interner = StringInterner::default();
+/// let interned = interner.intern_crawl_result(&result);
+/// let strings = interner.into_strings();
+/// assert_eq!(interned.resolve(&strings).unwrap().title, result.title);
+/// ```
+#[derive(Debug, Default)]
+pub struct StringInterner {
+  indices: HashMap<String, u32>,
+  strings: Vec<String>,
+}
+
+impl StringInterner {
+  /// Returns the index of `value` in the table, adding it when it is new.
+  pub fn intern(&mut self, value: &str) -> u32 {
+    if let Some(index) = self.indices.get(value) {
+      return *index;
+    }
+    let index = self.strings.len() as u32;
+    self.indices.insert(value.to_string(), index);
+    self.strings.push(value.to_string());
+    index
+  }
+
+  pub fn intern_crawl_result(&mut self, result: &CrawlResult) -> InternedCrawlResult {
+    InternedCrawlResult {
+      blocks: result
+        .blocks
+        .iter()
+        .map(|block| self.intern_block(block))
+        .collect(),
+      title: self.intern(&result.title),
+      summary: self.intern(&result.summary),
+    }
+  }
+
+  fn intern_block(&mut self, block: &BlockInfo) -> InternedBlockInfo {
+    let mut list = |items: &Option<Vec<String>>| {
+      items
+        .as_ref()
+        .map(|items| items.iter().map(|item| self.intern(item)).collect())
+    };
+    let content = list(&block.content);
+    let blob = list(&block.blob);
+    let ref_doc_id = list(&block.ref_doc_id);
+    let ref_info = list(&block.ref_info);
+
+    InternedBlockInfo {
+      block_id: self.intern(&block.block_id),
+      flavour: self.intern(&block.flavour),
+      content,
+      blob,
+      ref_doc_id,
+      ref_info,
+      parent_flavour: block
+        .parent_flavour
+        .as_deref()
+        .map(|value| self.intern(value)),
+      parent_block_id: block
+        .parent_block_id
+        .as_deref()
+        .map(|value| self.intern(value)),
+      additional: block.additional.as_deref().map(|value| self.intern(value)),
+      checksum: block.checksum.as_deref().map(|value| self.intern(value)),
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.strings.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.strings.is_empty()
+  }
+
+  /// The table, indexed by the values [`StringInterner::intern`] returned.
+  pub fn into_strings(self) -> Vec<String> {
+    self.strings
+  }
+}
+
+impl InternedCrawlResult {
+  /// Turns the indices back into strings, `None` when one is out of
+  /// `strings`.
+  pub fn resolve(&self, strings: &[String]) -> Option<CrawlResult> {
+    let string = |index: &u32| strings.get(*index as usize).cloned();
+    // an absent field resolves to `Some(None)`, an unresolvable one to `None`
+    let optional = |index: &Option<u32>| match index {
+      Some(index) => string(index).map(Some),
+      None => Some(None),
+    };
+    let list = |items: &Option<Vec<u32>>| match items {
+      Some(items) => items
+        .iter()
+        .map(string)
+        .collect::<Option<Vec<_>>>()
+        .map(Some),
+      None => Some(None),
+    };
+
+    let blocks = self
+      .blocks
+      .iter()
+      .map(|block| {
+        Some(BlockInfo {
+          block_id: string(&block.block_id)?,
+          flavour: string(&block.flavour)?,
+          content: list(&block.content)?,
+          blob: list(&block.blob)?,
+          ref_doc_id: list(&block.ref_doc_id)?,
+          ref_info: list(&block.ref_info)?,
+          parent_flavour: optional(&block.parent_flavour)?,
+          parent_block_id: optional(&block.parent_block_id)?,
+          additional: optional(&block.additional)?,
+          checksum: optional(&block.checksum)?,
+        })
+      })
+      .collect::<Option<Vec<_>>>()?;
+
+    Some(CrawlResult {
+      blocks,
+      title: string(&self.title)?,
+      summary: string(&self.summary)?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::parse_doc_from_binary;
+
+  #[test]
+  fn test_intern_crawl_results() {
+    let doc_bin = include_bytes!("../../fixtures/demo.ydoc").to_vec();
+    let result = parse_doc_from_binary(doc_bin, "dYpV7PPhk8amRkY5IAcVO".into()).unwrap();
+
+    let mut interner = StringInterner::default();
+    let first = interner.intern_crawl_result(&result);
+    let unique = interner.len();
+    // a second copy of the doc adds no strings
+    let second = interner.intern_crawl_result(&result);
+    assert_eq!(interner.len(), unique);
+    assert_eq!(first, second);
+
+    let flavours = first
+      .blocks
+      .iter()
+      .map(|block| block.flavour)
+      .collect::<std::collections::HashSet<_>>();
+    assert!(flavours.len() < first.blocks.len());
+
+    let strings = interner.into_strings();
+    assert_eq!(
+      serde_json::to_value(first.resolve(&strings).unwrap()).unwrap(),
+      serde_json::to_value(&result).unwrap()
+    );
+    assert!(first.resolve(&strings[..1]).is_none());
+  }
+}
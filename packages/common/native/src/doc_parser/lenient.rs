@@ -0,0 +1,296 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use y_octo::{Map, Update};
+
+use super::{
+  collect_child_ids, crawl_blocks, find_root_block_id, get_block_id, get_flavour, new_doc,
+  CrawlOptions, CrawlResult, ParseError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoverableErrorKind {
+  /// The binary is damaged; structs after the damage and deletions were
+  /// dropped.
+  Decode,
+  /// Applying the decoded structs failed part way.
+  Integrate,
+  /// A block without an id or a flavour, skipped.
+  MalformedBlock,
+  /// The doc has no `affine:page` block, its blocks are crawled as detached
+  /// subtrees and the title is lost.
+  MissingRoot,
+  /// A block lists a child that is not in the doc.
+  DanglingChild,
+  /// A subtree not reachable from the root, crawled after it.
+  DetachedBlock,
+}
+
+impl RecoverableErrorKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      RecoverableErrorKind::Decode => "decode",
+      RecoverableErrorKind::Integrate => "integrate",
+      RecoverableErrorKind::MalformedBlock => "malformed_block",
+      RecoverableErrorKind::MissingRoot => "missing_root",
+      RecoverableErrorKind::DanglingChild => "dangling_child",
+      RecoverableErrorKind::DetachedBlock => "detached_block",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoverableError {
+  pub kind: RecoverableErrorKind,
+  /// The block the error is about, if any.
+  pub block_id: Option<String>,
+  pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LenientCrawlResult {
+  /// The salvaged blocks, crawled like [`super::parse_doc_from_binary`] does.
+  pub result: CrawlResult,
+  /// Empty when the doc is intact.
+  pub errors: Vec<RecoverableError>,
+}
+
+/// Same as [`super::parse_doc_from_binary`], salvaging what a damaged doc
+/// still holds instead of failing on the first problem.
+///
+/// The binary is decoded up to the first damaged struct and applied as far
+/// as it goes. Blocks are crawled from the root, then every subtree that is
+/// no longer reachable from it, in block id order. What was skipped or
+/// repaired along the way is listed in [`LenientCrawlResult::errors`].
+///
+/// Fails only when nothing can be salvaged: with `ParseError::InvalidBinary`
+/// when no struct decodes and `ParseError::ParserError` when the doc has no
+/// blocks.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::parse_doc_lenient;
+///
+/// let doc_bin = std::fs::read("fixtures/damaged.ydoc").unwrap();
+/// let recovered = parse_doc_lenient(doc_bin, "doc-id".into()).unwrap();
+/// for error in &recovered.errors {
+///   eprintln!("{}: {}", error.kind.as_str(), error.message);
+/// }
+/// println!("salvaged {} blocks", recovered.result.blocks.len());
+/// ```
+pub fn parse_doc_lenient(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+) -> Result<LenientCrawlResult, ParseError> {
+  if doc_bin.is_empty() || doc_bin == [0, 0] {
+    return Err(ParseError::InvalidBinary);
+  }
+
+  let mut errors = Vec::new();
+  let error = |kind, block_id: Option<&str>, message: String| RecoverableError {
+    kind,
+    block_id: block_id.map(str::to_string),
+    message,
+  };
+
+  let (update, decode_error) = Update::decode_v1_partial(&doc_bin);
+  if let Some(e) = decode_error {
+    if update.is_empty() {
+      return Err(ParseError::InvalidBinary);
+    }
+    errors.push(error(RecoverableErrorKind::Decode, None, e.to_string()));
+  }
+  let mut doc = new_doc(Some(&doc_id));
+  if let Err(e) = doc.apply_update(update) {
+    errors.push(error(RecoverableErrorKind::Integrate, None, e.to_string()));
+  }
+
+  let blocks_map = doc.get_map("blocks")?;
+  if blocks_map.is_empty() {
+    return Err(ParseError::ParserError("blocks map is empty".into()));
+  }
+
+  let mut block_pool: HashMap<String, Map> = HashMap::new();
+  let mut parent_lookup: HashMap<String, String> = HashMap::new();
+  let mut malformed = BTreeSet::new();
+  for (key, value) in blocks_map.iter() {
+    match value.to_map() {
+      Some(block_map) if get_flavour(&block_map).is_some() => match get_block_id(&block_map) {
+        Some(block_id) => {
+          for child_id in collect_child_ids(&block_map) {
+            parent_lookup.insert(child_id, block_id.clone());
+          }
+          block_pool.insert(block_id, block_map);
+        }
+        None => {
+          malformed.insert(key.to_string());
+        }
+      },
+      _ => {
+        malformed.insert(key.to_string());
+      }
+    }
+  }
+  for block_id in malformed {
+    errors.push(error(
+      RecoverableErrorKind::MalformedBlock,
+      Some(&block_id),
+      "block has no id or flavour".into(),
+    ));
+  }
+
+  let mut dangling = block_pool
+    .iter()
+    .flat_map(|(block_id, block)| {
+      collect_child_ids(block)
+        .into_iter()
+        .filter(|child_id| !block_pool.contains_key(child_id))
+        .map(move |child_id| (block_id.clone(), child_id))
+    })
+    .collect::<Vec<_>>();
+  dangling.sort();
+  for (block_id, child_id) in dangling {
+    errors.push(error(
+      RecoverableErrorKind::DanglingChild,
+      Some(&block_id),
+      format!("child {child_id} is missing"),
+    ));
+  }
+
+  let mut roots = Vec::new();
+  let mut reached = HashSet::new();
+  let reach = |root: &str, reached: &mut HashSet<String>| {
+    let mut stack = vec![root.to_string()];
+    while let Some(block_id) = stack.pop() {
+      let Some(block) = block_pool.get(&block_id) else {
+        continue;
+      };
+      if reached.insert(block_id) {
+        stack.extend(collect_child_ids(block));
+      }
+    }
+  };
+  match find_root_block_id(&block_pool) {
+    Some(root_block_id) => {
+      reach(&root_block_id, &mut reached);
+      roots.push(root_block_id);
+    }
+    None => errors.push(error(
+      RecoverableErrorKind::MissingRoot,
+      None,
+      "root block not found".into(),
+    )),
+  }
+
+  let mut unreached = block_pool
+    .keys()
+    .filter(|block_id| !reached.contains(*block_id))
+    .cloned()
+    .collect::<Vec<_>>();
+  // blocks without a parent first, so a subtree is crawled from its top;
+  // blocks left after them sit in parent cycles
+  unreached.sort_by_key(|block_id| (parent_lookup.contains_key(block_id), block_id.clone()));
+  for block_id in unreached {
+    if reached.contains(&block_id) {
+      continue;
+    }
+    reach(&block_id, &mut reached);
+    errors.push(error(
+      RecoverableErrorKind::DetachedBlock,
+      Some(&block_id),
+      "subtree is not reachable from the root".into(),
+    ));
+    roots.push(block_id);
+  }
+
+  let result = crawl_blocks(
+    &block_pool,
+    &parent_lookup,
+    &roots,
+    &CrawlOptions::default(),
+  );
+  Ok(LenientCrawlResult { result, errors })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_from_binary, test_utils::TestDoc};
+
+  fn block_ids(result: &LenientCrawlResult) -> Vec<&str> {
+    result
+      .result
+      .blocks
+      .iter()
+      .map(|block| block.block_id.as_str())
+      .collect()
+  }
+
+  #[test]
+  fn test_parse_doc_lenient() {
+    let mut doc = TestDoc::new("Damaged");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "kept")]);
+    doc.add_block("note", "lost", "affine:paragraph", &[("text", "lost")]);
+    doc.add_block(
+      "lost",
+      "lost-child",
+      "affine:paragraph",
+      &[("text", "found")],
+    );
+    doc.remove_block("note", "lost");
+    doc.add_reference("p1", "gone");
+    let doc_bin = doc.encode();
+
+    let recovered = parse_doc_lenient(doc_bin.clone(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(recovered.result.title, "Damaged");
+    // p1 is kept without its missing child, notes are never crawled as blocks
+    assert_eq!(block_ids(&recovered), ["page", "p1", "lost-child"]);
+    assert_eq!(
+      recovered
+        .errors
+        .iter()
+        .map(|error| (error.kind, error.block_id.as_deref()))
+        .collect::<Vec<_>>(),
+      [
+        (RecoverableErrorKind::DanglingChild, Some("p1")),
+        (RecoverableErrorKind::DetachedBlock, Some("lost-child")),
+      ]
+    );
+
+    // an intact doc crawls like the strict parser
+    let mut doc = TestDoc::new("Intact");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "kept")]);
+    doc.add_block(
+      "note",
+      "tail",
+      "affine:paragraph",
+      &[("text", "last words")],
+    );
+    let doc_bin = doc.encode();
+    let recovered = parse_doc_lenient(doc_bin.clone(), TestDoc::DOC_ID.into()).unwrap();
+    assert!(recovered.errors.is_empty());
+    assert_eq!(
+      block_ids(&recovered),
+      parse_doc_from_binary(doc_bin.clone(), TestDoc::DOC_ID.into())
+        .unwrap()
+        .blocks
+        .iter()
+        .map(|block| block.block_id.as_str())
+        .collect::<Vec<_>>()
+    );
+
+    // the last structs are cut off
+    let damaged = doc_bin[..doc_bin.len() - 8].to_vec();
+    assert!(parse_doc_from_binary(damaged.clone(), TestDoc::DOC_ID.into()).is_err());
+    let recovered = parse_doc_lenient(damaged, TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(recovered.errors[0].kind, RecoverableErrorKind::Decode);
+    assert!(block_ids(&recovered).starts_with(&["page", "p1"]));
+
+    assert!(matches!(
+      parse_doc_lenient(vec![0xff; 4], TestDoc::DOC_ID.into()),
+      Err(ParseError::InvalidBinary)
+    ));
+  }
+}
@@ -0,0 +1,207 @@
+use std::collections::{BTreeSet, HashSet};
+
+use serde::{Deserialize, Serialize};
+use y_octo::Any;
+
+use super::{block_index::BlockIndex, get_string, ParseError, NOTE_FLAVOUR};
+
+const LIST_FLAVOUR: &str = "affine:list";
+const LIST_TYPES: [&str; 4] = ["bulleted", "numbered", "todo", "toggle"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizedLists {
+  /// Update to apply to the doc, empty of changes when nothing was broken.
+  pub update: Vec<u8>,
+  /// Ids of the list items that were changed, sorted.
+  pub fixed_block_ids: Vec<String>,
+}
+
+/// Repairs the list structures of a doc and returns the changes as an update.
+///
+/// - list items referenced by several parents, or by one of their own
+///   descendants, stay with the first parent in document order
+/// - list items no block refers to are appended to the last note
+/// - missing or unknown list types become `bulleted`
+/// - runs of adjacent numbered items are renumbered from the first item's
+///   order
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::normalize_lists;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let normalized = normalize_lists(doc_bin, "doc-id".into()).unwrap();
+/// println!("fixed {} list items", normalized.fixed_block_ids.len());
+/// ```
+pub fn normalize_lists(doc_bin: Vec<u8>, doc_id: String) -> Result<NormalizedLists, ParseError> {
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(NormalizedLists {
+      update: vec![],
+      fixed_block_ids: vec![],
+    });
+  };
+  let state_vector = index.doc().get_state_vector();
+  let is_list = |block_id: &str| index.flavour_of(block_id).as_deref() == Some(LIST_FLAVOUR);
+  let mut fixed = BTreeSet::new();
+
+  let parents = index.descendants(&index.root_block_id);
+
+  let mut seen = HashSet::from([index.root_block_id.clone()]);
+  for parent_id in &parents {
+    let child_ids = index.children_of(parent_id);
+    let duplicates = child_ids
+      .iter()
+      .enumerate()
+      .filter(|(_, child_id)| !seen.insert(child_id.to_string()) && is_list(child_id))
+      .map(|(position, _)| position)
+      .collect::<Vec<_>>();
+    if duplicates.is_empty() {
+      continue;
+    }
+    let Some(mut children) = index
+      .get(parent_id)
+      .and_then(|parent| parent.get("sys:children"))
+      .and_then(|value| value.to_array())
+    else {
+      continue;
+    };
+    for position in duplicates.into_iter().rev() {
+      fixed.insert(child_ids[position].clone());
+      children.remove(position as u64, 1)?;
+    }
+  }
+
+  let mut orphans = index
+    .block_pool
+    .keys()
+    .filter(|block_id| is_list(block_id) && index.parent_of(block_id).is_none())
+    .cloned()
+    .collect::<Vec<_>>();
+  orphans.sort();
+  let last_note = parents
+    .iter()
+    .rev()
+    .find(|block_id| index.flavour_of(block_id).as_deref() == Some(NOTE_FLAVOUR))
+    .and_then(|note_id| index.get(note_id))
+    .and_then(|note| note.get("sys:children"))
+    .and_then(|value| value.to_array());
+  if let Some(mut children) = last_note {
+    for orphan_id in orphans {
+      children.push(orphan_id.as_str())?;
+      fixed.insert(orphan_id);
+    }
+  }
+
+  for (block_id, block) in &index.block_pool {
+    if !is_list(block_id) {
+      continue;
+    }
+    let type_ = get_string(block, "prop:type");
+    if !type_
+      .as_deref()
+      .is_some_and(|type_| LIST_TYPES.contains(&type_))
+    {
+      block.clone().insert("prop:type".into(), "bulleted")?;
+      fixed.insert(block_id.clone());
+    }
+  }
+
+  // children are re-read so removed duplicates don't split numbered runs
+  for parent_id in &parents {
+    let mut next_order: Option<i32> = None;
+    for child_id in index.children_of(parent_id) {
+      let Some(block) = index.get(&child_id).filter(|_| is_list(&child_id)) else {
+        next_order = None;
+        continue;
+      };
+      if get_string(block, "prop:type").as_deref() != Some("numbered") {
+        next_order = None;
+        continue;
+      }
+      let order = get_string(block, "prop:order").and_then(|order| order.parse::<i32>().ok());
+      let expected = next_order.unwrap_or_else(|| order.filter(|order| *order > 0).unwrap_or(1));
+      if order != Some(expected) {
+        block
+          .clone()
+          .insert("prop:order".into(), Any::Integer(expected))?;
+        fixed.insert(child_id);
+      }
+      next_order = Some(expected + 1);
+    }
+  }
+
+  Ok(NormalizedLists {
+    update: index.doc().encode_state_as_update_v1(&state_vector)?,
+    fixed_block_ids: fixed.into_iter().collect(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_to_markdown, test_utils::TestDoc};
+
+  #[test]
+  fn test_normalize_lists() {
+    let mut doc = TestDoc::new("Lists");
+    doc.add_block(
+      "note",
+      "n1",
+      LIST_FLAVOUR,
+      &[("type", "numbered"), ("text", "one")],
+    );
+    doc.add_block(
+      "note",
+      "n2",
+      LIST_FLAVOUR,
+      &[("type", "numbered"), ("text", "two")],
+    );
+    doc.add_block(
+      "n2",
+      "x",
+      LIST_FLAVOUR,
+      &[("type", "weird"), ("text", "nested")],
+    );
+    // `n1` listed again under its sibling's child
+    doc.add_reference("x", "n1");
+    doc.add_block(
+      "note",
+      "n3",
+      LIST_FLAVOUR,
+      &[("type", "numbered"), ("text", "three")],
+    );
+    let doc_bin = doc.encode();
+
+    // the exporter follows the first reference and keeps list depths stable
+    let markdown = parse_doc_to_markdown(doc_bin.clone(), TestDoc::DOC_ID.into(), false).unwrap();
+    assert_eq!(markdown.markdown, "- one\n- two\n    - nested\n- three\n");
+
+    let normalized = normalize_lists(doc_bin.clone(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(normalized.fixed_block_ids, vec!["n1", "n2", "n3", "x"]);
+
+    let mut fixed = y_octo::DocOptions::new()
+      .with_guid(TestDoc::DOC_ID.to_string())
+      .build();
+    fixed.apply_update_from_binary_v1(&doc_bin).unwrap();
+    fixed
+      .apply_update_from_binary_v1(&normalized.update)
+      .unwrap();
+    let fixed_bin = fixed.encode_update_v1().unwrap();
+
+    let index = BlockIndex::load(&fixed_bin, TestDoc::DOC_ID)
+      .unwrap()
+      .unwrap();
+    assert!(index.children_of("x").is_empty());
+    let block = |id: &str| index.get(id).unwrap().clone();
+    assert_eq!(
+      get_string(&block("x"), "prop:type").as_deref(),
+      Some("bulleted")
+    );
+    let orders = ["n1", "n2", "n3"].map(|id| get_string(&block(id), "prop:order"));
+    assert_eq!(orders, [1, 2, 3].map(|order: i32| Some(order.to_string())));
+
+    let again = normalize_lists(fixed_bin, TestDoc::DOC_ID.into()).unwrap();
+    assert!(again.fixed_block_ids.is_empty());
+  }
+}
@@ -0,0 +1,82 @@
+use std::{fs::File, io::ErrorKind, path::Path};
+
+use memmap2::Mmap;
+
+use super::{parse_doc_from_slice, CrawlOptions, CrawlResult, ParseError};
+
+/// Same as [`super::parse_doc_from_binary_with_options`], reading the doc
+/// from a file.
+///
+/// The file is memory-mapped instead of read, so large snapshots are paged
+/// in as the update is decoded and never copied into a buffer of their own.
+///
+/// Fails with `ParseError::DocNotFound` when there is no file at `path`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// use affine_common::doc_parser::{parse_doc_from_path, CrawlOptions};
+///
+/// let result = parse_doc_from_path(
+///   Path::new("fixtures/demo.ydoc"),
+///   "doc-id".into(),
+///   &CrawlOptions::default(),
+/// )
+/// .unwrap();
+/// println!("{}: {} blocks", result.title, result.blocks.len());
+/// ```
+pub fn parse_doc_from_path(
+  path: &Path,
+  doc_id: String,
+  options: &CrawlOptions,
+) -> Result<CrawlResult, ParseError> {
+  let io_error = |e: std::io::Error| match e.kind() {
+    ErrorKind::NotFound => ParseError::DocNotFound,
+    _ => ParseError::Unknown(format!("{}: {e}", path.display())),
+  };
+
+  let file = File::open(path).map_err(io_error)?;
+  // SAFETY: the map is only read while the doc is decoded. A file truncated
+  // or rewritten in place meanwhile can fail the parse or fault, which is why
+  // snapshots are only ever replaced by renaming a new file over them.
+  let map = unsafe { Mmap::map(&file) }.map_err(io_error)?;
+  parse_doc_from_slice(&map, doc_id, options)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::PathBuf;
+
+  use super::*;
+  use crate::doc_parser::parse_doc_from_binary;
+
+  #[test]
+  fn test_parse_doc_from_path() {
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+    let doc_id = "dYpV7PPhk8amRkY5IAcVO".to_string();
+
+    let mapped = parse_doc_from_path(
+      &fixtures.join("demo.ydoc"),
+      doc_id.clone(),
+      &CrawlOptions::default(),
+    )
+    .unwrap();
+    let read = parse_doc_from_binary(
+      include_bytes!("../../fixtures/demo.ydoc").to_vec(),
+      doc_id.clone(),
+    )
+    .unwrap();
+    assert_eq!(serde_json::json!(mapped), serde_json::json!(read));
+
+    assert!(matches!(
+      parse_doc_from_path(
+        &fixtures.join("missing.ydoc"),
+        doc_id,
+        &CrawlOptions::default()
+      ),
+      Err(ParseError::DocNotFound)
+    ));
+  }
+}
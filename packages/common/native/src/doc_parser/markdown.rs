@@ -0,0 +1,807 @@
+use std::{
+  cell::{OnceCell, RefCell},
+  collections::{HashMap, HashSet},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use y_octo::Map;
+
+use super::{
+  block_index::BlockIndex,
+  cross_ref::{contains_references, CrossReferenceTargets},
+  database::read_database,
+  footnote::{text_with_footnotes, Footnote, Footnotes},
+  gather_table_contents, get_flavour, get_string, is_builtin_flavour,
+  outline::outline,
+  BlockFilter, BlockSnapshot, ExportFallback, ExportMiddleware, FlavourHandler, FlavourRegistry,
+  GenericFlavourHandler, MarkdownResult, ParseError, UnsupportedBlock, NOTE_FLAVOUR, PAGE_FLAVOUR,
+};
+
+/// Markdown flavour to target. Only affects constructs the dialects disagree
+/// on, currently tables.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkdownDialect {
+  /// GitHub Flavored Markdown, pipe tables.
+  #[default]
+  Gfm,
+  /// Strict CommonMark, tables become raw HTML blocks.
+  CommonMark,
+}
+
+/// How image and attachment blocks reference their blobs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase", deny_unknown_fields)]
+pub enum BlobHandling {
+  /// Leave images and attachments out of the output.
+  #[default]
+  Omit,
+  /// Link to `blob://<blobId>` for the caller to resolve.
+  Reference,
+  /// Link to `template` with `{blobId}` substituted.
+  Url { template: String },
+}
+
+/// Options for [`parse_doc_to_markdown_with_options`].
+#[derive(Clone, Default)]
+pub struct MarkdownOptions<'a> {
+  /// Prefix top-level blocks with an HTML comment carrying their `block_id`
+  /// and `flavour` to aid downstream editing.
+  pub ai_editable: bool,
+  pub dialect: MarkdownDialect,
+  /// Prepend a YAML frontmatter block with the doc title and id.
+  pub frontmatter: bool,
+  /// Render linked and synced doc embeds as links to this template, with
+  /// `{docId}` substituted. Embeds are left out when unset.
+  pub doc_link_template: Option<String>,
+  pub blobs: BlobHandling,
+  /// Renders blocks of flavours the exporter does not know about.
+  pub handlers: Option<&'a FlavourRegistry<'a>>,
+  /// Transforms blocks before and output after rendering.
+  pub middleware: Option<&'a dyn ExportMiddleware>,
+  /// Blocks left out together with their descendants.
+  pub filter: Option<BlockFilter>,
+  /// Prefix headings with their hierarchical number, `1.`, `1.1`, ..., as
+  /// listed by [`super::extract_outline`].
+  pub numbered_headings: bool,
+}
+
+/// Converts a serialized document binary into a Markdown representation and a document title.
+///
+/// Parses the provided document binary, traverses its block structure, and renders supported
+/// block types (pages, paragraphs, lists, code blocks, tables, databases, and embeds) into
+/// a single Markdown string. When `ai_editable` is true, top-level child blocks include an
+/// HTML comment with their `block_id` and `flavour` to aid downstream editing.
+///
+/// # Returns
+///
+/// `Ok(MarkdownResult)` containing the document `title` and rendered `markdown` on success,
+/// `Err(ParseError)` when the binary is invalid or the document cannot be parsed.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::doc_parser::{parse_doc_to_markdown, ParseError};
+///
+/// // invalid/empty binary yields an error
+/// let res = parse_doc_to_markdown(vec![], "doc-id".into(), false);
+/// assert!(matches!(res, Err(ParseError::InvalidBinary)));
+/// ```
+pub fn parse_doc_to_markdown(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  ai_editable: bool,
+) -> Result<MarkdownResult, ParseError> {
+  parse_doc_to_markdown_with_options(
+    doc_bin,
+    doc_id,
+    &MarkdownOptions {
+      ai_editable,
+      ..Default::default()
+    },
+  )
+}
+
+/// Same as [`parse_doc_to_markdown`], with flavour handlers and export
+/// middleware taken from `options`.
+#[instrument(name = "markdown", skip_all, fields(doc_id = %doc_id), err)]
+pub fn parse_doc_to_markdown_with_options(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  options: &MarkdownOptions,
+) -> Result<MarkdownResult, ParseError> {
+  let index = match BlockIndex::load(&doc_bin, &doc_id)? {
+    Some(index) => index,
+    None => {
+      return Ok(MarkdownResult {
+        title: "".into(),
+        markdown: "".into(),
+        unsupported: vec![],
+      })
+    }
+  };
+
+  let root_block_id = index.root_block_id.clone();
+  render_markdown(&index, &doc_id, &root_block_id, options)
+}
+
+/// Renders only `block_id` and its descendants, e.g. the paragraph or list
+/// selected in the editor, without rendering the rest of the doc.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::parse_block_to_markdown;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let result = parse_block_to_markdown(doc_bin, "doc-id".into(), "block-id", false).unwrap();
+/// println!("{}", result.markdown);
+/// ```
+pub fn parse_block_to_markdown(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  block_id: &str,
+  ai_editable: bool,
+) -> Result<MarkdownResult, ParseError> {
+  parse_subtree_to_markdown_with_options(
+    doc_bin,
+    doc_id,
+    block_id,
+    &MarkdownOptions {
+      ai_editable,
+      ..Default::default()
+    },
+  )
+}
+
+/// Renders only `block_id` and its descendants. The title is still the one of
+/// the doc, and list depths are relative to `block_id`.
+///
+/// Fails with `ParseError::BlockNotFound` when the doc has no such block.
+#[instrument(name = "markdown", skip_all, fields(doc_id = %doc_id, block_id = %block_id), err)]
+pub fn parse_subtree_to_markdown_with_options(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  block_id: &str,
+  options: &MarkdownOptions,
+) -> Result<MarkdownResult, ParseError> {
+  let index = BlockIndex::load(&doc_bin, &doc_id)?
+    .filter(|index| index.get(block_id).is_some())
+    .ok_or_else(|| ParseError::BlockNotFound(block_id.to_string()))?;
+
+  render_markdown(&index, &doc_id, block_id, options)
+}
+
+fn render_markdown(
+  index: &BlockIndex,
+  doc_id: &str,
+  start_block_id: &str,
+  options: &MarkdownOptions,
+) -> Result<MarkdownResult, ParseError> {
+  let (doc_title, blocks, unsupported, footnotes) = render_blocks(index, start_block_id, options);
+  let mut markdown = blocks
+    .into_iter()
+    .map(|block| block.markdown)
+    .collect::<String>();
+
+  if !footnotes.is_empty() {
+    markdown.push('\n');
+    for footnote in footnotes {
+      let text = footnote.text().replace('\n', " ");
+      let href = footnote.href(options.doc_link_template.as_deref(), |blob_id| {
+        blob_url(&options.blobs, blob_id)
+      });
+      let note = match href {
+        Some(href) => format!("[{text}]({href})"),
+        None => text,
+      };
+      markdown.push_str(&format!("{}: {note}\n", footnote.marker()));
+    }
+  }
+
+  if options.frontmatter {
+    // JSON strings are valid YAML double-quoted scalars
+    let quote = |s: &str| serde_json::Value::String(s.to_string()).to_string();
+    markdown = format!(
+      "---\ntitle: {}\ndocId: {}\n---\n\n{}",
+      quote(&doc_title),
+      quote(doc_id),
+      markdown
+    );
+  }
+
+  Ok(MarkdownResult {
+    title: doc_title,
+    markdown,
+    unsupported,
+  })
+}
+
+/// The markdown of a single block, as produced by [`render_blocks`].
+pub(crate) struct RenderedBlock {
+  pub(crate) block_id: String,
+  pub(crate) markdown: String,
+}
+
+/// Renders `start_block_id` and its descendants in document order, returning
+/// the doc title, every block that produced output, the blocks that could
+/// not be represented faithfully and the footnotes referenced by the blocks.
+pub(crate) fn render_blocks(
+  index: &BlockIndex,
+  start_block_id: &str,
+  options: &MarkdownOptions,
+) -> (
+  String,
+  Vec<RenderedBlock>,
+  Vec<UnsupportedBlock>,
+  Vec<Footnote>,
+) {
+  // numbers are taken from the whole doc so a subtree export keeps them
+  let heading_numbers = if options.numbered_headings {
+    outline(index)
+      .into_iter()
+      .map(|entry| (entry.block_id, entry.number))
+      .collect()
+  } else {
+    HashMap::new()
+  };
+  let renderer = MarkdownRenderer {
+    index,
+    options,
+    heading_numbers,
+    references: OnceCell::new(),
+    footnotes: RefCell::default(),
+    unsupported: RefCell::default(),
+  };
+
+  // list depth is tracked along the traversal rather than by walking parent
+  // links, which can be ambiguous or cyclic in damaged docs; subtrees start
+  // flush at depth 0
+  let mut queue: Vec<(Option<String>, String, usize)> = vec![(
+    index.parent_of(start_block_id).cloned(),
+    start_block_id.to_string(),
+    0,
+  )];
+  let mut visited: HashSet<String> = HashSet::from([start_block_id.to_string()]);
+  let mut doc_title = String::from("Untitled");
+  if start_block_id != index.root_block_id {
+    if let Some(root) = index.get(&index.root_block_id) {
+      doc_title = get_string(root, "prop:title").unwrap_or_default();
+    }
+  }
+  let mut rendered = Vec::new();
+
+  while let Some((parent_block_id, block_id, list_depth)) = queue.pop() {
+    let block = match index.get(&block_id) {
+      Some(block) => block,
+      None => continue,
+    };
+
+    let flavour = match get_flavour(block) {
+      Some(flavour) => flavour,
+      None => continue,
+    };
+
+    let parent_flavour = index
+      .parent_of(&block_id)
+      .and_then(|id| index.flavour_of(id));
+
+    if parent_flavour.as_deref() == Some("affine:database") {
+      continue;
+    }
+
+    if options
+      .filter
+      .as_ref()
+      .is_some_and(|filter| filter.denies(block))
+    {
+      continue;
+    }
+
+    // enqueue children first to keep traversal order similar to JS implementation
+    let child_list_depth = if flavour == "affine:list" {
+      list_depth + 1
+    } else {
+      0
+    };
+    let mut child_ids = index.children_of(&block_id);
+    for child_id in child_ids.drain(..).rev() {
+      if visited.insert(child_id.clone()) {
+        queue.push((Some(block_id.clone()), child_id, child_list_depth));
+      }
+    }
+
+    if flavour == PAGE_FLAVOUR {
+      let title = get_string(block, "prop:title").unwrap_or_default();
+      doc_title = title.clone();
+      continue;
+    }
+
+    let snapshot = match options.middleware {
+      Some(middleware) => {
+        match BlockSnapshot::from_block(block, parent_block_id.as_ref())
+          .and_then(|snapshot| middleware.on_block(snapshot))
+        {
+          Some(snapshot) => Some(snapshot),
+          None => continue,
+        }
+      }
+      None => None,
+    };
+
+    let mut chunk = String::new();
+    renderer.render_block(
+      &mut chunk,
+      &block_id,
+      parent_block_id.as_ref(),
+      list_depth,
+      block,
+      snapshot.as_ref(),
+    );
+    if chunk.is_empty() {
+      continue;
+    }
+
+    let markdown = match options.middleware {
+      Some(middleware) => middleware.on_output(chunk),
+      None => chunk,
+    };
+    rendered.push(RenderedBlock { block_id, markdown });
+  }
+
+  (
+    doc_title,
+    rendered,
+    renderer.unsupported.into_inner(),
+    renderer.footnotes.into_inner().into_inner(),
+  )
+}
+
+struct MarkdownRenderer<'a> {
+  index: &'a BlockIndex,
+  options: &'a MarkdownOptions<'a>,
+  heading_numbers: HashMap<String, String>,
+  /// Built on the first block carrying a reference token.
+  references: OnceCell<CrossReferenceTargets>,
+  footnotes: RefCell<Footnotes>,
+  unsupported: RefCell<Vec<UnsupportedBlock>>,
+}
+
+impl MarkdownRenderer<'_> {
+  /// Renders a single block into `out`. Props are read from `snapshot` when
+  /// middleware produced one, and from the block itself otherwise.
+  fn render_block(
+    &self,
+    out: &mut String,
+    block_id: &str,
+    parent_block_id: Option<&String>,
+    list_depth: usize,
+    block: &Map,
+    snapshot: Option<&BlockSnapshot>,
+  ) {
+    let Some(flavour) = get_flavour(block) else {
+      return;
+    };
+    let flavour = flavour.as_str();
+
+    if flavour == "affine:database" {
+      if self.options.dialect == MarkdownDialect::CommonMark {
+        self.record_unsupported(block_id, flavour, ExportFallback::Html);
+      }
+      self.render_database(out, block);
+      return;
+    }
+
+    if flavour == "affine:table" {
+      self.record_unsupported(block_id, flavour, ExportFallback::PlainText);
+      let contents = gather_table_contents(block);
+      out.push_str(&contents.join("|"));
+      out.push('\n');
+      return;
+    }
+
+    if self.options.ai_editable
+      && parent_block_id.map(String::as_str) == Some(self.index.root_block_id.as_str())
+    {
+      out.push_str(&format!(
+        "<!-- block_id={} flavour={} -->\n",
+        block_id, flavour
+      ));
+    }
+
+    // footnotes only survive when the text is read from the block, as
+    // snapshots carry plain text
+    let text = || match snapshot {
+      Some(snapshot) => snapshot.prop_string("text"),
+      None => text_with_footnotes(block, "prop:text").map(|(text, footnotes)| {
+        let mut known = self.footnotes.borrow_mut();
+        for footnote in footnotes {
+          known.insert(footnote);
+        }
+        text
+      }),
+    };
+    let prop = |key: &str| match snapshot {
+      Some(snapshot) => snapshot.prop_string(key),
+      None => get_string(block, &format!("prop:{key}")),
+    };
+
+    match flavour {
+      "affine:paragraph" => {
+        if let Some(text) = text() {
+          let type_ = prop("type").unwrap_or_default();
+          let prefix = match type_.as_str() {
+            "h1" => "# ",
+            "h2" => "## ",
+            "h3" => "### ",
+            "h4" => "#### ",
+            "h5" => "##### ",
+            "h6" => "###### ",
+            "quote" => "> ",
+            _ => "",
+          };
+          out.push_str(prefix);
+          if let Some(number) = self.heading_numbers.get(block_id) {
+            out.push_str(number);
+            out.push(' ');
+          }
+          out.push_str(&self.resolve_references(text));
+          out.push('\n');
+        }
+      }
+      "affine:list" => {
+        if let Some(text) = text() {
+          let indent = "    ".repeat(list_depth);
+          out.push_str(&indent);
+          out.push_str("- ");
+          out.push_str(&self.resolve_references(text));
+          out.push('\n');
+        }
+      }
+      "affine:code" => {
+        if let Some(text) = text() {
+          let lang = prop("language").unwrap_or_default();
+          out.push_str("```");
+          out.push_str(&lang);
+          out.push('\n');
+          out.push_str(&text);
+          out.push_str("\n```\n");
+        }
+      }
+      "affine:embed-linked-doc" | "affine:embed-synced-doc" => {
+        let (Some(template), Some(page_id)) = (&self.options.doc_link_template, prop("pageId"))
+        else {
+          self.record_unsupported(block_id, flavour, ExportFallback::Omitted);
+          return;
+        };
+        if flavour == "affine:embed-synced-doc" {
+          self.record_unsupported(block_id, flavour, ExportFallback::Link);
+        }
+        let label = prop("title")
+          .filter(|title| !title.is_empty())
+          .unwrap_or_else(|| page_id.clone());
+        out.push_str(&format!(
+          "[{}]({})\n",
+          label,
+          template.replace("{docId}", &page_id)
+        ));
+      }
+      "affine:image" => match prop("sourceId").and_then(|id| self.blob_url(&id)) {
+        Some(url) => {
+          let caption = prop("caption").unwrap_or_default();
+          out.push_str(&format!("![{}]({})\n", caption, url));
+        }
+        None => self.record_unsupported(block_id, flavour, ExportFallback::Omitted),
+      },
+      "affine:attachment" => match prop("sourceId").and_then(|id| self.blob_url(&id)) {
+        Some(url) => {
+          let name = prop("name").unwrap_or_default();
+          out.push_str(&format!("[{}]({})\n", name, url));
+        }
+        None => self.record_unsupported(block_id, flavour, ExportFallback::Omitted),
+      },
+      // containers, their children are rendered on their own
+      NOTE_FLAVOUR | SURFACE_FLAVOUR => {}
+      _ => {
+        let registered = self
+          .options
+          .handlers
+          .and_then(|handlers| handlers.get(flavour));
+        // flavours nobody knows about, e.g. from newer clients, keep their
+        // text through the generic rules
+        let generic = registered.is_none() && !is_builtin_flavour(flavour);
+        let handler = match registered {
+          Some(handler) => Some(handler),
+          None if generic => Some(&GenericFlavourHandler as &dyn FlavourHandler),
+          None => None,
+        };
+        let rendered = handler.and_then(|handler| match snapshot {
+          Some(snapshot) => handler.markdown(snapshot),
+          None => BlockSnapshot::from_block(block, parent_block_id)
+            .and_then(|snapshot| handler.markdown(&snapshot)),
+        });
+        match rendered {
+          Some(rendered) => {
+            if generic {
+              self.record_unsupported(block_id, flavour, ExportFallback::Generic);
+            }
+            out.push_str(&rendered);
+            out.push('\n');
+          }
+          None => self.record_unsupported(block_id, flavour, ExportFallback::Omitted),
+        }
+      }
+    }
+  }
+
+  fn resolve_references(&self, text: String) -> String {
+    if !contains_references(&text) {
+      return text;
+    }
+    self
+      .references
+      .get_or_init(|| CrossReferenceTargets::new(self.index))
+      .render_markdown(self.index, &text, self.options.numbered_headings)
+  }
+
+  fn record_unsupported(&self, block_id: &str, flavour: &str, fallback: ExportFallback) {
+    self.unsupported.borrow_mut().push(UnsupportedBlock {
+      block_id: block_id.to_string(),
+      flavour: flavour.to_string(),
+      fallback,
+    });
+  }
+
+  fn render_database(&self, out: &mut String, block: &Map) {
+    let title = get_string(block, "prop:title").unwrap_or_default();
+    out.push_str(&format!("\n### {}\n", title));
+
+    let Some((header, rows)) = database_rows(self.index, block) else {
+      return;
+    };
+
+    match self.options.dialect {
+      MarkdownDialect::Gfm => {
+        let escape_table = |s: &str| s.replace('|', "\\|").replace('\n', "<br>");
+
+        out.push('|');
+        for name in &header {
+          out.push_str(&escape_table(name));
+          out.push('|');
+        }
+        out.push('\n');
+
+        out.push('|');
+        for _ in &header {
+          out.push_str("---|");
+        }
+        out.push('\n');
+
+        for row in rows {
+          out.push('|');
+          for cell in row {
+            out.push_str(&escape_table(&cell));
+            out.push('|');
+          }
+          out.push('\n');
+        }
+      }
+      MarkdownDialect::CommonMark => {
+        // CommonMark has no tables, fall back to an HTML block
+        let escape_cell = |s: &str| escape_html(s).replace('\n', "<br>");
+
+        out.push_str("<table>\n<tr>");
+        for name in &header {
+          out.push_str(&format!("<th>{}</th>", escape_cell(name)));
+        }
+        out.push_str("</tr>\n");
+        for row in rows {
+          out.push_str("<tr>");
+          for cell in row {
+            out.push_str(&format!("<td>{}</td>", escape_cell(&cell)));
+          }
+          out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+      }
+    }
+  }
+
+  fn blob_url(&self, blob_id: &str) -> Option<String> {
+    blob_url(&self.options.blobs, blob_id)
+  }
+}
+
+pub(crate) fn blob_url(blobs: &BlobHandling, blob_id: &str) -> Option<String> {
+  match blobs {
+    BlobHandling::Omit => None,
+    BlobHandling::Reference => Some(format!("blob://{blob_id}")),
+    BlobHandling::Url { template } => Some(template.replace("{blobId}", blob_id)),
+  }
+}
+
+const SURFACE_FLAVOUR: &str = "affine:surface";
+
+/// Resolves the column names and the display text of every cell of a
+/// database block, in column order.
+pub(crate) fn database_rows(
+  index: &BlockIndex,
+  block: &Map,
+) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+  block.get("prop:columns").and_then(|v| v.to_array())?;
+  block.get("prop:cells").and_then(|v| v.to_map())?;
+
+  let database = read_database(index, block);
+  let header = database
+    .columns
+    .into_iter()
+    .map(|column| column.name)
+    .collect();
+  let rows = database
+    .rows
+    .into_iter()
+    .map(|row| row.cells.into_iter().map(|cell| cell.text).collect())
+    .collect();
+  Some((header, rows))
+}
+
+pub(crate) fn escape_html(s: &str) -> String {
+  s.replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  struct StripSecrets;
+
+  impl ExportMiddleware for StripSecrets {
+    fn on_block(&self, mut block: BlockSnapshot) -> Option<BlockSnapshot> {
+      let text = block.prop_string("text")?;
+      if text.contains("secret") {
+        return None;
+      }
+      block
+        .props
+        .insert("text".into(), text.replace("http://", "https://").into());
+      Some(block)
+    }
+
+    fn on_output(&self, chunk: String) -> String {
+      format!("{}\n", chunk.trim_end())
+    }
+  }
+
+  #[test]
+  fn test_markdown_middleware() {
+    let mut doc = TestDoc::new("Hooks");
+    doc.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[("text", "see http://example.com")],
+    );
+    doc.add_block("note", "p2", "affine:paragraph", &[("text", "a secret")]);
+    doc.add_block("note", "l1", "affine:list", &[("text", "item")]);
+    let doc_bin = doc.encode();
+
+    let plain = parse_doc_to_markdown(doc_bin.clone(), TestDoc::DOC_ID.into(), false).unwrap();
+    assert_eq!(plain.markdown, "see http://example.com\na secret\n- item\n");
+
+    let hooked = parse_doc_to_markdown_with_options(
+      doc_bin,
+      TestDoc::DOC_ID.into(),
+      &MarkdownOptions {
+        middleware: Some(&StripSecrets),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(hooked.title, "Hooks");
+    assert_eq!(hooked.markdown, "see https://example.com\n- item\n");
+  }
+
+  #[test]
+  fn test_markdown_unsupported_blocks() {
+    let mut doc = TestDoc::new("Report");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "text")]);
+    doc.add_block("note", "i1", "affine:image", &[("sourceId", "blob")]);
+    doc.add_block("note", "d1", "affine:divider", &[]);
+    doc.add_block(
+      "note",
+      "s1",
+      "affine:embed-synced-doc",
+      &[("pageId", "other")],
+    );
+    let doc_bin = doc.encode();
+
+    let report = |result: MarkdownResult| {
+      result
+        .unsupported
+        .into_iter()
+        .map(|block| (block.block_id, block.fallback))
+        .collect::<Vec<_>>()
+    };
+
+    let plain = parse_doc_to_markdown(doc_bin.clone(), TestDoc::DOC_ID.into(), false).unwrap();
+    assert_eq!(
+      report(plain),
+      vec![
+        ("i1".to_string(), ExportFallback::Omitted),
+        ("d1".to_string(), ExportFallback::Omitted),
+        ("s1".to_string(), ExportFallback::Omitted),
+      ]
+    );
+
+    let linked = parse_doc_to_markdown_with_options(
+      doc_bin,
+      TestDoc::DOC_ID.into(),
+      &MarkdownOptions {
+        blobs: BlobHandling::Reference,
+        doc_link_template: Some("/doc/{docId}".into()),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(
+      report(linked),
+      vec![
+        ("d1".to_string(), ExportFallback::Omitted),
+        ("s1".to_string(), ExportFallback::Link),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_markdown_numbered_headings() {
+    let mut doc = TestDoc::new("Manual");
+    doc.add_block(
+      "note",
+      "h1",
+      "affine:paragraph",
+      &[("type", "h1"), ("text", "Intro")],
+    );
+    doc.add_block(
+      "note",
+      "h2",
+      "affine:paragraph",
+      &[("type", "h2"), ("text", "Scope")],
+    );
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "body")]);
+
+    let result = parse_doc_to_markdown_with_options(
+      doc.encode(),
+      TestDoc::DOC_ID.into(),
+      &MarkdownOptions {
+        numbered_headings: true,
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(result.markdown, "# 1. Intro\n## 1.1 Scope\nbody\n");
+  }
+
+  #[test]
+  fn test_parse_block_to_markdown() {
+    let mut doc = TestDoc::new("Selection");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "before")]);
+    doc.add_block("note", "l1", "affine:list", &[("text", "parent")]);
+    doc.add_block("l1", "l2", "affine:list", &[("text", "child")]);
+    doc.add_block("note", "p2", "affine:paragraph", &[("text", "after")]);
+    let doc_bin = doc.encode();
+
+    let result =
+      parse_block_to_markdown(doc_bin.clone(), TestDoc::DOC_ID.into(), "l1", false).unwrap();
+    assert_eq!(result.title, "Selection");
+    assert_eq!(result.markdown, "- parent\n    - child\n");
+    assert!(matches!(
+      parse_block_to_markdown(doc_bin, TestDoc::DOC_ID.into(), "missing", false),
+      Err(ParseError::BlockNotFound(id)) if id == "missing"
+    ));
+  }
+}
@@ -0,0 +1,260 @@
+use tracing::instrument;
+use y_octo::Map;
+
+use super::{
+  markdown_reader::{read_markdown_blocks, MarkdownBlock},
+  new_doc,
+  row_doc::{append_child, insert_block, insert_text},
+  ParseError, NOTE_FLAVOUR, PAGE_FLAVOUR,
+};
+
+/// Builds a doc from Markdown, the reverse of
+/// [`super::parse_doc_to_markdown`], and returns it as a full update.
+///
+/// Headings, quotes and other lines become `affine:paragraph` blocks, list
+/// items nested `affine:list` blocks (bulleted, numbered or todo), fenced code
+/// `affine:code` blocks and pipe tables `affine:table` blocks. A line made of
+/// a single `![caption](blob://<blobId>)` image becomes an `affine:image`
+/// block; images hosted elsewhere stay a paragraph with their Markdown, as
+/// the doc can only reference uploaded blobs. Blank lines separate blocks
+/// without producing any, and inline Markdown is kept as written.
+///
+/// The doc is titled after the `title` of a leading frontmatter block, as
+/// written by the `frontmatter` export option, or else after a leading `# `
+/// heading, which is then not repeated in the body.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::doc_parser::{markdown_to_doc_binary, parse_doc_to_markdown};
+///
+/// let doc_bin =
+///   markdown_to_doc_binary("# Plan\n\n- write\n- ship\n".into(), "doc-id".into()).unwrap();
+/// let result = parse_doc_to_markdown(doc_bin, "doc-id".into(), false).unwrap();
+/// assert_eq!(result.title, "Plan");
+/// assert_eq!(result.markdown, "- write\n- ship\n");
+/// ```
+#[instrument(
+  name = "markdown_import",
+  skip_all,
+  fields(doc_id = %doc_id, bytes = markdown.len()),
+  err
+)]
+pub fn markdown_to_doc_binary(markdown: String, doc_id: String) -> Result<Vec<u8>, ParseError> {
+  let (mut title, body) = split_frontmatter(&markdown);
+  let mut blocks = read_markdown_blocks(body)
+    .into_iter()
+    .filter(
+      |block| !matches!(block, MarkdownBlock::Paragraph { text, .. } if text.trim().is_empty()),
+    )
+    .peekable();
+  if title.is_none() {
+    if let Some(MarkdownBlock::Paragraph { kind, text }) = blocks.peek() {
+      if kind == "h1" {
+        title = Some(text.clone());
+        blocks.next();
+      }
+    }
+  }
+
+  let doc = new_doc(Some(&doc_id));
+  let mut block_pool = doc.get_or_create_map("blocks")?;
+  let mut page = insert_block(
+    &doc,
+    &mut block_pool,
+    &format!("{doc_id}-page"),
+    PAGE_FLAVOUR,
+  )?;
+  insert_text(&doc, &mut page, "prop:title", &title.unwrap_or_default())?;
+
+  let note_id = format!("{doc_id}-note");
+  let mut note = insert_block(&doc, &mut block_pool, &note_id, NOTE_FLAVOUR)?;
+  note.insert("prop:displayMode".into(), "both")?;
+  append_child(&page, &note_id)?;
+
+  // open list items by depth, the parents of deeper items
+  let mut lists: Vec<Map> = Vec::new();
+  for (i, markdown_block) in blocks.enumerate() {
+    let block_id = format!("{doc_id}-{i}");
+
+    if let MarkdownBlock::Image { caption, url } = &markdown_block {
+      if !url.starts_with("blob://") {
+        lists.clear();
+        let mut block = insert_block(&doc, &mut block_pool, &block_id, "affine:paragraph")?;
+        block.insert("prop:type".into(), "text")?;
+        insert_text(
+          &doc,
+          &mut block,
+          "prop:text",
+          &format!("![{caption}]({url})"),
+        )?;
+        append_child(&note, &block_id)?;
+        continue;
+      }
+    }
+
+    // a pipe table reads back as a database, but is imported as the simpler
+    // table block
+    let flavour = match &markdown_block {
+      MarkdownBlock::Table { .. } => "affine:table",
+      block => block.flavour(),
+    };
+    let mut block = insert_block(&doc, &mut block_pool, &block_id, flavour)?;
+    match &markdown_block {
+      MarkdownBlock::Paragraph { kind, text } => {
+        block.insert("prop:type".into(), kind.as_str())?;
+        insert_text(&doc, &mut block, "prop:text", text)?;
+      }
+      MarkdownBlock::List {
+        depth,
+        kind,
+        checked,
+        text,
+      } => {
+        block.insert("prop:type".into(), kind.as_str())?;
+        block.insert("prop:checked".into(), *checked)?;
+        insert_text(&doc, &mut block, "prop:text", text)?;
+
+        lists.truncate(*depth);
+        append_child(lists.last().unwrap_or(&note), &block_id)?;
+        lists.push(block);
+        continue;
+      }
+      MarkdownBlock::Code { language, text } => {
+        block.insert("prop:language".into(), language.as_str())?;
+        insert_text(&doc, &mut block, "prop:text", text)?;
+      }
+      MarkdownBlock::Table { rows } => {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+        for column in 0..columns {
+          block.insert(
+            format!("prop:columns.c{column}.columnId"),
+            format!("c{column}"),
+          )?;
+          block.insert(format!("prop:columns.c{column}.order"), order(column))?;
+        }
+        for (row, cells) in rows.iter().enumerate() {
+          block.insert(format!("prop:rows.r{row}.rowId"), format!("r{row}"))?;
+          block.insert(format!("prop:rows.r{row}.order"), order(row))?;
+          for (column, cell) in cells.iter().enumerate() {
+            insert_text(
+              &doc,
+              &mut block,
+              &format!("prop:cells.r{row}:c{column}.text"),
+              cell,
+            )?;
+          }
+        }
+      }
+      MarkdownBlock::Image { caption, url } => {
+        let blob_id = url.trim_start_matches("blob://");
+        block.insert("prop:sourceId".into(), blob_id)?;
+        block.insert("prop:caption".into(), caption.as_str())?;
+      }
+    }
+    lists.clear();
+    append_child(&note, &block_id)?;
+  }
+
+  Ok(doc.encode_update_v1()?)
+}
+
+/// Splits a leading `---` frontmatter block off `markdown`, returning its
+/// `title` and the rest of the Markdown.
+fn split_frontmatter(markdown: &str) -> (Option<String>, &str) {
+  let Some(rest) = markdown.strip_prefix("---\n") else {
+    return (None, markdown);
+  };
+  let Some(end) = rest.find("\n---\n") else {
+    return (None, markdown);
+  };
+
+  let title = rest[..end].lines().find_map(|line| {
+    let value = line.strip_prefix("title:")?.trim();
+    // the export writes JSON strings, which are valid YAML scalars
+    Some(serde_json::from_str::<String>(value).unwrap_or_else(|_| value.to_string()))
+  });
+  (title, &rest[end + "\n---\n".len()..])
+}
+
+/// Fractional index keeping rows and columns in Markdown order.
+fn order(position: usize) -> String {
+  format!("a{position:04}")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{
+    parse_doc_to_html, parse_doc_to_markdown, parse_doc_to_markdown_with_options, BlobHandling,
+    MarkdownOptions,
+  };
+
+  #[test]
+  fn test_markdown_to_doc_binary() {
+    let markdown = [
+      "---",
+      "title: \"Release: 1.0\"",
+      "docId: \"ignored\"",
+      "---",
+      "",
+      "## Steps",
+      "",
+      "1. build",
+      "    - [x] tests",
+      "    - [ ] docs",
+      "2. ship",
+      "```sh",
+      "cargo publish",
+      "```",
+      "|a|b|",
+      "|---|---|",
+      "|1|2|",
+      "![diagram](blob://diagram)",
+      "![logo](https://example.com/logo.png)",
+    ]
+    .join("\n");
+    let doc_bin = markdown_to_doc_binary(markdown, "imported".into()).unwrap();
+
+    let exported = parse_doc_to_markdown_with_options(
+      doc_bin.clone(),
+      "imported".into(),
+      &MarkdownOptions {
+        blobs: BlobHandling::Reference,
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(exported.title, "Release: 1.0");
+    assert!(exported.markdown.starts_with(
+      &[
+        "## Steps",
+        "- build",
+        "    - tests",
+        "    - docs",
+        "- ship",
+        "```sh",
+        "cargo publish",
+        "```",
+        "",
+      ]
+      .join("\n")
+    ));
+    assert!(exported.markdown.ends_with(
+      "```\na|b|1|2\n![diagram](blob://diagram)\n![logo](https://example.com/logo.png)\n"
+    ));
+
+    let html = parse_doc_to_html(doc_bin, "imported".into(), &Default::default())
+      .unwrap()
+      .html;
+    assert!(html.contains(
+      "<ol>\n<li>build\n<ul>\n<li><input type=\"checkbox\" disabled checked> tests</li>"
+    ));
+    assert!(html.contains("<tr><td>a</td><td>b</td></tr>\n<tr><td>1</td><td>2</td></tr>"));
+
+    let untitled = markdown_to_doc_binary("# Plan\n# Again\n".into(), "plan".into()).unwrap();
+    let exported = parse_doc_to_markdown(untitled, "plan".into(), false).unwrap();
+    assert_eq!(exported.title, "Plan");
+    assert_eq!(exported.markdown, "# Again\n");
+  }
+}
@@ -0,0 +1,230 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::{
+  options::{parse_options, OptionsObject},
+  ParseError,
+};
+
+static HEADING: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^ {0,3}(#{1,6})(?:\s|$)").unwrap());
+static URL: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r#"https?://[^\s<>()\[\]"'`]+[^\s<>()\[\]"'`.,;:!?]"#).unwrap());
+
+/// Style rules checked by [`lint_markdown`]. Every rule is on by default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct LintRules {
+  /// Headings may only go one level deeper than the heading before them.
+  pub heading_increment: bool,
+  /// Longest allowed line in characters, `null` to allow any length.
+  pub max_line_length: Option<usize>,
+  /// URLs must be written as links or autolinks.
+  pub bare_urls: bool,
+}
+
+impl Default for LintRules {
+  fn default() -> Self {
+    Self {
+      heading_increment: true,
+      max_line_length: Some(120),
+      bare_urls: true,
+    }
+  }
+}
+
+impl LintRules {
+  /// Parses and checks rules sent as JSON, see [`validate_options`].
+  ///
+  /// [`validate_options`]: super::validate_options
+  pub fn from_json(json: &str) -> Result<Self, ParseError> {
+    parse_options(json)
+  }
+}
+
+impl OptionsObject for LintRules {
+  fn check(&self) -> Result<(), String> {
+    if self.max_line_length == Some(0) {
+      return Err("`maxLineLength` must be greater than 0".into());
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintRule {
+  HeadingIncrement,
+  LineLength,
+  BareUrl,
+}
+
+impl LintRule {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      LintRule::HeadingIncrement => "headingincrement",
+      LintRule::LineLength => "linelength",
+      LintRule::BareUrl => "bareurl",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintIssue {
+  pub rule: LintRule,
+  /// 1-based line of the issue.
+  pub line: usize,
+  /// 1-based character column of the issue.
+  pub column: usize,
+  pub message: String,
+}
+
+/// Checks markdown, typically the output of an export, against `rules`.
+///
+/// Fenced code blocks and a leading YAML frontmatter are not checked.
+/// Issues are sorted by position.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::doc_parser::{lint_markdown, LintRule, LintRules};
+///
+/// let issues = lint_markdown("# Title\n\n### Details\n", &LintRules::default());
+/// assert_eq!(issues[0].rule, LintRule::HeadingIncrement);
+/// ```
+pub fn lint_markdown(markdown: &str, rules: &LintRules) -> Vec<LintIssue> {
+  let mut issues = Vec::new();
+  let mut fence: Option<&str> = None;
+  let mut in_frontmatter = markdown.starts_with("---\n");
+  let mut previous_level: Option<usize> = None;
+
+  for (index, line) in markdown.lines().enumerate() {
+    let line_number = index + 1;
+    if in_frontmatter {
+      if index > 0 && line == "---" {
+        in_frontmatter = false;
+      }
+      continue;
+    }
+
+    let trimmed = line.trim_start();
+    if let Some(marker) = fence {
+      if trimmed.starts_with(marker) {
+        fence = None;
+      }
+      continue;
+    }
+    if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+      fence = Some(&trimmed[..3]);
+      continue;
+    }
+
+    if let Some(max) = rules.max_line_length {
+      let length = line.chars().count();
+      if length > max {
+        issues.push(LintIssue {
+          rule: LintRule::LineLength,
+          line: line_number,
+          column: max + 1,
+          message: format!("line is {length} characters long, the limit is {max}"),
+        });
+      }
+    }
+
+    if let Some(captures) = HEADING.captures(line) {
+      let level = captures[1].len();
+      if rules.heading_increment {
+        if let Some(previous) = previous_level.filter(|previous| level > previous + 1) {
+          issues.push(LintIssue {
+            rule: LintRule::HeadingIncrement,
+            line: line_number,
+            column: 1,
+            message: format!("heading level {level} follows level {previous}"),
+          });
+        }
+      }
+      previous_level = Some(level);
+    }
+
+    if rules.bare_urls {
+      for url in URL.find_iter(line) {
+        if !is_bare(line, url.start()) {
+          continue;
+        }
+        issues.push(LintIssue {
+          rule: LintRule::BareUrl,
+          line: line_number,
+          column: line[..url.start()].chars().count() + 1,
+          message: format!(
+            "bare URL `{}`, write it as `<{}>`",
+            url.as_str(),
+            url.as_str()
+          ),
+        });
+      }
+    }
+  }
+
+  issues.sort_by_key(|issue| (issue.line, issue.column));
+  issues
+}
+
+/// Whether the URL starting at byte `start` of `line` is not already a link
+/// target, an autolink, a link label or inline code.
+fn is_bare(line: &str, start: usize) -> bool {
+  let before = &line[..start];
+  if before.ends_with("](") || before.ends_with('<') || before.ends_with('[') {
+    return false;
+  }
+  // an odd number of backticks before the URL opens a code span around it
+  before.matches('`').count().is_multiple_of(2)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_lint_markdown() {
+    let markdown = [
+      "---",
+      "title: \"https://example.com/frontmatter\"",
+      "---",
+      "",
+      "# Title",
+      "### Skipped a level",
+      "See https://example.com/docs.",
+      "Linked [docs](https://example.com/docs) and <https://example.com/auto>.",
+      "Code `https://example.com/code` stays.",
+      "```",
+      "# not a heading https://example.com/fenced",
+      "```",
+      "## Back to two",
+      &"a".repeat(130),
+    ]
+    .join("\n");
+
+    let issues = lint_markdown(&markdown, &LintRules::default());
+    let summary = issues
+      .iter()
+      .map(|issue| (issue.rule, issue.line, issue.column))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      summary,
+      vec![
+        (LintRule::HeadingIncrement, 6, 1),
+        (LintRule::BareUrl, 7, 5),
+        (LintRule::LineLength, 14, 121),
+      ]
+    );
+    assert_eq!(
+      issues[1].message,
+      "bare URL `https://example.com/docs`, write it as `<https://example.com/docs>`"
+    );
+
+    let rules = LintRules::from_json(r#"{ "maxLineLength": null, "bareUrls": false }"#).unwrap();
+    assert_eq!(lint_markdown(&markdown, &rules).len(), 1);
+    assert!(LintRules::from_json(r#"{ "maxLineLength": 0 }"#).is_err());
+  }
+}
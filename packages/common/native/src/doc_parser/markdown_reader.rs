@@ -0,0 +1,287 @@
+/// A block recovered from Markdown produced by `parse_doc_to_markdown`.
+///
+/// The reader only understands the constructs the exporter emits, so it is
+/// intentionally line based: one paragraph per line, `- ` list items indented
+/// by four spaces per level, fenced code blocks and pipe tables. Numbered and
+/// task list items and lines made of a single image are read as well, for
+/// Markdown written by hand or by AI.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MarkdownBlock {
+  Paragraph {
+    kind: String,
+    text: String,
+  },
+  /// `kind` is the `affine:list` type, `bulleted`, `numbered` or `todo`.
+  List {
+    depth: usize,
+    kind: String,
+    checked: bool,
+    text: String,
+  },
+  Code {
+    language: String,
+    text: String,
+  },
+  Table {
+    rows: Vec<Vec<String>>,
+  },
+  Image {
+    caption: String,
+    url: String,
+  },
+}
+
+impl MarkdownBlock {
+  /// Returns the block flavour this Markdown construct maps back to.
+  pub(crate) fn flavour(&self) -> &'static str {
+    match self {
+      MarkdownBlock::Paragraph { .. } => "affine:paragraph",
+      MarkdownBlock::List { .. } => "affine:list",
+      MarkdownBlock::Code { .. } => "affine:code",
+      MarkdownBlock::Table { .. } => "affine:database",
+      MarkdownBlock::Image { .. } => "affine:image",
+    }
+  }
+
+  /// Returns every text fragment carried by the block.
+  pub(crate) fn texts(&self) -> Vec<&str> {
+    match self {
+      MarkdownBlock::Paragraph { text, .. }
+      | MarkdownBlock::List { text, .. }
+      | MarkdownBlock::Code { text, .. } => vec![text.as_str()],
+      MarkdownBlock::Image { caption, .. } => vec![caption.as_str()],
+      MarkdownBlock::Table { rows } => rows
+        .iter()
+        .flat_map(|row| row.iter().map(String::as_str))
+        .collect(),
+    }
+  }
+}
+
+/// Splits Markdown into the blocks `parse_doc_to_markdown` would have emitted
+/// for them.
+///
+/// # Examples
+///
+/// ```ignore
+/// let blocks = read_markdown_blocks("# Title\n- item\n");
+/// assert_eq!(blocks.len(), 2);
+/// ```
+pub(crate) fn read_markdown_blocks(markdown: &str) -> Vec<MarkdownBlock> {
+  let mut blocks = Vec::new();
+  let mut lines = markdown.lines().peekable();
+
+  while let Some(line) = lines.next() {
+    if line.starts_with("<!-- block_id=") && line.ends_with("-->") {
+      continue;
+    }
+
+    if let Some(language) = line.strip_prefix("```") {
+      let mut body = Vec::new();
+      for code_line in lines.by_ref() {
+        if code_line == "```" {
+          break;
+        }
+        body.push(code_line);
+      }
+      blocks.push(MarkdownBlock::Code {
+        language: language.trim().to_string(),
+        text: body.join("\n"),
+      });
+      continue;
+    }
+
+    if is_table_row(line) {
+      let mut rows = vec![split_table_row(line)];
+      while let Some(next) = lines.peek() {
+        if !is_table_row(next) {
+          break;
+        }
+        let row = split_table_row(next);
+        if !is_separator_row(&row) {
+          rows.push(row);
+        }
+        lines.next();
+      }
+      blocks.push(MarkdownBlock::Table { rows });
+      continue;
+    }
+
+    let trimmed = line.trim_start_matches(' ');
+    let indent = line.len() - trimmed.len();
+    if let Some((kind, checked, text)) = list_item(trimmed) {
+      blocks.push(MarkdownBlock::List {
+        depth: indent / 4,
+        kind: kind.to_string(),
+        checked,
+        text: text.to_string(),
+      });
+      continue;
+    }
+
+    if let Some((caption, url)) = image(line) {
+      blocks.push(MarkdownBlock::Image {
+        caption: caption.to_string(),
+        url: url.to_string(),
+      });
+      continue;
+    }
+
+    let (kind, text) = paragraph_kind(line);
+    blocks.push(MarkdownBlock::Paragraph {
+      kind: kind.to_string(),
+      text: text.to_string(),
+    });
+  }
+
+  blocks
+}
+
+/// Splits a list item into its list type, checked state and text.
+fn list_item(line: &str) -> Option<(&'static str, bool, &str)> {
+  if let Some(text) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+    return Some(match text.get(..4) {
+      Some("[ ] ") => ("todo", false, &text[4..]),
+      Some("[x] " | "[X] ") => ("todo", true, &text[4..]),
+      _ => ("bulleted", false, text),
+    });
+  }
+
+  let digits = line.chars().take_while(char::is_ascii_digit).count();
+  if digits > 0 {
+    if let Some(text) = line[digits..].strip_prefix(". ") {
+      return Some(("numbered", false, text));
+    }
+  }
+  None
+}
+
+/// Reads a line holding nothing but `![caption](url)`.
+fn image(line: &str) -> Option<(&str, &str)> {
+  let (caption, rest) = line.trim().strip_prefix("![")?.split_once("](")?;
+  let url = rest.strip_suffix(')')?;
+  if url.is_empty() || url.contains(char::is_whitespace) {
+    return None;
+  }
+  Some((caption, url))
+}
+
+fn paragraph_kind(line: &str) -> (&'static str, &str) {
+  const HEADINGS: [&str; 6] = ["h1", "h2", "h3", "h4", "h5", "h6"];
+
+  let hashes = line.chars().take_while(|c| *c == '#').count();
+  if (1..=6).contains(&hashes) {
+    if let Some(text) = line[hashes..].strip_prefix(' ') {
+      return (HEADINGS[hashes - 1], text);
+    }
+  }
+
+  if let Some(text) = line.strip_prefix("> ") {
+    return ("quote", text);
+  }
+
+  ("text", line)
+}
+
+fn is_table_row(line: &str) -> bool {
+  line.len() > 1 && line.starts_with('|') && line.ends_with('|')
+}
+
+fn is_separator_row(row: &[String]) -> bool {
+  !row.is_empty()
+    && row.iter().all(|cell| {
+      let cell = cell.trim().trim_matches(':');
+      !cell.is_empty() && cell.chars().all(|c| c == '-')
+    })
+}
+
+fn split_table_row(line: &str) -> Vec<String> {
+  let inner = &line[1..line.len() - 1];
+  let mut cells = Vec::new();
+  let mut current = String::new();
+  let mut chars = inner.chars().peekable();
+
+  while let Some(ch) = chars.next() {
+    match ch {
+      '\\' if chars.peek() == Some(&'|') => {
+        current.push('|');
+        chars.next();
+      }
+      '|' => cells.push(std::mem::take(&mut current)),
+      _ => current.push(ch),
+    }
+  }
+  cells.push(current);
+
+  cells
+    .into_iter()
+    .map(|cell| cell.replace("<br>", "\n"))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_read_markdown_blocks() {
+    let markdown = "# Title\n\n> quoted\n- item\n    - nested\n```rust\nfn main() {}\n```\n|a|b\\|c|\n|---|---|\n|1|2<br>3|\n2. second\n    - [x] done\n![logo](blob://logo)\n";
+    let blocks = read_markdown_blocks(markdown);
+
+    assert_eq!(
+      blocks,
+      vec![
+        MarkdownBlock::Paragraph {
+          kind: "h1".into(),
+          text: "Title".into()
+        },
+        MarkdownBlock::Paragraph {
+          kind: "text".into(),
+          text: "".into()
+        },
+        MarkdownBlock::Paragraph {
+          kind: "quote".into(),
+          text: "quoted".into()
+        },
+        MarkdownBlock::List {
+          depth: 0,
+          kind: "bulleted".into(),
+          checked: false,
+          text: "item".into()
+        },
+        MarkdownBlock::List {
+          depth: 1,
+          kind: "bulleted".into(),
+          checked: false,
+          text: "nested".into()
+        },
+        MarkdownBlock::Code {
+          language: "rust".into(),
+          text: "fn main() {}".into()
+        },
+        MarkdownBlock::Table {
+          rows: vec![
+            vec!["a".into(), "b|c".into()],
+            vec!["1".into(), "2\n3".into()]
+          ]
+        },
+        MarkdownBlock::List {
+          depth: 0,
+          kind: "numbered".into(),
+          checked: false,
+          text: "second".into()
+        },
+        MarkdownBlock::List {
+          depth: 1,
+          kind: "todo".into(),
+          checked: true,
+          text: "done".into()
+        },
+        MarkdownBlock::Image {
+          caption: "logo".into(),
+          url: "blob://logo".into()
+        },
+      ]
+    );
+  }
+}
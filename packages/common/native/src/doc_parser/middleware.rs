@@ -0,0 +1,40 @@
+use super::BlockSnapshot;
+
+/// Hooks around the export pipeline, letting embedders transform blocks and
+/// rendered output without forking the renderer.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::doc_parser::{BlockSnapshot, ExportMiddleware};
+///
+/// /// Drops code blocks and upper-cases everything else.
+/// struct Shout;
+///
+/// impl ExportMiddleware for Shout {
+///   fn on_block(&self, block: BlockSnapshot) -> Option<BlockSnapshot> {
+///     (block.flavour != "affine:code").then_some(block)
+///   }
+///
+///   fn on_output(&self, chunk: String) -> String {
+///     chunk.to_uppercase()
+///   }
+/// }
+/// ```
+pub trait ExportMiddleware {
+  /// Called with every block before it is rendered. The returned snapshot is
+  /// rendered in place of the original; returning `None` skips the block.
+  ///
+  /// Only the `prop:*` values of simple text blocks (paragraphs, lists and
+  /// code) are read back from the snapshot; other flavours can be skipped
+  /// but not rewritten.
+  fn on_block(&self, block: BlockSnapshot) -> Option<BlockSnapshot> {
+    Some(block)
+  }
+
+  /// Called with the rendered output of every block before it is appended to
+  /// the result.
+  fn on_output(&self, chunk: String) -> String {
+    chunk
+  }
+}
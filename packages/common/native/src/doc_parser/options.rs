@@ -0,0 +1,105 @@
+use serde::de::DeserializeOwned;
+
+use super::{
+  AnalyticsOptions, BlockFilter, CleanupOptions, ExportPreset, LintRules, ParseError,
+  RetentionPolicy, SnapshotPolicy, SummaryOptions,
+};
+
+/// An option object the API layer accepts as JSON.
+///
+/// Implementors should deny unknown fields so typos surface as errors instead
+/// of being ignored.
+pub(crate) trait OptionsObject: DeserializeOwned {
+  /// Checks constraints serde cannot express, such as options that conflict
+  /// with each other. Returns a message naming the offending fields.
+  fn check(&self) -> Result<(), String> {
+    Ok(())
+  }
+}
+
+pub(crate) fn parse_options<T: OptionsObject>(json: &str) -> Result<T, ParseError> {
+  let options: T =
+    serde_json::from_str(json).map_err(|e| ParseError::InvalidOptions(e.to_string()))?;
+  options.check().map_err(ParseError::InvalidOptions)?;
+  Ok(options)
+}
+
+/// Validates the JSON of an option object without using it, so callers can
+/// reject bad input at request time.
+///
+/// `kind` names the object: `"preset"` for [`ExportPreset`], `"cleanup"`
+/// for [`CleanupOptions`], `"analytics"` for [`AnalyticsOptions`],
+/// `"retention"` for [`RetentionPolicy`], `"filter"` for [`BlockFilter`],
+/// `"lint"` for [`LintRules`], `"summary"` for [`SummaryOptions`] or
+/// `"snapshot"` for [`SnapshotPolicy`].
+/// Unknown fields, bad enum values and conflicting options are reported as
+/// `ParseError::InvalidOptions`.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::doc_parser::{validate_options, ParseError};
+///
+/// let res = validate_options("preset", r#"{ "name": "p", "dialect": "rst" }"#);
+/// assert!(matches!(res, Err(ParseError::InvalidOptions(_))));
+/// ```
+pub fn validate_options(kind: &str, json: &str) -> Result<(), ParseError> {
+  match kind {
+    "preset" => parse_options::<ExportPreset>(json).map(|_| ()),
+    "cleanup" => parse_options::<CleanupOptions>(json).map(|_| ()),
+    "analytics" => parse_options::<AnalyticsOptions>(json).map(|_| ()),
+    "retention" => parse_options::<RetentionPolicy>(json).map(|_| ()),
+    "filter" => parse_options::<BlockFilter>(json).map(|_| ()),
+    "lint" => parse_options::<LintRules>(json).map(|_| ()),
+    "summary" => parse_options::<SummaryOptions>(json).map(|_| ()),
+    "snapshot" => parse_options::<SnapshotPolicy>(json).map(|_| ()),
+    _ => Err(ParseError::InvalidOptions(format!(
+      "unknown options kind `{kind}`"
+    ))),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn error_of(kind: &str, json: &str) -> String {
+    match validate_options(kind, json) {
+      Err(ParseError::InvalidOptions(message)) => message,
+      other => panic!("expected invalid options, got {other:?}"),
+    }
+  }
+
+  #[test]
+  fn test_validate_options() {
+    assert!(validate_options("preset", r#"{ "name": "p", "dialect": "commonmark" }"#).is_ok());
+
+    assert!(
+      error_of("preset", r#"{ "name": "p", "frontMatter": true }"#)
+        .starts_with("unknown field `frontMatter`")
+    );
+    assert!(error_of("preset", r#"{ "name": "p", "dialect": "rst" }"#)
+      .starts_with("unknown variant `rst`"));
+    assert!(error_of(
+      "preset",
+      r#"{ "name": "p", "blobs": { "mode": "url", "template": "/b" } }"#
+    )
+    .contains("{blobId}"));
+    assert_eq!(
+      error_of(
+        "preset",
+        r#"{ "name": "p", "aiEditable": true, "frontmatter": true }"#
+      ),
+      "`aiEditable` cannot be combined with `frontmatter`"
+    );
+    assert!(validate_options("cleanup", r#"{ "collapseBlankRuns": false }"#).is_ok());
+    assert_eq!(
+      error_of(
+        "cleanup",
+        r#"{ "removeTrailingEmpty": false, "collapseBlankRuns": false, "trimTrailingWhitespace": false }"#
+      ),
+      "at least one cleanup pass must be enabled"
+    );
+    assert_eq!(error_of("theme", "{}"), "unknown options kind `theme`");
+  }
+}
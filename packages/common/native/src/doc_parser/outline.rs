@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{anchor::slugify, block_index::BlockIndex, get_string, text_content, ParseError};
+
+/// A heading of a doc, in document order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutlineEntry {
+  pub block_id: String,
+  /// `1` to `6`, from the `h1` to `h6` paragraph types.
+  pub level: u8,
+  pub text: String,
+  /// Fragment the heading is reachable at with [`super::resolve_anchor`].
+  pub slug: String,
+  /// Hierarchical number, `1.` for top-level headings and `1.1`, `1.1.1`, ...
+  /// below them, as rendered by exports with numbered headings.
+  pub number: String,
+}
+
+/// Lists the headings of a doc with their anchors and numbers.
+///
+/// Numbering starts at the shallowest heading level used in the doc, so a doc
+/// made of `h2` and `h3` headings is numbered `1.`, `1.1`, ... Levels skipped
+/// between a heading and its parent are numbered `0`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::extract_outline;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// for entry in extract_outline(doc_bin, "doc-id".into()).unwrap() {
+///   println!("{} {} (#{})", entry.number, entry.text, entry.slug);
+/// }
+/// ```
+pub fn extract_outline(doc_bin: Vec<u8>, doc_id: String) -> Result<Vec<OutlineEntry>, ParseError> {
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(vec![]);
+  };
+  Ok(outline(&index))
+}
+
+pub(crate) fn outline(index: &BlockIndex) -> Vec<OutlineEntry> {
+  let mut seen: HashMap<String, usize> = HashMap::new();
+  let mut entries = Vec::new();
+  for block_id in index.descendants(&index.root_block_id) {
+    let Some(block) = index.get(&block_id) else {
+      continue;
+    };
+    if index.flavour_of(&block_id).as_deref() != Some("affine:paragraph") {
+      continue;
+    }
+    let level = match get_string(block, "prop:type").as_deref() {
+      Some("h1") => 1,
+      Some("h2") => 2,
+      Some("h3") => 3,
+      Some("h4") => 4,
+      Some("h5") => 5,
+      Some("h6") => 6,
+      _ => continue,
+    };
+    let Some((text, _)) = text_content(block, "prop:text") else {
+      continue;
+    };
+
+    let base = slugify(&text);
+    let count = seen.entry(base.clone()).or_default();
+    let slug = match *count {
+      0 => base,
+      n => format!("{base}-{n}"),
+    };
+    *count += 1;
+
+    entries.push(OutlineEntry {
+      block_id,
+      level,
+      text,
+      slug,
+      number: String::new(),
+    });
+  }
+
+  let top = entries.iter().map(|entry| entry.level).min().unwrap_or(1);
+  let mut counters = [0usize; 6];
+  for entry in &mut entries {
+    let depth = usize::from(entry.level - top);
+    counters[depth] += 1;
+    counters[depth + 1..].fill(0);
+    entry.number = match depth {
+      0 => format!("{}.", counters[0]),
+      _ => counters[..=depth]
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("."),
+    };
+  }
+  entries
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_extract_outline() {
+    let mut doc = TestDoc::new("Guide");
+    for (id, type_, text) in [
+      ("a", "h2", "Setup"),
+      ("b", "text", "Install it."),
+      ("c", "h3", "Linux"),
+      ("d", "h3", "Setup"),
+      ("e", "h2", "Usage"),
+      ("f", "h5", "Flags"),
+    ] {
+      doc.add_block(
+        "note",
+        id,
+        "affine:paragraph",
+        &[("type", type_), ("text", text)],
+      );
+    }
+
+    let outline = extract_outline(doc.encode(), TestDoc::DOC_ID.into()).unwrap();
+    let summary = outline
+      .iter()
+      .map(|entry| {
+        (
+          entry.block_id.as_str(),
+          entry.slug.as_str(),
+          entry.number.as_str(),
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      summary,
+      vec![
+        ("a", "setup", "1."),
+        ("c", "linux", "1.1"),
+        ("d", "setup-1", "1.2"),
+        ("e", "usage", "2."),
+        ("f", "flags", "2.0.0.1"),
+      ]
+    );
+    assert_eq!(outline[3].level, 2);
+  }
+}
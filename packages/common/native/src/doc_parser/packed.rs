@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use super::{BlockInfo, CrawlResult, ParseError};
+
+/// First bytes of a packed crawl result, ending in the format version.
+pub const PACKED_CRAWL_MAGIC: &[u8; 4] = b"ACR1";
+/// Index written for a missing string or list.
+const NONE: u32 = u32::MAX;
+
+/// Packs a crawl result into a single buffer, so callers receive one
+/// allocation instead of a JS string per field of every block.
+///
+/// All integers are little-endian `u32`s and strings are referenced by index
+/// into a table of unique strings. The layout is
+///
+/// ```text
+/// magic          "ACR1"
+/// string count   n
+/// string ends    n byte offsets into the string data, each past its string
+/// string data    UTF-8
+/// title          string index
+/// summary        string index
+/// block count    m
+/// block offsets  m byte offsets from the start of the buffer
+/// blocks         block id, flavour, parent flavour, parent block id,
+///                additional and checksum as string indices, then content,
+///                blob, ref doc id and ref info as a length followed by that
+///                many string indices
+/// ```
+///
+/// with `0xffffffff` in place of a missing string or list.
+pub fn pack_crawl_result(result: &CrawlResult) -> Vec<u8> {
+  let mut strings = StringTable::default();
+  let title = strings.intern(&result.title);
+  let summary = strings.intern(&result.summary);
+
+  let blocks = result
+    .blocks
+    .iter()
+    .map(|block| {
+      let mut words = [
+        Some(&block.block_id),
+        Some(&block.flavour),
+        block.parent_flavour.as_ref(),
+        block.parent_block_id.as_ref(),
+        block.additional.as_ref(),
+        block.checksum.as_ref(),
+      ]
+      .into_iter()
+      .map(|value| value.map_or(NONE, |value| strings.intern(value)))
+      .collect::<Vec<_>>();
+      for list in [
+        &block.content,
+        &block.blob,
+        &block.ref_doc_id,
+        &block.ref_info,
+      ] {
+        match list {
+          Some(items) => {
+            words.push(items.len() as u32);
+            words.extend(items.iter().map(|item| strings.intern(item)));
+          }
+          None => words.push(NONE),
+        }
+      }
+      words
+    })
+    .collect::<Vec<_>>();
+
+  let mut out = Vec::new();
+  out.extend_from_slice(PACKED_CRAWL_MAGIC);
+  push_u32(&mut out, strings.values.len() as u32);
+  let mut end = 0;
+  for value in &strings.values {
+    end += value.len();
+    push_u32(&mut out, end as u32);
+  }
+  for value in &strings.values {
+    out.extend_from_slice(value.as_bytes());
+  }
+
+  push_u32(&mut out, title);
+  push_u32(&mut out, summary);
+  push_u32(&mut out, blocks.len() as u32);
+  let mut offset = out.len() + 4 * blocks.len();
+  for words in &blocks {
+    push_u32(&mut out, offset as u32);
+    offset += 4 * words.len();
+  }
+  for word in blocks.iter().flatten() {
+    push_u32(&mut out, *word);
+  }
+  out
+}
+
+/// Reads a buffer written by [`pack_crawl_result`].
+pub fn unpack_crawl_result(packed: &[u8]) -> Result<CrawlResult, ParseError> {
+  let invalid = || ParseError::ParserError("invalid packed crawl result".into());
+  if !packed.starts_with(PACKED_CRAWL_MAGIC) {
+    return Err(invalid());
+  }
+  let mut reader = Reader {
+    packed,
+    offset: PACKED_CRAWL_MAGIC.len(),
+  };
+
+  let count = reader.u32().ok_or_else(invalid)? as usize;
+  let ends = (0..count)
+    .map(|_| reader.u32().map(|end| end as usize))
+    .collect::<Option<Vec<_>>>()
+    .ok_or_else(invalid)?;
+  let data = packed
+    .get(reader.offset..reader.offset + ends.last().copied().unwrap_or_default())
+    .ok_or_else(invalid)?;
+  let strings = ends
+    .iter()
+    .scan(0, |start, &end| {
+      let value = data
+        .get(*start..end)
+        .and_then(|value| std::str::from_utf8(value).ok());
+      *start = end;
+      Some(value.map(str::to_string))
+    })
+    .collect::<Option<Vec<_>>>()
+    .ok_or_else(invalid)?;
+  reader.offset += data.len();
+
+  // `NONE` is past the end of any table
+  let string = |index: u32| strings.get(index as usize).cloned();
+  let title = reader.u32().and_then(string).ok_or_else(invalid)?;
+  let summary = reader.u32().and_then(string).ok_or_else(invalid)?;
+
+  let block_count = reader.u32().ok_or_else(invalid)?;
+  let offsets = (0..block_count)
+    .map(|_| reader.u32())
+    .collect::<Option<Vec<_>>>()
+    .ok_or_else(invalid)?;
+  let mut blocks = Vec::with_capacity(offsets.len());
+  for offset in offsets {
+    reader.offset = offset as usize;
+    let mut fields = Vec::with_capacity(6);
+    for _ in 0..6 {
+      fields.push(reader.u32().ok_or_else(invalid)?);
+    }
+    let mut lists = Vec::with_capacity(4);
+    for _ in 0..4 {
+      let len = reader.u32().ok_or_else(invalid)?;
+      if len == NONE {
+        lists.push(None);
+        continue;
+      }
+      let items = (0..len)
+        .map(|_| reader.u32().and_then(string))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(invalid)?;
+      lists.push(Some(items));
+    }
+    let mut lists = lists.into_iter();
+
+    blocks.push(BlockInfo {
+      block_id: string(fields[0]).ok_or_else(invalid)?,
+      flavour: string(fields[1]).ok_or_else(invalid)?,
+      parent_flavour: string(fields[2]),
+      parent_block_id: string(fields[3]),
+      additional: string(fields[4]),
+      checksum: string(fields[5]),
+      content: lists.next().flatten(),
+      blob: lists.next().flatten(),
+      ref_doc_id: lists.next().flatten(),
+      ref_info: lists.next().flatten(),
+    });
+  }
+
+  Ok(CrawlResult {
+    blocks,
+    title,
+    summary,
+  })
+}
+
+/// Unique strings in the order they were first seen.
+#[derive(Default)]
+struct StringTable<'a> {
+  indices: HashMap<&'a str, u32>,
+  values: Vec<&'a str>,
+}
+
+impl<'a> StringTable<'a> {
+  fn intern(&mut self, value: &'a str) -> u32 {
+    *self.indices.entry(value).or_insert_with(|| {
+      self.values.push(value);
+      (self.values.len() - 1) as u32
+    })
+  }
+}
+
+fn push_u32(out: &mut Vec<u8>, value: u32) {
+  out.extend_from_slice(&value.to_le_bytes());
+}
+
+struct Reader<'a> {
+  packed: &'a [u8],
+  offset: usize,
+}
+
+impl Reader<'_> {
+  fn u32(&mut self) -> Option<u32> {
+    let bytes = self.packed.get(self.offset..self.offset + 4)?;
+    self.offset += 4;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::parse_doc_from_binary;
+
+  #[test]
+  fn test_pack_crawl_result() {
+    let doc_bin = include_bytes!("../../fixtures/demo.ydoc").to_vec();
+    let result = parse_doc_from_binary(doc_bin, "dYpV7PPhk8amRkY5IAcVO".into()).unwrap();
+
+    let packed = pack_crawl_result(&result);
+    assert!(packed.starts_with(PACKED_CRAWL_MAGIC));
+    // flavours and parent ids repeat across blocks and are stored once
+    assert!(packed.len() < serde_json::to_vec(&result).unwrap().len());
+
+    let unpacked = unpack_crawl_result(&packed).unwrap();
+    assert_eq!(
+      serde_json::to_value(&unpacked).unwrap(),
+      serde_json::to_value(&result).unwrap()
+    );
+
+    assert!(unpack_crawl_result(b"ACR1").is_err());
+    assert!(unpack_crawl_result(&packed[..packed.len() - 1]).is_err());
+  }
+}
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use y_octo::Map;
+
+use super::{
+  block_index::BlockIndex, gather_database_texts, gather_surface_texts, gather_table_contents,
+  get_string, text_content, ParseError, PAGE_FLAVOUR,
+};
+
+/// Plain text of a doc, one entry per block that has any.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextResult {
+  pub title: String,
+  /// Text of every block, separated by newlines.
+  pub text: String,
+  /// Blocks in document order, each pointing into `text`.
+  pub blocks: Vec<TextBlock>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextBlock {
+  pub block_id: String,
+  pub flavour: String,
+  pub text: String,
+  /// Byte offset of the block's text in [`TextResult::text`].
+  pub start: usize,
+  /// Byte offset just past the block's text in [`TextResult::text`].
+  pub end: usize,
+}
+
+/// Extracts the text of a doc without any markup, for search indexing.
+///
+/// Blocks are read like the crawl reads them: paragraphs, lists and code by
+/// their text with inline formatting dropped, attachments by name, images by
+/// caption and databases, tables and the surface by their cell and shape
+/// texts, one per line. Blocks without text are left out. Matches found in
+/// `text` map back to their block through the byte offsets.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::parse_doc_to_text;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let result = parse_doc_to_text(doc_bin, "doc-id".into()).unwrap();
+/// if let Some(offset) = result.text.find("AFFiNE") {
+///   let block = result.blocks.iter().find(|block| block.end > offset).unwrap();
+///   println!("match in {}", block.block_id);
+/// }
+/// ```
+#[instrument(name = "text", skip_all, fields(doc_id = %doc_id), err)]
+pub fn parse_doc_to_text(doc_bin: Vec<u8>, doc_id: String) -> Result<TextResult, ParseError> {
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(TextResult {
+      title: "".into(),
+      text: "".into(),
+      blocks: vec![],
+    });
+  };
+
+  let mut title = String::new();
+  let mut text = String::new();
+  let mut blocks = Vec::new();
+  for block_id in index.descendants(&index.root_block_id) {
+    let (Some(block), Some(flavour)) = (index.get(&block_id), index.flavour_of(&block_id)) else {
+      continue;
+    };
+
+    let block_text = block_text(block, &flavour);
+    if flavour == PAGE_FLAVOUR {
+      title = block_text.clone();
+    }
+    if block_text.is_empty() {
+      continue;
+    }
+
+    if !text.is_empty() {
+      text.push('\n');
+    }
+    let start = text.len();
+    text.push_str(&block_text);
+    blocks.push(TextBlock {
+      block_id,
+      flavour,
+      text: block_text,
+      start,
+      end: text.len(),
+    });
+  }
+
+  if title.is_empty() {
+    title = "Untitled".into();
+  }
+
+  Ok(TextResult {
+    title,
+    text,
+    blocks,
+  })
+}
+
+/// The text of a single block as [`parse_doc_to_text`] reads it, the texts
+/// of blocks with several joined by newlines.
+pub(crate) fn block_text(block: &Map, flavour: &str) -> String {
+  let texts = match flavour {
+    PAGE_FLAVOUR => get_string(block, "prop:title").into_iter().collect(),
+    "affine:paragraph" | "affine:list" | "affine:code" => text_content(block, "prop:text")
+      .map(|(text, _)| vec![text])
+      .unwrap_or_default(),
+    "affine:attachment" => get_string(block, "prop:name").into_iter().collect(),
+    "affine:image" => get_string(block, "prop:caption").into_iter().collect(),
+    "affine:latex" => get_string(block, "prop:latex").into_iter().collect(),
+    "affine:surface" => gather_surface_texts(block),
+    "affine:database" => {
+      let (texts, name) = gather_database_texts(block);
+      name.into_iter().chain(texts).collect()
+    }
+    "affine:table" => gather_table_contents(block),
+    _ => vec![],
+  };
+  texts
+    .into_iter()
+    .filter(|text| !text.trim().is_empty())
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_parse_doc_to_text() {
+    let mut doc = TestDoc::new("Notes");
+    doc.add_block(
+      "note",
+      "h1",
+      "affine:paragraph",
+      &[("type", "h1"), ("text", "Café")],
+    );
+    doc.add_block("note", "empty", "affine:paragraph", &[("text", "")]);
+    doc.add_block(
+      "note",
+      "l1",
+      "affine:list",
+      &[("type", "bulleted"), ("text", "one")],
+    );
+    doc.add_block(
+      "note",
+      "c1",
+      "affine:code",
+      &[("language", "rust"), ("text", "fn main() {}")],
+    );
+
+    let result = parse_doc_to_text(doc.encode(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(result.title, "Notes");
+    assert_eq!(result.text, "Notes\nCafé\none\nfn main() {}");
+    assert_eq!(
+      result
+        .blocks
+        .iter()
+        .map(|block| block.block_id.as_str())
+        .collect::<Vec<_>>(),
+      vec!["page", "h1", "l1", "c1"]
+    );
+    for block in &result.blocks {
+      assert_eq!(&result.text[block.start..block.end], block.text);
+    }
+    // offsets count bytes, `é` takes two
+    assert_eq!((result.blocks[2].start, result.blocks[2].end), (12, 15));
+  }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+  markdown::{parse_doc_to_markdown_with_options, BlobHandling, MarkdownDialect, MarkdownOptions},
+  options::{parse_options, OptionsObject},
+  BlockFilter, ExportFormat, FlavourRegistry, ParseError, UnsupportedBlock,
+};
+
+/// A named bundle of export options.
+///
+/// Presets are plain JSON so services can persist them per workspace and
+/// re-register them with [`PresetStore::define`] on startup.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExportPreset {
+  pub name: String,
+  #[serde(default = "default_format")]
+  pub format: ExportFormat,
+  #[serde(default)]
+  pub dialect: MarkdownDialect,
+  #[serde(default)]
+  pub frontmatter: bool,
+  #[serde(default)]
+  pub ai_editable: bool,
+  #[serde(default)]
+  pub doc_link_template: Option<String>,
+  #[serde(default)]
+  pub blobs: BlobHandling,
+  #[serde(default)]
+  pub filter: Option<BlockFilter>,
+  #[serde(default)]
+  pub numbered_headings: bool,
+}
+
+fn default_format() -> ExportFormat {
+  ExportFormat::Markdown
+}
+
+impl ExportPreset {
+  pub fn markdown_options<'a>(&self) -> MarkdownOptions<'a> {
+    MarkdownOptions {
+      ai_editable: self.ai_editable,
+      dialect: self.dialect,
+      frontmatter: self.frontmatter,
+      doc_link_template: self.doc_link_template.clone(),
+      blobs: self.blobs.clone(),
+      filter: self.filter.clone(),
+      numbered_headings: self.numbered_headings,
+      ..Default::default()
+    }
+  }
+}
+
+impl OptionsObject for ExportPreset {
+  fn check(&self) -> Result<(), String> {
+    if self.name.is_empty() {
+      return Err("`name` must not be empty".into());
+    }
+    if let Some(template) = &self.doc_link_template {
+      if !template.contains("{docId}") {
+        return Err("`docLinkTemplate` must contain `{docId}`".into());
+      }
+    }
+    if let BlobHandling::Url { template } = &self.blobs {
+      if !template.contains("{blobId}") {
+        return Err("`blobs.template` must contain `{blobId}`".into());
+      }
+    }
+    // ai-editable output is meant to be read back, where the frontmatter
+    // would turn into stray paragraphs
+    if self.ai_editable && self.frontmatter {
+      return Err("`aiEditable` cannot be combined with `frontmatter`".into());
+    }
+    if let Some(filter) = &self.filter {
+      filter.check()?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportResult {
+  pub title: String,
+  pub format: ExportFormat,
+  pub content: String,
+  /// Blocks the content does not represent faithfully, in document order.
+  pub unsupported: Vec<UnsupportedBlock>,
+}
+
+/// Named presets of one workspace.
+///
+/// Callers own the store, so presets of one workspace are never visible to
+/// another.
+#[derive(Debug, Clone, Default)]
+pub struct PresetStore {
+  presets: HashMap<String, ExportPreset>,
+}
+
+impl PresetStore {
+  /// Registers the preset described by `json`, replacing any preset with the
+  /// same name.
+  pub fn define(&mut self, json: &str) -> Result<ExportPreset, ParseError> {
+    let preset: ExportPreset = parse_options(json)?;
+    self.presets.insert(preset.name.clone(), preset.clone());
+    Ok(preset)
+  }
+
+  /// Removes a preset, returning whether it was registered.
+  pub fn remove(&mut self, name: &str) -> bool {
+    self.presets.remove(name).is_some()
+  }
+
+  pub fn get(&self, name: &str) -> Option<&ExportPreset> {
+    self.presets.get(name)
+  }
+
+  /// Exports a doc with the options of a previously defined preset.
+  pub fn export(
+    &self,
+    doc_bin: Vec<u8>,
+    doc_id: String,
+    preset_name: &str,
+  ) -> Result<ExportResult, ParseError> {
+    self.export_with_handlers(doc_bin, doc_id, preset_name, None)
+  }
+
+  /// Same as [`PresetStore::export`], rendering custom flavours with
+  /// `handlers`.
+  pub fn export_with_handlers(
+    &self,
+    doc_bin: Vec<u8>,
+    doc_id: String,
+    preset_name: &str,
+    handlers: Option<&FlavourRegistry>,
+  ) -> Result<ExportResult, ParseError> {
+    let preset = self
+      .get(preset_name)
+      .ok_or_else(|| ParseError::PresetNotFound(preset_name.into()))?;
+
+    match preset.format {
+      ExportFormat::Markdown => {
+        let options = MarkdownOptions {
+          handlers,
+          ..preset.markdown_options()
+        };
+        let result = parse_doc_to_markdown_with_options(doc_bin, doc_id, &options)?;
+        Ok(ExportResult {
+          title: result.title,
+          format: preset.format,
+          content: result.markdown,
+          unsupported: result.unsupported,
+        })
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_export_with_preset() {
+    let mut doc = TestDoc::new("Preset");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "hello")]);
+    doc.add_block(
+      "note",
+      "img",
+      "affine:image",
+      &[("sourceId", "blob-1"), ("caption", "cat")],
+    );
+    doc.add_block(
+      "note",
+      "link",
+      "affine:embed-linked-doc",
+      &[("pageId", "other-doc")],
+    );
+    let doc_bin = doc.encode();
+
+    let mut store = PresetStore::default();
+    let preset = store
+      .define(
+        r#"{
+        "name": "test-publish",
+        "frontmatter": true,
+        "docLinkTemplate": "/workspace/ws/{docId}",
+        "blobs": { "mode": "url", "template": "/api/blobs/{blobId}" }
+      }"#,
+      )
+      .unwrap();
+    assert_eq!(preset.format, ExportFormat::Markdown);
+    assert_eq!(preset.dialect, MarkdownDialect::Gfm);
+
+    let result = store
+      .export(doc_bin.clone(), TestDoc::DOC_ID.into(), "test-publish")
+      .unwrap();
+    assert_eq!(result.title, "Preset");
+    assert_eq!(
+      result.content,
+      "---\ntitle: \"Preset\"\ndocId: \"test-doc\"\n---\n\nhello\n![cat](/api/blobs/blob-1)\n[other-doc](/workspace/ws/other-doc)\n"
+    );
+
+    // presets are scoped to their store
+    assert!(matches!(
+      PresetStore::default().export(doc_bin.clone(), TestDoc::DOC_ID.into(), "test-publish"),
+      Err(ParseError::PresetNotFound(_))
+    ));
+
+    assert!(store.remove("test-publish"));
+    assert!(matches!(
+      store.export(doc_bin, TestDoc::DOC_ID.into(), "test-publish"),
+      Err(ParseError::PresetNotFound(_))
+    ));
+    assert!(matches!(
+      store.define(r#"{ "name": "bad", "dialect": "rst" }"#),
+      Err(ParseError::InvalidOptions(_))
+    ));
+  }
+}
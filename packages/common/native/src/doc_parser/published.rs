@@ -0,0 +1,121 @@
+use sha2::{Digest, Sha256};
+use y_octo::Update;
+
+use super::{markdown::parse_doc_to_markdown_with_options, new_doc, MarkdownOptions, ParseError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishedPage {
+  pub title: String,
+  pub content: String,
+  /// Strong ETag of the title and content, quoted as sent in the header.
+  pub etag: String,
+  /// The [`doc_clock`] of the rendered doc.
+  pub clock: u64,
+}
+
+/// Renders a doc for its published page, along with the validators a share
+/// service needs for conditional requests.
+///
+/// The service keeps the `etag` and `clock` of the last render. Requests
+/// whose `If-None-Match` carries that ETag can be answered with a 304 as
+/// long as [`doc_clock`] of the stored doc still returns the same clock, and
+/// the time the clock was first seen serves as `Last-Modified`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{doc_clock, render_published_page};
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let page = render_published_page(doc_bin.clone(), "doc-id".into(), &Default::default()).unwrap();
+/// assert_eq!(doc_clock(&doc_bin).unwrap(), page.clock);
+/// ```
+pub fn render_published_page(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  options: &MarkdownOptions,
+) -> Result<PublishedPage, ParseError> {
+  let clock = doc_clock(&doc_bin)?;
+  let result = parse_doc_to_markdown_with_options(doc_bin, doc_id, options)?;
+
+  let mut hasher = Sha256::new();
+  hasher.update(result.title.as_bytes());
+  hasher.update([0u8]);
+  hasher.update(result.markdown.as_bytes());
+
+  Ok(PublishedPage {
+    etag: format!("\"{:x}\"", hasher.finalize()),
+    title: result.title,
+    content: result.markdown,
+    clock,
+  })
+}
+
+/// A version of a doc that grows with every edit: the sum of the clocks of
+/// its clients, which advance on every insertion, and of the length of its
+/// deleted ranges, which grows on every deletion.
+///
+/// It is much cheaper than rendering the doc, so it can be checked on every
+/// request. As a stored doc only ever receives updates, an unchanged clock
+/// means unchanged content.
+pub fn doc_clock(doc_bin: &[u8]) -> Result<u64, ParseError> {
+  if doc_bin.is_empty() || doc_bin == [0, 0] {
+    return Err(ParseError::InvalidBinary);
+  }
+  let update = Update::decode_v1(doc_bin).map_err(|_| ParseError::InvalidBinary)?;
+  let deleted = update.deleted_lengths().map(|(_, len)| len).sum::<u64>();
+
+  let mut doc = new_doc(None);
+  doc
+    .apply_update(update)
+    .map_err(|_| ParseError::InvalidBinary)?;
+  let inserted = doc
+    .get_state_vector()
+    .iter()
+    .map(|(_, clock)| *clock)
+    .sum::<u64>();
+
+  Ok(inserted + deleted)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_render_published_page() {
+    let mut doc = TestDoc::new("Shared");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "hello")]);
+    let first = doc.encode();
+
+    let page =
+      render_published_page(first.clone(), TestDoc::DOC_ID.into(), &Default::default()).unwrap();
+    assert_eq!(page.title, "Shared");
+    assert_eq!(page.content, "hello\n");
+    assert!(page.etag.starts_with('"') && page.etag.ends_with('"'));
+    assert_eq!(page.etag.len(), 66);
+    assert_eq!(page.clock, doc_clock(&first).unwrap());
+    assert_eq!(
+      render_published_page(first, TestDoc::DOC_ID.into(), &Default::default()).unwrap(),
+      page
+    );
+
+    doc.add_block("note", "p2", "affine:paragraph", &[("text", "world")]);
+    let second = doc.encode();
+    let edited =
+      render_published_page(second, TestDoc::DOC_ID.into(), &Default::default()).unwrap();
+    assert!(edited.clock > page.clock);
+    assert_ne!(edited.etag, page.etag);
+
+    // deletions do not advance client clocks, but still change the clock
+    doc.remove_block("note", "p2");
+    let third = doc.encode();
+    let reverted =
+      render_published_page(third, TestDoc::DOC_ID.into(), &Default::default()).unwrap();
+    assert!(reverted.clock > edited.clock);
+    assert_eq!(reverted.etag, page.etag);
+
+    assert!(matches!(doc_clock(&[0, 0]), Err(ParseError::InvalidBinary)));
+  }
+}
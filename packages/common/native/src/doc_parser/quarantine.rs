@@ -0,0 +1,222 @@
+use std::collections::{BTreeSet, HashSet};
+
+use serde::{Deserialize, Serialize};
+use y_octo::Any;
+
+use super::{
+  blob_manifest::declared_size, block_index::BlockIndex, get_flavour, get_string,
+  row_doc::insert_text, ParseError,
+};
+
+/// Flavours whose blob an antivirus pipeline has to scan.
+const SCANNED_FLAVOURS: [&str; 2] = ["affine:attachment", "affine:image"];
+
+/// A blob of a doc to scan, with the block that embeds it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanTarget {
+  pub block_id: String,
+  pub flavour: String,
+  pub blob_id: String,
+  /// File name, only attachments have one.
+  pub name: Option<String>,
+  /// MIME type recorded by the editor, not checked against the blob.
+  pub mime_type: Option<String>,
+  /// Size in bytes recorded by the editor.
+  pub size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineResult {
+  /// Update to apply to the doc, empty of changes when no block embeds a
+  /// flagged blob.
+  pub update: Vec<u8>,
+  /// Ids of the blocks replaced by a placeholder, sorted.
+  pub quarantined_block_ids: Vec<String>,
+}
+
+/// Lists the blobs attachments and images of a doc embed, in document
+/// order, for an antivirus pipeline to scan. A blob embedded by several
+/// blocks is listed once per block.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::enumerate_attachments_for_scan;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// for target in enumerate_attachments_for_scan(doc_bin, "doc-id".into()).unwrap() {
+///   println!("scan {} ({:?})", target.blob_id, target.name);
+/// }
+/// ```
+pub fn enumerate_attachments_for_scan(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+) -> Result<Vec<ScanTarget>, ParseError> {
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(vec![]);
+  };
+
+  Ok(
+    index
+      .descendants(&index.root_block_id)
+      .into_iter()
+      .filter_map(|block_id| {
+        let block = index.get(&block_id)?;
+        let flavour =
+          get_flavour(block).filter(|flavour| SCANNED_FLAVOURS.contains(&flavour.as_str()))?;
+        Some(ScanTarget {
+          blob_id: get_string(block, "prop:sourceId")?,
+          name: get_string(block, "prop:name"),
+          mime_type: get_string(block, "prop:type"),
+          size: declared_size(block),
+          block_id,
+          flavour,
+        })
+      })
+      .collect(),
+  )
+}
+
+/// Replaces the attachments and images embedding one of `blob_ids` with a
+/// paragraph saying the file was quarantined, and returns the changes as an
+/// update.
+///
+/// Placeholders keep the id of the block they replace, so links to it still
+/// resolve, and no longer reference the blob, so it can be deleted once no
+/// other doc embeds it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::quarantine_attachments;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let result =
+///   quarantine_attachments(doc_bin, "doc-id".into(), &["infected-blob".into()]).unwrap();
+/// println!("quarantined {} blocks", result.quarantined_block_ids.len());
+/// ```
+pub fn quarantine_attachments(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  blob_ids: &[String],
+) -> Result<QuarantineResult, ParseError> {
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(QuarantineResult {
+      update: vec![],
+      quarantined_block_ids: vec![],
+    });
+  };
+  let state_vector = index.doc().get_state_vector();
+  let blob_ids = blob_ids.iter().map(String::as_str).collect::<HashSet<_>>();
+  let mut quarantined = BTreeSet::new();
+
+  for (block_id, block) in &index.block_pool {
+    let scanned =
+      get_flavour(block).is_some_and(|flavour| SCANNED_FLAVOURS.contains(&flavour.as_str()));
+    let flagged =
+      get_string(block, "prop:sourceId").is_some_and(|blob_id| blob_ids.contains(blob_id.as_str()));
+    if !scanned || !flagged {
+      continue;
+    }
+
+    let message = match get_string(block, "prop:name") {
+      Some(name) => format!("\"{name}\" was removed because it failed a virus scan."),
+      None => "This file was removed because it failed a virus scan.".to_string(),
+    };
+    let mut block = block.clone();
+    let props = block
+      .iter()
+      .map(|(key, _)| key.to_string())
+      .filter(|key| key.starts_with("prop:"))
+      .collect::<Vec<_>>();
+    for key in props {
+      block.remove(&key);
+    }
+    block.insert("sys:flavour".into(), "affine:paragraph")?;
+    block.insert("sys:version".into(), Any::Integer(1))?;
+    block.insert("prop:type".into(), "quote")?;
+    insert_text(index.doc(), &mut block, "prop:text", &message)?;
+    quarantined.insert(block_id.clone());
+  }
+
+  Ok(QuarantineResult {
+    update: index.doc().encode_state_as_update_v1(&state_vector)?,
+    quarantined_block_ids: quarantined.into_iter().collect(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_to_markdown, test_utils::TestDoc};
+
+  #[test]
+  fn test_quarantine_attachments() {
+    let mut doc = TestDoc::new("Shared files");
+    doc.add_block(
+      "note",
+      "a1",
+      "affine:attachment",
+      &[
+        ("sourceId", "infected"),
+        ("name", "invoice.pdf"),
+        ("type", "application/pdf"),
+        ("size", "2048"),
+      ],
+    );
+    doc.add_block("note", "i1", "affine:image", &[("sourceId", "clean")]);
+    doc.add_block("note", "i2", "affine:image", &[("sourceId", "infected")]);
+    let doc_bin = doc.encode();
+
+    let targets = enumerate_attachments_for_scan(doc_bin.clone(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(
+      targets[0],
+      ScanTarget {
+        block_id: "a1".into(),
+        flavour: "affine:attachment".into(),
+        blob_id: "infected".into(),
+        name: Some("invoice.pdf".into()),
+        mime_type: Some("application/pdf".into()),
+        size: Some(2048),
+      }
+    );
+    assert_eq!(
+      targets
+        .iter()
+        .map(|target| target.block_id.as_str())
+        .collect::<Vec<_>>(),
+      vec!["a1", "i1", "i2"]
+    );
+
+    let result = quarantine_attachments(
+      doc_bin.clone(),
+      TestDoc::DOC_ID.into(),
+      &["infected".into()],
+    )
+    .unwrap();
+    assert_eq!(result.quarantined_block_ids, vec!["a1", "i2"]);
+
+    let mut doc = y_octo::DocOptions::new()
+      .with_guid(TestDoc::DOC_ID.to_string())
+      .build();
+    doc.apply_update_from_binary_v1(&doc_bin).unwrap();
+    doc.apply_update_from_binary_v1(&result.update).unwrap();
+    let quarantined = doc.encode_update_v1().unwrap();
+
+    let targets =
+      enumerate_attachments_for_scan(quarantined.clone(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].blob_id, "clean");
+    let markdown = parse_doc_to_markdown(quarantined, TestDoc::DOC_ID.into(), false)
+      .unwrap()
+      .markdown;
+    assert!(
+      markdown.contains("> \"invoice.pdf\" was removed because it failed a virus scan."),
+      "{markdown}"
+    );
+
+    let result =
+      quarantine_attachments(doc_bin, TestDoc::DOC_ID.into(), &["other".into()]).unwrap();
+    assert!(result.quarantined_block_ids.is_empty());
+  }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use y_octo::Any;
+
+use super::{block_index::BlockIndex, embed_ref_payload, get_flavour, get_string, ParseError};
+
+/// How a block refers to another doc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceKind {
+  /// An `affine:embed-linked-doc` card.
+  Linked,
+  /// An `affine:embed-synced-doc` block showing the other doc inline.
+  Synced,
+  /// A doc mention in the text of a block.
+  Inline,
+}
+
+impl ReferenceKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ReferenceKind::Linked => "linked",
+      ReferenceKind::Synced => "synced",
+      ReferenceKind::Inline => "inline",
+    }
+  }
+}
+
+/// A reference from a block of one doc to another doc.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReferenceEdge {
+  pub source_doc_id: String,
+  pub source_block_id: String,
+  pub target_doc_id: String,
+  pub kind: ReferenceKind,
+  /// The `ref_info` the crawl reports for embeds, the target doc and the
+  /// params of the embed as JSON. `None` for inline references.
+  pub ref_info: Option<String>,
+}
+
+/// Lists the references between the docs of a workspace, to answer
+/// backlink and forward link queries without crawling every doc in JS.
+///
+/// Embedded docs are reported like the crawl reports them in `ref_doc_id`
+/// and `ref_info`, doc mentions in text are reported too. A block mentioning
+/// the same doc several times yields one edge. Targets are not checked
+/// against `docs`, so references to deleted or unknown docs are kept.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::build_reference_graph;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// for edge in build_reference_graph(vec![("doc-id".into(), doc_bin)]).unwrap() {
+///   println!("{} -> {} ({})", edge.source_doc_id, edge.target_doc_id, edge.kind.as_str());
+/// }
+/// ```
+pub fn build_reference_graph(
+  docs: Vec<(String, Vec<u8>)>,
+) -> Result<Vec<ReferenceEdge>, ParseError> {
+  let mut edges = Vec::new();
+  for (doc_id, doc_bin) in docs {
+    let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+      continue;
+    };
+
+    let mut seen = HashSet::new();
+    for block_id in index.descendants(&index.root_block_id) {
+      let Some(block) = index.get(&block_id) else {
+        continue;
+      };
+
+      let embed = match get_flavour(block).as_deref() {
+        Some("affine:embed-linked-doc") => Some(ReferenceKind::Linked),
+        Some("affine:embed-synced-doc") => Some(ReferenceKind::Synced),
+        _ => None,
+      };
+      if let Some(kind) = embed {
+        if let Some(page_id) = get_string(block, "prop:pageId") {
+          edges.push(ReferenceEdge {
+            source_doc_id: doc_id.clone(),
+            source_block_id: block_id.clone(),
+            ref_info: embed_ref_payload(block, &page_id),
+            target_doc_id: page_id,
+            kind,
+          });
+        }
+        continue;
+      }
+
+      let Some(text) = block.get("prop:text").and_then(|value| value.to_text()) else {
+        continue;
+      };
+      for op in text.to_delta() {
+        let Some(Any::Object(reference)) = op.attributes.get("reference") else {
+          continue;
+        };
+        let Some(Any::String(page_id)) = reference.get("pageId") else {
+          continue;
+        };
+        if seen.insert((block_id.clone(), page_id.clone())) {
+          edges.push(ReferenceEdge {
+            source_doc_id: doc_id.clone(),
+            source_block_id: block_id.clone(),
+            target_doc_id: page_id.clone(),
+            kind: ReferenceKind::Inline,
+            ref_info: None,
+          });
+        }
+      }
+    }
+  }
+  Ok(edges)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_build_reference_graph() {
+    let mut source = TestDoc::new("Source");
+    source.add_block(
+      "note",
+      "linked",
+      "affine:embed-linked-doc",
+      &[("pageId", "target")],
+    );
+    source.add_block(
+      "note",
+      "synced",
+      "affine:embed-synced-doc",
+      &[("pageId", "target")],
+    );
+    let paragraph = source.add_block("note", "p1", "affine:paragraph", &[("text", "see ")]);
+    let mut text = paragraph
+      .get("prop:text")
+      .and_then(|value| value.to_text())
+      .unwrap();
+    let reference = Any::Object(
+      [("pageId".to_string(), Any::String("other".into()))]
+        .into_iter()
+        .collect(),
+    );
+    for _ in 0..2 {
+      text
+        .push_with_attributes(
+          " ",
+          [("reference".to_string(), reference.clone())]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap();
+    }
+
+    let target = TestDoc::new("Target");
+    let edges = build_reference_graph(vec![
+      ("source".into(), source.encode()),
+      ("target".into(), target.encode()),
+    ])
+    .unwrap();
+
+    let summary = edges
+      .iter()
+      .map(|edge| {
+        (
+          edge.source_block_id.as_str(),
+          edge.target_doc_id.as_str(),
+          edge.kind,
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      summary,
+      vec![
+        ("linked", "target", ReferenceKind::Linked),
+        ("synced", "target", ReferenceKind::Synced),
+        ("p1", "other", ReferenceKind::Inline),
+      ]
+    );
+    assert!(edges.iter().all(|edge| edge.source_doc_id == "source"));
+    assert_eq!(edges[0].ref_info.as_deref(), Some(r#"{"docId":"target"}"#));
+  }
+}
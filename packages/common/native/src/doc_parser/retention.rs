@@ -0,0 +1,261 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+  options::{parse_options, OptionsObject},
+  root_meta::{timestamp, RootDoc},
+  ParseError,
+};
+
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+/// Retention rules of a workspace. Ages are counted in whole days since the
+/// last edit of a doc, or since it was moved to the trash.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct RetentionPolicy {
+  pub archive_after_days: Option<u32>,
+  pub delete_after_days: Option<u32>,
+  /// Deletes trashed docs for good once they spent this long in the trash.
+  pub purge_trash_after_days: Option<u32>,
+  /// Tag ids or names that exempt a doc from every rule, e.g. a legal hold.
+  pub exempt_tags: Vec<String>,
+}
+
+impl RetentionPolicy {
+  /// Parses and checks a policy sent as JSON, see [`validate_options`].
+  ///
+  /// [`validate_options`]: super::validate_options
+  pub fn from_json(json: &str) -> Result<Self, ParseError> {
+    parse_options(json)
+  }
+}
+
+impl OptionsObject for RetentionPolicy {
+  fn check(&self) -> Result<(), String> {
+    if self.archive_after_days.is_none()
+      && self.delete_after_days.is_none()
+      && self.purge_trash_after_days.is_none()
+    {
+      return Err("at least one retention rule must be set".into());
+    }
+    if let (Some(archive), Some(delete)) = (self.archive_after_days, self.delete_after_days) {
+      if archive >= delete {
+        return Err("`archiveAfterDays` must be less than `deleteAfterDays`".into());
+      }
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionAction {
+  Archive,
+  Delete,
+}
+
+impl RetentionAction {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      RetentionAction::Archive => "archive",
+      RetentionAction::Delete => "delete",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionReason {
+  /// Not edited for longer than the policy allows.
+  Age,
+  /// In the trash for longer than the policy allows.
+  Trash,
+}
+
+impl RetentionReason {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      RetentionReason::Age => "age",
+      RetentionReason::Trash => "trash",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionDecision {
+  pub doc_id: String,
+  pub action: RetentionAction,
+  pub reason: RetentionReason,
+  /// Whole days since the last edit, or since the doc was trashed.
+  pub days: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionReport {
+  /// Sorted by doc id.
+  pub decisions: Vec<RetentionDecision>,
+  /// Sorted ids of the docs a rule matched but an exempt tag protects.
+  pub exempted: Vec<String>,
+}
+
+/// Finds the docs of a workspace that its retention policy archives or
+/// deletes as of `now`, in milliseconds since the Unix epoch.
+///
+/// Dates are read from the page meta of the root doc. `last_edits` maps doc
+/// ids to the time of their last stored update and takes precedence over the
+/// meta, which clients may not keep current. Docs without any date are left
+/// alone. Trashed docs are only subject to the trash rule.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::collections::HashMap;
+///
+/// use affine_common::doc_parser::{evaluate_retention, RetentionPolicy};
+///
+/// let root_doc_bin = std::fs::read("fixtures/root.ydoc").unwrap();
+/// let policy = RetentionPolicy::from_json(r#"{ "purgeTrashAfterDays": 30 }"#).unwrap();
+/// let now = 1_700_000_000_000;
+/// let report = evaluate_retention(root_doc_bin, &HashMap::new(), &policy, now).unwrap();
+/// ```
+pub fn evaluate_retention(
+  root_doc_bin: Vec<u8>,
+  last_edits: &HashMap<String, i64>,
+  policy: &RetentionPolicy,
+  now: i64,
+) -> Result<RetentionReport, ParseError> {
+  let root = RootDoc::decode(&root_doc_bin)?;
+
+  let exempt_tags = exempt_tag_ids(&root.tags()?, &policy.exempt_tags);
+  let mut report = RetentionReport::default();
+  for (meta, page) in root.pages()? {
+    let days_since = |since: Option<i64>| since.map(|since| ((now - since) / DAY_MILLIS).max(0));
+
+    let decision = if meta.trash {
+      let days = days_since(timestamp(page.get("trashDate")));
+      days
+        .zip(policy.purge_trash_after_days)
+        .filter(|(days, limit)| *days >= *limit as i64)
+        .map(|(days, _)| (RetentionAction::Delete, RetentionReason::Trash, days))
+    } else {
+      let last_edit = last_edits
+        .get(&meta.doc_id)
+        .copied()
+        .or(meta.updated_date)
+        .or(meta.create_date);
+      days_since(last_edit).and_then(|days| {
+        let exceeds = |limit: Option<u32>| limit.is_some_and(|limit| days >= limit as i64);
+        if exceeds(policy.delete_after_days) {
+          Some((RetentionAction::Delete, RetentionReason::Age, days))
+        } else if exceeds(policy.archive_after_days) {
+          Some((RetentionAction::Archive, RetentionReason::Age, days))
+        } else {
+          None
+        }
+      })
+    };
+    let Some((action, reason, days)) = decision else {
+      continue;
+    };
+
+    if meta.tags.iter().any(|tag| exempt_tags.contains(tag)) {
+      report.exempted.push(meta.doc_id);
+    } else {
+      report.decisions.push(RetentionDecision {
+        doc_id: meta.doc_id,
+        action,
+        reason,
+        days: days.min(u32::MAX as i64) as u32,
+      });
+    }
+  }
+
+  report.decisions.sort_by(|a, b| a.doc_id.cmp(&b.doc_id));
+  report.exempted.sort();
+  Ok(report)
+}
+
+/// Resolves exempt tags given by name to their ids. Tags given by id are
+/// kept as is.
+fn exempt_tag_ids(tags: &[(String, String)], exempt_tags: &[String]) -> HashSet<String> {
+  let mut ids = exempt_tags.iter().cloned().collect::<HashSet<_>>();
+  ids.extend(
+    tags
+      .iter()
+      .filter(|(_, name)| exempt_tags.contains(name))
+      .map(|(id, _)| id.clone()),
+  );
+  ids
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestRootDoc;
+
+  const NOW: i64 = 1_000 * DAY_MILLIS;
+
+  #[test]
+  fn test_evaluate_retention() {
+    // pages as `(id, trashed, days since updated or trashed, tags)`
+    let mut root = TestRootDoc::new();
+    for (id, trashed, days, tags) in [
+      ("fresh", false, 10, &[][..]),
+      ("stale", false, 100, &[]),
+      ("ancient", false, 400, &[]),
+      ("held", false, 400, &["t-hold"]),
+      ("edited", false, 400, &[]),
+      ("binned", true, 31, &[]),
+      ("just-binned", true, 2, &[]),
+    ] {
+      let date = (NOW - days * DAY_MILLIS) as f64;
+      let key = if trashed { "trashDate" } else { "updatedDate" };
+      root.add_page(id, tags, &[("trash", trashed.into()), (key, date.into())]);
+    }
+    root.set_tags(&[("t-hold", "legal hold")]);
+    let last_edits = HashMap::from([("edited".to_string(), NOW - DAY_MILLIS)]);
+    let policy = RetentionPolicy::from_json(
+      r#"{
+        "archiveAfterDays": 90,
+        "deleteAfterDays": 365,
+        "purgeTrashAfterDays": 30,
+        "exemptTags": ["legal hold"]
+      }"#,
+    )
+    .unwrap();
+
+    let report = evaluate_retention(root.encode(), &last_edits, &policy, NOW).unwrap();
+    let decision = |doc_id: &str, action, reason, days| RetentionDecision {
+      doc_id: doc_id.into(),
+      action,
+      reason,
+      days,
+    };
+    assert_eq!(
+      report.decisions,
+      vec![
+        decision(
+          "ancient",
+          RetentionAction::Delete,
+          RetentionReason::Age,
+          400
+        ),
+        decision(
+          "binned",
+          RetentionAction::Delete,
+          RetentionReason::Trash,
+          31
+        ),
+        decision("stale", RetentionAction::Archive, RetentionReason::Age, 100),
+      ]
+    );
+    assert_eq!(report.exempted, vec!["held"]);
+
+    assert!(RetentionPolicy::from_json("{}").is_err());
+    assert!(
+      RetentionPolicy::from_json(r#"{ "archiveAfterDays": 30, "deleteAfterDays": 30 }"#).is_err()
+    );
+  }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use y_octo::{Any, Doc, Map, Value};
+
+use super::{folder_tree::folder_records, get_string, new_doc, ParseError};
+
+/// Listing metadata of a doc, as the workspace root doc records it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocMeta {
+  pub doc_id: String,
+  pub title: String,
+  /// Milliseconds since the Unix epoch.
+  pub create_date: Option<i64>,
+  /// Milliseconds since the Unix epoch.
+  pub updated_date: Option<i64>,
+  /// Tag ids, in the order of the doc.
+  pub tags: Vec<String>,
+  pub favorite: bool,
+  pub trash: bool,
+  /// Id of the folder the doc is organized in, only known from the folders
+  /// doc.
+  pub parent_folder_id: Option<String>,
+}
+
+/// Reads the metadata of every doc listed in a workspace root doc, so doc
+/// listings do not need to load each doc for its title.
+///
+/// Folders are not part of the root doc but of the workspace folders doc
+/// (`db$folders`), pass it as `folders_doc_bin` to fill
+/// [`DocMeta::parent_folder_id`]. Trashed docs are included with `trash`
+/// set.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::parse_root_doc_meta;
+///
+/// let root_doc_bin = std::fs::read("fixtures/root.ydoc").unwrap();
+/// for meta in parse_root_doc_meta(root_doc_bin, None).unwrap() {
+///   println!("{}: {}", meta.doc_id, meta.title);
+/// }
+/// ```
+pub fn parse_root_doc_meta(
+  doc_bin: Vec<u8>,
+  folders_doc_bin: Option<Vec<u8>>,
+) -> Result<Vec<DocMeta>, ParseError> {
+  let root = RootDoc::decode(&doc_bin)?;
+  let parent_folders = match folders_doc_bin {
+    Some(folders_doc_bin) => parent_folders(&folders_doc_bin)?,
+    None => HashMap::new(),
+  };
+
+  Ok(
+    root
+      .pages()?
+      .into_iter()
+      .map(|(mut meta, _)| {
+        meta.parent_folder_id = parent_folders.get(&meta.doc_id).cloned();
+        meta
+      })
+      .collect(),
+  )
+}
+
+/// A decoded workspace root doc, for readers of the page meta that need more
+/// than [`parse_root_doc_meta`] returns.
+pub(super) struct RootDoc {
+  doc: Doc,
+}
+
+impl RootDoc {
+  pub(super) fn decode(doc_bin: &[u8]) -> Result<Self, ParseError> {
+    if doc_bin.is_empty() || doc_bin == [0, 0] {
+      return Err(ParseError::InvalidBinary);
+    }
+    let mut doc = new_doc(None);
+    doc
+      .apply_update_from_binary_v1(doc_bin)
+      .map_err(|_| ParseError::InvalidBinary)?;
+    Ok(Self { doc })
+  }
+
+  pub(super) fn doc(&self) -> &Doc {
+    &self.doc
+  }
+
+  /// Listed docs in their order, with the page entry for fields [`DocMeta`]
+  /// does not keep. Entries without a doc id are skipped.
+  pub(super) fn pages(&self) -> Result<Vec<(DocMeta, Map)>, ParseError> {
+    let meta = self.doc.get_map("meta")?;
+    Ok(
+      meta
+        .get("pages")
+        .and_then(|pages| pages.to_array())
+        .into_iter()
+        .flat_map(|pages| pages.iter().collect::<Vec<_>>())
+        .filter_map(|page| page.to_map())
+        .filter_map(|page| {
+          let meta = DocMeta {
+            doc_id: get_string(&page, "id")?,
+            title: get_string(&page, "title").unwrap_or_default(),
+            create_date: timestamp(page.get("createDate")),
+            updated_date: timestamp(page.get("updatedDate")),
+            tags: page_tags(&page),
+            favorite: flag(&page, "favorite"),
+            trash: flag(&page, "trash"),
+            parent_folder_id: None,
+          };
+          Some((meta, page))
+        })
+        .collect(),
+    )
+  }
+
+  /// Workspace tags as `(id, name)`, in the order of their options.
+  pub(super) fn tags(&self) -> Result<Vec<(String, String)>, ParseError> {
+    let meta = self.doc.get_map("meta")?;
+    Ok(
+      meta
+        .get("properties")
+        .and_then(|properties| properties.to_map())
+        .and_then(|properties| properties.get("tags"))
+        .and_then(|tags| tags.to_map())
+        .and_then(|tags| tags.get("options"))
+        .and_then(|options| options.to_array())
+        .into_iter()
+        .flat_map(|options| options.iter().collect::<Vec<_>>())
+        .filter_map(|option| option.to_map())
+        .filter_map(|option| Some((get_string(&option, "id")?, get_string(&option, "value")?)))
+        .collect(),
+    )
+  }
+}
+
+/// Reads a meta date, in milliseconds since the Unix epoch.
+pub(super) fn timestamp(value: Option<Value>) -> Option<i64> {
+  match value?.to_any()? {
+    Any::Integer(value) => Some(value as i64),
+    Any::BigInt64(value) => Some(value),
+    Any::Float32(value) => Some(value.0 as i64),
+    Any::Float64(value) => Some(value.0 as i64),
+    _ => None,
+  }
+}
+
+fn flag(page: &Map, key: &str) -> bool {
+  matches!(
+    page.get(key).and_then(|value| value.to_any()),
+    Some(Any::True)
+  )
+}
+
+fn page_tags(page: &Map) -> Vec<String> {
+  page
+    .get("tags")
+    .and_then(|tags| tags.to_array())
+    .into_iter()
+    .flat_map(|tags| tags.iter().collect::<Vec<_>>())
+    .filter_map(|tag| match tag.to_any() {
+      Some(Any::String(tag)) => Some(tag),
+      _ => None,
+    })
+    .collect()
+}
+
+/// Folder of every doc in a folders doc.
+fn parent_folders(folders_doc_bin: &[u8]) -> Result<HashMap<String, String>, ParseError> {
+  Ok(
+    folder_records(folders_doc_bin)?
+      .into_iter()
+      .filter(|record| record.kind == "doc")
+      .filter_map(|record| Some((record.data, record.parent_id?)))
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::{folders_doc, TestRootDoc};
+
+  fn root_doc() -> Vec<u8> {
+    let mut root = TestRootDoc::new();
+    for (id, title, trash) in [("plan", "Plan", false), ("gone", "Old", true)] {
+      root.add_page(
+        id,
+        &["tag-1"],
+        &[
+          ("title", title.into()),
+          ("trash", trash.into()),
+          ("favorite", (!trash).into()),
+          ("createDate", 1_700_000_000_000f64.into()),
+        ],
+      );
+    }
+    root.encode()
+  }
+
+  #[test]
+  fn test_parse_root_doc_meta() {
+    let metas = parse_root_doc_meta(
+      root_doc(),
+      Some(folders_doc(&[
+        ("f1", None, "folder", "Projects", "a0"),
+        ("l1", Some("f1"), "doc", "plan", "a0"),
+      ])),
+    )
+    .unwrap();
+    assert_eq!(
+      metas[0],
+      DocMeta {
+        doc_id: "plan".into(),
+        title: "Plan".into(),
+        create_date: Some(1_700_000_000_000),
+        updated_date: None,
+        tags: vec!["tag-1".into()],
+        favorite: true,
+        trash: false,
+        parent_folder_id: Some("f1".into()),
+      }
+    );
+    assert_eq!(metas[1].doc_id, "gone");
+    assert!(metas[1].trash && !metas[1].favorite);
+    assert_eq!(metas[1].parent_folder_id, None);
+
+    let metas = parse_root_doc_meta(root_doc(), None).unwrap();
+    assert_eq!(metas[0].parent_folder_id, None);
+    assert!(matches!(
+      parse_root_doc_meta(vec![], None),
+      Err(ParseError::InvalidBinary)
+    ));
+  }
+}
@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+  markdown_reader::read_markdown_blocks, parse_doc_from_binary, parse_doc_to_markdown,
+  ExportFormat, ParseError, PAGE_FLAVOUR,
+};
+
+/// How well blocks of a single flavour survived an export/import round trip.
+///
+/// Every source block is counted exactly once: `preserved` when all of its
+/// text came back under the same flavour, `degraded` when the text came back
+/// under a different flavour, and `lost` when it did not come back at all.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlavourFidelity {
+  pub flavour: String,
+  pub total: usize,
+  pub preserved: usize,
+  pub degraded: usize,
+  pub lost: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundtripReport {
+  pub format: ExportFormat,
+  pub flavours: Vec<FlavourFidelity>,
+  pub lossless: bool,
+}
+
+/// Exports a document to `format`, reads the output back and reports the
+/// information lost per block flavour.
+///
+/// The page block is not part of the report: its title travels next to the
+/// exported body rather than inside it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{roundtrip_check, ExportFormat};
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let report = roundtrip_check(doc_bin, "doc-id".into(), ExportFormat::Markdown).unwrap();
+/// for flavour in report.flavours {
+///   println!("{}: {} lost", flavour.flavour, flavour.lost);
+/// }
+/// ```
+pub fn roundtrip_check(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  format: ExportFormat,
+) -> Result<RoundtripReport, ParseError> {
+  let crawled = parse_doc_from_binary(doc_bin.clone(), doc_id.clone())?;
+
+  let recovered = match format {
+    ExportFormat::Markdown => {
+      let exported = parse_doc_to_markdown(doc_bin, doc_id, false)?;
+      read_markdown_blocks(&exported.markdown)
+        .into_iter()
+        .map(|block| {
+          let texts = block.texts().into_iter().map(str::to_string).collect();
+          (block.flavour(), texts)
+        })
+        .collect::<Vec<(&str, Vec<String>)>>()
+    }
+  };
+
+  let mut recovered_texts: HashMap<String, HashSet<&str>> = HashMap::new();
+  let mut recovered_counts: HashMap<&str, usize> = HashMap::new();
+  for (flavour, texts) in &recovered {
+    *recovered_counts.entry(*flavour).or_default() += 1;
+    for text in texts {
+      let text = text.trim();
+      if !text.is_empty() {
+        recovered_texts
+          .entry(text.to_string())
+          .or_default()
+          .insert(*flavour);
+      }
+    }
+  }
+
+  let mut flavours: BTreeMap<String, FlavourFidelity> = BTreeMap::new();
+  for block in crawled.blocks {
+    if block.flavour == PAGE_FLAVOUR {
+      continue;
+    }
+
+    let texts = block
+      .content
+      .unwrap_or_default()
+      .into_iter()
+      .map(|text| text.trim().to_string())
+      .filter(|text| !text.is_empty())
+      .collect::<Vec<_>>();
+
+    let entry = flavours
+      .entry(block.flavour.clone())
+      .or_insert_with(|| FlavourFidelity {
+        flavour: block.flavour.clone(),
+        ..Default::default()
+      });
+    entry.total += 1;
+
+    if texts.is_empty() {
+      // nothing to compare, fall back to matching the number of blocks
+      match recovered_counts.get_mut(block.flavour.as_str()) {
+        Some(count) if *count > 0 => {
+          *count -= 1;
+          entry.preserved += 1;
+        }
+        _ => entry.lost += 1,
+      }
+      continue;
+    }
+
+    let mut same_flavour = true;
+    let mut found = true;
+    for text in &texts {
+      match recovered_texts.get(text) {
+        Some(found_flavours) => {
+          same_flavour &= found_flavours.contains(block.flavour.as_str());
+        }
+        None => found = false,
+      }
+    }
+
+    if !found {
+      entry.lost += 1;
+    } else if same_flavour {
+      entry.preserved += 1;
+    } else {
+      entry.degraded += 1;
+    }
+  }
+
+  let flavours = flavours.into_values().collect::<Vec<_>>();
+  let lossless = flavours
+    .iter()
+    .all(|fidelity| fidelity.degraded == 0 && fidelity.lost == 0);
+
+  Ok(RoundtripReport {
+    format,
+    flavours,
+    lossless,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_roundtrip_check_markdown() {
+    let doc_bin = include_bytes!("../../fixtures/demo.ydoc").to_vec();
+    let doc_id = "dYpV7PPhk8amRkY5IAcVO".to_string();
+
+    let report = roundtrip_check(doc_bin, doc_id, ExportFormat::Markdown).unwrap();
+    let find = |flavour: &str| {
+      report
+        .flavours
+        .iter()
+        .find(|fidelity| fidelity.flavour == flavour)
+        .cloned()
+        .unwrap()
+    };
+
+    let list = find("affine:list");
+    assert_eq!((list.total, list.preserved), (5, 5));
+
+    // markdown export has no representation for images yet
+    let image = find("affine:image");
+    assert_eq!((image.total, image.lost), (3, 3));
+
+    assert!(!report.lossless);
+  }
+}
@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+use y_octo::{Doc, Map};
+
+use super::{
+  block_index::BlockIndex, format_cell_value, get_string, new_doc, text_content, value_to_string,
+  ParseError, NOTE_FLAVOUR, PAGE_FLAVOUR,
+};
+
+const DATABASE_FLAVOUR: &str = "affine:database";
+const LINKED_DOC_FLAVOUR: &str = "affine:embed-linked-doc";
+/// Name of the column whose cell becomes the body of the expanded doc.
+const DESCRIPTION_COLUMN: &str = "description";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpandedRow {
+  pub title: String,
+  /// Full update of the new doc.
+  pub doc_bin: Vec<u8>,
+  /// Update to apply to the source doc to link the row to the new doc.
+  pub link_update: Vec<u8>,
+}
+
+/// Turns a database row into a standalone doc, like "open row as page" does
+/// in the editor.
+///
+/// The new doc is titled after the row, starts with one list item per
+/// non-empty property and continues with the row's `Description` cell, one
+/// paragraph per line. The row itself gets an `affine:embed-linked-doc`
+/// child pointing at `new_doc_id`; expanding a row that is already linked
+/// to `new_doc_id` leaves the source doc untouched.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::expand_row_to_doc;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let expanded =
+///   expand_row_to_doc(doc_bin, "doc-id".into(), "database-id", "row-id", "new-doc-id").unwrap();
+/// std::fs::write("new-doc.ydoc", expanded.doc_bin).unwrap();
+/// ```
+pub fn expand_row_to_doc(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  database_block_id: &str,
+  row_id: &str,
+  new_doc_id: &str,
+) -> Result<ExpandedRow, ParseError> {
+  let index = BlockIndex::load(&doc_bin, &doc_id)?
+    .ok_or_else(|| ParseError::BlockNotFound(database_block_id.to_string()))?;
+  let database = index
+    .get(database_block_id)
+    .filter(|_| index.flavour_of(database_block_id).as_deref() == Some(DATABASE_FLAVOUR))
+    .ok_or_else(|| ParseError::BlockNotFound(database_block_id.to_string()))?;
+  let row = index
+    .get(row_id)
+    .filter(|_| index.parent_of(row_id).map(String::as_str) == Some(database_block_id))
+    .ok_or_else(|| ParseError::BlockNotFound(row_id.to_string()))?;
+
+  let title = text_content(row, "prop:text")
+    .map(|(text, _)| text)
+    .unwrap_or_default();
+  let (properties, description) = row_properties(database, row_id);
+
+  let doc = new_doc(Some(new_doc_id));
+  let mut blocks = doc.get_or_create_map("blocks")?;
+  let mut page = insert_block(
+    &doc,
+    &mut blocks,
+    &format!("{new_doc_id}-page"),
+    PAGE_FLAVOUR,
+  )?;
+  insert_text(&doc, &mut page, "prop:title", &title)?;
+
+  let note_id = format!("{new_doc_id}-note");
+  let mut note = insert_block(&doc, &mut blocks, &note_id, NOTE_FLAVOUR)?;
+  note.insert("prop:displayMode".into(), "both")?;
+  append_child(&page, &note_id)?;
+
+  let lines = properties
+    .iter()
+    .map(|(name, value)| ("affine:list", format!("{name}: {value}")))
+    .chain(
+      description
+        .iter()
+        .flat_map(|description| description.lines())
+        .map(|line| ("affine:paragraph", line.to_string())),
+    );
+  for (i, (flavour, text)) in lines.enumerate() {
+    let block_id = format!("{new_doc_id}-{i}");
+    let mut block = insert_block(&doc, &mut blocks, &block_id, flavour)?;
+    if flavour == "affine:list" {
+      block.insert("prop:type".into(), "bulleted")?;
+    }
+    insert_text(&doc, &mut block, "prop:text", &text)?;
+    append_child(&note, &block_id)?;
+  }
+
+  let source = index.doc();
+  let state_vector = source.get_state_vector();
+  let link_id = format!("{row_id}-{new_doc_id}");
+  if index.get(&link_id).is_none() {
+    let mut source_blocks = source.get_map("blocks")?;
+    let mut link = insert_block(source, &mut source_blocks, &link_id, LINKED_DOC_FLAVOUR)?;
+    link.insert("prop:pageId".into(), new_doc_id)?;
+    link.insert("prop:title".into(), title.as_str())?;
+    append_child(row, &link_id)?;
+  }
+
+  Ok(ExpandedRow {
+    title,
+    doc_bin: doc.encode_update_v1()?,
+    link_update: source.encode_state_as_update_v1(&state_vector)?,
+  })
+}
+
+/// Collects the row's non-empty cells in column order as `(name, value)`,
+/// splitting off the description cell. The title column is skipped, it
+/// becomes the doc title.
+fn row_properties(database: &Map, row_id: &str) -> (Vec<(String, String)>, Option<String>) {
+  let mut properties = Vec::new();
+  let mut description = None;
+
+  let Some(columns) = database.get("prop:columns").and_then(|v| v.to_array()) else {
+    return (properties, description);
+  };
+  let row_cells = database
+    .get("prop:cells")
+    .and_then(|v| v.to_map())
+    .and_then(|cells| cells.get(row_id))
+    .and_then(|v| v.to_map());
+
+  for column in columns.iter().filter_map(|v| v.to_map()) {
+    let type_ = get_string(&column, "type").unwrap_or_default();
+    if type_ == "title" {
+      continue;
+    }
+    let id = get_string(&column, "id").unwrap_or_default();
+    let name = get_string(&column, "name").unwrap_or_default();
+    let data = column.get("data").and_then(|v| v.to_map());
+
+    let Some(value) = row_cells
+      .as_ref()
+      .and_then(|cells| cells.get(&id))
+      .and_then(|v| v.to_map())
+      .and_then(|cell| cell.get("value"))
+    else {
+      continue;
+    };
+    let value = match value.to_any() {
+      Some(any) => format_cell_value(&any, &type_, data.as_ref()),
+      None => value_to_string(&value).unwrap_or_default(),
+    };
+    if value.is_empty() {
+      continue;
+    }
+
+    if description.is_none() && name.eq_ignore_ascii_case(DESCRIPTION_COLUMN) {
+      description = Some(value);
+    } else {
+      properties.push((name, value));
+    }
+  }
+
+  (properties, description)
+}
+
+pub(crate) fn insert_block(
+  doc: &Doc,
+  blocks: &mut Map,
+  id: &str,
+  flavour: &str,
+) -> Result<Map, ParseError> {
+  let mut block = doc.create_map()?;
+  blocks.insert(id.to_string(), block.clone())?;
+  block.insert("sys:id".into(), id)?;
+  block.insert("sys:flavour".into(), flavour)?;
+  block.insert("sys:children".into(), doc.create_array()?)?;
+  Ok(block)
+}
+
+pub(crate) fn insert_text(
+  doc: &Doc,
+  block: &mut Map,
+  key: &str,
+  content: &str,
+) -> Result<(), ParseError> {
+  let mut text = doc.create_text()?;
+  block.insert(key.to_string(), text.clone())?;
+  text.insert(0, content)?;
+  Ok(())
+}
+
+pub(crate) fn append_child(parent: &Map, child_id: &str) -> Result<(), ParseError> {
+  let mut children = parent
+    .get("sys:children")
+    .and_then(|v| v.to_array())
+    .ok_or_else(|| ParseError::ParserError("block has no children array".into()))?;
+  children.push(child_id)?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_to_markdown, test_utils::TestDoc};
+
+  #[test]
+  fn test_expand_row_to_doc() {
+    let mut doc = TestDoc::new("Tasks");
+    doc.add_block("note", "db", DATABASE_FLAVOUR, &[]);
+    doc.add_block("db", "r1", "affine:paragraph", &[("text", "Write docs")]);
+    doc.set_database_cells(
+      "db",
+      &[
+        ("c0", "Name", "title"),
+        ("c1", "Owner", "text"),
+        ("c2", "Description", "text"),
+      ],
+      &[("r1", &[("c1", "Ann"), ("c2", "First line\nSecond line")])],
+    );
+    let doc_bin = doc.encode();
+
+    let expanded = expand_row_to_doc(
+      doc_bin.clone(),
+      TestDoc::DOC_ID.into(),
+      "db",
+      "r1",
+      "row-doc",
+    )
+    .unwrap();
+    assert_eq!(expanded.title, "Write docs");
+
+    let markdown = parse_doc_to_markdown(expanded.doc_bin, "row-doc".into(), false).unwrap();
+    assert_eq!(markdown.title, "Write docs");
+    assert_eq!(markdown.markdown, "- Owner: Ann\nFirst line\nSecond line\n");
+
+    let mut source = y_octo::DocOptions::new()
+      .with_guid(TestDoc::DOC_ID.to_string())
+      .build();
+    source.apply_update_from_binary_v1(&doc_bin).unwrap();
+    source
+      .apply_update_from_binary_v1(&expanded.link_update)
+      .unwrap();
+    let link = source
+      .get_map("blocks")
+      .unwrap()
+      .get("r1-row-doc")
+      .and_then(|v| v.to_map())
+      .unwrap();
+    assert_eq!(get_string(&link, "prop:pageId").as_deref(), Some("row-doc"));
+
+    assert!(matches!(
+      expand_row_to_doc(doc_bin, TestDoc::DOC_ID.into(), "note", "r1", "row-doc"),
+      Err(ParseError::BlockNotFound(_))
+    ));
+  }
+}
@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+  block_index::BlockIndex, get_string, html::table_rows, markdown::database_rows, text_content,
+  ParseError, PAGE_FLAVOUR,
+};
+
+/// Rows of a table or database kept in a sample, after the header.
+const SAMPLE_TABLE_ROWS: usize = 3;
+
+/// Why a block was picked for a sample. Kinds are filled in this order
+/// until the budget runs out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleKind {
+  Title,
+  Heading,
+  /// The first paragraph after the title or after a heading.
+  Paragraph,
+  /// A header or one of the first rows of a table or database.
+  TableRow,
+}
+
+impl SampleKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      SampleKind::Title => "title",
+      SampleKind::Heading => "heading",
+      SampleKind::Paragraph => "paragraph",
+      SampleKind::TableRow => "table_row",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampledBlock {
+  pub block_id: String,
+  pub flavour: String,
+  pub kind: SampleKind,
+  /// Table rows are their cells joined by ` | `.
+  pub text: String,
+  /// Set when the text was cut to fit the budget, only paragraphs are.
+  pub truncated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocSample {
+  pub title: String,
+  /// Picked blocks in document order.
+  pub blocks: Vec<SampledBlock>,
+  /// Bytes of text in `blocks`, at most the budget.
+  pub size: usize,
+  /// Blocks that would have been picked with a larger budget.
+  pub omitted: usize,
+}
+
+/// Picks a representative subset of a doc whose texts fit in `budget` bytes
+/// of UTF-8, for hover previews and AI context packing.
+///
+/// The title comes first, then the headings, then the first paragraph of
+/// every section, then the header and first rows of every table and
+/// database, each kind in document order. A block that does not fit is
+/// skipped, except paragraphs, which are cut at a char boundary. The same doc
+/// and budget always give the same sample.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::sample_doc;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let sample = sample_doc(doc_bin, "doc-id".into(), 2048).unwrap();
+/// for block in &sample.blocks {
+///   println!("[{}] {}", block.kind.as_str(), block.text);
+/// }
+/// ```
+pub fn sample_doc(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  budget: usize,
+) -> Result<DocSample, ParseError> {
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(DocSample {
+      title: String::new(),
+      blocks: vec![],
+      size: 0,
+      omitted: 0,
+    });
+  };
+
+  let mut title = String::new();
+  let mut candidates = Vec::new();
+  let mut section_has_paragraph = false;
+  for block_id in index.descendants(&index.root_block_id) {
+    let Some(block) = index.get(&block_id) else {
+      continue;
+    };
+    let Some(flavour) = index.flavour_of(&block_id) else {
+      continue;
+    };
+    // database rows are sampled with their database
+    if index
+      .parent_of(&block_id)
+      .and_then(|parent| index.flavour_of(parent))
+      .as_deref()
+      == Some("affine:database")
+    {
+      continue;
+    }
+
+    let mut push = |kind, text: String| {
+      if !text.trim().is_empty() {
+        candidates.push(SampledBlock {
+          block_id: block_id.clone(),
+          flavour: flavour.clone(),
+          kind,
+          text,
+          truncated: false,
+        });
+      }
+    };
+    match flavour.as_str() {
+      PAGE_FLAVOUR => {
+        title = get_string(block, "prop:title").unwrap_or_default();
+        push(SampleKind::Title, title.clone());
+      }
+      "affine:paragraph" => {
+        let Some((text, _)) = text_content(block, "prop:text") else {
+          continue;
+        };
+        let heading = get_string(block, "prop:type")
+          .is_some_and(|type_| matches!(type_.as_str(), "h1" | "h2" | "h3" | "h4" | "h5" | "h6"));
+        if heading {
+          push(SampleKind::Heading, text);
+          section_has_paragraph = false;
+        } else if !section_has_paragraph && !text.trim().is_empty() {
+          push(SampleKind::Paragraph, text);
+          section_has_paragraph = true;
+        }
+      }
+      "affine:table" => {
+        for row in table_rows(block).into_iter().take(SAMPLE_TABLE_ROWS + 1) {
+          push(SampleKind::TableRow, row.join(" | "));
+        }
+      }
+      "affine:database" => {
+        if let Some((header, rows)) = database_rows(&index, block) {
+          for row in std::iter::once(header).chain(rows.into_iter().take(SAMPLE_TABLE_ROWS)) {
+            push(SampleKind::TableRow, row.join(" | "));
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  // fill by kind, then restore document order; the sort is stable so
+  // blocks of a kind stay in document order
+  let mut order = (0..candidates.len()).collect::<Vec<_>>();
+  order.sort_by_key(|&i| candidates[i].kind);
+  let mut picked = vec![false; candidates.len()];
+  let mut remaining = budget;
+  let mut omitted = 0;
+  for i in order {
+    let candidate = &mut candidates[i];
+    if candidate.text.len() > remaining {
+      if candidate.kind != SampleKind::Paragraph || remaining == 0 {
+        omitted += 1;
+        continue;
+      }
+      let mut end = remaining;
+      while !candidate.text.is_char_boundary(end) {
+        end -= 1;
+      }
+      if end == 0 {
+        omitted += 1;
+        continue;
+      }
+      candidate.text.truncate(end);
+      candidate.truncated = true;
+    }
+    remaining -= candidate.text.len();
+    picked[i] = true;
+  }
+
+  let blocks = candidates
+    .into_iter()
+    .zip(picked)
+    .filter_map(|(candidate, picked)| picked.then_some(candidate))
+    .collect();
+  Ok(DocSample {
+    title,
+    blocks,
+    size: budget - remaining,
+    omitted,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_sample_doc() {
+    let mut doc = TestDoc::new("Guide");
+    for (id, type_, text) in [
+      ("a", "text", "Intro paragraph."),
+      ("b", "text", "Second intro paragraph."),
+      ("c", "h2", "Setup"),
+      ("d", "text", "Install the package first."),
+      ("e", "h2", "Usage"),
+    ] {
+      doc.add_block(
+        "note",
+        id,
+        "affine:paragraph",
+        &[("type", type_), ("text", text)],
+      );
+    }
+    doc.add_block("note", "db", "affine:database", &[("title", "Tasks")]);
+    doc.add_block("db", "r1", "affine:paragraph", &[("text", "Write")]);
+    doc.set_database_cells(
+      "db",
+      &[("c0", "Name", "title"), ("c1", "Owner", "text")],
+      &[("r1", &[("c1", "Ann")])],
+    );
+    let doc_bin = doc.encode();
+
+    let sample = sample_doc(doc_bin.clone(), TestDoc::DOC_ID.into(), 1024).unwrap();
+    assert_eq!(sample.title, "Guide");
+    assert_eq!(
+      sample
+        .blocks
+        .iter()
+        .map(|block| (block.block_id.as_str(), block.text.as_str()))
+        .collect::<Vec<_>>(),
+      [
+        ("page", "Guide"),
+        ("a", "Intro paragraph."),
+        ("c", "Setup"),
+        ("d", "Install the package first."),
+        ("e", "Usage"),
+        ("db", "Name | Owner"),
+        ("db", "Write | Ann"),
+      ]
+    );
+    assert_eq!(sample.omitted, 0);
+
+    // title and headings take 15 bytes, the intro paragraph gets the rest
+    let sample = sample_doc(doc_bin, TestDoc::DOC_ID.into(), 25).unwrap();
+    assert_eq!(
+      sample
+        .blocks
+        .iter()
+        .map(|block| (block.kind, block.text.as_str(), block.truncated))
+        .collect::<Vec<_>>(),
+      [
+        (SampleKind::Title, "Guide", false),
+        (SampleKind::Paragraph, "Intro para", true),
+        (SampleKind::Heading, "Setup", false),
+        (SampleKind::Heading, "Usage", false),
+      ]
+    );
+    assert_eq!(sample.size, 25);
+    assert_eq!(sample.omitted, 3);
+  }
+}
@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use y_octo::Any;
+
+use super::{get_flavour, is_builtin_flavour, new_doc, ParseError};
+
+/// `sys:version`s of the built-in flavours the parser reads, as
+/// `(flavour, min, max)`. Raise the max together with the parsing code when
+/// a block schema changes.
+const SCHEMA_VERSIONS: [(&str, i64, i64); 18] = [
+  ("affine:page", 1, 2),
+  ("affine:note", 1, 1),
+  ("affine:surface", 1, 5),
+  ("affine:paragraph", 1, 1),
+  ("affine:list", 1, 1),
+  ("affine:code", 1, 1),
+  ("affine:embed-linked-doc", 1, 1),
+  ("affine:embed-synced-doc", 1, 1),
+  ("affine:attachment", 1, 1),
+  ("affine:image", 1, 1),
+  ("affine:database", 1, 3),
+  ("affine:latex", 1, 1),
+  ("affine:table", 1, 1),
+  ("affine:bookmark", 1, 1),
+  ("affine:embed-youtube", 1, 1),
+  ("affine:embed-figma", 1, 1),
+  ("affine:embed-github", 1, 1),
+  ("affine:embed-loom", 1, 1),
+];
+
+/// Block schema versions of a flavour the parser reads.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaRange {
+  pub flavour: String,
+  pub min_version: i64,
+  pub max_version: i64,
+}
+
+/// Blocks of a flavour stored with a schema version outside its range.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaMismatch {
+  pub flavour: String,
+  pub version: i64,
+  pub block_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaCheck {
+  pub supported: bool,
+  /// Sorted by flavour, then version.
+  pub mismatches: Vec<SchemaMismatch>,
+}
+
+/// Returns the block schema versions this parser reads, one range per
+/// built-in flavour, for mixed-version deployments to compare workers.
+/// Other flavours have no version requirement, their blocks are read through
+/// [`super::GenericFlavourHandler`].
+pub fn get_supported_schema_range() -> Vec<SchemaRange> {
+  SCHEMA_VERSIONS
+    .iter()
+    .map(|(flavour, min_version, max_version)| SchemaRange {
+      flavour: flavour.to_string(),
+      min_version: *min_version,
+      max_version: *max_version,
+    })
+    .collect()
+}
+
+/// Checks the `sys:version` of every block against
+/// [`get_supported_schema_range`] without parsing the doc, so job routers
+/// can send docs written by newer clients to upgraded workers instead of
+/// failing mid-parse.
+///
+/// Blocks without a version and blocks of flavours that are not built in
+/// are accepted.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::can_parse;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// if !can_parse(&doc_bin).unwrap().supported {
+///   println!("route to an upgraded worker");
+/// }
+/// ```
+pub fn can_parse(doc_bin: &[u8]) -> Result<SchemaCheck, ParseError> {
+  if doc_bin.is_empty() || doc_bin == [0, 0] {
+    return Err(ParseError::InvalidBinary);
+  }
+  let mut doc = new_doc(None);
+  doc
+    .apply_update_from_binary_v1(doc_bin)
+    .map_err(|_| ParseError::InvalidBinary)?;
+
+  let mut mismatches = BTreeMap::<(String, i64), usize>::new();
+  for (_, value) in doc.get_map("blocks")?.iter() {
+    let Some(block) = value.to_map() else {
+      continue;
+    };
+    let Some(flavour) = get_flavour(&block).filter(|flavour| is_builtin_flavour(flavour)) else {
+      continue;
+    };
+    let version = match block.get("sys:version").and_then(|value| value.to_any()) {
+      Some(Any::Integer(version)) => version as i64,
+      Some(Any::BigInt64(version)) => version,
+      Some(Any::Float64(version)) => version.0 as i64,
+      _ => continue,
+    };
+    let supported = SCHEMA_VERSIONS
+      .iter()
+      .find(|(name, ..)| *name == flavour)
+      .is_some_and(|(_, min, max)| (*min..=*max).contains(&version));
+    if !supported {
+      *mismatches.entry((flavour, version)).or_default() += 1;
+    }
+  }
+
+  Ok(SchemaCheck {
+    supported: mismatches.is_empty(),
+    mismatches: mismatches
+      .into_iter()
+      .map(|((flavour, version), block_count)| SchemaMismatch {
+        flavour,
+        version,
+        block_count,
+      })
+      .collect(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{test_utils::TestDoc, BUILTIN_FLAVOURS};
+
+  #[test]
+  fn test_schema_versions_cover_builtin_flavours() {
+    let ranges = get_supported_schema_range();
+    for flavour in BUILTIN_FLAVOURS {
+      assert!(
+        ranges.iter().any(|range| range.flavour == flavour),
+        "{flavour} has no schema range"
+      );
+    }
+  }
+
+  #[test]
+  fn test_can_parse() {
+    let doc_bin = include_bytes!("../../fixtures/demo.ydoc");
+    let check = can_parse(doc_bin).unwrap();
+    assert!(check.supported);
+    assert!(check.mismatches.is_empty());
+
+    let mut doc = TestDoc::new("Newer");
+    for id in ["p1", "p2"] {
+      doc
+        .add_block("note", id, "affine:paragraph", &[("text", "future")])
+        .insert("sys:version".into(), Any::Integer(2))
+        .unwrap();
+    }
+    doc
+      .add_block("note", "w1", "acme:widget", &[])
+      .insert("sys:version".into(), Any::Integer(7))
+      .unwrap();
+
+    let check = can_parse(&doc.encode()).unwrap();
+    assert!(!check.supported);
+    assert_eq!(
+      check.mismatches,
+      vec![SchemaMismatch {
+        flavour: "affine:paragraph".into(),
+        version: 2,
+        block_count: 2,
+      }]
+    );
+
+    assert!(matches!(can_parse(&[]), Err(ParseError::InvalidBinary)));
+  }
+}
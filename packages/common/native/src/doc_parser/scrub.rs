@@ -0,0 +1,307 @@
+use std::{
+  collections::{BTreeMap, HashSet},
+  ops::Range,
+};
+
+use regex::Regex;
+use y_octo::{Client, ClientMap, Clock, Id, Update};
+
+use super::ParseError;
+
+/// Replaces every UTF-16 unit of a secret, so items keep their length.
+const MASK: char = '*';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrubResult {
+  pub snapshot: Vec<u8>,
+  /// In the order they were given.
+  pub updates: Vec<Vec<u8>>,
+  /// Occurrences of the patterns found in the history. Text present in
+  /// several binaries is counted once.
+  pub matches: usize,
+}
+
+/// Masks text matching any of `patterns`, regular expressions such as
+/// `sk-[A-Za-z0-9]{32}`, in a doc snapshot and in every stored update of
+/// the doc, so secrets pasted by mistake cannot be recovered from any
+/// version of its history.
+///
+/// Matched text is replaced by `*` in place rather than deleted. Ids and
+/// lengths of items are unchanged, so the rewritten updates still apply on
+/// top of each other, and clients that synced the original history merge
+/// with the rewritten one; only history stored by this call is scrubbed.
+/// Binaries without a match are returned as they were given.
+///
+/// Strings held in values, such as link attributes, embeds and map entries,
+/// are matched one by one and masked the same way.
+///
+/// Text is matched per client, over runs of strings each inserted right
+/// after the previous one, which covers text typed or pasted in one go even
+/// when it was later deleted or spread over several updates. Secrets
+/// assembled by several clients, or by editing inside text inserted
+/// earlier, are not found.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::scrub_history;
+///
+/// let snapshot = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let updates = vec![std::fs::read("fixtures/update.bin").unwrap()];
+/// let patterns = vec![r"sk-[A-Za-z0-9]{32}".to_string()];
+/// let scrubbed = scrub_history(snapshot, updates, &patterns).unwrap();
+/// ```
+pub fn scrub_history(
+  snapshot: Vec<u8>,
+  updates: Vec<Vec<u8>>,
+  patterns: &[String],
+) -> Result<ScrubResult, ParseError> {
+  let patterns = patterns
+    .iter()
+    .map(|pattern| {
+      Regex::new(pattern)
+        .map_err(|e| ParseError::InvalidOptions(format!("invalid pattern `{pattern}`: {e}")))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let mut decoded = std::iter::once(&snapshot)
+    .chain(&updates)
+    .map(|bin| Update::decode_v1(bin).map_err(|_| ParseError::InvalidBinary))
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let (ranges, matches) = secret_ranges(&decoded, &patterns);
+  let matches = matches + value_matches(&decoded, &patterns);
+  if matches == 0 {
+    return Ok(ScrubResult {
+      snapshot,
+      updates,
+      matches,
+    });
+  }
+
+  let mut scrubbed = Vec::with_capacity(decoded.len());
+  for (update, bin) in decoded
+    .iter_mut()
+    .zip(std::iter::once(snapshot).chain(updates))
+  {
+    let masked = update.mask_strings(&ranges, MASK)
+      + update.mask_values(|string| mask_matches(string, &patterns));
+    if masked > 0 {
+      scrubbed.push(update.encode_v1()?);
+    } else {
+      scrubbed.push(bin);
+    }
+  }
+  let snapshot = scrubbed.remove(0);
+
+  Ok(ScrubResult {
+    snapshot,
+    updates: scrubbed,
+    matches,
+  })
+}
+
+/// Clock ranges of the matched text per client, and the number of matches.
+fn secret_ranges(updates: &[Update], patterns: &[Regex]) -> (ClientMap<Vec<Range<Clock>>>, usize) {
+  // the same string may be stored in several updates, split differently
+  let mut strings: BTreeMap<Client, BTreeMap<Clock, (Option<Id>, &str)>> = BTreeMap::new();
+  for (id, origin, string) in updates.iter().flat_map(|update| update.strings()) {
+    let stored = strings
+      .entry(id.client)
+      .or_default()
+      .entry(id.clock)
+      .or_insert((origin, string));
+    if string.len() > stored.1.len() {
+      *stored = (origin, string);
+    }
+  }
+
+  let mut ranges = ClientMap::default();
+  let mut matches = 0;
+  for (client, strings) in strings {
+    for (start, run) in runs(client, strings) {
+      for pattern in patterns {
+        for found in pattern.find_iter(&run) {
+          if found.is_empty() {
+            continue;
+          }
+          let clock = |offset: usize| start + utf16_len(&run[..offset]);
+          ranges
+            .entry(client)
+            .or_insert_with(Vec::new)
+            .push(clock(found.start())..clock(found.end()));
+          matches += 1;
+        }
+      }
+    }
+  }
+  (ranges, matches)
+}
+
+/// Number of matches in strings held in values. A value stored in several
+/// updates is counted once.
+fn value_matches(updates: &[Update], patterns: &[Regex]) -> usize {
+  let strings = updates
+    .iter()
+    .flat_map(|update| update.value_strings())
+    .collect::<HashSet<_>>();
+  strings
+    .into_iter()
+    .map(|(_, string)| {
+      patterns
+        .iter()
+        .flat_map(|pattern| pattern.find_iter(string))
+        .filter(|found| !found.is_empty())
+        .count()
+    })
+    .sum()
+}
+
+/// Masks every UTF-16 unit of `string` matched by one of `patterns`, or
+/// returns `None` when nothing matches.
+fn mask_matches(string: &str, patterns: &[Regex]) -> Option<String> {
+  let found = patterns
+    .iter()
+    .flat_map(|pattern| pattern.find_iter(string))
+    .filter(|found| !found.is_empty())
+    .map(|found| found.range())
+    .collect::<Vec<_>>();
+  if found.is_empty() {
+    return None;
+  }
+  let mut masked = String::with_capacity(string.len());
+  for (offset, char) in string.char_indices() {
+    if found.iter().any(|range| range.contains(&offset)) {
+      masked.extend(std::iter::repeat_n(MASK, char.len_utf16()));
+    } else {
+      masked.push(char);
+    }
+  }
+  Some(masked)
+}
+
+/// Joins strings of one client into runs of adjacent text, given with the
+/// clock of their first unit. A string continues a run when it overlaps
+/// with it, as another split of the same item, or when it was inserted
+/// right after its last unit.
+fn runs(client: Client, strings: BTreeMap<Clock, (Option<Id>, &str)>) -> Vec<(Clock, String)> {
+  let mut runs: Vec<(Clock, String)> = Vec::new();
+  let mut end = 0;
+  for (clock, (origin, string)) in strings {
+    let len = utf16_len(string);
+    let follows =
+      clock < end || (clock == end && end > 0 && origin == Some(Id::new(client, end - 1)));
+    match runs.last_mut() {
+      Some((_, run)) if follows => {
+        // only the part past the end of the run is new
+        let mut skip = end - clock;
+        let rest = string.trim_start_matches(|char: char| {
+          let inside = skip > 0;
+          skip = skip.saturating_sub(char.len_utf16() as u64);
+          inside
+        });
+        run.push_str(rest);
+      }
+      _ => runs.push((clock, string.to_string())),
+    }
+    end = end.max(clock + len);
+  }
+  runs
+}
+
+fn utf16_len(string: &str) -> u64 {
+  string.chars().map(|char| char.len_utf16() as u64).sum()
+}
+
+#[cfg(test)]
+mod tests {
+  use y_octo::{Any, Doc, DocOptions, StateVector};
+
+  use super::*;
+
+  fn diff(doc: &Doc, state: &mut StateVector) -> Vec<u8> {
+    let update = doc.encode_state_as_update_v1(state).unwrap();
+    *state = doc.get_state_vector();
+    update
+  }
+
+  fn text_of(bins: &[Vec<u8>]) -> String {
+    let mut doc = DocOptions::new().build();
+    for bin in bins {
+      doc.apply_update_from_binary_v1(bin).unwrap();
+    }
+    doc.get_or_create_text("content").unwrap().to_string()
+  }
+
+  #[test]
+  fn test_scrub_history() {
+    let doc = DocOptions::new().with_client_id(1).build();
+    let mut text = doc.get_or_create_text("content").unwrap();
+    let mut state = doc.get_state_vector();
+    let mut updates = Vec::new();
+
+    text.insert(0, "notes: ").unwrap();
+    updates.push(diff(&doc, &mut state));
+    // pasted, then removed again
+    text.insert(7, "key sk-live-abc123").unwrap();
+    updates.push(diff(&doc, &mut state));
+    text.remove(7, 18).unwrap();
+    updates.push(diff(&doc, &mut state));
+    // typed over two updates
+    text.insert(7, "sk-").unwrap();
+    updates.push(diff(&doc, &mut state));
+    text.insert(10, "live-xyz789").unwrap();
+    updates.push(diff(&doc, &mut state));
+    let snapshot = doc.encode_update_v1().unwrap();
+
+    let patterns = vec![r"sk-live-[a-z0-9]+".to_string()];
+    let result = scrub_history(snapshot.clone(), updates.clone(), &patterns).unwrap();
+    assert_eq!(result.matches, 2);
+    assert_eq!(result.updates.len(), updates.len());
+    // updates without the secret are kept byte for byte
+    assert_eq!(result.updates[0], updates[0]);
+    assert_eq!(result.updates[2], updates[2]);
+    for bin in std::iter::once(&result.snapshot).chain(&result.updates) {
+      let bin = String::from_utf8_lossy(bin);
+      assert!(!bin.contains("abc123") && !bin.contains("xyz789"));
+    }
+
+    let masked = "notes: **************";
+    assert_eq!(text_of(std::slice::from_ref(&result.snapshot)), masked);
+    assert_eq!(text_of(&result.updates), masked);
+    assert_eq!(text_of(&result.updates[..2]), "notes: key **************");
+
+    // a secret in a link attribute is masked in the attribute
+    let doc = DocOptions::new().with_client_id(1).build();
+    let mut text = doc.get_or_create_text("content").unwrap();
+    let link = "https://example.com/?key=sk-live-def456";
+    text
+      .push_with_attributes(
+        "docs",
+        [("link".to_string(), link.into())].into_iter().collect(),
+      )
+      .unwrap();
+    let linked = doc.encode_update_v1().unwrap();
+    let result = scrub_history(linked.clone(), vec![linked], &patterns).unwrap();
+    assert_eq!(result.matches, 1);
+    let doc = Doc::try_from_binary_v1(result.snapshot).unwrap();
+    let text = doc.get_or_create_text("content").unwrap();
+    assert_eq!(text.to_string(), "docs");
+    assert_eq!(
+      text.to_delta()[0].attributes.get("link"),
+      Some(&Any::String(
+        "https://example.com/?key=**************".into()
+      ))
+    );
+    assert!(!String::from_utf8_lossy(&result.updates[0]).contains("def456"));
+
+    let clean = scrub_history(snapshot.clone(), updates.clone(), &["password".into()]).unwrap();
+    assert_eq!(clean.matches, 0);
+    assert_eq!(clean.snapshot, snapshot);
+
+    assert!(matches!(
+      scrub_history(snapshot, updates, &["(".into()]),
+      Err(ParseError::InvalidOptions(_))
+    ));
+  }
+}
@@ -0,0 +1,138 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, SecondsFormat};
+
+use super::{parse_root_doc_meta, ParseError};
+
+/// Builds the `sitemap.xml` of a publicly published workspace, listing the
+/// pages of `published_doc_ids` at `{base_url}/{doc id}`.
+///
+/// `lastmod` is the time of the last stored update of a doc from
+/// `last_edits`, in milliseconds since the Unix epoch, and falls back to the
+/// dates of the page meta in the root doc. Docs that are trashed or missing
+/// from the root doc are left out, and pages are sorted by doc id. Sitemaps
+/// are limited to 50,000 URLs, so callers with more docs split them over
+/// several sitemaps.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::collections::HashMap;
+///
+/// use affine_common::doc_parser::build_sitemap;
+///
+/// let root_doc_bin = std::fs::read("fixtures/root.ydoc").unwrap();
+/// let sitemap = build_sitemap(
+///   root_doc_bin,
+///   &["doc-id".to_string()],
+///   "https://app.affine.pro/workspace/ws-id",
+///   &HashMap::new(),
+/// )
+/// .unwrap();
+/// ```
+pub fn build_sitemap(
+  root_doc_bin: Vec<u8>,
+  published_doc_ids: &[String],
+  base_url: &str,
+  last_edits: &HashMap<String, i64>,
+) -> Result<String, ParseError> {
+  let published = published_doc_ids
+    .iter()
+    .map(String::as_str)
+    .collect::<HashSet<_>>();
+  let mut pages = parse_root_doc_meta(root_doc_bin, None)?
+    .into_iter()
+    .filter(|meta| !meta.trash && published.contains(meta.doc_id.as_str()))
+    .map(|meta| {
+      let last_modified = last_edits
+        .get(&meta.doc_id)
+        .copied()
+        .or(meta.updated_date)
+        .or(meta.create_date);
+      (meta.doc_id, last_modified)
+    })
+    .collect::<Vec<_>>();
+  pages.sort();
+  pages.dedup_by(|(a, _), (b, _)| a == b);
+
+  let base_url = base_url.trim_end_matches('/');
+  let mut xml = String::from(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+  );
+  for (doc_id, last_modified) in pages {
+    xml.push_str("  <url>\n");
+    xml.push_str(&format!(
+      "    <loc>{}</loc>\n",
+      escape_xml(&format!("{base_url}/{doc_id}"))
+    ));
+    if let Some(date) = last_modified.and_then(DateTime::from_timestamp_millis) {
+      xml.push_str(&format!(
+        "    <lastmod>{}</lastmod>\n",
+        date.to_rfc3339_opts(SecondsFormat::Secs, true)
+      ));
+    }
+    xml.push_str("  </url>\n");
+  }
+  xml.push_str("</urlset>\n");
+
+  Ok(xml)
+}
+
+fn escape_xml(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestRootDoc;
+
+  #[test]
+  fn test_build_sitemap() {
+    let published = ["a-doc", "b-doc", "binned", "unknown"].map(String::from);
+    let last_edits = HashMap::from([("b-doc".to_string(), 1_800_000_000_000)]);
+
+    let mut root = TestRootDoc::new();
+    for (id, trash, updated) in [
+      ("b-doc", false, Some(1_700_000_000_000f64)),
+      ("a-doc", false, None),
+      ("binned", true, Some(1_700_000_000_000f64)),
+      ("private", false, None),
+    ] {
+      let mut fields = vec![
+        ("trash", trash.into()),
+        ("createDate", 1_600_000_000_000f64.into()),
+      ];
+      fields.extend(updated.map(|updated| ("updatedDate", updated.into())));
+      root.add_page(id, &[], &fields);
+    }
+
+    let sitemap = build_sitemap(
+      root.encode(),
+      &published,
+      "https://example.com/share/a&b/",
+      &last_edits,
+    )
+    .unwrap();
+    assert_eq!(
+      sitemap,
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/share/a&amp;b/a-doc</loc>
+    <lastmod>2020-09-13T12:26:40Z</lastmod>
+  </url>
+  <url>
+    <loc>https://example.com/share/a&amp;b/b-doc</loc>
+    <lastmod>2027-01-15T08:00:00Z</lastmod>
+  </url>
+</urlset>
+"#
+    );
+  }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::{block_index::BlockIndex, text_content, ParseError};
+
+/// A block with its text replaced by its length.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkeletonNode {
+  pub flavour: String,
+  /// Length of the block's text, or of the title for pages. `0` for blocks
+  /// without text.
+  pub text_length: usize,
+  pub children: Vec<SkeletonNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocSkeleton {
+  pub root: SkeletonNode,
+  pub block_count: usize,
+}
+
+/// Extracts the block tree of a doc without any of its content.
+///
+/// The skeleton carries flavours, nesting and text lengths only; block ids,
+/// titles, text and props are left out so it can be handed to pipelines that
+/// must not see user content.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::extract_skeleton;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let skeleton = extract_skeleton(doc_bin, "doc-id".into()).unwrap();
+/// println!("{} blocks", skeleton.block_count);
+/// ```
+pub fn extract_skeleton(doc_bin: Vec<u8>, doc_id: String) -> Result<DocSkeleton, ParseError> {
+  let index = BlockIndex::load(&doc_bin, &doc_id)?
+    .ok_or_else(|| ParseError::ParserError("blocks map is empty".into()))?;
+
+  let mut visited = HashSet::new();
+  let root = build_node(&index, &index.root_block_id, &mut visited)
+    .ok_or_else(|| ParseError::ParserError("root block not found".into()))?;
+
+  Ok(DocSkeleton {
+    root,
+    block_count: visited.len(),
+  })
+}
+
+fn build_node(
+  index: &BlockIndex,
+  block_id: &str,
+  visited: &mut HashSet<String>,
+) -> Option<SkeletonNode> {
+  let flavour = index.flavour_of(block_id)?;
+  if !visited.insert(block_id.to_string()) {
+    return None;
+  }
+  let block = index.get(block_id)?;
+
+  let text_length = text_content(block, "prop:text")
+    .or_else(|| text_content(block, "prop:title"))
+    .map(|(_, len)| len)
+    .unwrap_or_default();
+
+  let children = index
+    .children_of(block_id)
+    .iter()
+    .filter_map(|child_id| build_node(index, child_id, visited))
+    .collect();
+
+  Some(SkeletonNode {
+    flavour,
+    text_length,
+    children,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_extract_skeleton() {
+    let mut doc = TestDoc::new("Private");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "secret")]);
+    doc.add_block("note", "l1", "affine:list", &[("text", "abc")]);
+    doc.add_block("l1", "l2", "affine:list", &[("text", "de")]);
+
+    let skeleton = extract_skeleton(doc.encode(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(skeleton.block_count, 5);
+
+    let node = |flavour: &str, text_length, children| SkeletonNode {
+      flavour: flavour.into(),
+      text_length,
+      children,
+    };
+    assert_eq!(
+      skeleton.root,
+      node(
+        "affine:page",
+        7,
+        vec![node(
+          "affine:note",
+          0,
+          vec![
+            node("affine:paragraph", 6, vec![]),
+            node("affine:list", 3, vec![node("affine:list", 2, vec![])]),
+          ],
+        )],
+      )
+    );
+
+    let json = serde_json::to_string(&skeleton).unwrap();
+    assert!(!json.contains("Private") && !json.contains("secret"));
+  }
+}
@@ -0,0 +1,285 @@
+use serde::{Deserialize, Serialize};
+use y_octo::Update;
+
+use super::{
+  options::{parse_options, OptionsObject},
+  ParseError, UpdateAnomaly,
+};
+use crate::deterministic::now_millis;
+
+/// First bytes of a labeled snapshot, ending in the format version.
+pub const LABELED_SNAPSHOT_MAGIC: &[u8; 4] = b"ALS1";
+
+/// When the storage layer persists a restore point of a doc.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct SnapshotPolicy {
+  /// No snapshot is taken sooner than this after the last one, unless
+  /// `onAnomaly` asks for it.
+  pub min_interval_ms: i64,
+  /// A doc with pending updates is snapshotted once this long passed since
+  /// the last snapshot.
+  pub max_interval_ms: i64,
+  /// A doc is snapshotted once this many updates are pending.
+  pub max_pending_updates: u32,
+  /// Snapshots a doc as soon as its pending updates have an anomaly, see
+  /// [`super::analyze_update_stream`].
+  pub on_anomaly: bool,
+}
+
+impl Default for SnapshotPolicy {
+  fn default() -> Self {
+    Self {
+      min_interval_ms: 10 * 60 * 1000,
+      max_interval_ms: 24 * 60 * 60 * 1000,
+      max_pending_updates: 500,
+      on_anomaly: true,
+    }
+  }
+}
+
+impl SnapshotPolicy {
+  /// Parses and checks a policy sent as JSON, see [`validate_options`].
+  ///
+  /// [`validate_options`]: super::validate_options
+  pub fn from_json(json: &str) -> Result<Self, ParseError> {
+    parse_options(json)
+  }
+}
+
+impl OptionsObject for SnapshotPolicy {
+  fn check(&self) -> Result<(), String> {
+    if self.min_interval_ms < 0 {
+      return Err("`minIntervalMs` must not be negative".into());
+    }
+    if self.max_interval_ms <= self.min_interval_ms {
+      return Err("`maxIntervalMs` must be greater than `minIntervalMs`".into());
+    }
+    if self.max_pending_updates == 0 {
+      return Err("`maxPendingUpdates` must be greater than 0".into());
+    }
+    Ok(())
+  }
+}
+
+/// What the storage layer knows of a doc when deciding on a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocStateSummary {
+  /// Milliseconds since the Unix epoch.
+  pub now: i64,
+  /// When the last snapshot was taken, `None` when the doc has none.
+  pub last_snapshot_at: Option<i64>,
+  /// Updates applied since the last snapshot.
+  pub pending_updates: u32,
+  /// Anomalies found in the pending updates.
+  pub anomalies: Vec<UpdateAnomaly>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotReason {
+  /// The doc has no snapshot yet.
+  FirstSnapshot,
+  /// The pending updates have an anomaly.
+  Anomaly,
+  /// Too many updates are pending.
+  PendingUpdates,
+  /// The last snapshot is too old.
+  Interval,
+}
+
+impl SnapshotReason {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      SnapshotReason::FirstSnapshot => "first_snapshot",
+      SnapshotReason::Anomaly => "anomaly",
+      SnapshotReason::PendingUpdates => "pending_updates",
+      SnapshotReason::Interval => "interval",
+    }
+  }
+}
+
+/// Tells whether a doc needs a restore point now, and why.
+///
+/// Returns `None` when it does not. Anomalies bypass `min_interval_ms`, as
+/// the snapshot has to be taken before the suspicious updates are compacted
+/// into the doc.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::doc_parser::{should_snapshot, DocStateSummary, SnapshotPolicy};
+///
+/// let state = DocStateSummary {
+///   now: 1_700_000_000_000,
+///   last_snapshot_at: None,
+///   pending_updates: 3,
+///   anomalies: vec![],
+/// };
+/// assert!(should_snapshot(&state, &SnapshotPolicy::default()).is_some());
+/// ```
+pub fn should_snapshot(state: &DocStateSummary, policy: &SnapshotPolicy) -> Option<SnapshotReason> {
+  if state.pending_updates == 0 {
+    return None;
+  }
+  let Some(last_snapshot_at) = state.last_snapshot_at else {
+    return Some(SnapshotReason::FirstSnapshot);
+  };
+  if policy.on_anomaly && !state.anomalies.is_empty() {
+    return Some(SnapshotReason::Anomaly);
+  }
+
+  let elapsed = state.now - last_snapshot_at;
+  if elapsed < policy.min_interval_ms {
+    None
+  } else if state.pending_updates >= policy.max_pending_updates {
+    Some(SnapshotReason::PendingUpdates)
+  } else if elapsed >= policy.max_interval_ms {
+    Some(SnapshotReason::Interval)
+  } else {
+    None
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabeledSnapshot {
+  pub label: String,
+  /// Milliseconds since the Unix epoch.
+  pub created_at: i64,
+  pub doc_bin: Vec<u8>,
+}
+
+/// Wraps a doc snapshot with a label and the time it was taken, so restore
+/// points can be listed without decoding the docs.
+///
+/// The layout is the magic `"ALS1"`, the timestamp as a little-endian `i64`,
+/// the length of the label as a little-endian `u32`, the label in UTF-8 and
+/// the doc. The timestamp is the Unix epoch in deterministic mode.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{make_labeled_snapshot, read_labeled_snapshot};
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let snapshot = make_labeled_snapshot(doc_bin, "before bulk delete").unwrap();
+/// assert_eq!(read_labeled_snapshot(&snapshot).unwrap().label, "before bulk delete");
+/// ```
+pub fn make_labeled_snapshot(doc_bin: Vec<u8>, label: &str) -> Result<Vec<u8>, ParseError> {
+  Update::decode_v1(&doc_bin).map_err(|_| ParseError::InvalidBinary)?;
+  let created_at = now_millis();
+
+  let mut out = Vec::with_capacity(LABELED_SNAPSHOT_MAGIC.len() + 12 + label.len() + doc_bin.len());
+  out.extend_from_slice(LABELED_SNAPSHOT_MAGIC);
+  out.extend_from_slice(&created_at.to_le_bytes());
+  out.extend_from_slice(&(label.len() as u32).to_le_bytes());
+  out.extend_from_slice(label.as_bytes());
+  out.extend_from_slice(&doc_bin);
+  Ok(out)
+}
+
+/// Reads a snapshot written by [`make_labeled_snapshot`].
+pub fn read_labeled_snapshot(snapshot: &[u8]) -> Result<LabeledSnapshot, ParseError> {
+  let invalid = || ParseError::ParserError("invalid labeled snapshot".into());
+  let rest = snapshot
+    .strip_prefix(LABELED_SNAPSHOT_MAGIC)
+    .ok_or_else(invalid)?;
+  let (created_at, rest) = rest.split_first_chunk::<8>().ok_or_else(invalid)?;
+  let (label_len, rest) = rest.split_first_chunk::<4>().ok_or_else(invalid)?;
+  let label_len = u32::from_le_bytes(*label_len) as usize;
+  let label = rest
+    .get(..label_len)
+    .and_then(|label| std::str::from_utf8(label).ok())
+    .ok_or_else(invalid)?;
+
+  Ok(LabeledSnapshot {
+    label: label.to_string(),
+    created_at: i64::from_le_bytes(*created_at),
+    doc_bin: rest[label_len..].to_vec(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{test_utils::TestDoc, AnomalyKind};
+
+  const MINUTE: i64 = 60 * 1000;
+
+  #[test]
+  fn test_should_snapshot() {
+    let policy = SnapshotPolicy::default();
+    let state = |minutes_since_snapshot: Option<i64>, pending_updates, anomalies| DocStateSummary {
+      now: 1000 * MINUTE,
+      last_snapshot_at: minutes_since_snapshot.map(|minutes| (1000 - minutes) * MINUTE),
+      pending_updates,
+      anomalies,
+    };
+    let anomaly = UpdateAnomaly {
+      kind: AnomalyKind::MassDeletion,
+      first_update_index: 0,
+      update_count: 1,
+      start: 0,
+      end: 0,
+      inserted: 0,
+      deleted: 500,
+    };
+
+    assert_eq!(should_snapshot(&state(None, 0, vec![]), &policy), None);
+    assert_eq!(
+      should_snapshot(&state(None, 1, vec![]), &policy),
+      Some(SnapshotReason::FirstSnapshot)
+    );
+    assert_eq!(
+      should_snapshot(&state(Some(1), 1, vec![anomaly.clone()]), &policy),
+      Some(SnapshotReason::Anomaly)
+    );
+    assert_eq!(
+      should_snapshot(
+        &state(Some(1), 1, vec![anomaly]),
+        &SnapshotPolicy {
+          on_anomaly: false,
+          ..Default::default()
+        }
+      ),
+      None
+    );
+    assert_eq!(
+      should_snapshot(&state(Some(1), 1000, vec![]), &policy),
+      None
+    );
+    assert_eq!(
+      should_snapshot(&state(Some(20), 1000, vec![]), &policy),
+      Some(SnapshotReason::PendingUpdates)
+    );
+    assert_eq!(should_snapshot(&state(Some(20), 10, vec![]), &policy), None);
+    assert_eq!(
+      should_snapshot(&state(Some(24 * 60), 10, vec![]), &policy),
+      Some(SnapshotReason::Interval)
+    );
+
+    assert!(matches!(
+      SnapshotPolicy::from_json(r#"{ "minIntervalMs": 10, "maxIntervalMs": 5 }"#),
+      Err(ParseError::InvalidOptions(_))
+    ));
+  }
+
+  #[test]
+  fn test_labeled_snapshot() {
+    let doc_bin = TestDoc::new("Restore me").encode();
+    let snapshot = make_labeled_snapshot(doc_bin.clone(), "before cleanup").unwrap();
+    assert!(snapshot.starts_with(LABELED_SNAPSHOT_MAGIC));
+
+    let read = read_labeled_snapshot(&snapshot).unwrap();
+    assert_eq!(read.label, "before cleanup");
+    assert!(read.created_at > 0);
+    assert_eq!(read.doc_bin, doc_bin);
+
+    assert!(read_labeled_snapshot(&snapshot[..10]).is_err());
+    assert!(read_labeled_snapshot(&doc_bin).is_err());
+    assert!(matches!(
+      make_labeled_snapshot(vec![0xff], "broken"),
+      Err(ParseError::InvalidBinary)
+    ));
+  }
+}
@@ -0,0 +1,322 @@
+use serde::{Deserialize, Serialize};
+
+use super::{block_index::BlockIndex, plain_text::block_text, ParseError};
+
+/// Marks text cut from either end of a snippet.
+const ELLIPSIS: char = '…';
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnippetOptions {
+  /// Most snippets returned, one per block.
+  pub max_snippets: usize,
+  /// Characters kept before the first match of a block and after it.
+  pub context_chars: usize,
+  /// Inserted before every match in [`Snippet::highlighted`].
+  pub highlight_start: String,
+  /// Inserted after every match in [`Snippet::highlighted`].
+  pub highlight_end: String,
+}
+
+impl Default for SnippetOptions {
+  fn default() -> Self {
+    Self {
+      max_snippets: 5,
+      context_chars: 40,
+      highlight_start: "<mark>".into(),
+      highlight_end: "</mark>".into(),
+    }
+  }
+}
+
+/// A match in [`Snippet::text`], in UTF-16 units so it can be used on JS
+/// strings as is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnippetMatch {
+  pub start: usize,
+  pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet {
+  pub block_id: String,
+  pub flavour: String,
+  /// The text around the first match of the block, on one line, with `…`
+  /// where it was cut.
+  pub text: String,
+  /// `text` with the highlight markers around every match. The text is not
+  /// escaped, build markup from `text` and `matches` instead when it is
+  /// rendered as HTML.
+  pub highlighted: String,
+  /// Matches within `text`, in order and not overlapping.
+  pub matches: Vec<SnippetMatch>,
+  /// Matches in the whole block, including those cut from `text`.
+  pub match_count: usize,
+}
+
+/// Finds the blocks of a doc that match `query_terms` and cuts a highlighted
+/// snippet from each, for the rows of search results.
+///
+/// Blocks are read like [`super::parse_doc_to_text`] reads them, the title
+/// included. Terms match anywhere in the text, ignoring case. Blocks with
+/// more distinct terms come first, then those with more matches, then
+/// document order.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{generate_snippets, SnippetOptions};
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let snippets = generate_snippets(
+///   doc_bin,
+///   "doc-id".into(),
+///   &["affine".to_string()],
+///   &SnippetOptions::default(),
+/// )
+/// .unwrap();
+/// for snippet in &snippets {
+///   println!("{}: {}", snippet.block_id, snippet.highlighted);
+/// }
+/// ```
+pub fn generate_snippets(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+  query_terms: &[String],
+  options: &SnippetOptions,
+) -> Result<Vec<Snippet>, ParseError> {
+  let terms = query_terms
+    .iter()
+    .map(|term| fold(term.trim()))
+    .filter(|term| !term.is_empty())
+    .collect::<Vec<_>>();
+  if terms.is_empty() || options.max_snippets == 0 {
+    return Ok(vec![]);
+  }
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(vec![]);
+  };
+
+  let mut candidates = Vec::new();
+  for block_id in index.descendants(&index.root_block_id) {
+    let (Some(block), Some(flavour)) = (index.get(&block_id), index.flavour_of(&block_id)) else {
+      continue;
+    };
+    let text = block_text(block, &flavour).chars().collect::<Vec<_>>();
+    let folded = fold(&text.iter().collect::<String>());
+
+    let mut distinct = 0;
+    let mut matches = Vec::new();
+    for term in &terms {
+      let found = find_all(&folded, term);
+      distinct += usize::from(!found.is_empty());
+      matches.extend(found);
+    }
+    if matches.is_empty() {
+      continue;
+    }
+    candidates.push((distinct, merge(matches), block_id, flavour, text));
+  }
+  // stable, so blocks that rank the same stay in document order
+  candidates.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| b.1.len().cmp(&a.1.len())));
+
+  Ok(
+    candidates
+      .into_iter()
+      .take(options.max_snippets)
+      .map(|(_, matches, block_id, flavour, text)| {
+        cut_snippet(block_id, flavour, &text, &matches, options)
+      })
+      .collect(),
+  )
+}
+
+/// Lowercases `text` one character at a time, keeping characters that would
+/// lowercase to several, so positions in the result are positions in `text`.
+fn fold(text: &str) -> Vec<char> {
+  text
+    .chars()
+    .map(|char| {
+      let mut lower = char.to_lowercase();
+      match (lower.next(), lower.next()) {
+        (Some(lower), None) => lower,
+        _ => char,
+      }
+    })
+    .collect()
+}
+
+/// Character ranges of every occurrence of `term` in `text`, overlapping ones
+/// included.
+fn find_all(text: &[char], term: &[char]) -> Vec<(usize, usize)> {
+  text
+    .windows(term.len())
+    .enumerate()
+    .filter(|(_, window)| *window == term)
+    .map(|(start, _)| (start, start + term.len()))
+    .collect()
+}
+
+/// Sorts `matches` and joins those that overlap or touch.
+fn merge(mut matches: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+  matches.sort_unstable();
+  let mut merged: Vec<(usize, usize)> = Vec::with_capacity(matches.len());
+  for (start, end) in matches {
+    match merged.last_mut() {
+      Some(last) if start <= last.1 => last.1 = last.1.max(end),
+      _ => merged.push((start, end)),
+    }
+  }
+  merged
+}
+
+fn cut_snippet(
+  block_id: String,
+  flavour: String,
+  text: &[char],
+  matches: &[(usize, usize)],
+  options: &SnippetOptions,
+) -> Snippet {
+  let (first_start, first_end) = matches[0];
+  let mut from = first_start.saturating_sub(options.context_chars);
+  let mut to = (first_end + options.context_chars).min(text.len());
+  // cut between words, and without spaces next to the ellipses
+  if from > 0 {
+    while from < first_start && !text[from - 1].is_whitespace() {
+      from += 1;
+    }
+  }
+  while from < first_start && text[from].is_whitespace() {
+    from += 1;
+  }
+  if to < text.len() {
+    while to > first_end && !text[to].is_whitespace() {
+      to -= 1;
+    }
+  }
+  while to > first_end && text[to - 1].is_whitespace() {
+    to -= 1;
+  }
+
+  let mut snippet = Snippet {
+    block_id,
+    flavour,
+    text: String::new(),
+    highlighted: String::new(),
+    matches: vec![],
+    match_count: matches.len(),
+  };
+  let push = |snippet: &mut Snippet, char: char| {
+    snippet.text.push(char);
+    snippet.highlighted.push(char);
+  };
+  if from > 0 {
+    push(&mut snippet, ELLIPSIS);
+  }
+  let mut inside = matches
+    .iter()
+    .filter(|(start, end)| *start >= from && *end <= to)
+    .peekable();
+  let mut utf16_len = snippet.text.encode_utf16().count();
+  // UTF-16 start and character end of the match being highlighted
+  let mut open = None;
+  for (position, &char) in text.iter().enumerate().take(to).skip(from) {
+    if open.is_some_and(|(_, end)| end == position) {
+      close(&mut snippet, &mut open, utf16_len, options);
+    }
+    if let Some(&(_, end)) = inside.next_if(|(start, _)| *start == position) {
+      snippet.highlighted.push_str(&options.highlight_start);
+      open = Some((utf16_len, end));
+    }
+    push(&mut snippet, if char == '\n' { ' ' } else { char });
+    utf16_len += char.len_utf16();
+  }
+  close(&mut snippet, &mut open, utf16_len, options);
+  if to < text.len() {
+    push(&mut snippet, ELLIPSIS);
+  }
+  snippet
+}
+
+/// Ends the match in `open`, if any, at UTF-16 offset `end`.
+fn close(
+  snippet: &mut Snippet,
+  open: &mut Option<(usize, usize)>,
+  end: usize,
+  options: &SnippetOptions,
+) {
+  if let Some((start, _)) = open.take() {
+    snippet.highlighted.push_str(&options.highlight_end);
+    snippet.matches.push(SnippetMatch { start, end });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_generate_snippets() {
+    let mut doc = TestDoc::new("Editor roadmap");
+    doc.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[("text", "Nothing to see here.")],
+    );
+    doc.add_block(
+      "note",
+      "p2",
+      "affine:paragraph",
+      &[(
+        "text",
+        "The team spent the whole quarter on it: the new EDITOR ships with sync, and the editor plugins follow.",
+      )],
+    );
+    doc.add_block("note", "p3", "affine:paragraph", &[("text", "😀 editor")]);
+    let doc_bin = doc.encode();
+
+    let terms = ["editor".to_string(), "sync".to_string()];
+    let options = SnippetOptions {
+      context_chars: 20,
+      ..Default::default()
+    };
+    let snippets =
+      generate_snippets(doc_bin.clone(), TestDoc::DOC_ID.into(), &terms, &options).unwrap();
+    assert_eq!(
+      snippets
+        .iter()
+        .map(|snippet| snippet.block_id.as_str())
+        .collect::<Vec<_>>(),
+      ["p2", "page", "p3"]
+    );
+
+    assert_eq!(snippets[0].text, "…on it: the new EDITOR ships with sync,…");
+    assert_eq!(
+      snippets[0].highlighted,
+      "…on it: the new <mark>EDITOR</mark> ships with <mark>sync</mark>,…"
+    );
+    assert_eq!(
+      snippets[0].matches,
+      [
+        SnippetMatch { start: 16, end: 22 },
+        SnippetMatch { start: 34, end: 38 }
+      ]
+    );
+    assert_eq!(snippets[0].match_count, 3);
+    assert_eq!(snippets[1].highlighted, "<mark>Editor</mark> roadmap");
+    // the emoji takes two UTF-16 units
+    assert_eq!(snippets[2].matches, [SnippetMatch { start: 3, end: 9 }]);
+
+    let options = SnippetOptions {
+      max_snippets: 1,
+      ..Default::default()
+    };
+    assert_eq!(
+      generate_snippets(doc_bin, TestDoc::DOC_ID.into(), &terms, &options)
+        .unwrap()
+        .len(),
+      1
+    );
+  }
+}
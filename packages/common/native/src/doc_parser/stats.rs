@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{block_index::BlockIndex, get_string, ParseError};
+use crate::grounding::is_cjk;
+
+/// Average silent reading speed of adults. CJK characters count as words,
+/// which reads at about the same pace.
+const WORDS_PER_MINUTE: f64 = 238.0;
+/// Time added per image to the reading time.
+const SECONDS_PER_IMAGE: f64 = 12.0;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocStats {
+  /// Words of the title and the block texts and captions. Every CJK
+  /// character counts as a word.
+  pub words: usize,
+  /// Characters of the same texts, whitespace included.
+  pub characters: usize,
+  pub characters_without_spaces: usize,
+  /// Blocks by flavour, the page and notes included.
+  pub blocks: BTreeMap<String, usize>,
+  pub images: usize,
+  pub attachments: usize,
+  /// Estimated reading time of the words and images, rounded up.
+  pub reading_time_seconds: u64,
+}
+
+/// Counts the words, characters and blocks of a doc, for info panels.
+///
+/// Counts are taken on the text as stored, so unlike counting an export
+/// they leave out Markdown syntax. Only blocks reachable from the page are
+/// counted.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::doc_stats;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let stats = doc_stats(doc_bin, "doc-id".into()).unwrap();
+/// println!("{} words, {}s to read", stats.words, stats.reading_time_seconds);
+/// ```
+pub fn doc_stats(doc_bin: Vec<u8>, doc_id: String) -> Result<DocStats, ParseError> {
+  let mut stats = DocStats::default();
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(stats);
+  };
+
+  for block_id in index.descendants(&index.root_block_id) {
+    let Some(block) = index.get(&block_id) else {
+      continue;
+    };
+    let flavour = index.flavour_of(&block_id).unwrap_or_default();
+    match flavour.as_str() {
+      "affine:image" => stats.images += 1,
+      "affine:attachment" => stats.attachments += 1,
+      _ => {}
+    }
+    *stats.blocks.entry(flavour).or_default() += 1;
+
+    for key in ["prop:title", "prop:text", "prop:caption"] {
+      let Some(text) = get_string(block, key) else {
+        continue;
+      };
+      stats.words += count_words(&text);
+      stats.characters += text.chars().count();
+      stats.characters_without_spaces += text.chars().filter(|c| !c.is_whitespace()).count();
+    }
+  }
+
+  let seconds =
+    stats.words as f64 / WORDS_PER_MINUTE * 60.0 + stats.images as f64 * SECONDS_PER_IMAGE;
+  stats.reading_time_seconds = seconds.ceil() as u64;
+  Ok(stats)
+}
+
+/// Whitespace separated words with a letter or digit, plus one word per CJK
+/// character.
+fn count_words(text: &str) -> usize {
+  text
+    .split_whitespace()
+    .map(|token| {
+      let cjk = token.chars().filter(|c| is_cjk(*c)).count();
+      let other = token.chars().any(|c| c.is_alphanumeric() && !is_cjk(c));
+      cjk + usize::from(other)
+    })
+    .sum()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_doc_stats() {
+    let mut doc = TestDoc::new("Stats");
+    doc.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[("text", "Hello brave new world")],
+    );
+    doc.add_block("note", "p2", "affine:paragraph", &[("text", "你好 — ok")]);
+    doc.add_block(
+      "note",
+      "i1",
+      "affine:image",
+      &[("sourceId", "blob"), ("caption", "A lake")],
+    );
+    doc.add_block("note", "a1", "affine:attachment", &[("name", "a.pdf")]);
+
+    let stats = doc_stats(doc.encode(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(stats.words, 1 + 4 + 3 + 2);
+    assert_eq!(stats.characters, 5 + 21 + 7 + 6);
+    assert_eq!(stats.characters_without_spaces, 5 + 18 + 5 + 5);
+    assert_eq!(
+      stats
+        .blocks
+        .iter()
+        .map(|(flavour, count)| (flavour.as_str(), *count))
+        .collect::<Vec<_>>(),
+      [
+        ("affine:attachment", 1),
+        ("affine:image", 1),
+        ("affine:note", 1),
+        ("affine:page", 1),
+        ("affine:paragraph", 2),
+      ]
+    );
+    assert_eq!((stats.images, stats.attachments), (1, 1));
+    // 10 words take 2.5 seconds, the image 12
+    assert_eq!(stats.reading_time_seconds, 15);
+  }
+}
@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+  options::{parse_options, OptionsObject},
+  ParseError, SUMMARY_LIMIT,
+};
+
+/// How the crawl result summary is made. The defaults keep the summary of
+/// earlier versions: whole block texts, without separators, until 1000
+/// characters are exceeded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct SummaryOptions {
+  /// Characters the summary is cut at. Without it, whole block texts are
+  /// added until 1000 characters are exceeded.
+  pub max_chars: Option<usize>,
+  /// Separates block texts with a space and cuts the summary at the end of
+  /// the last sentence within `max_chars`, or of the last word when no
+  /// sentence fits.
+  pub sentence_aware: bool,
+  /// Leaves the text of code blocks out.
+  pub skip_code: bool,
+  /// Keeps the text of headings.
+  pub include_headings: bool,
+}
+
+impl Default for SummaryOptions {
+  fn default() -> Self {
+    Self {
+      max_chars: None,
+      sentence_aware: false,
+      skip_code: false,
+      include_headings: true,
+    }
+  }
+}
+
+impl SummaryOptions {
+  /// Parses and checks options sent as JSON, see [`validate_options`].
+  ///
+  /// [`validate_options`]: super::validate_options
+  pub fn from_json(json: &str) -> Result<Self, ParseError> {
+    parse_options(json)
+  }
+
+  /// Whether the text of a block of `flavour`, with the `prop:type` `type_`,
+  /// is part of the summary.
+  pub(super) fn includes(&self, flavour: &str, type_: Option<&str>) -> bool {
+    match flavour {
+      "affine:code" => !self.skip_code,
+      "affine:paragraph" => {
+        self.include_headings
+          || !type_.is_some_and(|type_| matches!(type_, "h1" | "h2" | "h3" | "h4" | "h5" | "h6"))
+      }
+      _ => true,
+    }
+  }
+
+  /// Builds the summary from block texts and their length in characters.
+  pub(super) fn summarize(&self, texts: Vec<(usize, String)>) -> String {
+    if self.sentence_aware {
+      let text = texts
+        .into_iter()
+        .map(|(_, text)| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+      return truncate_at_sentence(&text, self.max_chars.unwrap_or(SUMMARY_LIMIT)).to_string();
+    }
+
+    let mut summary = String::new();
+    let Some(max_chars) = self.max_chars else {
+      let mut remaining = SUMMARY_LIMIT as isize;
+      for (text_len, text) in texts {
+        if remaining <= 0 {
+          break;
+        }
+        summary.push_str(&text);
+        remaining -= text_len as isize;
+      }
+      return summary;
+    };
+
+    let mut remaining = max_chars;
+    for (text_len, text) in texts {
+      if text_len > remaining {
+        summary.extend(text.chars().take(remaining));
+        break;
+      }
+      summary.push_str(&text);
+      remaining -= text_len;
+    }
+    summary
+  }
+}
+
+impl OptionsObject for SummaryOptions {
+  fn check(&self) -> Result<(), String> {
+    if self.max_chars == Some(0) {
+      return Err("`maxChars` must be greater than 0".into());
+    }
+    Ok(())
+  }
+}
+
+/// Cuts `text` to at most `max_chars` characters, at the end of a sentence
+/// when one fits, else at the end of a word.
+fn truncate_at_sentence(text: &str, max_chars: usize) -> &str {
+  let Some((cut, _)) = text.char_indices().nth(max_chars) else {
+    return text;
+  };
+  let head = &text[..cut];
+
+  let sentence_end = head
+    .char_indices()
+    .filter(|(i, c)| match c {
+      '。' | '！' | '？' => true,
+      '.' | '!' | '?' => text[i + c.len_utf8()..]
+        .chars()
+        .next()
+        .is_none_or(char::is_whitespace),
+      _ => false,
+    })
+    .map(|(i, c)| i + c.len_utf8())
+    .next_back();
+  if let Some(end) = sentence_end {
+    return &head[..end];
+  }
+
+  if text[cut..].starts_with(char::is_whitespace) {
+    return head.trim_end();
+  }
+  match head.rfind(char::is_whitespace) {
+    Some(end) if !head[..end].trim_end().is_empty() => head[..end].trim_end(),
+    _ => head,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_from_binary_with_options, test_utils::TestDoc, CrawlOptions};
+
+  #[test]
+  fn test_truncate_at_sentence() {
+    let text = "First sentence. Second one is longer.";
+    assert_eq!(truncate_at_sentence(text, 100), text);
+    assert_eq!(truncate_at_sentence(text, 30), "First sentence.");
+    assert_eq!(
+      truncate_at_sentence("no sentence end here", 12),
+      "no sentence"
+    );
+    assert_eq!(
+      truncate_at_sentence("no sentence end here", 11),
+      "no sentence"
+    );
+    assert_eq!(truncate_at_sentence("v1.2 is out", 6), "v1.2");
+    assert_eq!(truncate_at_sentence("unbroken", 3), "unb");
+    assert_eq!(truncate_at_sentence("第一句。第二句", 5), "第一句。");
+  }
+
+  #[test]
+  fn test_summary_options() {
+    let mut doc = TestDoc::new("Summary");
+    doc.add_block(
+      "note",
+      "h1",
+      "affine:paragraph",
+      &[("type", "h1"), ("text", "Heading")],
+    );
+    doc.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[("text", "Intro sentence. More words follow here.")],
+    );
+    doc.add_block("note", "c1", "affine:code", &[("text", "let x = 1;")]);
+    let doc_bin = doc.encode();
+    let summary = |summary: SummaryOptions| {
+      parse_doc_from_binary_with_options(
+        doc_bin.clone(),
+        TestDoc::DOC_ID.into(),
+        &CrawlOptions {
+          summary,
+          ..Default::default()
+        },
+      )
+      .unwrap()
+      .summary
+    };
+
+    assert_eq!(
+      summary(SummaryOptions::default()),
+      "HeadingIntro sentence. More words follow here.let x = 1;"
+    );
+    assert_eq!(
+      summary(SummaryOptions {
+        max_chars: Some(30),
+        sentence_aware: true,
+        skip_code: true,
+        include_headings: false,
+      }),
+      "Intro sentence."
+    );
+    // a block longer than the limit is cut
+    assert_eq!(
+      summary(SummaryOptions {
+        max_chars: Some(12),
+        ..Default::default()
+      }),
+      "HeadingIntro"
+    );
+    assert_eq!(
+      summary(SummaryOptions {
+        max_chars: Some(3),
+        ..Default::default()
+      }),
+      "Hea"
+    );
+    assert_eq!(
+      summary(SummaryOptions {
+        sentence_aware: true,
+        ..Default::default()
+      }),
+      "Heading Intro sentence. More words follow here. let x = 1;"
+    );
+
+    assert!(matches!(
+      SummaryOptions::from_json(r#"{ "maxChars": 0 }"#),
+      Err(ParseError::InvalidOptions(_))
+    ));
+    assert_eq!(
+      SummaryOptions::from_json(r#"{ "skipCode": true }"#).unwrap(),
+      SummaryOptions {
+        skip_code: true,
+        ..Default::default()
+      }
+    );
+  }
+}
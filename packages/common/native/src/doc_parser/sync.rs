@@ -0,0 +1,85 @@
+use y_octo::{CrdtRead, CrdtWrite, Doc, RawDecoder, RawEncoder, StateVector};
+
+use super::{new_doc, ParseError};
+
+/// Encodes the state vector of a doc as `Y.encodeStateVector` does, for a
+/// peer to send with its next sync request.
+pub fn encode_state_vector(doc_bin: &[u8]) -> Result<Vec<u8>, ParseError> {
+  let doc = load(doc_bin)?;
+  let mut encoder = RawEncoder::default();
+  doc.get_state_vector().write(&mut encoder)?;
+  Ok(encoder.into_inner())
+}
+
+/// Returns the update a peer at `state_vector` is missing, as
+/// `Y.encodeStateAsUpdate` does, so sync requests are answered without
+/// loading the doc in JS.
+///
+/// `state_vector` is one written by `Y.encodeStateVector` or
+/// [`encode_state_vector`]. An empty one asks for the whole doc.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::{diff_update, encode_state_vector};
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let state_vector = encode_state_vector(&doc_bin).unwrap();
+/// let missing = diff_update(&doc_bin, &state_vector).unwrap();
+/// ```
+pub fn diff_update(doc_bin: &[u8], state_vector: &[u8]) -> Result<Vec<u8>, ParseError> {
+  let doc = load(doc_bin)?;
+  let state_vector = if state_vector.is_empty() {
+    StateVector::default()
+  } else {
+    StateVector::read(&mut RawDecoder::new(state_vector))
+      .map_err(|_| ParseError::InvalidOptions("invalid state vector".into()))?
+  };
+  Ok(doc.encode_state_as_update_v1(&state_vector)?)
+}
+
+fn load(doc_bin: &[u8]) -> Result<Doc, ParseError> {
+  if doc_bin.is_empty() {
+    return Err(ParseError::InvalidBinary);
+  }
+  let mut doc = new_doc(None);
+  doc
+    .apply_update_from_binary_v1(doc_bin)
+    .map_err(|_| ParseError::InvalidBinary)?;
+  Ok(doc)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{
+    merge_updates,
+    test_utils::{doc_text, TestDoc},
+  };
+
+  #[test]
+  fn test_diff_update() {
+    let mut doc = TestDoc::new("Sync");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "one")]);
+    let old_bin = doc.encode();
+    doc.add_block("note", "p2", "affine:paragraph", &[("text", "two")]);
+    let new_bin = doc.encode();
+
+    let state_vector = encode_state_vector(&old_bin).unwrap();
+    let missing = diff_update(&new_bin, &state_vector).unwrap();
+    assert!(missing.len() < new_bin.len());
+    let synced = merge_updates(vec![old_bin, missing]).unwrap();
+    assert_eq!(doc_text(synced), "Sync\none\ntwo");
+
+    let up_to_date = encode_state_vector(&new_bin).unwrap();
+    assert_eq!(diff_update(&new_bin, &up_to_date).unwrap(), vec![0, 0]);
+    assert_eq!(
+      doc_text(diff_update(&new_bin, &[]).unwrap()),
+      "Sync\none\ntwo"
+    );
+    assert!(matches!(
+      diff_update(&new_bin, &[0xff]),
+      Err(ParseError::InvalidOptions(_))
+    ));
+  }
+}
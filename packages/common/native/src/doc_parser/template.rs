@@ -0,0 +1,330 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::{
+  anchor::slugify, block_index::BlockIndex, get_string, text_content, ParseError, PAGE_FLAVOUR,
+};
+
+/// `{{name}}`, with optional whitespace inside the braces.
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_.-]*)\s*\}\}").expect("valid placeholder pattern")
+});
+
+/// `Label: ____`, a line left blank to be filled in.
+static BLANK_FIELD: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^\s*(.*\S)\s*:\s*_{3,}\s*$").expect("valid blank field pattern"));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateVariableKind {
+  /// Free text.
+  Text,
+  /// One of [`TemplateVariable::options`].
+  Choice,
+}
+
+impl TemplateVariableKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      TemplateVariableKind::Text => "text",
+      TemplateVariableKind::Choice => "choice",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateVariableSource {
+  /// A `{{name}}` token in the text.
+  Placeholder,
+  /// A block laid out like a form field.
+  Form,
+}
+
+impl TemplateVariableSource {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      TemplateVariableSource::Placeholder => "placeholder",
+      TemplateVariableSource::Form => "form",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateVariable {
+  /// The placeholder name, or the slug of a form field's label with `_`
+  /// between words.
+  pub name: String,
+  /// Human readable label for the form.
+  pub label: String,
+  pub kind: TemplateVariableKind,
+  /// Where the variable was first found.
+  pub source: TemplateVariableSource,
+  /// Choices of a [`TemplateVariableKind::Choice`], in document order.
+  pub options: Vec<String>,
+  /// Blocks the variable appears in, in document order.
+  pub block_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateSchema {
+  pub title: String,
+  /// One entry per name, in order of first appearance.
+  pub variables: Vec<TemplateVariable>,
+}
+
+/// Finds the variables of a template doc, so they can be asked for in a form
+/// before the doc is instantiated or handed to a workflow.
+///
+/// Variables come from `{{name}}` tokens in the title and block text, and
+/// from blocks laid out like form fields:
+///
+/// - `Label: ____`, a text field,
+/// - a question ending with `?` followed by todo items, a choice between the
+///   items.
+///
+/// A name found more than once is listed once, with all its blocks.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::extract_template_variables;
+///
+/// let doc_bin = std::fs::read("fixtures/template.ydoc").unwrap();
+/// let schema = extract_template_variables(doc_bin, "doc-id".into()).unwrap();
+/// for variable in &schema.variables {
+///   println!("{} ({})", variable.label, variable.kind.as_str());
+/// }
+/// ```
+pub fn extract_template_variables(
+  doc_bin: Vec<u8>,
+  doc_id: String,
+) -> Result<TemplateSchema, ParseError> {
+  let mut schema = TemplateSchema {
+    title: String::new(),
+    variables: vec![],
+  };
+  let Some(index) = BlockIndex::load(&doc_bin, &doc_id)? else {
+    return Ok(schema);
+  };
+
+  let mut positions: HashMap<String, usize> = HashMap::new();
+  let mut add = |variable: TemplateVariable| match positions.get(&variable.name) {
+    Some(&position) => {
+      let existing = &mut schema.variables[position];
+      for block_id in variable.block_ids {
+        if !existing.block_ids.contains(&block_id) {
+          existing.block_ids.push(block_id);
+        }
+      }
+    }
+    None => {
+      positions.insert(variable.name.clone(), schema.variables.len());
+      schema.variables.push(variable);
+    }
+  };
+
+  let mut title = String::new();
+  // todo items already taken as the options of a choice
+  let mut consumed = Vec::new();
+  for block_id in index.descendants(&index.root_block_id) {
+    let Some(block) = index.get(&block_id) else {
+      continue;
+    };
+    let key = if index.flavour_of(&block_id).as_deref() == Some(PAGE_FLAVOUR) {
+      "prop:title"
+    } else {
+      "prop:text"
+    };
+    let Some((text, _)) = text_content(block, key) else {
+      continue;
+    };
+    if block_id == index.root_block_id {
+      title = text.clone();
+    }
+
+    for captures in PLACEHOLDER.captures_iter(&text) {
+      let name = captures[1].to_string();
+      add(TemplateVariable {
+        label: label_of(&name),
+        name,
+        kind: TemplateVariableKind::Text,
+        source: TemplateVariableSource::Placeholder,
+        options: vec![],
+        block_ids: vec![block_id.clone()],
+      });
+    }
+    if key == "prop:title" || consumed.contains(&block_id) {
+      continue;
+    }
+
+    if let Some(captures) = BLANK_FIELD.captures(&text) {
+      let label = captures[1].to_string();
+      add(TemplateVariable {
+        name: name_of(&label),
+        label,
+        kind: TemplateVariableKind::Text,
+        source: TemplateVariableSource::Form,
+        options: vec![],
+        block_ids: vec![block_id.clone()],
+      });
+    } else if let Some(label) = text.trim().strip_suffix('?') {
+      let todos = following_todos(&index, &block_id);
+      if todos.is_empty() {
+        continue;
+      }
+      let label = label.trim().to_string();
+      let mut block_ids = vec![block_id.clone()];
+      let mut options = Vec::new();
+      for (todo_id, option) in todos {
+        options.push(option);
+        block_ids.push(todo_id.clone());
+        consumed.push(todo_id);
+      }
+      add(TemplateVariable {
+        name: name_of(&label),
+        label,
+        kind: TemplateVariableKind::Choice,
+        source: TemplateVariableSource::Form,
+        options,
+        block_ids,
+      });
+    }
+  }
+  schema.title = title;
+
+  Ok(schema)
+}
+
+/// The run of todo items right after `block_id` among its siblings, with
+/// their text.
+fn following_todos(index: &BlockIndex, block_id: &str) -> Vec<(String, String)> {
+  let Some(parent_id) = index.parent_of(block_id) else {
+    return vec![];
+  };
+  index
+    .children_of(parent_id)
+    .into_iter()
+    .skip_while(|sibling| sibling != block_id)
+    .skip(1)
+    .map_while(|sibling| {
+      let block = index.get(&sibling)?;
+      if index.flavour_of(&sibling).as_deref() != Some("affine:list")
+        || get_string(block, "prop:type").as_deref() != Some("todo")
+      {
+        return None;
+      }
+      let (text, _) = text_content(block, "prop:text")?;
+      Some((sibling, text.trim().to_string()))
+    })
+    .filter(|(_, text)| !text.is_empty())
+    .collect()
+}
+
+/// `client_name` and `client-name` become `Client name`.
+fn label_of(name: &str) -> String {
+  let words = name.replace(['_', '-', '.'], " ");
+  let mut chars = words.trim().chars();
+  match chars.next() {
+    Some(first) => first.to_uppercase().chain(chars).collect(),
+    None => String::new(),
+  }
+}
+
+fn name_of(label: &str) -> String {
+  slugify(label).replace('-', "_")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_extract_template_variables() {
+    let mut doc = TestDoc::new("Proposal for {{client_name}}");
+    doc.add_block(
+      "note",
+      "p1",
+      "affine:paragraph",
+      &[("text", "Dear {{ client_name }}, the fee is {{fee}}.")],
+    );
+    doc.add_block(
+      "note",
+      "p2",
+      "affine:paragraph",
+      &[("text", "Start date: ______")],
+    );
+    doc.add_block("note", "q", "affine:paragraph", &[("text", "Which plan?")]);
+    doc.add_block(
+      "note",
+      "o1",
+      "affine:list",
+      &[("type", "todo"), ("text", "Basic")],
+    );
+    doc.add_block(
+      "note",
+      "o2",
+      "affine:list",
+      &[("type", "todo"), ("text", "Pro")],
+    );
+    doc.add_block(
+      "note",
+      "p3",
+      "affine:paragraph",
+      &[("text", "Any questions? Call us, or see {{ref:faq}}.")],
+    );
+
+    let schema = extract_template_variables(doc.encode(), TestDoc::DOC_ID.into()).unwrap();
+    assert_eq!(schema.title, "Proposal for {{client_name}}");
+    let variables = schema
+      .variables
+      .iter()
+      .map(|variable| {
+        (
+          variable.name.as_str(),
+          variable.label.as_str(),
+          variable.kind,
+          variable.source,
+          variable.block_ids.join(","),
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      variables,
+      [
+        (
+          "client_name",
+          "Client name",
+          TemplateVariableKind::Text,
+          TemplateVariableSource::Placeholder,
+          "page,p1".to_string()
+        ),
+        (
+          "fee",
+          "Fee",
+          TemplateVariableKind::Text,
+          TemplateVariableSource::Placeholder,
+          "p1".to_string()
+        ),
+        (
+          "start_date",
+          "Start date",
+          TemplateVariableKind::Text,
+          TemplateVariableSource::Form,
+          "p2".to_string()
+        ),
+        (
+          "which_plan",
+          "Which plan",
+          TemplateVariableKind::Choice,
+          TemplateVariableSource::Form,
+          "q,o1,o2".to_string()
+        ),
+      ]
+    );
+    assert_eq!(schema.variables[3].options, ["Basic", "Pro"]);
+  }
+}
@@ -0,0 +1,290 @@
+use y_octo::{Any, Array, Doc, DocOptions, Map};
+
+use super::{collect_child_ids, parse_doc_to_text};
+
+/// Builds small block trees for tests that need more than the demo fixture.
+pub(crate) struct TestDoc {
+  doc: Doc,
+  blocks: Map,
+}
+
+impl TestDoc {
+  pub(crate) const DOC_ID: &'static str = "test-doc";
+
+  /// Creates a doc with an `affine:page` block `page` and an `affine:note`
+  /// block `note` under it.
+  pub(crate) fn new(title: &str) -> Self {
+    let doc = DocOptions::new()
+      .with_guid(Self::DOC_ID.to_string())
+      .build();
+    let blocks = doc.get_or_create_map("blocks").unwrap();
+    let mut test_doc = Self { doc, blocks };
+
+    let mut page = test_doc.create_block("page", "affine:page");
+    let mut text = test_doc.doc.create_text().unwrap();
+    page.insert("prop:title".into(), text.clone()).unwrap();
+    text.insert(0, title).unwrap();
+
+    test_doc.add_block("page", "note", "affine:note", &[("displayMode", "both")]);
+    test_doc
+  }
+
+  /// Appends a block to `parent`. The `text` prop is stored as Y text, every
+  /// other prop as a plain string.
+  pub(crate) fn add_block(
+    &mut self,
+    parent: &str,
+    id: &str,
+    flavour: &str,
+    props: &[(&str, &str)],
+  ) -> Map {
+    let mut block = self.create_block(id, flavour);
+    for (key, value) in props {
+      if *key == "text" {
+        let mut text = self.doc.create_text().unwrap();
+        block.insert("prop:text".into(), text.clone()).unwrap();
+        text.insert(0, *value).unwrap();
+      } else {
+        block.insert(format!("prop:{key}"), *value).unwrap();
+      }
+    }
+
+    self.add_reference(parent, id);
+
+    block
+  }
+
+  /// Lists an existing block among the children of `parent`, without
+  /// unlinking it from its current parent.
+  pub(crate) fn add_reference(&mut self, parent: &str, id: &str) {
+    let mut children = self
+      .blocks
+      .get(parent)
+      .and_then(|value| value.to_map())
+      .and_then(|parent| parent.get("sys:children"))
+      .and_then(|value| value.to_array())
+      .unwrap();
+    children.push(id).unwrap();
+  }
+
+  /// Sets the columns of database block `id` as `(id, name, type)` and its
+  /// cells as `(row id, [(column id, value)])`.
+  pub(crate) fn set_database_cells(
+    &mut self,
+    id: &str,
+    columns: &[(&str, &str, &str)],
+    cells: &[(&str, &[(&str, &str)])],
+  ) {
+    let mut block = self
+      .blocks
+      .get(id)
+      .and_then(|value| value.to_map())
+      .unwrap();
+
+    let mut column_array = self.doc.create_array().unwrap();
+    block
+      .insert("prop:columns".into(), column_array.clone())
+      .unwrap();
+    for (column_id, name, type_) in columns {
+      let mut column = self.doc.create_map().unwrap();
+      column_array.push(column.clone()).unwrap();
+      column.insert("id".into(), *column_id).unwrap();
+      column.insert("name".into(), *name).unwrap();
+      column.insert("type".into(), *type_).unwrap();
+    }
+
+    let mut cell_map = self.doc.create_map().unwrap();
+    block.insert("prop:cells".into(), cell_map.clone()).unwrap();
+    for (row_id, row_cells) in cells {
+      let mut row = self.doc.create_map().unwrap();
+      cell_map.insert(row_id.to_string(), row.clone()).unwrap();
+      for (column_id, value) in *row_cells {
+        let mut cell = self.doc.create_map().unwrap();
+        row.insert(column_id.to_string(), cell.clone()).unwrap();
+        cell.insert("columnId".into(), *column_id).unwrap();
+        cell.insert("value".into(), *value).unwrap();
+      }
+    }
+  }
+
+  /// Sets the views of database block `id` as `(id, mode)`.
+  pub(crate) fn set_database_views(&mut self, id: &str, views: &[(&str, &str)]) {
+    let mut block = self
+      .blocks
+      .get(id)
+      .and_then(|value| value.to_map())
+      .unwrap();
+
+    let mut view_array = self.doc.create_array().unwrap();
+    block
+      .insert("prop:views".into(), view_array.clone())
+      .unwrap();
+    for (view_id, mode) in views {
+      let mut view = self.doc.create_map().unwrap();
+      view_array.push(view.clone()).unwrap();
+      view.insert("id".into(), *view_id).unwrap();
+      view.insert("mode".into(), *mode).unwrap();
+    }
+  }
+
+  /// Deletes a block and unlinks it from `parent`.
+  pub(crate) fn remove_block(&mut self, parent: &str, id: &str) {
+    self.unlink(parent, id);
+    self.blocks.remove(id);
+  }
+
+  /// Moves a block from the children of `from` to the end of the children
+  /// of `to`.
+  pub(crate) fn move_block(&mut self, from: &str, to: &str, id: &str) {
+    self.unlink(from, id);
+    self.add_reference(to, id);
+  }
+
+  fn unlink(&mut self, parent: &str, id: &str) {
+    let parent = self
+      .blocks
+      .get(parent)
+      .and_then(|value| value.to_map())
+      .unwrap();
+    let index = collect_child_ids(&parent)
+      .iter()
+      .position(|child_id| child_id == id)
+      .unwrap();
+    parent
+      .get("sys:children")
+      .and_then(|value| value.to_array())
+      .unwrap()
+      .remove(index as u64, 1)
+      .unwrap();
+  }
+
+  pub(crate) fn encode(&self) -> Vec<u8> {
+    self.doc.encode_update_v1().unwrap()
+  }
+
+  fn create_block(&mut self, id: &str, flavour: &str) -> Map {
+    let mut block = self.doc.create_map().unwrap();
+    self.blocks.insert(id.to_string(), block.clone()).unwrap();
+    block.insert("sys:id".into(), id).unwrap();
+    block.insert("sys:flavour".into(), flavour).unwrap();
+    let children = self.doc.create_array().unwrap();
+    block.insert("sys:children".into(), children).unwrap();
+    block
+  }
+}
+
+/// Plain text of a [`TestDoc`] binary.
+pub(crate) fn doc_text(doc_bin: Vec<u8>) -> String {
+  parse_doc_to_text(doc_bin, TestDoc::DOC_ID.into())
+    .unwrap()
+    .text
+}
+
+/// Builds workspace root docs, which list the docs of a workspace in
+/// `meta.pages`.
+pub(crate) struct TestRootDoc {
+  doc: Doc,
+  pages: Array,
+}
+
+impl TestRootDoc {
+  pub(crate) fn new() -> Self {
+    let doc = DocOptions::new().build();
+    let mut meta = doc.get_or_create_map("meta").unwrap();
+    let pages = doc.create_array().unwrap();
+    meta.insert("pages".into(), pages.clone()).unwrap();
+    Self { doc, pages }
+  }
+
+  /// Lists doc `id` tagged with the tag ids `tags`. `fields`, e.g. `title`
+  /// or `trash`, are stored as plain values.
+  pub(crate) fn add_page(&mut self, id: &str, tags: &[&str], fields: &[(&str, Any)]) {
+    let mut page = self.doc.create_map().unwrap();
+    self.pages.push(page.clone()).unwrap();
+    page.insert("id".into(), id).unwrap();
+    for (key, value) in fields {
+      page.insert(key.to_string(), value.clone()).unwrap();
+    }
+    let mut tag_array = self.doc.create_array().unwrap();
+    page.insert("tags".into(), tag_array.clone()).unwrap();
+    for tag in tags {
+      tag_array.push(*tag).unwrap();
+    }
+  }
+
+  /// Sets the workspace tags as `(id, name)`.
+  pub(crate) fn set_tags(&mut self, tags: &[(&str, &str)]) {
+    let mut meta = self.doc.get_or_create_map("meta").unwrap();
+    let mut properties = self.doc.create_map().unwrap();
+    meta
+      .insert("properties".into(), properties.clone())
+      .unwrap();
+    let mut tag_property = self.doc.create_map().unwrap();
+    properties
+      .insert("tags".into(), tag_property.clone())
+      .unwrap();
+    let mut options = self.doc.create_array().unwrap();
+    tag_property
+      .insert("options".into(), options.clone())
+      .unwrap();
+    for (id, name) in tags {
+      let mut option = self.doc.create_map().unwrap();
+      options.push(option.clone()).unwrap();
+      option.insert("id".into(), *id).unwrap();
+      option.insert("value".into(), *name).unwrap();
+    }
+  }
+
+  /// Appends a collection holding the docs of `allow_list` to the workspace
+  /// settings.
+  pub(crate) fn add_collection(&mut self, id: &str, name: &str, allow_list: &[&str]) {
+    let mut setting = self.doc.get_or_create_map("setting").unwrap();
+    let mut collections = match setting
+      .get("collections")
+      .and_then(|value| value.to_array())
+    {
+      Some(collections) => collections,
+      None => {
+        let collections = self.doc.create_array().unwrap();
+        setting
+          .insert("collections".into(), collections.clone())
+          .unwrap();
+        collections
+      }
+    };
+    collections
+      .push(Any::Object(
+        [
+          ("id".to_string(), Any::from(id)),
+          ("name".to_string(), Any::from(name)),
+          (
+            "allowList".to_string(),
+            Any::Array(allow_list.iter().map(|doc_id| Any::from(*doc_id)).collect()),
+          ),
+        ]
+        .into_iter()
+        .collect(),
+      ))
+      .unwrap();
+  }
+
+  pub(crate) fn encode(&self) -> Vec<u8> {
+    self.doc.encode_update_v1().unwrap()
+  }
+}
+
+/// Builds a workspace folders doc from records as `(id, parent id, type,
+/// data, index)`.
+pub(crate) fn folders_doc(records: &[(&str, Option<&str>, &str, &str, &str)]) -> Vec<u8> {
+  let doc = DocOptions::new().build();
+  for (id, parent_id, kind, data, index) in records {
+    let mut record = doc.get_or_create_map(id).unwrap();
+    for (key, value) in [("id", id), ("type", kind), ("data", data), ("index", index)] {
+      record.insert(key.into(), *value).unwrap();
+    }
+    if let Some(parent_id) = parent_id {
+      record.insert("parentId".into(), *parent_id).unwrap();
+    }
+  }
+  doc.encode_update_v1().unwrap()
+}
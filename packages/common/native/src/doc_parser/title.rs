@@ -0,0 +1,75 @@
+use super::{get_flavour, get_string, is_deterministic, new_doc, ParseError, PAGE_FLAVOUR};
+
+/// Reads the title of a doc without indexing its blocks, for listings that
+/// need nothing else from it.
+///
+/// Returns `None` when the doc has no `affine:page` block. The page is picked
+/// like [`super::parse_doc_from_binary`] picks it, so both agree on docs with
+/// several pages.
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::read_doc_title;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let title = read_doc_title(&doc_bin, "doc-id").unwrap();
+/// println!("{}", title.unwrap_or_else(|| "Untitled".into()));
+/// ```
+pub fn read_doc_title(doc_bin: &[u8], doc_id: &str) -> Result<Option<String>, ParseError> {
+  if doc_bin.is_empty() {
+    return Err(ParseError::InvalidBinary);
+  }
+  let mut doc = new_doc(Some(doc_id));
+  doc
+    .apply_update_from_binary_v1(doc_bin)
+    .map_err(|_| ParseError::InvalidBinary)?;
+
+  // an empty doc, such as one encoded as `[0, 0]`, has no blocks at all
+  let Ok(blocks) = doc.get_map("blocks") else {
+    return Ok(None);
+  };
+  let mut pages = blocks
+    .iter()
+    .filter_map(|(block_id, value)| Some((block_id.to_string(), value.to_map()?)))
+    .filter(|(_, block)| get_flavour(block).as_deref() == Some(PAGE_FLAVOUR));
+  let page = if is_deterministic() {
+    pages.min_by(|(a, _), (b, _)| a.cmp(b))
+  } else {
+    pages.next()
+  };
+  Ok(page.map(|(_, page)| get_string(&page, "prop:title").unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::{parse_doc_from_binary, test_utils::TestDoc};
+
+  #[test]
+  fn test_read_doc_title() {
+    let doc_bin = include_bytes!("../../fixtures/demo.ydoc");
+    let doc_id = "dYpV7PPhk8amRkY5IAcVO";
+    assert_eq!(
+      read_doc_title(doc_bin, doc_id).unwrap(),
+      Some(
+        parse_doc_from_binary(doc_bin.to_vec(), doc_id.into())
+          .unwrap()
+          .title
+      )
+    );
+
+    let doc = TestDoc::new("Roadmap");
+    assert_eq!(
+      read_doc_title(&doc.encode(), TestDoc::DOC_ID).unwrap(),
+      Some("Roadmap".into())
+    );
+
+    let empty = new_doc(None).encode_update_v1().unwrap();
+    assert_eq!(read_doc_title(&empty, "empty").unwrap(), None);
+    assert!(matches!(
+      read_doc_title(&[], "empty"),
+      Err(ParseError::InvalidBinary)
+    ));
+  }
+}
@@ -0,0 +1,407 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value as JsonValue;
+
+use super::{
+  AnalyticsOptions, AnchorKind, BlobHandling, BlockExtras, BlockFilter, BlockNode, BlockTree,
+  CleanupOptions, CompletionKind, ExportFallback, ExportFormat, ExportPreset, ExtrasField,
+  HealReason, IndexOp, LintRule, LintRules, MarkdownDialect, ParseError, PropRule, ReferenceKind,
+  RetentionAction, RetentionPolicy, RetentionReason, SecretKind, BUILTIN_FLAVOURS, EXTRAS_VERSION,
+};
+
+/// Lists every variant of a field-less enum. Adding a variant to the enum
+/// breaks the match until it is listed here too.
+macro_rules! variants {
+  ($ty:ident { $($variant:ident),+ $(,)? }) => {{
+    let _ = |value: $ty| match value {
+      $($ty::$variant)|+ => (),
+    };
+    vec![$($ty::$variant),+]
+  }};
+}
+
+/// TypeScript declarations of the string unions, option objects and extras
+/// that cross the JS API as strings or JSON, which napi cannot describe.
+///
+/// Everything is derived from the Rust definitions: unions from the serde
+/// names of every variant, interfaces from a sample of every field, with a
+/// field optional when the JSON may leave it out and `| null` when it may be
+/// `null`. `affine_server_native` checks the result in as `types.d.ts`, and
+/// its tests fail when the file is outdated.
+pub fn typescript_definitions() -> String {
+  let mut out =
+    String::from("// Generated from the Rust definitions of affine_common, do not edit.\n");
+
+  out.push_str(&union(
+    "BuiltinFlavour",
+    BUILTIN_FLAVOURS.iter().map(|flavour| literal(flavour)),
+  ));
+  out.push_str(&union(
+    "ParseErrorCode",
+    [
+      ParseError::DocNotFound,
+      ParseError::BlockNotFound(String::new()),
+      ParseError::InvalidBinary,
+      ParseError::SqliteError(String::new()),
+      ParseError::ParserError(String::new()),
+      ParseError::UnsupportedFormat(String::new()),
+      ParseError::PresetNotFound(String::new()),
+      ParseError::InvalidOptions(String::new()),
+      ParseError::Unknown(String::new()),
+    ]
+    .iter()
+    .map(|error| literal(error.code())),
+  ));
+
+  out.push_str(&enum_union(
+    "AnchorKind",
+    variants!(AnchorKind {
+      Block,
+      Heading,
+      Text
+    }),
+  ));
+  out.push_str(&enum_union(
+    "CompletionKind",
+    variants!(CompletionKind { Doc, Heading, Tag }),
+  ));
+  out.push_str(&enum_union(
+    "ExportFallback",
+    variants!(ExportFallback {
+      Omitted,
+      PlainText,
+      Html,
+      Link,
+      Generic
+    }),
+  ));
+  out.push_str(&enum_union(
+    "ExportFormat",
+    variants!(ExportFormat { Markdown }),
+  ));
+  out.push_str(&enum_union(
+    "ExtrasField",
+    variants!(ExtrasField {
+      DisplayMode,
+      NoteBlockId,
+      DatabaseName
+    }),
+  ));
+  out.push_str(&enum_union(
+    "HealReason",
+    variants!(HealReason {
+      Exists,
+      Content,
+      Parent
+    }),
+  ));
+  out.push_str(&enum_union(
+    "IndexOp",
+    variants!(IndexOp {
+      Add,
+      Update,
+      Delete
+    }),
+  ));
+  out.push_str(&enum_union(
+    "LintRule",
+    variants!(LintRule {
+      HeadingIncrement,
+      LineLength,
+      BareUrl
+    }),
+  ));
+  out.push_str(&enum_union(
+    "MarkdownDialect",
+    variants!(MarkdownDialect { Gfm, CommonMark }),
+  ));
+  out.push_str(&enum_union(
+    "ReferenceKind",
+    variants!(ReferenceKind {
+      Linked,
+      Synced,
+      Inline
+    }),
+  ));
+  out.push_str(&enum_union(
+    "RetentionAction",
+    variants!(RetentionAction { Archive, Delete }),
+  ));
+  out.push_str(&enum_union(
+    "RetentionReason",
+    variants!(RetentionReason { Age, Trash }),
+  ));
+  out.push_str(&enum_union(
+    "SecretKind",
+    variants!(SecretKind {
+      Aws,
+      PrivateKey,
+      Token,
+      Credential,
+      Entropy
+    }),
+  ));
+
+  out.push_str(&tagged_union(
+    "BlobHandling",
+    "mode",
+    &[
+      BlobHandling::Omit,
+      BlobHandling::Reference,
+      BlobHandling::Url {
+        template: "/blobs/{blobId}".into(),
+      },
+    ],
+  ));
+
+  // samples list every field, so adding one fails to compile until it is
+  // given a value here
+  out.push_str(&interface(
+    "AnalyticsOptions",
+    &AnalyticsOptions {
+      no_content: true,
+      include_trash: true,
+    },
+    &[],
+  ));
+  out.push_str(&interface(
+    "CleanupOptions",
+    &CleanupOptions {
+      remove_trailing_empty: true,
+      collapse_blank_runs: true,
+      trim_trailing_whitespace: true,
+    },
+    &[],
+  ));
+  out.push_str(&interface(
+    "PropRule",
+    &PropRule {
+      key: "prop:type".into(),
+      values: vec!["h1".into()],
+    },
+    &[],
+  ));
+  out.push_str(&interface(
+    "BlockFilter",
+    &BlockFilter {
+      deny_block_ids: vec!["block".into()],
+      deny_flavours: vec!["affine:code".into()],
+      deny_props: vec![],
+    },
+    &[("denyProps", "PropRule[]")],
+  ));
+  out.push_str(&interface(
+    "ExportPreset",
+    &ExportPreset {
+      name: "preset".into(),
+      format: ExportFormat::Markdown,
+      dialect: MarkdownDialect::Gfm,
+      frontmatter: true,
+      ai_editable: false,
+      doc_link_template: Some("/doc/{docId}".into()),
+      blobs: BlobHandling::Reference,
+      filter: Some(BlockFilter::default()),
+      numbered_headings: true,
+    },
+    &[
+      ("format", "ExportFormat"),
+      ("dialect", "MarkdownDialect"),
+      ("blobs", "BlobHandling"),
+      ("filter", "BlockFilter"),
+    ],
+  ));
+  out.push_str(&interface(
+    "LintRules",
+    &LintRules {
+      heading_increment: true,
+      max_line_length: Some(80),
+      bare_urls: true,
+    },
+    &[],
+  ));
+  out.push_str(&interface(
+    "RetentionPolicy",
+    &RetentionPolicy {
+      archive_after_days: Some(30),
+      delete_after_days: Some(90),
+      purge_trash_after_days: Some(30),
+      exempt_tags: vec!["legal".into()],
+    },
+    &[],
+  ));
+
+  out.push_str(&interface(
+    "BlockNode",
+    &BlockNode {
+      block_id: "block".into(),
+      flavour: "affine:paragraph".into(),
+      props: Default::default(),
+      children: vec![],
+    },
+    &[
+      ("props", "Record<string, unknown>"),
+      ("children", "BlockNode[]"),
+    ],
+  ));
+  out.push_str(&interface(
+    "BlockTree",
+    &BlockTree {
+      title: "title".into(),
+      root: BlockNode {
+        block_id: "page".into(),
+        flavour: "affine:page".into(),
+        props: Default::default(),
+        children: vec![],
+      },
+      block_count: 1,
+    },
+    &[("root", "BlockNode")],
+  ));
+
+  out.push_str(&format!(
+    "\nexport type ExtrasVersion = {EXTRAS_VERSION};\n"
+  ));
+  out.push_str(&interface(
+    "BlockExtras",
+    &BlockExtras {
+      version: EXTRAS_VERSION,
+      display_mode: Some("page".into()),
+      note_block_id: Some("note".into()),
+      database_name: Some("database".into()),
+    },
+    &[("version", "ExtrasVersion")],
+  ));
+
+  out
+}
+
+/// Declares `name` as the union of the TypeScript types `members`.
+fn union(name: &str, members: impl IntoIterator<Item = String>) -> String {
+  let members = members
+    .into_iter()
+    .map(|member| format!("  | {member}"))
+    .collect::<Vec<_>>();
+  format!("\nexport type {name} =\n{};\n", members.join("\n"))
+}
+
+fn literal(value: &str) -> String {
+  format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn enum_union<T: Serialize>(name: &str, variants: Vec<T>) -> String {
+  union(
+    name,
+    variants.iter().map(|variant| match to_json(variant) {
+      JsonValue::String(value) => literal(&value),
+      other => panic!("{name} variants must serialize to strings, got {other}"),
+    }),
+  )
+}
+
+/// Declares an enum serialized as objects with a `tag` naming the variant.
+fn tagged_union<T: Serialize>(name: &str, tag: &str, variants: &[T]) -> String {
+  union(
+    name,
+    variants.iter().map(|variant| {
+      let JsonValue::Object(fields) = to_json(variant) else {
+        panic!("{name} variants must serialize to objects");
+      };
+      let mut members = vec![format!(
+        "{tag}: {}",
+        literal(fields[tag].as_str().unwrap_or_default())
+      )];
+      for (key, value) in fields.iter().filter(|(key, _)| *key != tag) {
+        members.push(format!("{key}: {}", ts_type(value)));
+      }
+      format!("{{ {} }}", members.join("; "))
+    }),
+  )
+}
+
+/// Declares the fields of `sample` as an interface, naming the types of the
+/// fields listed in `types` instead of spelling them out.
+fn interface<T: Serialize + DeserializeOwned>(
+  name: &str,
+  sample: &T,
+  types: &[(&str, &str)],
+) -> String {
+  let JsonValue::Object(fields) = to_json(sample) else {
+    panic!("{name} must serialize to an object");
+  };
+
+  let mut out = format!("\nexport interface {name} {{\n");
+  for (key, value) in &fields {
+    // whether the JSON still deserializes with the field set to `value`, or
+    // left out when `None`
+    let accepts = |value: Option<JsonValue>| {
+      let mut fields = fields.clone();
+      match value {
+        Some(value) => fields.insert(key.clone(), value),
+        None => fields.remove(key),
+      };
+      serde_json::from_value::<T>(JsonValue::Object(fields)).is_ok()
+    };
+
+    let mut ty = types
+      .iter()
+      .find(|(field, _)| field == key)
+      .map(|(_, ty)| ty.to_string())
+      .unwrap_or_else(|| ts_type(value));
+    if accepts(Some(JsonValue::Null)) {
+      ty.push_str(" | null");
+    }
+    let optional = if accepts(None) { "?" } else { "" };
+    out.push_str(&format!("  {key}{optional}: {ty};\n"));
+  }
+  out.push_str("}\n");
+  out
+}
+
+fn to_json<T: Serialize>(value: &T) -> JsonValue {
+  serde_json::to_value(value).expect("samples serialize to JSON")
+}
+
+/// TypeScript type of a sample value.
+fn ts_type(value: &JsonValue) -> String {
+  match value {
+    JsonValue::Null => "null".into(),
+    JsonValue::Bool(_) => "boolean".into(),
+    JsonValue::Number(_) => "number".into(),
+    JsonValue::String(_) => "string".into(),
+    JsonValue::Array(items) => match items.first() {
+      Some(item) => format!("{}[]", ts_type(item)),
+      None => "unknown[]".into(),
+    },
+    JsonValue::Object(fields) => {
+      let members = fields
+        .iter()
+        .map(|(key, value)| format!("{key}: {}", ts_type(value)))
+        .collect::<Vec<_>>();
+      format!("{{ {} }}", members.join("; "))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_typescript_definitions() {
+    let definitions = typescript_definitions();
+    assert!(definitions.contains("export type BuiltinFlavour =\n  | 'affine:page'\n"));
+    assert!(definitions.contains("  | 'invalid_options'\n"));
+    assert!(definitions.contains("export type MarkdownDialect =\n  | 'gfm'\n  | 'commonmark';\n"));
+    assert!(definitions.contains("  | { mode: 'url'; template: string };\n"));
+    assert!(definitions.contains(
+      "export interface ExportPreset {\n  aiEditable?: boolean;\n  blobs?: BlobHandling;\n"
+    ));
+    assert!(definitions.contains("  docLinkTemplate?: string | null;\n"));
+    assert!(definitions.contains("  name: string;\n"));
+    assert!(definitions.contains("  maxLineLength?: number | null;\n"));
+    assert!(definitions.contains("  version?: ExtrasVersion;\n"));
+    assert!(definitions.contains("  children: BlockNode[];\n"));
+
+    let error = ParseError::BlockNotFound("b1".into());
+    assert!(error.to_string().starts_with(error.code()));
+  }
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// How an exporter represented a block it has no faithful rendering for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFallback {
+  /// Left out of the output.
+  Omitted,
+  /// Rendered as its plain text, without structure.
+  PlainText,
+  /// Rendered as embedded HTML.
+  Html,
+  /// Rendered as a link instead of its content.
+  Link,
+  /// A flavour the parser does not know, rendered as the text of its
+  /// common props by [`super::GenericFlavourHandler`].
+  Generic,
+}
+
+impl ExportFallback {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ExportFallback::Omitted => "omitted",
+      ExportFallback::PlainText => "plaintext",
+      ExportFallback::Html => "html",
+      ExportFallback::Link => "link",
+      ExportFallback::Generic => "generic",
+    }
+  }
+}
+
+/// A block an export could not represent faithfully.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsupportedBlock {
+  pub block_id: String,
+  pub flavour: String,
+  pub fallback: ExportFallback,
+}
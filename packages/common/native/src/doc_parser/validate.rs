@@ -0,0 +1,192 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use y_octo::Map;
+
+use super::{
+  can_parse, collect_child_ids, downgrade::flavour_schema_version, find_root_block_id,
+  get_block_id, get_flavour, is_builtin_flavour, new_doc, ParseError, SchemaCheck,
+};
+
+/// A block listing a child that is not in the doc.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DanglingChild {
+  pub parent_block_id: String,
+  pub child_block_id: String,
+}
+
+/// Integrity of a doc binary, see [`validate_doc_binary`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+  /// `true` when nothing below is wrong.
+  pub valid: bool,
+  /// Whether the binary decodes and applies to an empty doc. Nothing else is
+  /// checked when it does not.
+  pub decodable: bool,
+  /// The codec error when the binary is not decodable.
+  pub decode_error: Option<String>,
+  /// Lowest doc schema version that reads every flavour of the doc, see
+  /// [`super::DOC_SCHEMA_VERSION`].
+  pub schema_version: Option<u32>,
+  /// Block schema versions checked against [`super::get_supported_schema_range`].
+  pub schema: Option<SchemaCheck>,
+  pub block_count: usize,
+  /// The `affine:page` block, `None` when the doc has none.
+  pub root_block_id: Option<String>,
+  /// Blocks not reachable from the root, sorted.
+  pub orphan_block_ids: Vec<String>,
+  /// Sorted by parent, then child.
+  pub dangling_children: Vec<DanglingChild>,
+  /// Flavours the parser does not handle natively, sorted.
+  pub unknown_flavours: Vec<String>,
+}
+
+/// Checks a doc binary for the damage that makes parsing fail or lose
+/// content, reporting every problem instead of the first one.
+///
+/// The doc is loaded once; blocks without an id or a flavour are counted but
+/// otherwise ignored, like the parser does. Unknown flavours do not make a
+/// doc unreadable, they are reported for triage and crawled through
+/// [`super::GenericFlavourHandler`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use affine_common::doc_parser::validate_doc_binary;
+///
+/// let doc_bin = std::fs::read("fixtures/demo.ydoc").unwrap();
+/// let report = validate_doc_binary(&doc_bin, "doc-id").unwrap();
+/// if !report.valid {
+///   eprintln!("orphans: {:?}", report.orphan_block_ids);
+/// }
+/// ```
+pub fn validate_doc_binary(doc_bin: &[u8], doc_id: &str) -> Result<ValidationReport, ParseError> {
+  let mut report = ValidationReport {
+    valid: false,
+    decodable: false,
+    decode_error: None,
+    schema_version: None,
+    schema: None,
+    block_count: 0,
+    root_block_id: None,
+    orphan_block_ids: vec![],
+    dangling_children: vec![],
+    unknown_flavours: vec![],
+  };
+
+  if doc_bin.is_empty() || doc_bin == [0, 0] {
+    report.decode_error = Some(ParseError::InvalidBinary.to_string());
+    return Ok(report);
+  }
+  let mut doc = new_doc(Some(doc_id));
+  if let Err(e) = doc.apply_update_from_binary_v1(doc_bin) {
+    report.decode_error = Some(e.to_string());
+    return Ok(report);
+  }
+  report.decodable = true;
+
+  let mut block_pool: HashMap<String, Map> = HashMap::new();
+  for (_, value) in doc.get_map("blocks")?.iter() {
+    report.block_count += 1;
+    if let Some(block) = value.to_map() {
+      if let Some(block_id) = get_block_id(&block) {
+        block_pool.insert(block_id, block);
+      }
+    }
+  }
+
+  let mut unknown_flavours = BTreeSet::new();
+  let mut schema_version = 1;
+  let mut dangling_children = Vec::new();
+  for (block_id, block) in &block_pool {
+    if let Some(flavour) = get_flavour(block) {
+      schema_version = schema_version.max(flavour_schema_version(&flavour));
+      if !is_builtin_flavour(&flavour) {
+        unknown_flavours.insert(flavour);
+      }
+    }
+    for child_id in collect_child_ids(block) {
+      if !block_pool.contains_key(&child_id) {
+        dangling_children.push(DanglingChild {
+          parent_block_id: block_id.clone(),
+          child_block_id: child_id,
+        });
+      }
+    }
+  }
+  dangling_children.sort_by(|a, b| {
+    (&a.parent_block_id, &a.child_block_id).cmp(&(&b.parent_block_id, &b.child_block_id))
+  });
+
+  let root_block_id = find_root_block_id(&block_pool);
+  let mut reachable = HashSet::new();
+  let mut stack = root_block_id.iter().cloned().collect::<Vec<_>>();
+  while let Some(block_id) = stack.pop() {
+    let Some(block) = block_pool.get(&block_id) else {
+      continue;
+    };
+    if reachable.insert(block_id) {
+      stack.extend(collect_child_ids(block));
+    }
+  }
+  let mut orphan_block_ids = block_pool
+    .keys()
+    .filter(|block_id| !reachable.contains(*block_id))
+    .cloned()
+    .collect::<Vec<_>>();
+  orphan_block_ids.sort();
+
+  let schema = can_parse(doc_bin).ok();
+  report.valid = root_block_id.is_some()
+    && orphan_block_ids.is_empty()
+    && dangling_children.is_empty()
+    && unknown_flavours.is_empty()
+    && schema.as_ref().is_some_and(|schema| schema.supported);
+  report.schema_version = (!block_pool.is_empty()).then_some(schema_version);
+  report.schema = schema;
+  report.root_block_id = root_block_id;
+  report.orphan_block_ids = orphan_block_ids;
+  report.dangling_children = dangling_children;
+  report.unknown_flavours = unknown_flavours.into_iter().collect();
+  Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_validate_doc_binary() {
+    let mut doc = TestDoc::new("Checked");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "hello")]);
+    let report = validate_doc_binary(&doc.encode(), TestDoc::DOC_ID).unwrap();
+    assert!(report.valid, "{report:?}");
+    assert_eq!(report.block_count, 3);
+    assert_eq!(report.root_block_id.as_deref(), Some("page"));
+    assert_eq!(report.schema_version, Some(1));
+
+    doc.add_block("note", "l1", "affine:latex", &[("latex", "x")]);
+    doc.add_block("note", "c1", "vendor:chart", &[]);
+    doc.add_reference("note", "gone");
+    doc.add_block("note", "lost", "affine:paragraph", &[("text", "lost")]);
+    doc.add_block("lost", "lost-child", "affine:paragraph", &[]);
+    doc.remove_block("note", "lost");
+    let report = validate_doc_binary(&doc.encode(), TestDoc::DOC_ID).unwrap();
+    assert!(!report.valid);
+    assert_eq!(report.schema_version, Some(2));
+    assert_eq!(report.unknown_flavours, ["vendor:chart"]);
+    assert_eq!(
+      report.dangling_children,
+      [DanglingChild {
+        parent_block_id: "note".into(),
+        child_block_id: "gone".into(),
+      }]
+    );
+    assert_eq!(report.orphan_block_ids, ["lost-child"]);
+
+    let report = validate_doc_binary(b"junk", TestDoc::DOC_ID).unwrap();
+    assert!(!report.decodable);
+    assert!(report.decode_error.is_some());
+  }
+}
@@ -0,0 +1,229 @@
+use std::{
+  fs,
+  io::ErrorKind,
+  path::{Path, PathBuf},
+};
+
+use super::{
+  build_reference_graph, export_docs, parse_doc_from_path, parse_root_doc_meta,
+  read_folder_tree_from_root_doc, CrawlOptions, CrawlResult, DocMeta, ExportFormat, ExportResult,
+  FolderTree, MarkdownOptions, ParseError, ReferenceEdge,
+};
+use crate::progress::ProgressSink;
+
+/// The workspace root doc, listing the docs and their meta.
+pub const ROOT_DOC_FILE: &str = "root.ydoc";
+/// One `<doc id>.ydoc` snapshot per doc.
+pub const DOCS_DIR: &str = "docs";
+/// One file per blob, named after its key.
+pub const BLOBS_DIR: &str = "blobs";
+/// Id of the workspace folders doc, stored among the docs.
+pub const FOLDERS_DOC_ID: &str = "db$folders";
+
+/// A workspace stored as files, see [`load_workspace_dir`].
+#[derive(Debug, Clone)]
+pub struct WorkspaceDir {
+  pub path: PathBuf,
+  /// Ids of the docs in [`DOCS_DIR`], sorted.
+  pub doc_ids: Vec<String>,
+  /// Keys of the blobs in [`BLOBS_DIR`], sorted.
+  pub blob_keys: Vec<String>,
+}
+
+/// Opens a workspace stored as files, so tools without a database can run
+/// the workspace-level parsers on it. The directory is laid out as
+///
+/// ```text
+/// <path>/root.ydoc
+/// <path>/docs/<doc id>.ydoc
+/// <path>/blobs/<blob key>
+/// ```
+///
+/// Only the listing is read here, docs and blobs are read when asked for.
+/// `docs` and `blobs` may be missing, files in them that do not fit the
+/// layout are ignored.
+///
+/// Fails with `ParseError::DocNotFound` when there is no root doc.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// use affine_common::doc_parser::load_workspace_dir;
+///
+/// let workspace = load_workspace_dir(Path::new("backups/workspace")).unwrap();
+/// for meta in workspace.doc_metas().unwrap() {
+///   println!("{}: {}", meta.doc_id, meta.title);
+/// }
+/// ```
+pub fn load_workspace_dir(path: &Path) -> Result<WorkspaceDir, ParseError> {
+  if !path.join(ROOT_DOC_FILE).is_file() {
+    return Err(ParseError::DocNotFound);
+  }
+
+  let doc_ids = list_files(&path.join(DOCS_DIR))?
+    .into_iter()
+    .filter_map(|name| name.strip_suffix(".ydoc").map(str::to_string))
+    .filter(|doc_id| !doc_id.is_empty())
+    .collect();
+  let blob_keys = list_files(&path.join(BLOBS_DIR))?;
+
+  Ok(WorkspaceDir {
+    path: path.to_path_buf(),
+    doc_ids,
+    blob_keys,
+  })
+}
+
+impl WorkspaceDir {
+  pub fn read_root_doc(&self) -> Result<Vec<u8>, ParseError> {
+    read_file(&self.path.join(ROOT_DOC_FILE))
+  }
+
+  /// Fails with `ParseError::DocNotFound` when the doc is not in the
+  /// workspace.
+  pub fn read_doc(&self, doc_id: &str) -> Result<Vec<u8>, ParseError> {
+    read_file(&self.doc_path(doc_id)?)
+  }
+
+  /// Every doc of the workspace with its id, the folders doc included.
+  pub fn read_docs(&self) -> Result<Vec<(String, Vec<u8>)>, ParseError> {
+    self
+      .doc_ids
+      .iter()
+      .map(|doc_id| Ok((doc_id.clone(), self.read_doc(doc_id)?)))
+      .collect()
+  }
+
+  /// Fails with `ParseError::DocNotFound` when the blob is not in the
+  /// workspace.
+  pub fn read_blob(&self, key: &str) -> Result<Vec<u8>, ParseError> {
+    if !self.blob_keys.iter().any(|other| other == key) {
+      return Err(ParseError::DocNotFound);
+    }
+    read_file(&self.path.join(BLOBS_DIR).join(key))
+  }
+
+  /// [`parse_doc_from_path`] of a doc of the workspace.
+  pub fn crawl_doc(&self, doc_id: &str, options: &CrawlOptions) -> Result<CrawlResult, ParseError> {
+    parse_doc_from_path(&self.doc_path(doc_id)?, doc_id.to_string(), options)
+  }
+
+  /// [`parse_root_doc_meta`] of the workspace, with folders when it has a
+  /// folders doc.
+  pub fn doc_metas(&self) -> Result<Vec<DocMeta>, ParseError> {
+    parse_root_doc_meta(self.read_root_doc()?, self.read_folders_doc()?)
+  }
+
+  /// [`read_folder_tree_from_root_doc`] of the workspace.
+  pub fn folder_tree(&self) -> Result<FolderTree, ParseError> {
+    read_folder_tree_from_root_doc(self.read_root_doc()?, self.read_folders_doc()?)
+  }
+
+  /// [`build_reference_graph`] of all docs of the workspace.
+  pub fn reference_graph(&self) -> Result<Vec<ReferenceEdge>, ParseError> {
+    build_reference_graph(self.read_docs()?)
+  }
+
+  /// [`export_docs`] of all docs of the workspace but the folders doc.
+  pub fn export(
+    &self,
+    format: ExportFormat,
+    options: &MarkdownOptions,
+    progress: Option<&dyn ProgressSink>,
+  ) -> Result<Vec<ExportResult>, ParseError> {
+    let mut docs = self.read_docs()?;
+    docs.retain(|(doc_id, _)| doc_id != FOLDERS_DOC_ID);
+    export_docs(docs, format, options, progress)
+  }
+
+  fn read_folders_doc(&self) -> Result<Option<Vec<u8>>, ParseError> {
+    if !self.doc_ids.iter().any(|doc_id| doc_id == FOLDERS_DOC_ID) {
+      return Ok(None);
+    }
+    self.read_doc(FOLDERS_DOC_ID).map(Some)
+  }
+
+  /// Only ids from the listing are turned into paths, so a doc id cannot
+  /// reach outside of the workspace.
+  fn doc_path(&self, doc_id: &str) -> Result<PathBuf, ParseError> {
+    if !self.doc_ids.iter().any(|other| other == doc_id) {
+      return Err(ParseError::DocNotFound);
+    }
+    Ok(self.path.join(DOCS_DIR).join(format!("{doc_id}.ydoc")))
+  }
+}
+
+/// Names of the files in `dir`, sorted, none when it does not exist.
+fn list_files(dir: &Path) -> Result<Vec<String>, ParseError> {
+  let entries = match fs::read_dir(dir) {
+    Ok(entries) => entries,
+    Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+    Err(e) => return Err(io_error(dir, e)),
+  };
+
+  let mut names = Vec::new();
+  for entry in entries {
+    let entry = entry.map_err(|e| io_error(dir, e))?;
+    if !entry.file_type().map_err(|e| io_error(dir, e))?.is_file() {
+      continue;
+    }
+    if let Some(name) = entry.file_name().to_str() {
+      names.push(name.to_string());
+    }
+  }
+  names.sort();
+  Ok(names)
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, ParseError> {
+  fs::read(path).map_err(|e| match e.kind() {
+    ErrorKind::NotFound => ParseError::DocNotFound,
+    _ => io_error(path, e),
+  })
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> ParseError {
+  ParseError::Unknown(format!("{}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::doc_parser::test_utils::TestDoc;
+
+  #[test]
+  fn test_load_workspace_dir() {
+    let path = std::env::temp_dir().join(format!("workspace-dir-{}", std::process::id()));
+    fs::create_dir_all(path.join(DOCS_DIR)).unwrap();
+    fs::create_dir_all(path.join(BLOBS_DIR)).unwrap();
+    assert!(matches!(
+      load_workspace_dir(&path),
+      Err(ParseError::DocNotFound)
+    ));
+
+    let mut doc = TestDoc::new("Plan");
+    doc.add_block("note", "p1", "affine:paragraph", &[("text", "Ship it")]);
+    fs::write(path.join(ROOT_DOC_FILE), TestDoc::new("").encode()).unwrap();
+    fs::write(path.join(DOCS_DIR).join("plan.ydoc"), doc.encode()).unwrap();
+    fs::write(path.join(DOCS_DIR).join("notes.txt"), "not a doc").unwrap();
+    fs::write(path.join(BLOBS_DIR).join("blob-1"), [1, 2, 3]).unwrap();
+
+    let workspace = load_workspace_dir(&path).unwrap();
+    assert_eq!(workspace.doc_ids, ["plan"]);
+    assert_eq!(workspace.blob_keys, ["blob-1"]);
+    assert_eq!(workspace.read_blob("blob-1").unwrap(), [1, 2, 3]);
+
+    let result = workspace
+      .crawl_doc("plan", &CrawlOptions::default())
+      .unwrap();
+    assert_eq!(result.title, "Plan");
+    assert!(matches!(
+      workspace.read_doc("../root"),
+      Err(ParseError::DocNotFound)
+    ));
+
+    fs::remove_dir_all(path).unwrap();
+  }
+}
@@ -0,0 +1,310 @@
+//! Checks which sentences of a generated answer are backed by the sources it
+//! was given.
+
+use std::collections::HashSet;
+
+use crate::context_pack::ContextChunk;
+
+/// Words too common to count as evidence.
+const STOPWORDS: &[&str] = &[
+  "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "has", "have", "in", "is", "it",
+  "its", "of", "on", "or", "that", "the", "this", "to", "was", "were", "will", "with",
+];
+
+/// Share of the score given to matching word pairs over single words.
+const BIGRAM_WEIGHT: f64 = 0.6;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroundingOptions {
+  /// Lowest score, between 0 and 1, a chunk needs to support a sentence.
+  pub threshold: f64,
+  /// Citations kept per sentence, best first.
+  pub max_citations: usize,
+}
+
+impl Default for GroundingOptions {
+  fn default() -> Self {
+    Self {
+      threshold: 0.5,
+      max_citations: 3,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroundingCitation {
+  pub doc_id: String,
+  pub block_id: Option<String>,
+  /// Position of the chunk in the input.
+  pub chunk_index: usize,
+  pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroundedSentence {
+  /// Offsets in the answer, in UTF-16 units.
+  pub start: usize,
+  pub end: usize,
+  pub text: String,
+  /// Best score of any chunk, 0 for sentences without content words.
+  pub score: f64,
+  pub supported: bool,
+  /// Chunks supporting the sentence, best first.
+  pub citations: Vec<GroundingCitation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroundingReport {
+  pub sentences: Vec<GroundedSentence>,
+  /// Sentences with content words that are supported, over all of them. 1
+  /// when there are none.
+  pub supported_ratio: f64,
+}
+
+/// Splits `answer` into sentences and finds, for each, the chunks that
+/// support it.
+///
+/// A chunk scores by how many of the sentence's content words, and of its
+/// pairs of adjacent words, it contains, so copied or lightly reworded
+/// sentences score high and sentences that merely share a topic score low.
+/// Sentences are checked independently: while an answer streams, the
+/// sentences already complete keep their result as text is appended.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::{
+///   context_pack::ContextChunk,
+///   grounding::{verify_grounding, GroundingOptions},
+/// };
+///
+/// let source = ContextChunk {
+///   doc_id: "doc".into(),
+///   block_id: Some("block".into()),
+///   text: "The release ships on Friday after the final review.".into(),
+///   score: 0.0,
+/// };
+/// let report = verify_grounding(
+///   "The release ships on Friday. It includes a new editor.",
+///   &[source],
+///   &GroundingOptions::default(),
+/// );
+/// assert!(report.sentences[0].supported);
+/// assert!(!report.sentences[1].supported);
+/// ```
+pub fn verify_grounding(
+  answer: &str,
+  chunks: &[ContextChunk],
+  options: &GroundingOptions,
+) -> GroundingReport {
+  let sources = chunks
+    .iter()
+    .map(|chunk| {
+      let words = content_words(&chunk.text);
+      let bigrams = bigrams(&words);
+      (words.into_iter().collect::<HashSet<_>>(), bigrams)
+    })
+    .collect::<Vec<_>>();
+
+  let mut sentences = Vec::new();
+  let mut checked = 0;
+  let mut supported = 0;
+  for (start, end, text) in split_sentences(answer) {
+    let words = content_words(text);
+    let pairs = bigrams(&words);
+    let mut citations = Vec::new();
+    if !words.is_empty() {
+      for (chunk_index, (chunk_words, chunk_pairs)) in sources.iter().enumerate() {
+        let unigram = coverage(words.iter(), chunk_words, words.len());
+        let bigram = if pairs.is_empty() {
+          unigram
+        } else {
+          coverage(pairs.iter(), chunk_pairs, pairs.len())
+        };
+        let score = (1.0 - BIGRAM_WEIGHT) * unigram + BIGRAM_WEIGHT * bigram;
+        if score > 0.0 {
+          let chunk = &chunks[chunk_index];
+          citations.push(GroundingCitation {
+            doc_id: chunk.doc_id.clone(),
+            block_id: chunk.block_id.clone(),
+            chunk_index,
+            score,
+          });
+        }
+      }
+    }
+    // the sort is stable, equal scores keep the input order
+    citations.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let score = citations.first().map_or(0.0, |citation| citation.score);
+    citations.retain(|citation| citation.score >= options.threshold);
+    citations.truncate(options.max_citations);
+
+    let is_supported = !citations.is_empty();
+    if !words.is_empty() {
+      checked += 1;
+      supported += is_supported as usize;
+    }
+    sentences.push(GroundedSentence {
+      start,
+      end,
+      text: text.to_string(),
+      score,
+      supported: is_supported,
+      citations,
+    });
+  }
+
+  GroundingReport {
+    sentences,
+    supported_ratio: if checked == 0 {
+      1.0
+    } else {
+      supported as f64 / checked as f64
+    },
+  }
+}
+
+/// Sentences of `text` with their UTF-16 offsets, trimmed. A sentence ends
+/// after `.`, `!` or `?` followed by whitespace, after CJK end marks and at
+/// line breaks.
+fn split_sentences(text: &str) -> Vec<(usize, usize, &str)> {
+  let mut sentences = Vec::new();
+  let mut push = |range: std::ops::Range<usize>| {
+    let sentence = &text[range.clone()];
+    let trimmed = sentence.trim();
+    if trimmed.is_empty() {
+      return;
+    }
+    let start = range.start + (sentence.len() - sentence.trim_start().len());
+    let utf16_start = text[..start].encode_utf16().count();
+    sentences.push((
+      utf16_start,
+      utf16_start + trimmed.encode_utf16().count(),
+      trimmed,
+    ));
+  };
+
+  let mut start = 0;
+  let mut chars = text.char_indices().peekable();
+  while let Some((i, char)) = chars.next() {
+    let next = chars.peek().map(|(_, next)| *next);
+    let end = match char {
+      '\n' => true,
+      '。' | '！' | '？' => true,
+      '.' | '!' | '?' => next.is_none_or(char::is_whitespace),
+      _ => false,
+    };
+    if end {
+      let end = i + char.len_utf8();
+      push(start..end);
+      start = end;
+    }
+  }
+  push(start..text.len());
+  sentences
+}
+
+/// Lowercased words without stopwords. CJK characters count as one word
+/// each, since those scripts do not separate words with spaces.
+fn content_words(text: &str) -> Vec<String> {
+  let mut words = Vec::new();
+  let mut word = String::new();
+  let mut flush = |word: &mut String| {
+    if !word.is_empty() && !STOPWORDS.contains(&word.as_str()) {
+      words.push(std::mem::take(word));
+    }
+    word.clear();
+  };
+  for char in text.chars() {
+    if is_cjk(char) {
+      flush(&mut word);
+      word.push(char);
+      flush(&mut word);
+    } else if char.is_alphanumeric() {
+      word.extend(char.to_lowercase());
+    } else {
+      flush(&mut word);
+    }
+  }
+  flush(&mut word);
+  words
+}
+
+fn bigrams(words: &[String]) -> HashSet<(String, String)> {
+  words
+    .windows(2)
+    .map(|pair| (pair[0].clone(), pair[1].clone()))
+    .collect()
+}
+
+fn coverage<'a, T: Eq + std::hash::Hash + 'a>(
+  items: impl Iterator<Item = &'a T>,
+  source: &HashSet<T>,
+  len: usize,
+) -> f64 {
+  items.filter(|item| source.contains(*item)).count() as f64 / len as f64
+}
+
+/// Kana, CJK ideographs and Hangul, scripts written without spaces between
+/// words.
+pub(crate) fn is_cjk(char: char) -> bool {
+  matches!(
+    char,
+    '\u{3040}'..='\u{30ff}' | '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{ac00}'..='\u{d7af}'
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn chunk(doc_id: &str, text: &str) -> ContextChunk {
+    ContextChunk {
+      doc_id: doc_id.into(),
+      block_id: None,
+      text: text.into(),
+      score: 0.0,
+    }
+  }
+
+  #[test]
+  fn test_verify_grounding() {
+    let chunks = [
+      chunk("a", "Invoices are due within thirty days of delivery."),
+      chunk("b", "Late invoices incur a two percent monthly fee."),
+    ];
+    let answer = "Invoices are due within thirty days. Late invoices incur a fee of two percent!\nThe CEO likes tea. 请 按时 付款。";
+    let report = verify_grounding(answer, &chunks, &GroundingOptions::default());
+
+    let sentences = report
+      .sentences
+      .iter()
+      .map(|sentence| (sentence.text.as_str(), sentence.supported))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      sentences,
+      [
+        ("Invoices are due within thirty days.", true),
+        ("Late invoices incur a fee of two percent!", true),
+        ("The CEO likes tea.", false),
+        ("请 按时 付款。", false),
+      ]
+    );
+    assert_eq!(report.sentences[0].citations[0].doc_id, "a");
+    assert_eq!(report.sentences[1].citations[0].chunk_index, 1);
+    assert_eq!(report.supported_ratio, 0.5);
+
+    let second = &report.sentences[1];
+    assert_eq!(
+      answer
+        .encode_utf16()
+        .skip(second.start)
+        .take(second.end - second.start)
+        .collect::<Vec<_>>(),
+      second.text.encode_utf16().collect::<Vec<_>>()
+    );
+
+    // decimals and abbreviations without a following space do not split
+    assert_eq!(split_sentences("Pi is 3.14 today.").len(), 1);
+  }
+}
@@ -1,6 +1,20 @@
+pub mod activity;
+#[cfg(feature = "blob-hash")]
+pub mod blob_hash;
+pub mod context_pack;
+pub mod deterministic;
 #[cfg(feature = "doc-loader")]
 pub mod doc_loader;
 #[cfg(feature = "ydoc-loader")]
 pub mod doc_parser;
+pub mod grounding;
 #[cfg(feature = "hashcash")]
 pub mod hashcash;
+pub mod progress;
+pub mod ranking;
+#[cfg(feature = "search-index")]
+pub mod search_index;
+pub mod search_query;
+pub mod spelling;
+#[cfg(feature = "vector-index")]
+pub mod vector_index;
@@ -0,0 +1,26 @@
+//! Progress reporting for long running exports and imports.
+//!
+//! Operations report after every completed step, so a UI can render a real
+//! progress bar. `done` never exceeds `total`, and the last report of a stage
+//! has `done == total`.
+
+/// A snapshot of the progress of one stage of an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+  /// Name of the running stage, e.g. `"export"`.
+  pub stage: &'static str,
+  pub done: usize,
+  pub total: usize,
+}
+
+/// Receives progress reports. Implemented for closures taking a
+/// [`Progress`].
+pub trait ProgressSink {
+  fn report(&self, progress: Progress);
+}
+
+impl<F: Fn(Progress)> ProgressSink for F {
+  fn report(&self, progress: Progress) {
+    self(progress)
+  }
+}
@@ -0,0 +1,210 @@
+//! Hybrid ranking of search hits from a lexical and a vector backend.
+
+use std::collections::HashMap;
+
+/// One entry of a ranked result list, best first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankedHit {
+  pub doc_id: String,
+  pub block_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitSource {
+  Lexical,
+  Vector,
+}
+
+impl HitSource {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      HitSource::Lexical => "lexical",
+      HitSource::Vector => "vector",
+    }
+  }
+}
+
+/// A hit that contributed to a fused result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HitProvenance {
+  pub block_id: Option<String>,
+  pub source: HitSource,
+  /// 1-based position of the hit in its source list.
+  pub position: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FusedHit {
+  pub doc_id: String,
+  /// Block of the best ranked hit of the result.
+  pub block_id: Option<String>,
+  pub score: f64,
+  /// 1-based rank of the result in the lexical list, if it appears there.
+  pub lexical_rank: Option<usize>,
+  pub vector_rank: Option<usize>,
+  /// Every hit merged into the result, lexical hits first.
+  pub provenance: Vec<HitProvenance>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FusionOptions {
+  /// Damping constant of reciprocal rank fusion, higher values flatten the
+  /// advantage of top ranks.
+  pub k: f64,
+  pub lexical_weight: f64,
+  pub vector_weight: f64,
+  /// Merge hits of the same doc into one result.
+  pub dedupe_docs: bool,
+  pub limit: Option<usize>,
+}
+
+impl Default for FusionOptions {
+  fn default() -> Self {
+    Self {
+      k: 60.0,
+      lexical_weight: 1.0,
+      vector_weight: 1.0,
+      dedupe_docs: true,
+      limit: None,
+    }
+  }
+}
+
+/// Merges lexical (e.g. BM25) and vector hits with reciprocal rank fusion:
+/// a result scores `weight / (k + rank)` for each list it appears in.
+///
+/// Only ranks matter, so scores of different backends never need to be
+/// comparable and a missing backend simply contributes nothing. With
+/// `dedupe_docs`, a doc ranks by its best hit in each list and the ranks of
+/// the docs below it move up accordingly. Ties are ordered by doc and block
+/// id, so the output is deterministic.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::ranking::{fuse_rankings, FusionOptions, RankedHit};
+///
+/// let hit = |doc_id: &str| RankedHit { doc_id: doc_id.into(), block_id: None };
+/// let fused = fuse_rankings(&[hit("a"), hit("b")], &[hit("b")], &FusionOptions::default());
+/// assert_eq!(fused[0].doc_id, "b");
+/// ```
+pub fn fuse_rankings(
+  lexical_hits: &[RankedHit],
+  vector_hits: &[RankedHit],
+  options: &FusionOptions,
+) -> Vec<FusedHit> {
+  let mut results: Vec<FusedHit> = Vec::new();
+  let mut positions: HashMap<(String, Option<String>), usize> = HashMap::new();
+
+  for (source, hits, weight) in [
+    (HitSource::Lexical, lexical_hits, options.lexical_weight),
+    (HitSource::Vector, vector_hits, options.vector_weight),
+  ] {
+    let mut rank = 0;
+    for (index, hit) in hits.iter().enumerate() {
+      let key = (
+        hit.doc_id.clone(),
+        hit.block_id.clone().filter(|_| !options.dedupe_docs),
+      );
+      let position = *positions.entry(key).or_insert_with(|| {
+        results.push(FusedHit {
+          doc_id: hit.doc_id.clone(),
+          block_id: hit.block_id.clone(),
+          score: 0.0,
+          lexical_rank: None,
+          vector_rank: None,
+          provenance: vec![],
+        });
+        results.len() - 1
+      });
+      let result = &mut results[position];
+      result.provenance.push(HitProvenance {
+        block_id: hit.block_id.clone(),
+        source,
+        position: index + 1,
+      });
+
+      let source_rank = match source {
+        HitSource::Lexical => &mut result.lexical_rank,
+        HitSource::Vector => &mut result.vector_rank,
+      };
+      if source_rank.is_some() {
+        continue;
+      }
+      rank += 1;
+      *source_rank = Some(rank);
+      result.score += weight / (options.k + rank as f64);
+      // the representative block is the one of the best ranked hit, lexical
+      // hits winning ties
+      if result
+        .lexical_rank
+        .is_none_or(|lexical_rank| rank < lexical_rank)
+      {
+        result.block_id = hit.block_id.clone();
+      }
+    }
+  }
+
+  results.sort_by(|a, b| {
+    b.score
+      .total_cmp(&a.score)
+      .then_with(|| a.doc_id.cmp(&b.doc_id))
+      .then_with(|| a.block_id.cmp(&b.block_id))
+  });
+  if let Some(limit) = options.limit {
+    results.truncate(limit);
+  }
+  results
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn hit(doc_id: &str, block_id: &str) -> RankedHit {
+    RankedHit {
+      doc_id: doc_id.into(),
+      block_id: Some(block_id.into()),
+    }
+  }
+
+  #[test]
+  fn test_fuse_rankings() {
+    let lexical = [
+      hit("a", "a1"),
+      hit("a", "a2"),
+      hit("b", "b1"),
+      hit("c", "c1"),
+    ];
+    let vector = [hit("c", "c2"), hit("b", "b2")];
+
+    let fused = fuse_rankings(&lexical, &vector, &FusionOptions::default());
+    let ids = fused
+      .iter()
+      .map(|hit| (hit.doc_id.as_str(), hit.block_id.as_deref().unwrap()))
+      .collect::<Vec<_>>();
+    // b: 1/62 + 1/62, c: 1/63 + 1/61, a: 1/61
+    assert_eq!(ids, vec![("c", "c2"), ("b", "b1"), ("a", "a1")]);
+    assert_eq!(
+      (fused[1].lexical_rank, fused[1].vector_rank),
+      (Some(2), Some(2))
+    );
+    assert_eq!(fused[2].provenance.len(), 2);
+    assert_eq!(fused[0].provenance[1].source, HitSource::Vector);
+
+    let options = FusionOptions {
+      dedupe_docs: false,
+      vector_weight: 0.0,
+      limit: Some(2),
+      ..Default::default()
+    };
+    let fused = fuse_rankings(&lexical, &vector, &options);
+    let ids = fused
+      .iter()
+      .map(|hit| hit.block_id.as_deref().unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["a1", "a2"]);
+
+    assert!(fuse_rankings(&[], &[], &FusionOptions::default()).is_empty());
+  }
+}
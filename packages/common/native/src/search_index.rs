@@ -0,0 +1,486 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{grounding::is_cjk, search_query::parse_search_query};
+
+const MAGIC: &[u8; 4] = b"AFSI";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4;
+
+/// How fast repeated terms stop adding to the BM25 score.
+const K1: f32 = 1.2;
+/// How much BM25 favours short docs.
+const B: f32 = 0.75;
+/// Times a term in the title counts.
+const TITLE_WEIGHT: u32 = 2;
+/// Most blocks listed in a hit.
+const MAX_BLOCKS_PER_HIT: usize = 3;
+/// Characters kept on each side of the match in a snippet.
+const SNIPPET_CONTEXT: usize = 40;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SearchIndexError {
+  #[error("invalid search index data")]
+  InvalidData,
+  #[error("unsupported search index version {0}")]
+  UnsupportedVersion(u8),
+}
+
+pub type SearchIndexResult<T> = Result<T, SearchIndexError>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedBlock {
+  pub block_id: String,
+  pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+  pub doc_id: String,
+  pub title: String,
+  /// BM25 score of the doc, higher is better.
+  pub score: f32,
+  /// Blocks with the most distinct query terms, then in document order.
+  /// Empty when only the title matched.
+  pub blocks: Vec<BlockHit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHit {
+  pub block_id: String,
+  /// The text around the first match, with `…` where it was cut.
+  pub snippet: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct IndexedDoc {
+  title: String,
+  blocks: Vec<IndexedBlock>,
+  /// Terms of the doc, those of the title counted [`TITLE_WEIGHT`] times.
+  length: u32,
+}
+
+/// In-memory inverted index over the titles and block texts of docs, ranking
+/// docs with BM25, for workspace search without a search server.
+///
+/// Only the texts are serialized, postings are rebuilt when the index is
+/// restored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchIndex {
+  docs: HashMap<String, IndexedDoc>,
+  /// Frequency of every term by doc.
+  postings: HashMap<String, HashMap<String, u32>>,
+  /// Sum of the lengths of all docs.
+  total_length: u64,
+}
+
+impl SearchIndex {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Number of docs.
+  pub fn len(&self) -> usize {
+    self.docs.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.docs.is_empty()
+  }
+
+  pub fn contains(&self, doc_id: &str) -> bool {
+    self.docs.contains_key(doc_id)
+  }
+
+  /// Adds a doc, replacing the one indexed under the same id.
+  pub fn add_doc(&mut self, doc_id: &str, title: &str, blocks: Vec<IndexedBlock>) {
+    self.remove_doc(doc_id);
+
+    let mut frequencies: HashMap<String, u32> = HashMap::new();
+    for token in tokenize(title) {
+      *frequencies.entry(token.term).or_default() += TITLE_WEIGHT;
+    }
+    for block in &blocks {
+      for token in tokenize(&block.text) {
+        *frequencies.entry(token.term).or_default() += 1;
+      }
+    }
+    let length = frequencies.values().sum();
+    for (term, frequency) in frequencies {
+      self
+        .postings
+        .entry(term)
+        .or_default()
+        .insert(doc_id.to_string(), frequency);
+    }
+
+    self.total_length += u64::from(length);
+    self.docs.insert(
+      doc_id.to_string(),
+      IndexedDoc {
+        title: title.to_string(),
+        blocks,
+        length,
+      },
+    );
+  }
+
+  /// Removes a doc, returning whether it was indexed.
+  pub fn remove_doc(&mut self, doc_id: &str) -> bool {
+    let Some(doc) = self.docs.remove(doc_id) else {
+      return false;
+    };
+    self.total_length -= u64::from(doc.length);
+    for token in doc.texts().flat_map(tokenize) {
+      if let Some(docs) = self.postings.get_mut(&token.term) {
+        docs.remove(doc_id);
+        if docs.is_empty() {
+          self.postings.remove(&token.term);
+        }
+      }
+    }
+    true
+  }
+
+  /// Returns the `limit` docs matching `query` best, best first. Equal
+  /// scores are ordered by doc id.
+  ///
+  /// The query is read with [`parse_search_query`]. A doc matches when its
+  /// title and blocks hold every term and phrase; filters like `tag:` are
+  /// left to the caller.
+  pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+    let query = parse_search_query(query);
+    let phrases = query
+      .phrases
+      .iter()
+      .map(|phrase| phrase.to_lowercase())
+      .collect::<Vec<_>>();
+    let mut terms = query
+      .terms
+      .iter()
+      .chain(&query.phrases)
+      .flat_map(|text| tokenize(text))
+      .map(|token| token.term)
+      .collect::<Vec<_>>();
+    terms.sort_unstable();
+    terms.dedup();
+
+    let mut postings = Vec::with_capacity(terms.len());
+    for term in &terms {
+      let Some(docs) = self.postings.get(term) else {
+        return vec![];
+      };
+      postings.push(docs);
+    }
+    // candidates come from the rarest term
+    postings.sort_by_key(|docs| docs.len());
+    let Some((rarest, others)) = postings.split_first() else {
+      return vec![];
+    };
+
+    let count = self.docs.len() as f32;
+    let average_length = self.total_length as f32 / count;
+    let mut scores = rarest
+      .keys()
+      .filter(|doc_id| others.iter().all(|docs| docs.contains_key(*doc_id)))
+      .filter(|doc_id| {
+        let doc = &self.docs[*doc_id];
+        phrases.iter().all(|phrase| {
+          doc
+            .texts()
+            .any(|text| text.to_lowercase().contains(phrase.as_str()))
+        })
+      })
+      .map(|doc_id| {
+        let length = self.docs[doc_id].length as f32;
+        let score = postings
+          .iter()
+          .map(|docs| {
+            let frequency = docs[doc_id] as f32;
+            let matching = docs.len() as f32;
+            let idf = (1.0 + (count - matching + 0.5) / (matching + 0.5)).ln();
+            idf * frequency * (K1 + 1.0)
+              / (frequency + K1 * (1.0 - B + B * length / average_length))
+          })
+          .sum::<f32>();
+        (doc_id, score)
+      })
+      .collect::<Vec<_>>();
+    scores.sort_unstable_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    scores.truncate(limit);
+
+    scores
+      .into_iter()
+      .map(|(doc_id, score)| {
+        let doc = &self.docs[doc_id];
+        SearchHit {
+          doc_id: doc_id.clone(),
+          title: doc.title.clone(),
+          score,
+          blocks: doc.block_hits(&terms),
+        }
+      })
+      .collect()
+  }
+
+  pub fn from_bytes(bytes: &[u8]) -> SearchIndexResult<Self> {
+    if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+      return Err(SearchIndexError::InvalidData);
+    }
+    if bytes[MAGIC.len()] != VERSION {
+      return Err(SearchIndexError::UnsupportedVersion(bytes[MAGIC.len()]));
+    }
+
+    let mut reader = Reader {
+      bytes,
+      at: MAGIC.len() + 1,
+    };
+    let mut index = Self::new();
+    for _ in 0..reader.u32()? {
+      let doc_id = reader.string()?;
+      let title = reader.string()?;
+      let blocks = (0..reader.u32()?)
+        .map(|_| {
+          Ok(IndexedBlock {
+            block_id: reader.string()?.to_string(),
+            text: reader.string()?.to_string(),
+          })
+        })
+        .collect::<SearchIndexResult<Vec<_>>>()?;
+      index.add_doc(doc_id, title, blocks);
+    }
+    if reader.at != bytes.len() {
+      return Err(SearchIndexError::InvalidData);
+    }
+    Ok(index)
+  }
+
+  /// Serializes the index with its docs in id order, so equal indexes give
+  /// equal bytes.
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN);
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&(self.docs.len() as u32).to_le_bytes());
+
+    let write_string = |bytes: &mut Vec<u8>, value: &str| {
+      bytes.extend_from_slice(&(value.len() as u32).to_le_bytes());
+      bytes.extend_from_slice(value.as_bytes());
+    };
+    let mut docs = self.docs.iter().collect::<Vec<_>>();
+    docs.sort_unstable_by_key(|(doc_id, _)| *doc_id);
+    for (doc_id, doc) in docs {
+      write_string(&mut bytes, doc_id);
+      write_string(&mut bytes, &doc.title);
+      bytes.extend_from_slice(&(doc.blocks.len() as u32).to_le_bytes());
+      for block in &doc.blocks {
+        write_string(&mut bytes, &block.block_id);
+        write_string(&mut bytes, &block.text);
+      }
+    }
+    bytes
+  }
+}
+
+impl IndexedDoc {
+  fn texts(&self) -> impl Iterator<Item = &str> {
+    std::iter::once(self.title.as_str()).chain(self.blocks.iter().map(|block| block.text.as_str()))
+  }
+
+  /// The blocks of [`SearchHit::blocks`].
+  fn block_hits(&self, terms: &[String]) -> Vec<BlockHit> {
+    let mut matches = self
+      .blocks
+      .iter()
+      .filter_map(|block| {
+        let tokens = tokenize(&block.text);
+        let first = tokens.iter().find(|token| terms.contains(&token.term))?;
+        let distinct = terms
+          .iter()
+          .filter(|term| tokens.iter().any(|token| &token.term == *term))
+          .count();
+        Some((distinct, block, first.start, first.end))
+      })
+      .collect::<Vec<_>>();
+    // stable, so blocks with as many terms stay in document order
+    matches.sort_by_key(|(distinct, ..)| std::cmp::Reverse(*distinct));
+
+    matches
+      .into_iter()
+      .take(MAX_BLOCKS_PER_HIT)
+      .map(|(_, block, start, end)| BlockHit {
+        block_id: block.block_id.clone(),
+        snippet: snippet(&block.text, start, end),
+      })
+      .collect()
+  }
+}
+
+struct Reader<'a> {
+  bytes: &'a [u8],
+  at: usize,
+}
+
+impl<'a> Reader<'a> {
+  fn u32(&mut self) -> SearchIndexResult<u32> {
+    let value = self
+      .bytes
+      .get(self.at..self.at + 4)
+      .map(|value| u32::from_le_bytes(value.try_into().unwrap()))
+      .ok_or(SearchIndexError::InvalidData)?;
+    self.at += 4;
+    Ok(value)
+  }
+
+  fn string(&mut self) -> SearchIndexResult<&'a str> {
+    let len = self.u32()? as usize;
+    let value = self
+      .bytes
+      .get(self.at..self.at + len)
+      .and_then(|value| std::str::from_utf8(value).ok())
+      .ok_or(SearchIndexError::InvalidData)?;
+    self.at += len;
+    Ok(value)
+  }
+}
+
+struct Token {
+  /// Byte range in the text.
+  start: usize,
+  end: usize,
+  term: String,
+}
+
+/// Lowercased alphanumeric runs of `text`. CJK characters are a term each,
+/// since those scripts do not separate words with spaces.
+fn tokenize(text: &str) -> Vec<Token> {
+  let mut tokens = Vec::new();
+  let mut current: Option<Token> = None;
+  for (start, char) in text.char_indices() {
+    let end = start + char.len_utf8();
+    if is_cjk(char) {
+      tokens.extend(current.take());
+      tokens.push(Token {
+        start,
+        end,
+        term: char.to_string(),
+      });
+    } else if char.is_alphanumeric() {
+      let token = current.get_or_insert_with(|| Token {
+        start,
+        end,
+        term: String::new(),
+      });
+      token.end = end;
+      token.term.extend(char.to_lowercase());
+    } else {
+      tokens.extend(current.take());
+    }
+  }
+  tokens.extend(current);
+  tokens
+}
+
+/// `text` cut to [`SNIPPET_CONTEXT`] characters around the match at
+/// `start..end`, on one line.
+fn snippet(text: &str, start: usize, end: usize) -> String {
+  let from = text[..start]
+    .char_indices()
+    .rev()
+    .nth(SNIPPET_CONTEXT - 1)
+    .map_or(0, |(i, _)| i);
+  let to = text[end..]
+    .char_indices()
+    .nth(SNIPPET_CONTEXT)
+    .map_or(text.len(), |(i, _)| end + i);
+
+  let mut snippet = text[from..to].trim().replace('\n', " ");
+  if from > 0 {
+    snippet.insert(0, '…');
+  }
+  if to < text.len() {
+    snippet.push('…');
+  }
+  snippet
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn blocks(texts: &[(&str, &str)]) -> Vec<IndexedBlock> {
+    texts
+      .iter()
+      .map(|(block_id, text)| IndexedBlock {
+        block_id: block_id.to_string(),
+        text: text.to_string(),
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_search_index() {
+    let mut index = SearchIndex::new();
+    index.add_doc(
+      "roadmap",
+      "Roadmap",
+      blocks(&[
+        ("r1", "Ship the new editor in Q3."),
+        ("r2", "Then the sync engine and the editor plugins."),
+      ]),
+    );
+    index.add_doc(
+      "notes",
+      "Meeting notes",
+      blocks(&[("n1", "We talked about the editor.")]),
+    );
+    index.add_doc("recipes", "Recipes", blocks(&[("c1", "Bake bread.")]));
+
+    let ids = |hits: &[SearchHit]| {
+      hits
+        .iter()
+        .map(|hit| hit.doc_id.clone())
+        .collect::<Vec<_>>()
+    };
+    let hits = index.search("Editor", 10);
+    assert_eq!(ids(&hits), ["roadmap", "notes"]);
+    assert_eq!(
+      hits[0].blocks,
+      [
+        BlockHit {
+          block_id: "r1".into(),
+          snippet: "Ship the new editor in Q3.".into()
+        },
+        BlockHit {
+          block_id: "r2".into(),
+          snippet: "Then the sync engine and the editor plugins.".into()
+        },
+      ]
+    );
+    assert_eq!(ids(&index.search("editor sync", 10)), ["roadmap"]);
+    assert_eq!(ids(&index.search("\"about the editor\"", 10)), ["notes"]);
+    assert_eq!(ids(&index.search("meeting", 10)), ["notes"]);
+    assert!(index.search("meeting", 10)[0].blocks.is_empty());
+    assert!(index.search("editor missing", 10).is_empty());
+
+    let long = format!("{}needle{}", "a ".repeat(30), " b".repeat(30));
+    index.add_doc("long", "", blocks(&[("l1", &long)]));
+    assert_eq!(
+      index.search("needle", 1)[0].blocks[0].snippet,
+      format!("…{}needle{}…", "a ".repeat(20), " b".repeat(20))
+    );
+
+    assert!(index.remove_doc("roadmap"));
+    assert!(!index.remove_doc("roadmap"));
+    assert_eq!(ids(&index.search("editor", 10)), ["notes"]);
+    assert!(index.search("sync", 10).is_empty());
+
+    let restored = SearchIndex::from_bytes(&index.to_bytes()).unwrap();
+    assert_eq!(restored, index);
+    assert_eq!(
+      SearchIndex::from_bytes(b"AFSI"),
+      Err(SearchIndexError::InvalidData)
+    );
+  }
+}
@@ -0,0 +1,205 @@
+//! Search syntax shared by every search backend.
+//!
+//! A query is a whitespace separated list of terms, `"quoted phrases"` and
+//! operators:
+//!
+//! - `tag:name` and `in:folder`, values may be quoted
+//! - `is:flag`, e.g. `is:todo`
+//! - `updated:` and `created:` followed by `>`, `>=`, `<`, `<=` or nothing
+//!   and a `YYYY-MM-DD` date
+//!
+//! Unknown operators and invalid dates are searched for as plain terms.
+
+use chrono::NaiveDate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateField {
+  Created,
+  Updated,
+}
+
+impl DateField {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      DateField::Created => "created",
+      DateField::Updated => "updated",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOp {
+  Eq,
+  Gt,
+  Ge,
+  Lt,
+  Le,
+}
+
+impl DateOp {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      DateOp::Eq => "=",
+      DateOp::Gt => ">",
+      DateOp::Ge => ">=",
+      DateOp::Lt => "<",
+      DateOp::Le => "<=",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateFilter {
+  pub field: DateField,
+  pub op: DateOp,
+  pub date: NaiveDate,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+  pub terms: Vec<String>,
+  pub phrases: Vec<String>,
+  pub tags: Vec<String>,
+  pub folders: Vec<String>,
+  /// Lowercased values of `is:` operators.
+  pub flags: Vec<String>,
+  pub dates: Vec<DateFilter>,
+}
+
+/// Parses a search string into its terms and filters, see the
+/// [module docs](self) for the syntax.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::search_query::parse_search_query;
+///
+/// let query = parse_search_query(r#"roadmap tag:"q3 plans" is:todo"#);
+/// assert_eq!(query.terms, vec!["roadmap"]);
+/// assert_eq!(query.tags, vec!["q3 plans"]);
+/// assert_eq!(query.flags, vec!["todo"]);
+/// ```
+pub fn parse_search_query(input: &str) -> SearchQuery {
+  let mut query = SearchQuery::default();
+  for token in tokenize(input) {
+    if let Some(phrase) = token.strip_prefix('"') {
+      let phrase = unquote(phrase);
+      if !phrase.is_empty() {
+        query.phrases.push(phrase.to_string());
+      }
+      continue;
+    }
+    if !query.apply_operator(&token) {
+      query.terms.push(token);
+    }
+  }
+  query
+}
+
+impl SearchQuery {
+  /// Applies `token` if it is a known operator with a valid value.
+  fn apply_operator(&mut self, token: &str) -> bool {
+    let Some((key, value)) = token.split_once(':') else {
+      return false;
+    };
+    let value = value.strip_prefix('"').map(unquote).unwrap_or(value);
+    if value.is_empty() {
+      return false;
+    }
+    match key.to_ascii_lowercase().as_str() {
+      "tag" => self.tags.push(value.to_string()),
+      "in" => self.folders.push(value.to_string()),
+      "is" => self.flags.push(value.to_lowercase()),
+      "created" | "updated" => {
+        let Some(filter) = parse_date_filter(key, value) else {
+          return false;
+        };
+        self.dates.push(filter);
+      }
+      _ => return false,
+    }
+    true
+  }
+}
+
+fn parse_date_filter(key: &str, value: &str) -> Option<DateFilter> {
+  let field = if key.eq_ignore_ascii_case("created") {
+    DateField::Created
+  } else {
+    DateField::Updated
+  };
+  let (op, date) = [
+    (">=", DateOp::Ge),
+    ("<=", DateOp::Le),
+    (">", DateOp::Gt),
+    ("<", DateOp::Lt),
+  ]
+  .into_iter()
+  .find_map(|(prefix, op)| value.strip_prefix(prefix).map(|date| (op, date)))
+  .unwrap_or((DateOp::Eq, value));
+  let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+  Some(DateFilter { field, op, date })
+}
+
+/// Splits on whitespace outside of double quotes. Quotes are kept and an
+/// unclosed quote runs to the end of the input.
+fn tokenize(input: &str) -> Vec<String> {
+  let mut tokens = Vec::new();
+  let mut token = String::new();
+  let mut quoted = false;
+  for c in input.chars() {
+    if c == '"' {
+      quoted = !quoted;
+    } else if c.is_whitespace() && !quoted {
+      if !token.is_empty() {
+        tokens.push(std::mem::take(&mut token));
+      }
+      continue;
+    }
+    token.push(c);
+  }
+  if !token.is_empty() {
+    tokens.push(token);
+  }
+  tokens
+}
+
+/// Strips the closing quote from a value whose opening quote was removed.
+fn unquote(value: &str) -> &str {
+  value.strip_suffix('"').unwrap_or(value).trim()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_search_query() {
+    let query = parse_search_query(
+      r#"  Launch "release notes" tag:work in:"Team Docs" IS:Todo updated:>=2024-03-01 created:2023-12-31 foo:bar updated:>yesterday "unclosed phrase"#,
+    );
+    assert_eq!(query.terms, vec!["Launch", "foo:bar", "updated:>yesterday"]);
+    assert_eq!(query.phrases, vec!["release notes", "unclosed phrase"]);
+    assert_eq!(query.tags, vec!["work"]);
+    assert_eq!(query.folders, vec!["Team Docs"]);
+    assert_eq!(query.flags, vec!["todo"]);
+    assert_eq!(
+      query.dates,
+      vec![
+        DateFilter {
+          field: DateField::Updated,
+          op: DateOp::Ge,
+          date: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+        },
+        DateFilter {
+          field: DateField::Created,
+          op: DateOp::Eq,
+          date: NaiveDate::from_ymd_opt(2023, 12, 31).unwrap(),
+        },
+      ]
+    );
+
+    assert_eq!(parse_search_query("   "), SearchQuery::default());
+    assert_eq!(parse_search_query("tag:").terms, vec!["tag:"]);
+  }
+}
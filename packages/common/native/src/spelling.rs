@@ -0,0 +1,286 @@
+//! "Did you mean" suggestions drawn from the words of a workspace.
+//!
+//! Lookups follow SymSpell: every word is indexed under the strings left
+//! after deleting up to [`MAX_DISTANCE`] characters from its prefix, so a
+//! query only has to generate its own deletes instead of comparing against
+//! the whole vocabulary.
+
+use std::collections::{HashMap, HashSet};
+
+/// Largest edit distance between a term and its suggestions.
+pub const MAX_DISTANCE: usize = 2;
+/// Characters of a word used to generate deletes. Longer words are still
+/// compared in full.
+const PREFIX_LENGTH: usize = 7;
+const MIN_WORD_LENGTH: usize = 2;
+const MAX_WORD_LENGTH: usize = 32;
+/// Shorter terms are too ambiguous to correct.
+const MIN_TERM_LENGTH: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+  pub word: String,
+  pub distance: usize,
+  /// Occurrences of the word in the vocabulary.
+  pub frequency: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Correction {
+  pub term: String,
+  pub suggestion: String,
+  pub distance: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryCorrection {
+  /// The query with every correction applied.
+  pub query: String,
+  pub corrections: Vec<Correction>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+  words: Vec<(String, u32)>,
+  ids: HashMap<String, usize>,
+  deletes: HashMap<String, Vec<usize>>,
+}
+
+impl Vocabulary {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Number of distinct words.
+  pub fn len(&self) -> usize {
+    self.words.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.words.is_empty()
+  }
+
+  pub fn frequency(&self, word: &str) -> Option<u32> {
+    self
+      .ids
+      .get(&word.to_lowercase())
+      .map(|&id| self.words[id].1)
+  }
+
+  /// Adds the words of `text`, lowercased. Numbers and words shorter than 2
+  /// or longer than 32 characters are skipped.
+  pub fn add_text(&mut self, text: &str) {
+    for (_, word) in words(text) {
+      let length = word.chars().count();
+      if (MIN_WORD_LENGTH..=MAX_WORD_LENGTH).contains(&length)
+        && !word.chars().all(|c| c.is_numeric())
+      {
+        self.add_word(word.to_lowercase());
+      }
+    }
+  }
+
+  fn add_word(&mut self, word: String) {
+    if let Some(&id) = self.ids.get(&word) {
+      self.words[id].1 += 1;
+      return;
+    }
+    let id = self.words.len();
+    for delete in deletes(&word) {
+      self.deletes.entry(delete).or_default().push(id);
+    }
+    self.ids.insert(word.clone(), id);
+    self.words.push((word, 1));
+  }
+
+  /// Returns up to `limit` words within [`MAX_DISTANCE`] edits of `term`,
+  /// closest first and more frequent first among equally close words. An
+  /// exact match comes first with a distance of 0.
+  pub fn suggest(&self, term: &str, limit: usize) -> Vec<Suggestion> {
+    let term = term.to_lowercase();
+    let term_length = term.chars().count();
+    if !(MIN_TERM_LENGTH..=MAX_WORD_LENGTH).contains(&term_length) {
+      return vec![];
+    }
+
+    let candidates = deletes(&term)
+      .iter()
+      .filter_map(|delete| self.deletes.get(delete))
+      .flatten()
+      .copied()
+      .collect::<HashSet<_>>();
+    let mut suggestions = candidates
+      .into_iter()
+      .filter_map(|id| {
+        let (word, frequency) = &self.words[id];
+        if word.chars().count().abs_diff(term_length) > MAX_DISTANCE {
+          return None;
+        }
+        let distance = edit_distance(&term, word);
+        (distance <= MAX_DISTANCE).then(|| Suggestion {
+          word: word.clone(),
+          distance,
+          frequency: *frequency,
+        })
+      })
+      .collect::<Vec<_>>();
+    suggestions.sort_by(|a, b| {
+      a.distance
+        .cmp(&b.distance)
+        .then_with(|| b.frequency.cmp(&a.frequency))
+        .then_with(|| a.word.cmp(&b.word))
+    });
+    suggestions.truncate(limit);
+    suggestions
+  }
+}
+
+/// Builds a vocabulary from the given texts, e.g. the titles and block
+/// contents of every doc of a workspace.
+pub fn build_vocabulary<'a>(texts: impl IntoIterator<Item = &'a str>) -> Vocabulary {
+  let mut vocabulary = Vocabulary::new();
+  for text in texts {
+    vocabulary.add_text(text);
+  }
+  vocabulary
+}
+
+/// Replaces every word of `query` missing from `vocabulary` with its best
+/// suggestion. Returns `None` when nothing could be corrected.
+///
+/// # Examples
+///
+/// ```
+/// use affine_common::spelling::{build_vocabulary, suggest_corrections};
+///
+/// let vocabulary = build_vocabulary(["Quarterly roadmap review"]);
+/// let corrected = suggest_corrections(&vocabulary, "raodmap review").unwrap();
+/// assert_eq!(corrected.query, "roadmap review");
+/// ```
+pub fn suggest_corrections(vocabulary: &Vocabulary, query: &str) -> Option<QueryCorrection> {
+  let mut corrected = String::with_capacity(query.len());
+  let mut corrections = Vec::new();
+  let mut copied = 0;
+  for (start, term) in words(query) {
+    if vocabulary.frequency(term).is_some() {
+      continue;
+    }
+    let Some(suggestion) = vocabulary.suggest(term, 1).into_iter().next() else {
+      continue;
+    };
+    corrected.push_str(&query[copied..start]);
+    corrected.push_str(&suggestion.word);
+    copied = start + term.len();
+    corrections.push(Correction {
+      term: term.to_string(),
+      suggestion: suggestion.word,
+      distance: suggestion.distance,
+    });
+  }
+  if corrections.is_empty() {
+    return None;
+  }
+  corrected.push_str(&query[copied..]);
+  Some(QueryCorrection {
+    query: corrected,
+    corrections,
+  })
+}
+
+/// Alphanumeric runs of `text` with their byte offsets.
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+  text
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+/// `word` and every string left after deleting up to [`MAX_DISTANCE`]
+/// characters from its prefix.
+fn deletes(word: &str) -> HashSet<String> {
+  let prefix = word.chars().take(PREFIX_LENGTH).collect::<String>();
+  let mut deletes = HashSet::from([prefix.clone()]);
+  let mut frontier = vec![prefix];
+  for _ in 0..MAX_DISTANCE {
+    let mut next = Vec::new();
+    for word in frontier {
+      let chars = word.chars().collect::<Vec<_>>();
+      for skip in 0..chars.len() {
+        let delete = chars
+          .iter()
+          .enumerate()
+          .filter(|(i, _)| *i != skip)
+          .map(|(_, c)| c)
+          .collect::<String>();
+        if deletes.insert(delete.clone()) {
+          next.push(delete);
+        }
+      }
+    }
+    frontier = next;
+  }
+  deletes
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions
+/// and transpositions of adjacent characters each cost one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+  let a = a.chars().collect::<Vec<_>>();
+  let b = b.chars().collect::<Vec<_>>();
+  let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+  for (i, row) in rows.iter_mut().enumerate() {
+    row[0] = i;
+  }
+  for (j, cell) in rows[0].iter_mut().enumerate() {
+    *cell = j;
+  }
+  for i in 1..=a.len() {
+    for j in 1..=b.len() {
+      let cost = usize::from(a[i - 1] != b[j - 1]);
+      let mut distance = (rows[i - 1][j] + 1)
+        .min(rows[i][j - 1] + 1)
+        .min(rows[i - 1][j - 1] + cost);
+      if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+        distance = distance.min(rows[i - 2][j - 2] + 1);
+      }
+      rows[i][j] = distance;
+    }
+  }
+  rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_suggest_corrections() {
+    let vocabulary = build_vocabulary([
+      "Meeting notes: meeting agenda",
+      "Meting room 2024",
+      "Kanban board",
+    ]);
+    assert_eq!(vocabulary.frequency("MEETING"), Some(2));
+    assert_eq!(vocabulary.frequency("2024"), None);
+
+    let suggestions = vocabulary.suggest("meetnig", 5);
+    let words = suggestions
+      .iter()
+      .map(|suggestion| (suggestion.word.as_str(), suggestion.distance))
+      .collect::<Vec<_>>();
+    assert_eq!(words, vec![("meeting", 1), ("meting", 2)]);
+    assert!(vocabulary.suggest("xyz", 5).is_empty());
+
+    let corrected = suggest_corrections(&vocabulary, "Kanbna, bored & meeting").unwrap();
+    assert_eq!(corrected.query, "kanban, board & meeting");
+    assert_eq!(corrected.corrections.len(), 2);
+    assert_eq!(suggest_corrections(&vocabulary, "meeting notes"), None);
+  }
+
+  #[test]
+  fn test_edit_distance() {
+    assert_eq!(edit_distance("", "abc"), 3);
+    assert_eq!(edit_distance("abcd", "acbd"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+  }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+const MAGIC: &[u8; 4] = b"AFVI";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 + 4;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum VectorIndexError {
+  #[error("vector has {actual} dimensions, the index expects {expected}")]
+  DimensionMismatch { expected: usize, actual: usize },
+  #[error("vector has no direction, it is zero or not finite")]
+  InvalidVector,
+  #[error("invalid vector index data")]
+  InvalidData,
+  #[error("unsupported vector index version {0}")]
+  UnsupportedVersion(u8),
+}
+
+pub type VectorIndexResult<T> = Result<T, VectorIndexError>;
+
+/// In-memory vector store ranking entries by cosine similarity, keyed by
+/// block or chunk id.
+///
+/// Queries compare against every entry. Vectors are normalized when added so
+/// each comparison is a single dot product, which keeps exact search fast
+/// enough for the few hundred thousand chunks of a self-hosted instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorIndex {
+  dimensions: usize,
+  ids: Vec<String>,
+  /// Normalized vectors, `dimensions` values per entry in `ids` order.
+  vectors: Vec<f32>,
+  positions: HashMap<String, usize>,
+}
+
+impl VectorIndex {
+  pub fn new(dimensions: usize) -> Self {
+    Self {
+      dimensions,
+      ids: vec![],
+      vectors: vec![],
+      positions: HashMap::new(),
+    }
+  }
+
+  pub fn dimensions(&self) -> usize {
+    self.dimensions
+  }
+
+  pub fn len(&self) -> usize {
+    self.ids.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.ids.is_empty()
+  }
+
+  pub fn contains(&self, id: &str) -> bool {
+    self.positions.contains_key(id)
+  }
+
+  /// Adds a vector, replacing the one stored under the same id.
+  pub fn add(&mut self, id: &str, vector: &[f32]) -> VectorIndexResult<()> {
+    let vector = self.normalize(vector)?;
+    match self.positions.get(id) {
+      Some(&position) => {
+        self.vectors[position * self.dimensions..(position + 1) * self.dimensions]
+          .copy_from_slice(&vector);
+      }
+      None => {
+        self.positions.insert(id.to_string(), self.ids.len());
+        self.ids.push(id.to_string());
+        self.vectors.extend_from_slice(&vector);
+      }
+    }
+    Ok(())
+  }
+
+  /// Removes a vector, returning whether it was stored.
+  pub fn remove(&mut self, id: &str) -> bool {
+    let Some(position) = self.positions.remove(id) else {
+      return false;
+    };
+    // move the last entry into the hole
+    let last = self.ids.len() - 1;
+    self.ids.swap_remove(position);
+    if position != last {
+      self.positions.insert(self.ids[position].clone(), position);
+      self.vectors.copy_within(
+        last * self.dimensions..(last + 1) * self.dimensions,
+        position * self.dimensions,
+      );
+    }
+    self.vectors.truncate(last * self.dimensions);
+    true
+  }
+
+  /// Returns the `limit` entries most similar to `vector` with their cosine
+  /// similarity, best first. Equal scores are ordered by id.
+  pub fn query(&self, vector: &[f32], limit: usize) -> VectorIndexResult<Vec<(String, f32)>> {
+    let vector = self.normalize(vector)?;
+    let mut scores = self
+      .vectors
+      .chunks_exact(self.dimensions.max(1))
+      .zip(&self.ids)
+      .map(|(stored, id)| {
+        let score = stored.iter().zip(&vector).map(|(a, b)| a * b).sum::<f32>();
+        (id, score)
+      })
+      .collect::<Vec<_>>();
+    let by_score =
+      |a: &(&String, f32), b: &(&String, f32)| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0));
+    if limit < scores.len() {
+      scores.select_nth_unstable_by(limit, by_score);
+      scores.truncate(limit);
+    }
+    scores.sort_unstable_by(by_score);
+    Ok(
+      scores
+        .into_iter()
+        .map(|(id, score)| (id.clone(), score))
+        .collect(),
+    )
+  }
+
+  pub fn from_bytes(bytes: &[u8]) -> VectorIndexResult<Self> {
+    if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+      return Err(VectorIndexError::InvalidData);
+    }
+    if bytes[MAGIC.len()] != VERSION {
+      return Err(VectorIndexError::UnsupportedVersion(bytes[MAGIC.len()]));
+    }
+    let read_u32 = |at: usize| {
+      bytes
+        .get(at..at + 4)
+        .map(|value| u32::from_le_bytes(value.try_into().unwrap()) as usize)
+        .ok_or(VectorIndexError::InvalidData)
+    };
+
+    let mut index = Self::new(read_u32(MAGIC.len() + 1)?);
+    let count = read_u32(MAGIC.len() + 5)?;
+    let mut at = HEADER_LEN;
+    for _ in 0..count {
+      let id_len = read_u32(at)?;
+      let id = bytes
+        .get(at + 4..at + 4 + id_len)
+        .and_then(|id| std::str::from_utf8(id).ok())
+        .ok_or(VectorIndexError::InvalidData)?;
+      at += 4 + id_len;
+      let vector = bytes
+        .get(at..at + index.dimensions * 4)
+        .ok_or(VectorIndexError::InvalidData)?
+        .chunks_exact(4)
+        .map(|value| f32::from_le_bytes(value.try_into().unwrap()))
+        .collect::<Vec<_>>();
+      at += index.dimensions * 4;
+      index.add(id, &vector)?;
+    }
+    if at != bytes.len() {
+      return Err(VectorIndexError::InvalidData);
+    }
+    Ok(index)
+  }
+
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + self.vectors.len() * 4);
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&(self.dimensions as u32).to_le_bytes());
+    bytes.extend_from_slice(&(self.ids.len() as u32).to_le_bytes());
+    for (id, vector) in self
+      .ids
+      .iter()
+      .zip(self.vectors.chunks_exact(self.dimensions.max(1)))
+    {
+      bytes.extend_from_slice(&(id.len() as u32).to_le_bytes());
+      bytes.extend_from_slice(id.as_bytes());
+      for value in vector {
+        bytes.extend_from_slice(&value.to_le_bytes());
+      }
+    }
+    bytes
+  }
+
+  fn normalize(&self, vector: &[f32]) -> VectorIndexResult<Vec<f32>> {
+    if vector.len() != self.dimensions {
+      return Err(VectorIndexError::DimensionMismatch {
+        expected: self.dimensions,
+        actual: vector.len(),
+      });
+    }
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if !norm.is_normal() {
+      return Err(VectorIndexError::InvalidVector);
+    }
+    Ok(vector.iter().map(|value| value / norm).collect())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_vector_index() {
+    let mut index = VectorIndex::new(3);
+    index.add("x", &[1.0, 0.0, 0.0]).unwrap();
+    index.add("y", &[0.0, 2.0, 0.0]).unwrap();
+    index.add("xy", &[1.0, 1.0, 0.0]).unwrap();
+    assert_eq!(
+      index.add("z", &[1.0, 0.0]),
+      Err(VectorIndexError::DimensionMismatch {
+        expected: 3,
+        actual: 2
+      })
+    );
+    assert_eq!(
+      index.add("z", &[0.0, 0.0, 0.0]),
+      Err(VectorIndexError::InvalidVector)
+    );
+
+    let ids =
+      |matches: Vec<(String, f32)>| matches.into_iter().map(|(id, _)| id).collect::<Vec<_>>();
+    let matches = index.query(&[3.0, 0.5, 0.0], 2).unwrap();
+    assert!((matches[0].1 - 0.986).abs() < 0.001);
+    assert_eq!(ids(matches), vec!["x", "xy"]);
+
+    assert!(index.remove("x"));
+    assert!(!index.remove("x"));
+    index.add("y", &[0.0, 0.0, 1.0]).unwrap();
+    assert_eq!(
+      ids(index.query(&[1.0, 0.0, 0.0], 10).unwrap()),
+      vec!["xy", "y"]
+    );
+
+    let restored = VectorIndex::from_bytes(&index.to_bytes()).unwrap();
+    assert_eq!(restored.len(), 2);
+    assert_eq!(
+      restored.query(&[0.0, 0.0, 1.0], 1).unwrap(),
+      index.query(&[0.0, 0.0, 1.0], 1).unwrap()
+    );
+    assert_eq!(
+      VectorIndex::from_bytes(b"AFVI"),
+      Err(VectorIndexError::InvalidData)
+    );
+  }
+}
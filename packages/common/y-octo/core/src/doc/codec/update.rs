@@ -20,29 +20,8 @@ pub struct Update {
 
 impl<R: CrdtReader> CrdtRead<R> for Update {
   fn read(decoder: &mut R) -> JwstCodecResult<Self> {
-    let num_of_clients = decoder.read_var_u64()? as usize;
-
-    // See: [HASHMAP_SAFE_CAPACITY]
-    let mut map = ClientMap::with_capacity(num_of_clients.min(HASHMAP_SAFE_CAPACITY));
-    for _ in 0..num_of_clients {
-      let num_of_structs = decoder.read_var_u64()? as usize;
-      let client = decoder.read_var_u64()?;
-      let mut clock = decoder.read_var_u64()?;
-
-      // same reason as above
-      let mut structs = VecDeque::with_capacity(num_of_structs.min(HASHMAP_SAFE_CAPACITY));
-
-      for _ in 0..num_of_structs {
-        let struct_info = Node::read(decoder, Id::new(client, clock))?;
-        clock += struct_info.len();
-        structs.push_back(struct_info);
-      }
-
-      structs.shrink_to_fit();
-      map.insert(client, structs);
-    }
-
-    map.shrink_to_fit();
+    let mut map = ClientMap::default();
+    Update::read_structs(decoder, &mut map)?;
 
     let delete_set = DeleteSet::read(decoder)?;
 
@@ -88,11 +67,86 @@ impl<W: CrdtWriter> CrdtWrite<W> for Update {
 }
 
 impl Update {
+  /// Reads the structs of every client into `map`. On error, the structs
+  /// read before the failing one are left in `map`.
+  fn read_structs<R: CrdtReader>(
+    decoder: &mut R,
+    map: &mut ClientMap<VecDeque<Node>>,
+  ) -> JwstCodecResult {
+    let num_of_clients = decoder.read_var_u64()? as usize;
+
+    // See: [HASHMAP_SAFE_CAPACITY]
+    map.reserve(num_of_clients.min(HASHMAP_SAFE_CAPACITY));
+    for _ in 0..num_of_clients {
+      let num_of_structs = decoder.read_var_u64()? as usize;
+      let client = decoder.read_var_u64()?;
+      let mut clock = decoder.read_var_u64()?;
+
+      // same reason as above
+      let mut structs = VecDeque::with_capacity(num_of_structs.min(HASHMAP_SAFE_CAPACITY));
+
+      for _ in 0..num_of_structs {
+        match Node::read(decoder, Id::new(client, clock)) {
+          Ok(struct_info) => {
+            clock += struct_info.len();
+            structs.push_back(struct_info);
+          }
+          Err(e) => {
+            if !structs.is_empty() {
+              map.insert(client, structs);
+            }
+            return Err(e);
+          }
+        }
+      }
+
+      structs.shrink_to_fit();
+      map.insert(client, structs);
+    }
+
+    map.shrink_to_fit();
+    Ok(())
+  }
+
   // decode from ydoc v1
   pub fn decode_v1<T: AsRef<[u8]>>(buffer: T) -> JwstCodecResult<Update> {
     Update::read(&mut RawDecoder::new(buffer.as_ref()))
   }
 
+  /// Decodes a damaged v1 update as far as it goes, for recovering content.
+  ///
+  /// Structs are kept up to the first one that fails to decode, which is
+  /// returned next to them. The delete set is only kept when every struct
+  /// decodes, so content deleted before the damage may come back.
+  pub fn decode_v1_partial<T: AsRef<[u8]>>(buffer: T) -> (Update, Option<JwstCodecError>) {
+    let mut decoder = RawDecoder::new(buffer.as_ref());
+    let mut structs = ClientMap::default();
+    if let Err(e) = Update::read_structs(&mut decoder, &mut structs) {
+      let update = Update {
+        structs,
+        ..Update::default()
+      };
+      return (update, Some(e));
+    }
+
+    let (delete_set, error) = match DeleteSet::read(&mut decoder) {
+      Ok(delete_set) if decoder.is_empty() => (delete_set, None),
+      Ok(delete_set) => (
+        delete_set,
+        Some(JwstCodecError::UpdateNotFullyConsumed(
+          decoder.len() as usize
+        )),
+      ),
+      Err(e) => (DeleteSet::default(), Some(e)),
+    };
+    let update = Update {
+      structs,
+      delete_set,
+      ..Update::default()
+    };
+    (update, error)
+  }
+
   pub fn encode_v1(&self) -> JwstCodecResult<Vec<u8>> {
     let mut encoder = RawEncoder::default();
     self.write(&mut encoder)?;
@@ -200,6 +254,181 @@ impl Update {
   pub fn is_pending_empty(&self) -> bool {
     self.pending_structs.is_empty() && self.pending_delete_set.is_empty()
   }
+
+  /// Total length of the items each client contributes to this update.
+  /// Skipped ranges and garbage collected items are not counted.
+  pub fn item_lengths(&self) -> impl Iterator<Item = (Client, u64)> + '_ {
+    self.structs.iter().map(|(client, structs)| {
+      let len = structs
+        .iter()
+        .filter(|node| node.is_item())
+        .map(|node| node.len())
+        .sum();
+      (*client, len)
+    })
+  }
+
+  /// Total length of the ranges this update deletes, per client.
+  pub fn deleted_lengths(&self) -> impl Iterator<Item = (Client, u64)> + '_ {
+    self.delete_set.iter().map(|(client, ranges)| {
+      let len = ranges
+        .into_iter()
+        .map(|range| range.end - range.start)
+        .sum();
+      (*client, len)
+    })
+  }
+
+  /// Ranges this update deletes. Updates encoded from a doc carry every
+  /// deletion the doc knows of, not only the ones made since the state they
+  /// were encoded against.
+  pub fn delete_set(&self) -> &DeleteSet {
+    &self.delete_set
+  }
+
+  /// Strings inserted by this update, with the id of their first unit and
+  /// the id of the unit they were inserted after. Clocks advance by one per
+  /// UTF-16 unit of a string.
+  pub fn strings(&self) -> impl Iterator<Item = (Id, Option<Id>, &str)> + '_ {
+    self
+      .structs
+      .values()
+      .flatten()
+      .filter_map(|node| match node {
+        Node::Item(item) => {
+          let item = item.get()?;
+          match &item.content {
+            Content::String(string) => Some((item.id, item.origin_left_id, string.as_str())),
+            _ => None,
+          }
+        }
+        _ => None,
+      })
+  }
+
+  /// Overwrites the UTF-16 units of inserted strings that fall in one of the
+  /// clock `ranges` of their client with `mask`, and returns how many units
+  /// were overwritten. Item lengths are kept, so the update stays
+  /// consistent with the ids other updates refer to, as long as `mask` is a
+  /// single UTF-16 unit.
+  pub fn mask_strings(&mut self, ranges: &ClientMap<Vec<Range<Clock>>>, mask: char) -> u64 {
+    debug_assert_eq!(mask.len_utf16(), 1);
+    let mut masked = 0;
+    for (client, structs) in self.structs.iter_mut() {
+      let Some(ranges) = ranges.get(client) else {
+        continue;
+      };
+      for node in structs.iter_mut() {
+        let Node::Item(item) = node else {
+          continue;
+        };
+        let Some(item) = item.get_mut() else {
+          continue;
+        };
+        let Content::String(string) = &mut item.content else {
+          continue;
+        };
+
+        let mut clock = item.id.clock;
+        let mut replaced = String::with_capacity(string.len());
+        for char in string.chars() {
+          let len = char.len_utf16() as u64;
+          if ranges
+            .iter()
+            .any(|range| range.start < clock + len && clock < range.end)
+          {
+            replaced.extend(std::iter::repeat_n(mask, len as usize));
+            masked += len;
+          } else {
+            replaced.push(char);
+          }
+          clock += len;
+        }
+        *string = replaced;
+      }
+    }
+    masked
+  }
+
+  /// Strings held in values inserted by this update: embeds, format
+  /// attributes such as links, and entries of maps and arrays, with the id of
+  /// the value they are part of.
+  pub fn value_strings(&self) -> Vec<(Id, &str)> {
+    let mut strings = Vec::new();
+    for node in self.structs.values().flatten() {
+      let Node::Item(item) = node else {
+        continue;
+      };
+      let Some(item) = item.get() else {
+        continue;
+      };
+      match &item.content {
+        Content::Embed(value) | Content::Format { value, .. } => {
+          any_strings(value, &mut |string| strings.push((item.id, string)))
+        }
+        Content::Any(values) => {
+          for (offset, value) in values.iter().enumerate() {
+            let id = Id::new(item.id.client, item.id.clock + offset as Clock);
+            any_strings(value, &mut |string| strings.push((id, string)));
+          }
+        }
+        _ => {}
+      }
+    }
+    strings
+  }
+
+  /// Replaces the strings [`Update::value_strings`] returns with what `mask`
+  /// returns for them, keeping the ones it returns `None` for, and returns
+  /// how many were replaced. A value takes one clock whatever it holds, so
+  /// ids are unchanged.
+  pub fn mask_values(&mut self, mut mask: impl FnMut(&str) -> Option<String>) -> u64 {
+    let mut masked = 0;
+    for node in self.structs.values_mut().flatten() {
+      let Node::Item(item) = node else {
+        continue;
+      };
+      let Some(item) = item.get_mut() else {
+        continue;
+      };
+      match &mut item.content {
+        Content::Embed(value) | Content::Format { value, .. } => {
+          masked += mask_any(value, &mut mask)
+        }
+        Content::Any(values) => {
+          for value in values {
+            masked += mask_any(value, &mut mask);
+          }
+        }
+        _ => {}
+      }
+    }
+    masked
+  }
+}
+
+fn any_strings<'a>(value: &'a Any, f: &mut impl FnMut(&'a str)) {
+  match value {
+    Any::String(string) => f(string),
+    Any::Array(values) => values.iter().for_each(|value| any_strings(value, f)),
+    Any::Object(values) => values.values().for_each(|value| any_strings(value, f)),
+    _ => {}
+  }
+}
+
+fn mask_any(value: &mut Any, mask: &mut impl FnMut(&str) -> Option<String>) -> u64 {
+  match value {
+    Any::String(string) => match mask(string) {
+      Some(masked) => {
+        *string = masked;
+        1
+      }
+      None => 0,
+    },
+    Any::Array(values) => values.iter_mut().map(|value| mask_any(value, mask)).sum(),
+    Any::Object(values) => values.values_mut().map(|value| mask_any(value, mask)).sum(),
+    _ => 0,
+  }
 }
 
 pub(crate) struct UpdateIterator<'a> {
@@ -507,6 +736,21 @@ mod tests {
     }
   }
 
+  #[test]
+  #[cfg_attr(any(miri, loom), ignore)]
+  fn test_decode_partial() {
+    let doc = include_bytes!("../../fixtures/basic.bin");
+    let (update, error) = Update::decode_v1_partial(doc);
+    assert!(error.is_none());
+    assert_eq!(update.structs.values().map(|s| s.len()).sum::<usize>(), 188);
+
+    let (update, error) = Update::decode_v1_partial(&doc[..doc.len() / 2]);
+    assert!(error.is_some());
+    let structs = update.structs.values().map(|s| s.len()).sum::<usize>();
+    assert!(structs > 0 && structs < 188);
+    assert!(update.delete_set.is_empty());
+  }
+
   fn decode_hex(s: &str) -> Result<Vec<u8>, ParseIntError> {
     (0..s.len())
       .step_by(2)
@@ -718,4 +962,74 @@ mod tests {
       assert_eq!(merged2.structs.get(&0).unwrap().len(), 9);
     });
   }
+
+  #[test]
+  fn test_mask_strings() {
+    let doc = DocOptions::new().with_client_id(1).build();
+    let mut text = doc.get_or_create_text("content").unwrap();
+    text.insert(0, "key 😀secret here").unwrap();
+    let mut update = Update::decode_v1(doc.encode_update_v1().unwrap()).unwrap();
+    assert_eq!(
+      update.strings().collect::<Vec<_>>(),
+      vec![((1, 0).into(), None, "key 😀secret here")]
+    );
+
+    // the emoji takes two clocks, and is masked as a whole when either is hit
+    let ranges = ClientMap::from_iter([(1, vec![5..12]), (2, vec![0..16])]);
+    assert_eq!(update.mask_strings(&ranges, '*'), 8);
+
+    let doc = Doc::try_from_binary_v1(update.encode_v1().unwrap()).unwrap();
+    let mut text = doc.get_or_create_text("content").unwrap();
+    assert_eq!(text.to_string(), "key ******** here");
+    text.insert(17, "!").unwrap();
+    assert_eq!(text.to_string(), "key ******** here!");
+  }
+
+  #[test]
+  fn test_mask_values() {
+    let doc = DocOptions::new().with_client_id(1).build();
+    let mut text = doc.get_or_create_text("content").unwrap();
+    let link = Any::String("https://example.com/?token=secret".into());
+    text
+      .push_with_attributes("docs", HashMap::from_iter([("link".to_string(), link)]))
+      .unwrap();
+    let mut map = doc.get_or_create_map("meta").unwrap();
+    map.insert("key".into(), "secret").unwrap();
+    map.insert("title".into(), "Notes").unwrap();
+    let mut update = Update::decode_v1(doc.encode_update_v1().unwrap()).unwrap();
+
+    let mut strings = update
+      .value_strings()
+      .into_iter()
+      .map(|(_, string)| string)
+      .collect::<Vec<_>>();
+    strings.sort();
+    assert_eq!(
+      strings,
+      vec!["Notes", "https://example.com/?token=secret", "secret"]
+    );
+
+    let masked = update.mask_values(|string| {
+      string
+        .contains("secret")
+        .then(|| string.replace("secret", "******"))
+    });
+    assert_eq!(masked, 2);
+
+    let doc = Doc::try_from_binary_v1(update.encode_v1().unwrap()).unwrap();
+    let text = doc.get_or_create_text("content").unwrap();
+    assert_eq!(
+      text.to_delta()[0].attributes.get("link"),
+      Some(&Any::String("https://example.com/?token=******".into()))
+    );
+    let map = doc.get_or_create_map("meta").unwrap();
+    assert_eq!(
+      map.get("key").and_then(|value| value.to_any()),
+      Some(Any::String("******".into()))
+    );
+    assert_eq!(
+      map.get("title").and_then(|value| value.to_any()),
+      Some(Any::String("Notes".into()))
+    );
+  }
 }
@@ -374,6 +374,12 @@ impl Doc {
     self.store.read().unwrap().get_state_vector()
   }
 
+  /// Ranges of the items deleted so far, per client. Together with
+  /// [`Doc::get_state_vector`] this identifies a version of the doc.
+  pub fn get_delete_set(&self) -> DeleteSet {
+    self.store.read().unwrap().delete_set.clone()
+  }
+
   #[cfg(feature = "events")]
   pub fn subscribe(&self, cb: impl Fn(&[u8], &[History]) + Sync + Send + 'static) {
     self.publisher.subscribe(cb);
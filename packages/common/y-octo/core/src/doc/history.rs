@@ -139,6 +139,30 @@ impl StoreHistory {
     self.parse_items(store_items)
   }
 
+  /// Parses the deleted items the store still holds, i.e. the ones that
+  /// have not been garbage collected. Formatting marks and subdocs are
+  /// skipped.
+  pub fn parse_deleted(&self) -> Vec<History> {
+    let deleted_items = {
+      let store = self.store.read().unwrap();
+      store
+        .items
+        .values()
+        .flat_map(|items| items.iter())
+        .filter_map(|n| n.as_item().get().cloned())
+        .filter(|item| {
+          item.deleted()
+            && !matches!(
+              item.content,
+              Content::Deleted(_) | Content::Format { .. } | Content::Doc { .. }
+            )
+        })
+        .collect::<Vec<_>>()
+    };
+
+    self.parse_deleted_items(deleted_items)
+  }
+
   fn parse_items(&self, store_items: Vec<&Item>) -> Vec<History> {
     let parents = self.parents.read().unwrap();
     let mut histories = vec![];
@@ -324,4 +348,25 @@ mod test {
       );
     });
   }
+
+  #[test]
+  fn parse_deleted_test() {
+    loom_model!({
+      let doc = Doc::default();
+      let mut map = doc.get_or_create_map("map").unwrap();
+      map.insert("kept".to_string(), "value").unwrap();
+      map.insert("key".to_string(), "value").unwrap();
+      map.remove("key");
+
+      assert_eq!(
+        doc.history().parse_deleted(),
+        vec![History {
+          id: format!("({}, 1)", doc.client()),
+          parent: vec!["map".to_string(), "key".to_string()],
+          content: "\"value\"".to_string(),
+          action: HistoryAction::Delete,
+        }]
+      );
+    });
+  }
 }
@@ -1,10 +1,20 @@
 use std::fmt::Display;
 
-use super::list::ListType;
-use crate::{impl_type, Content, JwstCodecResult};
+use super::list::{ItemPosition, ListType};
+use crate::{
+  doc::{AsInner, HashMap, Somr},
+  impl_type, Any, Content, JwstCodecError, JwstCodecResult,
+};
 
 impl_type!(Text);
 
+/// A run of text sharing the same formatting attributes, as in a Yjs delta.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextDelta {
+  pub insert: String,
+  pub attributes: HashMap<String, Any>,
+}
+
 impl ListType for Text {}
 
 impl Text {
@@ -27,6 +37,107 @@ impl Text {
   pub fn remove(&mut self, char_index: u64, len: u64) -> JwstCodecResult {
     self.remove_at(char_index, len)
   }
+
+  /// Appends a copy of the content of `other`, including the formatting
+  /// marks and embeds that [`Text::insert`] cannot express.
+  pub fn extend_from(&mut self, other: &Text) -> JwstCodecResult {
+    let contents = other
+      .iter_item()
+      .filter_map(|item| {
+        let item = item.get()?;
+        matches!(
+          item.content,
+          Content::String(_) | Content::Embed(_) | Content::Format { .. }
+        )
+        .then(|| item.content.clone())
+      })
+      .collect::<Vec<_>>();
+    self.append_contents(contents)
+  }
+
+  /// Appends `str` formatted with `attributes`, closing the marks after it
+  /// the way `Y.Text.insert(length, str, attributes)` does at the end of a
+  /// text.
+  pub fn push_with_attributes<T: ToString>(
+    &mut self,
+    str: T,
+    attributes: HashMap<String, Any>,
+  ) -> JwstCodecResult {
+    let mut contents = attributes
+      .iter()
+      .map(|(key, value)| Content::Format {
+        key: key.clone(),
+        value: value.clone(),
+      })
+      .collect::<Vec<_>>();
+    contents.push(Content::String(str.to_string()));
+    contents.extend(attributes.into_keys().map(|key| Content::Format {
+      key,
+      value: Any::Null,
+    }));
+    self.append_contents(contents)
+  }
+
+  fn append_contents(&mut self, contents: Vec<Content>) -> JwstCodecResult {
+    let Some((mut store, mut ty)) = self.as_inner().write() else {
+      return Err(JwstCodecError::DocReleased);
+    };
+    let mut last = Somr::none();
+    let mut next = ty.start.clone();
+    while let Some(item) = next.get() {
+      last = next.clone();
+      next = item.right.clone();
+    }
+
+    for content in contents {
+      let pos = ItemPosition {
+        parent: self.as_inner().clone(),
+        left: last.clone(),
+        right: Somr::none(),
+        index: ty.len,
+        offset: 0,
+      };
+      Self::insert_after(&mut ty, &mut store, pos, content)?;
+      last = match last.get() {
+        Some(item) => item.right.clone(),
+        None => ty.start.clone(),
+      };
+    }
+
+    Ok(())
+  }
+
+  /// The content as runs of text with the formatting attributes applied to
+  /// them, the way `Y.Text.toDelta()` reports it. Embeds are left out.
+  pub fn to_delta(&self) -> Vec<TextDelta> {
+    let mut delta: Vec<TextDelta> = Vec::new();
+    let mut attributes = HashMap::default();
+    for item in self.iter_item() {
+      let Some(item) = item.get() else {
+        continue;
+      };
+      match &item.content {
+        // a null value closes the mark opened with the same key
+        Content::Format { key, value } => match value {
+          Any::Null | Any::Undefined => {
+            attributes.remove(key);
+          }
+          value => {
+            attributes.insert(key.clone(), value.clone());
+          }
+        },
+        Content::String(text) => match delta.last_mut() {
+          Some(last) if last.attributes == attributes => last.insert.push_str(text),
+          _ => delta.push(TextDelta {
+            insert: text.clone(),
+            attributes: attributes.clone(),
+          }),
+        },
+        _ => {}
+      }
+    }
+    delta
+  }
 }
 
 impl Display for Text {
@@ -290,4 +401,100 @@ mod tests {
       assert_eq!(text.to_string(), "hello great world!");
     });
   }
+
+  #[test]
+  #[cfg_attr(miri, ignore)]
+  fn test_extend_from() {
+    let binary = {
+      let doc = yrs::Doc::new();
+      let text = doc.get_or_insert_text("content");
+      let mut trx = doc.transact_mut();
+      text.insert(&mut trx, 0, "hello brave world");
+      text.format(
+        &mut trx,
+        0,
+        5,
+        yrs::types::Attrs::from([("bold".into(), true.into())]),
+      );
+      text.remove_range(&mut trx, 5, 6);
+
+      trx.encode_update_v1()
+    };
+    let source = Doc::try_from_binary_v1(&binary)
+      .unwrap()
+      .get_or_create_text("content")
+      .unwrap();
+
+    let doc = Doc::new();
+    let mut text = doc.get_or_create_text("content").unwrap();
+    text.insert(0, "> ").unwrap();
+    text.extend_from(&source).unwrap();
+    assert_eq!(text.to_string(), "> hello world");
+
+    let formats = |text: &super::Text| {
+      text
+        .iter_item()
+        .filter(|item| matches!(item.get().unwrap().content, Content::Format { .. }))
+        .count()
+    };
+    assert_eq!(formats(&text), 2);
+    assert_eq!(formats(&text), formats(&source));
+
+    // the copy decodes with the same marks
+    let copy = Doc::try_from_binary_v1(doc.encode_update_v1().unwrap()).unwrap();
+    let copy = copy.get_or_create_text("content").unwrap();
+    assert_eq!(copy.to_string(), "> hello world");
+    assert_eq!(formats(&copy), 2);
+  }
+
+  #[test]
+  #[cfg_attr(miri, ignore)]
+  fn test_to_delta() {
+    let binary = {
+      let doc = yrs::Doc::new();
+      let text = doc.get_or_insert_text("content");
+      let mut trx = doc.transact_mut();
+      text.insert(&mut trx, 0, "see the docs now");
+      text.format(
+        &mut trx,
+        4,
+        8,
+        yrs::types::Attrs::from([("link".into(), "https://affine.pro".into())]),
+      );
+      text.format(
+        &mut trx,
+        8,
+        4,
+        yrs::types::Attrs::from([("bold".into(), true.into())]),
+      );
+
+      trx.encode_update_v1()
+    };
+    let text = Doc::try_from_binary_v1(&binary)
+      .unwrap()
+      .get_or_create_text("content")
+      .unwrap();
+
+    let delta = text
+      .to_delta()
+      .into_iter()
+      .map(|op| {
+        let mut keys = op.attributes.into_keys().collect::<Vec<_>>();
+        keys.sort();
+        (op.insert, keys)
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      delta,
+      vec![
+        ("see ".to_string(), vec![]),
+        ("the ".to_string(), vec!["link".to_string()]),
+        (
+          "docs".to_string(),
+          vec!["bold".to_string(), "link".to_string()]
+        ),
+        (" now".to_string(), vec![]),
+      ]
+    );
+  }
 }